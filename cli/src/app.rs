@@ -66,6 +66,10 @@ pub enum Command {
     #[clap(subcommand)]
     State(state::ServiceState),
 
+    /// Manage subscriptions, ingesting events from sources like Kafka into services
+    #[clap(subcommand)]
+    Subscriptions(subscriptions::Subscriptions),
+
     /// Manage CLI config
     #[clap(subcommand, alias = "conf")]
     Config(config::Config),
@@ -38,6 +38,7 @@ pub const CLI_CONFIG_HOME_ENV: &str = "RESTATE_CLI_CONFIG_HOME";
 pub const CLI_CONFIG_FILE_ENV: &str = "RESTATE_CLI_CONFIG";
 
 pub const RESTATE_AUTH_TOKEN_ENV: &str = "RESTATE_AUTH_TOKEN";
+pub const RESTATE_ADMIN_AUTH_TOKEN_ENV: &str = "RESTATE_ADMIN_AUTH_TOKEN";
 // TODO: Deprecated, will be removed once this is provided by the admin server
 pub const INGRESS_URL_ENV: &str = "RESTATE_INGRESS_URL";
 pub const ADMIN_URL_ENV: &str = "RESTATE_ADMIN_URL";
@@ -50,6 +51,8 @@ pub struct CliConfig {
     pub ingress_base_url: Option<Url>,
     pub admin_base_url: Option<Url>,
     pub bearer_token: Option<String>,
+    /// Token for the admin API, if it requires different credentials than the ingress
+    pub admin_bearer_token: Option<String>,
 
     #[cfg(feature = "cloud")]
     pub cloud: crate::commands::cloud::CloudConfig,
@@ -65,6 +68,7 @@ impl CliConfig {
             ingress_base_url: Some(Url::parse("http://localhost:8080/").unwrap()),
             admin_base_url: Some(Url::parse("http://localhost:9070/").unwrap()),
             bearer_token: None,
+            admin_bearer_token: None,
 
             #[cfg(feature = "cloud")]
             cloud: crate::commands::cloud::CloudConfig::default(),
@@ -224,6 +228,12 @@ impl CliEnv {
             figment
         };
 
+        let figment = if let Some(admin_bearer_token) = os_env.get(RESTATE_ADMIN_AUTH_TOKEN_ENV) {
+            figment.join(("admin_bearer_token", admin_bearer_token))
+        } else {
+            figment
+        };
+
         Ok(figment)
     }
 
@@ -284,6 +294,15 @@ impl CliEnv {
         }
     }
 
+    /// The token for the admin API, falling back to the [`Self::bearer_token`] if no admin
+    /// specific token is configured.
+    pub fn admin_bearer_token(&self) -> Result<Option<&str>> {
+        match self.config.admin_bearer_token.as_deref() {
+            Some(admin_bearer_token) => Ok(Some(admin_bearer_token)),
+            None => self.bearer_token(),
+        }
+    }
+
     pub fn write_environment(&self, environment: &str) -> std::io::Result<()> {
         if let Some(parent) = self.environment_file.parent() {
             std::fs::create_dir_all(parent)?
@@ -446,4 +465,20 @@ mod tests {
         let cli_env = CliEnv::load_from_env(&os_env, &GlobalOpts::default()).unwrap();
         assert_eq!(cli_env.config.bearer_token, Some("token".to_string()));
     }
+
+    #[test]
+    fn test_admin_bearer_token_applied() {
+        let mut os_env = OsEnv::default();
+        // avoid using any files from the test runner
+        os_env.insert(CLI_CONFIG_HOME_ENV, "/dev/null".into());
+        os_env.insert(RESTATE_AUTH_TOKEN_ENV, "token".to_string());
+        let cli_env = CliEnv::load_from_env(&os_env, &GlobalOpts::default()).unwrap();
+        // Without an admin token, the bearer token is used for the admin API too
+        assert_eq!(cli_env.admin_bearer_token().unwrap(), Some("token"));
+
+        os_env.insert(RESTATE_ADMIN_AUTH_TOKEN_ENV, "admin-token".to_string());
+        let cli_env = CliEnv::load_from_env(&os_env, &GlobalOpts::default()).unwrap();
+        assert_eq!(cli_env.admin_bearer_token().unwrap(), Some("admin-token"));
+        assert_eq!(cli_env.bearer_token().unwrap(), Some("token"));
+    }
 }
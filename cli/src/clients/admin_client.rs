@@ -128,7 +128,7 @@ impl AdminClient {
             .build()?;
 
         let base_url = env.admin_base_url()?.clone();
-        let bearer_token = env.bearer_token()?.map(str::to_string);
+        let bearer_token = env.admin_bearer_token()?.map(str::to_string);
 
         let client = Self {
             inner: raw_client,
@@ -14,6 +14,7 @@ use super::AdminClient;
 
 use restate_admin_rest_model::deployments::*;
 use restate_admin_rest_model::services::*;
+use restate_admin_rest_model::subscriptions::*;
 use restate_admin_rest_model::version::VersionInformation;
 use restate_types::schema::service::ServiceMetadata;
 
@@ -44,6 +45,17 @@ pub trait AdminClientInterface {
         req: ModifyServiceStateRequest,
     ) -> reqwest::Result<Envelope<()>>;
 
+    async fn get_subscriptions(&self) -> reqwest::Result<Envelope<ListSubscriptionsResponse>>;
+
+    async fn get_subscription(&self, id: &str) -> reqwest::Result<Envelope<SubscriptionResponse>>;
+
+    async fn create_subscription(
+        &self,
+        body: CreateSubscriptionRequest,
+    ) -> reqwest::Result<Envelope<SubscriptionResponse>>;
+
+    async fn delete_subscription(&self, id: &str) -> reqwest::Result<Envelope<()>>;
+
     async fn version(&self) -> reqwest::Result<Envelope<VersionInformation>>;
 }
 
@@ -140,6 +152,35 @@ impl AdminClientInterface for AdminClient {
         self.run_with_body(reqwest::Method::POST, url, req).await
     }
 
+    async fn get_subscriptions(&self) -> reqwest::Result<Envelope<ListSubscriptionsResponse>> {
+        let url = self.base_url.join("/subscriptions").expect("Bad url!");
+        self.run(reqwest::Method::GET, url).await
+    }
+
+    async fn get_subscription(&self, id: &str) -> reqwest::Result<Envelope<SubscriptionResponse>> {
+        let url = self
+            .base_url
+            .join(&format!("/subscriptions/{}", id))
+            .expect("Bad url!");
+        self.run(reqwest::Method::GET, url).await
+    }
+
+    async fn create_subscription(
+        &self,
+        body: CreateSubscriptionRequest,
+    ) -> reqwest::Result<Envelope<SubscriptionResponse>> {
+        let url = self.base_url.join("/subscriptions").expect("Bad url!");
+        self.run_with_body(reqwest::Method::POST, url, body).await
+    }
+
+    async fn delete_subscription(&self, id: &str) -> reqwest::Result<Envelope<()>> {
+        let url = self
+            .base_url
+            .join(&format!("/subscriptions/{}", id))
+            .expect("Bad url!");
+        self.run(reqwest::Method::DELETE, url).await
+    }
+
     async fn version(&self) -> reqwest::Result<Envelope<VersionInformation>> {
         let url = self.base_url.join("/version").expect("Bad url!");
 
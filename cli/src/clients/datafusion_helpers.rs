@@ -22,6 +22,7 @@ use arrow_convert::{ArrowDeserialize, ArrowField};
 use bytes::Bytes;
 use chrono::{DateTime, Duration, Local, TimeZone};
 use clap::ValueEnum;
+use itertools::Itertools;
 
 use restate_service_protocol::awakeable_id::AwakeableIdentifier;
 use restate_types::identifiers::DeploymentId;
@@ -994,10 +995,54 @@ pub(crate) async fn get_state_keys(
     key: Option<&str>,
 ) -> Result<HashMap<ServiceId, HashMap<String, Bytes>>> {
     let filter = if let Some(k) = key {
-        format!("service_name = '{}' AND service_key = '{}'", service, k)
+        format!(
+            "service_name = '{}' AND service_key = '{}'",
+            escape_sql_literal(service),
+            escape_sql_literal(k)
+        )
     } else {
-        format!("service_name = '{}'", service)
+        format!("service_name = '{}'", escape_sql_literal(service))
     };
+    query_state(client, filter).await
+}
+
+/// Gets the state of the objects of the service whose key starts with the given prefix.
+pub(crate) async fn get_state_by_key_prefix(
+    client: &DataFusionHttpClient,
+    service: &str,
+    key_prefix: &str,
+) -> Result<HashMap<ServiceId, HashMap<String, Bytes>>> {
+    let filter = format!(
+        "service_name = '{}' AND starts_with(service_key, '{}')",
+        escape_sql_literal(service),
+        escape_sql_literal(key_prefix)
+    );
+    query_state(client, filter).await
+}
+
+/// Gets the state of the given objects of the service. Objects without state are omitted.
+pub(crate) async fn get_state_of_keys(
+    client: &DataFusionHttpClient,
+    service: &str,
+    keys: &[&str],
+) -> Result<HashMap<ServiceId, HashMap<String, Bytes>>> {
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let filter = format!(
+        "service_name = '{}' AND service_key IN ({})",
+        escape_sql_literal(service),
+        keys.iter()
+            .map(|key| format!("'{}'", escape_sql_literal(key)))
+            .join(", ")
+    );
+    query_state(client, filter).await
+}
+
+async fn query_state(
+    client: &DataFusionHttpClient,
+    filter: String,
+) -> Result<HashMap<ServiceId, HashMap<String, Bytes>>> {
     let sql = format!(
         "SELECT service_name, service_key, key, value FROM state WHERE {}",
         filter
@@ -1019,3 +1064,52 @@ pub(crate) async fn get_state_keys(
     }
     Ok(user_state)
 }
+
+fn escape_sql_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+pub struct SubscriptionPartitionLag {
+    pub topic: String,
+    pub partition: i64,
+    pub offset: i64,
+    pub high_watermark: i64,
+    pub lag: i64,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+/// Returns the lag of the consumers of the given subscription, per topic partition.
+pub async fn get_subscription_lag(
+    client: &DataFusionHttpClient,
+    subscription_id: &str,
+) -> Result<Vec<SubscriptionPartitionLag>> {
+    let mut output = vec![];
+
+    let query = format!(
+        "SELECT
+            topic,
+            CAST(\"partition\" AS BIGINT),
+            CAST(\"offset\" AS BIGINT),
+            CAST(high_watermark AS BIGINT),
+            CAST(lag AS BIGINT),
+            updated_at
+            FROM sys_subscription_lag
+            WHERE subscription_id = '{}'
+            ORDER BY topic, \"partition\"",
+        escape_sql_literal(subscription_id)
+    );
+
+    for batch in client.run_query(query).await?.batches {
+        for i in 0..batch.num_rows() {
+            output.push(SubscriptionPartitionLag {
+                topic: value_as_string(&batch, 0, i),
+                partition: value_as_i64(&batch, 1, i),
+                offset: value_as_i64(&batch, 2, i),
+                high_watermark: value_as_i64(&batch, 3, i),
+                lag: value_as_i64(&batch, 4, i),
+                updated_at: value_as_dt_opt(&batch, 5, i),
+            });
+        }
+    }
+    Ok(output)
+}
@@ -22,8 +22,9 @@ use arrow::record_batch::RecordBatch;
 use arrow_convert::deserialize::{arrow_array_deserialize_iterator, ArrowDeserialize};
 use arrow_convert::field::ArrowField;
 use bytes::Buf;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::{debug, info};
 
 #[derive(Error, Debug)]
@@ -37,6 +38,7 @@ pub enum Error {
     #[error("Mapping from query '{0}': {1}")]
     Mapping(String, #[source] ArrowError),
     UrlParse(#[from] url::ParseError),
+    Io(#[from] std::io::Error),
 }
 
 /// A handy client for the datafusion HTTP service.
@@ -60,9 +62,39 @@ impl DataFusionHttpClient {
 
     /// Prepare a request builder for a DataFusion request.
     fn prepare(&self) -> Result<reqwest::RequestBuilder, Error> {
+        self.prepare_path("/query")
+    }
+
+    fn prepare_path(&self, path: &str) -> Result<reqwest::RequestBuilder, Error> {
         Ok(self
             .inner
-            .prepare(reqwest::Method::POST, self.inner.base_url.join("/query")?))
+            .prepare(reqwest::Method::POST, self.inner.base_url.join(path)?))
+    }
+
+    /// Runs the query and streams its results, encoded by the server in the given format,
+    /// into the writer. Returns the number of bytes written.
+    pub async fn export_query<W: AsyncWrite + Unpin>(
+        &self,
+        query: String,
+        format: ExportFormat,
+        writer: &mut W,
+    ) -> Result<u64, Error> {
+        debug!("Exporting sql query '{}' as {:?}", query, format);
+        let resp = self
+            .prepare_path("/query/export")?
+            .json(&ExportQueryRequest { query, format })
+            .send()
+            .await?;
+        let mut resp = ensure_success(resp).await?;
+
+        let mut written = 0;
+        while let Some(chunk) = resp.chunk().await? {
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+
+        Ok(written)
     }
 
     pub async fn run_query(&self, query: String) -> Result<SqlResponse, Error> {
@@ -73,19 +105,7 @@ impl DataFusionHttpClient {
             .send()
             .await?;
 
-        let http_status_code = resp.status();
-        let url = resp.url().clone();
-        if !resp.status().is_success() {
-            let body = resp.text().await?;
-            info!("Response from {} ({})", url, http_status_code);
-            info!("  {}", body);
-            // Wrap the error into ApiError
-            return Err(Error::Api(Box::new(ApiError {
-                http_status_code,
-                url,
-                body: serde_json::from_str(&body)?,
-            })));
-        }
+        let resp = ensure_success(resp).await?;
 
         // We read the entire payload first in-memory to simplify the logic, however,
         // if this ever becomes a problem, we can use bytes_stream() (requires
@@ -135,6 +155,25 @@ impl DataFusionHttpClient {
     }
 }
 
+/// Turns unsuccessful responses into an [`ApiError`].
+async fn ensure_success(resp: reqwest::Response) -> Result<reqwest::Response, Error> {
+    let http_status_code = resp.status();
+    let url = resp.url().clone();
+    if !resp.status().is_success() {
+        let body = resp.text().await?;
+        info!("Response from {} ({})", url, http_status_code);
+        info!("  {}", body);
+        // Wrap the error into ApiError
+        return Err(Error::Api(Box::new(ApiError {
+            http_status_code,
+            url,
+            body: serde_json::from_str(&body)?,
+        })));
+    }
+
+    Ok(resp)
+}
+
 fn get_column_as<T>(
     batches: &[RecordBatch],
     column_index: usize,
@@ -158,6 +197,21 @@ pub struct SqlQueryRequest {
     pub query: String,
 }
 
+/// File format in which the admin service encodes exported query results.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+    Ndjson,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct ExportQueryRequest {
+    query: String,
+    format: ExportFormat,
+}
+
 pub struct SqlResponse {
     pub schema: SchemaRef,
     pub batches: Vec<RecordBatch>,
@@ -20,4 +20,4 @@ pub use self::admin_client::AdminClient;
 pub use self::admin_client::Error as MetasClientError;
 pub use self::admin_client::{MAX_ADMIN_API_VERSION, MIN_ADMIN_API_VERSION};
 pub use self::admin_interface::AdminClientInterface;
-pub use self::datafusion_http_client::DataFusionHttpClient;
+pub use self::datafusion_http_client::{DataFusionHttpClient, ExportFormat};
@@ -15,7 +15,7 @@ use std::pin::Pin;
 
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 use futures::FutureExt;
@@ -27,7 +27,7 @@ use hyper::Response;
 use hyper_rustls::HttpsConnector;
 use restate_cli_util::CliContext;
 use restate_types::retries::RetryPolicy;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use url::Url;
 
 use crate::cli_env::CliEnv;
@@ -62,14 +62,31 @@ pub(crate) async fn run_local(
         .enable_http2()
         .wrap_connector(http_connector);
 
-    let request_identity_key =
-        environment_info
-            .signing_public_key
-            .as_deref()
-            .map(|signing_public_key| {
-                super::request_identity::parse_public_key(signing_public_key)
-                    .expect("Problem validating request identity public key")
-            });
+    let mut request_identity_keys = super::request_identity::PublicKeys::default();
+    if let Some(signing_public_key) = environment_info.signing_public_key.as_deref() {
+        request_identity_keys
+            .insert_public_key(signing_public_key)
+            .expect("Problem validating request identity public key");
+    }
+    // the admin API publishes all the keys in use, including the ones being rotated in
+    let admin_base_url = env.admin_base_url().ok().cloned();
+    let mut last_fetch = None;
+    if let Some(admin_base_url) = &admin_base_url {
+        last_fetch = Some(Instant::now());
+        match super::request_identity::fetch_published_keys(&client, admin_base_url, bearer_token)
+            .await
+        {
+            Ok(jwks) => request_identity_keys.insert_jwks(&jwks),
+            Err(err) => warn!("Failed to fetch the published request identity keys: {err:#}"),
+        }
+    }
+    let request_identity_keys = (!request_identity_keys.is_empty()).then(|| {
+        super::request_identity::RequestIdentityKeys::new(
+            request_identity_keys,
+            admin_base_url,
+            last_fetch,
+        )
+    });
 
     let retry_policy = RetryPolicy::exponential(
         Duration::from_millis(10),
@@ -88,7 +105,7 @@ pub(crate) async fn run_local(
         https_connector,
         inner: Arc::new(HandlerInner {
             tunnel_renderer,
-            request_identity_key,
+            request_identity_keys,
             environment_id: environment_info.environment_id,
             environment_name: environment_info.name,
             bearer_token: bearer_token.into(),
@@ -177,7 +194,7 @@ impl<Proxy: Clone> Clone for Handler<Proxy> {
 pub(crate) struct HandlerInner {
     pub opts: super::Tunnel,
     pub tunnel_renderer: Arc<TunnelRenderer>,
-    pub request_identity_key: Option<jsonwebtoken::DecodingKey>,
+    pub request_identity_keys: Option<super::request_identity::RequestIdentityKeys>,
     pub environment_id: String,
     pub bearer_token: String,
     pub client: reqwest::Client,
@@ -449,12 +466,16 @@ pub(crate) async fn proxy(
     inner: Arc<HandlerInner>,
     request: reqwest::Request,
 ) -> Result<Response<hyper::Body>, StartError> {
-    if let Some(request_identity_key) = &inner.request_identity_key {
-        if let Err(err) = super::request_identity::validate_request_identity(
-            request_identity_key,
-            request.headers(),
-            request.url().path(),
-        ) {
+    if let Some(request_identity_keys) = &inner.request_identity_keys {
+        if let Err(err) = request_identity_keys
+            .validate(
+                &inner.client,
+                &inner.bearer_token,
+                request.headers(),
+                request.url().path(),
+            )
+            .await
+        {
             error!("Failed to validate request identity: {}", err);
 
             inner.tunnel_renderer.store_error(format!(
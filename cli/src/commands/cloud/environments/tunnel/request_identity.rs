@@ -8,8 +8,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
 use http::HeaderMap;
+use jsonwebtoken::jwk::JwkSet;
 use serde::Deserialize;
+use tracing::warn;
+use url::Url;
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum Error {
@@ -23,6 +31,10 @@ pub(crate) enum Error {
     MissingJWT,
     #[error("Invalid JWT header")]
     InvalidJWT,
+    #[error("Missing key id in the JWT")]
+    MissingKeyId,
+    #[error("Unknown key id '{0}'; the key is not published by the environment")]
+    UnknownKeyId(String),
     #[error("Could not validate JWT: {0}")]
     JWTValidation(#[from] jsonwebtoken::errors::Error),
 }
@@ -38,11 +50,118 @@ pub(crate) fn parse_public_key(public_key: &str) -> Result<jsonwebtoken::Decodin
     Ok(jsonwebtoken::DecodingKey::from_ed_der(&public_key_bytes))
 }
 
+/// The public keys accepted when validating the request identity, by key id.
+#[derive(Default)]
+pub(crate) struct PublicKeys(HashMap<String, jsonwebtoken::DecodingKey>);
+
+impl PublicKeys {
+    /// Adds a public key in the `publickeyv1_` format, which is also its key id.
+    pub(crate) fn insert_public_key(&mut self, public_key: &str) -> Result<(), Error> {
+        self.0
+            .insert(public_key.to_owned(), parse_public_key(public_key)?);
+        Ok(())
+    }
+
+    /// Adds the keys of a key set published by the admin API, skipping those which can't be used.
+    pub(crate) fn insert_jwks(&mut self, jwks: &JwkSet) {
+        for jwk in &jwks.keys {
+            if let (Some(kid), Ok(decoding_key)) =
+                (&jwk.common.key_id, jsonwebtoken::DecodingKey::from_jwk(jwk))
+            {
+                self.0.insert(kid.clone(), decoding_key);
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Minimum time between two fetches of the published keys, to not hit the admin API on every
+/// request signed with an unknown key.
+const MIN_FETCH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The public keys accepted when validating the request identity. The keys published by the admin
+/// API are fetched again when a request is signed with an unknown key, e.g. after a rotation.
+pub(crate) struct RequestIdentityKeys {
+    keys: RwLock<PublicKeys>,
+    admin_base_url: Option<Url>,
+    last_fetch: Mutex<Option<Instant>>,
+}
+
+impl RequestIdentityKeys {
+    pub(crate) fn new(
+        keys: PublicKeys,
+        admin_base_url: Option<Url>,
+        last_fetch: Option<Instant>,
+    ) -> Self {
+        Self {
+            keys: RwLock::new(keys),
+            admin_base_url,
+            last_fetch: Mutex::new(last_fetch),
+        }
+    }
+
+    pub(crate) async fn validate(
+        &self,
+        client: &reqwest::Client,
+        bearer_token: &str,
+        headers: &HeaderMap,
+        path: &str,
+    ) -> Result<(), Error> {
+        let admin_base_url = match (
+            validate_request_identity(&self.keys.read().expect("not poisoned"), headers, path),
+            &self.admin_base_url,
+        ) {
+            (Err(Error::UnknownKeyId(_)), Some(admin_base_url)) if self.start_fetch() => {
+                admin_base_url
+            }
+            (result, _) => return result,
+        };
+
+        match fetch_published_keys(client, admin_base_url, bearer_token).await {
+            Ok(jwks) => self.keys.write().expect("not poisoned").insert_jwks(&jwks),
+            Err(err) => warn!("Failed to fetch the published request identity keys: {err:#}"),
+        }
+
+        validate_request_identity(&self.keys.read().expect("not poisoned"), headers, path)
+    }
+
+    fn start_fetch(&self) -> bool {
+        let mut last_fetch = self.last_fetch.lock().expect("not poisoned");
+        if last_fetch.is_some_and(|last_fetch| last_fetch.elapsed() < MIN_FETCH_INTERVAL) {
+            return false;
+        }
+        *last_fetch = Some(Instant::now());
+        true
+    }
+}
+
+/// Fetches the request identity public keys published by the admin API.
+pub(crate) async fn fetch_published_keys(
+    client: &reqwest::Client,
+    admin_base_url: &Url,
+    bearer_token: &str,
+) -> anyhow::Result<JwkSet> {
+    let url = admin_base_url
+        .join(".well-known/jwks.json")
+        .context("Invalid admin URL")?;
+    Ok(client
+        .get(url)
+        .bearer_auth(bearer_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
 #[derive(Deserialize)]
 struct Claims {}
 
 pub(crate) fn validate_request_identity(
-    decoding_key: &jsonwebtoken::DecodingKey,
+    public_keys: &PublicKeys,
     headers: &HeaderMap,
     path: &str,
 ) -> Result<(), Error> {
@@ -73,6 +192,14 @@ pub(crate) fn validate_request_identity(
     validate.validate_nbf = true;
     validate.set_audience(&[path]);
 
+    let kid = jsonwebtoken::decode_header(jwt)?
+        .kid
+        .ok_or(Error::MissingKeyId)?;
+    let decoding_key = public_keys
+        .0
+        .get(&kid)
+        .ok_or_else(|| Error::UnknownKeyId(kid.clone()))?;
+
     jsonwebtoken::decode::<Claims>(jwt, decoding_key, &validate)?;
 
     Ok(())
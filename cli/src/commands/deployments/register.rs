@@ -10,6 +10,7 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::num::NonZeroUsize;
 use std::str::FromStr;
 
 use anyhow::Result;
@@ -23,6 +24,7 @@ use restate_cli_util::ui::console::{confirm_or_exit, Styled, StyledTable};
 use restate_cli_util::ui::stylesheet::Style;
 use restate_cli_util::{c_eprintln, c_error, c_indent_table, c_indentln, c_success, c_warn};
 use restate_types::identifiers::LambdaARN;
+use restate_types::schema::deployment::LambdaInvokeMode;
 use restate_types::schema::service::ServiceMetadata;
 
 use crate::cli_env::CliEnv;
@@ -58,6 +60,25 @@ pub struct Register {
     #[clap(long = "use-http1.1")]
     use_http_11: bool,
 
+    /// Maximum number of connections Restate server opens to the deployment. With HTTP2, the
+    /// streams are spread across the connections.
+    #[clap(long)]
+    max_connections: Option<NonZeroUsize>,
+
+    /// Maximum number of concurrent HTTP2 streams per connection. Set it to the limit of the load
+    /// balancer in front of the deployment, if any.
+    #[clap(long, conflicts_with = "use_http_11")]
+    max_concurrent_streams: Option<NonZeroUsize>,
+
+    /// How long an idle connection to the deployment is kept open, e.g. `90s`.
+    #[clap(long)]
+    pool_idle_timeout: Option<humantime::Duration>,
+
+    /// Invoke the Lambda function with response streaming. The function must stream its response
+    /// with `awslambda.HttpResponseStream`.
+    #[clap(long)]
+    lambda_response_streaming: bool,
+
     /// The URL or ARN that Restate server needs to fetch service information from.
     ///
     /// The URL must be network-accessible from Restate server. In case of using
@@ -182,12 +203,20 @@ pub async fn run_register(State(env): State<CliEnv>, discover_opts: &Register) -
             uri: uri.clone(),
             additional_headers: headers.clone().map(Into::into),
             use_http_11: discover_opts.use_http_11,
+            max_connections: discover_opts.max_connections,
+            max_concurrent_streams: discover_opts.max_concurrent_streams,
+            pool_idle_timeout: discover_opts.pool_idle_timeout,
             force,
             dry_run,
         },
         DeploymentEndpoint::Lambda(arn) => RegisterDeploymentRequest::Lambda {
             arn: arn.to_string(),
             assume_role_arn: discover_opts.assume_role_arn.clone(),
+            invoke_mode: if discover_opts.lambda_response_streaming {
+                LambdaInvokeMode::ResponseStream
+            } else {
+                LambdaInvokeMode::Buffered
+            },
             additional_headers: headers.clone().map(Into::into),
             force,
             dry_run,
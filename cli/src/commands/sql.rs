@@ -9,10 +9,15 @@
 // by the Apache License, Version 2.0.
 //
 
+use std::collections::BTreeSet;
+use std::ffi::OsString;
 use std::io;
+use std::num::NonZeroU64;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use arrow::error::ArrowError;
 use arrow::util::display::ArrayFormatter;
 use arrow::util::display::FormatOptions;
@@ -30,6 +35,10 @@ use restate_cli_util::ui::stylesheet::Style;
 use restate_cli_util::ui::watcher::Watch;
 
 use crate::cli_env::CliEnv;
+use crate::clients::{DataFusionHttpClient, ExportFormat};
+
+/// Name of the file recording which parts of a split export have been completed.
+const EXPORT_PROGRESS_FILE: &str = "export-progress.json";
 
 #[derive(Run, Parser, Collect, Clone)]
 #[cling(run = "run_sql")]
@@ -47,6 +56,18 @@ pub struct Sql {
     /// Print result as json array instead of using the tabular format
     #[arg(long)]
     pub json: bool,
+
+    /// Write the result to a file instead of printing it, e.g. `--output parquet state.parquet`.
+    /// Supported formats are parquet, csv and ndjson.
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "PATH"], conflicts_with_all = ["json", "jsonl"])]
+    pub output: Option<Vec<String>>,
+
+    /// Split the exported result into the given number of files, each covering an equal slice
+    /// of the partition key space. PATH is then a directory, and re-running an interrupted
+    /// export with the same arguments resumes after the last completed file. The query result
+    /// must contain the `partition_key` column, as the `state` table does.
+    #[arg(long, value_name = "FILES", requires = "output")]
+    pub split_by_partition_key: Option<NonZeroU64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -54,12 +75,172 @@ pub struct SqlQueryRequest {
     pub query: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct ExportProgress {
+    query: String,
+    format: ExportFormat,
+    files: u64,
+    completed: BTreeSet<u64>,
+}
+
 pub async fn run_sql(State(env): State<CliEnv>, opts: &Sql) -> Result<()> {
+    if let Some(output) = &opts.output {
+        return run_export(&env, opts, output).await;
+    }
     opts.watch.run(|| run_query(&env, opts)).await
 }
 
+async fn run_export(env: &CliEnv, sql_opts: &Sql, output: &[String]) -> Result<()> {
+    let [format, path] = output else {
+        bail!("--output expects a format and a path");
+    };
+    let format = match format.to_ascii_lowercase().as_str() {
+        "parquet" => ExportFormat::Parquet,
+        "csv" => ExportFormat::Csv,
+        "ndjson" | "jsonl" => ExportFormat::Ndjson,
+        _ => bail!(
+            "Unsupported output format '{}', expected one of parquet, csv or ndjson",
+            format
+        ),
+    };
+    let path = PathBuf::from(path);
+
+    let client = DataFusionHttpClient::new(env).await?;
+    let start_time = Instant::now();
+
+    if let Some(files) = sql_opts.split_by_partition_key {
+        export_by_partition_key(&client, &sql_opts.query, format, &path, files.get()).await?;
+    } else {
+        let written = export_to_file(&client, sql_opts.query.clone(), format, &path).await?;
+        c_eprintln!("Wrote {} bytes to {}", written, path.display());
+    }
+
+    c_eprintln!(
+        "Export took {:?}",
+        Styled(Style::Notice, start_time.elapsed())
+    );
+    Ok(())
+}
+
+async fn export_by_partition_key(
+    client: &DataFusionHttpClient,
+    query: &str,
+    format: ExportFormat,
+    dir: &Path,
+    files: u64,
+) -> Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let progress_path = dir.join(EXPORT_PROGRESS_FILE);
+
+    let mut progress = match tokio::fs::read(&progress_path).await {
+        Ok(content) => {
+            let progress: ExportProgress = serde_json::from_slice(&content)?;
+            if progress.query != query || progress.format != format || progress.files != files {
+                bail!(
+                    "{} belongs to a different export. Use another directory, or remove it to start over",
+                    progress_path.display()
+                );
+            }
+            progress
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => ExportProgress {
+            query: query.to_owned(),
+            format,
+            files,
+            completed: BTreeSet::new(),
+        },
+        Err(err) => return Err(err.into()),
+    };
+
+    let query = query.trim().trim_end_matches(';');
+    for (part, range) in (0..files).zip(partition_key_ranges(files)) {
+        if progress.completed.contains(&part) {
+            continue;
+        }
+
+        let path = dir.join(format!("part-{:05}.{}", part, file_extension(format)));
+        let written = export_to_file(
+            client,
+            format!(
+                "SELECT * FROM ({}) WHERE partition_key BETWEEN {} AND {}",
+                query,
+                range.start(),
+                range.end()
+            ),
+            format,
+            &path,
+        )
+        .await?;
+
+        progress.completed.insert(part);
+        write_atomically(&progress_path, &serde_json::to_vec_pretty(&progress)?).await?;
+        c_eprintln!(
+            "[{}/{}] Wrote {} bytes to {}",
+            progress.completed.len(),
+            files,
+            written,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Streams the exported result into a sibling file that is renamed once complete, so that an
+/// interrupted export never leaves a truncated file at `path`.
+async fn export_to_file(
+    client: &DataFusionHttpClient,
+    query: String,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<u64> {
+    let partial_path = partial_path(path);
+    let mut file = tokio::fs::File::create(&partial_path).await?;
+    let written = client.export_query(query, format, &mut file).await?;
+    file.sync_all().await?;
+    tokio::fs::rename(&partial_path, path).await?;
+
+    Ok(written)
+}
+
+async fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let partial_path = partial_path(path);
+    tokio::fs::write(&partial_path, content).await?;
+    tokio::fs::rename(&partial_path, path).await?;
+    Ok(())
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut partial_path = OsString::from(path.as_os_str());
+    partial_path.push(".partial");
+    PathBuf::from(partial_path)
+}
+
+fn file_extension(format: ExportFormat) -> &'static str {
+    match format {
+        ExportFormat::Parquet => "parquet",
+        ExportFormat::Csv => "csv",
+        ExportFormat::Ndjson => "ndjson",
+    }
+}
+
+/// Splits the partition key space into `parts` contiguous ranges of (almost) equal size.
+fn partition_key_ranges(parts: u64) -> impl Iterator<Item = RangeInclusive<u64>> {
+    let key_space = u128::from(u64::MAX) + 1;
+    let start_of = move |part: u64| (u128::from(part) * key_space / u128::from(parts)) as u64;
+
+    (0..parts).map(move |part| {
+        let end = if part + 1 == parts {
+            u64::MAX
+        } else {
+            start_of(part + 1) - 1
+        };
+        start_of(part)..=end
+    })
+}
+
 async fn run_query(env: &CliEnv, sql_opts: &Sql) -> Result<()> {
-    let client = crate::clients::DataFusionHttpClient::new(env).await?;
+    let client = DataFusionHttpClient::new(env).await?;
     let start_time = Instant::now();
     let resp = client.run_query(sql_opts.query.clone()).await?;
 
@@ -115,3 +296,24 @@ async fn run_query(env: &CliEnv, sql_opts: &Sql) -> Result<()> {
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_key_ranges_cover_key_space() {
+        assert_eq!(
+            partition_key_ranges(1).collect::<Vec<_>>(),
+            vec![0..=u64::MAX]
+        );
+
+        let ranges: Vec<_> = partition_key_ranges(3).collect();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(*ranges[0].start(), 0);
+        assert_eq!(*ranges[2].end(), u64::MAX);
+        for window in ranges.windows(2) {
+            assert_eq!(*window[0].end() + 1, *window[1].start());
+        }
+    }
+}
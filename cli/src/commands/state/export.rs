@@ -0,0 +1,90 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use cling::prelude::*;
+use itertools::Itertools;
+
+use restate_cli_util::c_eprintln;
+
+use crate::cli_env::CliEnv;
+use crate::clients::datafusion_helpers::{get_state_by_key_prefix, get_state_keys};
+use crate::commands::state::util::{as_json, compute_version, ExportedObjectState};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_export")]
+pub struct Export {
+    /// Don't try to convert the values to a UTF-8 string
+    #[clap(long, alias = "bin")]
+    binary: bool,
+
+    /// Only export the objects whose key starts with this prefix
+    #[clap(long)]
+    key_prefix: Option<String>,
+
+    /// Write the export to this file instead of stdout
+    #[clap(long, short)]
+    output: Option<PathBuf>,
+
+    /// service name
+    service: String,
+}
+
+pub async fn run_export(State(env): State<CliEnv>, opts: &Export) -> Result<()> {
+    export(&env, opts).await
+}
+
+async fn export(env: &CliEnv, opts: &Export) -> Result<()> {
+    let sql_client = crate::clients::DataFusionHttpClient::new(env).await?;
+
+    #[allow(clippy::mutable_key_type)]
+    let services_state = match &opts.key_prefix {
+        Some(key_prefix) => get_state_by_key_prefix(&sql_client, &opts.service, key_prefix).await?,
+        None => get_state_keys(&sql_client, &opts.service, None).await?,
+    };
+
+    let mut writer: Box<dyn Write> = match &opts.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).context("Failed to create the export file")?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    let mut count = 0;
+    for (service_id, state) in services_state
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.key.cmp(&b.key))
+    {
+        let line = ExportedObjectState {
+            service: service_id.service_name.to_string(),
+            key: service_id.key.to_string(),
+            version: compute_version(&state),
+            binary: opts.binary,
+            state: as_json(state, opts.binary).with_context(|| {
+                format!(
+                    "Failed to convert the state of '{}/{}', consider exporting with --binary",
+                    service_id.service_name, service_id.key
+                )
+            })?,
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+    writer.flush()?;
+
+    c_eprintln!("Exported the state of {} objects", count);
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use cling::prelude::*;
+use comfy_table::{Cell, Table};
+use futures::future::try_join_all;
+use itertools::Itertools;
+
+use restate_admin_rest_model::services::ModifyServiceStateRequest;
+use restate_cli_util::ui::console::{confirm_or_exit, StyledTable};
+use restate_cli_util::{c_indent_table, c_println, c_title, CliContext};
+use restate_types::identifiers::ServiceId;
+
+use crate::cli_env::CliEnv;
+use crate::clients::datafusion_helpers::get_state_of_keys;
+use crate::clients::{AdminClient, AdminClientInterface, DataFusionHttpClient};
+use crate::commands::state::util::{compute_version, from_json, ExportedObjectState};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_import")]
+pub struct Import {
+    /// Import the state into this service, instead of the service recorded in the file
+    #[clap(long)]
+    service: Option<String>,
+
+    /// Force means, ignore the current version of the objects being overwritten
+    #[clap(long, short)]
+    force: bool,
+
+    /// Only print the changes the import would make, without applying them
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Number of objects read and updated together
+    #[clap(long, default_value = "100")]
+    batch_size: NonZeroUsize,
+
+    /// File written by `restate state export`, or `-` to read from stdin (requires `--yes`)
+    file: PathBuf,
+}
+
+/// Differences between the imported state of an object and its current state.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct StateDiff {
+    added: usize,
+    changed: usize,
+    removed: usize,
+}
+
+impl StateDiff {
+    fn new(current: &HashMap<String, Bytes>, imported: &HashMap<String, Bytes>) -> Self {
+        let mut diff = StateDiff::default();
+        for (key, value) in imported {
+            match current.get(key) {
+                None => diff.added += 1,
+                Some(current_value) if current_value != value => diff.changed += 1,
+                Some(_) => {}
+            }
+        }
+        diff.removed = current
+            .keys()
+            .filter(|key| !imported.contains_key(*key))
+            .count();
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == StateDiff::default()
+    }
+}
+
+pub async fn run_import(State(env): State<CliEnv>, opts: &Import) -> Result<()> {
+    import(&env, opts).await
+}
+
+async fn import(env: &CliEnv, opts: &Import) -> Result<()> {
+    let read_stdin = opts.file.as_os_str() == "-";
+    // The confirmation prompt can't be answered once stdin was consumed by the import
+    if read_stdin && !opts.dry_run && !CliContext::get().auto_confirm() {
+        bail!("Reading the import file from stdin requires --yes, or --dry-run");
+    }
+
+    let objects = if read_stdin {
+        parse_export_file(BufReader::new(io::stdin()), opts.service.as_deref())?
+    } else {
+        parse_export_file(
+            BufReader::new(File::open(&opts.file).context("Unable to open the file for reading")?),
+            opts.service.as_deref(),
+        )?
+    };
+    if objects.is_empty() {
+        bail!("No state found in the import file!");
+    }
+
+    let mut table = Table::new_styled();
+    table.set_styled_header(vec!["", ""]);
+    table.add_row(vec![
+        Cell::new("Services"),
+        Cell::new(objects.iter().map(|object| &object.service).unique().join(", ")),
+    ]);
+    table.add_row(vec![Cell::new("Objects"), Cell::new(objects.len())]);
+    table.add_row(vec![Cell::new("Force?"), Cell::new(opts.force)]);
+    table.add_row(vec![Cell::new("Dry run?"), Cell::new(opts.dry_run)]);
+
+    c_title!("ℹ️ ", "State Import");
+    c_println!("{table}");
+    c_println!();
+
+    if !opts.dry_run {
+        c_println!("About to submit the state mutations to the system for processing.");
+        c_println!("If there are currently active invocations, then these mutations will be enqueued to be processed after them.");
+        c_println!();
+        confirm_or_exit("Are you sure?")?;
+        c_println!();
+    }
+
+    let client = AdminClient::new(env).await?;
+    let sql_client = DataFusionHttpClient::from(client.clone());
+
+    let mut changes = Table::new_styled();
+    changes.set_styled_header(vec!["SERVICE/KEY", "ADDED", "CHANGED", "REMOVED"]);
+    let mut submitted = 0;
+    let mut unchanged = 0;
+
+    for batch in &objects.into_iter().chunks(opts.batch_size.get()) {
+        let batch = batch.collect::<Vec<_>>();
+
+        #[allow(clippy::mutable_key_type)]
+        let mut current_states = HashMap::new();
+        for (service, objects) in &batch.iter().group_by(|object| &object.service) {
+            let keys = objects.map(|object| object.key.as_str()).collect::<Vec<_>>();
+            current_states.extend(get_state_of_keys(&sql_client, service, &keys).await?);
+        }
+
+        let mut mutations = Vec::with_capacity(batch.len());
+        for object in batch {
+            let current_state = current_states
+                .remove(&ServiceId::new(object.service.clone(), object.key.clone()))
+                .unwrap_or_default();
+            let imported_state = from_json(object.state, object.binary).with_context(|| {
+                format!(
+                    "Failed to read the state of '{}/{}'",
+                    object.service, object.key
+                )
+            })?;
+
+            let diff = StateDiff::new(&current_state, &imported_state);
+            if diff.is_empty() {
+                unchanged += 1;
+                continue;
+            }
+            changes.add_row(vec![
+                Cell::new(format!("{}/{}", object.service, object.key)),
+                Cell::new(diff.added),
+                Cell::new(diff.changed),
+                Cell::new(diff.removed),
+            ]);
+
+            let version = if opts.force {
+                None
+            } else {
+                Some(compute_version(&current_state))
+            };
+            mutations.push((
+                object.service,
+                ModifyServiceStateRequest {
+                    version,
+                    object_key: object.key,
+                    new_state: imported_state,
+                },
+            ));
+        }
+
+        if opts.dry_run {
+            continue;
+        }
+
+        submitted += mutations.len();
+        try_join_all(mutations.into_iter().map(|(service, request)| {
+            let client = &client;
+            async move {
+                client
+                    .patch_state(&service, request)
+                    .await?
+                    .success_or_error()?;
+                anyhow::Ok(())
+            }
+        }))
+        .await?;
+    }
+
+    if changes.row_count() > 0 {
+        c_indent_table!(0, changes);
+        c_println!();
+    }
+
+    if opts.dry_run {
+        c_println!(
+            "Dry run: {} objects would be updated, {} are unchanged",
+            changes.row_count(),
+            unchanged
+        );
+    } else {
+        c_println!(
+            "Enqueued {} state mutations successfully for processing, {} objects are unchanged",
+            submitted,
+            unchanged
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_export_file(
+    reader: impl BufRead,
+    service: Option<&str>,
+) -> Result<Vec<ExportedObjectState>> {
+    let mut objects = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.context("Unable to read the import file")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut object: ExportedObjectState = serde_json::from_str(&line)
+            .with_context(|| format!("Failed parsing line {} of the import file", line_number + 1))?;
+        if let Some(service) = service {
+            object.service = service.to_owned();
+        }
+        objects.push(object);
+    }
+
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(entries: &[(&str, &str)]) -> HashMap<String, Bytes> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), Bytes::copy_from_slice(value.as_bytes())))
+            .collect()
+    }
+
+    #[test]
+    fn diff_states() {
+        let current = state(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let imported = state(&[("a", "1"), ("b", "20"), ("d", "4")]);
+
+        assert_eq!(
+            StateDiff::new(&current, &imported),
+            StateDiff {
+                added: 1,
+                changed: 1,
+                removed: 1,
+            }
+        );
+        assert!(StateDiff::new(&current, &current).is_empty());
+        assert_eq!(
+            StateDiff::new(&HashMap::new(), &current),
+            StateDiff {
+                added: 3,
+                changed: 0,
+                removed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_export_lines() {
+        let file = concat!(
+            r#"{"service":"Counter","key":"a","version":"v1","state":{"count":1}}"#,
+            "\n\n",
+            r#"{"service":"Counter","key":"b","version":"v2","binary":true,"state":{"count":"MQ=="}}"#,
+            "\n",
+        );
+
+        let objects = parse_export_file(file.as_bytes(), None).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].service, "Counter");
+        assert_eq!(objects[0].key, "a");
+        assert!(!objects[0].binary);
+        assert_eq!(objects[1].key, "b");
+        assert!(objects[1].binary);
+
+        let objects = parse_export_file(file.as_bytes(), Some("Other")).unwrap();
+        assert!(objects.iter().all(|object| object.service == "Other"));
+    }
+
+    #[test]
+    fn reject_malformed_export_lines() {
+        let file = concat!(
+            r#"{"service":"Counter","key":"a","version":"v1","state":{}}"#,
+            "\n",
+            r#"{"service":"Counter"}"#,
+            "\n",
+        );
+
+        let err = parse_export_file(file.as_bytes(), None).unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
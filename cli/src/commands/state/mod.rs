@@ -10,7 +10,9 @@
 
 mod clear;
 mod edit;
+mod export;
 mod get;
+mod import;
 mod util;
 
 use cling::prelude::*;
@@ -23,4 +25,8 @@ pub enum ServiceState {
     Edit(edit::Edit),
     /// Clear of the state of a given service
     Clear(clear::Clear),
+    /// Export the state of a service's objects as newline delimited JSON
+    Export(export::Export),
+    /// Import the state of objects from a file written by `state export`
+    Import(import::Import),
 }
@@ -20,6 +20,7 @@ use base64::engine::{Engine, GeneralPurpose, GeneralPurposeConfig};
 use bytes::Bytes;
 use comfy_table::{Cell, Table};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use restate_admin_rest_model::services::ModifyServiceStateRequest;
@@ -36,6 +37,19 @@ pub struct StateEntriesQueryResult {
     value: Option<Vec<u8>>,
 }
 
+/// A line of a state export file, holding the state of a single object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ExportedObjectState {
+    pub service: String,
+    pub key: String,
+    /// Version of the state at export time
+    pub version: String,
+    /// Whether the values are base64 encoded, rather than JSON
+    #[serde(default)]
+    pub binary: bool,
+    pub state: Value,
+}
+
 pub(crate) async fn get_current_state(
     env: &CliEnv,
     service: &str,
@@ -0,0 +1,169 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use cling::prelude::*;
+use comfy_table::Table;
+use http::{StatusCode, Uri};
+use itertools::Itertools;
+
+use restate_admin_rest_model::subscriptions::CreateSubscriptionRequest;
+use restate_cli_util::ui::console::{confirm_or_exit, StyledTable};
+use restate_cli_util::{c_println, c_success};
+
+use crate::cli_env::CliEnv;
+use crate::clients::{AdminClient, AdminClientInterface};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_create")]
+pub struct Create {
+    /// Additional option of the subscription, e.g. `auto.offset.reset=earliest` to configure
+    /// the Kafka consumer.
+    ///
+    /// Use `--option name=value` format and repeat --option for each additional option.
+    #[clap(long = "option", value_parser = parse_option, action = clap::ArgAction::Append)]
+    options: Option<Vec<(String, String)>>,
+
+    /// Source of the events, in the form `kafka://<cluster>/<topic>`.
+    ///
+    /// The cluster must be configured in the ingress options of the Restate server.
+    #[clap(value_parser = parse_source)]
+    source: Uri,
+
+    /// Handler receiving the events, in the form `service://<service>/<handler>`.
+    #[clap(value_parser = parse_sink)]
+    sink: Uri,
+}
+
+fn parse_option(
+    raw: &str,
+) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    // key=value
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("invalid name=value: no `=` found in `{raw}`"))?;
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+/// Splits a `<scheme>://<authority>/<name>` uri into its authority and name, naming the
+/// expected parts in the error messages.
+fn parse_uri<'a>(
+    uri: &'a Uri,
+    scheme: &str,
+    authority_part: &str,
+    name_part: &str,
+) -> Result<(&'a str, &'a str), String> {
+    let form = format!("{scheme}://<{authority_part}>/<{name_part}>");
+    if uri.scheme_str() != Some(scheme) {
+        return Err(format!(
+            "unsupported scheme in `{uri}`, expected the form `{form}`"
+        ));
+    }
+    let authority = uri
+        .authority()
+        .map(|authority| authority.as_str())
+        .filter(|authority| !authority.is_empty())
+        .ok_or_else(|| format!("missing {authority_part} in `{uri}`, expected the form `{form}`"))?;
+    let name = uri.path().trim_start_matches('/');
+    if name.is_empty() || name.contains('/') || uri.query().is_some() {
+        return Err(format!(
+            "missing or invalid {name_part} in `{uri}`, expected the form `{form}`"
+        ));
+    }
+    Ok((authority, name))
+}
+
+fn parse_source(raw: &str) -> Result<Uri, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let uri = Uri::from_str(raw).map_err(|e| format!("invalid source uri `{raw}` ({e})"))?;
+    parse_uri(&uri, "kafka", "cluster", "topic")?;
+    Ok(uri)
+}
+
+fn parse_sink(raw: &str) -> Result<Uri, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let uri = Uri::from_str(raw).map_err(|e| format!("invalid sink uri `{raw}` ({e})"))?;
+    parse_uri(&uri, "service", "service", "handler")?;
+    Ok(uri)
+}
+
+pub async fn run_create(State(env): State<CliEnv>, opts: &Create) -> Result<()> {
+    let client = AdminClient::new(&env).await?;
+
+    // The server validates the sink as well, but we can give more helpful hints here
+    let (service_name, handler_name) =
+        parse_uri(&opts.sink, "service", "service", "handler").map_err(anyhow::Error::msg)?;
+    let envelope = client.get_service(service_name).await?;
+    if envelope.status_code() == StatusCode::NOT_FOUND {
+        bail!(
+            "Service '{}' of the sink is not registered. Did you forget to register its deployment with 'restate deployments register'?",
+            service_name
+        );
+    }
+    let service = envelope.into_body().await?;
+    if !service
+        .handlers
+        .iter()
+        .any(|handler| handler.name == handler_name)
+    {
+        bail!(
+            "Service '{}' has no handler '{}' for the sink. Available handlers are: {}",
+            service_name,
+            handler_name,
+            service.handlers.iter().map(|handler| &handler.name).join(", ")
+        );
+    }
+
+    let options: HashMap<_, _> = opts.options.iter().flatten().cloned().collect();
+
+    let mut table = Table::new_styled();
+    table.add_kv_row("Source:", &opts.source);
+    table.add_kv_row("Sink:", &opts.sink);
+    for (key, value) in options.iter().sorted() {
+        table.add_kv_row(&format!("{key}:"), value);
+    }
+    c_println!("{}", table);
+    c_println!();
+
+    confirm_or_exit("Are you sure you want to create this subscription?")?;
+
+    let subscription = client
+        .create_subscription(CreateSubscriptionRequest {
+            source: opts.source.clone(),
+            sink: opts.sink.clone(),
+            options: (!options.is_empty()).then_some(options),
+        })
+        .await?
+        .into_body()
+        .await?;
+
+    c_println!();
+    c_success!("Subscription {} created successfully", subscription.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_source_and_sink() {
+        assert!(parse_source("kafka://my-cluster/my-topic").is_ok());
+        assert!(parse_sink("service://Counter/count").is_ok());
+
+        let err = parse_source("http://my-cluster/my-topic").unwrap_err();
+        assert!(err.to_string().contains("kafka://<cluster>/<topic>"));
+        let err = parse_source("kafka://my-cluster").unwrap_err();
+        assert!(err.to_string().contains("topic"));
+        let err = parse_sink("service://Counter/count/extra").unwrap_err();
+        assert!(err.to_string().contains("handler"));
+    }
+}
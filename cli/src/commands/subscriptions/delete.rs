@@ -0,0 +1,56 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use cling::prelude::*;
+use comfy_table::Table;
+
+use restate_cli_util::ui::console::{confirm_or_exit, StyledTable};
+use restate_cli_util::{c_println, c_success};
+
+use crate::cli_env::CliEnv;
+use crate::clients::{AdminClient, AdminClientInterface};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[clap(visible_alias = "rm", alias = "remove")]
+#[cling(run = "run_delete")]
+pub struct Delete {
+    /// Subscription ID
+    subscription_id: String,
+}
+
+pub async fn run_delete(State(env): State<CliEnv>, opts: &Delete) -> Result<()> {
+    let client = AdminClient::new(&env).await?;
+
+    let subscription = client
+        .get_subscription(&opts.subscription_id)
+        .await?
+        .into_body()
+        .await?;
+
+    let mut table = Table::new_styled();
+    table.add_kv_row("ID:", subscription.id);
+    table.add_kv_row("Source:", &subscription.source);
+    table.add_kv_row("Sink:", &subscription.sink);
+    c_println!("{}", table);
+    c_println!();
+
+    c_println!("Events already ingested by this subscription will still be processed.");
+    confirm_or_exit("Are you sure you want to delete this subscription?")?;
+
+    client
+        .delete_subscription(&opts.subscription_id)
+        .await?
+        .success_or_error()?;
+
+    c_println!();
+    c_success!("Subscription {} deleted successfully", &opts.subscription_id);
+    Ok(())
+}
@@ -0,0 +1,106 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use cling::prelude::*;
+use comfy_table::{Cell, Table};
+use itertools::Itertools;
+
+use restate_cli_util::ui::console::StyledTable;
+use restate_cli_util::ui::watcher::Watch;
+use restate_cli_util::{c_indent_table, c_println, c_tip, c_title};
+
+use crate::cli_env::CliEnv;
+use crate::clients::datafusion_helpers::get_subscription_lag;
+use crate::clients::{AdminClient, AdminClientInterface, DataFusionHttpClient};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_describe")]
+#[clap(visible_alias = "get")]
+pub struct Describe {
+    /// Subscription ID
+    subscription_id: String,
+
+    #[clap(flatten)]
+    watch: Watch,
+}
+
+pub async fn run_describe(State(env): State<CliEnv>, opts: &Describe) -> Result<()> {
+    opts.watch.run(|| describe(&env, opts)).await
+}
+
+async fn describe(env: &CliEnv, opts: &Describe) -> Result<()> {
+    let client = AdminClient::new(env).await?;
+
+    let subscription = client
+        .get_subscription(&opts.subscription_id)
+        .await?
+        .into_body()
+        .await?;
+
+    let mut table = Table::new_styled();
+    table.add_kv_row("ID:", subscription.id);
+    table.add_kv_row("Source:", &subscription.source);
+    table.add_kv_row("Sink:", &subscription.sink);
+    for (key, value) in subscription.options.iter().sorted() {
+        table.add_kv_row(&format!("{key}:"), value);
+    }
+
+    c_title!("📜", "Subscription Information");
+    c_println!("{}", table);
+    c_println!();
+
+    let sql_client = DataFusionHttpClient::from(client);
+    let partitions = get_subscription_lag(&sql_client, &opts.subscription_id).await?;
+
+    c_title!("⏳", "Consumer Lag");
+    if partitions.is_empty() {
+        c_println!("No consumer progress was reported yet.");
+        c_tip!(
+            "The lag is reported once the first event of a partition has been ingested. \
+            Make sure that the Kafka cluster of the source is reachable by the Restate server. \
+            Only the partitions consumed by the node serving the query are listed."
+        );
+        return Ok(());
+    }
+
+    let mut lag_table = Table::new_styled();
+    lag_table.set_styled_header(vec![
+        "TOPIC",
+        "PARTITION",
+        "OFFSET",
+        "HIGH WATERMARK",
+        "LAG",
+        "UPDATED AT",
+    ]);
+    for partition in &partitions {
+        lag_table.add_row(vec![
+            Cell::new(&partition.topic),
+            Cell::new(partition.partition),
+            Cell::new(partition.offset),
+            Cell::new(partition.high_watermark),
+            Cell::new(partition.lag),
+            Cell::new(
+                partition
+                    .updated_at
+                    .map(|updated_at| updated_at.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]);
+    }
+    c_indent_table!(0, lag_table);
+    c_println!();
+    c_println!(
+        "Total lag: {}",
+        partitions.iter().map(|partition| partition.lag).sum::<i64>()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use anyhow::Result;
+use cling::prelude::*;
+use comfy_table::{Cell, Table};
+use itertools::Itertools;
+
+use restate_cli_util::ui::console::StyledTable;
+use restate_cli_util::ui::watcher::Watch;
+use restate_cli_util::{c_error, c_println};
+
+use crate::cli_env::CliEnv;
+use crate::clients::{AdminClient, AdminClientInterface};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[clap(visible_alias = "ls")]
+#[cling(run = "run_list")]
+pub struct List {
+    /// Show the options of the subscriptions
+    #[clap(long)]
+    extra: bool,
+
+    #[clap(flatten)]
+    watch: Watch,
+}
+
+pub async fn run_list(State(env): State<CliEnv>, opts: &List) -> Result<()> {
+    opts.watch.run(|| list(&env, opts)).await
+}
+
+async fn list(env: &CliEnv, opts: &List) -> Result<()> {
+    let client = AdminClient::new(env).await?;
+    let subscriptions = client
+        .get_subscriptions()
+        .await?
+        .into_body()
+        .await?
+        .subscriptions;
+
+    if subscriptions.is_empty() {
+        c_error!(
+            "No subscriptions were found! You can create one with 'restate subscriptions create'"
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new_styled();
+    let mut header = vec!["ID", "SOURCE", "SINK"];
+    if opts.extra {
+        header.push("OPTIONS");
+    }
+    table.set_styled_header(header);
+
+    for subscription in subscriptions {
+        let mut row = vec![
+            Cell::new(subscription.id),
+            Cell::new(subscription.source),
+            Cell::new(subscription.sink),
+        ];
+        if opts.extra {
+            row.push(Cell::new(
+                subscription
+                    .options
+                    .iter()
+                    .sorted()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .join("\n"),
+            ));
+        }
+        table.add_row(row);
+    }
+
+    c_println!("{}", table);
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod create;
+mod delete;
+mod describe;
+mod list;
+
+use cling::prelude::*;
+
+#[derive(Run, Subcommand, Clone)]
+#[clap(visible_alias = "sub", alias = "subscription")]
+pub enum Subscriptions {
+    /// List the subscriptions
+    List(list::List),
+    /// Prints detailed information about a given subscription, including the lag of its consumers
+    Describe(describe::Describe),
+    /// Create a subscription, ingesting the events of a source into a service handler
+    Create(create::Create),
+    /// Delete a subscription
+    Delete(delete::Delete),
+}
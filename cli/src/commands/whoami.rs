@@ -54,6 +54,10 @@ pub async fn run(State(env): State<CliEnv>) {
         table.add_row(vec!["Authentication Token", "(set)"]);
     }
 
+    if env.config.admin_bearer_token.is_some() {
+        table.add_row(vec!["Admin Authentication Token", "(set)"]);
+    }
+
     c_println!("{}", table);
 
     c_println!();
@@ -15,7 +15,7 @@ use comfy_table::{Cell, Color, Table};
 use restate_admin_rest_model::deployments::{Deployment, ServiceNameRevPair};
 use restate_cli_util::ui::console::StyledTable;
 use restate_types::identifiers::DeploymentId;
-use restate_types::schema::deployment::ProtocolType;
+use restate_types::schema::deployment::{LambdaInvokeMode, ProtocolType};
 use restate_types::schema::service::ServiceMetadata;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -100,6 +100,7 @@ pub fn add_deployment_to_kv_table(deployment: &Deployment, table: &mut Table) {
                 protocol_type,
                 http_version: _,
                 additional_headers,
+                http_connection,
                 created_at,
                 min_protocol_version,
                 max_protocol_version,
@@ -112,6 +113,15 @@ pub fn add_deployment_to_kv_table(deployment: &Deployment, table: &mut Table) {
                 table.add_kv_row("Protocol Style:", protocol_type);
 
                 table.add_kv_row("Endpoint:", uri);
+                if let Some(max_connections) = http_connection.max_connections {
+                    table.add_kv_row("Max Connections:", max_connections);
+                }
+                if let Some(max_concurrent_streams) = http_connection.max_concurrent_streams {
+                    table.add_kv_row("Max Concurrent Streams:", max_concurrent_streams);
+                }
+                if let Some(pool_idle_timeout) = http_connection.pool_idle_timeout {
+                    table.add_kv_row("Pool Idle Timeout:", pool_idle_timeout);
+                }
                 (
                     additional_headers.clone(),
                     created_at,
@@ -122,12 +132,20 @@ pub fn add_deployment_to_kv_table(deployment: &Deployment, table: &mut Table) {
             Deployment::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
                 additional_headers,
                 created_at,
                 min_protocol_version,
                 max_protocol_version,
             } => {
                 table.add_kv_row("Protocol Style:", "Request/Response");
+                table.add_kv_row(
+                    "Invoke Mode:",
+                    match invoke_mode {
+                        LambdaInvokeMode::Buffered => "Buffered",
+                        LambdaInvokeMode::ResponseStream => "Response Stream",
+                    },
+                );
                 table.add_kv_row_if(
                     || assume_role_arn.is_some(),
                     "Deployment Assume Role ARN:",
@@ -11,13 +11,16 @@
 use http::Uri;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::num::NonZeroUsize;
 use std::time::SystemTime;
 
 use restate_serde_util::{SerdeableHeaderHashMap, VersionSerde};
 use restate_types::identifiers::ServiceRevision;
 use restate_types::identifiers::{DeploymentId, LambdaARN};
 use restate_types::schema::deployment::DeploymentType;
-use restate_types::schema::deployment::{DeploymentMetadata, ProtocolType};
+use restate_types::schema::deployment::{
+    DeploymentMetadata, HttpConnectionOptions, LambdaInvokeMode, ProtocolType,
+};
 use restate_types::schema::service::ServiceMetadata;
 
 #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
@@ -36,6 +39,9 @@ pub enum Deployment {
         #[serde(skip_serializing_if = "SerdeableHeaderHashMap::is_empty")]
         #[serde(default)]
         additional_headers: SerdeableHeaderHashMap,
+        #[serde(skip_serializing_if = "HttpConnectionOptions::is_default")]
+        #[serde(default)]
+        http_connection: HttpConnectionOptions,
         #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
         #[cfg_attr(feature = "schema", schemars(with = "String"))]
         created_at: humantime::Timestamp,
@@ -47,6 +53,7 @@ pub enum Deployment {
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(default)]
         assume_role_arn: Option<String>,
+        invoke_mode: LambdaInvokeMode,
         #[serde(skip_serializing_if = "SerdeableHeaderHashMap::is_empty")]
         #[serde(default)]
         additional_headers: SerdeableHeaderHashMap,
@@ -72,6 +79,8 @@ enum DeploymentShadow {
         #[serde(skip_serializing_if = "SerdeableHeaderHashMap::is_empty")]
         #[serde(default)]
         additional_headers: SerdeableHeaderHashMap,
+        #[serde(default)]
+        http_connection: HttpConnectionOptions,
         #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
         created_at: humantime::Timestamp,
         min_protocol_version: i32,
@@ -82,6 +91,9 @@ enum DeploymentShadow {
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(default)]
         assume_role_arn: Option<String>,
+        // this field did not used to be provided; to provide backwards compatibility with old restate, we must consider it optional when deserialising
+        #[serde(default)]
+        invoke_mode: LambdaInvokeMode,
         #[serde(skip_serializing_if = "SerdeableHeaderHashMap::is_empty")]
         #[serde(default)]
         additional_headers: SerdeableHeaderHashMap,
@@ -100,6 +112,7 @@ impl From<DeploymentShadow> for Deployment {
                 protocol_type,
                 http_version,
                 additional_headers,
+                http_connection,
                 created_at,
                 min_protocol_version,
                 max_protocol_version,
@@ -109,6 +122,7 @@ impl From<DeploymentShadow> for Deployment {
                 http_version: http_version
                     .unwrap_or_else(|| DeploymentType::backfill_http_version(protocol_type)),
                 additional_headers,
+                http_connection,
                 created_at,
                 min_protocol_version,
                 max_protocol_version,
@@ -116,6 +130,7 @@ impl From<DeploymentShadow> for Deployment {
             DeploymentShadow::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
                 additional_headers,
                 created_at,
                 min_protocol_version,
@@ -123,6 +138,7 @@ impl From<DeploymentShadow> for Deployment {
             } => Self::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
                 additional_headers,
                 created_at,
                 min_protocol_version,
@@ -170,6 +186,7 @@ impl From<DeploymentMetadata> for Deployment {
                 protocol_type,
                 http_version,
                 additional_headers: value.delivery_options.additional_headers.into(),
+                http_connection: value.delivery_options.http_connection,
                 created_at: SystemTime::from(value.created_at).into(),
                 min_protocol_version: *value.supported_protocol_versions.start(),
                 max_protocol_version: *value.supported_protocol_versions.end(),
@@ -177,9 +194,11 @@ impl From<DeploymentMetadata> for Deployment {
             DeploymentType::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
             } => Self::Lambda {
                 arn,
                 assume_role_arn: assume_role_arn.map(Into::into),
+                invoke_mode,
                 additional_headers: value.delivery_options.additional_headers.into(),
                 created_at: SystemTime::from(value.created_at).into(),
                 min_protocol_version: *value.supported_protocol_versions.start(),
@@ -219,6 +238,30 @@ pub enum RegisterDeploymentRequest {
         #[serde(default = "restate_serde_util::default::bool::<false>")]
         use_http_11: bool,
 
+        /// # Max connections
+        ///
+        /// Maximum number of connections to open to the deployment. With HTTP2, the streams are
+        /// spread across the connections. With HTTP1.1, this caps the number of concurrent requests.
+        /// If unset, a single HTTP2 connection is shared by all the streams.
+        #[serde(default)]
+        max_connections: Option<NonZeroUsize>,
+
+        /// # Max concurrent streams
+        ///
+        /// Maximum number of concurrent HTTP2 streams per connection. Set it to the limit of the
+        /// load balancer in front of the deployment, if any, to avoid `REFUSED_STREAM` errors.
+        /// Cannot be set together with `use_http_11`.
+        #[serde(default)]
+        max_concurrent_streams: Option<NonZeroUsize>,
+
+        /// # Pool idle timeout
+        ///
+        /// How long an idle connection to the deployment is kept open.
+        #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        #[serde(default)]
+        pool_idle_timeout: Option<humantime::Duration>,
+
         /// # Force
         ///
         /// If `true`, it will override, if existing, any deployment using the same `uri`.
@@ -248,6 +291,16 @@ pub enum RegisterDeploymentRequest {
         /// Optional ARN of a role to assume when invoking the addressed Lambda, to support role chaining
         assume_role_arn: Option<String>,
 
+        /// # Invoke mode
+        ///
+        /// How the Lambda is invoked. With `ResponseStream`, the function must stream its response
+        /// with `awslambda.HttpResponseStream`. This raises the limit on the response size from 6 MB
+        /// to 20 MB, and lets the function send the journal entries while it is still running.
+        ///
+        /// Defaults to `Buffered`.
+        #[serde(default)]
+        invoke_mode: LambdaInvokeMode,
+
         /// # Additional headers
         ///
         /// Additional headers added to the discover/invoke requests to the deployment.
@@ -0,0 +1,90 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::num::NonZeroU32;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkInvocationAction {
+    /// Gracefully cancel the invocations.
+    Cancel,
+    /// Kill the invocations, without guaranteeing the consistency of the virtual object state.
+    Kill,
+    /// Purge the stored result of the completed invocations.
+    Purge,
+    /// Retry the invocations waiting for a retry now, skipping the remaining retry backoff.
+    /// The other selected invocations are skipped.
+    RetryNow,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkInvocationRequest {
+    /// # Filter
+    ///
+    /// SQL `WHERE` clause selecting the invocations from the `sys_invocation` table,
+    /// e.g. `target_service_name = 'Greeter' AND status = 'backing-off'`.
+    #[serde(rename = "where")]
+    pub filter: String,
+    /// # Action
+    ///
+    /// Action to apply to each selected invocation.
+    pub action: BulkInvocationAction,
+    /// # Max invocations per second
+    ///
+    /// Maximum number of commands sent to the partitions per second.
+    /// Defaults to 100.
+    pub max_invocations_per_second: Option<NonZeroU32>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkInvocationJobStatus {
+    /// The filter is being evaluated.
+    Querying,
+    /// The commands are being sent to the partitions.
+    Running,
+    Completed,
+    Failed,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkInvocationJobResponse {
+    pub id: String,
+    pub action: BulkInvocationAction,
+    pub status: BulkInvocationJobStatus,
+    /// # Matched
+    ///
+    /// Number of invocations selected by the filter.
+    pub matched: u64,
+    /// # Skipped
+    ///
+    /// Number of selected invocations to which the action doesn't apply,
+    /// e.g. invocations which are not backing off for `retry_now`.
+    #[serde(default)]
+    pub skipped: u64,
+    /// # Submitted
+    ///
+    /// Number of commands successfully sent to the partitions.
+    pub submitted: u64,
+    /// # Failed
+    ///
+    /// Number of commands which could not be sent.
+    pub failed: u64,
+    /// # Error
+    ///
+    /// Reason of the failure, if the job failed.
+    pub error: Option<String>,
+}
@@ -10,6 +10,7 @@
 
 pub mod deployments;
 pub mod handlers;
+pub mod invocations;
 pub mod services;
 pub mod subscriptions;
 pub mod version;
@@ -0,0 +1,129 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Authentication and role based access control for the Admin API.
+//!
+//! The Flight SQL storage query API authenticates the same bearer tokens through
+//! [`AdminAuthenticator`]. The psql service doesn't authenticate clients, so it refuses to start
+//! with admin authentication enabled unless it is bound to a loopback address.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use codederror::CodedError;
+use http::{header, Method, Request, StatusCode};
+
+use restate_types::config::AdminRole;
+use restate_types::jwt::{AdminAuthenticator, JwksError};
+
+#[derive(Debug, thiserror::Error, CodedError)]
+pub enum AuthenticationError {
+    #[error("invalid 'admin.authentication.jwks-file': {0}")]
+    #[code(unknown)]
+    Jwks(#[from] JwksError),
+}
+
+/// Returns the role required to perform the request, or `None` if the endpoint is public.
+fn required_role(method: &Method, path: &str) -> Option<AdminRole> {
+    let mut segments = path.trim_start_matches('/').split('/');
+    let first_segment = segments.next().unwrap_or_default();
+    let has_more_segments = segments.next().is_some();
+
+    match (method, first_segment) {
+        (_, "health" | "version") => None,
+        // The request identity public keys must be readable by the deployments
+        (&Method::GET | &Method::HEAD, ".well-known") => None,
+        (&Method::GET | &Method::HEAD, _) => Some(AdminRole::Viewer),
+        // Queries are read only
+        (&Method::POST, "query") => Some(AdminRole::Viewer),
+        (&Method::DELETE | &Method::POST, "invocations") => Some(AdminRole::Operator),
+        (&Method::POST | &Method::DELETE, "subscriptions") => Some(AdminRole::Operator),
+        (&Method::PATCH, "services") if has_more_segments => Some(AdminRole::Operator),
+        // Deployments registration/removal and service state modification
+        _ => Some(AdminRole::Admin),
+    }
+}
+
+/// Axum middleware checking that the bearer token grants the role required by the request.
+pub(crate) async fn authorize<B>(
+    State(authenticator): State<Arc<AdminAuthenticator>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(required_role) = required_role(req.method(), req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    let role = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| authenticator.authenticate(token.trim()));
+
+    match role {
+        None => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+        )
+            .into_response(),
+        Some(role) if role < required_role => (
+            StatusCode::FORBIDDEN,
+            format!("this operation requires the role '{required_role:?}'"),
+        )
+            .into_response(),
+        Some(_) => next.run(req).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_roles() {
+        assert_eq!(required_role(&Method::GET, "/health"), None);
+        assert_eq!(required_role(&Method::GET, "/.well-known/jwks.json"), None);
+        assert_eq!(
+            required_role(&Method::POST, "/.well-known/jwks.json"),
+            Some(AdminRole::Admin)
+        );
+        assert_eq!(
+            required_role(&Method::GET, "/deployments"),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/query"),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(
+            required_role(&Method::DELETE, "/invocations/inv_1"),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/invocations/bulk"),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(
+            required_role(&Method::PATCH, "/services/greeter"),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(
+            required_role(&Method::POST, "/services/greeter/state"),
+            Some(AdminRole::Admin)
+        );
+        assert_eq!(
+            required_role(&Method::DELETE, "/deployments/dp_1"),
+            Some(AdminRole::Admin)
+        );
+    }
+}
@@ -24,4 +24,10 @@ pub enum Error {
     #[error("error while running admin server: {0}")]
     #[code(unknown)]
     Running(hyper::Error),
+    #[error("failed loading the tls certificates specified in 'admin.tls': {0}")]
+    #[code(unknown)]
+    Tls(#[from] restate_core::network::tls::TlsError),
+    #[error(transparent)]
+    #[code(unknown)]
+    Authentication(#[from] crate::auth::AuthenticationError),
 }
@@ -0,0 +1,371 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Background jobs applying an action to all the invocations selected by a SQL filter.
+//!
+//! Jobs are tracked in memory by the admin node which started them, and are lost when the node
+//! restarts: an interrupted job is not resumed, and its progress can't be queried anymore.
+
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::error::FlightError;
+use arrow_flight::FlightData;
+use datafusion::arrow::array::AsArray;
+use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::{Parser, ParserError};
+use datafusion::sql::sqlparser::tokenizer::Token;
+use futures::TryStreamExt;
+use parking_lot::Mutex;
+use tokio::time::MissedTickBehavior;
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+use restate_admin_rest_model::invocations::{
+    BulkInvocationAction, BulkInvocationJobResponse, BulkInvocationJobStatus,
+};
+use restate_bifrost::Bifrost;
+use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
+use restate_core::network::protobuf::node_svc::StorageQueryRequest;
+use restate_core::{cancellation_watcher, TaskCenter, TaskKind};
+use restate_types::identifiers::{InvocationId, WithPartitionKey};
+use restate_types::invocation::{
+    InvocationTermination, PurgeInvocationRequest, RetryInvocationRequest,
+};
+use restate_wal_protocol::{append_envelope_to_bifrost, Command, Envelope};
+
+use super::create_envelope_header;
+
+pub(crate) const DEFAULT_MAX_INVOCATIONS_PER_SECOND: NonZeroU32 = match NonZeroU32::new(100) {
+    Some(v) => v,
+    None => unreachable!(),
+};
+/// Finished jobs are evicted once more than this number of jobs is tracked.
+const MAX_TRACKED_JOBS: usize = 100;
+
+#[derive(Debug, thiserror::Error)]
+enum BulkInvocationError {
+    #[error("cannot evaluate the filter: {0}")]
+    Query(#[from] tonic::Status),
+    #[error("cannot read the query results: {0}")]
+    Decode(#[from] FlightError),
+    #[error("the query results don't contain the '{0}' column")]
+    MissingColumn(&'static str),
+    #[error("bad invocation id '{0}' in the query results")]
+    BadInvocationId(String),
+}
+
+/// SQL filter over `sys_invocation`, validated to be a single expression so that it can't
+/// alter the query it is embedded in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InvocationFilter(String);
+
+impl InvocationFilter {
+    pub(crate) fn parse(filter: &str) -> Result<Self, ParserError> {
+        let mut parser = Parser::new(&GenericDialect {}).try_with_sql(filter)?;
+        let expr = parser.parse_expr()?;
+
+        let next_token = parser.peek_token();
+        if next_token.token != Token::EOF {
+            return Err(ParserError::ParserError(format!(
+                "expected a single expression, found '{}' at {}",
+                next_token.token, next_token.location
+            )));
+        }
+
+        // Render the parsed expression rather than embedding the user input
+        Ok(InvocationFilter(expr.to_string()))
+    }
+}
+
+type Job = Arc<Mutex<BulkInvocationJobResponse>>;
+
+/// Registry of the bulk invocation jobs started through this admin node.
+#[derive(Clone, Default)]
+pub struct BulkInvocationJobs {
+    jobs: Arc<Mutex<Vec<Job>>>,
+}
+
+impl BulkInvocationJobs {
+    pub(crate) fn get(&self, id: &str) -> Option<BulkInvocationJobResponse> {
+        self.jobs
+            .lock()
+            .iter()
+            .map(|job| job.lock())
+            .find(|job| job.id == id)
+            .map(|job| job.clone())
+    }
+
+    fn register(&self, action: BulkInvocationAction) -> Job {
+        let job = Arc::new(Mutex::new(BulkInvocationJobResponse {
+            id: ulid::Ulid::new().to_string(),
+            action,
+            status: BulkInvocationJobStatus::Querying,
+            matched: 0,
+            skipped: 0,
+            submitted: 0,
+            failed: 0,
+            error: None,
+        }));
+
+        let mut jobs = self.jobs.lock();
+        if jobs.len() >= MAX_TRACKED_JOBS {
+            if let Some(idx) = jobs.iter().position(|job| {
+                matches!(
+                    job.lock().status,
+                    BulkInvocationJobStatus::Completed | BulkInvocationJobStatus::Failed
+                )
+            }) {
+                jobs.remove(idx);
+            }
+        }
+        jobs.push(Arc::clone(&job));
+
+        job
+    }
+
+    /// Starts a job evaluating the filter over `sys_invocation`, then sending the command
+    /// matching the action for each selected invocation, at most `max_per_second` per second.
+    pub(crate) fn start(
+        &self,
+        task_center: &TaskCenter,
+        node_svc_client: NodeSvcClient<Channel>,
+        bifrost: Bifrost,
+        filter: InvocationFilter,
+        action: BulkInvocationAction,
+        max_per_second: NonZeroU32,
+    ) -> Result<BulkInvocationJobResponse, restate_core::ShutdownError> {
+        let job = self.register(action);
+        let response = job.lock().clone();
+
+        task_center.spawn(
+            TaskKind::Disposable,
+            "bulk-invocation-job",
+            None,
+            run_job(job, node_svc_client, bifrost, filter, max_per_second),
+        )?;
+
+        Ok(response)
+    }
+}
+
+async fn run_job(
+    job: Job,
+    node_svc_client: NodeSvcClient<Channel>,
+    mut bifrost: Bifrost,
+    filter: InvocationFilter,
+    max_per_second: NonZeroU32,
+) -> anyhow::Result<()> {
+    let action = job.lock().action;
+    let (invocation_ids, skipped) =
+        match query_invocation_ids(node_svc_client, &filter, action).await {
+            Ok(selected) => selected,
+            Err(err) => {
+                let mut progress = job.lock();
+                progress.status = BulkInvocationJobStatus::Failed;
+                progress.error = Some(err.to_string());
+                return Ok(());
+            }
+        };
+
+    {
+        let mut progress = job.lock();
+        progress.status = BulkInvocationJobStatus::Running;
+        progress.matched = invocation_ids.len() as u64 + skipped;
+        progress.skipped = skipped;
+    }
+    debug!(
+        "Bulk invocation job is sending {:?} to {} invocations",
+        action,
+        invocation_ids.len()
+    );
+
+    let mut ticker = tokio::time::interval(Duration::from_secs(1) / max_per_second.get());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut shutdown = std::pin::pin!(cancellation_watcher());
+
+    for invocation_id in invocation_ids {
+        tokio::select! {
+            _ = ticker.tick() => {},
+            _ = &mut shutdown => {
+                let mut progress = job.lock();
+                progress.status = BulkInvocationJobStatus::Failed;
+                progress.error = Some("interrupted by the node shutdown".to_owned());
+                return Ok(());
+            }
+        }
+
+        let envelope = Envelope::new(
+            create_envelope_header(invocation_id.partition_key()),
+            command(action, invocation_id),
+        );
+        let result = append_envelope_to_bifrost(&mut bifrost, envelope).await;
+
+        let mut progress = job.lock();
+        match result {
+            Ok(_) => progress.submitted += 1,
+            Err(err) => {
+                warn!("Could not append the {action:?} command for invocation '{invocation_id}' to Bifrost: {err}");
+                progress.failed += 1;
+            }
+        }
+    }
+
+    job.lock().status = BulkInvocationJobStatus::Completed;
+    Ok(())
+}
+
+fn command(action: BulkInvocationAction, invocation_id: InvocationId) -> Command {
+    match action {
+        BulkInvocationAction::Cancel => {
+            Command::TerminateInvocation(InvocationTermination::cancel(invocation_id))
+        }
+        BulkInvocationAction::Kill => {
+            Command::TerminateInvocation(InvocationTermination::kill(invocation_id))
+        }
+        BulkInvocationAction::Purge => {
+            Command::PurgeInvocation(PurgeInvocationRequest { invocation_id })
+        }
+        BulkInvocationAction::RetryNow => {
+            Command::RetryInvocation(RetryInvocationRequest { invocation_id })
+        }
+    }
+}
+
+/// Whether the action applies to an invocation with the given `sys_invocation` status.
+///
+/// This is only a pre-selection: the partitions check again when applying the command, e.g. an
+/// invocation which is not waiting for a retry anymore is left untouched by `retry_now`.
+fn applies_to(action: BulkInvocationAction, status: Option<&str>) -> bool {
+    match action {
+        BulkInvocationAction::RetryNow => status == Some("backing-off"),
+        BulkInvocationAction::Cancel | BulkInvocationAction::Kill | BulkInvocationAction::Purge => {
+            true
+        }
+    }
+}
+
+/// Returns the invocations selected by the filter to which the action applies, and the number
+/// of the skipped ones.
+async fn query_invocation_ids(
+    mut node_svc_client: NodeSvcClient<Channel>,
+    filter: &InvocationFilter,
+    action: BulkInvocationAction,
+) -> Result<(Vec<InvocationId>, u64), BulkInvocationError> {
+    let response_stream = node_svc_client
+        .query_storage(StorageQueryRequest {
+            query: format!("SELECT id, status FROM sys_invocation WHERE ({})", filter.0),
+        })
+        .await?
+        .into_inner();
+
+    let mut record_batches = FlightRecordBatchStream::new_from_flight_data(
+        response_stream
+            .map_ok(|response| FlightData {
+                data_header: response.header,
+                data_body: response.data,
+                ..FlightData::default()
+            })
+            .map_err(FlightError::from),
+    );
+
+    let mut invocation_ids = Vec::new();
+    let mut skipped = 0;
+    while let Some(record_batch) = record_batches.try_next().await? {
+        let ids = string_column(&record_batch, "id")?;
+        let statuses = string_column(&record_batch, "status")?;
+        for (id, status) in ids.zip(statuses) {
+            let Some(id) = id else {
+                continue;
+            };
+            if !applies_to(action, status) {
+                skipped += 1;
+                continue;
+            }
+            invocation_ids.push(
+                id.parse()
+                    .map_err(|_| BulkInvocationError::BadInvocationId(id.to_owned()))?,
+            );
+        }
+    }
+
+    Ok((invocation_ids, skipped))
+}
+
+fn string_column<'a>(
+    record_batch: &'a RecordBatch,
+    name: &'static str,
+) -> Result<Box<dyn Iterator<Item = Option<&'a str>> + 'a>, BulkInvocationError> {
+    let column = record_batch
+        .column_by_name(name)
+        .ok_or(BulkInvocationError::MissingColumn(name))?;
+    match column.data_type() {
+        DataType::LargeUtf8 => Ok(Box::new(column.as_string::<i64>().iter())),
+        DataType::Utf8 => Ok(Box::new(column.as_string::<i32>().iter())),
+        _ => Err(BulkInvocationError::MissingColumn(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finished_jobs_are_evicted() {
+        let jobs = BulkInvocationJobs::default();
+        let first = jobs.register(BulkInvocationAction::Cancel);
+        let first_id = first.lock().id.clone();
+        assert!(jobs.get(&first_id).is_some());
+
+        // Running jobs are never evicted
+        for _ in 1..MAX_TRACKED_JOBS {
+            jobs.register(BulkInvocationAction::Kill);
+        }
+        jobs.register(BulkInvocationAction::Purge);
+        assert!(jobs.get(&first_id).is_some());
+
+        first.lock().status = BulkInvocationJobStatus::Completed;
+        jobs.register(BulkInvocationAction::RetryNow);
+        assert!(jobs.get(&first_id).is_none());
+    }
+
+    #[test]
+    fn filter_is_a_single_expression() {
+        let filter = InvocationFilter::parse(
+            "target_service_name = 'Greeter' AND (status = 'backing-off' OR retry_count > 3)",
+        )
+        .unwrap();
+        assert_eq!(
+            filter.0,
+            "target_service_name = 'Greeter' AND (status = 'backing-off' OR retry_count > 3)"
+        );
+
+        assert!(InvocationFilter::parse("status = 'x') OR (1 = 1").is_err());
+        assert!(InvocationFilter::parse("true; DROP TABLE sys_invocation").is_err());
+        assert!(InvocationFilter::parse("true UNION SELECT id FROM sys_journal").is_err());
+        assert!(InvocationFilter::parse("status = 'unterminated").is_err());
+        assert!(InvocationFilter::parse("").is_err());
+    }
+
+    #[test]
+    fn retry_now_only_applies_to_backing_off_invocations() {
+        assert!(applies_to(
+            BulkInvocationAction::RetryNow,
+            Some("backing-off")
+        ));
+        assert!(!applies_to(BulkInvocationAction::RetryNow, Some("running")));
+        assert!(!applies_to(BulkInvocationAction::RetryNow, None));
+        assert!(applies_to(BulkInvocationAction::Kill, Some("running")));
+    }
+}
@@ -22,6 +22,7 @@ use restate_admin_rest_model::deployments::*;
 use restate_service_client::Endpoint;
 use restate_service_protocol::discovery::DiscoverEndpoint;
 use restate_types::identifiers::{DeploymentId, InvalidLambdaARN};
+use restate_types::schema::deployment::HttpConnectionOptions;
 use serde::Deserialize;
 
 /// Create deployment and return discovered services.
@@ -49,26 +50,43 @@ pub async fn create_deployment<V>(
             uri,
             additional_headers,
             use_http_11,
+            max_connections,
+            max_concurrent_streams,
+            pool_idle_timeout,
             force,
             dry_run,
-        } => (
-            DiscoverEndpoint::new(
-                Endpoint::Http(
-                    uri,
-                    if use_http_11 {
-                        http::Version::HTTP_11
-                    } else {
-                        http::Version::HTTP_2
-                    },
+        } => {
+            if use_http_11 && max_concurrent_streams.is_some() {
+                return Err(MetaApiError::InvalidField(
+                    "max_concurrent_streams",
+                    "HTTP/1.1 connections serve one request at a time, use max_connections to cap the concurrent requests".to_owned(),
+                ));
+            }
+            (
+                DiscoverEndpoint::new(
+                    Endpoint::Http(
+                        uri,
+                        if use_http_11 {
+                            http::Version::HTTP_11
+                        } else {
+                            http::Version::HTTP_2
+                        },
+                        HttpConnectionOptions {
+                            max_connections,
+                            max_concurrent_streams,
+                            pool_idle_timeout,
+                        },
+                    ),
+                    additional_headers.unwrap_or_default().into(),
                 ),
-                additional_headers.unwrap_or_default().into(),
-            ),
-            force,
-            dry_run,
-        ),
+                force,
+                dry_run,
+            )
+        }
         RegisterDeploymentRequest::Lambda {
             arn,
             assume_role_arn,
+            invoke_mode,
             additional_headers,
             force,
             dry_run,
@@ -79,6 +97,7 @@ pub async fn create_deployment<V>(
                         MetaApiError::InvalidField("arn", e.to_string())
                     })?,
                     assume_role_arn.map(Into::into),
+                    invoke_mode,
                 ),
                 additional_headers.unwrap_or_default().into(),
             ),
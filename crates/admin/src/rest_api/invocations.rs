@@ -10,11 +10,14 @@
 
 use super::error::*;
 
+use crate::rest_api::bulk_invocations::{InvocationFilter, DEFAULT_MAX_INVOCATIONS_PER_SECOND};
 use crate::rest_api::create_envelope_header;
 use crate::state::AdminServiceState;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
+use axum::Json;
 use okapi_operation::*;
+use restate_admin_rest_model::invocations::{BulkInvocationJobResponse, BulkInvocationRequest};
 use restate_types::identifiers::{InvocationId, WithPartitionKey};
 use restate_types::invocation::{InvocationTermination, PurgeInvocationRequest};
 use restate_wal_protocol::{append_envelope_to_bifrost, Command, Envelope};
@@ -114,3 +117,76 @@ pub async fn delete_invocation<V>(
         Ok(StatusCode::ACCEPTED)
     }
 }
+
+/// Apply an action to the invocations matching a filter
+#[openapi(
+    summary = "Bulk invocation management",
+    description = "Start a job applying the given action to all the invocations selected by the SQL \
+    filter over the sys_invocation table. The commands are sent to the partitions in the background, \
+    at the given maximum rate. The progress of the job can be followed with the returned job id. \
+    Jobs are tracked in memory by the admin node: they are not resumed after a restart of the node.",
+    operation_id = "create_bulk_invocation_job",
+    tags = "invocation",
+    responses(
+        ignore_return_type = true,
+        response(
+            status = "202",
+            description = "Accepted",
+            content = "Json<BulkInvocationJobResponse>",
+        ),
+        from_type = "MetaApiError",
+    )
+)]
+pub async fn create_bulk_invocation_job<V>(
+    State(state): State<AdminServiceState<V>>,
+    #[request_body(required = true)] Json(payload): Json<BulkInvocationRequest>,
+) -> Result<impl axum::response::IntoResponse, MetaApiError> {
+    let filter = InvocationFilter::parse(&payload.filter)
+        .map_err(|e| MetaApiError::InvalidField("where", e.to_string()))?;
+
+    let job = state
+        .bulk_invocation_jobs
+        .start(
+            &state.task_center,
+            state.node_svc_client.clone(),
+            state.bifrost.clone(),
+            filter,
+            payload.action,
+            payload
+                .max_invocations_per_second
+                .unwrap_or(DEFAULT_MAX_INVOCATIONS_PER_SECOND),
+        )
+        .map_err(|e| MetaApiError::Internal(e.to_string()))?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(
+            http::header::LOCATION,
+            format!("/invocations/bulk/{}", job.id),
+        )],
+        Json(job),
+    ))
+}
+
+/// Get the progress of a bulk invocation job
+#[openapi(
+    summary = "Get bulk invocation job",
+    description = "Get the progress of a bulk invocation job.",
+    operation_id = "get_bulk_invocation_job",
+    tags = "invocation",
+    parameters(path(
+        name = "job_id",
+        description = "Bulk invocation job identifier.",
+        schema = "std::string::String"
+    ))
+)]
+pub async fn get_bulk_invocation_job<V>(
+    State(state): State<AdminServiceState<V>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<BulkInvocationJobResponse>, MetaApiError> {
+    state
+        .bulk_invocation_jobs
+        .get(&job_id)
+        .map(Json)
+        .ok_or(MetaApiError::BulkInvocationJobNotFound(job_id))
+}
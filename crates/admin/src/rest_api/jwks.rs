@@ -0,0 +1,21 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use axum::extract::State;
+use axum::Json;
+use jsonwebtoken::jwk::JwkSet;
+
+use restate_service_client::RequestIdentityKeys;
+
+/// Returns the public keys deployments can use to verify the identity of the requests sent by
+/// Restate.
+pub async fn get_jwks(State(request_identity_keys): State<RequestIdentityKeys>) -> Json<JwkSet> {
+    Json(request_identity_keys.jwks())
+}
@@ -10,11 +10,13 @@
 
 //! This module implements the Meta API endpoint.
 
+mod bulk_invocations;
 mod deployments;
 mod error;
 mod handlers;
 mod health;
 mod invocations;
+mod jwks;
 mod services;
 mod subscriptions;
 mod version;
@@ -23,12 +25,15 @@ use codederror::CodedError;
 use okapi_operation::axum_integration::{delete, get, patch, post};
 use okapi_operation::*;
 use restate_errors::warn_it;
+use restate_service_client::RequestIdentityKeys;
 use restate_types::identifiers::PartitionKey;
 use restate_types::schema::subscriptions::SubscriptionValidator;
 use restate_wal_protocol::{Destination, Header, Source};
 
 use crate::state::AdminServiceState;
 
+pub(crate) use bulk_invocations::BulkInvocationJobs;
+
 pub fn create_router<V>(state: AdminServiceState<V>) -> axum::Router<()>
 where
     V: SubscriptionValidator + Send + Sync + Clone + 'static,
@@ -76,6 +81,14 @@ where
             "/invocations/:invocation_id",
             delete(openapi_handler!(invocations::delete_invocation)),
         )
+        .route(
+            "/invocations/bulk",
+            post(openapi_handler!(invocations::create_bulk_invocation_job)),
+        )
+        .route(
+            "/invocations/bulk/:job_id",
+            get(openapi_handler!(invocations::get_bulk_invocation_job)),
+        )
         .route(
             "/subscriptions",
             post(openapi_handler!(subscriptions::create_subscription)),
@@ -102,6 +115,14 @@ where
         .with_state(state)
 }
 
+/// Publishes the public keys of the request identity keys at `/.well-known/jwks.json`. The route
+/// is not part of the OpenAPI specification, as its format is defined by RFC 7517.
+pub fn create_jwks_router(request_identity_keys: RequestIdentityKeys) -> axum::Router<()> {
+    axum::Router::new()
+        .route("/.well-known/jwks.json", axum::routing::get(jwks::get_jwks))
+        .with_state(request_identity_keys)
+}
+
 fn create_envelope_header(partition_key: PartitionKey) -> Header {
     Header {
         source: Source::ControlPlane {},
@@ -97,6 +97,9 @@ pub enum ServiceError {
     #[error("modifying retention time for service type {0} is unsupported")]
     #[code(unknown)]
     CannotModifyRetentionTime(ServiceType),
+    #[error("the service '{0}' declares eager state keys, but its eager state mode is not KEYS")]
+    #[code(unknown)]
+    UnexpectedEagerStateKeys(ServiceName),
 }
 
 #[derive(Debug, thiserror::Error, codederror::CodedError)]
@@ -106,19 +106,24 @@ impl<V> SchemaRegistry<V> {
         let discovered_metadata = self.service_discovery.discover(&discover_endpoint).await?;
 
         let deployment_metadata = match discover_endpoint.into_inner() {
-            (Endpoint::Http(uri, http_version), headers) => DeploymentMetadata::new_http(
-                uri.clone(),
-                discovered_metadata.protocol_type,
-                http_version,
-                DeliveryOptions::new(headers),
-                discovered_metadata.supported_protocol_versions,
-            ),
-            (Endpoint::Lambda(arn, assume_role_arn), headers) => DeploymentMetadata::new_lambda(
-                arn,
-                assume_role_arn,
-                DeliveryOptions::new(headers),
-                discovered_metadata.supported_protocol_versions,
-            ),
+            (Endpoint::Http(uri, http_version, http_connection), headers) => {
+                DeploymentMetadata::new_http(
+                    uri.clone(),
+                    discovered_metadata.protocol_type,
+                    http_version,
+                    DeliveryOptions::new(headers).with_http_connection(http_connection),
+                    discovered_metadata.supported_protocol_versions,
+                )
+            }
+            (Endpoint::Lambda(arn, assume_role_arn, invoke_mode), headers) => {
+                DeploymentMetadata::new_lambda(
+                    arn,
+                    assume_role_arn,
+                    invoke_mode,
+                    DeliveryOptions::new(headers),
+                    discovered_metadata.supported_protocol_versions,
+                )
+            }
         };
 
         let (id, services) = if !apply_mode.should_apply() {
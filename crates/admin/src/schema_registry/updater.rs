@@ -24,7 +24,9 @@ use restate_types::schema::invocation_target::{
     InputRules, InputValidationRule, InvocationTargetMetadata, OutputContentTypeRule, OutputRules,
     DEFAULT_IDEMPOTENCY_RETENTION, DEFAULT_WORKFLOW_COMPLETION_RETENTION,
 };
-use restate_types::schema::service::{HandlerSchemas, ServiceLocation, ServiceSchemas};
+use restate_types::schema::service::{
+    EagerStatePolicy, HandlerSchemas, ServiceLocation, ServiceSchemas,
+};
 use restate_types::schema::subscriptions::{
     EventReceiverServiceType, Sink, Source, Subscription, SubscriptionValidator,
 };
@@ -135,6 +137,11 @@ impl SchemaUpdater {
                     .map(|h| DiscoveredHandlerMetadata::from_schema(service_type, h))
                     .collect::<Result<Vec<_>, _>>()?,
             );
+            let eager_state = eager_state_policy_from_schema(
+                &service_name,
+                service.eager_state,
+                service.eager_state_keys,
+            )?;
 
             // For the time being when updating we overwrite existing data
             let service_schema = if let Some(existing_service) =
@@ -189,6 +196,7 @@ impl SchemaUpdater {
                 service_schemas.revision = existing_service.revision.wrapping_add(1);
                 service_schemas.ty = service_type;
                 service_schemas.handlers = handlers;
+                service_schemas.eager_state = eager_state;
                 service_schemas.location.latest_deployment = deployment_id;
 
                 service_schemas
@@ -207,6 +215,7 @@ impl SchemaUpdater {
                     } else {
                         None
                     },
+                    eager_state,
                 }
             };
 
@@ -447,6 +456,23 @@ struct DiscoveredHandlerMetadata {
     output: OutputRules,
 }
 
+fn eager_state_policy_from_schema(
+    service_name: &ServiceName,
+    eager_state: Option<endpoint_manifest::EagerStateMode>,
+    eager_state_keys: Vec<String>,
+) -> Result<EagerStatePolicy, ServiceError> {
+    match eager_state {
+        Some(endpoint_manifest::EagerStateMode::Keys) => {
+            Ok(EagerStatePolicy::Keys(eager_state_keys))
+        }
+        _ if !eager_state_keys.is_empty() => {
+            Err(ServiceError::UnexpectedEagerStateKeys(service_name.clone()))
+        }
+        None | Some(endpoint_manifest::EagerStateMode::Full) => Ok(EagerStatePolicy::Full),
+        Some(endpoint_manifest::EagerStateMode::None) => Ok(EagerStatePolicy::None),
+    }
+}
+
 impl DiscoveredHandlerMetadata {
     fn from_schema(
         service_type: ServiceType,
@@ -594,6 +620,8 @@ mod tests {
         endpoint_manifest::Service {
             ty: endpoint_manifest::ServiceType::Service,
             name: GREETER_SERVICE_NAME.parse().unwrap(),
+            eager_state: None,
+            eager_state_keys: vec![],
             handlers: vec![endpoint_manifest::Handler {
                 name: "greet".parse().unwrap(),
                 ty: None,
@@ -607,6 +635,8 @@ mod tests {
         endpoint_manifest::Service {
             ty: endpoint_manifest::ServiceType::VirtualObject,
             name: GREETER_SERVICE_NAME.parse().unwrap(),
+            eager_state: None,
+            eager_state_keys: vec![],
             handlers: vec![endpoint_manifest::Handler {
                 name: "greet".parse().unwrap(),
                 ty: None,
@@ -620,6 +650,8 @@ mod tests {
         endpoint_manifest::Service {
             ty: endpoint_manifest::ServiceType::Service,
             name: ANOTHER_GREETER_SERVICE_NAME.parse().unwrap(),
+            eager_state: None,
+            eager_state_keys: vec![],
             handlers: vec![endpoint_manifest::Handler {
                 name: "another_greeter".parse().unwrap(),
                 ty: None,
@@ -656,6 +688,45 @@ mod tests {
         schema.assert_service_handler(GREETER_SERVICE_NAME, "greet");
     }
 
+    #[test]
+    fn register_eager_state_keys() {
+        let mut updater = SchemaUpdater::default();
+
+        let deployment = Deployment::mock();
+        let mut service = greeter_virtual_object();
+        service.eager_state = Some(endpoint_manifest::EagerStateMode::Keys);
+        service.eager_state_keys = vec!["counter".to_owned()];
+        updater
+            .add_deployment(
+                Some(deployment.id),
+                deployment.metadata.clone(),
+                vec![service],
+                false,
+            )
+            .unwrap();
+
+        let schemas = updater.into_inner();
+        assert_eq!(
+            schemas.resolve_latest_service_eager_state(GREETER_SERVICE_NAME),
+            Some(EagerStatePolicy::Keys(vec!["counter".to_owned()]))
+        );
+
+        // Keys are accepted only together with the KEYS mode
+        updater = schemas.into();
+        let mut service = greeter_virtual_object();
+        service.eager_state = Some(endpoint_manifest::EagerStateMode::None);
+        service.eager_state_keys = vec!["counter".to_owned()];
+        let rejection = updater
+            .add_deployment(
+                Some(deployment.id),
+                deployment.metadata,
+                vec![service],
+                true,
+            )
+            .unwrap_err();
+        let_assert!(SchemaError::Service(ServiceError::UnexpectedEagerStateKeys(_)) = rejection);
+    }
+
     #[test]
     fn register_new_deployment_add_unregistered_service() {
         let mut updater = SchemaUpdater::default();
@@ -923,6 +994,8 @@ mod tests {
             endpoint_manifest::Service {
                 ty: endpoint_manifest::ServiceType::Service,
                 name: GREETER_SERVICE_NAME.parse().unwrap(),
+                eager_state: None,
+                eager_state_keys: vec![],
                 handlers: vec![
                     endpoint_manifest::Handler {
                         name: "greet".parse().unwrap(),
@@ -944,6 +1017,8 @@ mod tests {
             endpoint_manifest::Service {
                 ty: endpoint_manifest::ServiceType::Service,
                 name: GREETER_SERVICE_NAME.parse().unwrap(),
+                eager_state: None,
+                eager_state_keys: vec![],
                 handlers: vec![endpoint_manifest::Handler {
                     name: "greet".parse().unwrap(),
                     ty: None,
@@ -12,22 +12,28 @@ use std::sync::Arc;
 
 use axum::error_handling::HandleErrorLayer;
 use http::StatusCode;
+use hyper::server::accept::Accept;
+use hyper::server::conn::AddrIncoming;
 use restate_bifrost::Bifrost;
 use restate_types::config::AdminOptions;
+use restate_types::jwt::AdminAuthenticator;
 use restate_types::live::LiveLoad;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tonic::transport::Channel;
 use tower::ServiceBuilder;
 use tracing::info;
 
 use restate_core::metadata_store::MetadataStoreClient;
 use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
+use restate_core::network::tls::{accept_tls, ReloadableTlsConfig};
 use restate_core::{cancellation_watcher, task_center, MetadataWriter};
+use restate_service_client::RequestIdentityKeys;
 use restate_service_protocol::discovery::ServiceDiscovery;
 use restate_types::schema::subscriptions::SubscriptionValidator;
 
 use crate::schema_registry::SchemaRegistry;
 use crate::Error;
-use crate::{rest_api, state, storage_query};
+use crate::{auth, rest_api, state, storage_query};
 
 #[derive(Debug, thiserror::Error)]
 #[error("could not create the service client: {0}")]
@@ -35,6 +41,7 @@ pub struct BuildError(#[from] restate_service_client::BuildError);
 
 pub struct AdminService<V> {
     schema_registry: SchemaRegistry<V>,
+    request_identity_keys: RequestIdentityKeys,
 }
 
 impl<V> AdminService<V>
@@ -46,6 +53,7 @@ where
         metadata_store_client: MetadataStoreClient,
         subscription_validator: V,
         service_discovery: ServiceDiscovery,
+        request_identity_keys: RequestIdentityKeys,
     ) -> Self {
         Self {
             schema_registry: SchemaRegistry::new(
@@ -54,6 +62,7 @@ where
                 service_discovery,
                 subscription_validator,
             ),
+            request_identity_keys,
         }
     }
 
@@ -65,44 +74,79 @@ where
     ) -> anyhow::Result<()> {
         let opts = updateable_config.live_load();
 
-        let rest_state =
-            state::AdminServiceState::new(self.schema_registry, bifrost, task_center());
+        let rest_state = state::AdminServiceState::new(
+            self.schema_registry,
+            bifrost,
+            task_center(),
+            node_svc_client.clone(),
+        );
 
         let query_state = Arc::new(state::QueryServiceState { node_svc_client });
         let router = axum::Router::new().merge(storage_query::create_router(query_state));
 
-        let router = router
+        let mut router = router
             // Merge meta API router
             .merge(rest_api::create_router(rest_state))
-            .layer(
-                ServiceBuilder::new()
-                    .layer(HandleErrorLayer::new(|_| async {
-                        StatusCode::TOO_MANY_REQUESTS
-                    }))
-                    .layer(tower::load_shed::LoadShedLayer::new())
-                    .layer(tower::limit::GlobalConcurrencyLimitLayer::new(
-                        opts.concurrent_api_requests_limit(),
-                    )),
+            .merge(rest_api::create_jwks_router(self.request_identity_keys));
+
+        if let Some(authentication) = &opts.authentication {
+            let authenticator = Arc::new(
+                AdminAuthenticator::from_options(authentication)
+                    .map_err(|err| Error::Authentication(err.into()))?,
             );
+            router = router.layer(axum::middleware::from_fn_with_state(
+                authenticator,
+                auth::authorize,
+            ));
+        }
+
+        let router = router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(|_| async {
+                    StatusCode::TOO_MANY_REQUESTS
+                }))
+                .layer(tower::load_shed::LoadShedLayer::new())
+                .layer(tower::limit::GlobalConcurrencyLimitLayer::new(
+                    opts.concurrent_api_requests_limit(),
+                )),
+        );
+
+        let tls =
+            ReloadableTlsConfig::start_server(opts.tls.as_ref(), "admin-tls-reload", |config| {
+                config.admin.tls.as_ref()
+            })
+            .map_err(Error::Tls)?;
 
         // Bind and serve
-        let server = hyper::Server::try_bind(&opts.bind_address)
-            .map_err(|err| Error::Binding {
-                address: opts.bind_address,
-                source: err,
-            })?
-            .serve(router.into_make_service());
+        let incoming = AddrIncoming::bind(&opts.bind_address).map_err(|err| Error::Binding {
+            address: opts.bind_address,
+            source: err,
+        })?;
 
         info!(
-            net.host.addr = %server.local_addr().ip(),
-            net.host.port = %server.local_addr().port(),
+            net.host.addr = %incoming.local_addr().ip(),
+            net.host.port = %incoming.local_addr().port(),
+            tls = tls.is_some(),
             "Admin API listening"
         );
 
         // Wait server graceful shutdown
-        Ok(server
-            .with_graceful_shutdown(cancellation_watcher())
-            .await
-            .map_err(Error::Running)?)
+        match tls {
+            Some(tls) => serve(accept_tls(incoming, tls), router).await,
+            None => serve(incoming, router).await,
+        }
     }
 }
+
+async fn serve<A>(incoming: A, router: axum::Router) -> anyhow::Result<()>
+where
+    A: Accept,
+    A::Conn: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    A::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Ok(hyper::Server::builder(incoming)
+        .serve(router.into_make_service())
+        .with_graceful_shutdown(cancellation_watcher())
+        .await
+        .map_err(Error::Running)?)
+}
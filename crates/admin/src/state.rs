@@ -9,6 +9,7 @@
 // by the Apache License, Version 2.0.
 //
 
+use crate::rest_api::BulkInvocationJobs;
 use crate::schema_registry::SchemaRegistry;
 use restate_bifrost::Bifrost;
 use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
@@ -20,6 +21,8 @@ pub struct AdminServiceState<V> {
     pub schema_registry: SchemaRegistry<V>,
     pub bifrost: Bifrost,
     pub task_center: TaskCenter,
+    pub node_svc_client: NodeSvcClient<Channel>,
+    pub bulk_invocation_jobs: BulkInvocationJobs,
 }
 
 #[derive(Clone)]
@@ -32,11 +35,14 @@ impl<V> AdminServiceState<V> {
         schema_registry: SchemaRegistry<V>,
         bifrost: Bifrost,
         task_center: TaskCenter,
+        node_svc_client: NodeSvcClient<Channel>,
     ) -> Self {
         Self {
             schema_registry,
             bifrost,
             task_center,
+            node_svc_client,
+            bulk_invocation_jobs: BulkInvocationJobs::default(),
         }
     }
 }
@@ -25,6 +25,8 @@ use serde::Serialize;
 pub enum StorageQueryError {
     #[error("failed grpc: {0}")]
     Tonic(#[from] tonic::Status),
+    #[error("invalid resume position '{0}', expected '<partition_id>:<lsn>'")]
+    InvalidResumePosition(String),
 }
 
 /// # Error description response
@@ -37,7 +39,10 @@ struct ErrorDescriptionResponse {
 
 impl IntoResponse for StorageQueryError {
     fn into_response(self) -> Response {
-        let status_code = StatusCode::INTERNAL_SERVER_ERROR;
+        let status_code = match &self {
+            StorageQueryError::Tonic(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            StorageQueryError::InvalidResumePosition(_) => StatusCode::BAD_REQUEST,
+        };
 
         (
             status_code,
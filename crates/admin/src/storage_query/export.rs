@@ -0,0 +1,322 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::error::FlightError;
+use axum::body::StreamBody;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{http, Json};
+use bytes::Bytes;
+use datafusion::arrow::csv;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::json::LineDelimitedWriter;
+use datafusion::arrow::record_batch::RecordBatch;
+use futures::{stream, Stream, StreamExt};
+use okapi_operation::*;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_with::serde_as;
+
+use super::error::StorageQueryError;
+use super::query::query_record_batches;
+use crate::state::QueryServiceState;
+
+/// # Export format
+///
+/// File format of the exported query results
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Apache Parquet file, compressed with zstd
+    Parquet,
+    /// Comma separated values, with a header row
+    Csv,
+    /// Newline delimited JSON, one object per row
+    Ndjson,
+}
+
+impl ExportFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExportRequest {
+    /// # Query
+    ///
+    /// SQL query whose results are exported
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[schemars(with = "String")]
+    pub query: String,
+
+    /// # Format
+    ///
+    /// File format of the export
+    pub format: ExportFormat,
+}
+
+/// Export query results
+#[openapi(
+    summary = "Export query results",
+    description = "Run a query against the storage API and stream its results as a Parquet, CSV or NDJSON file",
+    operation_id = "export_query",
+    tags = "storage",
+    responses(ignore_return_type = true, from_type = "StorageQueryError")
+)]
+pub async fn export(
+    State(state): State<Arc<QueryServiceState>>,
+    #[request_body(required = true)] Json(payload): Json<ExportRequest>,
+) -> Result<impl IntoResponse, StorageQueryError> {
+    let record_batch_stream = query_record_batches(&state, payload.query).await?;
+
+    let body = StreamBody::new(encode_record_batches(payload.format, record_batch_stream));
+    Ok((
+        [(http::header::CONTENT_TYPE, payload.format.content_type())],
+        body,
+    ))
+}
+
+/// Encodes the record batches in the requested format, yielding the encoded bytes as soon as
+/// the underlying writer produces them.
+fn encode_record_batches(
+    format: ExportFormat,
+    record_batch_stream: FlightRecordBatchStream,
+) -> impl Stream<Item = Result<Bytes, FlightError>> {
+    let buffer = SharedBuffer::default();
+
+    stream::unfold(
+        Some((record_batch_stream, None::<RecordBatchEncoder>)),
+        move |state| {
+            let buffer = buffer.clone();
+            async move {
+                let (mut record_batch_stream, mut encoder) = state?;
+
+                match record_batch_stream.next().await {
+                    Some(Ok(record_batch)) => {
+                        let result = match encoder.take() {
+                            Some(encoder) => Ok(encoder),
+                            None => RecordBatchEncoder::try_new(
+                                format,
+                                record_batch.schema(),
+                                buffer.clone(),
+                            ),
+                        }
+                        .and_then(|mut next_encoder| {
+                            next_encoder.write(&record_batch)?;
+                            encoder = Some(next_encoder);
+                            Ok(())
+                        });
+
+                        match result {
+                            Ok(()) => {
+                                Some((Ok(buffer.take()), Some((record_batch_stream, encoder))))
+                            }
+                            Err(err) => Some((Err(err.into()), None)),
+                        }
+                    }
+                    Some(Err(err)) => Some((Err(err), None)),
+                    None => {
+                        // an empty result still produces a file with the schema, if we know it
+                        let encoder = match encoder {
+                            Some(encoder) => Some(Ok(encoder)),
+                            None => record_batch_stream.schema().map(|schema| {
+                                RecordBatchEncoder::try_new(format, schema.clone(), buffer.clone())
+                            }),
+                        };
+
+                        match encoder.transpose().and_then(|encoder| {
+                            encoder.map(RecordBatchEncoder::finish).unwrap_or(Ok(()))
+                        }) {
+                            Ok(()) => Some((Ok(buffer.take()), None)),
+                            Err(err) => Some((Err(err.into()), None)),
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+enum RecordBatchEncoder {
+    Parquet(ArrowWriter<SharedBuffer>),
+    Csv(csv::Writer<SharedBuffer>),
+    Ndjson(LineDelimitedWriter<SharedBuffer>),
+}
+
+impl RecordBatchEncoder {
+    fn try_new(
+        format: ExportFormat,
+        schema: SchemaRef,
+        buffer: SharedBuffer,
+    ) -> Result<Self, ArrowError> {
+        Ok(match format {
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .build();
+                RecordBatchEncoder::Parquet(
+                    ArrowWriter::try_new(buffer, schema, Some(properties))
+                        .map_err(|err| ArrowError::ExternalError(Box::new(err)))?,
+                )
+            }
+            ExportFormat::Csv => RecordBatchEncoder::Csv(csv::Writer::new(buffer)),
+            ExportFormat::Ndjson => RecordBatchEncoder::Ndjson(LineDelimitedWriter::new(buffer)),
+        })
+    }
+
+    fn write(&mut self, record_batch: &RecordBatch) -> Result<(), ArrowError> {
+        match self {
+            // Parquet buffers the rows until a row group is complete, flush them to stream
+            // every batch as its own row group
+            RecordBatchEncoder::Parquet(writer) => writer
+                .write(record_batch)
+                .and_then(|_| writer.flush())
+                .map_err(|err| ArrowError::ExternalError(Box::new(err))),
+            RecordBatchEncoder::Csv(writer) => writer.write(record_batch),
+            RecordBatchEncoder::Ndjson(writer) => writer.write(record_batch),
+        }
+    }
+
+    fn finish(self) -> Result<(), ArrowError> {
+        match self {
+            RecordBatchEncoder::Parquet(writer) => writer
+                .close()
+                .map(|_| ())
+                .map_err(|err| ArrowError::ExternalError(Box::new(err))),
+            RecordBatchEncoder::Csv(_) => Ok(()),
+            RecordBatchEncoder::Ndjson(mut writer) => writer.finish(),
+        }
+    }
+}
+
+/// In-memory sink shared between the writer and the response stream, which drains it after
+/// every written batch.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(
+            &mut *self.0.lock().expect("buffer lock is not poisoned"),
+        ))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .expect("buffer lock is not poisoned")
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_flight::encode::FlightDataEncoderBuilder;
+    use datafusion::arrow::array::{Int64Array, StringArray};
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+    use futures::TryStreamExt;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn record_batches() -> Vec<RecordBatch> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        vec![
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from(vec![1, 2])),
+                    Arc::new(StringArray::from(vec!["a", "b"])),
+                ],
+            )
+            .unwrap(),
+            RecordBatch::try_new(
+                schema,
+                vec![
+                    Arc::new(Int64Array::from(vec![3])),
+                    Arc::new(StringArray::from(vec!["c"])),
+                ],
+            )
+            .unwrap(),
+        ]
+    }
+
+    async fn encode(format: ExportFormat, record_batches: Vec<RecordBatch>) -> Vec<Bytes> {
+        let flight_data =
+            FlightDataEncoderBuilder::new().build(stream::iter(record_batches.into_iter().map(Ok)));
+        encode_record_batches(
+            format,
+            FlightRecordBatchStream::new_from_flight_data(flight_data),
+        )
+        .try_collect()
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn parquet_is_streamed_per_batch() {
+        let chunks = encode(ExportFormat::Parquet, record_batches()).await;
+
+        // Each batch is written as soon as it is received, then the footer
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(Bytes::from(chunks.concat())).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let rows: usize = reader
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        assert_eq!(rows, 3);
+    }
+
+    #[tokio::test]
+    async fn csv() {
+        let chunks = encode(ExportFormat::Csv, record_batches()).await;
+
+        assert_eq!(chunks.concat(), b"id,name\n1,a\n2,b\n3,c\n");
+    }
+
+    #[tokio::test]
+    async fn ndjson() {
+        let chunks = encode(ExportFormat::Ndjson, record_batches()).await;
+
+        assert_eq!(
+            String::from_utf8(chunks.concat()).unwrap(),
+            "{\"id\":1,\"name\":\"a\"}\n{\"id\":2,\"name\":\"b\"}\n{\"id\":3,\"name\":\"c\"}\n"
+        );
+    }
+}
@@ -9,9 +9,12 @@
 // by the Apache License, Version 2.0.
 
 mod error;
+mod export;
 mod query;
+mod state_changes;
 
-use axum::{routing::post, Router};
+use axum::routing::{get, post};
+use axum::Router;
 use std::sync::Arc;
 
 use crate::state::QueryServiceState;
@@ -20,5 +23,7 @@ pub fn create_router(state: Arc<QueryServiceState>) -> Router<()> {
     // Setup the router
     axum::Router::new()
         .route("/query", post(query::query))
+        .route("/query/export", post(export::export))
+        .route("/state/changes", get(state_changes::stream_state_changes))
         .with_state(state)
 }
@@ -62,16 +62,34 @@ pub async fn query(
     State(state): State<Arc<QueryServiceState>>,
     #[request_body(required = true)] Json(payload): Json<QueryRequest>,
 ) -> Result<impl IntoResponse, StorageQueryError> {
+    let record_batch_stream = query_record_batches(&state, payload.query).await?;
+
+    // create a stream without LargeUtf8 or LargeBinary columns as JS doesn't support these yet
+    let result_stream = ConvertRecordBatchStream::new(record_batch_stream);
+
+    let body = StreamBody::new(result_stream);
+    Ok((
+        [(
+            http::header::CONTENT_TYPE,
+            "application/vnd.apache.arrow.stream",
+        )],
+        body,
+    ))
+}
+
+/// Runs the query on the worker and decodes the returned record batches.
+pub(super) async fn query_record_batches(
+    state: &QueryServiceState,
+    query: String,
+) -> Result<FlightRecordBatchStream, StorageQueryError> {
     let mut worker_grpc_client = state.node_svc_client.clone();
 
     let response_stream = worker_grpc_client
-        .query_storage(StorageQueryRequest {
-            query: payload.query,
-        })
+        .query_storage(StorageQueryRequest { query })
         .await?
         .into_inner();
 
-    let record_batch_stream = FlightRecordBatchStream::new_from_flight_data(
+    Ok(FlightRecordBatchStream::new_from_flight_data(
         response_stream
             .map_ok(|response| FlightData {
                 data_header: response.header,
@@ -79,18 +97,6 @@ pub async fn query(
                 ..FlightData::default()
             })
             .map_err(FlightError::from),
-    );
-
-    // create a stream without LargeUtf8 or LargeBinary columns as JS doesn't support these yet
-    let result_stream = ConvertRecordBatchStream::new(record_batch_stream);
-
-    let body = StreamBody::new(result_stream);
-    Ok((
-        [(
-            http::header::CONTENT_TYPE,
-            "application/vnd.apache.arrow.stream",
-        )],
-        body,
     ))
 }
 
@@ -0,0 +1,182 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::body::StreamBody;
+use axum::extract::{Query, State};
+use axum::http;
+use axum::response::IntoResponse;
+use base64::Engine;
+use bytes::Bytes;
+use futures::StreamExt;
+use okapi_operation::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use restate_core::network::protobuf::node_svc::{StateChangeEvent, SubscribeStateChangesRequest};
+
+use super::error::StorageQueryError;
+use crate::state::QueryServiceState;
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct StateChangesParams {
+    /// # Service
+    ///
+    /// Only stream the state changes of this virtual object or workflow.
+    pub service: Option<String>,
+    /// # Resume after
+    ///
+    /// Comma separated list of `<partition_id>:<lsn>` positions to resume the state changes of
+    /// each partition from. The changes of the other partitions start with the next applied
+    /// record.
+    pub resume_after: Option<String>,
+}
+
+/// Change to the state of a virtual object or workflow, serialized as one line of the response.
+#[derive(Debug, Serialize)]
+struct StateChangeResponse {
+    partition_id: u64,
+    /// Position in the partition log of the record which caused the change.
+    /// Changes of the same partition are streamed in increasing order, and can be resumed
+    /// from the last processed position.
+    lsn: u64,
+    service: String,
+    key: String,
+    /// Key of the changed state entry. Absent if the whole state of the object has been cleared.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_key: Option<String>,
+    /// Base64 encoded new value of the state entry. Absent if the entry has been deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    deleted: bool,
+}
+
+impl From<StateChangeEvent> for StateChangeResponse {
+    fn from(event: StateChangeEvent) -> Self {
+        StateChangeResponse {
+            partition_id: event.partition_id,
+            lsn: event.lsn,
+            service: event.service_name,
+            key: event.service_key,
+            state_key: event
+                .state_key
+                .map(|state_key| String::from_utf8_lossy(&state_key).into_owned()),
+            deleted: event.value.is_none(),
+            value: event
+                .value
+                .map(|value| base64::prelude::BASE64_STANDARD.encode(value)),
+        }
+    }
+}
+
+/// Stream state changes
+#[openapi(
+    summary = "Stream state changes",
+    description = "Stream the changes to the state of virtual objects and workflows as newline delimited JSON, once the partition processors applied them. The stream ends with an error line if the changes to resume from are no longer retained, or if the client falls behind.",
+    operation_id = "stream_state_changes",
+    tags = "storage",
+    parameters(
+        query(
+            name = "service",
+            description = "Only stream the state changes of this virtual object or workflow.",
+            required = false,
+            style = "simple",
+            allow_empty_value = false,
+            schema = "String",
+        ),
+        query(
+            name = "resume_after",
+            description = "Comma separated list of `<partition_id>:<lsn>` positions to resume the state changes of each partition from.",
+            required = false,
+            style = "simple",
+            allow_empty_value = false,
+            schema = "String",
+        )
+    ),
+    responses(ignore_return_type = true, from_type = "StorageQueryError")
+)]
+pub async fn stream_state_changes(
+    State(state): State<Arc<QueryServiceState>>,
+    Query(params): Query<StateChangesParams>,
+) -> Result<impl IntoResponse, StorageQueryError> {
+    let resume_after = params
+        .resume_after
+        .as_deref()
+        .map(parse_resume_after)
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut worker_grpc_client = state.node_svc_client.clone();
+
+    let event_stream = worker_grpc_client
+        .subscribe_state_changes(SubscribeStateChangesRequest {
+            service: params.service.unwrap_or_default(),
+            resume_after,
+        })
+        .await?
+        .into_inner();
+
+    // the stream ends after the first error, e.g. when the client falls behind
+    let body = event_stream.map(|event| {
+        let line = match event {
+            Ok(event) => serde_json::to_vec(&StateChangeResponse::from(event)),
+            Err(status) => serde_json::to_vec(&serde_json::json!({
+                "error": status.message(),
+            })),
+        };
+        line.map(|mut line| {
+            line.push(b'\n');
+            Bytes::from(line)
+        })
+    });
+
+    Ok((
+        [(http::header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(body),
+    ))
+}
+
+fn parse_resume_after(resume_after: &str) -> Result<HashMap<u64, u64>, StorageQueryError> {
+    resume_after
+        .split(',')
+        .map(str::trim)
+        .filter(|position| !position.is_empty())
+        .map(|position| {
+            position
+                .split_once(':')
+                .and_then(|(partition_id, lsn)| {
+                    Some((partition_id.trim().parse().ok()?, lsn.trim().parse().ok()?))
+                })
+                .ok_or_else(|| StorageQueryError::InvalidResumePosition(position.to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_resume_positions() {
+        let resume_after = parse_resume_after("0:12, 3:7,").unwrap();
+        assert_eq!(resume_after, HashMap::from([(0, 12), (3, 7)]));
+    }
+
+    #[test]
+    fn rejects_invalid_resume_positions() {
+        assert!(matches!(
+            parse_resume_after("0:12,3"),
+            Err(StorageQueryError::InvalidResumePosition(position)) if position == "3"
+        ));
+        assert!(parse_resume_after("0:-1").is_err());
+    }
+}
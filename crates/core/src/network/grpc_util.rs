@@ -13,22 +13,41 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::time::Duration;
 
+use http::uri::Scheme;
 use http::Uri;
 use hyper::body::HttpBody;
 use hyper::server::accept::Accept;
 use hyper::server::conn::AddrIncoming;
+use once_cell::sync::OnceCell;
+use restate_types::config::Configuration;
 use restate_types::net::{AdvertisedAddress, BindAddress};
+use rustls::{ClientConfig, ServerConfig, ServerName};
 use tokio::io;
 use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::net::{UnixListener, UnixStream};
+use tokio::net::{TcpStream, UnixListener, UnixStream};
+use tokio_rustls::TlsConnector;
 use tokio_stream::wrappers::UnixListenerStream;
 use tonic::transport::{Channel, Endpoint};
 use tower::service_fn;
 use tracing::{debug, info};
 
+use super::tls::{accept_tls, client_config, default_client_config, ReloadableTlsConfig, TlsError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChannelError {
+    #[error(transparent)]
+    Http(#[from] http::Error),
+    #[error("failed loading the tls certificates specified in 'common.tls': {0}")]
+    Tls(#[from] TlsError),
+}
+
+/// Creates a channel to the given address. Addresses with the `https` scheme are connected
+/// through TLS, presenting the certificate configured in `common.tls` as client certificate.
+/// Every new connection uses the current certificates, so that they can be rotated without
+/// recreating the channel.
 pub fn create_grpc_channel_from_advertised_address(
     address: AdvertisedAddress,
-) -> Result<Channel, http::Error> {
+) -> Result<Channel, ChannelError> {
     let channel = match address {
         AdvertisedAddress::Uds(uds_path) => {
             // dummy endpoint required to specify an uds connector, it is not used anywhere
@@ -39,17 +58,62 @@ pub fn create_grpc_channel_from_advertised_address(
                 }))
         }
         AdvertisedAddress::Http(uri) => {
+            let use_tls = uri.scheme() == Some(&Scheme::HTTPS);
             // todo: Make the channel settings configurable
-            Channel::builder(uri)
+            let endpoint = Channel::builder(uri)
                 .connect_timeout(Duration::from_secs(5))
                 // todo: configure the channel from configuration file
-                .http2_adaptive_window(true)
-                .connect_lazy()
+                .http2_adaptive_window(true);
+            if use_tls {
+                let tls = client_tls_config()?.clone();
+                endpoint.connect_with_connector_lazy(service_fn(move |uri: Uri| {
+                    connect_tls(uri, TlsConnector::from(tls.current()))
+                }))
+            } else {
+                endpoint.connect_lazy()
+            }
         }
     };
     Ok(channel)
 }
 
+/// Client TLS configuration shared by all the channels, reloaded when `common.tls` or its
+/// certificate files change.
+fn client_tls_config() -> Result<&'static ReloadableTlsConfig<ClientConfig>, TlsError> {
+    static CLIENT_TLS_CONFIG: OnceCell<ReloadableTlsConfig<ClientConfig>> = OnceCell::new();
+
+    CLIENT_TLS_CONFIG.get_or_try_init(|| {
+        let Some(tls_options) = Configuration::pinned().common.tls.clone() else {
+            return Ok(ReloadableTlsConfig::fixed(default_client_config()?));
+        };
+        let tls = ReloadableTlsConfig::load(&tls_options, client_config)?;
+        tls.reload_on_change_detached(
+            "client-tls-reload",
+            |config| config.common.tls.as_ref(),
+            client_config,
+        )?;
+        Ok(tls)
+    })
+}
+
+async fn connect_tls(
+    uri: Uri,
+    connector: TlsConnector,
+) -> io::Result<tokio_rustls::client::TlsStream<TcpStream>> {
+    let host = uri
+        .host()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing host"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = uri.port_u16().unwrap_or(443);
+    let server_name = ServerName::try_from(host)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+    let stream = TcpStream::connect((host, port)).await?;
+    stream.set_nodelay(true)?;
+    connector.connect(server_name, stream).await
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("failed binding to address '{address}': {source}")]
@@ -68,8 +132,11 @@ pub enum Error {
     Running(#[from] hyper::Error),
 }
 
+/// Runs the server on the given address. If `tls` is set, TCP connections must use TLS, while
+/// unix domain sockets are always served in plaintext.
 pub async fn run_hyper_server<S, B, F>(
     bind_address: &BindAddress,
+    tls: Option<ReloadableTlsConfig<ServerConfig>>,
     service: S,
     shutdown_signal: F,
     server_name: &str,
@@ -100,7 +167,7 @@ where
             run_server(acceptor, service, shutdown_signal).await?
         }
         BindAddress::Socket(socket_addr) => {
-            run_tcp_server(socket_addr, service, shutdown_signal, server_name).await?
+            run_tcp_server(socket_addr, tls, service, shutdown_signal, server_name).await?
         }
     }
 
@@ -111,6 +178,7 @@ where
 
 async fn run_tcp_server<S, B, F>(
     socket_addr: &SocketAddr,
+    tls: Option<ReloadableTlsConfig<ServerConfig>>,
     service: S,
     shutdown_signal: F,
     server_name: &str,
@@ -135,10 +203,15 @@ where
     info!(
         net.host.addr = %acceptor.local_addr().ip(),
         net.host.port = %acceptor.local_addr().port(),
+        tls = tls.is_some(),
         "Server '{}' listening", server_name
     );
 
-    run_server(acceptor, service, shutdown_signal).await
+    let Some(tls) = tls else {
+        return run_server(acceptor, service, shutdown_signal).await;
+    };
+
+    run_server(accept_tls(acceptor, tls), service, shutdown_signal).await
 }
 
 async fn run_server<S, B, Conn, Err, F>(
@@ -19,6 +19,7 @@ mod network_sender;
 mod networking;
 pub mod protobuf;
 pub mod rpc_router;
+pub mod tls;
 
 pub use connection::ConnectionSender;
 pub use connection_manager::ConnectionManager;
@@ -0,0 +1,459 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Loading of the TLS certificates configured through [`TlsOptions`], and their hot reload on
+//! configuration updates and on changes of the certificate files.
+
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwap;
+use futures::StreamExt;
+use hyper::server::accept::Accept;
+use hyper::server::conn::{AddrIncoming, AddrStream};
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+use restate_types::config::{Configuration, TlsOptions};
+
+use crate::{cancellation_watcher, task_center, ShutdownError, TaskId, TaskKind};
+
+/// ALPN protocols offered by the servers, which all support both HTTP/2 and HTTP/1.1.
+const ALPN_PROTOCOLS: [&[u8]; 2] = [b"h2", b"http/1.1"];
+/// Time a client has to complete the TLS handshake after connecting
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of TLS handshakes performed concurrently by a server
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 128;
+/// Interval at which the certificate files are checked for changes
+const TLS_FILES_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("cannot read '{0}': {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+    #[error("no certificate found in '{0}'")]
+    NoCertificate(PathBuf),
+    #[error("no private key found in '{0}'")]
+    NoPrivateKey(PathBuf),
+    #[error("bad tls configuration: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Shutdown(#[from] ShutdownError),
+}
+
+/// DER encoded private key, as found in the key file.
+#[derive(Debug, Clone)]
+pub enum PrivateKeyDer {
+    Pkcs8(Vec<u8>),
+    Pkcs1(Vec<u8>),
+    Sec1(Vec<u8>),
+}
+
+impl PrivateKeyDer {
+    pub fn secret_der(&self) -> &[u8] {
+        match self {
+            PrivateKeyDer::Pkcs8(der) | PrivateKeyDer::Pkcs1(der) | PrivateKeyDer::Sec1(der) => der,
+        }
+    }
+}
+
+/// Certificates and private key read from the files configured in [`TlsOptions`].
+#[derive(Debug, Clone)]
+pub struct TlsMaterial {
+    /// DER encoded certificate chain
+    pub cert_chain: Vec<Vec<u8>>,
+    pub private_key: PrivateKeyDer,
+    /// DER encoded client CA certificates
+    pub client_ca: Option<Vec<Vec<u8>>>,
+
+    // The original PEM files, for the libraries taking PEM directly
+    pub cert_pem: Vec<u8>,
+    pub key_pem: Vec<u8>,
+    pub client_ca_pem: Option<Vec<u8>>,
+}
+
+impl TlsMaterial {
+    pub fn load(options: &TlsOptions) -> Result<Self, TlsError> {
+        let cert_pem = read(&options.cert_file)?;
+        let key_pem = read(&options.key_file)?;
+        let client_ca_pem = options.client_ca_file.as_deref().map(read).transpose()?;
+
+        let cert_chain = parse_certs(&cert_pem, &options.cert_file)?;
+        let private_key = parse_private_key(&key_pem, &options.key_file)?;
+        let client_ca = match (&client_ca_pem, &options.client_ca_file) {
+            (Some(pem), Some(path)) => Some(parse_certs(pem, path)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            cert_chain,
+            private_key,
+            client_ca,
+            cert_pem,
+            key_pem,
+            client_ca_pem,
+        })
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, TlsError> {
+    std::fs::read(path).map_err(|e| TlsError::Read(path.to_owned(), e))
+}
+
+fn parse_certs(pem: &[u8], path: &Path) -> Result<Vec<Vec<u8>>, TlsError> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(pem))
+        .map_err(|e| TlsError::Read(path.to_owned(), e))?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(path.to_owned()));
+    }
+    Ok(certs)
+}
+
+fn parse_private_key(pem: &[u8], path: &Path) -> Result<PrivateKeyDer, TlsError> {
+    rustls_pemfile::read_all(&mut BufReader::new(pem))
+        .map_err(|e| TlsError::Read(path.to_owned(), e))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(der) => Some(PrivateKeyDer::Pkcs8(der)),
+            rustls_pemfile::Item::RSAKey(der) => Some(PrivateKeyDer::Pkcs1(der)),
+            rustls_pemfile::Item::ECKey(der) => Some(PrivateKeyDer::Sec1(der)),
+            _ => None,
+        })
+        .ok_or_else(|| TlsError::NoPrivateKey(path.to_owned()))
+}
+
+/// Builds the rustls server configuration, requiring client certificates if a client CA is
+/// configured.
+pub fn server_config(material: TlsMaterial) -> Result<ServerConfig, TlsError> {
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = if let Some(client_ca) = material.client_ca {
+        let mut roots = RootCertStore::empty();
+        for ca in client_ca {
+            roots
+                .add(&Certificate(ca))
+                .map_err(|e| TlsError::Config(e.to_string()))?;
+        }
+        builder.with_client_cert_verifier(Arc::new(AllowAnyAuthenticatedClient::new(roots)))
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let mut config = builder
+        .with_single_cert(
+            material.cert_chain.into_iter().map(Certificate).collect(),
+            PrivateKey(material.private_key.secret_der().to_vec()),
+        )
+        .map_err(|e| TlsError::Config(e.to_string()))?;
+    config.alpn_protocols = ALPN_PROTOCOLS.iter().map(|p| p.to_vec()).collect();
+
+    Ok(config)
+}
+
+/// Builds the rustls client configuration, presenting the configured certificate as client
+/// certificate. The server certificates are verified against the configured CA if any, otherwise
+/// against the CA certificates of the platform.
+pub fn client_config(material: TlsMaterial) -> Result<ClientConfig, TlsError> {
+    let roots = match material.client_ca {
+        Some(ca) => ca,
+        None => native_root_certs()?,
+    };
+
+    let mut config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store(roots)?)
+        .with_client_auth_cert(
+            material.cert_chain.into_iter().map(Certificate).collect(),
+            PrivateKey(material.private_key.secret_der().to_vec()),
+        )
+        .map_err(|e| TlsError::Config(e.to_string()))?;
+    // The gRPC channels are HTTP/2 only
+    config.alpn_protocols = vec![b"h2".to_vec()];
+
+    Ok(config)
+}
+
+/// Builds the rustls client configuration used when no certificate is configured, verifying the
+/// server certificates against the CA certificates of the platform.
+pub fn default_client_config() -> Result<ClientConfig, TlsError> {
+    let mut config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store(native_root_certs()?)?)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"h2".to_vec()];
+
+    Ok(config)
+}
+
+fn native_root_certs() -> Result<Vec<Vec<u8>>, TlsError> {
+    Ok(rustls_native_certs::load_native_certs()
+        .map_err(|e| TlsError::Config(format!("cannot load the platform CA certificates: {e}")))?
+        .into_iter()
+        .map(|cert| cert.0)
+        .collect())
+}
+
+fn root_cert_store(certs: Vec<Vec<u8>>) -> Result<RootCertStore, TlsError> {
+    let mut roots = RootCertStore::empty();
+    for cert in certs {
+        roots
+            .add(&Certificate(cert))
+            .map_err(|e| TlsError::Config(e.to_string()))?;
+    }
+    Ok(roots)
+}
+
+/// Modification time and size of the certificate files, to detect their changes.
+#[derive(Debug, PartialEq, Eq)]
+struct TlsFilesFingerprint(Vec<Option<(SystemTime, u64)>>);
+
+impl TlsFilesFingerprint {
+    async fn of(options: &TlsOptions) -> Self {
+        let mut fingerprint = Vec::with_capacity(3);
+        for path in [Some(&options.cert_file), Some(&options.key_file)]
+            .into_iter()
+            .chain(std::iter::once(options.client_ca_file.as_ref()))
+            .flatten()
+        {
+            // follows the symlinks, which are commonly swapped to rotate mounted certificates
+            let metadata = tokio::fs::metadata(path).await.ok();
+            fingerprint
+                .push(metadata.and_then(|m| m.modified().ok().map(|modified| (modified, m.len()))));
+        }
+        Self(fingerprint)
+    }
+}
+
+/// TLS configuration which is rebuilt from the [`TlsOptions`] on every configuration update, and
+/// whenever the certificate files change. If the new certificates cannot be loaded, the previous
+/// configuration is kept.
+///
+/// The configuration type is generic to support libraries depending on other TLS stacks.
+pub struct ReloadableTlsConfig<T> {
+    current: Arc<ArcSwap<T>>,
+}
+
+impl<T> Clone for ReloadableTlsConfig<T> {
+    fn clone(&self) -> Self {
+        Self {
+            current: Arc::clone(&self.current),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> ReloadableTlsConfig<T> {
+    pub fn load(
+        options: &TlsOptions,
+        build: fn(TlsMaterial) -> Result<T, TlsError>,
+    ) -> Result<Self, TlsError> {
+        Ok(Self {
+            current: Arc::new(ArcSwap::from_pointee(build(TlsMaterial::load(options)?)?)),
+        })
+    }
+
+    /// Configuration which is never reloaded.
+    pub fn fixed(config: T) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(config)),
+        }
+    }
+
+    pub fn current(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    /// Spawns a child task reloading the TLS configuration on every configuration update, and
+    /// whenever the certificate files change.
+    /// `options` selects the [`TlsOptions`] of the server within the [`Configuration`].
+    pub fn reload_on_change(
+        &self,
+        name: &'static str,
+        options: fn(&Configuration) -> Option<&TlsOptions>,
+        build: fn(TlsMaterial) -> Result<T, TlsError>,
+    ) -> Result<TaskId, ShutdownError> {
+        task_center().spawn_child(
+            TaskKind::Disposable,
+            name,
+            None,
+            reload(Arc::clone(&self.current), options, build),
+        )
+    }
+
+    /// Like [`Self::reload_on_change`], but the reload task is not bound to the current task.
+    /// Used for the configurations shared by the whole process.
+    pub fn reload_on_change_detached(
+        &self,
+        name: &'static str,
+        options: fn(&Configuration) -> Option<&TlsOptions>,
+        build: fn(TlsMaterial) -> Result<T, TlsError>,
+    ) -> Result<TaskId, ShutdownError> {
+        task_center().spawn(
+            TaskKind::Disposable,
+            name,
+            None,
+            reload(Arc::clone(&self.current), options, build),
+        )
+    }
+}
+
+async fn reload<T>(
+    current: Arc<ArcSwap<T>>,
+    options: fn(&Configuration) -> Option<&TlsOptions>,
+    build: fn(TlsMaterial) -> Result<T, TlsError>,
+) -> anyhow::Result<()> {
+    let mut config_watcher = Configuration::watcher();
+    let mut shutdown = std::pin::pin!(cancellation_watcher());
+    let mut files_check = tokio::time::interval(TLS_FILES_CHECK_INTERVAL);
+    files_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let mut fingerprint = match options(&Configuration::pinned()).cloned() {
+        Some(tls_options) => Some(TlsFilesFingerprint::of(&tls_options).await),
+        None => None,
+    };
+
+    loop {
+        let files_check_due = tokio::select! {
+            _ = config_watcher.changed() => false,
+            _ = files_check.tick() => true,
+            _ = &mut shutdown => return Ok(()),
+        };
+
+        let Some(tls_options) = options(&Configuration::pinned()).cloned() else {
+            if !files_check_due {
+                warn!("TLS can't be disabled without restarting the server, keeping the current certificates");
+            }
+            continue;
+        };
+        let new_fingerprint = TlsFilesFingerprint::of(&tls_options).await;
+        if files_check_due && fingerprint.as_ref() == Some(&new_fingerprint) {
+            continue;
+        }
+        fingerprint = Some(new_fingerprint);
+
+        match TlsMaterial::load(&tls_options).and_then(build) {
+            Ok(config) => {
+                current.store(Arc::new(config));
+                info!("Reloaded the TLS certificates");
+            }
+            Err(err) => {
+                warn!("Failed reloading the TLS certificates, keeping the current ones: {err}");
+            }
+        }
+    }
+}
+
+impl ReloadableTlsConfig<ServerConfig> {
+    /// Loads the server TLS configuration and reloads it whenever the configuration or the
+    /// certificate files change.
+    /// Returns `None` if TLS is not configured.
+    pub fn start_server(
+        tls_options: Option<&TlsOptions>,
+        reload_task_name: &'static str,
+        options: fn(&Configuration) -> Option<&TlsOptions>,
+    ) -> Result<Option<Self>, TlsError> {
+        let Some(tls_options) = tls_options else {
+            return Ok(None);
+        };
+        let tls = Self::load(tls_options, server_config)?;
+        tls.reload_on_change(reload_task_name, options, server_config)?;
+        Ok(Some(tls))
+    }
+
+    /// Acceptor for the new connections, using the current certificates.
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.current())
+    }
+}
+
+/// Wraps the accepted TCP connections in TLS, using the current certificates for every new
+/// connection. The handshakes are performed concurrently so that slow clients don't block the
+/// others, and failed handshakes are dropped.
+pub fn accept_tls(
+    mut incoming: AddrIncoming,
+    tls: ReloadableTlsConfig<ServerConfig>,
+) -> impl Accept<Conn = TlsStream<AddrStream>, Error = std::io::Error> {
+    let tls_connections =
+        futures::stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx))
+            .filter_map(|conn| async move { conn.ok() })
+            .map(move |conn| {
+                let tls_acceptor = tls.acceptor();
+                async move {
+                    let remote_addr = conn.remote_addr();
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(conn))
+                        .await
+                    {
+                        Ok(Ok(tls_stream)) => Some(tls_stream),
+                        Ok(Err(err)) => {
+                            debug!("TLS handshake with '{}' failed: {}", remote_addr, err);
+                            None
+                        }
+                        Err(_) => {
+                            debug!("TLS handshake with '{}' timed out", remote_addr);
+                            None
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+            .filter_map(|tls_stream| async move { tls_stream.map(Ok) });
+
+    hyper::server::accept::from_stream(tls_connections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_files_are_reported() {
+        let err = TlsMaterial::load(&TlsOptions {
+            cert_file: PathBuf::from("/does/not/exist.pem"),
+            key_file: PathBuf::from("/does/not/exist.key"),
+            client_ca_file: None,
+        })
+        .unwrap_err();
+        assert!(matches!(err, TlsError::Read(path, _) if path == Path::new("/does/not/exist.pem")));
+    }
+
+    #[test]
+    fn private_key_is_required() {
+        assert!(matches!(
+            parse_private_key(b"not a pem", Path::new("key.pem")),
+            Err(TlsError::NoPrivateKey(_))
+        ));
+        assert!(matches!(
+            parse_certs(b"", Path::new("cert.pem")),
+            Err(TlsError::NoCertificate(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fingerprint_changes_with_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = TlsOptions {
+            cert_file: dir.path().join("cert.pem"),
+            key_file: dir.path().join("key.pem"),
+            client_ca_file: None,
+        };
+        std::fs::write(&options.cert_file, b"cert").unwrap();
+        std::fs::write(&options.key_file, b"key").unwrap();
+
+        let initial = TlsFilesFingerprint::of(&options).await;
+        assert_eq!(initial, TlsFilesFingerprint::of(&options).await);
+
+        std::fs::write(&options.cert_file, b"rotated cert").unwrap();
+        assert_ne!(initial, TlsFilesFingerprint::of(&options).await);
+    }
+}
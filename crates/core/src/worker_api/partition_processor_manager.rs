@@ -8,6 +8,9 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::BTreeMap;
+
+use restate_types::cluster::cluster_state::PartitionProcessorStatus;
 use restate_types::identifiers::PartitionId;
 use tokio::sync::{mpsc, oneshot};
 
@@ -16,6 +19,7 @@ use crate::ShutdownError;
 #[derive(Debug)]
 pub enum ProcessorsManagerCommand {
     GetLivePartitions(oneshot::Sender<Vec<PartitionId>>),
+    GetState(oneshot::Sender<BTreeMap<PartitionId, PartitionProcessorStatus>>),
 }
 
 #[derive(Debug, Clone)]
@@ -34,4 +38,15 @@ impl ProcessorsManagerHandle {
             .unwrap();
         rx.await.map_err(|_| ShutdownError)
     }
+
+    pub async fn get_state(
+        &self,
+    ) -> Result<BTreeMap<PartitionId, PartitionProcessorStatus>, ShutdownError> {
+        let (tx, rx) = oneshot::channel();
+        self.0
+            .send(ProcessorsManagerCommand::GetState(tx))
+            .await
+            .map_err(|_| ShutdownError)?;
+        rx.await.map_err(|_| ShutdownError)
+    }
 }
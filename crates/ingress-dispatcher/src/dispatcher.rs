@@ -242,6 +242,9 @@ fn wrap_service_invocation_in_envelope(
             IngressDispatcherRequestInner::Attach(attach_invocation_req) => {
                 Command::AttachInvocation(attach_invocation_req)
             }
+            IngressDispatcherRequestInner::CompletePromise(complete_promise_req) => {
+                Command::CompletePromise(complete_promise_req)
+            }
         },
     )
 }
@@ -21,9 +21,9 @@ use restate_types::identifiers::{
 };
 use restate_types::ingress::IngressResponseResult;
 use restate_types::invocation::{
-    AttachInvocationRequest, InvocationQuery, InvocationResponse, InvocationTarget,
-    InvocationTargetType, ServiceInvocation, ServiceInvocationResponseSink, SpanRelation,
-    SubmitNotificationSink, VirtualObjectHandlerType, WorkflowHandlerType,
+    AttachInvocationRequest, CompletePromiseRequest, InvocationQuery, InvocationResponse,
+    InvocationTarget, InvocationTargetType, ServiceInvocation, ServiceInvocationResponseSink,
+    SpanRelation, SubmitNotificationSink, VirtualObjectHandlerType, WorkflowHandlerType,
 };
 use restate_types::message::MessageIndex;
 use restate_types::schema::subscriptions::{EventReceiverServiceType, Sink, Subscription};
@@ -49,6 +49,7 @@ enum IngressDispatcherRequestInner {
     ProxyThrough(ServiceInvocation),
     InvocationResponse(InvocationResponse),
     Attach(AttachInvocationRequest),
+    CompletePromise(CompletePromiseRequest),
 }
 
 impl WithPartitionKey for IngressDispatcherRequestInner {
@@ -58,6 +59,7 @@ impl WithPartitionKey for IngressDispatcherRequestInner {
             IngressDispatcherRequestInner::ProxyThrough(si) => si.invocation_id.partition_key(),
             IngressDispatcherRequestInner::InvocationResponse(ir) => ir.id.partition_key(),
             IngressDispatcherRequestInner::Attach(iq) => iq.partition_key(),
+            IngressDispatcherRequestInner::CompletePromise(cp) => cp.partition_key(),
         }
     }
 }
@@ -276,6 +278,28 @@ impl IngressDispatcherRequest {
             inner: IngressDispatcherRequestInner::InvocationResponse(invocation_response),
         }
     }
+
+    pub fn complete_promise(
+        mut complete_promise_request: CompletePromiseRequest,
+    ) -> (Self, IngressRequestId, IngressInvocationResponseReceiver) {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let node_id = metadata().my_node_id();
+        let request_id = IngressRequestId::default();
+        complete_promise_request.response_sink = Some(ServiceInvocationResponseSink::Ingress {
+            node_id,
+            request_id,
+        });
+
+        (
+            IngressDispatcherRequest {
+                request_mode: IngressRequestMode::RequestResponse(request_id, result_tx),
+                inner: IngressDispatcherRequestInner::CompletePromise(complete_promise_request),
+            },
+            request_id,
+            result_rx,
+        )
+    }
 }
 
 #[cfg(feature = "test-util")]
@@ -370,6 +394,18 @@ pub mod test_util {
             ir
         }
 
+        pub fn expect_complete_promise(
+            self,
+        ) -> (CompletePromiseRequest, IngressInvocationResponseSender) {
+            let_assert!(
+                IngressDispatcherRequest {
+                    inner: IngressDispatcherRequestInner::CompletePromise(cp),
+                    request_mode: IngressRequestMode::RequestResponse(_, ingress_response_sender),
+                } = self
+            );
+            (cp, ingress_response_sender)
+        }
+
         pub fn expect_event(self) -> (ServiceInvocation, IngressDeduplicationId) {
             let_assert!(
                 IngressDispatcherRequest {
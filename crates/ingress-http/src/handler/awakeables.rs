@@ -11,22 +11,53 @@
 use super::path_parsing::AwakeableRequestType;
 use super::Handler;
 use super::HandlerError;
+use super::APPLICATION_JSON;
+use crate::{GetAwakeableResult, InvocationStorageReader};
 
 use bytes::Bytes;
-use http::{Method, Request, Response, StatusCode};
+use http::{header, Method, Request, Response, StatusCode};
 use http_body_util::BodyExt;
 use http_body_util::Full;
 use restate_ingress_dispatcher::DispatchIngressRequest;
 use restate_ingress_dispatcher::IngressDispatcherRequest;
 use restate_service_protocol::awakeable_id::AwakeableIdentifier;
 use restate_types::errors::{codes, InvocationError};
+use restate_types::identifiers::InvocationId;
 use restate_types::invocation::{InvocationResponse, ResponseResult};
+use serde::Serialize;
 use std::str::FromStr;
+use std::time::SystemTime;
 use tracing::{info, trace, warn};
 
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AwakeableStatus {
+    Pending,
+    Resolved,
+    Rejected,
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AwakeableResponse {
+    pub(crate) invocation_id: InvocationId,
+    pub(crate) status: AwakeableStatus,
+    #[serde(
+        with = "serde_with::As::<Option<serde_with::DisplayFromStr>>",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    expires_at: Option<humantime::Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    failure: Option<InvocationError>,
+}
+
 impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageReader>
 where
     Dispatcher: DispatchIngressRequest + Clone + Send + Sync + 'static,
+    StorageReader: InvocationStorageReader + Clone + Send + Sync + 'static,
 {
     pub(crate) async fn handle_awakeable<B: http_body::Body>(
         self,
@@ -36,6 +67,10 @@ where
     where
         <B as http_body::Body>::Error: std::error::Error + Send + Sync + 'static,
     {
+        if let AwakeableRequestType::Get { awakeable_id } = awakeable_request_type {
+            return self.handle_get_awakeable(req, awakeable_id).await;
+        }
+
         // Check HTTP Method
         if req.method() != Method::POST {
             return Err(HandlerError::MethodNotAllowed);
@@ -51,6 +86,7 @@ where
         trace!(rpc.request = ?collected_request_bytes);
 
         let (awakeable_identifier, result) = match awakeable_request_type {
+            AwakeableRequestType::Get { .. } => unreachable!("Get requests are handled above"),
             AwakeableRequestType::Resolve { awakeable_id } => (
                 AwakeableIdentifier::from_str(&awakeable_id)
                     .map_err(|e| HandlerError::BadAwakeableId(awakeable_id, e))?,
@@ -93,4 +129,62 @@ where
             .body(Full::default())
             .unwrap())
     }
+
+    async fn handle_get_awakeable<B: http_body::Body>(
+        self,
+        req: Request<B>,
+        awakeable_id: String,
+    ) -> Result<Response<Full<Bytes>>, HandlerError> {
+        // Check HTTP Method
+        if req.method() != Method::GET {
+            return Err(HandlerError::MethodNotAllowed);
+        }
+
+        let (invocation_id, entry_index) = AwakeableIdentifier::from_str(&awakeable_id)
+            .map_err(|e| HandlerError::BadAwakeableId(awakeable_id, e))?
+            .into_inner();
+
+        let (status, expires_at, failure) = match self
+            .storage_reader
+            .get_awakeable(invocation_id, entry_index)
+            .await
+        {
+            Ok(GetAwakeableResult::NotFound) => return Err(HandlerError::NotFound),
+            Ok(GetAwakeableResult::Pending { expiry_time }) => (
+                AwakeableStatus::Pending,
+                expiry_time.map(|expiry_time| SystemTime::from(expiry_time).into()),
+                None,
+            ),
+            Ok(GetAwakeableResult::Completed(ResponseResult::Success(_))) => {
+                (AwakeableStatus::Resolved, None, None)
+            }
+            Ok(GetAwakeableResult::Completed(ResponseResult::Failure(error))) => {
+                (AwakeableStatus::Rejected, None, Some(error))
+            }
+            Err(e) => {
+                warn!(
+                    restate.invocation.id = %invocation_id,
+                    restate.journal.index = entry_index,
+                    "Failed to read awakeable: {}",
+                    e,
+                );
+                return Err(HandlerError::Unavailable);
+            }
+        };
+
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, APPLICATION_JSON)
+            .body(Full::new(
+                serde_json::to_vec(&AwakeableResponse {
+                    invocation_id,
+                    status,
+                    expires_at,
+                    failure,
+                })
+                .expect("Serializing the AwakeableResponse must not fail")
+                .into(),
+            ))
+            .unwrap())
+    }
 }
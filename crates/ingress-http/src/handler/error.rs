@@ -26,7 +26,7 @@ pub(crate) enum HandlerError {
     )]
     BadServicePath,
     #[error(
-        "bad path, expected either /restate/awakeables/:id/resolve or /restate/awakeables/:id/reject"
+        "bad path, expected either /restate/awakeables/:id, /restate/awakeables/:id/resolve or /restate/awakeables/:id/reject"
     )]
     BadAwakeablesPath,
     #[error(
@@ -34,9 +34,11 @@ pub(crate) enum HandlerError {
     )]
     BadInvocationPath,
     #[error(
-    "bad path, expected either /restate/workflow/:workflow_name/:workflow_key/output or /restate/workflow/:workflow_name/:workflow_key/attach"
+    "bad path, expected either /restate/workflow/:workflow_name/:workflow_key/output or /restate/workflow/:workflow_name/:workflow_key/attach or /restate/workflow/:workflow_name/:workflow_key/promise/:promise_name/resolve|reject|peek"
     )]
     BadWorkflowPath,
+    #[error("the service '{0}' is not a workflow")]
+    NotAWorkflow(String),
     #[error("not implemented")]
     NotImplemented,
     #[error("bad header {0}: {1:?}")]
@@ -47,6 +49,8 @@ pub(crate) enum HandlerError {
     UrlDecodingError(string::FromUtf8Error),
     #[error("the invoked service is not public")]
     PrivateService,
+    #[error("the authenticated principal is not allowed to access this resource")]
+    Forbidden,
     #[error("cannot read body: {0:?}")]
     Body(anyhow::Error),
     #[error("unavailable")]
@@ -75,6 +79,12 @@ pub(crate) enum HandlerError {
     BadAwakeableId(String, IdDecodeError),
     #[error("bad invocation id '{0}': {1}")]
     BadInvocationId(String, IdDecodeError),
+    #[error("bad callback url '{0}': {1}")]
+    BadCallbackUrl(String, String),
+    #[error(
+        "cannot use the callback url header with calls. The callback url is supported only with sends"
+    )]
+    UnsupportedCallbackUrl,
 }
 
 #[derive(Debug, Serialize)]
@@ -109,10 +119,14 @@ impl HandlerError {
             | HandlerError::BadInvocationPath
             | HandlerError::BadInvocationId(_, _)
             | HandlerError::BadWorkflowPath
+            | HandlerError::NotAWorkflow(_)
             | HandlerError::InputValidation(_)
             | HandlerError::UnsupportedIdempotencyKey
-            | HandlerError::UnsupportedGetOutput => StatusCode::BAD_REQUEST,
+            | HandlerError::UnsupportedGetOutput
+            | HandlerError::BadCallbackUrl(_, _)
+            | HandlerError::UnsupportedCallbackUrl => StatusCode::BAD_REQUEST,
             HandlerError::Body(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            HandlerError::Forbidden => StatusCode::FORBIDDEN,
             HandlerError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
             HandlerError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
             HandlerError::NotImplemented => StatusCode::NOT_IMPLEMENTED,
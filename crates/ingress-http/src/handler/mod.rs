@@ -21,15 +21,19 @@ mod tracing;
 mod workflow;
 
 use std::convert::Infallible;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use ::tracing::warn;
 use error::HandlerError;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use http_body_util::Full;
 use hyper::http::HeaderValue;
 use hyper::{Request, Response};
-use path_parsing::RequestType;
+use path_parsing::{AuthorizationTarget, RequestType};
+
+use crate::layers::authentication::Principal;
 
 use restate_ingress_dispatcher::DispatchIngressRequest;
 use restate_types::live::Live;
@@ -45,6 +49,7 @@ pub(crate) struct Handler<Schemas, Dispatcher, StorageReader> {
     schemas: Live<Schemas>,
     dispatcher: Dispatcher,
     storage_reader: StorageReader,
+    callback_allowed_hosts: Arc<[String]>,
 }
 
 impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageReader> {
@@ -52,11 +57,13 @@ impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageRea
         schemas: Live<Schemas>,
         dispatcher: Dispatcher,
         storage_reader: StorageReader,
+        callback_allowed_hosts: Arc<[String]>,
     ) -> Self {
         Self {
             schemas,
             dispatcher,
             storage_reader,
+            callback_allowed_hosts,
         }
     }
 }
@@ -84,7 +91,14 @@ where
 
         let mut this = self.clone();
         async move {
-            match res? {
+            let request_type = res?;
+
+            // Check the allow rules for the principal authenticated by the AuthenticationLayer
+            if let Some(principal) = req.extensions().get::<Principal>() {
+                this.authorize(principal, &request_type).await?;
+            }
+
+            match request_type {
                 RequestType::Health => this.handle_health(req),
                 RequestType::OpenAPI => {
                     // TODO
@@ -108,3 +122,52 @@ where
         .boxed()
     }
 }
+
+impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageReader>
+where
+    StorageReader: InvocationStorageReader + Clone + Send + Sync + 'static,
+{
+    async fn authorize(
+        &self,
+        principal: &Principal,
+        request_type: &RequestType,
+    ) -> Result<(), HandlerError> {
+        let is_allowed = match request_type.authorization_target() {
+            None => true,
+            Some(AuthorizationTarget::Handler(service, handler)) => {
+                principal.is_allowed(service, handler)
+            }
+            Some(AuthorizationTarget::Invocation(id)) => {
+                let invocation_id = id
+                    .parse()
+                    .map_err(|e| HandlerError::BadInvocationId(id.to_owned(), e))?;
+                match self
+                    .storage_reader
+                    .get_invocation_target(invocation_id)
+                    .await
+                {
+                    Ok(Some(invocation_target)) => principal.is_allowed(
+                        invocation_target.service_name(),
+                        Some(invocation_target.handler_name()),
+                    ),
+                    // Don't disclose which invocations exist to restricted principals
+                    Ok(None) => principal.is_unrestricted(),
+                    Err(e) => {
+                        warn!(
+                            restate.invocation.id = %invocation_id,
+                            "Failed to read the invocation target: {}",
+                            e,
+                        );
+                        return Err(HandlerError::Unavailable);
+                    }
+                }
+            }
+        };
+
+        if is_allowed {
+            Ok(())
+        } else {
+            Err(HandlerError::Forbidden)
+        }
+    }
+}
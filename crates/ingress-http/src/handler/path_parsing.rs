@@ -19,6 +19,13 @@ use crate::InvocationStorageReader;
 pub(crate) enum WorkflowRequestType {
     Attach(String, String),
     GetOutput(String, String),
+    Promise(String, String, String, PromiseRequestType),
+}
+
+pub(crate) enum PromiseRequestType {
+    Resolve,
+    Reject,
+    Peek,
 }
 
 impl WorkflowRequestType {
@@ -39,6 +46,25 @@ impl WorkflowRequestType {
         match path_parts.next().ok_or(HandlerError::BadWorkflowPath)? {
             "output" => Ok(WorkflowRequestType::GetOutput(workflow_name, workflow_key)),
             "attach" => Ok(WorkflowRequestType::Attach(workflow_name, workflow_key)),
+            "promise" => {
+                let promise_name =
+                    urlencoding::decode(path_parts.next().ok_or(HandlerError::BadWorkflowPath)?)
+                        .map_err(HandlerError::UrlDecodingError)?
+                        .into_owned();
+                let promise_request_type =
+                    match path_parts.next().ok_or(HandlerError::BadWorkflowPath)? {
+                        "resolve" => PromiseRequestType::Resolve,
+                        "reject" => PromiseRequestType::Reject,
+                        "peek" => PromiseRequestType::Peek,
+                        _ => return Err(HandlerError::NotFound),
+                    };
+                Ok(WorkflowRequestType::Promise(
+                    workflow_name,
+                    workflow_key,
+                    promise_name,
+                    promise_request_type,
+                ))
+            }
             _ => Err(HandlerError::NotFound),
         }
     }
@@ -124,6 +150,7 @@ impl InvocationRequestType {
 }
 
 pub(crate) enum AwakeableRequestType {
+    Get { awakeable_id: String },
     Resolve { awakeable_id: String },
     Reject { awakeable_id: String },
 }
@@ -138,10 +165,11 @@ impl AwakeableRequestType {
             .ok_or(HandlerError::BadAwakeablesPath)?
             .to_string();
 
-        // Resolve or reject
-        match path_parts.next().ok_or(HandlerError::BadAwakeablesPath)? {
-            "resolve" => Ok(AwakeableRequestType::Resolve { awakeable_id }),
-            "reject" => Ok(AwakeableRequestType::Reject { awakeable_id }),
+        // Get, resolve or reject
+        match path_parts.next() {
+            None => Ok(AwakeableRequestType::Get { awakeable_id }),
+            Some("resolve") => Ok(AwakeableRequestType::Resolve { awakeable_id }),
+            Some("reject") => Ok(AwakeableRequestType::Reject { awakeable_id }),
             _ => Err(HandlerError::NotFound),
         }
     }
@@ -223,6 +251,54 @@ pub(crate) enum RequestType {
     Workflow(WorkflowRequestType),
 }
 
+/// Target of a request, to check against the ingress allow rules.
+pub(crate) enum AuthorizationTarget<'a> {
+    /// Service and handler, or every handler of the service if unset.
+    Handler(&'a str, Option<&'a str>),
+    /// Invocation addressed by its id, checked against the target of the invocation.
+    Invocation(&'a str),
+}
+
+impl RequestType {
+    /// Target to check against the ingress allow rules. The awakeables API is checked as handler of
+    /// the `restate` service, the invocations addressed by id are checked against their target.
+    /// Workflow promises are checked as the handlers `promise:resolve`, `promise:reject` and
+    /// `promise:peek` of the workflow service, the other workflow requests against the workflow
+    /// service only.
+    pub(crate) fn authorization_target(&self) -> Option<AuthorizationTarget<'_>> {
+        match self {
+            RequestType::Health | RequestType::OpenAPI => None,
+            RequestType::Awakeable(_) => {
+                Some(AuthorizationTarget::Handler("restate", Some("awakeables")))
+            }
+            RequestType::Invocation(
+                InvocationRequestType::Attach(target) | InvocationRequestType::GetOutput(target),
+            ) => match target {
+                InvocationTargetType::InvocationId(id) => {
+                    Some(AuthorizationTarget::Invocation(id.as_str()))
+                }
+                InvocationTargetType::IdempotencyId { name, handler, .. } => Some(
+                    AuthorizationTarget::Handler(name.as_str(), Some(handler.as_str())),
+                ),
+            },
+            RequestType::Service(ServiceRequestType { name, handler, .. }) => Some(
+                AuthorizationTarget::Handler(name.as_str(), Some(handler.as_str())),
+            ),
+            RequestType::Workflow(WorkflowRequestType::Promise(name, _, _, request_type)) => {
+                let handler = match request_type {
+                    PromiseRequestType::Resolve => "promise:resolve",
+                    PromiseRequestType::Reject => "promise:reject",
+                    PromiseRequestType::Peek => "promise:peek",
+                };
+                Some(AuthorizationTarget::Handler(name.as_str(), Some(handler)))
+            }
+            RequestType::Workflow(
+                WorkflowRequestType::Attach(name, _) | WorkflowRequestType::GetOutput(name, _),
+            ) => Some(AuthorizationTarget::Handler(name.as_str(), None)),
+        }
+    }
+}
+
 impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageReader>
 where
     Schemas: ServiceMetadataResolver + Clone + Send + Sync + 'static,
@@ -21,10 +21,11 @@ use serde_with::serde_as;
 use tracing::{info, trace, warn, Instrument};
 
 use restate_ingress_dispatcher::{DispatchIngressRequest, IngressDispatcherRequest};
+use restate_types::config::is_callback_host_allowed;
 use restate_types::identifiers::InvocationId;
 use restate_types::invocation::{
-    Header, InvocationTarget, InvocationTargetType, ServiceInvocation, Source, SpanRelation,
-    WorkflowHandlerType,
+    Header, InvocationTarget, InvocationTargetType, ServiceInvocation,
+    ServiceInvocationResponseSink, Source, SpanRelation, WorkflowHandlerType,
 };
 use restate_types::schema::invocation_target::{
     InvocationTargetMetadata, InvocationTargetResolver,
@@ -38,6 +39,9 @@ use crate::handler::responses::{IDEMPOTENCY_EXPIRES, X_RESTATE_ID};
 use crate::metric_definitions::{INGRESS_REQUESTS, INGRESS_REQUEST_DURATION, REQUEST_COMPLETED};
 
 pub(crate) const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Url where the invocation result of a send should be delivered
+pub(crate) const X_RESTATE_CALLBACK_URL: HeaderName =
+    HeaderName::from_static("x-restate-callback-url");
 const DELAY_QUERY_PARAM: &str = "delay";
 
 #[derive(Debug, Serialize)]
@@ -170,6 +174,9 @@ where
                 &body,
             )?;
 
+            // Check if the callback url is available
+            let callback_url = parse_callback_url(&parts.headers, &self.callback_allowed_hosts)?;
+
             // Get headers
             let headers = parse_headers(parts.headers)?;
 
@@ -193,6 +200,9 @@ where
                     if delay.is_some() {
                         return Err(HandlerError::UnsupportedDelay);
                     }
+                    if callback_url.is_some() {
+                        return Err(HandlerError::UnsupportedCallbackUrl);
+                    }
                     Self::handle_service_call(
                         service_invocation,
                         invocation_target_meta,
@@ -203,6 +213,8 @@ where
                 InvokeType::Send => {
                     service_invocation.execution_time =
                         delay.map(|d| SystemTime::now() + d).map(Into::into);
+                    service_invocation.response_sink =
+                        callback_url.map(ServiceInvocationResponseSink::callback);
 
                     Self::handle_service_send(service_invocation, self.dispatcher).await
                 }
@@ -322,12 +334,13 @@ fn parse_headers(headers: HeaderMap) -> Result<Vec<Header>, HandlerError> {
     headers
         .into_iter()
         .filter_map(|(k, v)| k.map(|k| (k, v)))
-        // Filter out Connection, Host and idempotency headers
+        // Filter out Connection, Host, idempotency and callback headers
         .filter(|(k, _)| {
             k != header::CONNECTION
                 && k != header::HOST
                 && k != IDEMPOTENCY_KEY
                 && k != IDEMPOTENCY_EXPIRES
+                && k != X_RESTATE_CALLBACK_URL
         })
         .map(|(k, v)| {
             let value = v
@@ -377,10 +390,67 @@ fn parse_idempotency(headers: &HeaderMap) -> Result<Option<ByteString>, HandlerE
     Ok(Some(idempotency_key))
 }
 
+fn parse_callback_url(
+    headers: &HeaderMap,
+    allowed_hosts: &[String],
+) -> Result<Option<ByteString>, HandlerError> {
+    let callback_url = if let Some(callback_url) = headers.get(X_RESTATE_CALLBACK_URL) {
+        callback_url
+            .to_str()
+            .map_err(|e| HandlerError::BadHeader(X_RESTATE_CALLBACK_URL, e))?
+    } else {
+        return Ok(None);
+    };
+
+    let url = url::Url::parse(callback_url)
+        .map_err(|e| HandlerError::BadCallbackUrl(callback_url.to_owned(), e.to_string()))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(HandlerError::BadCallbackUrl(
+            callback_url.to_owned(),
+            "only http and https urls are supported".to_owned(),
+        ));
+    }
+    if !is_callback_host_allowed(
+        allowed_hosts,
+        url.host_str().unwrap_or_default(),
+        url.port_or_known_default().unwrap_or_default(),
+    ) {
+        return Err(HandlerError::BadCallbackUrl(
+            callback_url.to_owned(),
+            "the host is not in the allowed callback hosts of the ingress".to_owned(),
+        ));
+    }
+
+    Ok(Some(ByteString::from(callback_url)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn callback_url(url: &'static str, allowed_hosts: &[&str]) -> Result<(), HandlerError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_RESTATE_CALLBACK_URL, http::HeaderValue::from_static(url));
+        let allowed_hosts = allowed_hosts
+            .iter()
+            .map(|host| host.to_string())
+            .collect::<Vec<_>>();
+        parse_callback_url(&headers, &allowed_hosts).map(|_| ())
+    }
+
+    #[test]
+    fn callback_url_allowed_hosts() {
+        assert!(callback_url("https://example.com/hooks", &["example.com"]).is_ok());
+        assert!(callback_url("https://EXAMPLE.com:443/hooks", &["example.com:443"]).is_ok());
+        assert!(callback_url("http://example.com:8080/hooks", &["example.com:8080"]).is_ok());
+
+        // callback urls are disabled without allowed hosts
+        assert!(callback_url("https://example.com/hooks", &[]).is_err());
+        assert!(callback_url("http://169.254.169.254/latest", &["example.com"]).is_err());
+        assert!(callback_url("http://example.com:8080/hooks", &["example.com:443"]).is_err());
+        assert!(callback_url("https://example.com.evil.net/hooks", &["example.com"]).is_err());
+    }
+
     #[test]
     fn delay() {
         assert_eq!(
@@ -9,6 +9,7 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use bytes::Bytes;
@@ -26,12 +27,14 @@ use restate_core::TestCoreEnv;
 use restate_ingress_dispatcher::test_util::MockDispatcher;
 use restate_ingress_dispatcher::IngressDispatcherRequest;
 use restate_ingress_dispatcher::{IngressInvocationResponse, SubmittedInvocationNotification};
+use restate_service_protocol::awakeable_id::AwakeableIdentifier;
 use restate_test_util::{assert, assert_eq};
+use restate_types::errors::ALREADY_COMPLETED_INVOCATION_ERROR;
 use restate_types::identifiers::{IdempotencyId, InvocationId, ServiceId};
 use restate_types::ingress::{IngressResponseResult, InvocationResponse};
 use restate_types::invocation::{
-    Header, InvocationQuery, InvocationTarget, InvocationTargetType, VirtualObjectHandlerType,
-    WorkflowHandlerType,
+    Header, InvocationQuery, InvocationTarget, InvocationTargetType, ResponseResult,
+    ServiceInvocationResponseSink, VirtualObjectHandlerType, WorkflowHandlerType,
 };
 use restate_types::schema::invocation_target::{
     InputContentType, InputRules, InputValidationRule, InvocationTargetMetadata,
@@ -297,6 +300,80 @@ async fn send_with_delay_service() {
     let _: SendResponse = serde_json::from_slice(&response_bytes).unwrap();
 }
 
+#[tokio::test]
+#[traced_test]
+async fn send_with_callback_url() {
+    let greeting_req = GreetingRequest {
+        person: "Francesco".to_string(),
+    };
+
+    let req = hyper::Request::builder()
+        .uri("http://localhost/greeter.Greeter/greet/send")
+        .method(Method::POST)
+        .header("content-type", "application/json")
+        .header(
+            X_RESTATE_CALLBACK_URL,
+            "https://example.com/callback?token=abc",
+        )
+        .body(Full::new(Bytes::from(
+            serde_json::to_vec(&greeting_req).unwrap(),
+        )))
+        .unwrap();
+
+    let response = handle(req, |ingress_req| {
+        // Get the function invocation and assert on it
+        let service_invocation = ingress_req.expect_one_way_invocation();
+        assert_eq!(
+            service_invocation.response_sink,
+            Some(ServiceInvocationResponseSink::callback(
+                "https://example.com/callback?token=abc"
+            ))
+        );
+        assert!(!service_invocation
+            .headers
+            .iter()
+            .any(|h| h.name.eq_ignore_ascii_case(X_RESTATE_CALLBACK_URL.as_str())));
+    })
+    .await;
+
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn bad_callback_url() {
+    let greeting_req = GreetingRequest {
+        person: "Francesco".to_string(),
+    };
+
+    let response = handle(
+        hyper::Request::post("http://localhost/greeter.Greeter/greet/send")
+            .header("content-type", "application/json")
+            .header(X_RESTATE_CALLBACK_URL, "ftp://example.com/callback")
+            .body(Full::new(Bytes::from(
+                serde_json::to_vec(&greeting_req).unwrap(),
+            )))
+            .unwrap(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Callback urls are supported only with sends
+    let response = handle(
+        hyper::Request::post("http://localhost/greeter.Greeter/greet")
+            .header("content-type", "application/json")
+            .header(X_RESTATE_CALLBACK_URL, "https://example.com/callback")
+            .body(Full::new(Bytes::from(
+                serde_json::to_vec(&greeting_req).unwrap(),
+            )))
+            .unwrap(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 #[traced_test]
 async fn send_virtual_object() {
@@ -784,6 +861,170 @@ async fn get_output_with_workflow_key() {
     assert_eq!(response_value.greeting, "Igal");
 }
 
+fn mock_workflow_schemas() -> MockSchemas {
+    MockSchemas::default().with_service_and_target(
+        "MyWorkflow",
+        "run",
+        InvocationTargetMetadata::mock(InvocationTargetType::Workflow(
+            WorkflowHandlerType::Workflow,
+        )),
+    )
+}
+
+fn resolve_promise_request(service_name: &str) -> Request<Full<Bytes>> {
+    hyper::Request::builder()
+        .uri(format!(
+            "http://localhost/restate/workflow/{service_name}/my-key/promise/approval/resolve"
+        ))
+        .method(Method::POST)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from_static(b"true")))
+        .unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn resolve_workflow_promise() {
+    let response = handle_with_schemas(
+        resolve_promise_request("MyWorkflow"),
+        mock_workflow_schemas(),
+        |ingress_req| {
+            let (complete_promise_request, response_tx) = ingress_req.expect_complete_promise();
+            assert_eq!(
+                complete_promise_request.service_id,
+                ServiceId::new("MyWorkflow", "my-key")
+            );
+            assert_eq!(complete_promise_request.key, "approval");
+            assert_eq!(
+                complete_promise_request.result,
+                ResponseResult::Success(Bytes::from_static(b"true"))
+            );
+            response_tx
+                .send(IngressInvocationResponse {
+                    idempotency_expiry_time: None,
+                    result: IngressResponseResult::Success(
+                        InvocationTarget::workflow(
+                            "MyWorkflow",
+                            "my-key",
+                            "approval",
+                            WorkflowHandlerType::Shared,
+                        ),
+                        Bytes::new(),
+                    ),
+                    invocation_id: None,
+                })
+                .unwrap();
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn resolve_already_completed_workflow_promise() {
+    let response = handle_with_schemas(
+        resolve_promise_request("MyWorkflow"),
+        mock_workflow_schemas(),
+        |ingress_req| {
+            let (_, response_tx) = ingress_req.expect_complete_promise();
+            response_tx
+                .send(IngressInvocationResponse {
+                    idempotency_expiry_time: None,
+                    result: IngressResponseResult::Failure(ALREADY_COMPLETED_INVOCATION_ERROR),
+                    invocation_id: None,
+                })
+                .unwrap();
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn resolve_promise_of_unknown_or_non_workflow_service() {
+    let response = handle_with_schemas(
+        resolve_promise_request("UnknownWorkflow"),
+        mock_workflow_schemas(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = handle(
+        resolve_promise_request("greeter.GreeterObject"),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = handle(
+        hyper::Request::get(
+            "http://localhost/restate/workflow/greeter.GreeterObject/my-key/promise/approval/peek",
+        )
+        .body(Empty::<Bytes>::default())
+        .unwrap(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn resolve_or_peek_promise_of_private_workflow() {
+    let private_workflow_schemas = || {
+        MockSchemas::default().with_service_and_target(
+            "MyWorkflow",
+            "run",
+            InvocationTargetMetadata {
+                public: false,
+                ..InvocationTargetMetadata::mock(InvocationTargetType::Workflow(
+                    WorkflowHandlerType::Workflow,
+                ))
+            },
+        )
+    };
+
+    let response = handle_with_schemas(
+        resolve_promise_request("MyWorkflow"),
+        private_workflow_schemas(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = handle_with_schemas(
+        hyper::Request::get(
+            "http://localhost/restate/workflow/MyWorkflow/my-key/promise/approval/peek",
+        )
+        .body(Empty::<Bytes>::default())
+        .unwrap(),
+        private_workflow_schemas(),
+        request_handler_not_reached,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn get_unknown_awakeable() {
+    let awakeable_id = AwakeableIdentifier::new(InvocationId::mock_random(), 1);
+    let req = hyper::Request::get(format!(
+        "http://localhost/restate/awakeables/{awakeable_id}"
+    ))
+    .body(Empty::<Bytes>::default())
+    .unwrap();
+
+    let response = handle(req, |_| panic!("This should not be called")).await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 #[traced_test]
 async fn bad_path_service() {
@@ -1098,6 +1339,7 @@ where
             Live::from_value(schemas),
             dispatcher,
             invocation_storage_reader,
+            Arc::from(["example.com".to_owned()]),
         )
         .oneshot(req),
     );
@@ -9,24 +9,30 @@
 // by the Apache License, Version 2.0.
 
 use bytes::Bytes;
-use http::{Method, Request, Response};
-use http_body_util::Full;
-use tracing::{info, warn};
+use bytestring::ByteString;
+use http::{Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use tracing::{info, trace, warn};
 
 use restate_ingress_dispatcher::DispatchIngressRequest;
 use restate_ingress_dispatcher::IngressDispatcherRequest;
+use restate_types::errors::{codes, InvocationError};
 use restate_types::identifiers::ServiceId;
-use restate_types::invocation::InvocationQuery;
+use restate_types::ingress::IngressResponseResult;
+use restate_types::invocation::{
+    CompletePromiseRequest, InvocationQuery, ResponseResult, ServiceType,
+};
 use restate_types::schema::invocation_target::InvocationTargetResolver;
+use restate_types::schema::service::ServiceMetadataResolver;
 
-use super::path_parsing::WorkflowRequestType;
+use super::path_parsing::{PromiseRequestType, WorkflowRequestType};
 use super::Handler;
 use super::HandlerError;
-use crate::{GetOutputResult, InvocationStorageReader};
+use crate::{GetOutputResult, InvocationStorageReader, PeekPromiseResult};
 
 impl<Schemas, Dispatcher, StorageReader> Handler<Schemas, Dispatcher, StorageReader>
 where
-    Schemas: InvocationTargetResolver + Clone + Send + Sync + 'static,
+    Schemas: ServiceMetadataResolver + InvocationTargetResolver + Clone + Send + Sync + 'static,
     Dispatcher: DispatchIngressRequest + Clone + Send + Sync + 'static,
     StorageReader: InvocationStorageReader + Clone + Send + Sync + 'static,
 {
@@ -47,6 +53,155 @@ where
                 self.handle_workflow_get_output(req, ServiceId::new(name, key))
                     .await
             }
+            WorkflowRequestType::Promise(name, key, promise_name, PromiseRequestType::Peek) => {
+                self.handle_workflow_peek_promise(
+                    req,
+                    ServiceId::new(name, key),
+                    promise_name.into(),
+                )
+                .await
+            }
+            WorkflowRequestType::Promise(name, key, promise_name, promise_request_type) => {
+                self.handle_workflow_complete_promise(
+                    req,
+                    ServiceId::new(name, key),
+                    promise_name.into(),
+                    promise_request_type,
+                )
+                .await
+            }
+        }
+    }
+
+    pub(crate) async fn handle_workflow_complete_promise<B: http_body::Body>(
+        self,
+        req: Request<B>,
+        workflow_id: ServiceId,
+        promise_name: ByteString,
+        promise_request_type: PromiseRequestType,
+    ) -> Result<Response<Full<Bytes>>, HandlerError>
+    where
+        <B as http_body::Body>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        // Check HTTP Method
+        if req.method() != Method::POST {
+            return Err(HandlerError::MethodNotAllowed);
+        }
+        self.check_is_workflow(&workflow_id)?;
+
+        // Collect body
+        let collected_request_bytes = req
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| HandlerError::Body(e.into()))?
+            .to_bytes();
+        trace!(rpc.request = ?collected_request_bytes);
+
+        let result = match promise_request_type {
+            PromiseRequestType::Reject => ResponseResult::from(Err(InvocationError::new(
+                codes::UNKNOWN,
+                String::from_utf8_lossy(&collected_request_bytes).to_string(),
+            ))),
+            _ => ResponseResult::from(Ok(collected_request_bytes)),
+        };
+
+        info!(
+            restate.workflow.id = %workflow_id,
+            "Processing workflow promise completion request"
+        );
+
+        let (dispatcher_req, correlation_id, response_rx) =
+            IngressDispatcherRequest::complete_promise(CompletePromiseRequest {
+                service_id: workflow_id.clone(),
+                key: promise_name,
+                result,
+                response_sink: None,
+            });
+        if let Err(e) = self
+            .dispatcher
+            .dispatch_ingress_request(dispatcher_req)
+            .await
+        {
+            warn!(
+                restate.workflow.id = %workflow_id,
+                "Failed to dispatch promise completion: {}",
+                e,
+            );
+            return Err(HandlerError::Unavailable);
+        }
+
+        // Wait for the partition processor to accept the completion
+        let response = if let Ok(response) = response_rx.await {
+            response
+        } else {
+            self.dispatcher.evict_pending_response(correlation_id);
+            warn!("Response channel was closed");
+            return Err(HandlerError::Unavailable);
+        };
+
+        match response.result {
+            IngressResponseResult::Success(_, _) => Ok(hyper::Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .body(Full::default())
+                .unwrap()),
+            // The promise was already completed
+            IngressResponseResult::Failure(error) => Err(HandlerError::Invocation(error)),
+        }
+    }
+
+    pub(crate) async fn handle_workflow_peek_promise<B: http_body::Body>(
+        self,
+        req: Request<B>,
+        workflow_id: ServiceId,
+        promise_name: ByteString,
+    ) -> Result<Response<Full<Bytes>>, HandlerError>
+    where
+        <B as http_body::Body>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        // Check HTTP Method
+        if req.method() != Method::GET {
+            return Err(HandlerError::MethodNotAllowed);
+        }
+        self.check_is_workflow(&workflow_id)?;
+
+        match self
+            .storage_reader
+            .peek_promise(workflow_id.clone(), promise_name)
+            .await
+        {
+            Ok(PeekPromiseResult::Completed(ResponseResult::Success(value))) => {
+                Ok(hyper::Response::builder().body(Full::new(value)).unwrap())
+            }
+            Ok(PeekPromiseResult::Completed(ResponseResult::Failure(error))) => {
+                Ok(HandlerError::Invocation(error).fill_builder(hyper::Response::builder()))
+            }
+            Ok(PeekPromiseResult::NotCompleted) => Err(HandlerError::NotReady),
+            Err(e) => {
+                warn!(
+                    restate.workflow.id = %workflow_id,
+                    "Failed to read promise: {}",
+                    e,
+                );
+                Err(HandlerError::Unavailable)
+            }
+        }
+    }
+
+    /// Promises exist only for registered workflows, and are accessible through the ingress only
+    /// if the workflow is public.
+    fn check_is_workflow(&self, workflow_id: &ServiceId) -> Result<(), HandlerError> {
+        match self
+            .schemas
+            .pinned()
+            .resolve_latest_service(&workflow_id.service_name)
+        {
+            Some(service) if service.ty != ServiceType::Workflow => Err(
+                HandlerError::NotAWorkflow(workflow_id.service_name.to_string()),
+            ),
+            Some(service) if !service.public => Err(HandlerError::PrivateService),
+            Some(_) => Ok(()),
+            None => Err(HandlerError::NotFound),
         }
     }
 
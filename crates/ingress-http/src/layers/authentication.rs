@@ -0,0 +1,367 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future::{ready, Either, Ready};
+use http::{header, HeaderName, HeaderValue, Request, Response, StatusCode};
+use serde_json::{Map, Value};
+use tower::{Layer, Service};
+
+use restate_types::config::{IngressAllowRule, IngressAuthenticationOptions};
+use restate_types::jwt::{constant_time_eq, JwksError, JwtVerifier};
+
+/// Header containing the authenticated principal, forwarded to the invoked service.
+pub(crate) const X_RESTATE_PRINCIPAL: HeaderName = HeaderName::from_static("x-restate-principal");
+
+const HEALTH_PATH: &str = "/restate/health";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthenticationError {
+    #[error("invalid 'ingress.authentication.jwks-file': {0}")]
+    Jwks(#[from] JwksError),
+}
+
+/// The principal authenticated by the [`AuthenticationLayer`], available as request extension.
+#[derive(Debug, Clone)]
+pub(crate) struct Principal {
+    name: String,
+    claims: Map<String, Value>,
+    rules: Arc<[IngressAllowRule]>,
+}
+
+impl Principal {
+    /// Returns true if one of the allow rules grants this principal access to the given target.
+    pub(crate) fn is_allowed(&self, service: &str, handler: Option<&str>) -> bool {
+        self.is_unrestricted()
+            || self
+                .rules
+                .iter()
+                .any(|rule| rule.matches_target(service, handler) && self.matches_principal(rule))
+    }
+
+    /// Returns true if no allow rules are configured, granting access to every target.
+    pub(crate) fn is_unrestricted(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn matches_principal(&self, rule: &IngressAllowRule) -> bool {
+        (rule.principals.is_empty() || rule.principals.contains(&self.name))
+            && rule
+                .claims
+                .iter()
+                .all(|(claim, expected)| match self.claims.get(claim) {
+                    Some(Value::String(value)) => {
+                        value == expected || value.split(' ').any(|v| v == expected)
+                    }
+                    Some(Value::Array(values)) => {
+                        values.iter().any(|v| v.as_str() == Some(expected.as_str()))
+                    }
+                    _ => false,
+                })
+    }
+}
+
+struct Authenticator {
+    api_keys: Vec<(String, String)>,
+    jwt_verifier: Option<JwtVerifier>,
+    principal_claim: String,
+    rules: Arc<[IngressAllowRule]>,
+}
+
+impl Authenticator {
+    fn from_options(options: &IngressAuthenticationOptions) -> Result<Self, AuthenticationError> {
+        let jwt_verifier = options
+            .jwks_file
+            .as_ref()
+            .map(|path| {
+                JwtVerifier::from_jwks_file(
+                    path,
+                    options.jwt_issuer.clone(),
+                    options.jwt_audience.clone(),
+                )
+            })
+            .transpose()?;
+
+        Ok(Self {
+            api_keys: options
+                .api_keys
+                .iter()
+                .map(|api_key| (api_key.key.clone(), api_key.principal.clone()))
+                .collect(),
+            jwt_verifier,
+            principal_claim: options.principal_claim().to_owned(),
+            rules: options.rules.clone().into(),
+        })
+    }
+
+    fn authenticate(&self, token: &str) -> Option<Principal> {
+        if let Some((_, principal)) = self
+            .api_keys
+            .iter()
+            .find(|(key, _)| constant_time_eq(key.as_bytes(), token.as_bytes()))
+        {
+            return Some(Principal {
+                name: principal.clone(),
+                claims: Map::new(),
+                rules: self.rules.clone(),
+            });
+        }
+
+        let claims = self.jwt_verifier.as_ref()?.verify(token)?;
+        let name = claims.get(&self.principal_claim)?.as_str()?.to_owned();
+        Some(Principal {
+            name,
+            claims,
+            rules: self.rules.clone(),
+        })
+    }
+}
+
+/// Layer authenticating the requests with bearer tokens, either static API keys or JWTs.
+///
+/// Unauthenticated requests are rejected with `401 Unauthorized`. For authenticated requests,
+/// the [`Principal`] is added to the request extensions, to check the allow rules once the
+/// request target is known, and forwarded in the [`X_RESTATE_PRINCIPAL`] header.
+/// When no authentication is configured, every request is passed through.
+#[derive(Clone)]
+pub struct AuthenticationLayer {
+    authenticator: Option<Arc<Authenticator>>,
+}
+
+impl AuthenticationLayer {
+    pub fn new(
+        options: Option<&IngressAuthenticationOptions>,
+    ) -> Result<Self, AuthenticationError> {
+        Ok(Self {
+            authenticator: options
+                .map(Authenticator::from_options)
+                .transpose()?
+                .map(Arc::new),
+        })
+    }
+}
+
+impl<S> Layer<S> for AuthenticationLayer {
+    type Service = Authentication<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Authentication {
+            inner,
+            authenticator: self.authenticator.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Authentication<S> {
+    inner: S,
+    authenticator: Option<Arc<Authenticator>>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Authentication<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<Response<ResBody>, S::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // Never trust the principal header sent by the client
+        req.headers_mut().remove(X_RESTATE_PRINCIPAL);
+
+        let Some(authenticator) = &self.authenticator else {
+            return Either::Left(self.inner.call(req));
+        };
+        if req.uri().path() == HEALTH_PATH {
+            return Either::Left(self.inner.call(req));
+        }
+
+        let principal = req
+            .headers_mut()
+            .remove(header::AUTHORIZATION)
+            .and_then(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|v| v.strip_prefix("Bearer "))
+                    .and_then(|token| authenticator.authenticate(token.trim()))
+            });
+
+        let Some(principal) = principal else {
+            let mut response = Response::new(ResBody::default());
+            *response.status_mut() = StatusCode::UNAUTHORIZED;
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            return Either::Right(ready(Ok(response)));
+        };
+
+        match HeaderValue::try_from(principal.name.as_str()) {
+            Ok(value) => {
+                req.headers_mut().insert(X_RESTATE_PRINCIPAL, value);
+            }
+            Err(_) => {
+                let mut response = Response::new(ResBody::default());
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                return Either::Right(ready(Ok(response)));
+            }
+        }
+        req.extensions_mut().insert(principal);
+
+        Either::Left(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+
+    use restate_types::config::IngressApiKey;
+    use tower::ServiceExt;
+
+    fn options(rules: Vec<IngressAllowRule>) -> IngressAuthenticationOptions {
+        IngressAuthenticationOptions {
+            api_keys: vec![IngressApiKey {
+                principal: "billing".to_owned(),
+                key: "secret".to_owned(),
+            }],
+            rules,
+            ..Default::default()
+        }
+    }
+
+    fn rule(service: &str, handler: Option<&str>, principals: &[&str]) -> IngressAllowRule {
+        IngressAllowRule {
+            service: service.to_owned(),
+            handler: handler.map(ToOwned::to_owned),
+            principals: principals.iter().map(|p| p.to_string()).collect(),
+            claims: HashMap::new(),
+        }
+    }
+
+    async fn call(
+        layer: &AuthenticationLayer,
+        req: Request<()>,
+    ) -> (StatusCode, Option<Principal>, Option<HeaderValue>) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let response = layer
+            .layer(tower::service_fn(move |req: Request<()>| {
+                tx.send((
+                    req.extensions().get::<Principal>().cloned(),
+                    req.headers().get(X_RESTATE_PRINCIPAL).cloned(),
+                ))
+                .unwrap();
+                async { Ok::<_, Infallible>(Response::new(())) }
+            }))
+            .oneshot(req)
+            .await
+            .unwrap();
+        let (principal, header) = rx.try_recv().unwrap_or((None, None));
+        (response.status(), principal, header)
+    }
+
+    #[tokio::test]
+    async fn api_key_authentication() {
+        let layer = AuthenticationLayer::new(Some(&options(vec![]))).unwrap();
+
+        let (status, principal, header) = call(
+            &layer,
+            Request::post("/greeter/greet")
+                .header(header::AUTHORIZATION, "Bearer secret")
+                .header(X_RESTATE_PRINCIPAL, "admin")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(principal.unwrap().name, "billing");
+        assert_eq!(header.unwrap(), "billing");
+    }
+
+    #[tokio::test]
+    async fn missing_or_wrong_token_is_rejected() {
+        let layer = AuthenticationLayer::new(Some(&options(vec![]))).unwrap();
+
+        let (status, _, _) = call(&layer, Request::post("/greeter/greet").body(()).unwrap()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _, _) = call(
+            &layer,
+            Request::post("/greeter/greet")
+                .header(header::AUTHORIZATION, "Bearer wrong")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _, _) = call(&layer, Request::get(HEALTH_PATH).body(()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn disabled_authentication_strips_principal_header() {
+        let layer = AuthenticationLayer::new(None).unwrap();
+
+        let (status, principal, header) = call(
+            &layer,
+            Request::post("/greeter/greet")
+                .header(X_RESTATE_PRINCIPAL, "admin")
+                .body(())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(principal.is_none());
+        assert!(header.is_none());
+    }
+
+    #[test]
+    fn allow_rules() {
+        let principal = Principal {
+            name: "billing".to_owned(),
+            claims: serde_json::json!({"scope": "read write", "groups": ["ops"]})
+                .as_object()
+                .unwrap()
+                .clone(),
+            rules: vec![
+                rule("greeter", Some("greet"), &["billing"]),
+                rule("counter", None, &["other"]),
+                IngressAllowRule {
+                    claims: HashMap::from([("scope".to_owned(), "write".to_owned())]),
+                    ..rule("restate", Some("awakeables"), &[])
+                },
+                IngressAllowRule {
+                    claims: HashMap::from([("groups".to_owned(), "admin".to_owned())]),
+                    ..rule("*", None, &[])
+                },
+            ]
+            .into(),
+        };
+
+        assert!(principal.is_allowed("greeter", Some("greet")));
+        assert!(!principal.is_allowed("greeter", Some("other")));
+        assert!(!principal.is_allowed("greeter", None));
+        assert!(!principal.is_allowed("counter", Some("add")));
+        assert!(principal.is_allowed("restate", Some("awakeables")));
+        assert!(!principal.is_allowed("restate", Some("invocation")));
+    }
+}
@@ -0,0 +1,385 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::future::{ready, Either, Ready};
+use http::{header, HeaderName, HeaderValue, Request, Response, StatusCode};
+use metrics::counter;
+use parking_lot::Mutex;
+use tower::{Layer, Service};
+use tracing::debug;
+
+use restate_types::config::{IngressRateLimitRule, RateLimitKey};
+
+use crate::metric_definitions::{
+    INGRESS_RATE_LIMITED_REQUESTS, INGRESS_REQUESTS, REQUEST_DENIED_RATE_LIMIT,
+};
+use crate::ConnectInfo;
+
+/// Maximum number of buckets tracked per rule. Above it, the least recently used buckets get
+/// evicted, which resets them to full.
+const MAX_TRACKED_KEYS: usize = 10_000;
+const GENERATION_SIZE: usize = MAX_TRACKED_KEYS / 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RateLimitError {
+    #[error("bad header name '{1}' in rate limit rule '{0}': {2}")]
+    BadHeaderName(String, String, #[source] http::header::InvalidHeaderName),
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: f64, now: Instant) -> Self {
+        Self {
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant, rate: f64, capacity: f64) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(capacity);
+        self.last_refill = now;
+    }
+}
+
+/// Buckets of a rule, in two generations to evict the least recently used ones in constant time.
+/// Once the recent generation is full, it replaces the previous one, dropping the buckets which
+/// were not used since the last rotation.
+#[derive(Debug, Default)]
+struct Buckets {
+    recent: HashMap<String, TokenBucket>,
+    previous: HashMap<String, TokenBucket>,
+}
+
+impl Buckets {
+    /// Returns the bucket of the key, and the evicted generation if the generations were rotated.
+    /// The evicted generation should be dropped once the lock is released.
+    fn bucket(
+        &mut self,
+        key: &str,
+        now: Instant,
+        capacity: f64,
+    ) -> (&mut TokenBucket, Option<HashMap<String, TokenBucket>>) {
+        let mut evicted = None;
+        if !self.recent.contains_key(key) {
+            let bucket = self
+                .previous
+                .remove(key)
+                .unwrap_or_else(|| TokenBucket::full(capacity, now));
+            if self.recent.len() >= GENERATION_SIZE {
+                evicted = Some(mem::replace(
+                    &mut self.previous,
+                    mem::take(&mut self.recent),
+                ));
+            }
+            self.recent.insert(key.to_owned(), bucket);
+        }
+
+        (
+            self.recent
+                .get_mut(key)
+                .expect("bucket is in the recent generation"),
+            evicted,
+        )
+    }
+}
+
+enum Key {
+    ClientIp,
+    Header(HeaderName),
+    Service,
+}
+
+struct Limiter {
+    name: String,
+    key: Key,
+    rate: f64,
+    capacity: f64,
+    buckets: Mutex<Buckets>,
+}
+
+impl Limiter {
+    fn new(rule: &IngressRateLimitRule) -> Result<Self, RateLimitError> {
+        let key = match &rule.key {
+            RateLimitKey::ClientIp => Key::ClientIp,
+            RateLimitKey::Header { name } => {
+                Key::Header(HeaderName::try_from(name.as_str()).map_err(|e| {
+                    RateLimitError::BadHeaderName(rule.name.clone(), name.clone(), e)
+                })?)
+            }
+            RateLimitKey::Service => Key::Service,
+        };
+
+        Ok(Self {
+            name: rule.name.clone(),
+            key,
+            rate: f64::from(rule.requests_per_second.get()),
+            capacity: f64::from(rule.burst().get()),
+            buckets: Mutex::new(Buckets::default()),
+        })
+    }
+
+    fn extract_key<B>(&self, req: &Request<B>) -> Option<String> {
+        match &self.key {
+            Key::ClientIp => req
+                .extensions()
+                .get::<ConnectInfo>()
+                .map(|connect_info| connect_info.address().to_string()),
+            Key::Header(name) => req
+                .headers()
+                .get(name)
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned()),
+            Key::Service => req
+                .uri()
+                .path()
+                .split('/')
+                .nth(1)
+                .filter(|s| !s.is_empty())
+                .map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// Takes a token from the bucket of each rule, or none at all if one of the buckets is empty.
+/// In that case, returns the first rule rejecting the request and how long to wait for its next
+/// token. The buckets are locked in the order of the rules until all of them were checked.
+fn try_acquire_all<'a>(
+    keys: impl IntoIterator<Item = (&'a Limiter, String)>,
+    now: Instant,
+) -> Result<(), (&'a Limiter, Duration)> {
+    // Declared first, so that the evicted buckets are dropped after the locks are released
+    let mut evicted = Vec::new();
+    let mut locked = Vec::new();
+
+    for (limiter, key) in keys {
+        let mut buckets = limiter.buckets.lock();
+        let (bucket, evicted_generation) = buckets.bucket(&key, now, limiter.capacity);
+        evicted.extend(evicted_generation);
+        bucket.refill(now, limiter.rate, limiter.capacity);
+
+        if bucket.tokens < 1.0 {
+            return Err((
+                limiter,
+                Duration::from_secs_f64((1.0 - bucket.tokens) / limiter.rate),
+            ));
+        }
+        locked.push((buckets, key));
+    }
+
+    for (mut buckets, key) in locked {
+        buckets
+            .recent
+            .get_mut(&key)
+            .expect("bucket is in the recent generation")
+            .tokens -= 1.0;
+    }
+    Ok(())
+}
+
+/// Layer applying the configured token bucket rate limits. Each rule keeps a bucket per key
+/// (client IP, header value or target service), so a single client exceeding its quota doesn't
+/// affect the others. Rejected requests get `429 Too Many Requests` with a `Retry-After` header.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiters: Arc<[Limiter]>,
+}
+
+impl RateLimitLayer {
+    pub fn new(rules: &[IngressRateLimitRule]) -> Result<Self, RateLimitError> {
+        Ok(Self {
+            limiters: rules
+                .iter()
+                .map(Limiter::new)
+                .collect::<Result<Vec<_>, _>>()?
+                .into(),
+        })
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiters: self.limiters.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiters: Arc<[Limiter]>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RateLimit<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    ResBody: Default,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Either<S::Future, Ready<Result<Response<ResBody>, S::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let keys = self
+            .limiters
+            .iter()
+            .filter_map(|limiter| Some((limiter, limiter.extract_key(&req)?)));
+
+        if let Err((limiter, retry_after)) = try_acquire_all(keys, Instant::now()) {
+            debug!("Request rejected by the rate limit rule '{}'", limiter.name);
+
+            counter!(INGRESS_REQUESTS, "status" => REQUEST_DENIED_RATE_LIMIT).increment(1);
+            counter!(INGRESS_RATE_LIMITED_REQUESTS, "rule" => limiter.name.clone()).increment(1);
+
+            let mut response = Response::new(ResBody::default());
+            *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after.as_secs_f64().ceil() as u64),
+            );
+            return Either::Right(ready(Ok(response)));
+        }
+
+        Either::Left(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::num::NonZeroU32;
+
+    fn limiter(key: RateLimitKey, requests_per_second: u32, burst: u32) -> Limiter {
+        Limiter::new(&IngressRateLimitRule {
+            name: "test".to_owned(),
+            key,
+            requests_per_second: NonZeroU32::new(requests_per_second).unwrap(),
+            burst: NonZeroU32::new(burst),
+        })
+        .unwrap()
+    }
+
+    fn try_acquire(limiter: &Limiter, key: &str, now: Instant) -> Result<(), Duration> {
+        try_acquire_all([(limiter, key.to_owned())], now).map_err(|(_, retry_after)| retry_after)
+    }
+
+    #[test]
+    fn buckets_are_per_key() {
+        let limiter = limiter(RateLimitKey::ClientIp, 1, 2);
+        let now = Instant::now();
+
+        assert!(try_acquire(&limiter, "a", now).is_ok());
+        assert!(try_acquire(&limiter, "a", now).is_ok());
+        assert_eq!(try_acquire(&limiter, "a", now), Err(Duration::from_secs(1)));
+        assert!(try_acquire(&limiter, "b", now).is_ok());
+    }
+
+    #[test]
+    fn bucket_is_refilled() {
+        let limiter = limiter(RateLimitKey::ClientIp, 10, 1);
+        let now = Instant::now();
+
+        assert!(try_acquire(&limiter, "a", now).is_ok());
+        assert!(try_acquire(&limiter, "a", now).is_err());
+        assert!(try_acquire(&limiter, "a", now + Duration::from_millis(100)).is_ok());
+    }
+
+    #[test]
+    fn rejected_requests_take_no_tokens() {
+        let first = limiter(RateLimitKey::ClientIp, 1, 2);
+        let second = limiter(RateLimitKey::Service, 1, 1);
+        let now = Instant::now();
+
+        assert!(
+            try_acquire_all([(&first, "a".to_owned()), (&second, "s".to_owned())], now).is_ok()
+        );
+        // Rejected by the second rule, the first one keeps its last token
+        assert!(
+            try_acquire_all([(&first, "a".to_owned()), (&second, "s".to_owned())], now).is_err()
+        );
+        assert!(try_acquire(&first, "a", now).is_ok());
+        assert!(try_acquire(&first, "a", now).is_err());
+    }
+
+    #[test]
+    fn least_recently_used_buckets_are_evicted() {
+        let limiter = limiter(RateLimitKey::ClientIp, 1, 1);
+        let now = Instant::now();
+        let mut next_key = 0;
+        let mut fill_generation = || {
+            for _ in 0..GENERATION_SIZE {
+                assert!(try_acquire(&limiter, &next_key.to_string(), now).is_ok());
+                next_key += 1;
+            }
+        };
+
+        assert!(try_acquire(&limiter, "a", now).is_ok());
+        fill_generation();
+        // Still tracked after a rotation, since it was recently used
+        assert!(try_acquire(&limiter, "a", now).is_err());
+        fill_generation();
+        fill_generation();
+        {
+            let buckets = limiter.buckets.lock();
+            assert!(buckets.recent.len() + buckets.previous.len() <= MAX_TRACKED_KEYS);
+        }
+        // Evicted, it starts again with a full bucket
+        assert!(try_acquire(&limiter, "a", now).is_ok());
+    }
+
+    #[test]
+    fn extract_keys() {
+        let req = Request::post("/greeter/greet")
+            .header("x-api-key", "my-key")
+            .body(())
+            .unwrap();
+
+        assert_eq!(
+            limiter(RateLimitKey::Service, 1, 1).extract_key(&req),
+            Some("greeter".to_owned())
+        );
+        assert_eq!(
+            limiter(
+                RateLimitKey::Header {
+                    name: "x-api-key".to_owned()
+                },
+                1,
+                1
+            )
+            .extract_key(&req),
+            Some("my-key".to_owned())
+        );
+        assert_eq!(
+            limiter(RateLimitKey::ClientIp, 1, 1).extract_key(&req),
+            None
+        );
+    }
+}
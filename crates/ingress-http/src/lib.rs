@@ -16,8 +16,11 @@ mod server;
 pub use server::{HyperServerIngress, IngressServerError, StartSignal};
 
 use bytes::Bytes;
+use bytestring::ByteString;
+use restate_types::identifiers::{EntryIndex, InvocationId, ServiceId};
 use restate_types::ingress::InvocationResponse;
-use restate_types::invocation::InvocationQuery;
+use restate_types::invocation::{InvocationQuery, InvocationTarget, ResponseResult};
+use restate_types::time::MillisSinceEpoch;
 use std::net::{IpAddr, SocketAddr};
 
 /// Client connection information for a given RPC request
@@ -45,11 +48,42 @@ pub enum GetOutputResult {
     Ready(InvocationResponse),
 }
 
+pub enum PeekPromiseResult {
+    NotCompleted,
+    Completed(ResponseResult),
+}
+
+pub enum GetAwakeableResult {
+    NotFound,
+    Pending {
+        expiry_time: Option<MillisSinceEpoch>,
+    },
+    Completed(ResponseResult),
+}
+
 pub trait InvocationStorageReader {
     fn get_output(
         &self,
         query: InvocationQuery,
     ) -> impl std::future::Future<Output = Result<GetOutputResult, anyhow::Error>> + Send;
+
+    fn peek_promise(
+        &self,
+        workflow_id: ServiceId,
+        key: ByteString,
+    ) -> impl std::future::Future<Output = Result<PeekPromiseResult, anyhow::Error>> + Send;
+
+    fn get_awakeable(
+        &self,
+        invocation_id: InvocationId,
+        entry_index: EntryIndex,
+    ) -> impl std::future::Future<Output = Result<GetAwakeableResult, anyhow::Error>> + Send;
+
+    /// Returns the target of the invocation, or `None` if the invocation is unknown.
+    fn get_invocation_target(
+        &self,
+        invocation_id: InvocationId,
+    ) -> impl std::future::Future<Output = Result<Option<InvocationTarget>, anyhow::Error>> + Send;
 }
 
 // Contains some mocks we use in unit tests in this crate
@@ -61,7 +95,7 @@ mod mocks {
     use serde::{Deserialize, Serialize};
 
     use restate_types::identifiers::DeploymentId;
-    use restate_types::ingress::InvocationResponse;
+    use restate_types::ingress::{IngressResponseResult, InvocationResponse};
     use restate_types::invocation::{
         InvocationQuery, InvocationTargetType, ServiceType, VirtualObjectHandlerType,
     };
@@ -71,7 +105,7 @@ mod mocks {
     };
     use restate_types::schema::service::test_util::MockServiceMetadataResolver;
     use restate_types::schema::service::{
-        HandlerMetadata, ServiceMetadata, ServiceMetadataResolver,
+        EagerStatePolicy, HandlerMetadata, ServiceMetadata, ServiceMetadataResolver,
     };
 
     use super::*;
@@ -113,6 +147,7 @@ mod mocks {
                 public: invocation_target_metadata.public,
                 idempotency_retention: DEFAULT_IDEMPOTENCY_RETENTION.into(),
                 workflow_completion_retention: None,
+                eager_state: Default::default(),
             });
             self.1
                 .add(service_name, [(handler_name, invocation_target_metadata)]);
@@ -141,6 +176,13 @@ mod mocks {
             self.0.resolve_latest_service_type(service_name)
         }
 
+        fn resolve_latest_service_eager_state(
+            &self,
+            service_name: impl AsRef<str>,
+        ) -> Option<EagerStatePolicy> {
+            self.0.resolve_latest_service_eager_state(service_name)
+        }
+
         fn list_services(&self) -> Vec<ServiceMetadata> {
             self.0.list_services()
         }
@@ -188,5 +230,38 @@ mod mocks {
                 .map(GetOutputResult::Ready)
                 .unwrap_or(GetOutputResult::NotFound))
         }
+
+        async fn peek_promise(
+            &self,
+            _workflow_id: ServiceId,
+            _key: ByteString,
+        ) -> Result<PeekPromiseResult, Error> {
+            Ok(PeekPromiseResult::NotCompleted)
+        }
+
+        async fn get_awakeable(
+            &self,
+            _invocation_id: InvocationId,
+            _entry_index: EntryIndex,
+        ) -> Result<GetAwakeableResult, Error> {
+            Ok(GetAwakeableResult::NotFound)
+        }
+
+        async fn get_invocation_target(
+            &self,
+            invocation_id: InvocationId,
+        ) -> Result<Option<InvocationTarget>, Error> {
+            Ok(self
+                .0
+                .values()
+                .find_map(|response| match &response.response {
+                    IngressResponseResult::Success(invocation_target, _)
+                        if response.invocation_id == Some(invocation_id) =>
+                    {
+                        Some(invocation_target.clone())
+                    }
+                    _ => None,
+                }))
+        }
     }
 }
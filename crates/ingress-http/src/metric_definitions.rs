@@ -17,6 +17,9 @@ pub const INGRESS_REQUESTS: &str = "restate.ingress.requests.total";
 pub const REQUEST_ADMITTED: &str = "admitted";
 pub const REQUEST_COMPLETED: &str = "completed";
 pub const REQUEST_DENIED_THROTTLE: &str = "throttled";
+pub const REQUEST_DENIED_RATE_LIMIT: &str = "rate_limited";
+
+pub const INGRESS_RATE_LIMITED_REQUESTS: &str = "restate.ingress.rate_limited_requests.total";
 
 pub const INGRESS_REQUEST_DURATION: &str = "restate.ingress.request_duration.seconds";
 
@@ -26,6 +29,11 @@ pub(crate) fn describe_metrics() {
         Unit::Count,
         "Number of ingress requests in different states, see label state to classify"
     );
+    describe_counter!(
+        INGRESS_RATE_LIMITED_REQUESTS,
+        Unit::Count,
+        "Number of ingress requests rejected by the rate limits, see label rule to classify"
+    );
     describe_histogram!(
         INGRESS_REQUEST_DURATION,
         Unit::Seconds,
@@ -11,6 +11,8 @@
 use super::*;
 
 use crate::handler::Handler;
+use crate::layers::authentication::{AuthenticationError, AuthenticationLayer};
+use crate::layers::rate_limit::{RateLimitError, RateLimitLayer};
 use codederror::CodedError;
 use http::{Request, Response};
 use http_body_util::Full;
@@ -18,9 +20,12 @@ use hyper::body::Incoming;
 use hyper::service::service_fn;
 use hyper_util::rt::TokioIo;
 use hyper_util::server::conn::auto;
+use restate_core::network::tls::{ReloadableTlsConfig, TLS_HANDSHAKE_TIMEOUT};
 use restate_core::{cancellation_watcher, task_center, TaskKind};
 use restate_ingress_dispatcher::{DispatchIngressRequest, IngressDispatcher};
-use restate_types::config::IngressOptions;
+use restate_types::config::{
+    IngressAuthenticationOptions, IngressOptions, IngressRateLimitRule, TlsOptions,
+};
 use restate_types::live::Live;
 use restate_types::schema::invocation_target::InvocationTargetResolver;
 use restate_types::schema::service::ServiceMetadataResolver;
@@ -29,10 +34,11 @@ use std::future::Future;
 use std::net::SocketAddr;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::oneshot;
+use tokio_rustls::TlsAcceptor;
 use tower::{ServiceBuilder, ServiceExt};
 use tower_http::cors::CorsLayer;
 use tower_http::normalize_path::NormalizePathLayer;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 pub type StartSignal = oneshot::Receiver<SocketAddr>;
 
@@ -50,11 +56,24 @@ pub enum IngressServerError {
     #[error("error while running ingress http server: {0}")]
     #[code(unknown)]
     Running(#[from] hyper::Error),
+    #[error("failed configuring the ingress authentication: {0}")]
+    #[code(unknown)]
+    Authentication(#[from] AuthenticationError),
+    #[error("failed loading the tls certificates specified in 'ingress.tls': {0}")]
+    #[code(unknown)]
+    Tls(#[from] restate_core::network::tls::TlsError),
+    #[error("failed configuring the ingress rate limits: {0}")]
+    #[code(unknown)]
+    RateLimit(#[from] RateLimitError),
 }
 
 pub struct HyperServerIngress<Schemas, Dispatcher, StorageReader> {
     listening_addr: SocketAddr,
+    tls: Option<TlsOptions>,
     concurrency_limit: usize,
+    rate_limits: Vec<IngressRateLimitRule>,
+    authentication: Option<IngressAuthenticationOptions>,
+    callback_allowed_hosts: Vec<String>,
 
     // Parameters to build the layers
     schemas: Live<Schemas>,
@@ -79,7 +98,11 @@ where
         crate::metric_definitions::describe_metrics();
         let (hyper_ingress_server, _) = HyperServerIngress::new(
             ingress_options.bind_address,
+            ingress_options.tls.clone(),
             ingress_options.concurrent_api_requests_limit(),
+            ingress_options.rate_limits.clone(),
+            ingress_options.authentication.clone(),
+            ingress_options.callback_allowed_hosts.clone(),
             schemas,
             dispatcher,
             storage_reader,
@@ -97,7 +120,11 @@ where
 {
     pub(crate) fn new(
         listening_addr: SocketAddr,
+        tls: Option<TlsOptions>,
         concurrency_limit: usize,
+        rate_limits: Vec<IngressRateLimitRule>,
+        authentication: Option<IngressAuthenticationOptions>,
+        callback_allowed_hosts: Vec<String>,
         schemas: Live<Schemas>,
         dispatcher: Dispatcher,
         storage_reader: StorageReader,
@@ -106,7 +133,11 @@ where
 
         let ingress = Self {
             listening_addr,
+            tls,
             concurrency_limit,
+            rate_limits,
+            authentication,
+            callback_allowed_hosts,
             schemas,
             dispatcher,
             storage_reader,
@@ -119,13 +150,26 @@ where
     pub async fn run(self) -> anyhow::Result<()> {
         let HyperServerIngress {
             listening_addr,
+            tls,
             concurrency_limit,
+            rate_limits,
+            authentication,
+            callback_allowed_hosts,
             schemas,
             dispatcher,
             storage_reader,
             start_signal_tx,
         } = self;
 
+        let tls = ReloadableTlsConfig::start_server(tls.as_ref(), "ingress-tls-reload", |config| {
+            config.ingress.tls.as_ref()
+        })
+        .map_err(IngressServerError::Tls)?;
+        let rate_limit_layer =
+            RateLimitLayer::new(&rate_limits).map_err(IngressServerError::RateLimit)?;
+        let authentication_layer = AuthenticationLayer::new(authentication.as_ref())
+            .map_err(IngressServerError::Authentication)?;
+
         // We create a TcpListener and bind it
         let listener =
             TcpListener::bind(listening_addr)
@@ -144,14 +188,22 @@ where
         // Prepare the handler
         let service = ServiceBuilder::new()
             .layer(NormalizePathLayer::trim_trailing_slash())
+            .layer(rate_limit_layer)
             .layer(layers::load_shed::LoadShedLayer::new(concurrency_limit))
             .layer(CorsLayer::very_permissive())
+            .layer(authentication_layer)
             .layer(layers::tracing_context_extractor::HttpTraceContextExtractorLayer)
-            .service(Handler::new(schemas, dispatcher, storage_reader));
+            .service(Handler::new(
+                schemas,
+                dispatcher,
+                storage_reader,
+                callback_allowed_hosts.into(),
+            ));
 
         info!(
             net.host.addr = %local_addr.ip(),
             net.host.port = %local_addr.port(),
+            tls = tls.is_some(),
             "Ingress HTTP listening"
         );
 
@@ -166,7 +218,8 @@ where
             tokio::select! {
                 res = listener.accept() => {
                     let (stream, remote_peer) = res?;
-                    Self::handle_connection(stream, remote_peer, service.clone())?;
+                    let tls_acceptor = tls.as_ref().map(ReloadableTlsConfig::acceptor);
+                    Self::handle_connection(stream, remote_peer, tls_acceptor, service.clone())?;
                 }
                   _ = &mut shutdown => {
                     return Ok(());
@@ -178,6 +231,7 @@ where
     fn handle_connection<T, F>(
         stream: TcpStream,
         remote_peer: SocketAddr,
+        tls_acceptor: Option<TlsAcceptor>,
         handler: T,
     ) -> anyhow::Result<()>
     where
@@ -192,33 +246,59 @@ where
             + 'static,
     {
         let connect_info = ConnectInfo::new(remote_peer);
-        let io = TokioIo::new(stream);
 
         // Spawn a tokio task to serve the connection
         task_center().spawn(TaskKind::Ingress, "ingress", None, async move {
-            let svc = service_fn(move |mut hyper_req| {
-                hyper_req.extensions_mut().insert(connect_info);
-                let h = handler.clone();
-                async move { h.oneshot(hyper_req).await }
-            });
-
-            let shutdown = cancellation_watcher();
-            let auto_connection = auto::Builder::new(TaskCenterExecutor);
-            let serve_connection_fut = auto_connection.serve_connection(io, svc);
-
-            tokio::select! {
-                res = serve_connection_fut => {
-                    if let Err(err) = res {
-                        warn!("Error when serving the connection: {:?}", err);
-                    }
+            let Some(tls_acceptor) = tls_acceptor else {
+                Self::serve_connection(TokioIo::new(stream), connect_info, handler).await;
+                return Ok(());
+            };
+
+            match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(stream)).await {
+                Ok(Ok(tls_stream)) => {
+                    Self::serve_connection(TokioIo::new(tls_stream), connect_info, handler).await
                 }
-                _ = shutdown => {}
+                Ok(Err(err)) => debug!("TLS handshake with '{}' failed: {}", remote_peer, err),
+                Err(_) => debug!("TLS handshake with '{}' timed out", remote_peer),
             }
             Ok(())
         })?;
 
         Ok(())
     }
+
+    async fn serve_connection<I, T, F>(io: I, connect_info: ConnectInfo, handler: T)
+    where
+        I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+        F: Send,
+        T: tower::Service<
+                Request<Incoming>,
+                Response = Response<Full<Bytes>>,
+                Error = Infallible,
+                Future = F,
+            > + Clone
+            + Send
+            + 'static,
+    {
+        let svc = service_fn(move |mut hyper_req| {
+            hyper_req.extensions_mut().insert(connect_info);
+            let h = handler.clone();
+            async move { h.oneshot(hyper_req).await }
+        });
+
+        let shutdown = cancellation_watcher();
+        let auto_connection = auto::Builder::new(TaskCenterExecutor);
+        let serve_connection_fut = auto_connection.serve_connection(io, svc);
+
+        tokio::select! {
+            res = serve_connection_fut => {
+                if let Err(err) = res {
+                    warn!("Error when serving the connection: {:?}", err);
+                }
+            }
+            _ = shutdown => {}
+        }
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy)]
@@ -346,7 +426,11 @@ mod tests {
         // Create the ingress and start it
         let (ingress, start_signal) = HyperServerIngress::new(
             "0.0.0.0:0".parse().unwrap(),
+            None,
             Semaphore::MAX_PERMITS,
+            vec![],
+            None,
+            vec![],
             Live::from_value(mock_schemas()),
             MockDispatcher::new(ingress_request_tx),
             MockStorageReader::default(),
@@ -0,0 +1,54 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use restate_types::identifiers::SubscriptionId;
+use restate_types::subscription::SubscriptionPartitionLag;
+
+type PartitionKey = (SubscriptionId, String, i32);
+
+/// Last reported consumer lag of the running subscriptions, shared between the consumer tasks
+/// and the readers.
+#[derive(Debug, Clone, Default)]
+pub struct ConsumerLag(Arc<Mutex<BTreeMap<PartitionKey, SubscriptionPartitionLag>>>);
+
+impl ConsumerLag {
+    pub fn snapshot(&self) -> Vec<SubscriptionPartitionLag> {
+        self.0
+            .lock()
+            .expect("consumer lag lock is not poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn report(&self, partition_lag: SubscriptionPartitionLag) {
+        self.0
+            .lock()
+            .expect("consumer lag lock is not poisoned")
+            .insert(
+                (
+                    partition_lag.subscription_id,
+                    partition_lag.topic.clone(),
+                    partition_lag.partition,
+                ),
+                partition_lag,
+            );
+    }
+
+    pub(crate) fn remove_subscription(&self, subscription_id: SubscriptionId) {
+        self.0
+            .lock()
+            .expect("consumer lag lock is not poisoned")
+            .retain(|(id, _, _), _| *id != subscription_id);
+    }
+}
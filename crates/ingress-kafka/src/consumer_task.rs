@@ -28,6 +28,10 @@ use restate_types::identifiers::SubscriptionId;
 use restate_types::invocation::{Header, SpanRelation};
 use restate_types::message::MessageIndex;
 use restate_types::schema::subscriptions::{EventReceiverServiceType, Sink, Subscription};
+use restate_types::subscription::SubscriptionPartitionLag;
+use restate_types::time::MillisSinceEpoch;
+
+use crate::consumer_lag::ConsumerLag;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -92,6 +96,10 @@ impl MessageSender {
         }
     }
 
+    fn subscription_id(&self) -> SubscriptionId {
+        self.subscription.id()
+    }
+
     async fn send(
         &mut self,
         consumer_group_id: &str,
@@ -190,14 +198,21 @@ pub struct ConsumerTask {
     client_config: ClientConfig,
     topics: Vec<String>,
     sender: MessageSender,
+    consumer_lag: ConsumerLag,
 }
 
 impl ConsumerTask {
-    pub fn new(client_config: ClientConfig, topics: Vec<String>, sender: MessageSender) -> Self {
+    pub fn new(
+        client_config: ClientConfig,
+        topics: Vec<String>,
+        sender: MessageSender,
+        consumer_lag: ConsumerLag,
+    ) -> Self {
         Self {
             client_config,
             topics,
             sender,
+            consumer_lag,
         }
     }
 
@@ -227,6 +242,7 @@ impl ConsumerTask {
                     // rdkafka periodically commits these offsets asynchronously, with a period configurable
                     // with auto.commit.interval.ms
                     consumer.store_offset_from_message(&msg)?;
+                    self.report_lag(&consumer, &msg);
                 }
                 _ = &mut rx => {
                     return Ok(());
@@ -234,4 +250,23 @@ impl ConsumerTask {
             }
         }
     }
+
+    fn report_lag(&self, consumer: &MessageConsumer, msg: &BorrowedMessage<'_>) {
+        // Reads the watermarks cached from the last fetch response, hence it doesn't block
+        match consumer.get_watermark_offsets(msg.topic(), msg.partition()) {
+            Ok((_, high_watermark)) => self.consumer_lag.report(SubscriptionPartitionLag {
+                subscription_id: self.sender.subscription_id(),
+                topic: msg.topic().to_owned(),
+                partition: msg.partition(),
+                offset: msg.offset(),
+                high_watermark,
+                updated_at: MillisSinceEpoch::now(),
+            }),
+            Err(err) => debug!(
+                "Cannot read the watermarks of topic {} partition {}: {err}",
+                msg.topic(),
+                msg.partition()
+            ),
+        }
+    }
 }
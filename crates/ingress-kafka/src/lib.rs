@@ -8,11 +8,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+mod consumer_lag;
 mod consumer_task;
 mod subscription_controller;
 
 use tokio::sync::mpsc;
 
+pub use consumer_lag::ConsumerLag;
 pub use subscription_controller::{Command, Error, Service};
 
 pub type SubscriptionCommandSender = mpsc::Sender<Command>;
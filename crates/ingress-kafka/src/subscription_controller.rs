@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use super::consumer_lag::ConsumerLag;
 use super::consumer_task::MessageSender;
 use super::*;
 use std::collections::HashSet;
@@ -41,6 +42,7 @@ pub enum Error {
 // In future versions, we should either pull this out in a separate process, or generify it and move it to the worker, or an ad-hoc module
 pub struct Service {
     dispatcher: IngressDispatcher,
+    consumer_lag: ConsumerLag,
 
     commands_tx: SubscriptionCommandSender,
     commands_rx: SubscriptionCommandReceiver,
@@ -52,6 +54,7 @@ impl Service {
 
         Service {
             dispatcher,
+            consumer_lag: ConsumerLag::default(),
             commands_tx,
             commands_rx,
         }
@@ -61,6 +64,11 @@ impl Service {
         self.commands_tx.clone()
     }
 
+    /// Lag of the consumers of the subscriptions running on this node.
+    pub fn consumer_lag(&self) -> ConsumerLag {
+        self.consumer_lag.clone()
+    }
+
     pub async fn run(
         mut self,
         mut updateable_config: impl LiveLoad<IngressOptions> + Send + 'static,
@@ -135,6 +143,7 @@ impl Service {
             client_config,
             vec![topic.to_string()],
             MessageSender::new(subscription, self.dispatcher.clone()),
+            self.consumer_lag.clone(),
         );
 
         task_orchestrator.start(subscription_id, consumer_task);
@@ -146,6 +155,7 @@ impl Service {
         task_orchestrator: &mut TaskOrchestrator,
     ) {
         task_orchestrator.stop(subscription_id);
+        self.consumer_lag.remove_subscription(subscription_id);
     }
 
     fn handle_update_subscriptions(
@@ -61,6 +61,14 @@ pub trait ServiceHandle<SR> {
         invocation_id: InvocationId,
     ) -> Self::Future;
 
+    /// Retries the invocation without waiting for the remaining retry backoff.
+    /// Invocations which are not waiting for a retry are left untouched.
+    fn retry_invocation_now(
+        &mut self,
+        partition_leader_epoch: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) -> Self::Future;
+
     fn register_partition(
         &mut self,
         partition: PartitionLeaderEpoch,
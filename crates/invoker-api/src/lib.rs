@@ -20,7 +20,10 @@ pub use entry_enricher::EntryEnricher;
 pub use handle::*;
 pub use journal_reader::{JournalMetadata, JournalReader};
 pub use state_reader::{EagerState, StateReader};
-pub use status_handle::{InvocationErrorReport, InvocationStatusReport, StatusHandle};
+pub use status_handle::{
+    CircuitBreakerState, DeploymentHealthReport, InvocationErrorReport, InvocationStatusReport,
+    StatusHandle,
+};
 
 #[cfg(any(test, feature = "test-util"))]
 pub mod test_util {
@@ -60,5 +63,13 @@ pub mod test_util {
         ) -> Result<EagerState<Self::StateIter>, Self::Error> {
             Ok(EagerState::new_complete(empty()))
         }
+
+        async fn read_state_keys<'a>(
+            &'a mut self,
+            _service_id: &'a ServiceId,
+            _keys: &'a [Bytes],
+        ) -> Result<EagerState<Self::StateIter>, Self::Error> {
+            Ok(EagerState::new_partial(empty()))
+        }
     }
 }
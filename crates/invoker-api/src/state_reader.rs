@@ -74,4 +74,12 @@ pub trait StateReader {
         &'a mut self,
         service_id: &'a ServiceId,
     ) -> impl Future<Output = Result<EagerState<Self::StateIter>, Self::Error>> + Send;
+
+    /// Read only the given state keys. The returned [`EagerState`] is partial, and doesn't contain
+    /// the keys which are not set.
+    fn read_state_keys<'a>(
+        &'a mut self,
+        service_id: &'a ServiceId,
+        keys: &'a [Bytes],
+    ) -> impl Future<Output = Result<EagerState<Self::StateIter>, Self::Error>> + Send;
 }
@@ -110,6 +110,38 @@ pub struct InvocationErrorReport {
     pub related_entry_type: Option<EntryType>,
 }
 
+/// State of the circuit breaker the invoker keeps for each deployment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CircuitBreakerState {
+    /// Invocations are sent to the deployment
+    #[default]
+    Closed,
+    /// The deployment is considered unreachable, invocations are held until a health probe succeeds
+    Open,
+    /// A health probe succeeded, the held invocations are released gradually until one of them
+    /// reaches the deployment
+    HalfOpen,
+}
+
+impl CircuitBreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitBreakerState::Closed => "closed",
+            CircuitBreakerState::Open => "open",
+            CircuitBreakerState::HalfOpen => "half-open",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeploymentHealthReport {
+    pub deployment_id: DeploymentId,
+    pub circuit_breaker_state: CircuitBreakerState,
+    pub consecutive_failures: usize,
+    pub held_invocations: usize,
+    pub opened_at: Option<SystemTime>,
+}
+
 /// Struct to access the status of the invocations currently handled by the invoker
 pub trait StatusHandle {
     type Iterator: Iterator<Item = InvocationStatusReport> + Send;
@@ -122,6 +154,12 @@ pub trait StatusHandle {
         &self,
         keys: RangeInclusive<PartitionKey>,
     ) -> impl Future<Output = Self::Iterator> + Send;
+
+    /// This method returns a snapshot of the health of the deployments this invoker has seen failing.
+    /// Deployments without a report are considered healthy.
+    ///
+    /// The data returned by this method is eventually consistent.
+    fn read_deployment_health(&self) -> impl Future<Output = Vec<DeploymentHealthReport>> + Send;
 }
 
 #[cfg(any(test, feature = "test-util"))]
@@ -129,13 +167,21 @@ pub mod test_util {
     use super::*;
 
     #[derive(Debug, Clone, Default)]
-    pub struct MockStatusHandle(Vec<InvocationStatusReport>);
+    pub struct MockStatusHandle(Vec<InvocationStatusReport>, Vec<DeploymentHealthReport>);
 
     impl MockStatusHandle {
         pub fn with(mut self, invocation_status_report: InvocationStatusReport) -> Self {
             self.0.push(invocation_status_report);
             self
         }
+
+        pub fn with_deployment_health(
+            mut self,
+            deployment_health_report: DeploymentHealthReport,
+        ) -> Self {
+            self.1.push(deployment_health_report);
+            self
+        }
     }
 
     impl StatusHandle for MockStatusHandle {
@@ -144,5 +190,9 @@ pub mod test_util {
         async fn read_status(&self, _keys: RangeInclusive<PartitionKey>) -> Self::Iterator {
             self.0.clone().into_iter()
         }
+
+        async fn read_deployment_health(&self) -> Vec<DeploymentHealthReport> {
+            self.1.clone()
+        }
     }
 }
@@ -0,0 +1,396 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::*;
+
+use metrics::gauge;
+use restate_invoker_api::{CircuitBreakerState, DeploymentHealthReport};
+
+use crate::metric_definitions::INVOKER_CIRCUIT_BREAKER_OPEN;
+
+type HeldInvocation = (PartitionLeaderEpoch, InvocationId);
+
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    state: CircuitBreakerState,
+    consecutive_failures: usize,
+    opened_at: Option<SystemTime>,
+    // Invocations waiting in WaitingRetry for the breaker to close
+    held_invocations: HashSet<HeldInvocation>,
+    // Invocations released while half-open, to test whether the deployment is reachable again
+    trial_invocations: HashSet<HeldInvocation>,
+    // Number of invocations released by the next successful probe while half-open
+    next_release_batch: usize,
+}
+
+impl CircuitBreaker {
+    fn release(&mut self, count: usize) -> Vec<HeldInvocation> {
+        let released: Vec<_> = self.held_invocations.iter().take(count).copied().collect();
+        for invocation in &released {
+            self.held_invocations.remove(invocation);
+            self.trial_invocations.insert(*invocation);
+        }
+        released
+    }
+}
+
+/// Result of a health probe, telling how to go on with the deployment.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum ProbeOutcome {
+    /// Probe the deployment again later, resuming the released invocations meanwhile.
+    ProbeAgain(Vec<HeldInvocation>),
+    /// The breaker is closed, the released invocations should be resumed.
+    Closed(HashSet<HeldInvocation>),
+}
+
+/// Tracks the connection failures of the deployments, to stop retrying the invocations targeting
+/// a deployment which is down.
+///
+/// After [`InvokerOptions::circuit_breaker_failure_threshold`] consecutive connect failures, the
+/// breaker of the deployment opens. Invocations failing against an open breaker are held,
+/// rather than retried on their own timers, and the deployment is probed periodically.
+///
+/// Once a probe succeeds, the breaker is half-open: a single held invocation is released, and
+/// each further successful probe releases twice as many as the previous one. As soon as one of
+/// them reaches the deployment, the breaker closes and all the held invocations are released.
+/// A connect failure while half-open opens the breaker again.
+///
+/// Only deployments which failed since the last success are tracked.
+#[derive(Debug, Default)]
+pub(super) struct DeploymentCircuitBreakers(HashMap<DeploymentId, CircuitBreaker>);
+
+impl DeploymentCircuitBreakers {
+    pub(super) fn state(&self, deployment_id: &DeploymentId) -> CircuitBreakerState {
+        self.0
+            .get(deployment_id)
+            .map(|breaker| breaker.state)
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the invocation must not be retried, because the breaker of the deployment
+    /// is open, or half-open and the invocation was not released to test the deployment.
+    pub(super) fn should_hold(
+        &self,
+        deployment_id: &DeploymentId,
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) -> bool {
+        self.0
+            .get(deployment_id)
+            .is_some_and(|breaker| match breaker.state {
+                CircuitBreakerState::Closed => false,
+                CircuitBreakerState::Open => true,
+                CircuitBreakerState::HalfOpen => !breaker
+                    .trial_invocations
+                    .contains(&(partition, invocation_id)),
+            })
+    }
+
+    /// Records a connect failure. Returns true if this failure opened a closed breaker, in which
+    /// case the deployment must be probed. A half-open breaker is already being probed.
+    pub(super) fn on_connect_failure(
+        &mut self,
+        deployment_id: DeploymentId,
+        failure_threshold: Option<usize>,
+    ) -> bool {
+        let Some(failure_threshold) = failure_threshold else {
+            // breaker disabled
+            return false;
+        };
+
+        let breaker = self.0.entry(deployment_id).or_default();
+        breaker.consecutive_failures += 1;
+        match breaker.state {
+            CircuitBreakerState::Closed if breaker.consecutive_failures >= failure_threshold => {
+                breaker.state = CircuitBreakerState::Open;
+                breaker.opened_at = Some(SystemTime::now());
+                gauge!(INVOKER_CIRCUIT_BREAKER_OPEN, "deployment" => deployment_id.to_string())
+                    .set(1.0);
+                true
+            }
+            CircuitBreakerState::HalfOpen => {
+                breaker.state = CircuitBreakerState::Open;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Records that the deployment has been reached. Returns the held invocations to resume, if
+    /// this closed a half-open breaker.
+    pub(super) fn on_success(&mut self, deployment_id: &DeploymentId) -> HashSet<HeldInvocation> {
+        match self.state(deployment_id) {
+            CircuitBreakerState::Closed => {
+                self.0.remove(deployment_id);
+                HashSet::new()
+            }
+            CircuitBreakerState::HalfOpen => self.close(deployment_id),
+            // An in-flight invocation might still reach the deployment after the breaker opened,
+            // in this case we let the health probe close the breaker.
+            CircuitBreakerState::Open => HashSet::new(),
+        }
+    }
+
+    pub(super) fn hold(
+        &mut self,
+        deployment_id: DeploymentId,
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) {
+        debug_assert!(self.should_hold(&deployment_id, partition, invocation_id));
+        if let Some(breaker) = self.0.get_mut(&deployment_id) {
+            breaker
+                .trial_invocations
+                .remove(&(partition, invocation_id));
+            breaker.held_invocations.insert((partition, invocation_id));
+        }
+    }
+
+    /// Forgets the invocation, e.g. because it was aborted, so that it's not resumed when the
+    /// breaker closes.
+    pub(super) fn remove_invocation(
+        &mut self,
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) {
+        for breaker in self.0.values_mut() {
+            breaker.held_invocations.remove(&(partition, invocation_id));
+            breaker
+                .trial_invocations
+                .remove(&(partition, invocation_id));
+        }
+    }
+
+    /// Forgets the invocations of the partition, e.g. because this node is no longer its leader.
+    pub(super) fn remove_partition(&mut self, partition: PartitionLeaderEpoch) {
+        for breaker in self.0.values_mut() {
+            breaker.held_invocations.retain(|(p, _)| *p != partition);
+            breaker.trial_invocations.retain(|(p, _)| *p != partition);
+        }
+    }
+
+    pub(super) fn on_probe_result(
+        &mut self,
+        deployment_id: &DeploymentId,
+        healthy: bool,
+    ) -> ProbeOutcome {
+        let Some(breaker) = self
+            .0
+            .get_mut(deployment_id)
+            .filter(|breaker| breaker.state != CircuitBreakerState::Closed)
+        else {
+            return ProbeOutcome::Closed(HashSet::new());
+        };
+
+        if !healthy {
+            breaker.state = CircuitBreakerState::Open;
+            return ProbeOutcome::ProbeAgain(vec![]);
+        }
+        if breaker.held_invocations.is_empty() {
+            // Nothing left to test the deployment with
+            return ProbeOutcome::Closed(self.close(deployment_id));
+        }
+
+        let batch = if breaker.state == CircuitBreakerState::Open {
+            breaker.state = CircuitBreakerState::HalfOpen;
+            1
+        } else {
+            breaker.next_release_batch
+        };
+        breaker.next_release_batch = batch * 2;
+        ProbeOutcome::ProbeAgain(breaker.release(batch))
+    }
+
+    /// Closes the breaker, returning the invocations which were held.
+    fn close(&mut self, deployment_id: &DeploymentId) -> HashSet<HeldInvocation> {
+        if let Some(breaker) = self.0.remove(deployment_id) {
+            if breaker.state != CircuitBreakerState::Closed {
+                gauge!(INVOKER_CIRCUIT_BREAKER_OPEN, "deployment" => deployment_id.to_string())
+                    .set(0.0);
+            }
+            breaker.held_invocations
+        } else {
+            HashSet::new()
+        }
+    }
+
+    pub(super) fn health_reports(&self) -> Vec<DeploymentHealthReport> {
+        self.0
+            .iter()
+            .map(|(deployment_id, breaker)| DeploymentHealthReport {
+                deployment_id: *deployment_id,
+                circuit_breaker_state: breaker.state,
+                consecutive_failures: breaker.consecutive_failures,
+                held_invocations: breaker.held_invocations.len(),
+                opened_at: breaker.opened_at,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use googletest::prelude::*;
+    use restate_test_util::let_assert;
+    use restate_types::identifiers::{LeaderEpoch, PartitionId};
+
+    const PARTITION: PartitionLeaderEpoch = (PartitionId::MIN, LeaderEpoch::INITIAL);
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let deployment_id = DeploymentId::new();
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        assert!(!breakers.on_connect_failure(deployment_id, Some(2)));
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Closed);
+        assert!(breakers.on_connect_failure(deployment_id, Some(2)));
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Open);
+        // Further failures don't open the breaker again
+        assert!(!breakers.on_connect_failure(deployment_id, Some(2)));
+
+        assert_that!(
+            breakers.health_reports(),
+            elements_are![pat!(DeploymentHealthReport {
+                deployment_id: eq(deployment_id),
+                circuit_breaker_state: eq(CircuitBreakerState::Open),
+                consecutive_failures: eq(3),
+                opened_at: some(anything()),
+            })]
+        );
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let deployment_id = DeploymentId::new();
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        breakers.on_connect_failure(deployment_id, Some(2));
+        breakers.on_success(&deployment_id);
+        assert!(!breakers.on_connect_failure(deployment_id, Some(2)));
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Closed);
+    }
+
+    #[test]
+    fn disabled_breaker_never_opens() {
+        let deployment_id = DeploymentId::new();
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        for _ in 0..10 {
+            assert!(!breakers.on_connect_failure(deployment_id, None));
+        }
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Closed);
+        assert!(breakers.health_reports().is_empty());
+    }
+
+    #[test]
+    fn half_open_releases_invocations_gradually() {
+        let deployment_id = DeploymentId::new();
+        let invocations: Vec<_> = (0..4)
+            .map(|_| (PARTITION, InvocationId::mock_random()))
+            .collect();
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        breakers.on_connect_failure(deployment_id, Some(1));
+        for (partition, invocation_id) in &invocations {
+            breakers.hold(deployment_id, *partition, *invocation_id);
+        }
+        // Holding twice the same invocation releases it only once
+        breakers.hold(deployment_id, invocations[0].0, invocations[0].1);
+        // Success of an invocation in flight doesn't close an open breaker
+        assert!(breakers.on_success(&deployment_id).is_empty());
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Open);
+
+        // A failed probe releases nothing
+        assert_eq!(
+            breakers.on_probe_result(&deployment_id, false),
+            ProbeOutcome::ProbeAgain(vec![])
+        );
+
+        // The first successful probe releases a single invocation, the next one two more
+        let_assert!(
+            ProbeOutcome::ProbeAgain(first) = breakers.on_probe_result(&deployment_id, true)
+        );
+        assert_eq!(first.len(), 1);
+        assert_eq!(
+            breakers.state(&deployment_id),
+            CircuitBreakerState::HalfOpen
+        );
+        assert!(!breakers.should_hold(&deployment_id, first[0].0, first[0].1));
+        let_assert!(
+            ProbeOutcome::ProbeAgain(second) = breakers.on_probe_result(&deployment_id, true)
+        );
+        assert_eq!(second.len(), 2);
+
+        // Once one of them reaches the deployment, the remaining invocation is released
+        let released = breakers.on_success(&deployment_id);
+        assert_eq!(released.len(), 1);
+        assert!(!released.contains(&first[0]));
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Closed);
+        assert!(breakers.health_reports().is_empty());
+    }
+
+    #[test]
+    fn aborted_invocations_are_not_released() {
+        let deployment_id = DeploymentId::new();
+        let other_partition = (PartitionId::from(1), LeaderEpoch::INITIAL);
+        let aborted = (PARTITION, InvocationId::mock_random());
+        let held = (PARTITION, InvocationId::mock_random());
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        breakers.on_connect_failure(deployment_id, Some(1));
+        breakers.hold(deployment_id, aborted.0, aborted.1);
+        breakers.hold(deployment_id, held.0, held.1);
+        breakers.hold(deployment_id, other_partition, InvocationId::mock_random());
+
+        breakers.remove_invocation(aborted.0, aborted.1);
+        breakers.remove_partition(other_partition);
+
+        let_assert!([report] = &breakers.health_reports()[..]);
+        assert_eq!(report.held_invocations, 1);
+        assert_eq!(
+            breakers.on_probe_result(&deployment_id, true),
+            ProbeOutcome::ProbeAgain(vec![held])
+        );
+    }
+
+    #[test]
+    fn half_open_opens_again_on_failure() {
+        let deployment_id = DeploymentId::new();
+        let held = (PARTITION, InvocationId::mock_random());
+        let mut breakers = DeploymentCircuitBreakers::default();
+
+        breakers.on_connect_failure(deployment_id, Some(1));
+        breakers.hold(deployment_id, held.0, held.1);
+        assert_eq!(
+            breakers.on_probe_result(&deployment_id, true),
+            ProbeOutcome::ProbeAgain(vec![held])
+        );
+
+        // The trial invocation fails, it's held again
+        assert!(!breakers.on_connect_failure(deployment_id, Some(1)));
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Open);
+        assert!(breakers.should_hold(&deployment_id, held.0, held.1));
+        breakers.hold(deployment_id, held.0, held.1);
+
+        // Once probed successfully again, it's released again
+        assert_eq!(
+            breakers.on_probe_result(&deployment_id, true),
+            ProbeOutcome::ProbeAgain(vec![held])
+        );
+        // With no invocation left to release, the next successful probe closes the breaker
+        assert_eq!(
+            breakers.on_probe_result(&deployment_id, true),
+            ProbeOutcome::Closed(HashSet::new())
+        );
+        assert_eq!(breakers.state(&deployment_id), CircuitBreakerState::Closed);
+    }
+}
@@ -10,7 +10,8 @@
 
 use restate_errors::NotRunningError;
 use restate_invoker_api::{
-    Effect, InvocationStatusReport, InvokeInputJournal, ServiceHandle, StatusHandle,
+    DeploymentHealthReport, Effect, InvocationStatusReport, InvokeInputJournal, ServiceHandle,
+    StatusHandle,
 };
 use restate_types::identifiers::{EntryIndex, InvocationId, PartitionKey, PartitionLeaderEpoch};
 use restate_types::invocation::InvocationTarget;
@@ -49,6 +50,12 @@ pub(crate) enum InputCommand<SR> {
         invocation_id: InvocationId,
     },
 
+    /// Retry specific invocation id now, if it's waiting for a retry
+    RetryNow {
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    },
+
     /// Command used to clean up internal state when a partition leader is going away
     AbortAllPartition {
         partition: PartitionLeaderEpoch,
@@ -148,6 +155,21 @@ impl<SR> ServiceHandle<SR> for InvokerHandle<SR> {
         )
     }
 
+    fn retry_invocation_now(
+        &mut self,
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) -> Self::Future {
+        futures::future::ready(
+            self.input
+                .send(InputCommand::RetryNow {
+                    partition,
+                    invocation_id,
+                })
+                .map_err(|_| NotRunningError),
+        )
+    }
+
     fn register_partition(
         &mut self,
         partition: PartitionLeaderEpoch,
@@ -169,14 +191,17 @@ impl<SR> ServiceHandle<SR> for InvokerHandle<SR> {
 }
 
 #[derive(Debug, Clone)]
-pub struct ChannelStatusReader(
-    pub(super)  mpsc::UnboundedSender<
+pub struct ChannelStatusReader {
+    pub(super) status_tx: mpsc::UnboundedSender<
         restate_futures_util::command::Command<
             RangeInclusive<PartitionKey>,
             Vec<InvocationStatusReport>,
         >,
     >,
-);
+    pub(super) deployment_health_tx: mpsc::UnboundedSender<
+        restate_futures_util::command::Command<(), Vec<DeploymentHealthReport>>,
+    >,
+}
 
 impl StatusHandle for ChannelStatusReader {
     type Iterator = itertools::Either<
@@ -186,7 +211,7 @@ impl StatusHandle for ChannelStatusReader {
 
     async fn read_status(&self, keys: RangeInclusive<PartitionKey>) -> Self::Iterator {
         let (cmd, rx) = restate_futures_util::command::Command::prepare(keys);
-        if self.0.send(cmd).is_err() {
+        if self.status_tx.send(cmd).is_err() {
             return itertools::Either::Left(std::iter::empty::<InvocationStatusReport>());
         }
 
@@ -196,4 +221,13 @@ impl StatusHandle for ChannelStatusReader {
             itertools::Either::Left(std::iter::empty::<InvocationStatusReport>())
         }
     }
+
+    async fn read_deployment_health(&self) -> Vec<DeploymentHealthReport> {
+        let (cmd, rx) = restate_futures_util::command::Command::prepare(());
+        if self.deployment_health_tx.send(cmd).is_err() {
+            return Vec::new();
+        }
+
+        rx.await.unwrap_or_default()
+    }
 }
@@ -23,6 +23,8 @@ pub(super) struct InvocationStateMachine {
     pub(super) invocation_target: InvocationTarget,
     invocation_state: InvocationState,
     retry_iter: retries::RetryIter,
+    /// Identifies the last retry timer, to ignore timers made stale by an early retry.
+    retry_timer_generation: u32,
 }
 
 /// This struct tracks which entries the invocation task generates,
@@ -135,6 +137,7 @@ impl InvocationStateMachine {
             invocation_target,
             invocation_state: InvocationState::New,
             retry_iter: retry_policy.into_iter(),
+            retry_timer_generation: 0,
         }
     }
 
@@ -259,7 +262,11 @@ impl InvocationStateMachine {
         });
     }
 
-    pub(super) fn notify_retry_timer_fired(&mut self) {
+    pub(super) fn notify_retry_timer_fired(&mut self, retry_timer_generation: u32) {
+        if retry_timer_generation != self.retry_timer_generation {
+            // The invocation was retried before this timer fired
+            return;
+        }
         debug_assert!(matches!(
             &self.invocation_state,
             InvocationState::WaitingRetry { .. }
@@ -270,6 +277,26 @@ impl InvocationStateMachine {
         }
     }
 
+    /// Retries the invocation without waiting for the retry timer. Returns false if the
+    /// invocation is not waiting for a retry.
+    pub(super) fn notify_retry_now(&mut self) -> bool {
+        if let InvocationState::WaitingRetry { timer_fired, .. } = &mut self.invocation_state {
+            *timer_fired = true;
+            self.retry_timer_generation = self.retry_timer_generation.wrapping_add(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Like a fired retry timer, but tolerates invocations which are not waiting for a retry,
+    /// as these might have been restarted while held by the circuit breaker.
+    pub(super) fn notify_circuit_breaker_closed(&mut self) {
+        if let InvocationState::WaitingRetry { timer_fired, .. } = &mut self.invocation_state {
+            *timer_fired = true;
+        }
+    }
+
     /// Returns Some() with the timer for the next retry, otherwise None if retry limit exhausted
     pub(super) fn handle_task_error(&mut self) -> Option<Duration> {
         let journal_tracker = match &self.invocation_state {
@@ -296,12 +323,17 @@ impl InvocationStateMachine {
                 timer_fired: false,
                 journal_tracker,
             };
+            self.retry_timer_generation = self.retry_timer_generation.wrapping_add(1);
             next_timer
         } else {
             None
         }
     }
 
+    pub(super) fn retry_timer_generation(&self) -> u32 {
+        self.retry_timer_generation
+    }
+
     pub(super) fn is_ready_to_retry(&self) -> bool {
         match self.invocation_state {
             InvocationState::WaitingRetry {
@@ -342,13 +374,37 @@ mod tests {
         assert!(invocation_state_machine.handle_task_error().is_some());
         check!(let InvocationState::WaitingRetry { .. } = invocation_state_machine.invocation_state);
 
-        invocation_state_machine.notify_retry_timer_fired();
+        invocation_state_machine
+            .notify_retry_timer_fired(invocation_state_machine.retry_timer_generation());
 
         // We stay in `WaitingForRetry`
         assert!(invocation_state_machine.handle_task_error().is_some());
         check!(let InvocationState::WaitingRetry { .. } = invocation_state_machine.invocation_state);
     }
 
+    #[test]
+    fn retry_now_fences_off_the_pending_retry_timer() {
+        let mut invocation_state_machine = InvocationStateMachine::create(
+            InvocationTarget::mock_virtual_object(),
+            RetryPolicy::fixed_delay(Duration::from_secs(1), Some(10)),
+        );
+        assert!(!invocation_state_machine.notify_retry_now());
+
+        assert!(invocation_state_machine.handle_task_error().is_some());
+        let stale_retry_timer_generation = invocation_state_machine.retry_timer_generation();
+        assert!(invocation_state_machine.notify_retry_now());
+        assert!(invocation_state_machine.is_ready_to_retry());
+
+        // The retried attempt fails again before the stale timer fires
+        assert!(invocation_state_machine.handle_task_error().is_some());
+        invocation_state_machine.notify_retry_timer_fired(stale_retry_timer_generation);
+        assert!(!invocation_state_machine.is_ready_to_retry());
+
+        invocation_state_machine
+            .notify_retry_timer_fired(invocation_state_machine.retry_timer_generation());
+        assert!(invocation_state_machine.is_ready_to_retry());
+    }
+
     #[test(tokio::test)]
     async fn handle_requires_ack() {
         let mut invocation_state_machine = InvocationStateMachine::create(
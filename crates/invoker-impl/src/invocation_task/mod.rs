@@ -24,7 +24,7 @@ use restate_invoker_api::{
     EagerState, EntryEnricher, InvocationErrorReport, InvokeInputJournal, JournalReader,
     StateReader,
 };
-use restate_service_client::{Request, ServiceClient, ServiceClientError};
+use restate_service_client::{Endpoint, Request, ServiceClient, ServiceClientError};
 use restate_service_protocol::message::{EncodingError, MessageType};
 use restate_types::deployment::PinnedDeployment;
 use restate_types::errors::InvocationError;
@@ -33,8 +33,11 @@ use restate_types::invocation::InvocationTarget;
 use restate_types::journal::enriched::EnrichedRawEntry;
 use restate_types::journal::EntryType;
 use restate_types::live::Live;
-use restate_types::schema::deployment::DeploymentResolver;
-use restate_types::service_protocol::ServiceProtocolVersion;
+use restate_types::schema::deployment::{
+    DeploymentResolver, DeploymentType, HttpConnectionOptions,
+};
+use restate_types::schema::service::{EagerStatePolicy, ServiceMetadataResolver};
+use restate_types::service_protocol::{MessageCompression, ServiceProtocolVersion};
 use restate_types::service_protocol::{MAX_SERVICE_PROTOCOL_VERSION, MIN_SERVICE_PROTOCOL_VERSION};
 use std::collections::HashSet;
 use std::error::Error;
@@ -55,6 +58,10 @@ use tracing::instrument;
 const SERVICE_PROTOCOL_VERSION_V1: HeaderValue =
     HeaderValue::from_static("application/vnd.restate.invocation.v1");
 
+#[allow(clippy::declare_interior_mutable_const)]
+const SERVICE_PROTOCOL_VERSION_V2: HeaderValue =
+    HeaderValue::from_static("application/vnd.restate.invocation.v2");
+
 #[allow(clippy::declare_interior_mutable_const)]
 const X_RESTATE_SERVER: HeaderName = HeaderName::from_static("x-restate-server");
 
@@ -145,6 +152,14 @@ impl InvocationTaskError {
         true
     }
 
+    /// Returns true if the deployment could not be reached at all.
+    pub(crate) fn is_connect_failure(&self) -> bool {
+        match self {
+            InvocationTaskError::Client(err) => err.is_connect_failure(),
+            _ => false,
+        }
+    }
+
     pub(crate) fn into_invocation_error(self) -> InvocationError {
         match self {
             InvocationTaskError::ErrorMessageReceived(_, e) => e,
@@ -257,6 +272,7 @@ pub(super) struct InvocationTask<SR, JR, EE, DMR> {
     disable_eager_state: bool,
     message_size_warning: usize,
     message_size_limit: Option<usize>,
+    message_compression: Option<(MessageCompression, usize)>,
 
     // Invoker tx/rx
     state_reader: SR,
@@ -304,7 +320,7 @@ where
     <JR as JournalReader>::JournalStream: Unpin + Send + 'static,
     <SR as StateReader>::StateIter: Send,
     EE: EntryEnricher,
-    DMR: DeploymentResolver,
+    DMR: DeploymentResolver + ServiceMetadataResolver,
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -317,6 +333,7 @@ where
         disable_eager_state: bool,
         message_size_warning: usize,
         message_size_limit: Option<usize>,
+        message_compression: Option<(MessageCompression, usize)>,
         state_reader: SR,
         journal_reader: JR,
         entry_enricher: EE,
@@ -340,6 +357,7 @@ where
             invoker_rx,
             message_size_limit,
             message_size_warning,
+            message_compression,
         }
     }
 
@@ -385,18 +403,33 @@ where
                 ),
             })
         };
-        // Read eager state
+        // Read eager state, according to the policy of the service
+        let eager_state_policy = if self.disable_eager_state {
+            EagerStatePolicy::None
+        } else {
+            self.deployment_metadata_resolver
+                .live_load()
+                .resolve_latest_service_eager_state(self.invocation_target.service_name())
+                .unwrap_or_default()
+        };
         let read_state_future = async {
             let keyed_service_id = self.invocation_target.as_keyed_service_id();
-            if self.disable_eager_state || keyed_service_id.is_none() {
-                Ok(EagerState::<iter::Empty<_>>::default().map(itertools::Either::Right))
-            } else {
-                self.state_reader
-                    .read_state(&keyed_service_id.unwrap())
-                    .await
-                    .map_err(|e| InvocationTaskError::StateReader(e.into()))
-                    .map(|r| r.map(itertools::Either::Left))
-            }
+            let state = match (keyed_service_id, eager_state_policy) {
+                (Some(service_id), EagerStatePolicy::Full) => {
+                    self.state_reader.read_state(&service_id).await
+                }
+                (Some(service_id), EagerStatePolicy::Keys(keys)) => {
+                    let keys: Vec<_> = keys.into_iter().map(Bytes::from).collect();
+                    self.state_reader.read_state_keys(&service_id, &keys).await
+                }
+                (None, _) | (_, EagerStatePolicy::None) => {
+                    let no_state = EagerState::<iter::Empty<_>>::default();
+                    return Ok(no_state.map(itertools::Either::Right));
+                }
+            };
+            state
+                .map_err(|e| InvocationTaskError::StateReader(e.into()))
+                .map(|r| r.map(itertools::Either::Left))
         };
 
         // We execute those concurrently
@@ -489,6 +522,25 @@ fn service_protocol_version_to_header_value(
             unreachable!("unknown protocol version should never be chosen")
         }
         ServiceProtocolVersion::V1 => SERVICE_PROTOCOL_VERSION_V1,
+        ServiceProtocolVersion::V2 => SERVICE_PROTOCOL_VERSION_V2,
+    }
+}
+
+pub(crate) fn deployment_endpoint(
+    deployment_type: DeploymentType,
+    http_connection: HttpConnectionOptions,
+) -> Endpoint {
+    match deployment_type {
+        DeploymentType::Lambda {
+            arn,
+            assume_role_arn,
+            invoke_mode,
+        } => Endpoint::Lambda(arn, assume_role_arn, invoke_mode),
+        DeploymentType::Http {
+            address,
+            http_version,
+            ..
+        } => Endpoint::Http(address, http_version, http_connection),
     }
 }
 
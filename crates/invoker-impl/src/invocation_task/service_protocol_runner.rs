@@ -9,7 +9,7 @@
 // by the Apache License, Version 2.0.
 
 use crate::invocation_task::{
-    invocation_id_to_header_value, service_protocol_version_to_header_value,
+    deployment_endpoint, invocation_id_to_header_value, service_protocol_version_to_header_value,
     InvocationErrorRelatedEntry, InvocationTask, InvocationTaskError, InvocationTaskOutputInner,
     ResponseChunk, ResponseStreamState, TerminalLoopState, X_RESTATE_SERVER,
 };
@@ -26,7 +26,7 @@ use opentelemetry_http::HeaderInjector;
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use restate_errors::warn_it;
 use restate_invoker_api::{EagerState, EntryEnricher, JournalMetadata};
-use restate_service_client::{Endpoint, Method, Parts, Request, ServiceClientError};
+use restate_service_client::{Method, Parts, Request, ServiceClientError};
 use restate_service_protocol::message::{
     Decoder, Encoder, MessageHeader, MessageType, ProtocolMessage,
 };
@@ -35,9 +35,7 @@ use restate_types::identifiers::{EntryIndex, InvocationId};
 use restate_types::invocation::ServiceInvocationSpanContext;
 use restate_types::journal::raw::PlainRawEntry;
 use restate_types::journal::EntryType;
-use restate_types::schema::deployment::{
-    Deployment, DeploymentMetadata, DeploymentType, ProtocolType,
-};
+use restate_types::schema::deployment::{Deployment, DeploymentMetadata, ProtocolType};
 use restate_types::service_protocol::ServiceProtocolVersion;
 use std::collections::HashSet;
 use std::future::poll_fn;
@@ -69,7 +67,10 @@ where
         invocation_task: &'a mut InvocationTask<SR, JR, EE, DMR>,
         service_protocol_version: ServiceProtocolVersion,
     ) -> Self {
-        let encoder = Encoder::new(service_protocol_version);
+        let mut encoder = Encoder::new(service_protocol_version);
+        if let Some((compression, threshold)) = invocation_task.message_compression {
+            encoder = encoder.with_compression(compression, threshold);
+        }
         let decoder = Decoder::new(
             service_protocol_version,
             invocation_task.message_size_warning,
@@ -224,17 +225,10 @@ where
             &mut HeaderInjector(&mut headers),
         );
 
-        let address = match deployment_metadata.ty {
-            DeploymentType::Lambda {
-                arn,
-                assume_role_arn,
-            } => Endpoint::Lambda(arn, assume_role_arn),
-            DeploymentType::Http {
-                address,
-                http_version,
-                ..
-            } => Endpoint::Http(address, http_version),
-        };
+        let address = deployment_endpoint(
+            deployment_metadata.ty,
+            deployment_metadata.delivery_options.http_connection,
+        );
 
         headers.extend(deployment_metadata.delivery_options.additional_headers);
 
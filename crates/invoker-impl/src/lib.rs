@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+mod circuit_breaker;
 mod input_command;
 mod invocation_state_machine;
 mod invocation_task;
@@ -16,6 +17,7 @@ mod quota;
 mod state_machine_manager;
 mod status_store;
 
+use circuit_breaker::{DeploymentCircuitBreakers, ProbeOutcome};
 use futures::Stream;
 use input_command::{InputCommand, InvokeCommand};
 use invocation_state_machine::InvocationStateMachine;
@@ -25,8 +27,8 @@ use metrics::counter;
 use restate_core::cancellation_watcher;
 use restate_errors::warn_it;
 use restate_invoker_api::{
-    Effect, EffectKind, EntryEnricher, InvocationErrorReport, InvocationStatusReport,
-    InvokeInputJournal, JournalReader, StateReader,
+    DeploymentHealthReport, Effect, EffectKind, EntryEnricher, InvocationErrorReport,
+    InvocationStatusReport, InvokeInputJournal, JournalReader, StateReader,
 };
 use restate_queue::SegmentQueue;
 use restate_timer_queue::TimerQueue;
@@ -39,6 +41,7 @@ use restate_types::journal::Completion;
 use restate_types::live::{Live, LiveLoad};
 use restate_types::retries::RetryPolicy;
 use restate_types::schema::deployment::DeploymentResolver;
+use restate_types::schema::service::ServiceMetadataResolver;
 use status_store::InvocationStatusStore;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
@@ -50,12 +53,13 @@ use std::{cmp, panic};
 use tokio::sync::mpsc;
 use tokio::task::{AbortHandle, JoinSet};
 use tracing::instrument;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
-use crate::invocation_task::InvocationTaskError;
+use crate::invocation_task::{deployment_endpoint, InvocationTaskError};
 pub use input_command::ChannelStatusReader;
 pub use input_command::InvokerHandle;
-use restate_service_client::{AssumeRoleCacheMode, ServiceClient};
+use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys, ServiceClient};
+use restate_service_protocol::discovery::{DiscoverEndpoint, ServiceDiscovery};
 use restate_types::deployment::PinnedDeployment;
 use restate_types::invocation::InvocationTarget;
 
@@ -86,6 +90,12 @@ trait InvocationTaskRunner<SR> {
         input_journal: InvokeInputJournal,
         task_pool: &mut JoinSet<()>,
     ) -> AbortHandle;
+
+    /// Sends a health probe to the deployment, resolving to true if the deployment is reachable.
+    fn probe_deployment(
+        &self,
+        deployment_id: DeploymentId,
+    ) -> impl Future<Output = bool> + Send + 'static;
 }
 
 struct DefaultInvocationTaskRunner<EE, DMR> {
@@ -100,7 +110,7 @@ where
     <SR as JournalReader>::JournalStream: Unpin + Send + 'static,
     <SR as StateReader>::StateIter: Send,
     EE: EntryEnricher + Clone + Send + Sync + 'static,
-    DMR: DeploymentResolver + Clone + Send + Sync + 'static,
+    DMR: DeploymentResolver + ServiceMetadataResolver + Clone + Send + Sync + 'static,
 {
     fn start_invocation_task(
         &self,
@@ -125,6 +135,8 @@ where
                 opts.disable_eager_state,
                 opts.message_size_warning.get(),
                 opts.message_size_limit(),
+                opts.message_compression
+                    .map(|compression| (compression, opts.message_compression_threshold.get())),
                 storage_reader.clone(),
                 storage_reader,
                 self.entry_enricher.clone(),
@@ -135,6 +147,42 @@ where
             .run(input_journal),
         )
     }
+
+    fn probe_deployment(
+        &self,
+        deployment_id: DeploymentId,
+    ) -> impl Future<Output = bool> + Send + 'static {
+        let deployment = self
+            .deployment_metadata_resolver
+            .pinned()
+            .get_deployment(&deployment_id);
+        let service_discovery = ServiceDiscovery::new(RetryPolicy::None, self.client.clone());
+
+        async move {
+            let Some(deployment) = deployment else {
+                // The deployment has been removed, the held invocations will fail on their own
+                return true;
+            };
+            let endpoint = DiscoverEndpoint::new(
+                deployment_endpoint(
+                    deployment.metadata.ty,
+                    deployment.metadata.delivery_options.http_connection,
+                ),
+                deployment.metadata.delivery_options.additional_headers,
+            );
+
+            match service_discovery.probe(&endpoint).await {
+                Ok(()) => true,
+                Err(err) => {
+                    debug!(
+                        restate.deployment.id = %deployment_id,
+                        "Health probe of deployment failed: {}", err
+                    );
+                    false
+                }
+            }
+        }
+    }
 }
 
 // -- Service implementation
@@ -147,6 +195,9 @@ pub struct Service<SR, EntryEnricher, DeploymentRegistry> {
             Vec<InvocationStatusReport>,
         >,
     >,
+    deployment_health_tx: mpsc::UnboundedSender<
+        restate_futures_util::command::Command<(), Vec<DeploymentHealthReport>>,
+    >,
     // For the segment queue
     tmp_dir: PathBuf,
     // We have this level of indirection to hide the InvocationTaskRunner,
@@ -170,15 +221,18 @@ impl<SR, EE, DMR> Service<SR, EE, DMR> {
     {
         let (input_tx, input_rx) = mpsc::unbounded_channel();
         let (status_tx, status_rx) = mpsc::unbounded_channel();
+        let (deployment_health_tx, deployment_health_rx) = mpsc::unbounded_channel();
         let (invocation_tasks_tx, invocation_tasks_rx) = mpsc::unbounded_channel();
 
         Self {
             input_tx,
             status_tx,
+            deployment_health_tx,
             tmp_dir: options.gen_tmp_dir(),
             inner: ServiceInner {
                 input_rx,
                 status_rx,
+                deployment_health_rx,
                 invocation_tasks_tx,
                 invocation_tasks_rx,
                 invocation_task_runner: DefaultInvocationTaskRunner {
@@ -191,6 +245,8 @@ impl<SR, EE, DMR> Service<SR, EE, DMR> {
                 quota: quota::InvokerConcurrencyQuota::new(options.concurrent_invocations_limit()),
                 status_store: Default::default(),
                 invocation_state_machine_manager: Default::default(),
+                circuit_breakers: Default::default(),
+                deployment_probes: Default::default(),
             },
         }
     }
@@ -200,7 +256,8 @@ impl<SR, EE, DMR> Service<SR, EE, DMR> {
         invoker_options: &InvokerOptions,
         entry_enricher: EE,
         deployment_registry: Live<DMR>,
-    ) -> Result<Service<SR, EE, DMR>, BuildError>
+        request_identity_keys: RequestIdentityKeys,
+    ) -> Service<SR, EE, DMR>
     where
         SR: JournalReader<JournalStream = JS> + StateReader + Clone + Send + Sync + 'static,
         JS: Stream<Item = PlainRawEntry> + Unpin + Send + 'static,
@@ -208,22 +265,14 @@ impl<SR, EE, DMR> Service<SR, EE, DMR> {
         DMR: DeploymentResolver,
     {
         metric_definitions::describe_metrics();
-        let client =
-            ServiceClient::from_options(service_client_options, AssumeRoleCacheMode::Unbounded)?;
-
-        Ok(Service::new(
-            invoker_options,
-            deployment_registry,
-            client,
-            entry_enricher,
-        ))
-    }
-}
+        let client = ServiceClient::from_options(
+            service_client_options,
+            AssumeRoleCacheMode::Unbounded,
+            request_identity_keys,
+        );
 
-#[derive(Debug, thiserror::Error)]
-#[error("failed building the invoker service: {0}")]
-pub enum BuildError {
-    ServiceClient(#[from] restate_service_client::BuildError),
+        Service::new(invoker_options, deployment_registry, client, entry_enricher)
+    }
 }
 
 impl<SR, EE, EMR> Service<SR, EE, EMR>
@@ -232,7 +281,7 @@ where
     <SR as JournalReader>::JournalStream: Unpin + Send + 'static,
     <SR as StateReader>::StateIter: Send,
     EE: EntryEnricher + Clone + Send + Sync + 'static,
-    EMR: DeploymentResolver + Clone + Send + Sync + 'static,
+    EMR: DeploymentResolver + ServiceMetadataResolver + Clone + Send + Sync + 'static,
 {
     pub fn handle(&self) -> InvokerHandle<SR> {
         InvokerHandle {
@@ -241,7 +290,10 @@ where
     }
 
     pub fn status_reader(&self) -> ChannelStatusReader {
-        ChannelStatusReader(self.status_tx.clone())
+        ChannelStatusReader {
+            status_tx: self.status_tx.clone(),
+            deployment_health_tx: self.deployment_health_tx.clone(),
+        }
     }
 
     pub async fn run(
@@ -290,6 +342,9 @@ struct ServiceInner<InvocationTaskRunner, SR> {
             Vec<InvocationStatusReport>,
         >,
     >,
+    deployment_health_rx: mpsc::UnboundedReceiver<
+        restate_futures_util::command::Command<(), Vec<DeploymentHealthReport>>,
+    >,
 
     // Channel to communicate with invocation tasks
     invocation_tasks_tx: mpsc::UnboundedSender<InvocationTaskOutput>,
@@ -300,10 +355,12 @@ struct ServiceInner<InvocationTaskRunner, SR> {
 
     // Invoker state machine
     invocation_tasks: JoinSet<()>,
-    retry_timers: TimerQueue<(PartitionLeaderEpoch, InvocationId)>,
+    retry_timers: TimerQueue<(PartitionLeaderEpoch, InvocationId, u32)>,
     quota: quota::InvokerConcurrencyQuota,
     status_store: InvocationStatusStore,
     invocation_state_machine_manager: state_machine_manager::InvocationStateMachineManager<SR>,
+    circuit_breakers: DeploymentCircuitBreakers,
+    deployment_probes: JoinSet<(DeploymentId, bool)>,
 }
 
 impl<ITR, SR> ServiceInner<ITR, SR>
@@ -335,6 +392,10 @@ where
                 let _ = cmd.reply(statuses);
             },
 
+            Some(cmd) = self.deployment_health_rx.recv() => {
+                let _ = cmd.reply(self.circuit_breakers.health_reports());
+            },
+
             Some(input_message) = self.input_rx.recv() => {
                 match input_message {
                     // --- Spillable queue loading/offloading
@@ -353,6 +414,9 @@ where
                     InputCommand::AbortAllPartition { partition } => {
                         self.handle_abort_partition(partition);
                     }
+                    InputCommand::RetryNow { partition, invocation_id } => {
+                        self.handle_retry_now(options, partition, invocation_id);
+                    }
                     InputCommand::Completion { partition, invocation_id, completion } => {
                         self.handle_completion(partition, invocation_id, completion);
                     },
@@ -390,6 +454,7 @@ where
                     }
                     InvocationTaskOutputInner::NewEntry {entry_index, entry, requires_ack} => {
                         self.handle_new_entry(
+                            options,
                             partition,
                             invocation_id,
                             entry_index,
@@ -398,19 +463,19 @@ where
                         ).await
                     },
                     InvocationTaskOutputInner::Closed => {
-                        self.handle_invocation_task_closed(partition, invocation_id).await
+                        self.handle_invocation_task_closed(options, partition, invocation_id).await
                     },
                     InvocationTaskOutputInner::Failed(e) => {
-                        self.handle_invocation_task_failed(partition, invocation_id, e).await
+                        self.handle_invocation_task_failed(options, partition, invocation_id, e).await
                     },
                     InvocationTaskOutputInner::Suspended(indexes) => {
-                        self.handle_invocation_task_suspended(partition, invocation_id, indexes).await
+                        self.handle_invocation_task_suspended(options, partition, invocation_id, indexes).await
                     }
                 };
             },
             timer = self.retry_timers.await_timer() => {
-                let (partition, fid) = timer.into_inner();
-                self.handle_retry_timer_fired(options, partition, fid);
+                let (partition, fid, retry_timer_generation) = timer.into_inner();
+                self.handle_retry_timer_fired(options, partition, fid, retry_timer_generation);
             },
             Some(invocation_task_result) = self.invocation_tasks.join_next() => {
                 if let Err(err) = invocation_task_result {
@@ -422,6 +487,15 @@ where
                 // Other errors are cancellations caused by us (e.g. after AbortAllPartition),
                 // hence we can ignore them.
             }
+            Some(probe_result) = self.deployment_probes.join_next() => {
+                match probe_result {
+                    Ok((deployment_id, healthy)) => {
+                        self.handle_deployment_probe_result(options, deployment_id, healthy);
+                    }
+                    Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+                    Err(_) => {}
+                }
+            }
             _ = &mut shutdown => {
                 debug!("Shutting down the invoker");
                 self.handle_shutdown();
@@ -511,10 +585,36 @@ where
         options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
+        retry_timer_generation: u32,
     ) {
         trace!("Retry timeout fired");
         self.handle_retry_event(options, partition, invocation_id, |sm| {
-            sm.notify_retry_timer_fired()
+            sm.notify_retry_timer_fired(retry_timer_generation)
+        });
+    }
+
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            restate.invocation.id = %invocation_id,
+            restate.invoker.partition_leader_epoch = ?partition,
+        )
+    )]
+    fn handle_retry_now(
+        &mut self,
+        options: &InvokerOptions,
+        partition: PartitionLeaderEpoch,
+        invocation_id: InvocationId,
+    ) {
+        self.handle_retry_event(options, partition, invocation_id, |sm| {
+            if sm.notify_retry_now() {
+                trace!("Retrying now, skipping the remaining retry backoff");
+            } else {
+                trace!(
+                    "Ignoring RetryNow command because the invocation is not waiting for a retry"
+                );
+            }
         });
     }
 
@@ -631,6 +731,7 @@ where
     )]
     async fn handle_new_entry(
         &mut self,
+        options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
         entry_index: EntryIndex,
@@ -661,6 +762,7 @@ where
                     kind: EffectKind::JournalEntry { entry_index, entry },
                 })
                 .await;
+            self.on_deployment_reached(options, partition, &invocation_id);
         } else {
             // If no state machine, this might be an entry for an aborted invocation.
             trace!("No state machine found for given entry");
@@ -707,6 +809,7 @@ where
     )]
     async fn handle_invocation_task_closed(
         &mut self,
+        options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
     ) {
@@ -719,6 +822,7 @@ where
                 restate.invocation.target = %ism.invocation_target,
                 "Invocation task closed correctly");
             self.quota.unreserve_slot();
+            self.on_deployment_reached(options, partition, &invocation_id);
             self.status_store.on_end(&partition, &invocation_id);
             let _ = sender
                 .send(Effect {
@@ -742,6 +846,7 @@ where
     )]
     async fn handle_invocation_task_suspended(
         &mut self,
+        options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
         entry_indexes: HashSet<EntryIndex>,
@@ -755,6 +860,7 @@ where
                 restate.invocation.target = %ism.invocation_target,
                 "Suspending invocation");
             self.quota.unreserve_slot();
+            self.on_deployment_reached(options, partition, &invocation_id);
             self.status_store.on_end(&partition, &invocation_id);
             let _ = sender
                 .send(Effect {
@@ -780,6 +886,7 @@ where
     )]
     async fn handle_invocation_task_failed(
         &mut self,
+        options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
         error: InvocationTaskError,
@@ -788,7 +895,7 @@ where
             .invocation_state_machine_manager
             .remove_invocation(partition, &invocation_id)
         {
-            self.handle_error_event(partition, invocation_id, error, ism)
+            self.handle_error_event(options, partition, invocation_id, error, ism)
                 .await;
         } else {
             // If no state machine, this might be a result for an aborted invocation.
@@ -819,6 +926,8 @@ where
             ism.abort();
             self.quota.unreserve_slot();
             self.status_store.on_end(&partition, &invocation_id);
+            self.circuit_breakers
+                .remove_invocation(partition, invocation_id);
         } else {
             trace!("Ignoring Abort command because there is no matching partition/invocation");
         }
@@ -832,6 +941,7 @@ where
         )
     )]
     fn handle_abort_partition(&mut self, partition: PartitionLeaderEpoch) {
+        self.circuit_breakers.remove_partition(partition);
         if let Some(invocation_state_machines) = self
             .invocation_state_machine_manager
             .remove_partition(partition)
@@ -851,6 +961,81 @@ where
         }
     }
 
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            restate.deployment.id = %deployment_id,
+        )
+    )]
+    fn handle_deployment_probe_result(
+        &mut self,
+        options: &InvokerOptions,
+        deployment_id: DeploymentId,
+        healthy: bool,
+    ) {
+        match self
+            .circuit_breakers
+            .on_probe_result(&deployment_id, healthy)
+        {
+            ProbeOutcome::ProbeAgain(released) => {
+                if healthy {
+                    debug!(
+                        "Deployment is reachable again, resuming {} held invocations to test it",
+                        released.len()
+                    );
+                } else {
+                    trace!("Deployment is still unreachable");
+                }
+                self.schedule_deployment_probe(options, deployment_id);
+                self.resume_held_invocations(options, released);
+            }
+            ProbeOutcome::Closed(released) => {
+                debug!(
+                    "Closing the circuit breaker of the deployment and resuming {} held invocations",
+                    released.len()
+                );
+                self.resume_held_invocations(options, released);
+            }
+        }
+    }
+
+    fn resume_held_invocations(
+        &mut self,
+        options: &InvokerOptions,
+        held_invocations: impl IntoIterator<Item = (PartitionLeaderEpoch, InvocationId)>,
+    ) {
+        for (partition, invocation_id) in held_invocations {
+            self.handle_retry_event(options, partition, invocation_id, |sm| {
+                sm.notify_circuit_breaker_closed()
+            });
+        }
+    }
+
+    /// Records that the invocation reached its deployment, resuming the invocations held by the
+    /// circuit breaker of the deployment if this closes it.
+    fn on_deployment_reached(
+        &mut self,
+        options: &InvokerOptions,
+        partition: PartitionLeaderEpoch,
+        invocation_id: &InvocationId,
+    ) {
+        if let Some(deployment_id) = self
+            .status_store
+            .last_attempt_deployment_id(&partition, invocation_id)
+        {
+            let released = self.circuit_breakers.on_success(&deployment_id);
+            if !released.is_empty() {
+                debug!(
+                    restate.deployment.id = %deployment_id,
+                    "Deployment has been reached, closing its circuit breaker and resuming {} held invocations",
+                    released.len()
+                );
+                self.resume_held_invocations(options, released);
+            }
+        }
+    }
+
     #[instrument(level = "trace", skip_all)]
     fn handle_shutdown(&mut self) {
         let partitions = self
@@ -865,13 +1050,63 @@ where
 
     async fn handle_error_event(
         &mut self,
+        options: &InvokerOptions,
         partition: PartitionLeaderEpoch,
         invocation_id: InvocationId,
         error: InvocationTaskError,
         mut ism: InvocationStateMachine,
     ) {
-        match ism.handle_task_error() {
-            Some(next_retry_timer_duration) if error.is_transient() => {
+        let unreachable_deployment_id = self
+            .status_store
+            .last_attempt_deployment_id(&partition, &invocation_id)
+            .filter(|_| error.is_connect_failure());
+        if let Some(deployment_id) = unreachable_deployment_id {
+            if self
+                .circuit_breakers
+                .on_connect_failure(deployment_id, options.circuit_breaker_failure_threshold())
+            {
+                warn!(
+                    restate.deployment.id = %deployment_id,
+                    "Cannot connect to the deployment, opening its circuit breaker. Invocations targeting it are held until it becomes reachable again."
+                );
+                self.schedule_deployment_probe(options, deployment_id);
+            }
+        }
+
+        let held_by_deployment_id = unreachable_deployment_id.filter(|deployment_id| {
+            self.circuit_breakers
+                .should_hold(deployment_id, partition, invocation_id)
+        });
+
+        match (ism.handle_task_error(), held_by_deployment_id) {
+            (Some(_), Some(deployment_id)) if error.is_transient() => {
+                counter!(INVOKER_INVOCATION_TASK,
+                    "status" => TASK_OP_FAILED,
+                    "transient" => "true"
+                )
+                .increment(1);
+                debug!(
+                    restate.invocation.id = %invocation_id,
+                    restate.invocation.target = %ism.invocation_target,
+                    restate.deployment.id = %deployment_id,
+                    "Error when executing the invocation, holding it until the circuit breaker of the deployment closes: {}", error);
+                trace!("Invocation state: {:?}.", ism.invocation_state_debug());
+
+                self.status_store.on_failure(
+                    partition,
+                    invocation_id,
+                    error.into_invocation_error_report(),
+                    None,
+                );
+                self.invocation_state_machine_manager.register_invocation(
+                    partition,
+                    invocation_id,
+                    ism,
+                );
+                self.circuit_breakers
+                    .hold(deployment_id, partition, invocation_id);
+            }
+            (Some(next_retry_timer_duration), _) if error.is_transient() => {
                 counter!(INVOKER_INVOCATION_TASK,
                     "status" => TASK_OP_FAILED,
                     "transient" => "true"
@@ -892,13 +1127,16 @@ where
                     error.into_invocation_error_report(),
                     Some(next_retry_at),
                 );
+                let retry_timer_generation = ism.retry_timer_generation();
                 self.invocation_state_machine_manager.register_invocation(
                     partition,
                     invocation_id,
                     ism,
                 );
-                self.retry_timers
-                    .sleep_until(next_retry_at, (partition, invocation_id));
+                self.retry_timers.sleep_until(
+                    next_retry_at,
+                    (partition, invocation_id, retry_timer_generation),
+                );
             }
             _ => {
                 counter!(INVOKER_INVOCATION_TASK,
@@ -927,6 +1165,15 @@ where
         }
     }
 
+    fn schedule_deployment_probe(&mut self, options: &InvokerOptions, deployment_id: DeploymentId) {
+        let probe_interval = options.circuit_breaker_probe_interval.into();
+        let probe = self.invocation_task_runner.probe_deployment(deployment_id);
+        self.deployment_probes.spawn(async move {
+            tokio::time::sleep(probe_interval).await;
+            (deployment_id, probe.await)
+        });
+    }
+
     fn start_invocation_task(
         &mut self,
         options: &InvokerOptions,
@@ -977,7 +1224,26 @@ where
             .remove_invocation(partition, &invocation_id)
         {
             f(&mut ism);
-            if ism.is_ready_to_retry() {
+            let held_by_deployment_id = self
+                .status_store
+                .last_attempt_deployment_id(&partition, &invocation_id)
+                .filter(|deployment_id| {
+                    self.circuit_breakers
+                        .should_hold(deployment_id, partition, invocation_id)
+                });
+            if let Some(deployment_id) = held_by_deployment_id.filter(|_| ism.is_ready_to_retry()) {
+                trace!(
+                    restate.invocation.target = %ism.invocation_target,
+                    restate.deployment.id = %deployment_id,
+                    "Not going to retry while the circuit breaker of the deployment is open");
+                self.circuit_breakers
+                    .hold(deployment_id, partition, invocation_id);
+                self.invocation_state_machine_manager.register_invocation(
+                    partition,
+                    invocation_id,
+                    ism,
+                );
+            } else if ism.is_ready_to_retry() {
                 trace!(
                     restate.invocation.target = %ism.invocation_target,
                     "Going to retry now");
@@ -1029,13 +1295,14 @@ mod tests {
     use tokio::sync::mpsc;
     use tokio_util::sync::CancellationToken;
 
-    use restate_invoker_api::{entry_enricher, ServiceHandle};
+    use restate_invoker_api::{entry_enricher, CircuitBreakerState, ServiceHandle};
     use restate_test_util::{check, let_assert};
     use restate_types::identifiers::{LeaderEpoch, PartitionId};
     use restate_types::journal::enriched::EnrichedEntryHeader;
     use restate_types::journal::raw::RawEntry;
     use restate_types::retries::RetryPolicy;
-    use restate_types::schema::deployment::test_util::MockDeploymentMetadataRegistry;
+    use restate_types::schema::Schema;
+    use restate_types::service_protocol::ServiceProtocolVersion;
 
     use crate::invocation_task::InvocationTaskError;
     use crate::quota::InvokerConcurrencyQuota;
@@ -1066,11 +1333,13 @@ mod tests {
         ) {
             let (input_tx, input_rx) = mpsc::unbounded_channel();
             let (status_tx, status_rx) = mpsc::unbounded_channel();
+            let (_, deployment_health_rx) = mpsc::unbounded_channel();
             let (invocation_tasks_tx, invocation_tasks_rx) = mpsc::unbounded_channel();
 
             let service_inner = Self {
                 input_rx,
                 status_rx,
+                deployment_health_rx,
                 invocation_tasks_tx,
                 invocation_tasks_rx,
                 invocation_task_runner,
@@ -1079,6 +1348,8 @@ mod tests {
                 quota: InvokerConcurrencyQuota::new(concurrency_limit),
                 status_store: Default::default(),
                 invocation_state_machine_manager: Default::default(),
+                circuit_breakers: Default::default(),
+                deployment_probes: Default::default(),
             };
             (input_tx, status_tx, service_inner)
         }
@@ -1136,6 +1407,13 @@ mod tests {
                 input_journal,
             ))
         }
+
+        fn probe_deployment(
+            &self,
+            _deployment_id: DeploymentId,
+        ) -> impl Future<Output = bool> + Send + 'static {
+            ready(true)
+        }
     }
 
     #[test(tokio::test)]
@@ -1155,12 +1433,12 @@ mod tests {
         let service = Service::new(
             &invoker_options,
             // all invocations are unknown leading to immediate retries
-            Live::from_value(MockDeploymentMetadataRegistry::default()),
+            Live::from_value(Schema::default()),
             ServiceClient::from_options(
                 &ServiceClientOptions::default(),
                 restate_service_client::AssumeRoleCacheMode::None,
-            )
-            .unwrap(),
+                RequestIdentityKeys::default(),
+            ),
             entry_enricher::test_util::MockEntryEnricher,
         );
 
@@ -1280,7 +1558,7 @@ mod tests {
 
         // Send the close signal
         service_inner
-            .handle_invocation_task_closed(MOCK_PARTITION, invocation_id_1)
+            .handle_invocation_task_closed(&invoker_options, MOCK_PARTITION, invocation_id_1)
             .await;
 
         // Slot should be available again
@@ -1369,6 +1647,7 @@ mod tests {
         // Handle error coming after the abort (this should be noop)
         service_inner
             .handle_invocation_task_failed(
+                &invoker_options,
                 MOCK_PARTITION,
                 invocation_id,
                 InvocationTaskError::EmptySuspensionMessage, /* any error is fine */
@@ -1379,4 +1658,152 @@ mod tests {
         let_assert!(InvokerConcurrencyQuota::Limited { available_slots } = &service_inner.quota);
         assert_eq!(*available_slots, 2);
     }
+
+    #[test(tokio::test)]
+    async fn hold_retries_while_circuit_breaker_is_open() {
+        let invoker_options = InvokerOptionsBuilder::default()
+            .retry_policy(RetryPolicy::fixed_delay(Duration::ZERO, Some(10)))
+            .inactivity_timeout(Duration::ZERO.into())
+            .abort_timeout(Duration::ZERO.into())
+            .disable_eager_state(false)
+            .message_size_warning(NonZeroUsize::new(1024).unwrap())
+            .message_size_limit(None)
+            .build()
+            .unwrap();
+        let invocation_id = InvocationId::mock_random();
+        let deployment_id = DeploymentId::new();
+
+        let (_, _status_tx, mut service_inner) =
+            ServiceInner::mock(|_, _, _, _, _, _, _| pending(), None);
+        let _ = service_inner.register_mock_partition(EmptyStorageReader);
+
+        // Invoke the service, and pin it to the deployment
+        service_inner.handle_invoke(
+            &invoker_options,
+            MOCK_PARTITION,
+            invocation_id,
+            InvocationTarget::mock_virtual_object(),
+            InvokeInputJournal::NoCachedJournal,
+        );
+        service_inner.handle_pinned_deployment(
+            MOCK_PARTITION,
+            invocation_id,
+            PinnedDeployment::new(deployment_id, ServiceProtocolVersion::V1),
+            true,
+        );
+
+        // Fail the invocation, and open the breaker of the deployment before the retry
+        service_inner
+            .handle_invocation_task_failed(
+                &invoker_options,
+                MOCK_PARTITION,
+                invocation_id,
+                InvocationTaskError::EmptySuspensionMessage,
+            )
+            .await;
+        assert!(service_inner
+            .circuit_breakers
+            .on_connect_failure(deployment_id, Some(1)));
+
+        // The retry is held
+        let retry_timer_generation = service_inner
+            .invocation_state_machine_manager
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap()
+            .1
+            .retry_timer_generation();
+        service_inner.handle_retry_timer_fired(
+            &invoker_options,
+            MOCK_PARTITION,
+            invocation_id,
+            retry_timer_generation,
+        );
+        assert!(!service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap()
+            .in_flight());
+        let_assert!([report] = &service_inner.circuit_breakers.health_reports()[..]);
+        assert_eq!(report.held_invocations, 1);
+
+        // A failed probe keeps holding it
+        service_inner.handle_deployment_probe_result(&invoker_options, deployment_id, false);
+        assert_eq!(
+            service_inner.circuit_breakers.state(&deployment_id),
+            CircuitBreakerState::Open
+        );
+
+        // A successful probe half opens the breaker and resumes it as a trial
+        service_inner.handle_deployment_probe_result(&invoker_options, deployment_id, true);
+        assert_eq!(
+            service_inner.circuit_breakers.state(&deployment_id),
+            CircuitBreakerState::HalfOpen
+        );
+        assert!(service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap()
+            .in_flight());
+    }
+
+    #[test(tokio::test)]
+    async fn retry_now_only_retries_invocations_waiting_for_retry() {
+        let invoker_options = InvokerOptionsBuilder::default()
+            .retry_policy(RetryPolicy::fixed_delay(
+                Duration::from_secs(3600),
+                Some(10),
+            ))
+            .inactivity_timeout(Duration::ZERO.into())
+            .abort_timeout(Duration::ZERO.into())
+            .disable_eager_state(false)
+            .message_size_warning(NonZeroUsize::new(1024).unwrap())
+            .message_size_limit(None)
+            .build()
+            .unwrap();
+        let invocation_id = InvocationId::mock_random();
+
+        let (_, _status_tx, mut service_inner) =
+            ServiceInner::mock(|_, _, _, _, _, _, _| pending(), None);
+        let _ = service_inner.register_mock_partition(EmptyStorageReader);
+
+        service_inner.handle_invoke(
+            &invoker_options,
+            MOCK_PARTITION,
+            invocation_id,
+            InvocationTarget::mock_virtual_object(),
+            InvokeInputJournal::NoCachedJournal,
+        );
+
+        // A running invocation is left untouched
+        service_inner.handle_retry_now(&invoker_options, MOCK_PARTITION, invocation_id);
+        let report = service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap();
+        assert!(report.in_flight());
+        assert_eq!(report.retry_count(), 1);
+
+        // A failed invocation is retried without waiting for the retry timer
+        service_inner
+            .handle_invocation_task_failed(
+                &invoker_options,
+                MOCK_PARTITION,
+                invocation_id,
+                InvocationTaskError::EmptySuspensionMessage,
+            )
+            .await;
+        assert!(!service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap()
+            .in_flight());
+
+        service_inner.handle_retry_now(&invoker_options, MOCK_PARTITION, invocation_id);
+        let report = service_inner
+            .status_store
+            .resolve_invocation(MOCK_PARTITION, &invocation_id)
+            .unwrap();
+        assert!(report.in_flight());
+        assert_eq!(report.retry_count(), 2);
+    }
 }
@@ -16,6 +16,7 @@ pub const INVOKER_ENQUEUE: &str = "restate.invoker.enqueue.total";
 pub const INVOKER_INVOCATION_TASK: &str = "restate.invoker.invocation_task.total";
 pub const INVOKER_AVAILABLE_SLOTS: &str = "restate.invoker.available_slots";
 pub const INVOKER_TASK_DURATION: &str = "restate.invoker.task_duration.seconds";
+pub const INVOKER_CIRCUIT_BREAKER_OPEN: &str = "restate.invoker.circuit_breaker.open";
 
 pub const TASK_OP_STARTED: &str = "started";
 pub const TASK_OP_SUSPENDED: &str = "suspended";
@@ -45,5 +46,11 @@ pub(crate) fn describe_metrics() {
         INVOKER_TASK_DURATION,
         Unit::Seconds,
         "Time taken to complete an invoker task"
+    );
+
+    describe_gauge!(
+        INVOKER_CIRCUIT_BREAKER_OPEN,
+        Unit::Count,
+        "Whether the circuit breaker of a deployment is open (1) or closed (0)"
     )
 }
@@ -11,9 +11,10 @@
 use tonic::server::NamedService;
 
 use restate_core::network::grpc_util;
+use restate_core::network::tls::{ReloadableTlsConfig, TlsError};
 use restate_core::{cancellation_watcher, task_center, ShutdownError, TaskKind};
 use restate_rocksdb::RocksError;
-use restate_types::config::{MetadataStoreOptions, RocksDbOptions};
+use restate_types::config::{Configuration, MetadataStoreOptions, RocksDbOptions};
 use restate_types::live::BoxedLiveLoad;
 
 use crate::grpc_svc;
@@ -36,6 +37,8 @@ pub enum Error {
     Shutdown(#[from] ShutdownError),
     #[error("rocksdb error: {0}")]
     RocksDB(#[from] RocksError),
+    #[error("failed loading the tls certificates specified in 'common.tls': {0}")]
+    Tls(#[from] TlsError),
 }
 
 impl LocalMetadataStoreService {
@@ -83,6 +86,11 @@ impl LocalMetadataStoreService {
             .add_service(reflection_service_builder.build()?);
 
         let service = server_builder.into_service();
+        let tls = ReloadableTlsConfig::start_server(
+            Configuration::pinned().common.tls.as_ref(),
+            "metadata-store-tls-reload",
+            |config| config.common.tls.as_ref(),
+        )?;
 
         task_center().spawn_child(
             TaskKind::RpcServer,
@@ -91,6 +99,7 @@ impl LocalMetadataStoreService {
             async move {
                 grpc_util::run_hyper_server(
                     &bind_address,
+                    tls,
                     service,
                     cancellation_watcher(),
                     "metadata-store-grpc",
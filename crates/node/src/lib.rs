@@ -13,25 +13,26 @@ mod network_server;
 mod roles;
 
 use std::future::Future;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use tokio::sync::oneshot;
 
 use codederror::CodedError;
 #[cfg(feature = "replicated-loglet")]
 use restate_log_server::LogServerService;
 use restate_types::live::Live;
-use tokio::time::Instant;
-use tracing::{debug, error, info, trace};
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, error, info, trace, warn};
 
 use restate_bifrost::BifrostService;
 use restate_core::metadata_store::{MetadataStoreClientError, ReadWriteError};
 use restate_core::network::MessageRouterBuilder;
 use restate_core::network::Networking;
+use restate_core::{cancellation_watcher, task_center, TaskKind};
 use restate_core::{spawn_metadata_manager, MetadataBuilder, MetadataKind, MetadataManager};
-use restate_core::{task_center, TaskKind};
 use restate_metadata_store::local::LocalMetadataStoreService;
 use restate_metadata_store::MetadataStoreClient;
-use restate_types::config::{CommonOptions, Configuration};
+use restate_service_client::RequestIdentityKeys;
+use restate_types::config::{CommonOptions, Configuration, ServiceClientOptions};
 use restate_types::logs::metadata::{create_static_metadata, Logs};
 use restate_types::metadata_store::keys::{
     BIFROST_CONFIG_KEY, NODES_CONFIG_KEY, PARTITION_TABLE_KEY,
@@ -93,8 +94,14 @@ pub enum BuildError {
     #[error("failed validating and updating cluster marker: {0}")]
     #[code(unknown)]
     ClusterValidation(#[from] ClusterValidationError),
+    #[error("reading the request identity keys failed: {0}")]
+    #[code(unknown)]
+    RequestIdentityKeys(#[from] restate_service_client::BuildError),
 }
 
+/// How often the request identity key file is checked for changes.
+const KEY_FILE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct Node {
     updateable_config: Live<Configuration>,
     metadata_manager: MetadataManager<Networking>,
@@ -105,6 +112,7 @@ pub struct Node {
     #[cfg(feature = "replicated-loglet")]
     log_server: Option<LogServerService>,
     server: NetworkServer,
+    request_identity_keys: RequestIdentityKeys,
 }
 
 impl Node {
@@ -189,6 +197,10 @@ impl Node {
             None
         };
 
+        // shared by the service clients of all roles, so that they pick up the reloaded keys
+        let request_identity_keys =
+            RequestIdentityKeys::from_options(&config.common.service_client)?;
+
         let admin_role = if config.has_role(Role::Admin) {
             Some(AdminRole::new(
                 tc.clone(),
@@ -198,6 +210,7 @@ impl Node {
                 metadata_manager.writer(),
                 &mut router_builder,
                 metadata_store_client.clone(),
+                request_identity_keys.clone(),
             )?)
         } else {
             None
@@ -213,6 +226,7 @@ impl Node {
                     bifrost_svc.handle(),
                     metadata_store_client,
                     updating_schema_information,
+                    request_identity_keys.clone(),
                 )
                 .await?,
             )
@@ -222,9 +236,13 @@ impl Node {
 
         let server = NetworkServer::new(
             networking.connection_manager(),
-            worker_role
-                .as_ref()
-                .map(|worker| WorkerDependencies::new(worker.storage_query_context().clone())),
+            worker_role.as_ref().map(|worker| {
+                WorkerDependencies::new(
+                    worker.storage_query_context().clone(),
+                    worker.state_change_feed(),
+                    worker.partition_processor_manager_handle(),
+                )
+            }),
             admin_role.as_ref().map(|cluster_controller| {
                 AdminDependencies::new(
                     cluster_controller.cluster_controller_handle(),
@@ -252,6 +270,7 @@ impl Node {
             #[cfg(feature = "replicated-loglet")]
             log_server,
             server,
+            request_identity_keys,
         })
     }
 
@@ -272,6 +291,41 @@ impl Node {
             )?;
         }
 
+        let request_identity_keys = self.request_identity_keys;
+        tc.spawn(
+            TaskKind::Disposable,
+            "request-identity-keys-reload",
+            None,
+            async move {
+                let mut config_watcher = Configuration::watcher();
+                let mut shutdown = std::pin::pin!(cancellation_watcher());
+                // The key file can be replaced without any configuration change, e.g. when it's
+                // mounted from a secret, hence it's checked for changes too
+                let mut key_file_check = tokio::time::interval(KEY_FILE_CHECK_INTERVAL);
+                key_file_check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                let mut key_file_modified =
+                    key_file_modified_time(&Configuration::pinned().common.service_client);
+
+                loop {
+                    tokio::select! {
+                        _ = config_watcher.changed() => {}
+                        _ = key_file_check.tick() => {
+                            if key_file_modified_time(&Configuration::pinned().common.service_client) == key_file_modified {
+                                continue;
+                            }
+                        }
+                        _ = &mut shutdown => return Ok(()),
+                    }
+
+                    let config = Configuration::pinned();
+                    key_file_modified = key_file_modified_time(&config.common.service_client);
+                    if let Err(err) = request_identity_keys.reload(&config.common.service_client) {
+                        warn!("Failed reloading the request identity keys, keeping the current ones: {err}");
+                    }
+                }
+            },
+        )?;
+
         let metadata_store_client = restate_metadata_store::local::create_client(
             config.common.metadata_store_address.clone(),
         );
@@ -576,3 +630,8 @@ impl Node {
             .await
     }
 }
+
+fn key_file_modified_time(options: &ServiceClientOptions) -> Option<SystemTime> {
+    let path = options.request_identity_private_key_pem_file.as_ref()?;
+    std::fs::metadata(path).ok()?.modified().ok()
+}
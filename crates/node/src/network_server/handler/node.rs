@@ -10,6 +10,7 @@
 
 use arrow_flight::encode::FlightDataEncoderBuilder;
 use arrow_flight::error::FlightError;
+use datafusion::execution::SendableRecordBatchStream;
 use futures::stream::BoxStream;
 use futures::TryStreamExt;
 use tokio_stream::StreamExt;
@@ -17,12 +18,18 @@ use tonic::{Request, Response, Status, Streaming};
 
 use restate_core::network::protobuf::node_svc::node_svc_server::NodeSvc;
 use restate_core::network::protobuf::node_svc::IdentResponse;
-use restate_core::network::protobuf::node_svc::{StorageQueryRequest, StorageQueryResponse};
+use restate_core::network::protobuf::node_svc::{
+    LeaderPartitionsResponse, ScanPartitionRequest, StateChangeEvent, StorageQueryRequest,
+    StorageQueryResponse, SubscribeStateChangesRequest,
+};
 use restate_core::network::ConnectionManager;
 use restate_core::network::ProtocolError;
 use restate_core::{metadata, TaskCenter};
+use restate_types::identifiers::PartitionId;
+use restate_types::logs::Lsn;
 use restate_types::protobuf::common::NodeStatus;
 use restate_types::protobuf::node::Message;
+use restate_worker::{StateChange, StateChangeFeedError, StateMutation};
 
 use crate::network_server::WorkerDependencies;
 
@@ -78,20 +85,92 @@ impl NodeSvc for NodeSvcHandler {
             })
             .await?;
 
-        let schema = record_stream.schema();
-        let response_stream =
-            FlightDataEncoderBuilder::new()
-                // CLI is expecting schema information
-                .with_schema(schema)
-                .build(record_stream.map_err(|err| {
-                    FlightError::from(datafusion::arrow::error::ArrowError::from(err))
-                }))
-                .map_ok(|flight_data| StorageQueryResponse {
-                    header: flight_data.data_header,
-                    data: flight_data.data_body,
-                })
-                .map_err(Status::from);
-        Ok(Response::new(Box::pin(response_stream)))
+        Ok(Response::new(encode_record_batches(record_stream)))
+    }
+
+    type ScanPartitionStream = BoxStream<'static, Result<StorageQueryResponse, Status>>;
+
+    async fn scan_partition(
+        &self,
+        request: Request<ScanPartitionRequest>,
+    ) -> Result<Response<Self::ScanPartitionStream>, Status> {
+        let Some(ref worker) = self.worker else {
+            return Err(Status::failed_precondition("Not a worker node"));
+        };
+        let request = request.into_inner();
+
+        let record_stream = self
+            .task_center
+            .run_in_scope_sync("scan-partition", None, || {
+                worker.query_context.scan_local_partition(
+                    &request.table,
+                    PartitionId::from(request.partition_id),
+                    request.range_start..=request.range_end,
+                    &request.columns,
+                )
+            })
+            .map_err(|err| {
+                Status::invalid_argument(format!(
+                    "failed scanning partition {} of table '{}': {}",
+                    request.partition_id, request.table, err
+                ))
+            })?;
+
+        Ok(Response::new(encode_record_batches(record_stream)))
+    }
+
+    async fn get_leader_partitions(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<LeaderPartitionsResponse>, Status> {
+        let Some(ref worker) = self.worker else {
+            return Err(Status::failed_precondition("Not a worker node"));
+        };
+
+        let partition_ids = worker
+            .processors_manager
+            .get_state()
+            .await
+            .map_err(|_| Status::unavailable("Node is shutting down"))?
+            .into_iter()
+            .filter(|(_, status)| status.is_effective_leader())
+            .map(|(partition_id, _)| u64::from(partition_id))
+            .collect();
+
+        Ok(Response::new(LeaderPartitionsResponse { partition_ids }))
+    }
+
+    type SubscribeStateChangesStream = BoxStream<'static, Result<StateChangeEvent, Status>>;
+
+    async fn subscribe_state_changes(
+        &self,
+        request: Request<SubscribeStateChangesRequest>,
+    ) -> Result<Response<Self::SubscribeStateChangesStream>, Status> {
+        let Some(ref worker) = self.worker else {
+            return Err(Status::failed_precondition("Not a worker node"));
+        };
+        let request = request.into_inner();
+        let service = request.service;
+        let resume_after = request
+            .resume_after
+            .into_iter()
+            .map(|(partition_id, lsn)| (PartitionId::from(partition_id), Lsn::from(lsn)))
+            .collect();
+
+        let state_changes = worker
+            .state_change_feed
+            .subscribe(&resume_after)
+            .map_err(into_status)?;
+
+        // the feed ends after the first error
+        let event_stream = state_changes.filter_map(move |state_change| match state_change {
+            Ok(state_change) => (service.is_empty()
+                || state_change.service_id.service_name == service)
+                .then(|| Ok(into_state_change_event(state_change))),
+            Err(err) => Some(Err(into_status(err))),
+        });
+
+        Ok(Response::new(Box::pin(event_stream)))
     }
 
     type CreateConnectionStream = BoxStream<'static, Result<Message, Status>>;
@@ -122,3 +201,50 @@ impl NodeSvc for NodeSvcHandler {
         Ok(Response::new(output_stream))
     }
 }
+
+fn into_state_change_event(state_change: StateChange) -> StateChangeEvent {
+    let (state_key, value) = match state_change.mutation {
+        StateMutation::Set { key, value } => (Some(key), Some(value)),
+        StateMutation::Clear { key } => (Some(key), None),
+        StateMutation::ClearAll => (None, None),
+    };
+
+    StateChangeEvent {
+        partition_id: u64::from(state_change.partition_id),
+        lsn: state_change.lsn.as_u64(),
+        service_name: state_change.service_id.service_name.to_string(),
+        service_key: state_change.service_id.key.to_string(),
+        state_key,
+        value,
+    }
+}
+
+fn into_status(err: StateChangeFeedError) -> Status {
+    match err {
+        StateChangeFeedError::NoPartitionTable | StateChangeFeedError::PartitionNotRunning(_) => {
+            Status::unavailable(err.to_string())
+        }
+        StateChangeFeedError::Trimmed(_, _) | StateChangeFeedError::Lagged(_) => {
+            Status::data_loss(err.to_string())
+        }
+    }
+}
+
+fn encode_record_batches(
+    record_stream: SendableRecordBatchStream,
+) -> BoxStream<'static, Result<StorageQueryResponse, Status>> {
+    let schema = record_stream.schema();
+    let response_stream = FlightDataEncoderBuilder::new()
+        // CLI is expecting schema information
+        .with_schema(schema)
+        .build(
+            record_stream
+                .map_err(|err| FlightError::from(datafusion::arrow::error::ArrowError::from(err))),
+        )
+        .map_ok(|flight_data| StorageQueryResponse {
+            header: flight_data.data_header,
+            data: flight_data.data_body,
+        })
+        .map_err(Status::from);
+    Box::pin(response_stream)
+}
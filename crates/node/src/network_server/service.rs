@@ -16,11 +16,14 @@ use restate_admin::cluster_controller::protobuf::cluster_ctrl_svc_server::Cluste
 use restate_admin::cluster_controller::ClusterControllerHandle;
 use restate_core::network::grpc_util::run_hyper_server;
 use restate_core::network::protobuf::node_svc::node_svc_server::NodeSvcServer;
+use restate_core::network::tls::ReloadableTlsConfig;
 use restate_core::network::ConnectionManager;
+use restate_core::worker_api::ProcessorsManagerHandle;
 use restate_core::{cancellation_watcher, task_center};
 use restate_metadata_store::MetadataStoreClient;
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_types::config::CommonOptions;
+use restate_worker::StateChangeFeed;
 
 use crate::network_server::handler;
 use crate::network_server::handler::cluster_ctrl::ClusterCtrlSvcHandler;
@@ -109,8 +112,14 @@ impl NetworkServer {
         // Multiplex both grpc and http based on content-type
         let service = MultiplexService::new(router, server_builder.into_service());
 
+        let tls =
+            ReloadableTlsConfig::start_server(options.tls.as_ref(), "node-tls-reload", |config| {
+                config.common.tls.as_ref()
+            })?;
+
         run_hyper_server(
             &options.bind_address,
+            tls,
             service,
             cancellation_watcher(),
             "node-grpc",
@@ -131,11 +140,21 @@ async fn handler_404() -> (http::StatusCode, &'static str) {
 
 pub struct WorkerDependencies {
     pub query_context: QueryContext,
+    pub state_change_feed: StateChangeFeed,
+    pub processors_manager: ProcessorsManagerHandle,
 }
 
 impl WorkerDependencies {
-    pub fn new(query_context: QueryContext) -> Self {
-        WorkerDependencies { query_context }
+    pub fn new(
+        query_context: QueryContext,
+        state_change_feed: StateChangeFeed,
+        processors_manager: ProcessorsManagerHandle,
+    ) -> Self {
+        WorkerDependencies {
+            query_context,
+            state_change_feed,
+            processors_manager,
+        }
     }
 }
 
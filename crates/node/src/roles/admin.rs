@@ -23,7 +23,7 @@ use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
 use restate_core::network::MessageRouterBuilder;
 use restate_core::network::Networking;
 use restate_core::{task_center, Metadata, MetadataWriter, TaskCenter, TaskKind};
-use restate_service_client::{AssumeRoleCacheMode, ServiceClient};
+use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys, ServiceClient};
 use restate_service_protocol::discovery::ServiceDiscovery;
 use restate_types::config::Configuration;
 use restate_types::config::IngressOptions;
@@ -38,9 +38,6 @@ pub enum AdminRoleBuildError {
     #[error("failed building the admin service: {0}")]
     #[code(unknown)]
     AdminService(#[from] restate_admin::service::BuildError),
-    #[error("failed building the service client: {0}")]
-    #[code(unknown)]
-    ServiceClient(#[from] restate_service_client::BuildError),
 }
 
 pub struct AdminRole {
@@ -58,13 +55,17 @@ impl AdminRole {
         metadata_writer: MetadataWriter,
         router_builder: &mut MessageRouterBuilder,
         metadata_store_client: MetadataStoreClient,
+        request_identity_keys: RequestIdentityKeys,
     ) -> Result<Self, AdminRoleBuildError> {
         let config = updateable_config.pinned();
 
         // Total duration roughly 1s
         let retry_policy = RetryPolicy::exponential(Duration::from_millis(100), 2.0, Some(4), None);
-        let client =
-            ServiceClient::from_options(&config.common.service_client, AssumeRoleCacheMode::None)?;
+        let client = ServiceClient::from_options(
+            &config.common.service_client,
+            AssumeRoleCacheMode::None,
+            request_identity_keys.clone(),
+        );
         let service_discovery = ServiceDiscovery::new(retry_policy, client);
 
         let admin = AdminService::new(
@@ -72,6 +73,7 @@ impl AdminRole {
             metadata_store_client,
             config.ingress.clone(),
             service_discovery,
+            request_identity_keys,
         );
 
         let controller = restate_admin::cluster_controller::Service::new(
@@ -14,15 +14,18 @@ use tokio::sync::oneshot;
 use restate_bifrost::Bifrost;
 use restate_core::network::MessageRouterBuilder;
 use restate_core::network::Networking;
+use restate_core::worker_api::ProcessorsManagerHandle;
 use restate_core::{cancellation_watcher, task_center, Metadata, MetadataKind};
 use restate_core::{ShutdownError, TaskKind};
 use restate_metadata_store::MetadataStoreClient;
+use restate_service_client::RequestIdentityKeys;
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_types::config::Configuration;
 use restate_types::live::Live;
 use restate_types::schema::subscriptions::SubscriptionResolver;
 use restate_types::schema::Schema;
 use restate_types::Version;
+use restate_worker::StateChangeFeed;
 use restate_worker::SubscriptionController;
 use restate_worker::Worker;
 
@@ -73,6 +76,7 @@ impl WorkerRole {
         bifrost: Bifrost,
         metadata_store_client: MetadataStoreClient,
         updating_schema_information: Live<Schema>,
+        request_identity_keys: RequestIdentityKeys,
     ) -> Result<Self, WorkerRoleBuildError> {
         let worker = Worker::create(
             updateable_config,
@@ -82,6 +86,7 @@ impl WorkerRole {
             router_builder,
             updating_schema_information,
             metadata_store_client,
+            request_identity_keys,
         )
         .await?;
 
@@ -92,6 +97,14 @@ impl WorkerRole {
         self.worker.storage_query_context()
     }
 
+    pub fn state_change_feed(&self) -> StateChangeFeed {
+        self.worker.state_change_feed()
+    }
+
+    pub fn partition_processor_manager_handle(&self) -> ProcessorsManagerHandle {
+        self.worker.partition_processor_manager_handle()
+    }
+
     pub async fn start(
         self,
         all_partitions_started_rx: oneshot::Receiver<()>,
@@ -0,0 +1,662 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use restate_storage_api::{Result, StorageError};
+use restate_types::identifiers::PartitionId;
+use restate_types::storage::{StorageCodec, StorageCodecKind, StorageDecode, StorageEncode};
+
+use crate::keys::{define_table_key, KeyKind, TableKey};
+use crate::TableKind::Blob;
+use crate::{StorageAccess, TableScan, TableScanIterationDecision};
+
+/// Sha256 hash of a blob, used to address it in the [`BlobStore`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlobHash([u8; BlobHash::LENGTH]);
+
+impl BlobHash {
+    pub const LENGTH: usize = 32;
+
+    pub fn of(data: &[u8]) -> Self {
+        Self(Sha256::digest(data).into())
+    }
+
+    pub fn from_slice(slice: &[u8]) -> Option<Self> {
+        Some(Self(slice.try_into().ok()?))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; BlobHash::LENGTH] {
+        &self.0
+    }
+}
+
+impl fmt::Display for BlobHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BlobHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BlobHash({})", self)
+    }
+}
+
+/// Content-addressed store for the payloads which are too large to be stored inline in RocksDB.
+///
+/// The methods perform blocking I/O, they must not be called from the async runtime threads.
+pub trait BlobStore: Send + Sync + 'static {
+    /// Stores the blob with the given hash. Storing the same content twice is a no-op.
+    ///
+    /// The blob must be durable once this method returns.
+    fn put(&self, hash: &BlobHash, data: &[u8]) -> io::Result<()>;
+
+    fn get(&self, hash: &BlobHash) -> io::Result<Option<Bytes>>;
+
+    /// Deletes the blob. Deleting a missing blob is a no-op.
+    fn delete(&self, hash: &BlobHash) -> io::Result<()>;
+}
+
+/// [`BlobStore`] storing every blob as a file on the local filesystem, named after its hash.
+#[derive(Debug)]
+pub struct LocalBlobStore {
+    root: PathBuf,
+    tmp_file_counter: AtomicU64,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            tmp_file_counter: AtomicU64::new(0),
+        }
+    }
+
+    fn blob_path(&self, hash: &BlobHash) -> PathBuf {
+        let hash = hash.to_string();
+        // Spread the blobs over sub-directories to keep the directories small
+        self.root.join(&hash[..2]).join(hash)
+    }
+}
+
+impl BlobStore for LocalBlobStore {
+    fn put(&self, hash: &BlobHash, data: &[u8]) -> io::Result<()> {
+        let path = self.blob_path(hash);
+        if path.exists() {
+            return Ok(());
+        }
+
+        let dir = path.parent().expect("blob path has a parent directory");
+        fs::create_dir_all(dir)?;
+
+        // Write to a temporary file first, so that a blob is either complete or absent
+        let tmp_path = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            self.tmp_file_counter.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+
+        Ok(())
+    }
+
+    fn get(&self, hash: &BlobHash) -> io::Result<Option<Bytes>> {
+        match fs::read(self.blob_path(hash)) {
+            Ok(data) => Ok(Some(data.into())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn delete(&self, hash: &BlobHash) -> io::Result<()> {
+        match fs::remove_file(self.blob_path(hash)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Number of references to a blob from the journal entries and the states of the partition. Only
+// the blobs with at least one reference have a count.
+define_table_key!(
+    Blob,
+    KeyKind::BlobRefCount,
+    BlobRefCountKey(partition_id: PartitionId, hash: Bytes)
+);
+
+// Blobs whose last reference has been deleted. They are written together with the deletion of the
+// reference count, so that the blobs are garbage collected even if the node restarts before.
+define_table_key!(
+    Blob,
+    KeyKind::BlobReleased,
+    BlobReleasedKey(partition_id: PartitionId, hash: Bytes)
+);
+
+/// Blobs which are candidates for garbage collection.
+#[derive(Debug, Default)]
+struct BlobReferences {
+    /// Blobs referenced by writes which are not committed yet. They must not be collected, since
+    /// their reference count doesn't account for these writes yet.
+    pinned: HashMap<BlobHash, usize>,
+    /// Blobs written for writes which have been discarded, and blobs whose collection has to be
+    /// retried. Unlike the released blobs, they are not recorded in the partition store: if the
+    /// node restarts before they are collected, they are leaked.
+    abandoned: HashSet<BlobHash>,
+}
+
+/// Decides which payloads of a partition are offloaded to the [`BlobStore`], resolves the
+/// references to the offloaded payloads and garbage collects the blobs which are no longer
+/// referenced.
+///
+/// Payloads are offloaded only if [`StorageOptions::blob_offload_threshold`] is set, but
+/// references are always resolved and counted, so that the threshold can be unset without losing
+/// access to the payloads offloaded so far, nor leaking them. Because blobs are content-addressed,
+/// the same blob can be referenced by several journal entries and states: the references are
+/// counted in the partition store, and a blob is deleted once its count drops to zero and the
+/// writes which dropped it are durable, see [`PartitionStore::flush_memtables`].
+///
+/// [`StorageOptions::blob_offload_threshold`]: restate_types::config::StorageOptions::blob_offload_threshold
+/// [`PartitionStore::flush_memtables`]: crate::PartitionStore::flush_memtables
+#[derive(Clone)]
+pub struct BlobOffloader {
+    partition_id: PartitionId,
+    threshold: Option<NonZeroUsize>,
+    store: Arc<dyn BlobStore>,
+    references: Arc<Mutex<BlobReferences>>,
+}
+
+impl fmt::Debug for BlobOffloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlobOffloader")
+            .field("partition_id", &self.partition_id)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl BlobOffloader {
+    pub fn new(
+        partition_id: PartitionId,
+        threshold: Option<NonZeroUsize>,
+        store: Arc<dyn BlobStore>,
+    ) -> Self {
+        Self {
+            partition_id,
+            threshold,
+            store,
+            references: Arc::default(),
+        }
+    }
+
+    /// Writes the payload to the blob store if it exceeds the offload threshold. Returns the hash
+    /// of the blob if the payload has been offloaded, in which case the blob is pinned until
+    /// [`Self::unpin`] is called.
+    pub(crate) async fn offload(&self, payload: Bytes) -> Option<BlobHash> {
+        let threshold = self.threshold?;
+        if payload.len() <= threshold.get() {
+            return None;
+        }
+
+        let store = Arc::clone(&self.store);
+        let references = Arc::clone(&self.references);
+        let result = tokio::task::spawn_blocking(move || {
+            let hash = BlobHash::of(&payload);
+            // Pin the blob before writing it, so that the garbage collection can't delete it
+            // before the write referencing it is committed
+            *references.lock().unwrap().pinned.entry(hash).or_default() += 1;
+            store.put(&hash, &payload).map(|()| hash).map_err(|err| {
+                unpin(&mut references.lock().unwrap(), [hash]);
+                (payload.len(), err)
+            })
+        })
+        .await
+        .expect("blob store writes don't panic");
+
+        match result {
+            Ok(hash) => Some(hash),
+            Err((len, err)) => {
+                warn!(
+                    "Failed to offload payload of {} bytes to the blob store, storing it inline: {}",
+                    len, err
+                );
+                None
+            }
+        }
+    }
+
+    pub(crate) async fn resolve(&self, hash: BlobHash) -> Result<Bytes> {
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || store.get(&hash))
+            .await
+            .map_err(|err| StorageError::Generic(err.into()))?
+            .map_err(|err| StorageError::Generic(err.into()))?
+            .ok_or_else(|| StorageError::Generic(anyhow::anyhow!("blob '{}' is missing", hash)))
+    }
+
+    pub(crate) fn partition_id(&self) -> PartitionId {
+        self.partition_id
+    }
+
+    /// Unpins the blobs once the writes referencing them are committed or discarded.
+    pub(crate) fn unpin(&self, hashes: impl IntoIterator<Item = BlobHash>) {
+        unpin(&mut self.references.lock().unwrap(), hashes);
+    }
+
+    /// Marks the blobs written for discarded writes as candidates for the garbage collection.
+    pub(crate) fn abandon(&self, hashes: impl IntoIterator<Item = BlobHash>) {
+        self.references.lock().unwrap().abandoned.extend(hashes);
+    }
+
+    pub(crate) fn take_abandoned(&self) -> Vec<BlobHash> {
+        self.references.lock().unwrap().abandoned.drain().collect()
+    }
+
+    /// Deletes the candidate blobs which are neither pinned nor referenced any more, and forgets
+    /// that they have been released. The blobs which can't be deleted yet are abandoned, to retry
+    /// on the next collection.
+    pub(crate) async fn collect_garbage<F, G>(
+        &self,
+        candidates: Vec<BlobHash>,
+        mut is_referenced: F,
+        mut forget_released: G,
+    ) where
+        F: FnMut(&BlobHash) -> Result<bool> + Send + 'static,
+        G: FnMut(&BlobHash) + Send + 'static,
+    {
+        if candidates.is_empty() {
+            return;
+        }
+
+        let store = Arc::clone(&self.store);
+        let references = Arc::clone(&self.references);
+        tokio::task::spawn_blocking(move || {
+            for hash in candidates {
+                // Hold the lock while deleting, so that the blob can't be pinned concurrently
+                let mut references = references.lock().unwrap();
+                if references.pinned.contains_key(&hash) {
+                    references.abandoned.insert(hash);
+                    continue;
+                }
+
+                // A blob which is referenced again has no released record any more, since the
+                // new reference deleted it
+                let result = is_referenced(&hash).and_then(|is_referenced| {
+                    if !is_referenced {
+                        store
+                            .delete(&hash)
+                            .map_err(|err| StorageError::Generic(err.into()))?;
+                        forget_released(&hash);
+                    }
+                    Ok(())
+                });
+                if let Err(err) = result {
+                    warn!("Failed to garbage collect blob '{}': {}", hash, err);
+                    references.abandoned.insert(hash);
+                }
+            }
+        })
+        .await
+        .expect("blob garbage collection doesn't panic");
+    }
+
+    /// Offloads the value encoded with the [`StorageCodec`] if it exceeds the threshold. In this
+    /// case, the returned value is a [`StorageCodecKind::BlobReference`] to the offloaded value,
+    /// along with the hash of the pinned blob.
+    pub(crate) async fn offload_encoded_value(
+        &self,
+        encoded_value: Bytes,
+    ) -> (Bytes, Option<BlobHash>) {
+        match self.offload(encoded_value.clone()).await {
+            Some(hash) => {
+                let mut reference = BytesMut::with_capacity(1 + BlobHash::LENGTH);
+                reference.put_u8(StorageCodecKind::BlobReference.into());
+                reference.put_slice(hash.as_bytes());
+                (reference.freeze(), Some(hash))
+            }
+            None => (encoded_value, None),
+        }
+    }
+
+    /// Decodes a value written by [`Self::offload_encoded_value`], unless it references a blob
+    /// which must be loaded with [`Self::load_value`].
+    pub(crate) fn decode_value<V: StorageDecode>(mut value: &[u8]) -> Result<MaybeOffloaded<V>> {
+        if let Some(hash) = blob_reference(value)? {
+            return Ok(MaybeOffloaded::Offloaded(hash));
+        }
+
+        StorageCodec::decode::<V, _>(&mut value)
+            .map(MaybeOffloaded::Inline)
+            .map_err(|err| StorageError::Generic(err.into()))
+    }
+
+    pub(crate) async fn load_value<V: StorageDecode>(&self, value: MaybeOffloaded<V>) -> Result<V> {
+        match value {
+            MaybeOffloaded::Inline(value) => Ok(value),
+            MaybeOffloaded::Offloaded(hash) => {
+                let mut blob = self.resolve(hash).await?;
+                StorageCodec::decode::<V, _>(&mut blob)
+                    .map_err(|err| StorageError::Generic(err.into()))
+            }
+        }
+    }
+}
+
+/// A value read from the partition store, whose payload might have been offloaded to the
+/// [`BlobStore`].
+#[derive(Debug)]
+pub(crate) enum MaybeOffloaded<V> {
+    Inline(V),
+    Offloaded(BlobHash),
+}
+
+fn unpin(references: &mut BlobReferences, hashes: impl IntoIterator<Item = BlobHash>) {
+    for hash in hashes {
+        if let Entry::Occupied(mut pinned) = references.pinned.entry(hash) {
+            *pinned.get_mut() -= 1;
+            if *pinned.get() == 0 {
+                pinned.remove();
+            }
+        }
+    }
+}
+
+/// Returns the hash of the blob if the value written by [`BlobOffloader::offload_encoded_value`]
+/// references one.
+pub(crate) fn blob_reference(value: &[u8]) -> Result<Option<BlobHash>> {
+    if value.first() == Some(&u8::from(StorageCodecKind::BlobReference)) {
+        BlobHash::from_slice(&value[1..])
+            .map(Some)
+            .ok_or(StorageError::DataIntegrityError)
+    } else {
+        Ok(None)
+    }
+}
+
+fn blob_ref_count_key(partition_id: PartitionId, hash: &BlobHash) -> BlobRefCountKey {
+    BlobRefCountKey::default()
+        .partition_id(partition_id)
+        .hash(Bytes::copy_from_slice(hash.as_bytes()))
+}
+
+fn blob_released_key(partition_id: PartitionId, hash: &BlobHash) -> BlobReleasedKey {
+    BlobReleasedKey::default()
+        .partition_id(partition_id)
+        .hash(Bytes::copy_from_slice(hash.as_bytes()))
+}
+
+/// Forgets that the blob has been released, once it has been garbage collected.
+pub(crate) fn forget_released_blob<S: StorageAccess>(storage: &mut S, hash: &BlobHash) {
+    let key = blob_released_key(storage.blob_offloader().partition_id(), hash);
+    storage.delete_key(&key);
+}
+
+/// Returns the blobs whose last reference has been deleted by the committed writes.
+pub(crate) fn released_blobs<S: StorageAccess>(storage: &S) -> Result<Vec<BlobHash>> {
+    let partition_id = storage.blob_offloader().partition_id();
+    storage
+        .for_each_key_value_in_place(
+            TableScan::<BlobReleasedKey>::SinglePartition(partition_id),
+            |mut k, _| {
+                TableScanIterationDecision::Emit(
+                    BlobReleasedKey::deserialize_from(&mut k).and_then(|key| {
+                        BlobHash::from_slice(key.hash_ok_or()?)
+                            .ok_or(StorageError::DataIntegrityError)
+                    }),
+                )
+            },
+        )
+        .into_iter()
+        .collect()
+}
+
+pub(crate) fn blob_ref_count<S: StorageAccess>(storage: &mut S, hash: &BlobHash) -> Result<u64> {
+    let key = blob_ref_count_key(storage.blob_offloader().partition_id(), hash);
+    storage.get_kv_raw(key, |_, value| match value {
+        Some(value) => value
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| StorageError::DataIntegrityError),
+        None => Ok(0),
+    })
+}
+
+/// Counts a new reference to the pinned blob returned by [`BlobOffloader::offload`].
+pub(crate) fn retain_blob<S: StorageAccess>(storage: &mut S, hash: BlobHash) -> Result<()> {
+    let partition_id = storage.blob_offloader().partition_id();
+    let count = blob_ref_count(storage, &hash)?;
+    if count == 0 {
+        storage.delete_key(&blob_released_key(partition_id, &hash));
+    }
+    storage.put_kv_raw(
+        blob_ref_count_key(partition_id, &hash),
+        (count + 1).to_be_bytes(),
+    );
+    storage.blob_retained(hash);
+    Ok(())
+}
+
+/// Drops a reference to the blob, releasing it if it was the last one.
+pub(crate) fn release_blob<S: StorageAccess>(storage: &mut S, hash: BlobHash) -> Result<()> {
+    let partition_id = storage.blob_offloader().partition_id();
+    let count = blob_ref_count(storage, &hash)?;
+    let key = blob_ref_count_key(partition_id, &hash);
+    if count > 1 {
+        storage.put_kv_raw(key, (count - 1).to_be_bytes());
+    } else {
+        storage.delete_key(&key);
+        storage.put_kv_raw(blob_released_key(partition_id, &hash), Bytes::new());
+    }
+    Ok(())
+}
+
+/// Like [`StorageAccess::put_kv`], but the encoded value is written to the blob store if it
+/// exceeds the offload threshold. Such values must be read with [`get_offloadable_value`] and
+/// deleted with [`delete_offloadable_value`].
+pub(crate) async fn put_offloadable_value<S, K, V>(storage: &mut S, key: K, value: V) -> Result<()>
+where
+    S: StorageAccess,
+    K: TableKey + Clone,
+    V: StorageEncode,
+{
+    let previous_blob = referenced_blob(storage, key.clone())?;
+
+    let value_buffer = storage.cleared_value_buffer_mut(0);
+    StorageCodec::encode(&value, value_buffer).unwrap();
+    let value_buffer = value_buffer.split().freeze();
+    let blob_offloader = storage.blob_offloader().clone();
+    let (value_buffer, blob) = blob_offloader.offload_encoded_value(value_buffer).await;
+
+    storage.put_kv_raw(key, value_buffer);
+    if let Some(hash) = blob {
+        retain_blob(storage, hash)?;
+    }
+    if let Some(hash) = previous_blob {
+        release_blob(storage, hash)?;
+    }
+    Ok(())
+}
+
+/// Reads a value written by [`put_offloadable_value`]. The offloaded values must be loaded with
+/// [`BlobOffloader::load_value`].
+pub(crate) fn get_offloadable_value<S, K, V>(
+    storage: &mut S,
+    key: K,
+) -> Result<Option<MaybeOffloaded<V>>>
+where
+    S: StorageAccess,
+    K: TableKey,
+    V: StorageDecode,
+{
+    storage.get_kv_raw(key, |_, value| {
+        value.map(BlobOffloader::decode_value::<V>).transpose()
+    })
+}
+
+/// Deletes a value written by [`put_offloadable_value`], releasing the blob it references.
+pub(crate) fn delete_offloadable_value<S, K>(storage: &mut S, key: K) -> Result<()>
+where
+    S: StorageAccess,
+    K: TableKey + Clone,
+{
+    let blob = referenced_blob(storage, key.clone())?;
+    storage.delete_key(&key);
+    if let Some(hash) = blob {
+        release_blob(storage, hash)?;
+    }
+    Ok(())
+}
+
+/// Returns the blob referenced by the value written by [`put_offloadable_value`].
+fn referenced_blob<S: StorageAccess, K: TableKey>(
+    storage: &mut S,
+    key: K,
+) -> Result<Option<BlobHash>> {
+    storage.get_kv_raw(key, |_, value| {
+        Ok(value.map(blob_reference).transpose()?.flatten())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_storage_api::journal_table::JournalEntry;
+    use restate_types::journal::CompletionResult;
+
+    fn test_offloader(threshold: Option<usize>) -> (tempfile::TempDir, BlobOffloader) {
+        let dir = tempfile::tempdir().unwrap();
+        let offloader = BlobOffloader::new(
+            PartitionId::MIN,
+            threshold.map(|t| NonZeroUsize::new(t).unwrap()),
+            Arc::new(LocalBlobStore::new(dir.path())),
+        );
+        (dir, offloader)
+    }
+
+    #[test]
+    fn local_blob_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalBlobStore::new(dir.path());
+
+        let hash = BlobHash::of(b"my-blob");
+        store.put(&hash, b"my-blob").unwrap();
+        // Storing the same content again is a no-op
+        store.put(&hash, b"my-blob").unwrap();
+
+        assert_eq!(
+            store.get(&hash).unwrap(),
+            Some(Bytes::from_static(b"my-blob"))
+        );
+        assert_eq!(store.get(&BlobHash::of(b"other-blob")).unwrap(), None);
+
+        store.delete(&hash).unwrap();
+        assert_eq!(store.get(&hash).unwrap(), None);
+        // Deleting a missing blob is a no-op
+        store.delete(&hash).unwrap();
+    }
+
+    #[tokio::test]
+    async fn offload_only_above_threshold() {
+        let (_dir, offloader) = test_offloader(Some(4));
+        assert_eq!(offloader.offload(Bytes::from_static(b"1234")).await, None);
+
+        let hash = offloader
+            .offload(Bytes::from_static(b"12345"))
+            .await
+            .unwrap();
+        assert_eq!(
+            offloader.resolve(hash).await.unwrap(),
+            Bytes::from_static(b"12345")
+        );
+
+        let (_dir, offloader) = test_offloader(None);
+        assert_eq!(offloader.offload(Bytes::from(vec![0; 1024])).await, None);
+    }
+
+    #[tokio::test]
+    async fn offloaded_value_is_decoded_transparently() {
+        let (_dir, offloader) = test_offloader(Some(16));
+        let entry = JournalEntry::Completion(CompletionResult::Success(Bytes::from(vec![1; 64])));
+
+        let mut encoded = BytesMut::new();
+        StorageCodec::encode(&entry, &mut encoded).unwrap();
+        let (stored, hash) = offloader.offload_encoded_value(encoded.freeze()).await;
+        assert_eq!(stored.len(), 1 + BlobHash::LENGTH);
+        assert_eq!(blob_reference(&stored).unwrap(), hash);
+
+        let decoded = BlobOffloader::decode_value::<JournalEntry>(&stored).unwrap();
+        assert!(matches!(decoded, MaybeOffloaded::Offloaded(_)));
+        assert_eq!(offloader.load_value(decoded).await.unwrap(), entry);
+    }
+
+    #[tokio::test]
+    async fn collect_only_unpinned_and_unreferenced_blobs() {
+        let (_dir, offloader) = test_offloader(Some(1));
+        let pinned = offloader
+            .offload(Bytes::from_static(b"pinned"))
+            .await
+            .unwrap();
+        let referenced = offloader
+            .offload(Bytes::from_static(b"referenced"))
+            .await
+            .unwrap();
+        let unreferenced = offloader
+            .offload(Bytes::from_static(b"unreferenced"))
+            .await
+            .unwrap();
+        offloader.unpin([referenced, unreferenced]);
+
+        let forgotten = Arc::new(Mutex::new(Vec::new()));
+        let forget_released = {
+            let forgotten = Arc::clone(&forgotten);
+            move |hash: &BlobHash| forgotten.lock().unwrap().push(*hash)
+        };
+        offloader
+            .collect_garbage(
+                vec![pinned, referenced, unreferenced],
+                move |hash| Ok(*hash == referenced),
+                forget_released.clone(),
+            )
+            .await;
+
+        assert!(offloader.resolve(pinned).await.is_ok());
+        assert!(offloader.resolve(referenced).await.is_ok());
+        assert!(offloader.resolve(unreferenced).await.is_err());
+        assert_eq!(*forgotten.lock().unwrap(), vec![unreferenced]);
+        // The pinned blob is retried on the next collection, once it is unpinned
+        assert_eq!(offloader.take_abandoned(), vec![pinned]);
+
+        offloader.unpin([pinned]);
+        offloader
+            .collect_garbage(vec![pinned], |_| Ok(false), forget_released)
+            .await;
+        assert!(offloader.resolve(pinned).await.is_err());
+    }
+}
@@ -8,6 +8,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::blob_store::{
+    delete_offloadable_value, get_offloadable_value, put_offloadable_value, BlobOffloader,
+    MaybeOffloaded,
+};
 use crate::keys::TableKey;
 use crate::keys::{define_table_key, KeyKind};
 use crate::owned_iter::OwnedIterator;
@@ -15,15 +19,14 @@ use crate::scan::TableScan::FullScanPartitionKeyRange;
 use crate::TableKind::Journal;
 use crate::{PartitionStore, RocksDBTransaction, StorageAccess};
 use crate::{TableScan, TableScanIterationDecision};
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use futures_util::stream;
 use restate_rocksdb::RocksDbPerfGuard;
 use restate_storage_api::journal_table::{JournalEntry, JournalTable, ReadOnlyJournalTable};
-use restate_storage_api::{Result, StorageError};
+use restate_storage_api::Result;
 use restate_types::identifiers::{
     EntryIndex, InvocationId, InvocationUuid, JournalEntryId, PartitionKey, WithPartitionKey,
 };
-use restate_types::storage::StorageCodec;
 use std::io::Cursor;
 use std::ops::RangeInclusive;
 
@@ -44,48 +47,57 @@ fn write_journal_entry_key(invocation_id: &InvocationId, journal_index: u32) ->
         .journal_index(journal_index)
 }
 
-fn put_journal_entry<S: StorageAccess>(
+async fn put_journal_entry<S: StorageAccess>(
     storage: &mut S,
     invocation_id: &InvocationId,
     journal_index: u32,
     journal_entry: JournalEntry,
-) {
+) -> Result<()> {
     let key = write_journal_entry_key(invocation_id, journal_index);
 
-    storage.put_kv(key, journal_entry);
+    put_offloadable_value(storage, key, journal_entry).await
 }
 
 fn get_journal_entry<S: StorageAccess>(
     storage: &mut S,
     invocation_id: &InvocationId,
     journal_index: u32,
-) -> Result<Option<JournalEntry>> {
+) -> Result<Option<MaybeOffloaded<JournalEntry>>> {
     let key = write_journal_entry_key(invocation_id, journal_index);
 
-    storage.get_value(key)
+    get_offloadable_value(storage, key)
+}
+
+async fn load_journal_entry(
+    blob_offloader: BlobOffloader,
+    journal_entry: Option<MaybeOffloaded<JournalEntry>>,
+) -> Result<Option<JournalEntry>> {
+    match journal_entry {
+        Some(journal_entry) => Ok(Some(blob_offloader.load_value(journal_entry).await?)),
+        None => Ok(None),
+    }
 }
 
 fn get_journal<S: StorageAccess>(
     storage: &mut S,
     invocation_id: &InvocationId,
     journal_length: EntryIndex,
-) -> Vec<Result<(EntryIndex, JournalEntry)>> {
+) -> impl Stream<Item = Result<(EntryIndex, JournalEntry)>> + Send {
     let _x = RocksDbPerfGuard::new("get-journal");
     let key = JournalKey::default()
         .partition_key(invocation_id.partition_key())
         .invocation_uuid(invocation_id.invocation_uuid());
 
     let mut n = 0;
-    storage.for_each_key_value_in_place(
+    let entries = storage.for_each_key_value_in_place(
         TableScan::SinglePartitionKeyPrefix(invocation_id.partition_key(), key),
-        move |k, mut v| {
+        move |k, v| {
             let key = JournalKey::deserialize_from(&mut Cursor::new(k)).map(|journal_key| {
                 journal_key
                     .journal_index
                     .expect("The journal index must be part of the journal key.")
             });
-            let entry = StorageCodec::decode::<JournalEntry, _>(&mut v)
-                .map_err(|error| StorageError::Generic(error.into()));
+            let entry = BlobOffloader::decode_value::<JournalEntry>(v);
 
             let result = key.and_then(|key| entry.map(|entry| (key, entry)));
 
@@ -96,42 +108,59 @@ fn get_journal<S: StorageAccess>(
                 TableScanIterationDecision::BreakWith(result)
             }
         },
-    )
+    );
+
+    let blob_offloader = storage.blob_offloader().clone();
+    stream::iter(entries).then(move |result| {
+        let blob_offloader = blob_offloader.clone();
+        async move {
+            let (index, entry) = result?;
+            let entry = blob_offloader.load_value(entry).await?;
+            Result::Ok((index, entry))
+        }
+    })
 }
 
 fn all_journals<S: StorageAccess>(
     storage: &S,
     range: RangeInclusive<PartitionKey>,
 ) -> impl Stream<Item = Result<(JournalEntryId, JournalEntry)>> + Send + '_ {
+    let blob_offloader = storage.blob_offloader();
     let iter = storage.iterator_from(FullScanPartitionKeyRange::<JournalKey>(range));
-    stream::iter(OwnedIterator::new(iter).map(|(mut key, mut value)| {
-        let journal_key = JournalKey::deserialize_from(&mut key)?;
-        let journal_entry = StorageCodec::decode::<JournalEntry, _>(&mut value)
-            .map_err(|err| StorageError::Conversion(err.into()))?;
-
-        let (partition_key, invocation_uuid, entry_index) = journal_key.into_inner_ok_or()?;
-
-        Ok((
-            JournalEntryId::from_parts(
-                InvocationId::from_parts(partition_key, invocation_uuid),
-                entry_index,
-            ),
-            journal_entry,
-        ))
-    }))
+    stream::iter(
+        OwnedIterator::new(iter).map(|(mut key, value)| -> Result<_> {
+            let journal_key = JournalKey::deserialize_from(&mut key)?;
+            let journal_entry = BlobOffloader::decode_value::<JournalEntry>(&value)?;
+
+            let (partition_key, invocation_uuid, entry_index) = journal_key.into_inner_ok_or()?;
+
+            Ok((
+                JournalEntryId::from_parts(
+                    InvocationId::from_parts(partition_key, invocation_uuid),
+                    entry_index,
+                ),
+                journal_entry,
+            ))
+        }),
+    )
+    .then(move |result| async move {
+        let (journal_entry_id, journal_entry) = result?;
+        let journal_entry = blob_offloader.load_value(journal_entry).await?;
+        Result::Ok((journal_entry_id, journal_entry))
+    })
 }
 
 fn delete_journal<S: StorageAccess>(
     storage: &mut S,
     invocation_id: &InvocationId,
     journal_length: EntryIndex,
-) {
+) -> Result<()> {
     let mut key = write_journal_entry_key(invocation_id, 0);
-    let k = &mut key;
     for journal_index in 0..journal_length {
-        k.journal_index = Some(journal_index);
-        storage.delete_key(k);
+        key.journal_index = Some(journal_index);
+        delete_offloadable_value(storage, key.clone())?;
     }
+    Ok(())
 }
 
 impl ReadOnlyJournalTable for PartitionStore {
@@ -140,8 +169,12 @@ impl ReadOnlyJournalTable for PartitionStore {
         invocation_id: &InvocationId,
         journal_index: u32,
     ) -> Result<Option<JournalEntry>> {
-        let _x = RocksDbPerfGuard::new("get-journal-entry");
-        get_journal_entry(self, invocation_id, journal_index)
+        let journal_entry = {
+            let _x = RocksDbPerfGuard::new("get-journal-entry");
+            get_journal_entry(self, invocation_id, journal_index)?
+        };
+        let blob_offloader = self.blob_offloader().clone();
+        load_journal_entry(blob_offloader, journal_entry).await
     }
 
     fn get_journal(
@@ -149,7 +182,7 @@ impl ReadOnlyJournalTable for PartitionStore {
         invocation_id: &InvocationId,
         journal_length: EntryIndex,
     ) -> impl Stream<Item = Result<(EntryIndex, JournalEntry)>> + Send {
-        stream::iter(get_journal(self, invocation_id, journal_length))
+        get_journal(self, invocation_id, journal_length)
     }
 
     fn all_journals(
@@ -166,8 +199,12 @@ impl<'a> ReadOnlyJournalTable for RocksDBTransaction<'a> {
         invocation_id: &InvocationId,
         journal_index: u32,
     ) -> Result<Option<JournalEntry>> {
-        let _x = RocksDbPerfGuard::new("get-journal-entry");
-        get_journal_entry(self, invocation_id, journal_index)
+        let journal_entry = {
+            let _x = RocksDbPerfGuard::new("get-journal-entry");
+            get_journal_entry(self, invocation_id, journal_index)?
+        };
+        let blob_offloader = self.blob_offloader().clone();
+        load_journal_entry(blob_offloader, journal_entry).await
     }
 
     fn get_journal(
@@ -175,7 +212,7 @@ impl<'a> ReadOnlyJournalTable for RocksDBTransaction<'a> {
         invocation_id: &InvocationId,
         journal_length: EntryIndex,
     ) -> impl Stream<Item = Result<(EntryIndex, JournalEntry)>> + Send {
-        stream::iter(get_journal(self, invocation_id, journal_length))
+        get_journal(self, invocation_id, journal_length)
     }
 
     fn all_journals(
@@ -194,11 +231,14 @@ impl<'a> JournalTable for RocksDBTransaction<'a> {
         journal_entry: JournalEntry,
     ) {
         put_journal_entry(self, invocation_id, journal_index, journal_entry)
+            .await
+            .expect("failed to update the blob references of the journal entry")
     }
 
     async fn delete_journal(&mut self, invocation_id: &InvocationId, journal_length: EntryIndex) {
         let _x = RocksDbPerfGuard::new("delete-journal");
         delete_journal(self, invocation_id, journal_length)
+            .expect("failed to update the blob references of the journal")
     }
 }
 
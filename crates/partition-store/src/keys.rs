@@ -34,6 +34,9 @@ pub enum KeyKind {
     Outbox,
     ServiceStatus,
     State,
+    StateBlob,
+    BlobRefCount,
+    BlobReleased,
     Timers,
     Promise,
 }
@@ -73,6 +76,9 @@ impl KeyKind {
             KeyKind::Outbox => b"ob",
             KeyKind::ServiceStatus => b"ss",
             KeyKind::State => b"st",
+            KeyKind::StateBlob => b"sb",
+            KeyKind::BlobRefCount => b"br",
+            KeyKind::BlobReleased => b"bl",
             KeyKind::Timers => b"ti",
             KeyKind::Promise => b"pr",
         }
@@ -97,6 +103,9 @@ impl KeyKind {
             b"ob" => Some(KeyKind::Outbox),
             b"ss" => Some(KeyKind::ServiceStatus),
             b"st" => Some(KeyKind::State),
+            b"sb" => Some(KeyKind::StateBlob),
+            b"br" => Some(KeyKind::BlobRefCount),
+            b"bl" => Some(KeyKind::BlobReleased),
             b"ti" => Some(KeyKind::Timers),
             b"pr" => Some(KeyKind::Promise),
             _ => None,
@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod blob_store;
 pub mod deduplication_table;
 pub mod fsm_table;
 pub mod idempotency_table;
@@ -10,10 +10,14 @@
 
 use crate::keys::{define_table_key, KeyKind, TableKey};
 use crate::TableKind::Outbox;
-use crate::{PartitionStore, RocksDBTransaction, StorageAccess, TableScan};
+use crate::{
+    PartitionStore, RocksDBTransaction, StorageAccess, TableScan, TableScanIterationDecision,
+};
 
+use futures::Stream;
+use futures_util::stream;
 use restate_rocksdb::RocksDbPerfGuard;
-use restate_storage_api::outbox_table::{OutboxMessage, OutboxTable};
+use restate_storage_api::outbox_table::{OutboxMessage, OutboxTable, ReadOnlyOutboxTable};
 use restate_storage_api::{Result, StorageError};
 use restate_types::identifiers::PartitionId;
 use restate_types::storage::StorageCodec;
@@ -93,6 +97,34 @@ fn truncate_outbox<S: StorageAccess>(
     }
 }
 
+fn all_outbox_messages<S: StorageAccess>(
+    storage: &S,
+    partition_id: PartitionId,
+) -> impl Stream<Item = Result<(u64, OutboxMessage)>> + Send {
+    stream::iter(storage.for_each_key_value_in_place(
+        TableScan::<OutboxKey>::SinglePartition(partition_id),
+        |k, v| TableScanIterationDecision::Emit(decode_key_value(k, v)),
+    ))
+}
+
+impl ReadOnlyOutboxTable for PartitionStore {
+    fn all_outbox_messages(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(u64, OutboxMessage)>> + Send {
+        all_outbox_messages(self, partition_id)
+    }
+}
+
+impl<'a> ReadOnlyOutboxTable for RocksDBTransaction<'a> {
+    fn all_outbox_messages(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(u64, OutboxMessage)>> + Send {
+        all_outbox_messages(self, partition_id)
+    }
+}
+
 impl OutboxTable for PartitionStore {
     async fn add_message(
         &mut self,
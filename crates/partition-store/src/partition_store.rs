@@ -36,6 +36,9 @@ use restate_storage_api::{Storage, StorageError, Transaction};
 use restate_types::identifiers::{PartitionId, PartitionKey};
 use restate_types::storage::{StorageCodec, StorageDecode, StorageEncode};
 
+use crate::blob_store::{
+    blob_ref_count, forget_released_blob, released_blobs, BlobHash, BlobOffloader,
+};
 use crate::keys::KeyKind;
 use crate::keys::TableKey;
 use crate::scan::PhysicalScan;
@@ -76,6 +79,7 @@ pub enum TableKind {
     Deduplication,
     Outbox,
     Timers,
+    Blob,
     // By Partition Key
     State,
     InvocationStatus,
@@ -89,7 +93,7 @@ pub enum TableKind {
 impl TableKind {
     pub const fn key_kinds(self) -> &'static [KeyKind] {
         match self {
-            Self::State => &[KeyKind::State],
+            Self::State => &[KeyKind::State, KeyKind::StateBlob],
             Self::InvocationStatus => &[KeyKind::InvocationStatus],
             Self::ServiceStatus => &[KeyKind::ServiceStatus],
             Self::Idempotency => &[KeyKind::Idempotency],
@@ -100,6 +104,7 @@ impl TableKind {
             Self::Timers => &[KeyKind::Timers],
             Self::Journal => &[KeyKind::Journal],
             Self::Promise => &[KeyKind::Promise],
+            Self::Blob => &[KeyKind::BlobRefCount, KeyKind::BlobReleased],
         }
     }
 
@@ -145,6 +150,7 @@ pub struct PartitionStore {
     partition_id: PartitionId,
     data_cf_name: CfName,
     key_range: RangeInclusive<PartitionKey>,
+    blob_offloader: BlobOffloader,
     key_buffer: BytesMut,
     value_buffer: BytesMut,
 }
@@ -155,6 +161,7 @@ impl std::fmt::Debug for PartitionStore {
             .field("db", &self.raw_db)
             .field("partition_id", &self.partition_id)
             .field("cf", &self.data_cf_name)
+            .field("blob_offloader", &self.blob_offloader)
             .field("key_buffer", &self.key_buffer.len())
             .field("value_buffer", &self.value_buffer.len())
             .finish()
@@ -169,6 +176,7 @@ impl Clone for PartitionStore {
             partition_id: self.partition_id,
             data_cf_name: self.data_cf_name.clone(),
             key_range: self.key_range.clone(),
+            blob_offloader: self.blob_offloader.clone(),
             key_buffer: BytesMut::default(),
             value_buffer: BytesMut::default(),
         }
@@ -233,6 +241,7 @@ impl PartitionStore {
         data_cf_name: CfName,
         partition_id: PartitionId,
         key_range: RangeInclusive<PartitionKey>,
+        blob_offloader: BlobOffloader,
     ) -> Self {
         Self {
             raw_db,
@@ -240,6 +249,7 @@ impl PartitionStore {
             partition_id,
             data_cf_name,
             key_range,
+            blob_offloader,
             key_buffer: BytesMut::new(),
             value_buffer: BytesMut::new(),
         }
@@ -354,16 +364,43 @@ impl PartitionStore {
             txn: self.raw_db.transaction(),
             data_cf_handle,
             rocksdb,
+            blob_offloader: &self.blob_offloader,
+            retained_blobs: Vec::new(),
             key_buffer: &mut self.key_buffer,
             value_buffer: &mut self.value_buffer,
         }
     }
 
+    /// Flushes the memtables. If `wait` is set, the blobs released by the writes committed before
+    /// the flush are garbage collected once the flush completes, since these writes can no longer
+    /// be lost.
     pub async fn flush_memtables(&self, wait: bool) -> Result<()> {
-        self.rocksdb
+        let garbage = if wait {
+            let mut garbage = released_blobs(self)?;
+            garbage.extend(self.blob_offloader.take_abandoned());
+            garbage
+        } else {
+            Vec::new()
+        };
+
+        if let Err(err) = self
+            .rocksdb
             .flush_memtables(slice::from_ref(&self.data_cf_name), wait)
             .await
-            .map_err(|err| StorageError::Generic(err.into()))?;
+        {
+            self.blob_offloader.abandon(garbage);
+            return Err(StorageError::Generic(err.into()));
+        }
+
+        let mut storage = self.clone();
+        let mut released = self.clone();
+        self.blob_offloader
+            .collect_garbage(
+                garbage,
+                move |hash| Ok(blob_ref_count(&mut storage, hash)? > 0),
+                move |hash| forget_released_blob(&mut released, hash),
+            )
+            .await;
         Ok(())
     }
 }
@@ -413,6 +450,17 @@ impl StorageAccess for PartitionStore {
         &mut self.value_buffer
     }
 
+    #[inline]
+    fn blob_offloader(&self) -> &BlobOffloader {
+        &self.blob_offloader
+    }
+
+    #[inline]
+    fn blob_retained(&mut self, hash: BlobHash) {
+        // written right away
+        self.blob_offloader.unpin([hash]);
+    }
+
     #[inline]
     fn get<K: AsRef<[u8]>>(&self, table: TableKind, key: K) -> Result<Option<DBPinnableSlice>> {
         let table = self.table_handle(table);
@@ -448,10 +496,22 @@ pub struct RocksDBTransaction<'a> {
     txn: rocksdb::Transaction<'a, DB>,
     rocksdb: Arc<RocksDb>,
     data_cf_handle: Arc<BoundColumnFamily<'a>>,
+    blob_offloader: &'a BlobOffloader,
+    /// Pinned blobs referenced by the writes of this transaction
+    retained_blobs: Vec<BlobHash>,
     key_buffer: &'a mut BytesMut,
     value_buffer: &'a mut BytesMut,
 }
 
+impl<'a> Drop for RocksDBTransaction<'a> {
+    fn drop(&mut self) {
+        // The transaction has not been committed, the blobs retained by it might be unreferenced
+        self.blob_offloader
+            .abandon(self.retained_blobs.iter().copied());
+        self.blob_offloader.unpin(self.retained_blobs.drain(..));
+    }
+}
+
 impl<'a> RocksDBTransaction<'a> {
     pub(crate) fn prefix_iterator(
         &self,
@@ -496,7 +556,7 @@ impl<'a> RocksDBTransaction<'a> {
 }
 
 impl<'a> Transaction for RocksDBTransaction<'a> {
-    async fn commit(self) -> Result<()> {
+    async fn commit(mut self) -> Result<()> {
         // We cannot directly commit the txn because it might fail because of unrelated concurrent
         // writes to RocksDB. However, it is safe to write the WriteBatch for a given partition,
         // because there can only be a single writer (the leading PartitionProcessor).
@@ -519,7 +579,10 @@ impl<'a> Transaction for RocksDBTransaction<'a> {
         self.rocksdb
             .write_tx_batch(Priority::High, io_mode, opts, write_batch)
             .await
-            .map_err(|error| StorageError::Generic(error.into()))
+            .map_err(|error| StorageError::Generic(error.into()))?;
+
+        self.blob_offloader.unpin(self.retained_blobs.drain(..));
+        Ok(())
     }
 }
 
@@ -577,6 +640,16 @@ impl<'a> StorageAccess for RocksDBTransaction<'a> {
         self.value_buffer
     }
 
+    #[inline]
+    fn blob_offloader(&self) -> &BlobOffloader {
+        self.blob_offloader
+    }
+
+    #[inline]
+    fn blob_retained(&mut self, hash: BlobHash) {
+        self.retained_blobs.push(hash);
+    }
+
     #[inline]
     fn get<K: AsRef<[u8]>>(&self, table: TableKind, key: K) -> Result<Option<DBPinnableSlice>> {
         let table = self.table_handle(table);
@@ -612,6 +685,12 @@ pub(crate) trait StorageAccess {
 
     fn cleared_value_buffer_mut(&mut self, min_size: usize) -> &mut BytesMut;
 
+    fn blob_offloader(&self) -> &BlobOffloader;
+
+    /// Called once a new reference to the pinned blob has been written. The blob must stay pinned
+    /// until the write is committed.
+    fn blob_retained(&mut self, hash: BlobHash);
+
     fn get<K: AsRef<[u8]>>(&self, table: TableKind, key: K) -> Result<Option<DBPinnableSlice>>;
 
     fn put_cf(&mut self, table: TableKind, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>);
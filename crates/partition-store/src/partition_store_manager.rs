@@ -9,7 +9,9 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use restate_types::live::BoxedLiveLoad;
@@ -26,6 +28,7 @@ use restate_types::identifiers::PartitionId;
 use restate_types::identifiers::PartitionKey;
 use restate_types::live::LiveLoad;
 
+use crate::blob_store::{BlobOffloader, LocalBlobStore};
 use crate::cf_options;
 use crate::PartitionStore;
 use crate::DB;
@@ -45,6 +48,8 @@ pub struct PartitionStoreManager {
     lookup: Arc<Mutex<PartitionLookup>>,
     rocksdb: Arc<RocksDb>,
     raw_db: Arc<DB>,
+    blob_offload_threshold: Option<NonZeroUsize>,
+    blob_store_dir: PathBuf,
 }
 
 #[derive(Default, Debug)]
@@ -82,6 +87,8 @@ impl PartitionStoreManager {
         Ok(Self {
             raw_db,
             rocksdb,
+            blob_offload_threshold: options.blob_offload_threshold,
+            blob_store_dir: options.blob_store_dir(),
             lookup: Arc::default(),
         })
     }
@@ -128,11 +135,23 @@ impl PartitionStoreManager {
             cf_name,
             partition_id,
             partition_key_range,
+            self.blob_offloader(partition_id),
         );
         guard.live.insert(partition_id, partition_store.clone());
 
         Ok(partition_store)
     }
+
+    /// Blobs are stored per partition, since their references are counted by the partition stores.
+    fn blob_offloader(&self, partition_id: PartitionId) -> BlobOffloader {
+        BlobOffloader::new(
+            partition_id,
+            self.blob_offload_threshold,
+            Arc::new(LocalBlobStore::new(
+                self.blob_store_dir.join(partition_id.to_string()),
+            )),
+        )
+    }
 }
 
 fn cf_for_partition(partition_id: PartitionId) -> CfName {
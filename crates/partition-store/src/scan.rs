@@ -11,7 +11,7 @@
 use crate::keys::{KeyCodec, KeyKind, TableKey};
 use crate::scan::TableScan::{
     FullScanPartitionKeyRange, KeyRangeInclusiveInSinglePartition, SinglePartition,
-    SinglePartitionKeyPrefix,
+    SinglePartitionKeyPrefix, SinglePartitionKeyPrefixFrom,
 };
 use crate::{ScanMode, TableKind};
 use bytes::BytesMut;
@@ -30,6 +30,8 @@ pub enum TableScan<K> {
     FullScanPartitionKeyRange(RangeInclusive<PartitionKey>),
     /// Scan within a single partition key
     SinglePartitionKeyPrefix(PartitionKey, K),
+    /// Scan within a single partition key, starting from the given key (inclusive).
+    SinglePartitionKeyPrefixFrom(PartitionKey, K, K),
     /// Inclusive Key Range in a single partition.
     KeyRangeInclusiveInSinglePartition(PartitionId, K, K),
 }
@@ -47,6 +49,23 @@ impl<K: TableKey> From<TableScan<K>> for PhysicalScan {
             SinglePartitionKeyPrefix(_partition_key, key) => {
                 PhysicalScan::Prefix(K::TABLE, K::KEY_KIND, key.serialize())
             }
+            SinglePartitionKeyPrefixFrom(_partition_key, prefix, start) => {
+                let start = start.serialize();
+                let mut end = prefix.serialize();
+                if try_increment(&mut end) {
+                    PhysicalScan::RangeExclusive(
+                        K::TABLE,
+                        K::KEY_KIND,
+                        ScanMode::WithinPrefix,
+                        start,
+                        end,
+                    )
+                } else {
+                    // not allowed to happen since we guarantee that KeyKind is
+                    // always incrementable.
+                    panic!("Key prefix overflowed, start key {:x?}", &start);
+                }
+            }
             KeyRangeInclusiveInSinglePartition(_partition_id, start, end) => {
                 let start = start.serialize();
                 let mut end = end.serialize();
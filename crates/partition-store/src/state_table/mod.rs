@@ -8,19 +8,21 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::keys::{define_table_key, KeyKind, TableKey};
+use crate::blob_store::{release_blob, retain_blob, BlobHash, BlobOffloader, MaybeOffloaded};
+use crate::keys::{define_table_key, KeyCodec, KeyKind, TableKey};
 use crate::owned_iter::OwnedIterator;
 use crate::TableKind::State;
 use crate::{PartitionStore, RocksDBTransaction, StorageAccess};
 use crate::{TableScan, TableScanIterationDecision};
 use bytes::Bytes;
 use bytestring::ByteString;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use futures_util::stream;
 use restate_rocksdb::RocksDbPerfGuard;
 use restate_storage_api::state_table::{ReadOnlyStateTable, StateTable};
 use restate_storage_api::{Result, StorageError};
 use restate_types::identifiers::{PartitionKey, ServiceId, WithPartitionKey};
+use std::cmp::Ordering;
 use std::future;
 use std::future::Future;
 use std::ops::RangeInclusive;
@@ -36,6 +38,18 @@ define_table_key!(
     )
 );
 
+// State values which have been offloaded to the blob store. The value is the blob hash.
+define_table_key!(
+    State,
+    KeyKind::StateBlob,
+    StateBlobKey(
+        partition_key: PartitionKey,
+        service_name: ByteString,
+        service_key: ByteString,
+        state_key: Bytes
+    )
+);
+
 #[inline]
 fn write_state_entry_key(service_id: &ServiceId, state_key: impl AsRef<[u8]>) -> StateKey {
     StateKey::default()
@@ -45,6 +59,15 @@ fn write_state_entry_key(service_id: &ServiceId, state_key: impl AsRef<[u8]>) ->
         .state_key(state_key.as_ref().to_vec().into())
 }
 
+#[inline]
+fn write_state_blob_key(service_id: &ServiceId, state_key: impl AsRef<[u8]>) -> StateBlobKey {
+    StateBlobKey::default()
+        .partition_key(service_id.partition_key())
+        .service_name(service_id.service_name.clone())
+        .service_key(service_id.key.clone())
+        .state_key(state_key.as_ref().to_vec().into())
+}
+
 fn user_state_key_from_slice(key: &[u8]) -> Result<Bytes> {
     let mut key = Bytes::copy_from_slice(key);
     let key = StateKey::deserialize_from(&mut key)?;
@@ -55,23 +78,48 @@ fn user_state_key_from_slice(key: &[u8]) -> Result<Bytes> {
     Ok(key)
 }
 
-fn put_user_state<S: StorageAccess>(
+async fn put_user_state<S: StorageAccess>(
     storage: &mut S,
     service_id: &ServiceId,
-    state_key: impl AsRef<[u8]>,
-    state_value: impl AsRef<[u8]>,
-) {
-    let key = write_state_entry_key(service_id, state_key);
-    storage.put_kv_raw(key, state_value.as_ref());
+    state_key: Bytes,
+    state_value: Bytes,
+) -> Result<()> {
+    let previous_blob = get_user_state_blob(storage, service_id, &state_key)?;
+    let blob_offloader = storage.blob_offloader().clone();
+
+    // Only one of the two keys must exist at any time
+    if let Some(hash) = blob_offloader.offload(state_value.clone()).await {
+        storage.delete_key(&write_state_entry_key(service_id, &state_key));
+        storage.put_kv_raw(
+            write_state_blob_key(service_id, &state_key),
+            hash.as_bytes(),
+        );
+        retain_blob(storage, hash)?;
+    } else {
+        storage.delete_key(&write_state_blob_key(service_id, &state_key));
+        storage.put_kv_raw(write_state_entry_key(service_id, &state_key), state_value);
+    }
+
+    if let Some(hash) = previous_blob {
+        release_blob(storage, hash)?;
+    }
+    Ok(())
 }
 
 fn delete_user_state<S: StorageAccess>(
     storage: &mut S,
     service_id: &ServiceId,
     state_key: impl AsRef<[u8]>,
-) {
-    let key = write_state_entry_key(service_id, state_key);
-    storage.delete_key(&key);
+) -> Result<()> {
+    let state_key = state_key.as_ref();
+    let blob = get_user_state_blob(storage, service_id, state_key)?;
+    storage.delete_key(&write_state_entry_key(service_id, state_key));
+    storage.delete_key(&write_state_blob_key(service_id, state_key));
+
+    if let Some(hash) = blob {
+        release_blob(storage, hash)?;
+    }
+    Ok(())
 }
 
 fn delete_all_user_state<S: StorageAccess>(storage: &mut S, service_id: &ServiceId) -> Result<()> {
@@ -79,43 +127,188 @@ fn delete_all_user_state<S: StorageAccess>(storage: &mut S, service_id: &Service
         .partition_key(service_id.partition_key())
         .service_name(service_id.service_name.clone())
         .service_key(service_id.key.clone());
+    let blob_prefix_key = StateBlobKey::default()
+        .partition_key(service_id.partition_key())
+        .service_name(service_id.service_name.clone())
+        .service_key(service_id.key.clone());
 
     let keys = storage.for_each_key_value_in_place(
         TableScan::SinglePartitionKeyPrefix(service_id.partition_key(), prefix_key),
         |k, _| TableScanIterationDecision::Emit(Ok(Bytes::copy_from_slice(k))),
     );
+    let blob_keys = storage.for_each_key_value_in_place(
+        TableScan::SinglePartitionKeyPrefix(service_id.partition_key(), blob_prefix_key),
+        |k, v| {
+            TableScanIterationDecision::Emit(
+                decode_blob_hash(v).map(|hash| (Bytes::copy_from_slice(k), hash)),
+            )
+        },
+    );
 
     for k in keys {
         storage.delete_cf(State, &k?);
     }
+    for blob_key in blob_keys {
+        let (k, hash) = blob_key?;
+        storage.delete_cf(State, &k);
+        release_blob(storage, hash)?;
+    }
 
     Ok(())
 }
 
+/// Returns the blob of the offloaded state value.
+fn get_user_state_blob<S: StorageAccess>(
+    storage: &mut S,
+    service_id: &ServiceId,
+    state_key: &[u8],
+) -> Result<Option<BlobHash>> {
+    let key = write_state_blob_key(service_id, state_key);
+    storage.get_kv_raw(key, |_k, v| v.map(decode_blob_hash).transpose())
+}
+
 fn get_user_state<S: StorageAccess>(
     storage: &mut S,
     service_id: &ServiceId,
     state_key: impl AsRef<[u8]>,
-) -> Result<Option<Bytes>> {
+) -> Result<Option<MaybeOffloaded<Bytes>>> {
     let _x = RocksDbPerfGuard::new("get-user-state");
+    let state_key = state_key.as_ref();
     let key = write_state_entry_key(service_id, state_key);
-    storage.get_kv_raw(key, move |_k, v| Ok(v.map(Bytes::copy_from_slice)))
+    if let Some(value) = storage.get_kv_raw(key, move |_k, v| Ok(v.map(Bytes::copy_from_slice)))? {
+        return Ok(Some(MaybeOffloaded::Inline(value)));
+    }
+
+    let key = write_state_blob_key(service_id, state_key);
+    storage.get_kv_raw(key, move |_k, v| {
+        v.map(|v| decode_blob_hash(v).map(MaybeOffloaded::Offloaded))
+            .transpose()
+    })
+}
+
+async fn load_user_state_value(
+    blob_offloader: &BlobOffloader,
+    value: MaybeOffloaded<Bytes>,
+) -> Result<Bytes> {
+    match value {
+        MaybeOffloaded::Inline(value) => Ok(value),
+        MaybeOffloaded::Offloaded(hash) => blob_offloader.resolve(hash).await,
+    }
 }
 
 fn get_all_user_states_for_service<S: StorageAccess>(
     storage: &mut S,
     service_id: &ServiceId,
-) -> Vec<Result<(Bytes, Bytes)>> {
+) -> impl Stream<Item = Result<(Bytes, Bytes)>> + Send {
     let _x = RocksDbPerfGuard::new("get-all-user-state");
     let key = StateKey::default()
         .partition_key(service_id.partition_key())
         .service_name(service_id.service_name.clone())
         .service_key(service_id.key.clone());
+    let blob_key = StateBlobKey::default()
+        .partition_key(service_id.partition_key())
+        .service_name(service_id.service_name.clone())
+        .service_key(service_id.key.clone());
 
-    storage.for_each_key_value_in_place(
+    let mut states = storage.for_each_key_value_in_place(
         TableScan::SinglePartitionKeyPrefix(service_id.partition_key(), key),
         |k, v| TableScanIterationDecision::Emit(decode_user_state_key_value(k, v)),
-    )
+    );
+    states.extend(storage.for_each_key_value_in_place(
+        TableScan::SinglePartitionKeyPrefix(service_id.partition_key(), blob_key),
+        |k, v| TableScanIterationDecision::Emit(decode_user_state_blob_key_value(k, v)),
+    ));
+
+    let blob_offloader = storage.blob_offloader().clone();
+    stream::iter(states).then(move |state| {
+        let blob_offloader = blob_offloader.clone();
+        async move {
+            let (key, value) = state?;
+            let value = load_user_state_value(&blob_offloader, value).await?;
+            Result::Ok((key, value))
+        }
+    })
+}
+
+fn get_user_state_keys<S: StorageAccess>(
+    storage: &mut S,
+    service_id: &ServiceId,
+    start_after: Option<&[u8]>,
+    limit: Option<usize>,
+) -> Vec<Result<Bytes>> {
+    let _x = RocksDbPerfGuard::new("get-user-state-keys");
+    let limit = limit.unwrap_or(usize::MAX);
+    let partition_key = service_id.partition_key();
+
+    let prefix = StateKey::default()
+        .partition_key(partition_key)
+        .service_name(service_id.service_name.clone())
+        .service_key(service_id.key.clone());
+    let start = start_after.map(|start_after| write_state_entry_key(service_id, start_after));
+    let mut keys = scan_user_state_keys(storage, partition_key, prefix, start, start_after, limit);
+
+    let blob_prefix = StateBlobKey::default()
+        .partition_key(partition_key)
+        .service_name(service_id.service_name.clone())
+        .service_key(service_id.key.clone());
+    let blob_start = start_after.map(|start_after| write_state_blob_key(service_id, start_after));
+    keys.extend(scan_user_state_keys(
+        storage,
+        partition_key,
+        blob_prefix,
+        blob_start,
+        start_after,
+        limit,
+    ));
+
+    // Merge the inline and the offloaded states in key order. Errors are sorted first, so that
+    // they are not truncated away.
+    keys.sort_by(|a, b| match (a, b) {
+        (Ok((a, _)), Ok((b, _))) => a.cmp(b),
+        (Err(_), Ok(_)) => Ordering::Less,
+        (Ok(_), Err(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => Ordering::Equal,
+    });
+    keys.truncate(limit);
+    keys.into_iter()
+        .map(|res| res.map(|(_, state_key)| state_key))
+        .collect()
+}
+
+/// Scans up to `limit` state keys of a single key kind, starting from `start`. Returns the
+/// serialized suffix of the row key following the prefix, which determines the key order, along
+/// with the state key.
+fn scan_user_state_keys<S: StorageAccess, K: TableKey>(
+    storage: &S,
+    partition_key: PartitionKey,
+    prefix: K,
+    start: Option<K>,
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> Vec<Result<(Bytes, Bytes)>> {
+    let prefix_length = prefix.serialized_length();
+    let scan = match start {
+        Some(start) => TableScan::SinglePartitionKeyPrefixFrom(partition_key, prefix, start),
+        None => TableScan::SinglePartitionKeyPrefix(partition_key, prefix),
+    };
+
+    let mut n = 0;
+    storage.for_each_key_value_in_place(scan, |k, _| {
+        if n >= limit {
+            return TableScanIterationDecision::Break;
+        }
+        let suffix = Bytes::copy_from_slice(&k[prefix_length..]);
+        let state_key = match Bytes::decode(&mut suffix.clone()) {
+            Ok(state_key) => state_key,
+            Err(err) => return TableScanIterationDecision::BreakWith(Err(err)),
+        };
+        if start_after == Some(state_key.as_ref()) {
+            return TableScanIterationDecision::Continue;
+        }
+
+        n += 1;
+        TableScanIterationDecision::Emit(Ok((suffix, state_key)))
+    })
 }
 
 fn get_all_user_states<S: StorageAccess>(
@@ -123,17 +316,38 @@ fn get_all_user_states<S: StorageAccess>(
     range: RangeInclusive<PartitionKey>,
 ) -> impl Stream<Item = Result<(ServiceId, Bytes, Bytes)>> + Send + '_ {
     let _x = RocksDbPerfGuard::new("get-all-user-state");
-    let iter = storage.iterator_from(TableScan::FullScanPartitionKeyRange::<StateKey>(range));
-    stream::iter(OwnedIterator::new(iter).map(|(mut key, value)| {
+    let iter = storage.iterator_from(TableScan::FullScanPartitionKeyRange::<StateKey>(
+        range.clone(),
+    ));
+    let states = OwnedIterator::new(iter).map(|(mut key, value)| -> Result<_> {
         let row_key = StateKey::deserialize_from(&mut key)?;
         let (partition_key, service_name, service_key, state_key) = row_key.into_inner_ok_or()?;
 
         Ok((
             ServiceId::from_parts(partition_key, service_name, service_key),
             state_key,
-            value,
+            MaybeOffloaded::Inline(value),
+        ))
+    });
+
+    let iter = storage.iterator_from(TableScan::FullScanPartitionKeyRange::<StateBlobKey>(range));
+    let blob_states = OwnedIterator::new(iter).map(|(mut key, value)| -> Result<_> {
+        let row_key = StateBlobKey::deserialize_from(&mut key)?;
+        let (partition_key, service_name, service_key, state_key) = row_key.into_inner_ok_or()?;
+
+        Ok((
+            ServiceId::from_parts(partition_key, service_name, service_key),
+            state_key,
+            MaybeOffloaded::Offloaded(decode_blob_hash(&value)?),
         ))
-    }))
+    });
+
+    let blob_offloader = storage.blob_offloader();
+    stream::iter(states.chain(blob_states)).then(move |state| async move {
+        let (service_id, state_key, value) = state?;
+        let value = load_user_state_value(blob_offloader, value).await?;
+        Result::Ok((service_id, state_key, value))
+    })
 }
 
 impl ReadOnlyStateTable for PartitionStore {
@@ -142,14 +356,30 @@ impl ReadOnlyStateTable for PartitionStore {
         service_id: &ServiceId,
         state_key: impl AsRef<[u8]>,
     ) -> impl Future<Output = Result<Option<Bytes>>> + Send {
-        future::ready(get_user_state(self, service_id, state_key))
+        let value = get_user_state(self, service_id, state_key);
+        let blob_offloader = self.blob_offloader().clone();
+        async move {
+            match value? {
+                Some(value) => Ok(Some(load_user_state_value(&blob_offloader, value).await?)),
+                None => Ok(None),
+            }
+        }
     }
 
     fn get_all_user_states_for_service(
         &mut self,
         service_id: &ServiceId,
     ) -> impl Stream<Item = Result<(Bytes, Bytes)>> + Send {
-        stream::iter(get_all_user_states_for_service(self, service_id))
+        get_all_user_states_for_service(self, service_id)
+    }
+
+    fn get_user_state_keys(
+        &mut self,
+        service_id: &ServiceId,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<Bytes>> + Send {
+        stream::iter(get_user_state_keys(self, service_id, start_after, limit))
     }
 
     fn get_all_user_states(
@@ -166,14 +396,30 @@ impl<'a> ReadOnlyStateTable for RocksDBTransaction<'a> {
         service_id: &ServiceId,
         state_key: impl AsRef<[u8]>,
     ) -> impl Future<Output = Result<Option<Bytes>>> + Send {
-        future::ready(get_user_state(self, service_id, state_key))
+        let value = get_user_state(self, service_id, state_key);
+        let blob_offloader = self.blob_offloader().clone();
+        async move {
+            match value? {
+                Some(value) => Ok(Some(load_user_state_value(&blob_offloader, value).await?)),
+                None => Ok(None),
+            }
+        }
     }
 
     fn get_all_user_states_for_service(
         &mut self,
         service_id: &ServiceId,
     ) -> impl Stream<Item = Result<(Bytes, Bytes)>> + Send {
-        stream::iter(get_all_user_states_for_service(self, service_id))
+        get_all_user_states_for_service(self, service_id)
+    }
+
+    fn get_user_state_keys(
+        &mut self,
+        service_id: &ServiceId,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<Bytes>> + Send {
+        stream::iter(get_user_state_keys(self, service_id, start_after, limit))
     }
 
     fn get_all_user_states(
@@ -191,8 +437,13 @@ impl<'a> StateTable for RocksDBTransaction<'a> {
         state_key: impl AsRef<[u8]>,
         state_value: impl AsRef<[u8]>,
     ) -> impl Future<Output = ()> + Send {
-        put_user_state(self, service_id, state_key, state_value);
-        future::ready(())
+        let state_key = Bytes::copy_from_slice(state_key.as_ref());
+        let state_value = Bytes::copy_from_slice(state_value.as_ref());
+        async move {
+            put_user_state(self, service_id, state_key, state_value)
+                .await
+                .expect("failed to update the blob references of the state")
+        }
     }
 
     fn delete_user_state(
@@ -200,7 +451,8 @@ impl<'a> StateTable for RocksDBTransaction<'a> {
         service_id: &ServiceId,
         state_key: impl AsRef<[u8]>,
     ) -> impl Future<Output = ()> + Send {
-        delete_user_state(self, service_id, state_key);
+        delete_user_state(self, service_id, state_key)
+            .expect("failed to update the blob references of the state");
         future::ready(())
     }
 
@@ -212,10 +464,22 @@ impl<'a> StateTable for RocksDBTransaction<'a> {
     }
 }
 
-fn decode_user_state_key_value(k: &[u8], v: &[u8]) -> Result<(Bytes, Bytes)> {
+fn decode_user_state_key_value(k: &[u8], v: &[u8]) -> Result<(Bytes, MaybeOffloaded<Bytes>)> {
     let user_key = user_state_key_from_slice(k)?;
     let user_value = Bytes::copy_from_slice(v);
-    Ok((user_key, user_value))
+    Ok((user_key, MaybeOffloaded::Inline(user_value)))
+}
+
+fn decode_user_state_blob_key_value(k: &[u8], v: &[u8]) -> Result<(Bytes, MaybeOffloaded<Bytes>)> {
+    let mut key = Bytes::copy_from_slice(k);
+    let user_key = StateBlobKey::deserialize_from(&mut key)?
+        .state_key
+        .ok_or(StorageError::DataIntegrityError)?;
+    Ok((user_key, MaybeOffloaded::Offloaded(decode_blob_hash(v)?)))
+}
+
+fn decode_blob_hash(v: &[u8]) -> Result<BlobHash> {
+    BlobHash::from_slice(v).ok_or(StorageError::DataIntegrityError)
 }
 
 #[cfg(test)]
@@ -16,7 +16,9 @@ use crate::{TableScan, TableScanIterationDecision};
 use futures::Stream;
 use futures_util::stream;
 use restate_rocksdb::RocksDbPerfGuard;
-use restate_storage_api::timer_table::{Timer, TimerKey, TimerKeyKind, TimerTable};
+use restate_storage_api::timer_table::{
+    ReadOnlyTimerTable, Timer, TimerKey, TimerKeyKind, TimerTable,
+};
 use restate_storage_api::{Result, StorageError};
 use restate_types::identifiers::{InvocationUuid, PartitionId};
 use restate_types::storage::StorageCodec;
@@ -158,6 +160,34 @@ fn next_timers_greater_than<S: StorageAccess>(
     })
 }
 
+fn all_timers<S: StorageAccess>(
+    storage: &S,
+    partition_id: PartitionId,
+) -> impl Stream<Item = Result<(TimerKey, Timer)>> + Send {
+    stream::iter(storage.for_each_key_value_in_place(
+        TableScan::<TimersKey>::SinglePartition(partition_id),
+        |k, v| Emit(decode_seq_timer_key_value(k, v)),
+    ))
+}
+
+impl ReadOnlyTimerTable for PartitionStore {
+    fn all_timers(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(TimerKey, Timer)>> + Send {
+        all_timers(self, partition_id)
+    }
+}
+
+impl<'a> ReadOnlyTimerTable for RocksDBTransaction<'a> {
+    fn all_timers(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(TimerKey, Timer)>> + Send {
+        all_timers(self, partition_id)
+    }
+}
+
 impl TimerTable for PartitionStore {
     async fn add_timer(&mut self, partition_id: PartitionId, key: &TimerKey, timer: Timer) {
         add_timer(self, partition_id, key, timer)
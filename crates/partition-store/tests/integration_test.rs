@@ -37,6 +37,10 @@ mod timer_table_test;
 mod virtual_object_status_table_test;
 
 async fn storage_test_environment() -> PartitionStore {
+    storage_test_environment_with_options(WorkerOptions::default()).await
+}
+
+async fn storage_test_environment_with_options(worker_options: WorkerOptions) -> PartitionStore {
     //
     // create a rocksdb storage from options
     //
@@ -47,7 +51,7 @@ async fn storage_test_environment() -> PartitionStore {
     tc.run_in_scope_sync("db-manager-init", None, || {
         RocksDbManager::init(Constant::new(CommonOptions::default()))
     });
-    let worker_options = Live::from_value(WorkerOptions::default());
+    let worker_options = Live::from_value(worker_options);
     let manager = PartitionStoreManager::create(
         worker_options.clone().map(|c| &c.storage),
         worker_options.clone().map(|c| &c.storage.rocksdb).boxed(),
@@ -66,6 +66,7 @@ fn invoked_status(invocation_target: InvocationTarget) -> InvocationStatus {
         source: Source::Ingress,
         completion_retention_time: Duration::ZERO,
         idempotency_key: None,
+        awakeable_expiry_timers: Vec::new(),
     })
 }
 
@@ -80,6 +81,7 @@ fn suspended_status(invocation_target: InvocationTarget) -> InvocationStatus {
             source: Source::Ingress,
             completion_retention_time: Duration::ZERO,
             idempotency_key: None,
+            awakeable_expiry_timers: Vec::new(),
         },
         waiting_for_completed_entries: HashSet::default(),
     }
@@ -8,12 +8,17 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::{assert_stream_eq, storage_test_environment};
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use crate::{assert_stream_eq, storage_test_environment, storage_test_environment_with_options};
 use bytes::Bytes;
 use restate_partition_store::PartitionStore;
 use restate_storage_api::state_table::{ReadOnlyStateTable, StateTable};
 use restate_storage_api::Transaction;
-use restate_types::identifiers::ServiceId;
+use restate_types::config::WorkerOptions;
+use restate_types::identifiers::{PartitionId, ServiceId};
 
 async fn populate_data<T: StateTable>(table: &mut T) {
     table
@@ -144,3 +149,155 @@ async fn test_delete_all() {
         .expect("should not fail")
         .is_some());
 }
+
+fn count_blobs(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .map(|entry| entry.expect("readable blob store entry").path())
+        .map(|path| if path.is_dir() { count_blobs(&path) } else { 1 })
+        .sum()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_offloaded_state() {
+    let mut worker_options = WorkerOptions::default();
+    worker_options.storage.blob_offload_threshold = NonZeroUsize::new(16);
+    let blob_dir = worker_options
+        .storage
+        .blob_store_dir()
+        .join(PartitionId::MIN.to_string());
+    let mut rocksdb = storage_test_environment_with_options(worker_options).await;
+
+    let service_id = ServiceId::with_partition_key(1337, "svc-offload", "key-1");
+    let k1 = Bytes::from_static(b"k1");
+    let k2 = Bytes::from_static(b"k2");
+    let small = Bytes::from_static(b"small");
+    let large = Bytes::from_static(b"a value larger than the offload threshold");
+
+    let mut txn = rocksdb.transaction();
+
+    // inline -> offloaded
+    txn.put_user_state(&service_id, &k1, &small).await;
+    txn.put_user_state(&service_id, &k1, &large).await;
+    assert_eq!(
+        txn.get_user_state(&service_id, &k1)
+            .await
+            .expect("should not fail"),
+        Some(large.clone())
+    );
+    assert_stream_eq(
+        txn.get_all_user_states_for_service(&service_id),
+        vec![(k1.clone(), large.clone())],
+    )
+    .await;
+
+    // offloaded -> inline
+    txn.put_user_state(&service_id, &k1, &small).await;
+    assert_eq!(
+        txn.get_user_state(&service_id, &k1)
+            .await
+            .expect("should not fail"),
+        Some(small.clone())
+    );
+    assert_stream_eq(
+        txn.get_all_user_states_for_service(&service_id),
+        vec![(k1.clone(), small.clone())],
+    )
+    .await;
+
+    // delete an offloaded state
+    txn.put_user_state(&service_id, &k1, &large).await;
+    txn.delete_user_state(&service_id, &k1).await;
+    assert!(txn
+        .get_user_state(&service_id, &k1)
+        .await
+        .expect("should not fail")
+        .is_none());
+    assert_stream_eq(txn.get_all_user_states_for_service(&service_id), vec![]).await;
+
+    // mix of inline and offloaded states
+    txn.put_user_state(&service_id, &k1, &large).await;
+    txn.put_user_state(&service_id, &k2, &small).await;
+    txn.commit().await.expect("should not fail");
+
+    let mut txn = rocksdb.transaction();
+    assert_stream_eq(
+        txn.get_all_user_states_for_service(&service_id),
+        vec![(k2.clone(), small.clone()), (k1.clone(), large.clone())],
+    )
+    .await;
+    drop(txn);
+    assert_eq!(count_blobs(&blob_dir), 1);
+
+    let mut txn = rocksdb.transaction();
+    txn.delete_all_user_state(&service_id)
+        .await
+        .expect("should not fail");
+    txn.commit().await.expect("should not fail");
+
+    let mut txn = rocksdb.transaction();
+    assert_stream_eq(txn.get_all_user_states_for_service(&service_id), vec![]).await;
+    drop(txn);
+
+    // the blob is no longer referenced and gets collected once the memtables are flushed
+    rocksdb
+        .flush_memtables(true)
+        .await
+        .expect("should not fail");
+    assert_eq!(count_blobs(&blob_dir), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_state_keys_paging_with_offloaded_state() {
+    let mut worker_options = WorkerOptions::default();
+    worker_options.storage.blob_offload_threshold = NonZeroUsize::new(16);
+    let mut rocksdb = storage_test_environment_with_options(worker_options).await;
+
+    let service_id = ServiceId::with_partition_key(1337, "svc-paging", "key-1");
+    let small = Bytes::from_static(b"small");
+    let large = Bytes::from_static(b"a value larger than the offload threshold");
+
+    // Alternate inline and offloaded states, so that every page merges both scans
+    let mut txn = rocksdb.transaction();
+    txn.put_user_state(&service_id, b"k1", &large).await;
+    txn.put_user_state(&service_id, b"k2", &small).await;
+    txn.put_user_state(&service_id, b"k3", &large).await;
+    txn.put_user_state(&service_id, b"k4", &small).await;
+    txn.put_user_state(&service_id, b"k5", &large).await;
+    txn.commit().await.expect("should not fail");
+
+    let mut txn = rocksdb.transaction();
+    assert_stream_eq(
+        txn.get_user_state_keys(&service_id, None, Some(2)),
+        vec![Bytes::from_static(b"k1"), Bytes::from_static(b"k2")],
+    )
+    .await;
+    assert_stream_eq(
+        txn.get_user_state_keys(&service_id, Some(b"k2".as_slice()), Some(2)),
+        vec![Bytes::from_static(b"k3"), Bytes::from_static(b"k4")],
+    )
+    .await;
+    assert_stream_eq(
+        txn.get_user_state_keys(&service_id, Some(b"k4".as_slice()), Some(2)),
+        vec![Bytes::from_static(b"k5")],
+    )
+    .await;
+    assert_stream_eq(
+        txn.get_user_state_keys(&service_id, Some(b"k5".as_slice()), Some(2)),
+        vec![],
+    )
+    .await;
+    assert_stream_eq(
+        txn.get_user_state_keys(&service_id, None, None),
+        vec![
+            Bytes::from_static(b"k1"),
+            Bytes::from_static(b"k2"),
+            Bytes::from_static(b"k3"),
+            Bytes::from_static(b"k4"),
+            Bytes::from_static(b"k5"),
+        ],
+    )
+    .await;
+}
@@ -13,17 +13,24 @@ use super::proxy::ProxyConnector;
 use crate::utils::ErrorExt;
 
 use futures::future::Either;
-use futures::FutureExt;
+use futures::{FutureExt, StreamExt};
 use hyper::client::HttpConnector;
-use hyper::http::uri::PathAndQuery;
+use hyper::http::uri::{Authority, PathAndQuery, Scheme};
 use hyper::http::HeaderValue;
 use hyper::{Body, HeaderMap, Method, Request, Response, Uri, Version};
 use hyper_rustls::HttpsConnector;
+use parking_lot::Mutex;
 use restate_types::config::HttpOptions;
+use restate_types::schema::deployment::HttpConnectionOptions;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Debug;
 use std::future;
 use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 type Connector = ProxyConnector<HttpsConnector<HttpConnector>>;
 
 #[derive(Clone, Debug)]
@@ -32,16 +39,21 @@ pub struct HttpClient {
     alpn_client: hyper::Client<Connector, Body>,
     // h2 client defaults to http2 and so supports unencrypted http2 servers
     h2_client: hyper::Client<Connector, Body>,
+
+    // used to build the pools of the deployments with custom connection options
+    builder: hyper::client::Builder,
+    connector: Connector,
+    pools: Arc<Mutex<ConnectionPools>>,
 }
 
 impl HttpClient {
-    pub fn new(
-        alpn_client: hyper::Client<Connector, Body>,
-        h2_client: hyper::Client<Connector, Body>,
-    ) -> Self {
+    pub fn new(builder: hyper::client::Builder, connector: Connector) -> Self {
         Self {
-            alpn_client,
-            h2_client,
+            alpn_client: builder.build(connector.clone()), // h1 client with alpn upgrade support
+            h2_client: builder.clone().http2_only(true).build(connector.clone()), // h2-prior knowledge client
+            builder,
+            connector,
+            pools: Default::default(),
         }
     }
 
@@ -64,13 +76,25 @@ impl HttpClient {
 
         let proxy_connector = ProxyConnector::new(options.http_proxy.clone(), https_connector);
 
-        HttpClient::new(
-            builder.clone().build::<_, Body>(proxy_connector.clone()), // h1 client with alpn upgrade support
-            {
-                builder.http2_only(true);
-                builder.build::<_, hyper::Body>(proxy_connector) // h2-prior knowledge client
-            },
-        )
+        HttpClient::new(builder, proxy_connector)
+    }
+
+    fn connection_pool(
+        &self,
+        uri: &Uri,
+        version: Version,
+        options: &HttpConnectionOptions,
+    ) -> Arc<ConnectionPool> {
+        let key = PoolKey {
+            scheme: uri.scheme().cloned(),
+            authority: uri.authority().cloned(),
+            version,
+            options: options.clone(),
+        };
+
+        self.pools.lock().get_or_create(key, Instant::now(), || {
+            ConnectionPool::new(&self.builder, &self.connector, version, options)
+        })
     }
 
     fn build_request(
@@ -116,30 +140,53 @@ impl HttpClient {
         http_request_builder.body(body)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn request(
         &self,
         uri: Uri,
         version: Version,
+        connection_options: &HttpConnectionOptions,
         method: Method,
         body: Body,
         path: PathAndQuery,
         headers: HeaderMap<HeaderValue>,
     ) -> impl Future<Output = Result<Response<Body>, HttpError>> + Send + 'static {
+        let pool = if connection_options.is_default() {
+            None
+        } else {
+            Some(self.connection_pool(&uri, version, connection_options))
+        };
+
         let request = match Self::build_request(uri, version, body, method, path, headers) {
             Ok(request) => request,
             Err(err) => return future::ready(Err(err.into())).right_future(),
         };
 
-        let client = match request.version() {
-            Version::HTTP_2 => &self.h2_client,
-            _ => &self.alpn_client,
+        let (client, streams) = match (pool, request.version()) {
+            (Some(pool), _) => {
+                let (client, streams) = pool.least_loaded();
+                (client.clone(), Some(Arc::clone(streams)))
+            }
+            (None, Version::HTTP_2) => (self.h2_client.clone(), None),
+            (None, _) => (self.alpn_client.clone(), None),
         };
 
-        let fut = client.request(request);
-
         Either::Left(async move {
-            match fut.await {
-                Ok(res) => Ok(res),
+            let permit = match streams {
+                Some(streams) => Some(
+                    streams
+                        .acquire_owned()
+                        .await
+                        .expect("connection pool semaphores are never closed"),
+                ),
+                None => None,
+            };
+
+            match client.request(request).await {
+                Ok(res) => Ok(match permit {
+                    Some(permit) => res.map(|body| hold_until_end(body, permit)),
+                    None => res,
+                }),
                 Err(err) if is_possible_h11_only_error(&err) => {
                     Err(HttpError::PossibleHTTP11Only(err))
                 }
@@ -149,6 +196,144 @@ impl HttpClient {
     }
 }
 
+/// Pools which served no request for this long are closed, as their deployment might have been
+/// removed or registered again with different options.
+const UNUSED_POOL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug)]
+struct ConnectionPools {
+    pools: HashMap<PoolKey, (Arc<ConnectionPool>, Instant)>,
+    last_eviction: Instant,
+}
+
+impl Default for ConnectionPools {
+    fn default() -> Self {
+        Self {
+            pools: HashMap::new(),
+            last_eviction: Instant::now(),
+        }
+    }
+}
+
+impl ConnectionPools {
+    fn get_or_create(
+        &mut self,
+        key: PoolKey,
+        now: Instant,
+        create: impl FnOnce() -> ConnectionPool,
+    ) -> Arc<ConnectionPool> {
+        if now.saturating_duration_since(self.last_eviction) >= UNUSED_POOL_TIMEOUT {
+            self.evict_unused(now);
+        }
+
+        let (pool, last_used) = self
+            .pools
+            .entry(key)
+            .or_insert_with(|| (Arc::new(create()), now));
+        *last_used = now;
+        Arc::clone(pool)
+    }
+
+    fn evict_unused(&mut self, now: Instant) {
+        self.pools.retain(|_, (pool, last_used)| {
+            now.saturating_duration_since(*last_used) < UNUSED_POOL_TIMEOUT || pool.is_in_use()
+        });
+        self.last_eviction = now;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct PoolKey {
+    scheme: Option<Scheme>,
+    authority: Option<Authority>,
+    version: Version,
+    options: HttpConnectionOptions,
+}
+
+/// Connections to a deployment using custom [`HttpConnectionOptions`].
+///
+/// Hyper multiplexes all the HTTP/2 streams to the same host over a single connection, hence the
+/// pool builds one client per connection and spreads the streams across them. With HTTP/1.1 a
+/// single client pools the connections, as each of them serves a request at a time.
+#[derive(Debug)]
+struct ConnectionPool {
+    // each client comes with the semaphore bounding its concurrent streams
+    clients: Vec<(hyper::Client<Connector, Body>, Arc<Semaphore>)>,
+    permits_per_client: usize,
+}
+
+impl ConnectionPool {
+    fn new(
+        builder: &hyper::client::Builder,
+        connector: &Connector,
+        version: Version,
+        options: &HttpConnectionOptions,
+    ) -> Self {
+        let mut builder = builder.clone();
+        if let Some(pool_idle_timeout) = options.pool_idle_timeout {
+            builder.pool_idle_timeout(Duration::from(pool_idle_timeout));
+        }
+
+        let (clients, permits_per_client) = if version == Version::HTTP_2 {
+            builder.http2_only(true);
+            let streams = options
+                .max_concurrent_streams
+                .map(NonZeroUsize::get)
+                .unwrap_or(Semaphore::MAX_PERMITS);
+            let clients = (0..options.max_connections.map(NonZeroUsize::get).unwrap_or(1))
+                .map(|_| {
+                    (
+                        builder.build(connector.clone()),
+                        Arc::new(Semaphore::new(streams)),
+                    )
+                })
+                .collect();
+            (clients, streams)
+        } else {
+            if let Some(max_connections) = options.max_connections {
+                builder.pool_max_idle_per_host(max_connections.get());
+            }
+            let requests = options
+                .max_connections
+                .map(NonZeroUsize::get)
+                .unwrap_or(Semaphore::MAX_PERMITS);
+            let clients = vec![(
+                builder.build(connector.clone()),
+                Arc::new(Semaphore::new(requests)),
+            )];
+            (clients, requests)
+        };
+
+        Self {
+            clients,
+            permits_per_client,
+        }
+    }
+
+    /// Returns true if a request is still using one of the connections of the pool.
+    fn is_in_use(&self) -> bool {
+        self.clients
+            .iter()
+            .any(|(_, permits)| permits.available_permits() < self.permits_per_client)
+    }
+
+    fn least_loaded(&self) -> &(hyper::Client<Connector, Body>, Arc<Semaphore>) {
+        self.clients
+            .iter()
+            .max_by_key(|(_, streams)| streams.available_permits())
+            .expect("connection pool has at least one client")
+    }
+}
+
+/// Holds the stream permit until the response body has been consumed, as the stream stays open
+/// until then.
+fn hold_until_end(body: Body, permit: OwnedSemaphorePermit) -> Body {
+    Body::wrap_stream(body.map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    }))
+}
+
 fn is_possible_h11_only_error(err: &hyper::Error) -> bool {
     // this is the error we see from the h2 lib when the server sends back an http1.1 response
     // to an http2 request. http2 is designed to start requests with what looks like an invalid
@@ -180,4 +365,131 @@ impl HttpError {
             HttpError::PossibleHTTP11Only(_) => false,
         }
     }
+
+    /// Returns true if no connection to the deployment could be established.
+    pub fn is_connect_failure(&self) -> bool {
+        match self {
+            HttpError::Hyper(err) => err.is_connect(),
+            HttpError::Http(_) => false,
+            HttpError::PossibleHTTP11Only(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection_options(
+        max_connections: usize,
+        max_concurrent_streams: usize,
+    ) -> HttpConnectionOptions {
+        HttpConnectionOptions {
+            max_connections: NonZeroUsize::new(max_connections),
+            max_concurrent_streams: NonZeroUsize::new(max_concurrent_streams),
+            pool_idle_timeout: None,
+        }
+    }
+
+    #[test]
+    fn spreads_streams_across_connections() {
+        let client = HttpClient::from_options(&HttpOptions::default());
+        let uri = Uri::from_static("http://localhost:9080");
+        let pool = client.connection_pool(&uri, Version::HTTP_2, &connection_options(2, 2));
+        assert_eq!(pool.clients.len(), 2);
+
+        let mut permits = vec![];
+        for _ in 0..4 {
+            let (_, streams) = pool.least_loaded();
+            permits.push(Arc::clone(streams).try_acquire_owned().unwrap());
+        }
+
+        // all the streams are in use
+        for (_, streams) in &pool.clients {
+            assert_eq!(streams.available_permits(), 0);
+        }
+
+        // a completed stream frees its connection
+        permits.pop();
+        assert_eq!(pool.least_loaded().1.available_permits(), 1);
+    }
+
+    #[test]
+    fn http11_pool_caps_requests() {
+        let client = HttpClient::from_options(&HttpOptions::default());
+        let uri = Uri::from_static("http://localhost:9080");
+        let pool = client.connection_pool(&uri, Version::HTTP_11, &connection_options(3, 10));
+
+        assert_eq!(pool.clients.len(), 1);
+        assert_eq!(pool.least_loaded().1.available_permits(), 3);
+    }
+
+    #[test]
+    fn reuses_pool_of_deployment() {
+        let client = HttpClient::from_options(&HttpOptions::default());
+        let options = connection_options(2, 0);
+
+        let pool = client.connection_pool(
+            &Uri::from_static("http://localhost:9080/a"),
+            Version::HTTP_2,
+            &options,
+        );
+        // Same authority, different path
+        assert!(Arc::ptr_eq(
+            &pool,
+            &client.connection_pool(
+                &Uri::from_static("http://localhost:9080/b"),
+                Version::HTTP_2,
+                &options
+            )
+        ));
+        // Different options
+        assert!(!Arc::ptr_eq(
+            &pool,
+            &client.connection_pool(
+                &Uri::from_static("http://localhost:9080/a"),
+                Version::HTTP_2,
+                &connection_options(4, 0)
+            )
+        ));
+    }
+
+    #[test]
+    fn evicts_unused_pools() {
+        let client = HttpClient::from_options(&HttpOptions::default());
+        let options = connection_options(2, 0);
+        let key = |authority: &'static str| PoolKey {
+            scheme: Some(Scheme::HTTP),
+            authority: Some(Authority::from_static(authority)),
+            version: Version::HTTP_2,
+            options: options.clone(),
+        };
+        let new_pool = || {
+            ConnectionPool::new(
+                &client.builder,
+                &client.connector,
+                Version::HTTP_2,
+                &options,
+            )
+        };
+
+        let now = Instant::now();
+        let mut pools = ConnectionPools {
+            pools: HashMap::new(),
+            last_eviction: now,
+        };
+        let unused = pools.get_or_create(key("unused:9080"), now, new_pool);
+        let in_use = pools.get_or_create(key("in-use:9080"), now, new_pool);
+        let _permit = Arc::clone(&in_use.least_loaded().1)
+            .try_acquire_owned()
+            .unwrap();
+        drop(unused);
+
+        let later = now + UNUSED_POOL_TIMEOUT;
+        pools.get_or_create(key("recent:9080"), later, new_pool);
+
+        assert!(!pools.pools.contains_key(&key("unused:9080")));
+        assert!(Arc::ptr_eq(&pools.pools[&key("in-use:9080")].0, &in_use));
+        assert!(pools.pools.contains_key(&key("recent:9080")));
+    }
 }
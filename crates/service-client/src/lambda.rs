@@ -19,13 +19,16 @@ use aws_config::BehaviorVersion;
 use aws_sdk_lambda::config::Region;
 use aws_sdk_lambda::error::{DisplayErrorContext, SdkError};
 use aws_sdk_lambda::operation::invoke::InvokeError;
+use aws_sdk_lambda::operation::invoke_with_response_stream::InvokeWithResponseStreamError;
 use aws_sdk_lambda::primitives::Blob;
+use aws_sdk_lambda::types::InvokeWithResponseStreamResponseEvent;
 use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
 use base64::display::Base64Display;
 use base64::Engine;
 use bytestring::ByteString;
 use futures::future::{BoxFuture, Shared};
-use futures::FutureExt;
+use futures::stream::BoxStream;
+use futures::{stream, FutureExt, StreamExt, TryStreamExt};
 use hyper::body::Bytes;
 use hyper::client::HttpConnector;
 use hyper::http::request::Parts;
@@ -36,6 +39,7 @@ use hyper_rustls::{ConfigBuilderExt, HttpsConnector};
 use once_cell::sync::Lazy;
 use restate_types::config::AwsOptions;
 use restate_types::identifiers::LambdaARN;
+use restate_types::schema::deployment::LambdaInvokeMode;
 use serde::ser::Error as _;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Serialize, Serializer};
@@ -120,6 +124,7 @@ impl LambdaClient {
     pub fn new(
         profile_name: Option<String>,
         assume_role_external_id: Option<String>,
+        lambda_endpoint_url: Option<String>,
         assume_role_cache_mode: AssumeRoleCacheMode,
     ) -> Self {
         // create client for a default region, region can be overridden per request
@@ -135,7 +140,10 @@ impl LambdaClient {
             let sts_conf = aws_sdk_sts::Config::from(&config);
             let sts_client = aws_sdk_sts::Client::from_conf(sts_conf);
 
-            let lambda_client_builder = aws_sdk_lambda::config::Builder::from(&config);
+            let mut lambda_client_builder = aws_sdk_lambda::config::Builder::from(&config);
+            if let Some(lambda_endpoint_url) = lambda_endpoint_url {
+                lambda_client_builder.set_endpoint_url(Some(lambda_endpoint_url));
+            }
 
             let lambda_client =
                 aws_sdk_lambda::Client::from_conf(lambda_client_builder.clone().build());
@@ -166,15 +174,18 @@ impl LambdaClient {
         LambdaClient::new(
             options.aws_profile.clone(),
             options.aws_assume_role_external_id.clone(),
+            options.aws_lambda_endpoint_url.clone(),
             assume_role_cache_mode,
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn invoke(
         &self,
         arn: LambdaARN,
         method: Method,
         assume_role_arn: Option<ByteString>,
+        invoke_mode: LambdaInvokeMode,
         body: Body,
         path: PathAndQuery,
         headers: HeaderMap<HeaderValue>,
@@ -195,58 +206,201 @@ impl LambdaClient {
                 body: body?,
                 is_base64_encoded: true,
             };
+            let payload =
+                Blob::new(serde_json::to_vec(&payload).map_err(LambdaError::SerializationError)?);
+            let client = inner.client(assume_role_arn);
 
-            let res = inner
-                .build_invoke(assume_role_arn)
-                .function_name(function_name)
-                .payload(Blob::new(
-                    serde_json::to_vec(&payload).map_err(LambdaError::SerializationError)?,
-                ))
-                .customize()
-                .config_override(aws_sdk_lambda::config::Builder::default().region(region))
-                .send()
-                .await?;
-
-            if res.function_error().is_some() {
-                return if let Some(payload) = res.payload() {
-                    let error: serde_json::Value = serde_json::from_slice(payload.as_ref())
-                        .map_err(LambdaError::DeserializationError)?;
-                    Err(LambdaError::FunctionError(error))
-                } else {
-                    Err(LambdaError::FunctionError(serde_json::Value::Null))
-                };
+            match invoke_mode {
+                LambdaInvokeMode::Buffered => {
+                    invoke_buffered(client, function_name, region, payload).await
+                }
+                LambdaInvokeMode::ResponseStream => {
+                    invoke_response_stream(client, function_name, region, payload).await
+                }
             }
+        }
+    }
+}
 
-            if let Some(payload) = res.payload() {
-                let response: ApiGatewayProxyResponse = serde_json::from_slice(payload.as_ref())
-                    .map_err(LambdaError::DeserializationError)?;
-                return response.try_into();
-            }
+async fn invoke_buffered(
+    client: aws_sdk_lambda::Client,
+    function_name: String,
+    region: Region,
+    payload: Blob,
+) -> Result<Response<Body>, LambdaError> {
+    let res = client
+        .invoke()
+        .function_name(function_name)
+        .payload(payload)
+        .customize()
+        .config_override(aws_sdk_lambda::config::Builder::default().region(region))
+        .send()
+        .await?;
+
+    if res.function_error().is_some() {
+        return if let Some(payload) = res.payload() {
+            let error: serde_json::Value = serde_json::from_slice(payload.as_ref())
+                .map_err(LambdaError::DeserializationError)?;
+            Err(LambdaError::FunctionError(error))
+        } else {
+            Err(LambdaError::FunctionError(serde_json::Value::Null))
+        };
+    }
+
+    if let Some(payload) = res.payload() {
+        let response: ApiGatewayProxyResponse =
+            serde_json::from_slice(payload.as_ref()).map_err(LambdaError::DeserializationError)?;
+        return response.try_into();
+    }
 
-            Err(LambdaError::MissingResponse)
+    Err(LambdaError::MissingResponse)
+}
+
+/// Content type of the response streams written with `awslambda.HttpResponseStream`, which
+/// start with a JSON prelude carrying the status code and headers.
+const HTTP_INTEGRATION_RESPONSE_CONTENT_TYPE: &str =
+    "application/vnd.awslambda.http-integration-response";
+
+/// Separates the prelude from the body in the http integration responses.
+const HTTP_INTEGRATION_PRELUDE_DELIMITER: [u8; 8] = [0; 8];
+
+/// Maximum size of the prelude of the http integration responses, to avoid buffering the whole
+/// response when the delimiter is missing.
+const MAX_HTTP_INTEGRATION_PRELUDE_SIZE: usize = 64 * 1024;
+
+async fn invoke_response_stream(
+    client: aws_sdk_lambda::Client,
+    function_name: String,
+    region: Region,
+    payload: Blob,
+) -> Result<Response<Body>, LambdaError> {
+    let res = client
+        .invoke_with_response_stream()
+        .function_name(function_name)
+        .payload(payload)
+        .customize()
+        .config_override(aws_sdk_lambda::config::Builder::default().region(region))
+        .send()
+        .await?;
+
+    let is_http_integration_response =
+        res.response_stream_content_type() == Some(HTTP_INTEGRATION_RESPONSE_CONTENT_TYPE);
+
+    let mut chunks = stream::try_unfold(res.event_stream, |mut event_stream| async move {
+        loop {
+            let event = event_stream.recv().await.map_err(|err| {
+                LambdaError::ResponseStream(DisplayErrorContext(&err).to_string())
+            })?;
+            match event {
+                Some(InvokeWithResponseStreamResponseEvent::PayloadChunk(chunk)) => {
+                    if let Some(payload) = chunk.payload {
+                        return Ok(Some((Bytes::from(payload.into_inner()), event_stream)));
+                    }
+                }
+                Some(InvokeWithResponseStreamResponseEvent::InvokeComplete(complete)) => {
+                    return match complete.error_code {
+                        Some(error_code) => Err(LambdaError::FunctionError(serde_json::json!({
+                            "errorType": error_code,
+                            "errorMessage": complete.error_details,
+                        }))),
+                        None => Ok(None),
+                    };
+                }
+                // events added in future versions of the API
+                Some(_) => {}
+                None => return Ok(None),
+            }
         }
+    })
+    .boxed();
+
+    if !is_http_integration_response {
+        // the function wrote a regular proxy response, which is only usable once complete
+        let payload = chunks
+            .try_fold(Vec::new(), |mut payload, chunk| async move {
+                payload.extend_from_slice(&chunk);
+                Ok(payload)
+            })
+            .await?;
+        let response: ApiGatewayProxyResponse =
+            serde_json::from_slice(&payload).map_err(LambdaError::DeserializationError)?;
+        return response.try_into();
     }
+
+    read_http_integration_response(chunks).await
+}
+
+/// Reads the prelude of an http integration response, and streams the rest as the body.
+async fn read_http_integration_response(
+    mut chunks: BoxStream<'static, Result<Bytes, LambdaError>>,
+) -> Result<Response<Body>, LambdaError> {
+    let mut buffer = Vec::new();
+    let (prelude, body_start) = loop {
+        let searched = buffer.len();
+        match chunks.try_next().await? {
+            Some(chunk) => buffer.extend_from_slice(&chunk),
+            None => return Err(LambdaError::MissingResponse),
+        }
+        // The delimiter can span the previous chunk and the new one
+        let search_start = searched.saturating_sub(HTTP_INTEGRATION_PRELUDE_DELIMITER.len() - 1);
+        match find_http_integration_prelude_delimiter(&buffer, search_start) {
+            Some(position) if position <= MAX_HTTP_INTEGRATION_PRELUDE_SIZE => {
+                let prelude: HttpIntegrationPrelude = serde_json::from_slice(&buffer[..position])
+                    .map_err(LambdaError::DeserializationError)?;
+                break (
+                    prelude,
+                    Bytes::copy_from_slice(
+                        &buffer[position + HTTP_INTEGRATION_PRELUDE_DELIMITER.len()..],
+                    ),
+                );
+            }
+            Some(_) => return Err(LambdaError::PreludeTooLarge),
+            None if buffer.len()
+                >= MAX_HTTP_INTEGRATION_PRELUDE_SIZE + HTTP_INTEGRATION_PRELUDE_DELIMITER.len() =>
+            {
+                return Err(LambdaError::PreludeTooLarge)
+            }
+            None => {}
+        }
+    };
+
+    let body = stream::once(async { Ok(body_start) }).chain(chunks);
+
+    let builder = Response::builder().status(prelude.status_code);
+    let builder = prelude
+        .headers
+        .iter()
+        .fold(builder, |builder, (k, v)| builder.header(k, v));
+
+    builder
+        .body(Body::wrap_stream(body))
+        .map_err(LambdaError::InvalidResponse)
+}
+
+/// Returns the position of the delimiter ending the prelude, searching from `start`.
+fn find_http_integration_prelude_delimiter(buffer: &[u8], start: usize) -> Option<usize> {
+    buffer[start..]
+        .windows(HTTP_INTEGRATION_PRELUDE_DELIMITER.len())
+        .position(|window| window == HTTP_INTEGRATION_PRELUDE_DELIMITER)
+        .map(|position| start + position)
 }
 
 impl LambdaClientInner {
-    fn build_invoke(
-        &self,
-        assume_role_arn: Option<ByteString>,
-    ) -> aws_sdk_lambda::operation::invoke::builders::InvokeFluentBuilder {
+    fn client(&self, assume_role_arn: Option<ByteString>) -> aws_sdk_lambda::Client {
         let assume_role_arn = if let Some(assume_role_arn) = assume_role_arn {
             assume_role_arn
         } else {
             // fastest path; no assumed role, don't bother with the shared hashmap
-            return self.no_role_lambda_client.invoke();
+            return self.no_role_lambda_client.clone();
         };
 
-        if let Some(invoke) = self.role_to_lambda_clients.as_ref().and_then(|rlc| {
-            rlc.load()
-                .get(&*assume_role_arn)
-                .map(|client| client.invoke())
-        }) {
+        if let Some(client) = self
+            .role_to_lambda_clients
+            .as_ref()
+            .and_then(|rlc| rlc.load().get(&*assume_role_arn).cloned())
+        {
             // fast-ish path; we've seen this assumed role before
-            return invoke;
+            return client;
         }
 
         // slow path; create the client for this assumed role
@@ -278,7 +432,7 @@ impl LambdaClientInner {
             });
         }
 
-        client.invoke()
+        client
     }
 }
 
@@ -288,6 +442,10 @@ pub enum LambdaError {
     Body(#[from] hyper::Error),
     #[error("lambda service returned error: {}", DisplayErrorContext(&.0))]
     SdkError(#[from] SdkError<InvokeError>),
+    #[error("lambda service returned error: {}", DisplayErrorContext(&.0))]
+    StreamSdkError(#[from] SdkError<InvokeWithResponseStreamError>),
+    #[error("problem reading the function response stream: {0}")]
+    ResponseStream(String),
     #[error("function returned an error during execution: {0}")]
     FunctionError(serde_json::Value),
     #[error("function request could not be serialized: {0}")]
@@ -298,6 +456,13 @@ pub enum LambdaError {
     Base64Error(base64::DecodeError),
     #[error("function returned neither a payload or an error")]
     MissingResponse,
+    #[error("function response is not a valid HTTP response: {0}")]
+    InvalidResponse(hyper::http::Error),
+    #[error(
+        "function response prelude exceeds {} bytes, or misses its delimiter",
+        MAX_HTTP_INTEGRATION_PRELUDE_SIZE
+    )]
+    PreludeTooLarge,
 }
 
 impl LambdaError {
@@ -307,11 +472,30 @@ impl LambdaError {
         match self {
             LambdaError::Body(err) => err.is_retryable(),
             LambdaError::SdkError(err) => err.is_retryable(),
+            LambdaError::StreamSdkError(err) => err.is_retryable(),
+            LambdaError::ResponseStream(_) => true,
             LambdaError::FunctionError(_) => false,
             LambdaError::SerializationError(_) => false,
             LambdaError::DeserializationError(_) => false,
             LambdaError::Base64Error(_) => false,
             LambdaError::MissingResponse => false,
+            LambdaError::InvalidResponse(_) => false,
+            LambdaError::PreludeTooLarge => false,
+        }
+    }
+
+    /// Returns true if the Lambda service could not be reached at all.
+    pub fn is_connect_failure(&self) -> bool {
+        match self {
+            LambdaError::SdkError(err) => matches!(
+                err,
+                SdkError::DispatchFailure(_) | SdkError::TimeoutError(_)
+            ),
+            LambdaError::StreamSdkError(err) => matches!(
+                err,
+                SdkError::DispatchFailure(_) | SdkError::TimeoutError(_)
+            ),
+            _ => false,
         }
     }
 }
@@ -378,10 +562,24 @@ impl TryFrom<ApiGatewayProxyResponse> for Response<Body> {
             .iter()
             .fold(builder, |builder, (k, v)| builder.header(k, v));
 
-        Ok(builder.body(body).expect("response must be created"))
+        builder.body(body).map_err(LambdaError::InvalidResponse)
     }
 }
 
+/// Status code and headers written by `awslambda.HttpResponseStream` before the body.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpIntegrationPrelude {
+    #[serde(default = "default_status_code")]
+    status_code: u16,
+    #[serde(default, with = "http_serde::header_map")]
+    headers: HeaderMap,
+}
+
+fn default_status_code() -> u16 {
+    200
+}
+
 fn serialize_method<S: Serializer>(method: &Method, ser: S) -> Result<S::Ok, S::Error> {
     ser.serialize_str(method.as_str())
 }
@@ -507,3 +705,106 @@ mod assume_role {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(chunks: Vec<&'static [u8]>) -> BoxStream<'static, Result<Bytes, LambdaError>> {
+        stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk))),
+        )
+        .boxed()
+    }
+
+    #[test]
+    fn find_prelude_delimiter() {
+        let mut buffer = br#"{"statusCode":201}"#.to_vec();
+        assert_eq!(find_http_integration_prelude_delimiter(&buffer, 0), None);
+
+        buffer.extend_from_slice(&HTTP_INTEGRATION_PRELUDE_DELIMITER);
+        buffer.extend_from_slice(b"body");
+        assert_eq!(
+            find_http_integration_prelude_delimiter(&buffer, 0),
+            Some(18)
+        );
+        assert_eq!(
+            find_http_integration_prelude_delimiter(&buffer, 12),
+            Some(18)
+        );
+        assert_eq!(find_http_integration_prelude_delimiter(&buffer, 19), None);
+    }
+
+    #[tokio::test]
+    async fn read_streamed_response() {
+        let response = read_http_integration_response(chunks(vec![
+            &br#"{"statusCode":201,"headers":{"content-type":"application/vnd.restate.invocation.v1"}}"#[..],
+            &[0; 5][..],
+            // the delimiter spans two chunks
+            &[0, 0, 0, b'b', b'o'][..],
+            &b"dy"[..],
+        ]))
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), 201);
+        assert_eq!(
+            response.headers().get(hyper::header::CONTENT_TYPE).unwrap(),
+            "application/vnd.restate.invocation.v1"
+        );
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "body");
+    }
+
+    #[tokio::test]
+    async fn read_streamed_response_without_body() {
+        let response = read_http_integration_response(chunks(vec![&b"{}"[..], &[0; 8][..]]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reject_incomplete_prelude() {
+        let err = read_http_integration_response(chunks(vec![&br#"{"statusCode":201}"#[..]]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, LambdaError::MissingResponse));
+    }
+
+    #[tokio::test]
+    async fn reject_too_large_prelude() {
+        static CHUNK: [u8; 1024] = [b' '; 1024];
+        let err = read_http_integration_response(
+            stream::repeat(Bytes::from_static(&CHUNK)).map(Ok).boxed(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, LambdaError::PreludeTooLarge));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_status_code() {
+        let err = read_http_integration_response(chunks(vec![
+            &br#"{"statusCode":1000}"#[..],
+            &[0; 8][..],
+        ]))
+        .await
+        .unwrap_err();
+        assert!(matches!(err, LambdaError::InvalidResponse(_)));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn prelude_defaults() {
+        let prelude: HttpIntegrationPrelude = serde_json::from_slice(b"{}").unwrap();
+        assert_eq!(prelude.status_code, 200);
+        assert!(prelude.headers.is_empty());
+    }
+}
@@ -11,7 +11,6 @@
 use crate::http::HttpClient;
 use crate::lambda::LambdaClient;
 
-use arc_swap::ArcSwapOption;
 use bytestring::ByteString;
 use core::fmt;
 
@@ -22,13 +21,14 @@ use hyper::Body;
 use hyper::{HeaderMap, Response, Uri};
 use restate_types::config::ServiceClientOptions;
 use restate_types::identifiers::LambdaARN;
+use restate_types::schema::deployment::{HttpConnectionOptions, LambdaInvokeMode};
 use std::fmt::Formatter;
 use std::future;
 use std::future::Future;
-use std::sync::Arc;
 
 pub use crate::http::HttpError;
 pub use crate::lambda::AssumeRoleCacheMode;
+pub use crate::request_identity::RequestIdentityKeys;
 use crate::request_identity::SignRequest;
 
 mod http;
@@ -39,48 +39,36 @@ mod utils;
 
 #[derive(Debug, Clone)]
 pub struct ServiceClient {
-    // TODO a single client uses the pooling provided by hyper, but this is not enough.
-    //  See https://github.com/restatedev/restate/issues/76 for more background on the topic.
     http: HttpClient,
     lambda: LambdaClient,
-    // this can be changed to re-read periodically if necessary
-    request_identity_key: Arc<ArcSwapOption<request_identity::v1::SigningKey>>,
+    request_identity_keys: RequestIdentityKeys,
 }
 
 impl ServiceClient {
     pub(crate) fn new(
         http: HttpClient,
         lambda: LambdaClient,
-        request_identity_key: Arc<ArcSwapOption<request_identity::v1::SigningKey>>,
+        request_identity_keys: RequestIdentityKeys,
     ) -> Self {
         Self {
             http,
             lambda,
-            request_identity_key,
+            request_identity_keys,
         }
     }
 
+    /// Creates a client signing its requests with the given keys. The request identity keys are
+    /// not read from the options, to let all the clients of a node share the reloaded keys.
     pub fn from_options(
         options: &ServiceClientOptions,
         assume_role_cache_mode: AssumeRoleCacheMode,
-    ) -> Result<Self, BuildError> {
-        let request_identity_key = if let Some(request_identity_private_key_pem_file) =
-            options.request_identity_private_key_pem_file.clone()
-        {
-            Arc::new(ArcSwapOption::from_pointee(
-                request_identity::v1::SigningKey::from_pem_file(
-                    request_identity_private_key_pem_file,
-                )?,
-            ))
-        } else {
-            Arc::new(ArcSwapOption::empty())
-        };
-
-        Ok(Self::new(
+        request_identity_keys: RequestIdentityKeys,
+    ) -> Self {
+        Self::new(
             HttpClient::from_options(&options.http),
             LambdaClient::from_options(&options.lambda, assume_role_cache_mode),
-            request_identity_key,
-        ))
+            request_identity_keys,
+        )
     }
 }
 
@@ -97,12 +85,12 @@ impl ServiceClient {
     ) -> impl Future<Output = Result<Response<Body>, ServiceClientError>> + Send + 'static {
         let (mut parts, body) = req.into_parts();
 
-        let request_identity_key = self.request_identity_key.load();
+        let request_identity_keys = self.request_identity_keys.load();
 
-        let signer = if let Some(request_identity_key) = request_identity_key.as_deref() {
+        let signer = if let Some(request_identity_keys) = request_identity_keys.as_deref() {
             Some(request_identity::v1::Signer::new(
                 parts.path.path(),
-                request_identity_key,
+                request_identity_keys.signing_key(),
             ))
         } else {
             None // will use null signing scheme
@@ -114,10 +102,11 @@ impl ServiceClient {
         };
 
         match parts.address {
-            Endpoint::Http(uri, version) => {
+            Endpoint::Http(uri, version, connection_options) => {
                 let fut = self.http.request(
                     uri,
                     version,
+                    &connection_options,
                     parts.method.into(),
                     body,
                     parts.path,
@@ -125,11 +114,12 @@ impl ServiceClient {
                 );
                 async move { Ok(fut.await?) }.left_future()
             }
-            Endpoint::Lambda(arn, assume_role_arn) => {
+            Endpoint::Lambda(arn, assume_role_arn, invoke_mode) => {
                 let fut = self.lambda.invoke(
                     arn,
                     parts.method.into(),
                     assume_role_arn,
+                    invoke_mode,
                     body,
                     parts.path,
                     parts.headers,
@@ -161,6 +151,16 @@ impl ServiceClientError {
             ServiceClientError::IdentityV1(_) => false, // this really should never happen
         }
     }
+
+    /// Connect failures are those where the request never reached the deployment, which
+    /// usually means the deployment is down.
+    pub fn is_connect_failure(&self) -> bool {
+        match self {
+            ServiceClientError::Http(http_error) => http_error.is_connect_failure(),
+            ServiceClientError::Lambda(lambda_error) => lambda_error.is_connect_failure(),
+            ServiceClientError::IdentityV1(_) => false,
+        }
+    }
 }
 
 pub struct Request<B> {
@@ -234,15 +234,15 @@ impl Parts {
 
 #[derive(Clone, Debug)]
 pub enum Endpoint {
-    Http(Uri, hyper::http::Version),
-    Lambda(LambdaARN, Option<ByteString>),
+    Http(Uri, hyper::http::Version, HttpConnectionOptions),
+    Lambda(LambdaARN, Option<ByteString>, LambdaInvokeMode),
 }
 
 impl fmt::Display for Endpoint {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Http(uri, _) => uri.fmt(f),
-            Self::Lambda(arn, _) => write!(f, "lambda://{}", arn),
+            Self::Http(uri, _, _) => uri.fmt(f),
+            Self::Lambda(arn, _, _) => write!(f, "lambda://{}", arn),
         }
     }
 }
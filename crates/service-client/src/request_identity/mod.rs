@@ -8,9 +8,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use hyper::header::HeaderName;
+use std::sync::Arc;
 
+use arc_swap::ArcSwapOption;
+use hyper::header::HeaderName;
 use hyper::HeaderMap;
+use jsonwebtoken::jwk::JwkSet;
+
+use restate_types::config::ServiceClientOptions;
+
+use crate::BuildError;
 
 pub(crate) mod v1;
 
@@ -30,3 +37,41 @@ impl<T: SignRequest> SignRequest for Option<T> {
         }
     }
 }
+
+/// Keys used to sign the requests to the deployments. They are shared by all the clients of a
+/// node and can be reloaded without restarting it.
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdentityKeys(Arc<ArcSwapOption<v1::SigningKeys>>);
+
+impl RequestIdentityKeys {
+    pub fn from_options(options: &ServiceClientOptions) -> Result<Self, BuildError> {
+        let keys = Self::default();
+        keys.reload(options)?;
+        Ok(keys)
+    }
+
+    /// Re-reads the keys from the configured PEM file. The current keys are kept if the file
+    /// can't be read.
+    pub fn reload(&self, options: &ServiceClientOptions) -> Result<(), BuildError> {
+        let keys = options
+            .request_identity_private_key_pem_file
+            .as_deref()
+            .map(v1::SigningKeys::from_pem_file)
+            .transpose()?;
+        self.0.store(keys.map(Arc::new));
+        Ok(())
+    }
+
+    pub(crate) fn load(&self) -> Option<Arc<v1::SigningKeys>> {
+        self.0.load_full()
+    }
+
+    /// The public keys of the loaded keys, which deployments can use to verify the requests.
+    pub fn jwks(&self) -> JwkSet {
+        self.0
+            .load()
+            .as_deref()
+            .map(v1::SigningKeys::jwks)
+            .unwrap_or(JwkSet { keys: Vec::new() })
+    }
+}
@@ -65,9 +65,9 @@ impl RawEntryCodec for ProtobufRawEntryCodec {
         )
     }
 
-    fn serialize_get_state_keys_completion(keys: Vec<Bytes>) -> CompletionResult {
+    fn serialize_get_state_keys_completion(keys: Vec<Bytes>, has_more: bool) -> CompletionResult {
         CompletionResult::Success(
-            service_protocol::get_state_keys_entry_message::StateKeys { keys }
+            service_protocol::get_state_keys_entry_message::StateKeys { keys, has_more }
                 .encode_to_vec()
                 .into(),
         )
@@ -374,12 +374,20 @@ mod test_util {
             .into()
         }
 
-        fn serialize_get_state_keys_entry(GetStateKeysEntry { value }: GetStateKeysEntry) -> Bytes {
+        fn serialize_get_state_keys_entry(
+            GetStateKeysEntry {
+                limit,
+                start_after,
+                value,
+            }: GetStateKeysEntry,
+        ) -> Bytes {
             GetStateKeysEntryMessage {
+                limit,
+                start_after,
                 result: value.map(|v| match v {
-                    GetStateKeysResult::Result(keys) => {
+                    GetStateKeysResult::Result { keys, has_more } => {
                         get_state_keys_entry_message::Result::Value(
-                            get_state_keys_entry_message::StateKeys { keys },
+                            get_state_keys_entry_message::StateKeys { keys, has_more },
                         )
                     }
                     GetStateKeysResult::Failure(code, reason) => {
@@ -395,8 +403,14 @@ mod test_util {
             .into()
         }
 
-        fn serialize_awakeable_entry(AwakeableEntry { result }: AwakeableEntry) -> Bytes {
+        fn serialize_awakeable_entry(
+            AwakeableEntry {
+                expiry_time,
+                result,
+            }: AwakeableEntry,
+        ) -> Bytes {
             AwakeableEntryMessage {
+                expiry_time,
                 result: result.map(|r| match r {
                     EntryResult::Success(success) => {
                         awakeable_entry_message::Result::Value(success)
@@ -221,6 +221,19 @@ impl ServiceDiscovery {
         Self::create_discovered_metadata_from_endpoint_response(endpoint.address(), response)
     }
 
+    /// Checks whether the endpoint answers discovery requests successfully, without
+    /// interpreting the returned manifest.
+    pub async fn probe(&self, endpoint: &DiscoverEndpoint) -> Result<(), DiscoveryError> {
+        Self::invoke_discovery_endpoint(
+            &self.client,
+            endpoint.address(),
+            || endpoint.request(),
+            self.retry_policy.clone().into_iter(),
+        )
+        .await
+        .map(|_| ())
+    }
+
     fn retrieve_service_discovery_protocol_version(
         content_type: Option<HeaderValue>,
     ) -> Result<ServiceDiscoveryProtocolVersion, DiscoveryError> {
@@ -279,12 +292,13 @@ impl ServiceDiscovery {
             // http2 upwards supports bidi
             (
                 ProtocolType::BidiStream,
-                Endpoint::Http(_, hyper::Version::HTTP_2 | hyper::Version::HTTP_3),
+                Endpoint::Http(_, hyper::Version::HTTP_2 | hyper::Version::HTTP_3, _),
             ) => {}
             // http1.1 *can* support bidi depending on server implementation (and load balancers)
             // trust the user if this is what they advertise
-            (ProtocolType::BidiStream, Endpoint::Http(_, hyper::Version::HTTP_11)) => {}
-            // lambda client and HTTP < 1.1 do not support bidi
+            (ProtocolType::BidiStream, Endpoint::Http(_, hyper::Version::HTTP_11, _)) => {}
+            // lambda client and HTTP < 1.1 do not support bidi; even when streaming the response,
+            // lambda functions receive the whole request at once
             (ProtocolType::BidiStream, _) => {
                 return Err(DiscoveryError::BidirectionalNotSupported);
             }
@@ -402,6 +416,7 @@ mod tests {
     };
     use restate_service_client::Endpoint;
     use restate_types::endpoint_manifest;
+    use restate_types::schema::deployment::LambdaInvokeMode;
     use restate_types::service_discovery::ServiceDiscoveryProtocolVersion;
     use restate_types::service_protocol::MAX_SERVICE_PROTOCOL_VERSION;
 
@@ -416,7 +431,11 @@ mod tests {
 
         assert!(matches!(
             ServiceDiscovery::create_discovered_metadata_from_endpoint_response(
-                &Endpoint::Http(hyper::Uri::default(), hyper::Version::HTTP_2),
+                &Endpoint::Http(
+                    hyper::Uri::default(),
+                    hyper::Version::HTTP_2,
+                    Default::default(),
+                ),
                 response
             ),
             Err(DiscoveryError::BadResponse(_))
@@ -438,7 +457,8 @@ mod tests {
                     "arn:partition:lambda:region:account_id:function:name:version"
                         .parse()
                         .unwrap(),
-                    None
+                    None,
+                    LambdaInvokeMode::ResponseStream,
                 ),
                 response
             ),
@@ -457,7 +477,11 @@ mod tests {
 
         assert!(matches!(
             ServiceDiscovery::create_discovered_metadata_from_endpoint_response(
-                &Endpoint::Http(hyper::Uri::default(), hyper::Version::HTTP_2),
+                &Endpoint::Http(
+                    hyper::Uri::default(),
+                    hyper::Version::HTTP_2,
+                    Default::default(),
+                ),
                 response
             ),
             Err(DiscoveryError::BadResponse(_))
@@ -475,7 +499,11 @@ mod tests {
 
         assert!(matches!(
             ServiceDiscovery::create_discovered_metadata_from_endpoint_response(
-                &Endpoint::Http(hyper::Uri::default(), hyper::Version::HTTP_2),
+                &Endpoint::Http(
+                    hyper::Uri::default(),
+                    hyper::Version::HTTP_2,
+                    Default::default(),
+                ),
                 response
             ),
             Err(DiscoveryError::BadResponse(_))
@@ -494,7 +522,11 @@ mod tests {
 
         assert!(
             matches!(ServiceDiscovery::create_discovered_metadata_from_endpoint_response(
-                &Endpoint::Http(hyper::Uri::default(), hyper::Version::HTTP_2),
+                &Endpoint::Http(
+                    hyper::Uri::default(),
+                    hyper::Version::HTTP_2,
+                    Default::default(),
+                ),
                 response
             ), Err(DiscoveryError::UnsupportedServiceProtocol { min_version, max_version }) if min_version == unsupported_version && max_version == unsupported_version )
         );
@@ -8,15 +8,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use super::header::UnknownMessageType;
+use super::header::{UnknownMessageCompression, UnknownMessageType};
 use super::*;
 
+use std::io::{self, Read, Write};
 use std::mem;
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use bytes_utils::SegmentedBuf;
 use restate_types::journal::raw::{PlainEntryHeader, RawEntry};
-use restate_types::service_protocol::ServiceProtocolVersion;
+use restate_types::service_protocol::{MessageCompression, ServiceProtocolVersion};
 use size::Size;
 use tracing::warn;
 
@@ -27,6 +28,16 @@ pub enum EncodingError {
     DecodeMessage(MessageType, #[source] prost::DecodeError),
     #[error(transparent)]
     UnknownMessageType(#[from] UnknownMessageType),
+    #[error(transparent)]
+    UnknownMessageCompression(#[from] UnknownMessageCompression),
+    #[error(
+        "cannot decompress message type {0:?}. This looks like a bug of the SDK. Reason: {1:?}"
+    )]
+    DecompressMessage(MessageType, #[source] io::Error),
+    #[error("received compressed message type {0:?}, but service protocol version {1:?} doesn't support compression. This looks like a bug of the SDK")]
+    UnsupportedMessageCompression(MessageType, ServiceProtocolVersion),
+    #[error("received message type {0:?} with field '{1}', but service protocol version {2:?} doesn't support it. This looks like a bug of the SDK")]
+    UnsupportedMessageField(MessageType, &'static str, ServiceProtocolVersion),
     #[error("hit message size limit: {0} >= {1}")]
     #[code(restate_errors::RT0003)]
     MessageSizeLimit(usize, usize),
@@ -34,20 +45,40 @@ pub enum EncodingError {
 
 // --- Input message encoder
 
-pub struct Encoder {}
+pub struct Encoder {
+    supports_compression: bool,
+    compression: Option<(MessageCompression, usize)>,
+}
 
 impl Encoder {
     pub fn new(service_protocol_version: ServiceProtocolVersion) -> Self {
-        assert_eq!(
-            service_protocol_version,
-            ServiceProtocolVersion::V1,
-            "Encoder only supports service protocol version V1"
+        assert!(
+            service_protocol_version.is_supported(),
+            "Encoder doesn't support service protocol version {service_protocol_version:?}"
         );
-        Self {}
+        Self {
+            supports_compression: service_protocol_version.supports_message_compression(),
+            compression: None,
+        }
+    }
+
+    /// Compresses the messages whose body is at least `threshold` bytes long.
+    /// This has no effect if the service protocol version doesn't support compression.
+    pub fn with_compression(mut self, compression: MessageCompression, threshold: usize) -> Self {
+        if self.supports_compression {
+            self.compression = Some((compression, threshold));
+        }
+        self
     }
 
     /// Encodes a message to bytes
     pub fn encode(&self, msg: ProtocolMessage) -> Bytes {
+        if let Some((compression, threshold)) = self.compression {
+            if msg.encoded_len() >= threshold {
+                return encode_compressed(msg, compression);
+            }
+        }
+
         let mut buf = BytesMut::with_capacity(self.encoded_len(&msg));
         self.encode_to_buf_mut(&mut buf, msg).expect(
             "Encoding messages should be infallible, \
@@ -57,11 +88,12 @@ impl Encoder {
         buf.freeze()
     }
 
-    /// Includes header len
+    /// Includes header len, without compression
     pub fn encoded_len(&self, msg: &ProtocolMessage) -> usize {
         8 + msg.encoded_len()
     }
 
+    /// Encodes a message without compressing it
     pub fn encode_to_buf_mut(
         &self,
         mut buf: impl BufMut,
@@ -78,6 +110,64 @@ impl Encoder {
     }
 }
 
+fn encode_compressed(msg: ProtocolMessage, compression: MessageCompression) -> Bytes {
+    let mut body = Vec::with_capacity(msg.encoded_len());
+    encode_msg(&msg, &mut body).expect("encoding to a Vec is infallible");
+
+    let compressed = compress(compression, &body).expect("compressing in memory is infallible");
+    // Not worth it, send the message as it is
+    if compressed.len() >= body.len() {
+        let mut buf = BytesMut::with_capacity(8 + body.len());
+        buf.put_u64(generate_header(&msg).into());
+        buf.put_slice(&body);
+        return buf.freeze();
+    }
+
+    let header = generate_header(&msg).with_compression(
+        compression,
+        compressed
+            .len()
+            .try_into()
+            .expect("Protocol messages can't be larger than u32"),
+    );
+    let mut buf = BytesMut::with_capacity(8 + compressed.len());
+    buf.put_u64(header.into());
+    buf.put_slice(&compressed);
+    buf.freeze()
+}
+
+fn compress(compression: MessageCompression, body: &[u8]) -> io::Result<Vec<u8>> {
+    match compression {
+        MessageCompression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(body)?;
+            encoder.finish()
+        }
+        MessageCompression::Zstd => zstd::bulk::compress(body, zstd::DEFAULT_COMPRESSION_LEVEL),
+    }
+}
+
+/// Decompresses the message body, stopping once `limit` bytes are read to bound the memory
+/// used by oversized messages.
+fn decompress(compression: MessageCompression, buf: impl Buf, limit: usize) -> io::Result<Bytes> {
+    let limit = u64::try_from(limit).unwrap_or(u64::MAX);
+    let mut body = Vec::new();
+    match compression {
+        MessageCompression::Gzip => {
+            flate2::read::GzDecoder::new(buf.reader())
+                .take(limit)
+                .read_to_end(&mut body)?;
+        }
+        MessageCompression::Zstd => {
+            zstd::stream::read::Decoder::new(buf.reader())?
+                .take(limit)
+                .read_to_end(&mut body)?;
+        }
+    }
+    Ok(body.into())
+}
+
 fn generate_header(msg: &ProtocolMessage) -> MessageHeader {
     let len: u32 = msg
         .encoded_len()
@@ -122,6 +212,8 @@ fn encode_msg(msg: &ProtocolMessage, buf: &mut impl BufMut) -> Result<(), prost:
 pub struct Decoder {
     buf: SegmentedBuf<Bytes>,
     state: DecoderState,
+    service_protocol_version: ServiceProtocolVersion,
+    supports_compression: bool,
     message_size_warning: usize,
     message_size_limit: usize,
 }
@@ -132,14 +224,15 @@ impl Decoder {
         message_size_warning: usize,
         message_size_limit: Option<usize>,
     ) -> Self {
-        assert_eq!(
-            service_protocol_version,
-            ServiceProtocolVersion::V1,
-            "Decoder only supports service protocol version V1"
+        assert!(
+            service_protocol_version.is_supported(),
+            "Decoder doesn't support service protocol version {service_protocol_version:?}"
         );
         Self {
             buf: SegmentedBuf::new(),
             state: DecoderState::WaitingHeader,
+            service_protocol_version,
+            supports_compression: service_protocol_version.supports_message_compression(),
             message_size_warning,
             message_size_limit: message_size_limit.unwrap_or(usize::MAX),
         }
@@ -167,6 +260,8 @@ impl Decoder {
 
             if let Some(res) = self.state.decode(
                 &mut self.buf,
+                self.service_protocol_version,
+                self.supports_compression,
                 self.message_size_warning,
                 self.message_size_limit,
             )? {
@@ -194,6 +289,8 @@ impl DecoderState {
     fn decode(
         &mut self,
         mut buf: impl Buf,
+        service_protocol_version: ServiceProtocolVersion,
+        supports_compression: bool,
         message_size_warning: usize,
         message_size_limit: usize,
     ) -> Result<Option<(MessageHeader, ProtocolMessage)>, EncodingError> {
@@ -202,6 +299,12 @@ impl DecoderState {
         *self = match mem::take(self) {
             DecoderState::WaitingHeader => {
                 let header: MessageHeader = buf.get_u64().try_into()?;
+                if !supports_compression && header.is_compressed() {
+                    return Err(EncodingError::UnsupportedMessageCompression(
+                        header.message_type(),
+                        service_protocol_version,
+                    ));
+                }
                 let message_length =
                     usize::try_from(header.frame_length()).expect("u32 must convert into usize");
 
@@ -225,8 +328,25 @@ impl DecoderState {
                 DecoderState::WaitingPayload(header)
             }
             DecoderState::WaitingPayload(h) => {
-                let msg = decode_protocol_message(&h, buf.take(h.frame_length() as usize))
-                    .map_err(|e| EncodingError::DecodeMessage(h.message_type(), e))?;
+                let mut payload = buf.take(h.frame_length() as usize);
+                let msg = match h.compression()? {
+                    None => decode_protocol_message(&h, payload),
+                    Some(compression) => {
+                        let body = decompress(compression, &mut payload, message_size_limit)
+                            .map_err(|e| EncodingError::DecompressMessage(h.message_type(), e))?;
+                        // Skip trailing bytes after the compressed body, if any
+                        payload.advance(payload.remaining());
+                        if body.len() >= message_size_limit {
+                            return Err(EncodingError::MessageSizeLimit(
+                                body.len(),
+                                message_size_limit,
+                            ));
+                        }
+                        decode_protocol_message(&h, body)
+                    }
+                }
+                .map_err(|e| EncodingError::DecodeMessage(h.message_type(), e))?;
+                check_message_fields(&h, &msg, service_protocol_version)?;
                 res = Some((h, msg));
                 DecoderState::WaitingHeader
             }
@@ -263,6 +383,52 @@ fn decode_protocol_message(
     })
 }
 
+/// Rejects the entry fields introduced by a newer service protocol version than the negotiated
+/// one.
+fn check_message_fields(
+    header: &MessageHeader,
+    msg: &ProtocolMessage,
+    service_protocol_version: ServiceProtocolVersion,
+) -> Result<(), EncodingError> {
+    let ProtocolMessage::UnparsedEntry(entry) = msg else {
+        return Ok(());
+    };
+
+    let unsupported_field = match header.message_type() {
+        MessageType::AwakeableEntry if !service_protocol_version.supports_awakeable_expiry() => {
+            service_protocol::AwakeableEntryMessage::decode(entry.serialized_entry().clone())
+                .map_err(|e| EncodingError::DecodeMessage(header.message_type(), e))?
+                .expiry_time
+                .map(|_| "expiry_time")
+        }
+        MessageType::GetStateKeysEntry
+            if !service_protocol_version.supports_state_keys_paging() =>
+        {
+            let entry_message = service_protocol::GetStateKeysEntryMessage::decode(
+                entry.serialized_entry().clone(),
+            )
+            .map_err(|e| EncodingError::DecodeMessage(header.message_type(), e))?;
+            if entry_message.limit.is_some() {
+                Some("limit")
+            } else if entry_message.start_after.is_some() {
+                Some("start_after")
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    match unsupported_field {
+        Some(field) => Err(EncodingError::UnsupportedMessageField(
+            header.message_type(),
+            field,
+            service_protocol_version,
+        )),
+        None => Ok(()),
+    }
+}
+
 macro_rules! expect_flag {
     ($message_header:expr, $name:ident) => {
         MessageHeader::$name($message_header)
@@ -471,4 +637,163 @@ mod tests {
         assert_eq!(msg_size, expected_msg_size);
         assert_eq!(limit, u8::MAX as usize)
     }
+
+    fn large_input_entry() -> ProtocolMessage {
+        ProtobufRawEntryCodec::serialize_as_input_entry(vec![], Bytes::from(vec![b'a'; 4096]))
+            .erase_enrichment()
+            .into()
+    }
+
+    fn compressing_encoder(compression: MessageCompression, threshold: usize) -> Encoder {
+        Encoder::new(ServiceProtocolVersion::V2).with_compression(compression, threshold)
+    }
+
+    fn decompressing_decoder(message_size_limit: Option<usize>) -> Decoder {
+        Decoder::new(ServiceProtocolVersion::V2, usize::MAX, message_size_limit)
+    }
+
+    #[test]
+    fn compressed_roundtrip() {
+        for compression in [MessageCompression::Gzip, MessageCompression::Zstd] {
+            let encoder = compressing_encoder(compression, 1024);
+            let mut decoder = decompressing_decoder(None);
+
+            let expected_msg = large_input_entry();
+            let encoded = encoder.encode(expected_msg.clone());
+            assert!(encoded.len() < encoder.encoded_len(&expected_msg));
+
+            // Push the message in two chunks
+            decoder.push(encoded.slice(0..20));
+            assert!(decoder.consume_next().unwrap().is_none());
+            decoder.push(encoded.slice(20..));
+
+            let (actual_msg_header, actual_msg) = decoder.consume_next().unwrap().unwrap();
+            assert_eq!(actual_msg_header.message_type(), MessageType::InputEntry);
+            assert_eq!(actual_msg_header.compression().unwrap(), Some(compression));
+            assert_eq!(actual_msg, expected_msg);
+            assert!(!decoder.has_remaining());
+        }
+    }
+
+    #[test]
+    fn compression_below_threshold_or_unsupported() {
+        let expected_msg = large_input_entry();
+
+        let encoder = compressing_encoder(MessageCompression::Zstd, usize::MAX);
+        assert_eq!(
+            encoder.encode(expected_msg.clone()).len(),
+            encoder.encoded_len(&expected_msg)
+        );
+
+        let encoder =
+            Encoder::new(ServiceProtocolVersion::V1).with_compression(MessageCompression::Zstd, 0);
+        let mut decoder = Decoder::new(ServiceProtocolVersion::V1, usize::MAX, None);
+        decoder.push(encoder.encode(expected_msg.clone()));
+        let (actual_msg_header, actual_msg) = decoder.consume_next().unwrap().unwrap();
+        assert_eq!(actual_msg_header.compression().unwrap(), None);
+        assert_eq!(actual_msg, expected_msg);
+    }
+
+    #[test]
+    fn hit_message_size_limit_after_decompression() {
+        let encoder = compressing_encoder(MessageCompression::Zstd, 0);
+        let mut decoder = decompressing_decoder(Some(1024));
+
+        decoder.push(encoder.encode(large_input_entry()));
+        let_assert!(
+            EncodingError::MessageSizeLimit(_, limit) = decoder.consume_next().unwrap_err()
+        );
+        assert_eq!(limit, 1024)
+    }
+
+    #[test]
+    fn reject_compressed_message_if_unsupported() {
+        let encoder = compressing_encoder(MessageCompression::Gzip, 0);
+        let mut decoder = Decoder::new(ServiceProtocolVersion::V1, usize::MAX, None);
+
+        decoder.push(encoder.encode(large_input_entry()));
+        let_assert!(
+            EncodingError::UnsupportedMessageCompression(message_type, version) =
+                decoder.consume_next().unwrap_err()
+        );
+        assert_eq!(message_type, MessageType::InputEntry);
+        assert_eq!(version, ServiceProtocolVersion::V1);
+    }
+
+    #[test]
+    fn negotiate_compression() {
+        let expected_msg = large_input_entry();
+
+        for (deployment_versions, compressed) in [(1..=1, false), (1..=2, true), (2..=3, true)] {
+            let service_protocol_version =
+                ServiceProtocolVersion::choose_max_supported_version(&deployment_versions).unwrap();
+            let encoder = Encoder::new(service_protocol_version)
+                .with_compression(MessageCompression::Zstd, 0);
+            let mut decoder = Decoder::new(service_protocol_version, usize::MAX, None);
+
+            decoder.push(encoder.encode(expected_msg.clone()));
+            let (actual_msg_header, actual_msg) = decoder.consume_next().unwrap().unwrap();
+            assert_eq!(actual_msg_header.is_compressed(), compressed);
+            assert_eq!(actual_msg, expected_msg);
+        }
+    }
+
+    fn assert_field_requires_v2(msg: ProtocolMessage, expected_field: &str) {
+        let encoder = Encoder::new(ServiceProtocolVersion::V1);
+        let mut decoder = Decoder::new(ServiceProtocolVersion::V1, usize::MAX, None);
+        decoder.push(encoder.encode(msg.clone()));
+        let_assert!(
+            EncodingError::UnsupportedMessageField(_, field, version) =
+                decoder.consume_next().unwrap_err()
+        );
+        assert_eq!(field, expected_field);
+        assert_eq!(version, ServiceProtocolVersion::V1);
+
+        let encoder = Encoder::new(ServiceProtocolVersion::V2);
+        let mut decoder = Decoder::new(ServiceProtocolVersion::V2, usize::MAX, None);
+        decoder.push(encoder.encode(msg.clone()));
+        let (_, actual_msg) = decoder.consume_next().unwrap().unwrap();
+        assert_eq!(actual_msg, msg);
+    }
+
+    #[test]
+    fn awakeable_expiry_requires_v2() {
+        let awakeable = ProtocolMessage::from(RawEntry::new(
+            PlainEntryHeader::Awakeable {
+                is_completed: false,
+            },
+            service_protocol::AwakeableEntryMessage {
+                expiry_time: Some(1000),
+                ..Default::default()
+            }
+            .encode_to_vec()
+            .into(),
+        ));
+
+        assert_field_requires_v2(awakeable, "expiry_time");
+    }
+
+    #[test]
+    fn state_keys_paging_requires_v2() {
+        let get_state_keys = |limit, start_after| {
+            ProtocolMessage::from(RawEntry::new(
+                PlainEntryHeader::GetStateKeys {
+                    is_completed: false,
+                },
+                service_protocol::GetStateKeysEntryMessage {
+                    limit,
+                    start_after,
+                    ..Default::default()
+                }
+                .encode_to_vec()
+                .into(),
+            ))
+        };
+
+        assert_field_requires_v2(get_state_keys(Some(10), None), "limit");
+        assert_field_requires_v2(
+            get_state_keys(None, Some(Bytes::from_static(b"key"))),
+            "start_after",
+        );
+    }
 }
@@ -9,10 +9,17 @@
 // by the Apache License, Version 2.0.
 
 use restate_types::journal::EntryType;
+use restate_types::service_protocol::MessageCompression;
 
 const CUSTOM_MESSAGE_MASK: u16 = 0xFC00;
 const COMPLETED_MASK: u64 = 0x0001_0000_0000;
 const REQUIRES_ACK_MASK: u64 = 0x8000_0000_0000;
+const COMPRESSION_MASK: u64 = 0x6000_0000_0000;
+const COMPRESSION_SHIFT: u32 = 45;
+
+const NO_COMPRESSION: u8 = 0;
+const GZIP_COMPRESSION: u8 = 1;
+const ZSTD_COMPRESSION: u8 = 2;
 
 type MessageTypeId = u16;
 
@@ -225,6 +232,10 @@ impl TryFrom<MessageType> for EntryType {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error("unknown message compression {0:#x}")]
+pub struct UnknownMessageCompression(u8);
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MessageHeader {
     ty: MessageType,
@@ -235,6 +246,8 @@ pub struct MessageHeader {
     completed_flag: Option<bool>,
     /// All Entry messages may have requires ack flag.
     requires_ack_flag: Option<bool>,
+    /// All messages may be compressed, if the service protocol version supports it.
+    compression: u8,
 }
 
 impl MessageHeader {
@@ -262,9 +275,22 @@ impl MessageHeader {
             completed_flag,
             // It is always false when sending entries from the runtime
             requires_ack_flag: Some(false),
+            compression: NO_COMPRESSION,
         }
     }
 
+    /// Marks the message body as compressed with the given algorithm, `length` being the
+    /// length of the compressed body.
+    #[inline]
+    pub(super) fn with_compression(mut self, compression: MessageCompression, length: u32) -> Self {
+        self.compression = match compression {
+            MessageCompression::Gzip => GZIP_COMPRESSION,
+            MessageCompression::Zstd => ZSTD_COMPRESSION,
+        };
+        self.length = length;
+        self
+    }
+
     #[inline]
     fn _new(
         ty: MessageType,
@@ -277,6 +303,7 @@ impl MessageHeader {
             length,
             completed_flag,
             requires_ack_flag,
+            compression: NO_COMPRESSION,
         }
     }
 
@@ -300,6 +327,22 @@ impl MessageHeader {
         self.requires_ack_flag
     }
 
+    /// Whether the `Z` field of the header is set, regardless of the algorithm being known.
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        self.compression != NO_COMPRESSION
+    }
+
+    #[inline]
+    pub fn compression(&self) -> Result<Option<MessageCompression>, UnknownMessageCompression> {
+        match self.compression {
+            NO_COMPRESSION => Ok(None),
+            GZIP_COMPRESSION => Ok(Some(MessageCompression::Gzip)),
+            ZSTD_COMPRESSION => Ok(Some(MessageCompression::Zstd)),
+            code => Err(UnknownMessageCompression(code)),
+        }
+    }
+
     #[inline]
     pub fn frame_length(&self) -> u32 {
         self.length
@@ -329,12 +372,9 @@ impl TryFrom<u64> for MessageHeader {
         let requires_ack_flag = read_flag_if!(ty.has_requires_ack_flag(), value, REQUIRES_ACK_MASK);
         let length = value as u32;
 
-        Ok(MessageHeader::_new(
-            ty,
-            completed_flag,
-            requires_ack_flag,
-            length,
-        ))
+        let mut header = MessageHeader::_new(ty, completed_flag, requires_ack_flag, length);
+        header.compression = ((value & COMPRESSION_MASK) >> COMPRESSION_SHIFT) as u8;
+        Ok(header)
     }
 }
 
@@ -359,6 +399,7 @@ impl From<MessageHeader> for u64 {
             &mut res,
             REQUIRES_ACK_MASK
         );
+        res |= ((message_header.compression as u64) << COMPRESSION_SHIFT) & COMPRESSION_MASK;
 
         res
     }
@@ -507,6 +548,31 @@ mod tests {
         requires_ack: false
     );
 
+    #[test]
+    fn compressed_completed_get_state() {
+        let header = MessageHeader::new_completable_entry(GetStateEntry, true, 10341)
+            .with_compression(MessageCompression::Zstd, 1024);
+        let serialized: u64 = header.into();
+        assert_eq!(serialized & COMPRESSION_MASK, 0x4000_0000_0000);
+
+        let header: MessageHeader = serialized.try_into().unwrap();
+        assert_eq!(header.message_type(), GetStateEntry);
+        assert_eq!(header.completed(), Some(true));
+        assert_eq!(header.requires_ack(), Some(false));
+        assert_eq!(header.frame_length(), 1024);
+        assert_eq!(
+            header.compression().unwrap(),
+            Some(MessageCompression::Zstd)
+        );
+    }
+
+    #[test]
+    fn unknown_compression() {
+        let serialized: u64 = u64::from(MessageHeader::new(Completion, 22)) | COMPRESSION_MASK;
+        let header: MessageHeader = serialized.try_into().unwrap();
+        assert!(header.compression().is_err());
+    }
+
     roundtrip_test!(
         custom_entry_with_requires_ack,
         MessageHeader::_new(MessageType::CustomEntry(0xFC00), None, Some(true), 10341),
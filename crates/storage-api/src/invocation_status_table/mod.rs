@@ -269,6 +269,9 @@ pub struct InFlightInvocationMetadata {
     /// If zero, the invocation completion will not be retained.
     pub completion_retention_time: Duration,
     pub idempotency_key: Option<ByteString>,
+    /// Entry index and expiry time of the awakeables registered with an expiry, so that their
+    /// timers can be deleted when the invocation ends without reading the journal.
+    pub awakeable_expiry_timers: Vec<(EntryIndex, MillisSinceEpoch)>,
 }
 
 impl InFlightInvocationMetadata {
@@ -287,6 +290,7 @@ impl InFlightInvocationMetadata {
                     .completion_retention_time
                     .unwrap_or_default(),
                 idempotency_key: service_invocation.idempotency_key,
+                awakeable_expiry_timers: Vec::new(),
             },
             InvocationInput {
                 argument: service_invocation.argument,
@@ -310,6 +314,7 @@ impl InFlightInvocationMetadata {
                 source: inboxed_invocation.source,
                 completion_retention_time: inboxed_invocation.completion_retention_time,
                 idempotency_key: inboxed_invocation.idempotency_key,
+                awakeable_expiry_timers: Vec::new(),
             },
             InvocationInput {
                 argument: inboxed_invocation.argument,
@@ -409,6 +414,7 @@ mod test_util {
                 source: Source::Ingress,
                 completion_retention_time: Duration::ZERO,
                 idempotency_key: None,
+                awakeable_expiry_timers: Vec::new(),
             }
         }
     }
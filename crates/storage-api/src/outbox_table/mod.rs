@@ -9,8 +9,11 @@
 // by the Apache License, Version 2.0.
 
 use crate::{protobuf_storage_encode_decode, Result};
+use futures_util::Stream;
 use restate_types::identifiers::{PartitionId, PartitionKey, WithPartitionKey};
-use restate_types::invocation::{InvocationResponse, InvocationTermination, ServiceInvocation};
+use restate_types::invocation::{
+    CallbackResponse, InvocationResponse, InvocationTermination, ServiceInvocation,
+};
 use std::future::Future;
 use std::ops::Range;
 
@@ -25,6 +28,9 @@ pub enum OutboxMessage {
 
     /// Terminate invocation to send to another partition processor
     InvocationTermination(InvocationTermination),
+
+    /// Invocation result to deliver to an external callback url
+    CallbackResponse(CallbackResponse),
 }
 
 protobuf_storage_encode_decode!(OutboxMessage);
@@ -35,10 +41,19 @@ impl WithPartitionKey for OutboxMessage {
             OutboxMessage::ServiceInvocation(si) => si.invocation_id.partition_key(),
             OutboxMessage::ServiceResponse(sr) => sr.id.partition_key(),
             OutboxMessage::InvocationTermination(it) => it.invocation_id.partition_key(),
+            OutboxMessage::CallbackResponse(cr) => cr.partition_key(),
         }
     }
 }
 
+pub trait ReadOnlyOutboxTable {
+    /// Scans all the messages of the given partition's outbox, ordered by their index.
+    fn all_outbox_messages(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(u64, OutboxMessage)>> + Send;
+}
+
 pub trait OutboxTable {
     fn add_message(
         &mut self,
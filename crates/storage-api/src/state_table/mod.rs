@@ -27,6 +27,16 @@ pub trait ReadOnlyStateTable {
         service_id: &ServiceId,
     ) -> impl Stream<Item = Result<(Bytes, Bytes)>> + Send;
 
+    /// Returns up to `limit` state keys of the service, without loading the state values. The keys
+    /// are returned in a stable order: if `start_after` is set, only the keys following it are
+    /// returned.
+    fn get_user_state_keys(
+        &mut self,
+        service_id: &ServiceId,
+        start_after: Option<&[u8]>,
+        limit: Option<usize>,
+    ) -> impl Stream<Item = Result<Bytes>> + Send;
+
     fn get_all_user_states(
         &self,
         range: RangeInclusive<PartitionKey>,
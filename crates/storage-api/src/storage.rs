@@ -101,20 +101,21 @@ pub mod v1 {
         use crate::storage::v1::journal_entry::completion_result::{Empty, Failure, Success};
         use crate::storage::v1::journal_entry::{completion_result, CompletionResult, Entry, Kind};
         use crate::storage::v1::outbox_message::{
-            OutboxCancel, OutboxKill, OutboxServiceInvocation, OutboxServiceInvocationResponse,
+            OutboxCallbackResponse, OutboxCancel, OutboxKill, OutboxServiceInvocation,
+            OutboxServiceInvocationResponse,
         };
         use crate::storage::v1::service_invocation_response_sink::{
-            Ingress, PartitionProcessor, ResponseSink,
+            Callback, Ingress, PartitionProcessor, ResponseSink,
         };
         use crate::storage::v1::{
             enriched_entry_header, entry_result, inbox_entry, invocation_resolution_result,
             invocation_status, invocation_target, outbox_message, promise, response_result, source,
             span_relation, submit_notification_sink, timer, virtual_object_status,
-            BackgroundCallResolutionResult, DedupSequenceNumber, Duration, EnrichedEntryHeader,
-            EntryResult, EpochSequenceNumber, Header, IdempotencyMetadata, InboxEntry,
-            InvocationId, InvocationResolutionResult, InvocationStatus, InvocationTarget,
-            JournalEntry, JournalEntryId, JournalMeta, KvPair, OutboxMessage, Promise,
-            ResponseResult, SequenceNumber, ServiceId, ServiceInvocation,
+            AwakeableExpiryTimer, BackgroundCallResolutionResult, DedupSequenceNumber, Duration,
+            EnrichedEntryHeader, EntryResult, EpochSequenceNumber, Header, IdempotencyMetadata,
+            InboxEntry, InvocationId, InvocationResolutionResult, InvocationStatus,
+            InvocationTarget, JournalEntry, JournalEntryId, JournalMeta, KvPair, OutboxMessage,
+            Promise, ResponseResult, SequenceNumber, ServiceId, ServiceInvocation,
             ServiceInvocationResponseSink, Source, SpanContext, SpanRelation, StateMutation,
             SubmitNotificationSink, Timer, VirtualObjectStatus,
         };
@@ -448,6 +449,9 @@ pub mod v1 {
                     source,
                     completion_retention_time,
                     idempotency_key,
+                    awakeable_expiry_timers: from_awakeable_expiry_timers(
+                        value.awakeable_expiry_timers,
+                    ),
                 })
             }
         }
@@ -463,6 +467,7 @@ pub mod v1 {
                     source,
                     completion_retention_time,
                     idempotency_key,
+                    awakeable_expiry_timers,
                 } = value;
 
                 let (deployment_id, service_protocol_version) = match pinned_deployment {
@@ -487,6 +492,7 @@ pub mod v1 {
                     source: Some(Source::from(source)),
                     completion_retention_time: Some(Duration::from(completion_retention_time)),
                     idempotency_key: idempotency_key.map(|key| key.to_string()),
+                    awakeable_expiry_timers: into_awakeable_expiry_timers(awakeable_expiry_timers),
                 }
             }
         }
@@ -554,6 +560,9 @@ pub mod v1 {
                         source: caller,
                         completion_retention_time,
                         idempotency_key,
+                        awakeable_expiry_timers: from_awakeable_expiry_timers(
+                            value.awakeable_expiry_timers,
+                        ),
                     },
                     waiting_for_completed_entries,
                 ))
@@ -602,10 +611,34 @@ pub mod v1 {
                         metadata.completion_retention_time,
                     )),
                     idempotency_key: metadata.idempotency_key.map(|key| key.to_string()),
+                    awakeable_expiry_timers: into_awakeable_expiry_timers(
+                        metadata.awakeable_expiry_timers,
+                    ),
                 }
             }
         }
 
+        fn from_awakeable_expiry_timers(
+            timers: Vec<AwakeableExpiryTimer>,
+        ) -> Vec<(restate_types::identifiers::EntryIndex, MillisSinceEpoch)> {
+            timers
+                .into_iter()
+                .map(|timer| (timer.entry_index, MillisSinceEpoch::new(timer.expiry_time)))
+                .collect()
+        }
+
+        fn into_awakeable_expiry_timers(
+            timers: Vec<(restate_types::identifiers::EntryIndex, MillisSinceEpoch)>,
+        ) -> Vec<AwakeableExpiryTimer> {
+            timers
+                .into_iter()
+                .map(|(entry_index, expiry_time)| AwakeableExpiryTimer {
+                    entry_index,
+                    expiry_time: expiry_time.as_u64(),
+                })
+                .collect()
+        }
+
         impl TryFrom<Inboxed> for crate::invocation_status_table::InboxedInvocation {
             type Error = ConversionError;
 
@@ -1383,6 +1416,11 @@ pub mod v1 {
                             },
                         )
                     }
+                    ResponseSink::Callback(callback) => Some(
+                        restate_types::invocation::ServiceInvocationResponseSink::Callback {
+                            url: ByteString::from(callback.url),
+                        },
+                    ),
                     ResponseSink::None(_) => None,
                 };
 
@@ -1412,6 +1450,11 @@ pub mod v1 {
                             request_id: Bytes::copy_from_slice(&request_id.to_bytes())
                         })
                     },
+                    Some(restate_types::invocation::ServiceInvocationResponseSink::Callback { url }) => {
+                        ResponseSink::Callback(Callback {
+                            url: url.to_string(),
+                        })
+                    },
                     None => ResponseSink::None(Default::default()),
                 };
 
@@ -1984,6 +2027,23 @@ pub mod v1 {
                             ),
                         )
                     }
+                    outbox_message::OutboxMessage::CallbackResponse(callback_response) => {
+                        crate::outbox_table::OutboxMessage::CallbackResponse(
+                            restate_types::invocation::CallbackResponse {
+                                url: ByteString::from(callback_response.url),
+                                invocation_id: restate_types::identifiers::InvocationId::try_from(
+                                    callback_response
+                                        .invocation_id
+                                        .ok_or(ConversionError::missing_field("invocation_id"))?,
+                                )?,
+                                result: restate_types::invocation::ResponseResult::try_from(
+                                    callback_response
+                                        .response_result
+                                        .ok_or(ConversionError::missing_field("response_result"))?,
+                                )?,
+                            },
+                        )
+                    }
                 };
 
                 Ok(result)
@@ -2031,6 +2091,15 @@ pub mod v1 {
                             })
                         }
                     },
+                    crate::outbox_table::OutboxMessage::CallbackResponse(callback_response) => {
+                        outbox_message::OutboxMessage::CallbackResponse(OutboxCallbackResponse {
+                            url: callback_response.url.to_string(),
+                            invocation_id: Some(InvocationId::from(
+                                callback_response.invocation_id,
+                            )),
+                            response_result: Some(ResponseResult::from(callback_response.result)),
+                        })
+                    }
                 };
 
                 OutboxMessage {
@@ -220,6 +220,14 @@ impl WithPartitionKey for Timer {
 
 protobuf_storage_encode_decode!(Timer);
 
+pub trait ReadOnlyTimerTable {
+    /// Scans all the timers of the given partition, ordered by their wake up time.
+    fn all_timers(
+        &self,
+        partition_id: PartitionId,
+    ) -> impl Stream<Item = Result<(TimerKey, Timer)>> + Send;
+}
+
 pub trait TimerTable {
     fn add_timer(
         &mut self,
@@ -9,14 +9,19 @@
 // by the Apache License, Version 2.0.
 
 use std::cmp::max;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use codederror::CodedError;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::datasource::TableProvider;
 use datafusion::error::DataFusionError;
 use datafusion::execution::context::{SQLOptions, SessionState};
 use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::logical_expr::LogicalPlan;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::prelude::{SessionConfig, SessionContext};
 
@@ -25,11 +30,15 @@ use restate_invoker_api::StatusHandle;
 use restate_partition_store::PartitionStoreManager;
 use restate_types::config::QueryEngineOptions;
 use restate_types::errors::GenericError;
-use restate_types::identifiers::PartitionId;
+use restate_types::identifiers::{PartitionId, PartitionKey};
 use restate_types::live::Live;
 use restate_types::schema::deployment::DeploymentResolver;
+use restate_types::schema::invocation_target::InvocationTargetResolver;
 use restate_types::schema::service::ServiceMetadataResolver;
 
+use crate::remote_query_scanner_manager::RemoteScannerManager;
+use crate::subscription_lag::ConsumerLagReader;
+use crate::table_providers::{PartitionedTableProvider, ScanPartition};
 use crate::{analyzer, physical_optimizer};
 
 const SYS_INVOCATION_VIEW: &str = "CREATE VIEW sys_invocation as SELECT
@@ -71,6 +80,27 @@ const SYS_INVOCATION_VIEW: &str = "CREATE VIEW sys_invocation as SELECT
         FROM sys_invocation_status ss
         LEFT JOIN sys_invocation_state sis ON ss.id = sis.id";
 
+/// Awakeables which expired are failed by the partition processor with the `codes::TIMEOUT` (408)
+/// failure code.
+const SYS_AWAKEABLE_VIEW: &str = "CREATE VIEW sys_awakeable as SELECT
+            sj.awakeable_id AS id,
+            sj.id AS invocation_id,
+            ss.target AS invocation_target,
+            sj.index AS entry_index,
+            sj.name,
+            sj.awakeable_expires_at AS expires_at,
+
+            CASE
+                WHEN NOT sj.completed THEN 'pending'
+                WHEN sj.awakeable_failure_code IS NULL THEN 'resolved'
+                WHEN sj.awakeable_failure_code = 408
+                    AND sj.awakeable_expires_at IS NOT NULL THEN 'expired'
+                ELSE 'rejected'
+            END AS status
+        FROM sys_journal sj
+        JOIN sys_invocation_status ss ON sj.id = ss.id
+        WHERE sj.entry_type = 'Awakeable'";
+
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum BuildError {
     #[error(transparent)]
@@ -83,10 +113,14 @@ pub trait SelectPartitions: Send + Sync + Debug + 'static {
     async fn get_live_partitions(&self) -> Result<Vec<PartitionId>, GenericError>;
 }
 
+type LocalPartitionScanners = HashMap<String, (SchemaRef, Arc<dyn ScanPartition>)>;
+
 #[derive(Clone)]
 pub struct QueryContext {
     sql_options: SQLOptions,
     datafusion_context: SessionContext,
+    remote_scanner_manager: Option<RemoteScannerManager>,
+    local_partition_scanners: Arc<Mutex<LocalPartitionScanners>>,
 }
 
 impl QueryContext {
@@ -96,17 +130,28 @@ impl QueryContext {
         partition_store_manager: PartitionStoreManager,
         status: impl StatusHandle + Send + Sync + Debug + Clone + 'static,
         schemas: Live<
-            impl DeploymentResolver + ServiceMetadataResolver + Send + Sync + Debug + Clone + 'static,
+            impl DeploymentResolver
+                + ServiceMetadataResolver
+                + InvocationTargetResolver
+                + Send
+                + Sync
+                + Debug
+                + Clone
+                + 'static,
         >,
+        consumer_lag: impl ConsumerLagReader + Send + Sync + 'static,
+        remote_scanner_manager: Option<RemoteScannerManager>,
     ) -> Result<QueryContext, BuildError> {
         let ctx = QueryContext::new(
             options.memory_size.get(),
             options.tmp_dir.clone(),
             options.query_parallelism(),
+            remote_scanner_manager,
         );
-        crate::deployment::register_self(&ctx, schemas.clone())?;
-        crate::service::register_self(&ctx, schemas)?;
+        crate::deployment::register_self(&ctx, schemas.clone(), status.clone())?;
+        crate::service::register_self(&ctx, schemas.clone())?;
         crate::invocation_state::register_self(&ctx, status)?;
+        crate::subscription_lag::register_self(&ctx, consumer_lag)?;
         // partition-key-based
         crate::invocation_status::register_self(
             &ctx,
@@ -127,6 +172,7 @@ impl QueryContext {
             &ctx,
             partition_selector.clone(),
             partition_store_manager.clone(),
+            schemas,
         )?;
         crate::inbox::register_self(
             &ctx,
@@ -138,13 +184,21 @@ impl QueryContext {
             partition_selector.clone(),
             partition_store_manager.clone(),
         )?;
-        crate::promise::register_self(&ctx, partition_selector.clone(), partition_store_manager)?;
+        crate::promise::register_self(
+            &ctx,
+            partition_selector.clone(),
+            partition_store_manager.clone(),
+        )?;
+        // partition-id-based
+        crate::timer::register_self(
+            &ctx,
+            partition_selector.clone(),
+            partition_store_manager.clone(),
+        )?;
+        crate::outbox::register_self(&ctx, partition_selector.clone(), partition_store_manager)?;
 
-        let ctx = ctx
-            .datafusion_context
-            .sql(SYS_INVOCATION_VIEW)
-            .await
-            .map(|_| ctx)?;
+        ctx.datafusion_context.sql(SYS_INVOCATION_VIEW).await?;
+        ctx.datafusion_context.sql(SYS_AWAKEABLE_VIEW).await?;
 
         Ok(ctx)
     }
@@ -153,6 +207,7 @@ impl QueryContext {
         memory_limit: usize,
         temp_folder: Option<String>,
         default_parallelism: Option<usize>,
+        remote_scanner_manager: Option<RemoteScannerManager>,
     ) -> Self {
         //
         // build the runtime
@@ -197,17 +252,101 @@ impl QueryContext {
         Self {
             sql_options,
             datafusion_context: ctx,
+            remote_scanner_manager,
+            local_partition_scanners: Default::default(),
         }
     }
 
+    /// Registers a table keyed by partition. If a [`RemoteScannerManager`] is configured, the
+    /// partitions whose leader runs on another node are scanned remotely.
+    pub(crate) fn register_partitioned_table<T>(
+        &self,
+        name: &str,
+        partition_selector: impl SelectPartitions,
+        schema: SchemaRef,
+        local_scanner: T,
+    ) -> datafusion::common::Result<()>
+    where
+        T: ScanPartition + Clone,
+    {
+        self.local_partition_scanners
+            .lock()
+            .expect("lock not poisoned")
+            .insert(
+                name.to_owned(),
+                (schema.clone(), Arc::new(local_scanner.clone())),
+            );
+
+        let table: Arc<dyn TableProvider> = match &self.remote_scanner_manager {
+            Some(remote_scanner_manager) => {
+                let (partition_selector, scanner) =
+                    remote_scanner_manager.create_distributed_scanner(name, local_scanner);
+                Arc::new(PartitionedTableProvider::new(
+                    partition_selector,
+                    schema,
+                    scanner,
+                ))
+            }
+            None => Arc::new(PartitionedTableProvider::new(
+                partition_selector,
+                schema,
+                local_scanner,
+            )),
+        };
+
+        self.datafusion_context
+            .register_table(name, table)
+            .map(|_| ())
+    }
+
+    /// Scans a partition of a partitioned table from the local partition store, returning only
+    /// the given columns. This serves the scans routed from other nodes.
+    pub fn scan_local_partition(
+        &self,
+        table: &str,
+        partition_id: PartitionId,
+        range: RangeInclusive<PartitionKey>,
+        columns: &[String],
+    ) -> datafusion::common::Result<SendableRecordBatchStream> {
+        let (schema, scanner) = self
+            .local_partition_scanners
+            .lock()
+            .expect("lock not poisoned")
+            .get(table)
+            .cloned()
+            .ok_or_else(|| DataFusionError::Plan(format!("unknown partitioned table '{table}'")))?;
+
+        let indices = columns
+            .iter()
+            .map(|column| schema.index_of(column))
+            .collect::<Result<Vec<_>, _>>()?;
+        let projection = SchemaRef::new(schema.project(&indices)?);
+
+        Ok(scanner.scan_partition(partition_id, range, projection))
+    }
+
     pub async fn execute(
         &self,
         sql: &str,
     ) -> datafusion::common::Result<SendableRecordBatchStream> {
+        let plan = self.create_logical_plan(sql).await?;
+        self.execute_logical_plan(plan).await
+    }
+
+    /// Plans the given SQL statement, rejecting statements which would modify the catalog or the
+    /// data.
+    pub async fn create_logical_plan(&self, sql: &str) -> datafusion::common::Result<LogicalPlan> {
         let state = self.datafusion_context.state();
         let statement = state.sql_to_statement(sql, "postgres")?;
         let plan = state.statement_to_plan(statement).await?;
         self.sql_options.verify_plan(&plan)?;
+        Ok(plan)
+    }
+
+    pub async fn execute_logical_plan(
+        &self,
+        plan: LogicalPlan,
+    ) -> datafusion::common::Result<SendableRecordBatchStream> {
         let df = self.datafusion_context.execute_logical_plan(plan).await?;
         df.execute_stream().await
     }
@@ -10,13 +10,16 @@
 
 use super::schema::SysDeploymentBuilder;
 use crate::table_util::format_using;
+use restate_invoker_api::{CircuitBreakerState, DeploymentHealthReport};
 use restate_types::schema::deployment::{Deployment, DeploymentType};
+use restate_types::time::MillisSinceEpoch;
 
 #[inline]
 pub(crate) fn append_deployment_row(
     builder: &mut SysDeploymentBuilder,
     output: &mut String,
     deployment: Deployment,
+    health: Option<&DeploymentHealthReport>,
 ) {
     let mut row = builder.row();
     row.id(format_using(output, &deployment.id));
@@ -32,4 +35,18 @@ pub(crate) fn append_deployment_row(
 
     row.endpoint(format_using(output, &deployment.metadata.address_display()));
     row.created_at(deployment.metadata.created_at.as_u64() as i64);
+
+    match health {
+        Some(health) => {
+            row.circuit_breaker_state(health.circuit_breaker_state.as_str());
+            if let Some(opened_at) = health.opened_at {
+                row.circuit_breaker_opened_at(MillisSinceEpoch::as_u64(&opened_at.into()) as i64);
+            }
+            row.held_invocations(health.held_invocations as u64);
+        }
+        None => {
+            row.circuit_breaker_state(CircuitBreakerState::Closed.as_str());
+            row.held_invocations(0);
+        }
+    }
 }
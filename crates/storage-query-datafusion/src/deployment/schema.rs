@@ -26,4 +26,16 @@ define_table!(sys_deployment(
 
     /// Timestamp indicating the deployment registration time.
     created_at: DataType::Date64,
+
+    /// State of the circuit breaker the invoker of this node keeps for the deployment.
+    /// Either `closed`, `open` or `half-open`. While open, the invocations targeting the
+    /// deployment are held until a health probe succeeds. While half-open, they are released
+    /// gradually until one of them reaches the deployment.
+    circuit_breaker_state: DataType::LargeUtf8,
+
+    /// Timestamp indicating when the circuit breaker was opened, if it is open.
+    circuit_breaker_opened_at: DataType::Date64,
+
+    /// Number of invocations held by the open or half-open circuit breaker.
+    held_invocations: DataType::UInt64,
 ));
@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
@@ -19,7 +20,8 @@ use datafusion::physical_plan::SendableRecordBatchStream;
 use restate_types::live::Live;
 use tokio::sync::mpsc::Sender;
 
-use restate_types::identifiers::ServiceRevision;
+use restate_invoker_api::{DeploymentHealthReport, StatusHandle};
+use restate_types::identifiers::{DeploymentId, ServiceRevision};
 use restate_types::schema::deployment::{Deployment, DeploymentResolver};
 
 use super::schema::SysDeploymentBuilder;
@@ -31,10 +33,11 @@ use crate::table_util::Builder;
 pub(crate) fn register_self(
     ctx: &QueryContext,
     resolver: Live<impl DeploymentResolver + Send + Sync + 'static>,
+    status: impl StatusHandle + Send + Sync + Clone + 'static,
 ) -> datafusion::common::Result<()> {
     let deployment_table = GenericTableProvider::new(
         SysDeploymentBuilder::schema(),
-        Arc::new(DeploymentMetadataScanner(resolver)),
+        Arc::new(DeploymentMetadataScanner(resolver, status)),
     );
 
     ctx.as_ref()
@@ -43,15 +46,19 @@ pub(crate) fn register_self(
 }
 
 #[derive(Clone)]
-struct DeploymentMetadataScanner<DMR>(Live<DMR>);
+struct DeploymentMetadataScanner<DMR, S>(Live<DMR>, S);
 
-impl<T> fmt::Debug for DeploymentMetadataScanner<T> {
+impl<T, S> fmt::Debug for DeploymentMetadataScanner<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("DeploymentMetadataScanner")
     }
 }
 
-impl<DMR: DeploymentResolver + Sync + Send + 'static> Scan for DeploymentMetadataScanner<DMR> {
+impl<DMR, S> Scan for DeploymentMetadataScanner<DMR, S>
+where
+    DMR: DeploymentResolver + Sync + Send + 'static,
+    S: StatusHandle + Send + Sync + Clone + 'static,
+{
     fn scan(
         &self,
         projection: SchemaRef,
@@ -63,8 +70,15 @@ impl<DMR: DeploymentResolver + Sync + Send + 'static> Scan for DeploymentMetadat
         let tx = stream_builder.tx();
 
         let rows = self.0.pinned().get_deployments();
+        let status = self.1.clone();
         stream_builder.spawn(async move {
-            for_each_state(schema, tx, rows).await;
+            let health = status
+                .read_deployment_health()
+                .await
+                .into_iter()
+                .map(|report| (report.deployment_id, report))
+                .collect();
+            for_each_state(schema, tx, rows, health).await;
             Ok(())
         });
         stream_builder.build()
@@ -75,11 +89,13 @@ async fn for_each_state(
     schema: SchemaRef,
     tx: Sender<datafusion::common::Result<RecordBatch>>,
     rows: Vec<(Deployment, Vec<(String, ServiceRevision)>)>,
+    health: HashMap<DeploymentId, DeploymentHealthReport>,
 ) {
     let mut builder = SysDeploymentBuilder::new(schema.clone());
     let mut temp = String::new();
     for (deployment, _) in rows {
-        append_deployment_row(&mut builder, &mut temp, deployment);
+        let deployment_health = health.get(&deployment.id);
+        append_deployment_row(&mut builder, &mut temp, deployment, deployment_health);
         if builder.full() {
             let batch = builder.finish();
             if tx.send(batch).await.is_err() {
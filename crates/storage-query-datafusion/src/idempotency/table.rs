@@ -10,7 +10,6 @@
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -22,22 +21,18 @@ use super::row::append_idempotency_row;
 use super::schema::SysIdempotencyBuilder;
 use crate::context::{QueryContext, SelectPartitions};
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_idempotency",
         partition_selector,
         SysIdempotencyBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, IdempotencyScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_idempotency", Arc::new(table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -55,6 +50,7 @@ impl ScanLocalPartition for IdempotencyScanner {
     }
 
     fn append_row(
+        &self,
         row_builder: &mut Self::Builder,
         string_buffer: &mut String,
         (idempotency_id, idempotency_metadata): Self::Item,
@@ -10,7 +10,6 @@
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -22,22 +21,18 @@ use crate::context::{QueryContext, SelectPartitions};
 use crate::inbox::row::append_inbox_row;
 use crate::inbox::schema::SysInboxBuilder;
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_inbox",
         partition_selector,
         SysInboxBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, InboxScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_inbox", Arc::new(table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Debug, Clone)]
@@ -54,7 +49,12 @@ impl ScanLocalPartition for InboxScanner {
         partition_store.all_inboxes(range)
     }
 
-    fn append_row(row_builder: &mut Self::Builder, string_buffer: &mut String, value: Self::Item) {
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
         append_inbox_row(row_builder, string_buffer, value);
     }
 }
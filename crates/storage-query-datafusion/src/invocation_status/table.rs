@@ -10,7 +10,6 @@
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -24,22 +23,18 @@ use crate::context::{QueryContext, SelectPartitions};
 use crate::invocation_status::row::append_invocation_status_row;
 use crate::invocation_status::schema::SysInvocationStatusBuilder;
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let status_table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_invocation_status",
         partition_selector,
         SysInvocationStatusBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, StatusScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_invocation_status", Arc::new(status_table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +52,7 @@ impl ScanLocalPartition for StatusScanner {
     }
 
     fn append_row(
+        &self,
         row_builder: &mut Self::Builder,
         string_buffer: &mut String,
         (invocation_id, invocation_status): Self::Item,
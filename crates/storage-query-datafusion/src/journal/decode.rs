@@ -0,0 +1,261 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Rendering of the decoded journal entries as JSON.
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use bytes::Bytes;
+use serde_json::{json, Value};
+
+use restate_service_protocol::codec::ProtobufRawEntryCodec;
+use restate_types::errors::InvocationErrorCode;
+use restate_types::journal::enriched::EnrichedRawEntry;
+use restate_types::journal::{
+    CompleteResult, CompletionResult, Entry, EntryResult, GetStateKeysResult, InvokeRequest,
+    SleepResult,
+};
+use restate_types::schema::invocation_target::{
+    InputContentType, InputRules, InputValidationRule, InvocationTargetMetadata,
+    InvocationTargetResolver, OutputContentTypeRule, OutputRules,
+};
+
+/// Decodes the entry and renders its parameters and results as JSON. Returns `None` if the
+/// entry cannot be decoded.
+///
+/// The parameters and results of calls are rendered according to the content types of the latest
+/// revision of the called handler, which is resolved with the given `resolver`.
+pub(crate) fn entry_to_json(
+    entry: &EnrichedRawEntry,
+    resolver: &impl InvocationTargetResolver,
+) -> Option<String> {
+    let entry = entry
+        .deserialize_entry_ref::<ProtobufRawEntryCodec>()
+        .ok()?;
+
+    let value = match entry {
+        Entry::Input(input) => json!({ "value": payload(&input.value, PayloadFormat::Unknown) }),
+        Entry::Output(output) => json!({
+            "result": entry_result(&output.result, PayloadFormat::Unknown),
+        }),
+        Entry::GetState(get_state) => json!({
+            "key": key(&get_state.key),
+            "value": get_state
+                .value
+                .as_ref()
+                .map(|result| completion_result(result, PayloadFormat::Unknown)),
+        }),
+        Entry::SetState(set_state) => json!({
+            "key": key(&set_state.key),
+            "value": payload(&set_state.value, PayloadFormat::Unknown),
+        }),
+        Entry::ClearState(clear_state) => json!({ "key": key(&clear_state.key) }),
+        Entry::GetStateKeys(get_state_keys) => json!({
+            "limit": get_state_keys.limit,
+            "start_after": get_state_keys.start_after.as_ref().map(key),
+            "value": get_state_keys.value.as_ref().map(|result| match result {
+                GetStateKeysResult::Result { keys, has_more } => {
+                    json!({
+                        "keys": keys.iter().map(key).collect::<Vec<_>>(),
+                        "has_more": has_more,
+                    })
+                }
+                GetStateKeysResult::Failure(code, message) => failure(*code, message),
+            }),
+        }),
+        Entry::ClearAllState => json!({}),
+        Entry::GetPromise(get_promise) => json!({
+            "key": get_promise.key.as_ref(),
+            "value": get_promise
+                .value
+                .as_ref()
+                .map(|result| entry_result(result, PayloadFormat::Unknown)),
+        }),
+        Entry::PeekPromise(peek_promise) => json!({
+            "key": peek_promise.key.as_ref(),
+            "value": peek_promise
+                .value
+                .as_ref()
+                .map(|result| completion_result(result, PayloadFormat::Unknown)),
+        }),
+        Entry::CompletePromise(complete_promise) => json!({
+            "key": complete_promise.key.as_ref(),
+            "completion": entry_result(&complete_promise.completion, PayloadFormat::Unknown),
+            "value": complete_promise.value.as_ref().map(|result| match result {
+                CompleteResult::Done => json!({ "done": true }),
+                CompleteResult::Failure(code, message) => failure(*code, message),
+            }),
+        }),
+        Entry::Sleep(sleep) => json!({
+            "wake_up_time": sleep.wake_up_time,
+            "result": sleep.result.as_ref().map(|result| match result {
+                SleepResult::Fired => json!({ "fired": true }),
+                SleepResult::Failure(code, message) => failure(*code, message),
+            }),
+        }),
+        Entry::Call(call) => {
+            let target = resolve_target(resolver, &call.request);
+            let result_format = target
+                .as_ref()
+                .map(|target| output_format(&target.output_rules))
+                .unwrap_or(PayloadFormat::Unknown);
+
+            let mut value = invoke_request(&call.request, target.as_ref());
+            value["result"] = call
+                .result
+                .as_ref()
+                .map(|result| entry_result(result, result_format))
+                .into();
+            value
+        }
+        Entry::OneWayCall(one_way_call) => {
+            let target = resolve_target(resolver, &one_way_call.request);
+            let mut value = invoke_request(&one_way_call.request, target.as_ref());
+            value["invoke_time"] = one_way_call.invoke_time.into();
+            value
+        }
+        Entry::Awakeable(awakeable) => json!({
+            "expiry_time": awakeable.expiry_time,
+            "result": awakeable
+                .result
+                .as_ref()
+                .map(|result| entry_result(result, PayloadFormat::Unknown)),
+        }),
+        Entry::CompleteAwakeable(complete_awakeable) => json!({
+            "id": complete_awakeable.id.as_ref(),
+            "result": entry_result(&complete_awakeable.result, PayloadFormat::Unknown),
+        }),
+        Entry::Run(run) => json!({ "result": entry_result(&run.result, PayloadFormat::Unknown) }),
+        Entry::Custom(value) => json!({ "value": base64(value) }),
+    };
+
+    Some(value.to_string())
+}
+
+pub(crate) fn completion_to_json(completion: &CompletionResult) -> String {
+    json!({ "result": completion_result(completion, PayloadFormat::Unknown) }).to_string()
+}
+
+fn resolve_target(
+    resolver: &impl InvocationTargetResolver,
+    request: &InvokeRequest,
+) -> Option<InvocationTargetMetadata> {
+    resolver.resolve_latest_invocation_target(&request.service_name, &request.handler_name)
+}
+
+fn invoke_request(request: &InvokeRequest, target: Option<&InvocationTargetMetadata>) -> Value {
+    let parameter_format = target
+        .map(|target| input_format(&target.input_rules))
+        .unwrap_or(PayloadFormat::Unknown);
+
+    json!({
+        "service_name": request.service_name.as_ref(),
+        "handler_name": request.handler_name.as_ref(),
+        "key": (!request.key.is_empty()).then(|| request.key.as_ref()),
+        "parameter": payload(&request.parameter, parameter_format),
+    })
+}
+
+fn entry_result(result: &EntryResult, format: PayloadFormat) -> Value {
+    match result {
+        EntryResult::Success(value) => json!({ "success": payload(value, format) }),
+        EntryResult::Failure(code, message) => failure(*code, message),
+    }
+}
+
+fn completion_result(result: &CompletionResult, format: PayloadFormat) -> Value {
+    match result {
+        CompletionResult::Empty => json!({ "success": null }),
+        CompletionResult::Success(value) => json!({ "success": payload(value, format) }),
+        CompletionResult::Failure(code, message) => failure(*code, message),
+    }
+}
+
+fn failure(code: InvocationErrorCode, message: &str) -> Value {
+    json!({ "failure": { "code": u16::from(code), "message": message } })
+}
+
+/// Content type of a payload, as far as it is known from the handler consuming or producing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PayloadFormat {
+    Json,
+    Binary,
+    /// The journal doesn't record the content type of the payloads, so unless they belong to a
+    /// handler with a declared content type, whether they are JSON is guessed by parsing them.
+    Unknown,
+}
+
+/// Payloads are tagged with how they are rendered: `{"json": ...}` for JSON payloads and
+/// `{"base64": "..."}` otherwise, including payloads which don't match their JSON content type.
+fn payload(value: &Bytes, format: PayloadFormat) -> Value {
+    if format == PayloadFormat::Binary {
+        return base64(value);
+    }
+    match serde_json::from_slice::<Value>(value) {
+        Ok(value) => json!({ "json": value }),
+        Err(_) => base64(value),
+    }
+}
+
+/// The input rules can accept several content types, the format is known only if they agree.
+fn input_format(rules: &InputRules) -> PayloadFormat {
+    rules
+        .input_validation_rules
+        .iter()
+        .filter_map(|rule| match rule {
+            InputValidationRule::NoBodyAndContentType => None,
+            InputValidationRule::JsonValue { .. } => Some(PayloadFormat::Json),
+            InputValidationRule::ContentType {
+                content_type: InputContentType::MimeTypeAndSubtype(_, subtype),
+            } => Some(subtype_format(subtype)),
+            InputValidationRule::ContentType { .. } => Some(PayloadFormat::Unknown),
+        })
+        .reduce(|a, b| if a == b { a } else { PayloadFormat::Unknown })
+        .unwrap_or(PayloadFormat::Unknown)
+}
+
+fn output_format(rules: &OutputRules) -> PayloadFormat {
+    match &rules.content_type_rule {
+        OutputContentTypeRule::None => PayloadFormat::Unknown,
+        OutputContentTypeRule::Set {
+            has_json_schema: true,
+            ..
+        } => PayloadFormat::Json,
+        OutputContentTypeRule::Set { content_type, .. } => content_type
+            .to_str()
+            .ok()
+            .and_then(|content_type| {
+                let (mime_type, _parameters) =
+                    content_type.split_once(';').unwrap_or((content_type, ""));
+                mime_type.split_once('/')
+            })
+            .map(|(_, subtype)| subtype_format(subtype.trim()))
+            .unwrap_or(PayloadFormat::Unknown),
+    }
+}
+
+fn subtype_format(subtype: &str) -> PayloadFormat {
+    if subtype.eq_ignore_ascii_case("json") || subtype.to_ascii_lowercase().ends_with("+json") {
+        PayloadFormat::Json
+    } else {
+        PayloadFormat::Binary
+    }
+}
+
+/// State keys are usually strings, but can contain arbitrary bytes, which are tagged as base64.
+fn key(key: &Bytes) -> Value {
+    match std::str::from_utf8(key) {
+        Ok(key) => Value::String(key.to_owned()),
+        Err(_) => base64(key),
+    }
+}
+
+fn base64(value: &[u8]) -> Value {
+    json!({ "base64": BASE64_STANDARD.encode(value) })
+}
@@ -8,21 +8,26 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::journal::decode::{completion_to_json, entry_to_json};
 use crate::journal::schema::SysJournalBuilder;
 
+use restate_service_protocol::awakeable_id::AwakeableIdentifier;
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
 
 use restate_storage_api::journal_table::JournalEntry;
 use restate_types::identifiers::{JournalEntryId, WithInvocationId, WithPartitionKey};
 use restate_types::journal::enriched::{EnrichedEntryHeader, EnrichedRawEntry};
+use restate_types::schema::invocation_target::InvocationTargetResolver;
 
 use crate::table_util::format_using;
-use restate_types::journal::{Entry, SleepEntry};
+use restate_types::journal::{AwakeableEntry, Entry, EntryResult, SleepEntry};
+use tracing::warn;
 
 #[inline]
 pub(crate) fn append_journal_row(
     builder: &mut SysJournalBuilder,
     output: &mut String,
+    resolver: &impl InvocationTargetResolver,
     journal_entry_id: JournalEntryId,
     journal_entry: JournalEntry,
 ) {
@@ -52,6 +57,12 @@ pub(crate) fn append_journal_row(
                 }
             }
 
+            if row.is_entry_json_defined() {
+                if let Some(entry_json) = entry_to_json(&entry, resolver) {
+                    row.entry_json(entry_json);
+                }
+            }
+
             if row.is_raw_defined() {
                 row.raw(entry.serialized_entry());
             }
@@ -81,12 +92,40 @@ pub(crate) fn append_journal_row(
                         }
                     }
                 }
+                EnrichedEntryHeader::Awakeable { .. } => {
+                    if row.is_awakeable_id_defined() {
+                        row.awakeable_id(format_using(
+                            output,
+                            &AwakeableIdentifier::new(
+                                journal_entry_id.invocation_id(),
+                                journal_entry_id.journal_index(),
+                            ),
+                        ));
+                    }
+                    if row.is_awakeable_expires_at_defined()
+                        || row.is_awakeable_failure_code_defined()
+                    {
+                        if let Some(awakeable_entry) =
+                            deserialize_awakeable_entry(journal_entry_id, &entry)
+                        {
+                            if let Some(expiry_time) = awakeable_entry.expiry_time {
+                                row.awakeable_expires_at(expiry_time as i64);
+                            }
+                            if let Some(EntryResult::Failure(code, _)) = awakeable_entry.result {
+                                row.awakeable_failure_code(code.into());
+                            }
+                        }
+                    }
+                }
                 _ => {}
             }
         }
-        JournalEntry::Completion(_completion) => {
+        JournalEntry::Completion(completion) => {
             row.entry_type("CompletionResult");
             row.completed(true);
+            if row.is_entry_json_defined() {
+                row.entry_json(completion_to_json(&completion));
+            }
         }
     };
 }
@@ -102,3 +141,31 @@ fn deserialize_sleep_entry(entry: &EnrichedRawEntry) -> Option<SleepEntry> {
         _ => None,
     }
 }
+
+fn deserialize_awakeable_entry(
+    journal_entry_id: JournalEntryId,
+    entry: &EnrichedRawEntry,
+) -> Option<AwakeableEntry> {
+    // A corrupted entry leaves the column empty rather than failing the whole query
+    match entry.deserialize_entry_ref::<ProtobufRawEntryCodec>() {
+        Ok(Entry::Awakeable(awakeable_entry)) => Some(awakeable_entry),
+        Ok(_) => {
+            warn!(
+                restate.invocation.id = %journal_entry_id.invocation_id(),
+                restate.journal.index = journal_entry_id.journal_index(),
+                "Expected an awakeable journal entry, got {:?}",
+                entry.ty()
+            );
+            None
+        }
+        Err(err) => {
+            warn!(
+                restate.invocation.id = %journal_entry_id.invocation_id(),
+                restate.journal.index = journal_entry_id.journal_index(),
+                "Cannot decode the awakeable journal entry: {}",
+                err
+            );
+            None
+        }
+    }
+}
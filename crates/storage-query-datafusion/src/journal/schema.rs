@@ -46,6 +46,23 @@ define_table!(sys_journal(
     /// If this entry represents a sleep, indicates wakeup time.
     sleep_wakeup_at: DataType::Date64,
 
+    /// If this entry represents an awakeable, indicates the ID to use to resolve or reject it.
+    awakeable_id: DataType::LargeUtf8,
+
+    /// If this entry represents an awakeable with an expiry, indicates when it fails unless
+    /// completed before.
+    awakeable_expires_at: DataType::Date64,
+
+    /// If this entry represents an awakeable which has been rejected or which expired, indicates
+    /// the failure code.
+    awakeable_failure_code: DataType::UInt32,
+
+    /// The entry decoded as JSON, including its parameters and results. Payloads are tagged with
+    /// their encoding: `{"json": ...}` for JSON payloads, `{"base64": "..."}` otherwise. The
+    /// parameters and results of calls follow the content types of the called handler, other
+    /// payloads are rendered as JSON if they parse as such.
+    entry_json: DataType::LargeUtf8,
+
     /// Raw binary representation of the entry. Check the [service protocol](https://github.com/restatedev/service-protocol)
     /// for more details to decode it.
     raw: DataType::LargeBinary,
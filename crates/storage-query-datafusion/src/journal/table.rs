@@ -9,40 +9,48 @@
 // by the Apache License, Version 2.0.
 
 use futures::Stream;
-use std::fmt::Debug;
+use std::fmt;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use restate_partition_store::{PartitionStore, PartitionStoreManager};
 use restate_storage_api::journal_table::{JournalEntry, ReadOnlyJournalTable};
 use restate_types::identifiers::{JournalEntryId, PartitionKey};
+use restate_types::live::Live;
+use restate_types::schema::invocation_target::InvocationTargetResolver;
 
 use crate::context::{QueryContext, SelectPartitions};
 use crate::journal::row::append_journal_row;
 use crate::journal::schema::SysJournalBuilder;
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
+    resolver: Live<impl InvocationTargetResolver + Send + Sync + Clone + 'static>,
 ) -> datafusion::common::Result<()> {
-    let journal_table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_journal",
         partition_selector,
         SysJournalBuilder::schema(),
-        LocalPartitionsScanner::new(partition_store_manager, JournalScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_journal", Arc::new(journal_table))
-        .map(|_| ())
+        LocalPartitionsScanner::new(partition_store_manager, JournalScanner(resolver)),
+    )
 }
 
-#[derive(Debug, Clone)]
-struct JournalScanner;
+/// Resolves the handlers of the calls, to render their parameters and results according to their
+/// content type.
+#[derive(Clone)]
+struct JournalScanner<R>(Live<R>);
+
+impl<R> fmt::Debug for JournalScanner<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JournalScanner")
+    }
+}
 
-impl ScanLocalPartition for JournalScanner {
+impl<R: InvocationTargetResolver + Send + Sync + Clone + 'static> ScanLocalPartition
+    for JournalScanner<R>
+{
     type Builder = SysJournalBuilder;
     type Item = (JournalEntryId, JournalEntry);
 
@@ -53,7 +61,18 @@ impl ScanLocalPartition for JournalScanner {
         partition_store.all_journals(range)
     }
 
-    fn append_row(row_builder: &mut Self::Builder, string_buffer: &mut String, value: Self::Item) {
-        append_journal_row(row_builder, string_buffer, value.0, value.1);
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
+        append_journal_row(
+            row_builder,
+            string_buffer,
+            &*self.0.pinned(),
+            value.0,
+            value.1,
+        );
     }
 }
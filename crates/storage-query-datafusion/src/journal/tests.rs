@@ -11,22 +11,32 @@
 use crate::mocks::*;
 use crate::row;
 use bytes::Bytes;
-use datafusion::arrow::array::{Int64Array, LargeStringArray, UInt32Array};
+use bytestring::ByteString;
+use datafusion::arrow::array::{Array, Date64Array, Int64Array, LargeStringArray, UInt32Array};
 use datafusion::arrow::record_batch::RecordBatch;
 use futures::StreamExt;
 use googletest::all;
 use googletest::prelude::{assert_that, eq};
 use prost::Message;
 use restate_core::TaskCenterBuilder;
+use restate_invoker_api::status_handle::test_util::MockStatusHandle;
+use restate_service_protocol::awakeable_id::AwakeableIdentifier;
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_api::journal_table::{JournalEntry, JournalTable};
 use restate_storage_api::Transaction;
+use restate_types::errors::codes;
 use restate_types::identifiers::InvocationId;
-use restate_types::invocation::InvocationTarget;
+use restate_types::invocation::{InvocationTarget, InvocationTargetType};
 use restate_types::journal::enriched::{
     CallEnrichmentResult, EnrichedEntryHeader, EnrichedRawEntry,
 };
-use restate_types::journal::{Entry, EntryType, InputEntry};
+use restate_types::journal::{
+    AwakeableEntry, Entry, EntryResult, EntryType, InputEntry, InvokeRequest, SetStateEntry,
+};
+use restate_types::schema::invocation_target::{
+    InputContentType, InputRules, InputValidationRule, InvocationTargetMetadata,
+    OutputContentTypeRule, OutputRules,
+};
 use restate_types::service_protocol;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -131,6 +141,236 @@ async fn get_entries() {
     );
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_entries_as_json() {
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+    let mut engine = tc
+        .run_in_scope("mock-query-engine", None, MockQueryEngine::create())
+        .await;
+
+    let mut tx = engine.partition_store().transaction();
+    let journal_invocation_id = InvocationId::mock_random();
+    tx.put_journal_entry(
+        &journal_invocation_id,
+        0,
+        JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Input(
+            InputEntry {
+                value: Bytes::from_static(&[0xff, 0x00]),
+            },
+        ))),
+    )
+    .await;
+    tx.put_journal_entry(
+        &journal_invocation_id,
+        1,
+        JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::SetState(
+            SetStateEntry {
+                key: Bytes::from_static(b"my-key"),
+                value: Bytes::from_static(br#"{"count":1}"#),
+            },
+        ))),
+    )
+    .await;
+    tx.commit().await.unwrap();
+
+    let records = engine
+        .execute("SELECT index, entry_json FROM sys_journal ORDER BY index")
+        .await
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_that!(
+        records,
+        all!(
+            row!(
+                0,
+                {
+                    "index" => UInt32Array: eq(0),
+                    "entry_json" => LargeStringArray: eq(r#"{"value":{"base64":"/wA="}}"#),
+                }
+            ),
+            row!(
+                1,
+                {
+                    "index" => UInt32Array: eq(1),
+                    "entry_json" => LargeStringArray: eq(r#"{"key":"my-key","value":{"json":{"count":1}}}"#),
+                }
+            )
+        )
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_call_entries_as_json_using_handler_content_types() {
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+
+    let mut schemas = MockSchemas::default();
+    schemas.2.add(
+        "Greeter",
+        [(
+            "greet",
+            InvocationTargetMetadata {
+                input_rules: InputRules {
+                    input_validation_rules: vec![InputValidationRule::ContentType {
+                        content_type: InputContentType::MimeTypeAndSubtype(
+                            "text".into(),
+                            "plain".into(),
+                        ),
+                    }],
+                },
+                output_rules: OutputRules {
+                    content_type_rule: OutputContentTypeRule::Set {
+                        content_type: "text/plain".parse().unwrap(),
+                        set_content_type_if_empty: false,
+                        has_json_schema: false,
+                    },
+                },
+                ..InvocationTargetMetadata::mock(InvocationTargetType::Service)
+            },
+        )],
+    );
+    let mut engine = tc
+        .run_in_scope(
+            "mock-query-engine",
+            None,
+            MockQueryEngine::create_with(MockStatusHandle::default(), schemas),
+        )
+        .await;
+
+    let mut tx = engine.partition_store().transaction();
+    let journal_invocation_id = InvocationId::mock_random();
+    for (index, service_name) in ["Greeter", "Unknown"].into_iter().enumerate() {
+        tx.put_journal_entry(
+            &journal_invocation_id,
+            index as u32,
+            JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::invoke(
+                InvokeRequest {
+                    service_name: service_name.into(),
+                    handler_name: "greet".into(),
+                    parameter: Bytes::from_static(b"123"),
+                    key: ByteString::new(),
+                },
+                Some(EntryResult::Success(Bytes::from_static(b"456"))),
+            ))),
+        )
+        .await;
+    }
+    tx.commit().await.unwrap();
+
+    let records = engine
+        .execute("SELECT index, entry_json FROM sys_journal ORDER BY index")
+        .await
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_that!(
+        records,
+        all!(
+            row!(
+                0,
+                {
+                    "index" => UInt32Array: eq(0),
+                    "entry_json" => LargeStringArray: eq(r#"{"handler_name":"greet","key":null,"parameter":{"base64":"MTIz"},"result":{"success":{"base64":"NDU2"}},"service_name":"Greeter"}"#),
+                }
+            ),
+            row!(
+                1,
+                {
+                    "index" => UInt32Array: eq(1),
+                    "entry_json" => LargeStringArray: eq(r#"{"handler_name":"greet","key":null,"parameter":{"json":123},"result":{"success":{"json":456}},"service_name":"Unknown"}"#),
+                }
+            )
+        )
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_awakeable_entries() {
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+    let mut engine = tc
+        .run_in_scope("mock-query-engine", None, MockQueryEngine::create())
+        .await;
+
+    let mut tx = engine.partition_store().transaction();
+    let journal_invocation_id = InvocationId::mock_random();
+    tx.put_journal_entry(
+        &journal_invocation_id,
+        1,
+        JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Awakeable(
+            AwakeableEntry {
+                expiry_time: Some(1337),
+                result: None,
+            },
+        ))),
+    )
+    .await;
+    tx.put_journal_entry(
+        &journal_invocation_id,
+        2,
+        JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Awakeable(
+            AwakeableEntry {
+                expiry_time: Some(1337),
+                result: Some(EntryResult::Failure(
+                    codes::TIMEOUT,
+                    ByteString::from_static("awakeable expired"),
+                )),
+            },
+        ))),
+    )
+    .await;
+    tx.commit().await.unwrap();
+
+    let records = engine
+        .execute(
+            "SELECT awakeable_id, awakeable_expires_at, awakeable_failure_code FROM sys_journal",
+        )
+        .await
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_that!(
+        records,
+        row!(
+            0,
+            {
+                "awakeable_id" => LargeStringArray: eq(AwakeableIdentifier::new(journal_invocation_id, 1).to_string()),
+                "awakeable_expires_at" => Date64Array: eq(1337),
+            }
+        )
+    );
+    assert!(records
+        .column_by_name("awakeable_failure_code")
+        .unwrap()
+        .is_null(0));
+    assert_that!(
+        records,
+        row!(
+            1,
+            {
+                "awakeable_failure_code" => UInt32Array: eq(408),
+            }
+        )
+    );
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn select_count_star() {
     let tc = TaskCenterBuilder::default()
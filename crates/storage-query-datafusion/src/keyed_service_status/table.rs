@@ -10,7 +10,6 @@
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -24,22 +23,18 @@ use crate::context::{QueryContext, SelectPartitions};
 use crate::keyed_service_status::row::append_virtual_object_status_row;
 use crate::keyed_service_status::schema::SysKeyedServiceStatusBuilder;
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let status_table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_keyed_service_status",
         partition_selector,
         SysKeyedServiceStatusBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, VirtualObjectStatusScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_keyed_service_status", Arc::new(status_table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Debug, Clone)]
@@ -56,7 +51,12 @@ impl ScanLocalPartition for VirtualObjectStatusScanner {
         partition_store.all_virtual_object_statuses(range)
     }
 
-    fn append_row(row_builder: &mut Self::Builder, string_buffer: &mut String, value: Self::Item) {
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
         append_virtual_object_status_row(row_builder, string_buffer, value.0, value.1)
     }
 }
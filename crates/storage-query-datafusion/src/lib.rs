@@ -17,18 +17,23 @@ mod invocation_state;
 mod invocation_status;
 mod journal;
 mod keyed_service_status;
+mod outbox;
 mod partition_store_scanner;
 mod physical_optimizer;
 mod promise;
+pub mod remote_query_scanner_manager;
 mod service;
 mod state;
+mod subscription_lag;
 #[cfg(feature = "table_docs")]
 pub mod table_docs;
 mod table_macro;
 mod table_providers;
 mod table_util;
+mod timer;
 
 pub use context::BuildError;
+pub use subscription_lag::ConsumerLagReader;
 
 #[cfg(test)]
 pub(crate) mod mocks;
@@ -30,16 +30,23 @@ use restate_types::invocation::ServiceType;
 use restate_types::live::{Constant, Live};
 use restate_types::schema::deployment::test_util::MockDeploymentMetadataRegistry;
 use restate_types::schema::deployment::{Deployment, DeploymentResolver};
+use restate_types::schema::invocation_target::test_util::MockInvocationTargetResolver;
+use restate_types::schema::invocation_target::{
+    InvocationTargetMetadata, InvocationTargetResolver,
+};
 use restate_types::schema::service::test_util::MockServiceMetadataResolver;
-use restate_types::schema::service::{ServiceMetadata, ServiceMetadataResolver};
+use restate_types::schema::service::{EagerStatePolicy, ServiceMetadata, ServiceMetadataResolver};
+use restate_types::subscription::SubscriptionPartitionLag;
 
 use super::context::QueryContext;
 use crate::context::SelectPartitions;
+use crate::subscription_lag::ConsumerLagReader;
 
 #[derive(Default, Clone, Debug)]
 pub(crate) struct MockSchemas(
     pub(crate) MockServiceMetadataResolver,
     pub(crate) MockDeploymentMetadataRegistry,
+    pub(crate) MockInvocationTargetResolver,
 );
 
 impl ServiceMetadataResolver for MockSchemas {
@@ -51,6 +58,13 @@ impl ServiceMetadataResolver for MockSchemas {
         self.0.resolve_latest_service_type(service_name)
     }
 
+    fn resolve_latest_service_eager_state(
+        &self,
+        service_name: impl AsRef<str>,
+    ) -> Option<EagerStatePolicy> {
+        self.0.resolve_latest_service_eager_state(service_name)
+    }
+
     fn list_services(&self) -> Vec<ServiceMetadata> {
         self.0.list_services()
     }
@@ -80,6 +94,17 @@ impl DeploymentResolver for MockSchemas {
     }
 }
 
+impl InvocationTargetResolver for MockSchemas {
+    fn resolve_latest_invocation_target(
+        &self,
+        service_name: impl AsRef<str>,
+        handler_name: impl AsRef<str>,
+    ) -> Option<InvocationTargetMetadata> {
+        self.2
+            .resolve_latest_invocation_target(service_name, handler_name)
+    }
+}
+
 #[derive(Clone, Debug)]
 struct MockPartitionSelector;
 
@@ -90,6 +115,15 @@ impl SelectPartitions for MockPartitionSelector {
     }
 }
 
+#[derive(Clone, Debug)]
+struct MockConsumerLagReader;
+
+impl ConsumerLagReader for MockConsumerLagReader {
+    fn consumer_lag(&self) -> Vec<SubscriptionPartitionLag> {
+        vec![]
+    }
+}
+
 #[allow(dead_code)]
 pub(crate) struct MockQueryEngine(PartitionStoreManager, PartitionStore, QueryContext);
 
@@ -98,6 +132,7 @@ impl MockQueryEngine {
         status: impl StatusHandle + Send + Sync + Debug + Clone + 'static,
         schemas: impl DeploymentResolver
             + ServiceMetadataResolver
+            + InvocationTargetResolver
             + Send
             + Sync
             + Debug
@@ -135,6 +170,8 @@ impl MockQueryEngine {
                 manager,
                 status,
                 Live::from_value(schemas),
+                MockConsumerLagReader,
+                None,
             )
             .await
             .unwrap(),
@@ -149,6 +186,10 @@ impl MockQueryEngine {
         &mut self.1
     }
 
+    pub fn query_context(&self) -> &QueryContext {
+        &self.2
+    }
+
     pub async fn execute(
         &self,
         sql: &str,
@@ -0,0 +1,66 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::SysOutboxBuilder;
+use crate::table_util::format_using;
+use restate_storage_api::outbox_table::OutboxMessage;
+use restate_types::identifiers::PartitionId;
+
+#[inline]
+pub(crate) fn append_outbox_row(
+    builder: &mut SysOutboxBuilder,
+    output: &mut String,
+    partition_id: PartitionId,
+    sequence_number: u64,
+    outbox_message: OutboxMessage,
+) {
+    let mut row = builder.row();
+
+    row.partition_id(*partition_id);
+    row.sequence_number(sequence_number);
+
+    match outbox_message {
+        OutboxMessage::ServiceInvocation(service_invocation) => {
+            row.kind("service_invocation");
+            if row.is_id_defined() {
+                row.id(format_using(output, &service_invocation.invocation_id));
+            }
+
+            let invocation_target = service_invocation.invocation_target;
+            row.target_service_name(invocation_target.service_name());
+            if let Some(key) = invocation_target.key() {
+                row.target_service_key(key);
+            }
+            row.target_handler_name(invocation_target.handler_name());
+            if row.is_target_defined() {
+                row.target(format_using(output, &invocation_target));
+            }
+        }
+        OutboxMessage::ServiceResponse(invocation_response) => {
+            row.kind("service_response");
+            if row.is_id_defined() {
+                row.id(format_using(output, &invocation_response.id));
+            }
+            row.entry_index(invocation_response.entry_index);
+        }
+        OutboxMessage::InvocationTermination(invocation_termination) => {
+            row.kind("invocation_termination");
+            if row.is_id_defined() {
+                row.id(format_using(output, &invocation_termination.invocation_id));
+            }
+        }
+        OutboxMessage::CallbackResponse(callback_response) => {
+            row.kind("callback_response");
+            if row.is_id_defined() {
+                row.id(format_using(output, &callback_response.invocation_id));
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![allow(dead_code)]
+
+use crate::table_macro::*;
+
+use datafusion::arrow::datatypes::DataType;
+
+define_table!(sys_outbox(
+    /// The partition whose outbox contains the message.
+    partition_id: DataType::UInt64,
+
+    /// Sequence number in the outbox. Messages are delivered in this order.
+    sequence_number: DataType::UInt64,
+
+    /// Either `service_invocation`, `service_response`, `invocation_termination` or
+    /// `callback_response`.
+    kind: DataType::LargeUtf8,
+
+    /// [Invocation ID](/operate/invocation#invocation-identifier) the message is addressed to.
+    /// For `callback_response` messages, this is the completed invocation.
+    id: DataType::LargeUtf8,
+
+    /// Target of the invocation. Format for plain services: `ServiceName/HandlerName`, e.g.
+    /// `Greeter/greet`. Format for virtual objects/workflows: `VirtualObjectName/Key/HandlerName`,
+    /// e.g. `Greeter/Francesco/greet`. Null unless `kind` is `service_invocation`.
+    target: DataType::LargeUtf8,
+
+    /// The name of the invoked service. Null unless `kind` is `service_invocation`.
+    target_service_name: DataType::LargeUtf8,
+
+    /// The key of the virtual object or the workflow ID. Null for regular services, or unless
+    /// `kind` is `service_invocation`.
+    target_service_key: DataType::LargeUtf8,
+
+    /// The invoked handler. Null unless `kind` is `service_invocation`.
+    target_handler_name: DataType::LargeUtf8,
+
+    /// The index of the journal entry completed by the response. Null unless `kind` is
+    /// `service_response`.
+    entry_index: DataType::UInt32,
+));
@@ -0,0 +1,73 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+use futures::{Stream, StreamExt};
+
+use restate_partition_store::{PartitionStore, PartitionStoreManager};
+use restate_storage_api::outbox_table::{OutboxMessage, ReadOnlyOutboxTable};
+use restate_types::identifiers::{PartitionId, PartitionKey};
+
+use crate::context::{QueryContext, SelectPartitions};
+use crate::outbox::row::append_outbox_row;
+use crate::outbox::schema::SysOutboxBuilder;
+use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
+
+pub(crate) fn register_self(
+    ctx: &QueryContext,
+    partition_selector: impl SelectPartitions,
+    partition_store_manager: PartitionStoreManager,
+) -> datafusion::common::Result<()> {
+    ctx.register_partitioned_table(
+        "sys_outbox",
+        partition_selector,
+        SysOutboxBuilder::schema(),
+        LocalPartitionsScanner::new(partition_store_manager, OutboxScanner),
+    )
+}
+
+#[derive(Debug, Clone)]
+struct OutboxScanner;
+
+impl ScanLocalPartition for OutboxScanner {
+    type Builder = SysOutboxBuilder;
+    type Item = (PartitionId, u64, OutboxMessage);
+
+    fn scan_partition_store(
+        partition_store: &PartitionStore,
+        _range: RangeInclusive<PartitionKey>,
+    ) -> impl Stream<Item = restate_storage_api::Result<Self::Item>> + Send {
+        // The outbox is stored by partition id, so the whole partition is scanned
+        let partition_id = partition_store.partition_id();
+        partition_store
+            .all_outbox_messages(partition_id)
+            .map(move |message| {
+                message.map(|(sequence_number, message)| (partition_id, sequence_number, message))
+            })
+    }
+
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
+        let (partition_id, sequence_number, outbox_message) = value;
+        append_outbox_row(
+            row_builder,
+            string_buffer,
+            partition_id,
+            sequence_number,
+            outbox_message,
+        );
+    }
+}
@@ -22,7 +22,7 @@ use restate_types::identifiers::{PartitionId, PartitionKey};
 
 use crate::table_providers::ScanPartition;
 
-pub trait ScanLocalPartition: Send + Sync + Debug + 'static {
+pub trait ScanLocalPartition: Send + Sync + Debug + Clone + 'static {
     type Builder;
     type Item;
 
@@ -31,23 +31,28 @@ pub trait ScanLocalPartition: Send + Sync + Debug + 'static {
         range: RangeInclusive<PartitionKey>,
     ) -> impl Stream<Item = restate_storage_api::Result<Self::Item>> + Send;
 
-    fn append_row(row_builder: &mut Self::Builder, string_buffer: &mut String, value: Self::Item);
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    );
 }
 
 #[derive(Clone, Debug)]
 pub struct LocalPartitionsScanner<S> {
     partition_store_manager: PartitionStoreManager,
-    _marker: std::marker::PhantomData<S>,
+    scanner: S,
 }
 
 impl<S> LocalPartitionsScanner<S>
 where
     S: ScanLocalPartition,
 {
-    pub fn new(partition_store_manager: PartitionStoreManager, _scanner: S) -> Self {
+    pub fn new(partition_store_manager: PartitionStoreManager, scanner: S) -> Self {
         Self {
             partition_store_manager,
-            _marker: std::marker::PhantomData,
+            scanner,
         }
     }
 }
@@ -67,6 +72,7 @@ where
         let mut stream_builder = RecordBatchReceiverStream::builder(projection.clone(), 16);
         let tx = stream_builder.tx();
         let partition_store_manager = self.partition_store_manager.clone();
+        let scanner = self.scanner.clone();
         let background_task = async move {
             let Some(partition_store) = partition_store_manager
                 .get_partition_store(partition_id)
@@ -82,7 +88,7 @@ where
 
             tokio::pin!(rows);
             while let Some(Ok(row)) = rows.next().await {
-                S::append_row(&mut builder, &mut temp, row);
+                scanner.append_row(&mut builder, &mut temp, row);
                 if builder.full() {
                     let batch = builder.finish();
                     if tx.send(batch).await.is_err() {
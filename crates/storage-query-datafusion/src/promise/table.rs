@@ -10,7 +10,6 @@
 
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -22,22 +21,18 @@ use super::row::append_promise_row;
 use super::schema::SysPromiseBuilder;
 use crate::context::{QueryContext, SelectPartitions};
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "sys_promise",
         partition_selector,
         SysPromiseBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, PromiseScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("sys_promise", Arc::new(table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -54,7 +49,12 @@ impl ScanLocalPartition for PromiseScanner {
         partition_store.all_promises(range)
     }
 
-    fn append_row(row_builder: &mut Self::Builder, string_buffer: &mut String, value: Self::Item) {
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
         append_promise_row(row_builder, string_buffer, value);
     }
 }
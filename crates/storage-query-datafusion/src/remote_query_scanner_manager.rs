@@ -0,0 +1,393 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::error::FlightError;
+use arrow_flight::FlightData;
+use async_trait::async_trait;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::DataFusionError;
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+use restate_core::network::grpc_util::create_grpc_channel_from_advertised_address;
+use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
+use restate_core::network::protobuf::node_svc::ScanPartitionRequest;
+use restate_core::worker_api::ProcessorsManagerHandle;
+use restate_core::Metadata;
+use restate_types::cluster::cluster_state::PartitionProcessorStatus;
+use restate_types::errors::GenericError;
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::nodes_config::Role;
+use restate_types::GenerationalNodeId;
+
+use crate::context::SelectPartitions;
+use crate::table_providers::ScanPartition;
+
+/// Time to wait for a node to report the partitions it leads
+const GET_LEADER_PARTITIONS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Node serving the scans of a partition, as seen from this node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionLocation {
+    Local,
+    Remote(GenerationalNodeId),
+}
+
+type PartitionLocations = BTreeMap<PartitionId, PartitionLocation>;
+
+/// Routes the scans of the partitioned tables to the nodes hosting the partition leaders, so
+/// that a query run on any node covers the data of the whole cluster.
+///
+/// The partitions are taken from the partition table. Each partition is scanned on the node
+/// running its leader, which is asked to every worker node when planning a query.
+#[derive(Clone)]
+pub struct RemoteScannerManager {
+    metadata: Metadata,
+    processors_manager: ProcessorsManagerHandle,
+    channels: Arc<Mutex<HashMap<GenerationalNodeId, Channel>>>,
+}
+
+impl RemoteScannerManager {
+    pub fn new(metadata: Metadata, processors_manager: ProcessorsManagerHandle) -> Self {
+        Self {
+            metadata,
+            processors_manager,
+            channels: Default::default(),
+        }
+    }
+
+    /// Creates the partition selector and the scanner of a table. The selector resolves the
+    /// partition locations once per query, and the scanner routes the partitions according to
+    /// the latest resolved locations.
+    pub(crate) fn create_distributed_scanner<T>(
+        &self,
+        table: impl Into<String>,
+        local_scanner: T,
+    ) -> (ClusterPartitionSelector, DistributedPartitionsScanner<T>) {
+        let locations = Arc::new(Mutex::new(Arc::new(PartitionLocations::new())));
+        (
+            ClusterPartitionSelector {
+                manager: self.clone(),
+                locations: Arc::clone(&locations),
+            },
+            DistributedPartitionsScanner {
+                table: table.into(),
+                local_scanner: Arc::new(local_scanner),
+                manager: self.clone(),
+                locations,
+            },
+        )
+    }
+
+    async fn get_partition_locations(&self) -> Result<PartitionLocations, GenericError> {
+        let partition_table = self
+            .metadata
+            .partition_table()
+            .ok_or("the partition table is not available yet")?;
+        let local_state = self.processors_manager.get_state().await?;
+        let remote_leaders = self.get_remote_leaders().await;
+
+        Ok(resolve_partition_locations(
+            partition_table
+                .partitioner()
+                .map(|(partition_id, _)| partition_id),
+            &local_state,
+            &remote_leaders,
+        ))
+    }
+
+    /// Asks the other worker nodes for the partitions they lead. Nodes which don't reply in time
+    /// are skipped.
+    async fn get_remote_leaders(&self) -> BTreeMap<GenerationalNodeId, BTreeSet<PartitionId>> {
+        let my_node_id = self.metadata.my_node_id();
+        let nodes = self
+            .metadata
+            .nodes_config_ref()
+            .iter()
+            .filter(|(node_id, node)| {
+                *node_id != my_node_id.as_plain() && node.has_role(Role::Worker)
+            })
+            .map(|(_, node)| node.current_generation)
+            .collect::<Vec<_>>();
+
+        let requests = nodes.into_iter().map(|node_id| async move {
+            let leader_partitions = async {
+                let mut client = self.node_svc_client(node_id)?;
+                let response = client.get_leader_partitions(()).await?.into_inner();
+                Ok::<_, GenericError>(response.partition_ids)
+            };
+            match tokio::time::timeout(GET_LEADER_PARTITIONS_TIMEOUT, leader_partitions).await {
+                Ok(Ok(partition_ids)) => Some((
+                    node_id,
+                    partition_ids.into_iter().map(PartitionId::from).collect(),
+                )),
+                Ok(Err(err)) => {
+                    debug!("Failed getting the leader partitions of node {node_id}: {err}");
+                    None
+                }
+                Err(_) => {
+                    debug!("Timed out getting the leader partitions of node {node_id}");
+                    None
+                }
+            }
+        });
+
+        futures::future::join_all(requests)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    async fn scan_remote_partition(
+        &self,
+        node_id: GenerationalNodeId,
+        request: ScanPartitionRequest,
+    ) -> Result<BoxStream<'static, datafusion::common::Result<RecordBatch>>, GenericError> {
+        let mut client = self.node_svc_client(node_id)?;
+        let response_stream = client.scan_partition(request).await?.into_inner();
+
+        let record_batches = FlightRecordBatchStream::new_from_flight_data(
+            response_stream
+                .map_ok(|response| FlightData {
+                    data_header: response.header,
+                    data_body: response.data,
+                    ..FlightData::default()
+                })
+                .map_err(FlightError::from),
+        )
+        .map_err(|err| DataFusionError::External(err.into()));
+
+        Ok(record_batches.boxed())
+    }
+
+    fn node_svc_client(
+        &self,
+        node_id: GenerationalNodeId,
+    ) -> Result<NodeSvcClient<Channel>, GenericError> {
+        let mut channels = self.channels.lock().expect("lock not poisoned");
+        if let Some(channel) = channels.get(&node_id) {
+            return Ok(NodeSvcClient::new(channel.clone()));
+        }
+
+        let address = self
+            .metadata
+            .nodes_config_ref()
+            .find_node_by_id(node_id)?
+            .address
+            .clone();
+        let channel = create_grpc_channel_from_advertised_address(address)?;
+        channels.insert(node_id, channel.clone());
+        Ok(NodeSvcClient::new(channel))
+    }
+}
+
+impl Debug for RemoteScannerManager {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteScannerManager")
+            .finish_non_exhaustive()
+    }
+}
+
+/// Locates every partition of the partition table. A partition led by this node is scanned
+/// locally, one led by another node on that node. Partitions without a known leader are scanned
+/// from the local replica if this node runs one, and are skipped otherwise.
+fn resolve_partition_locations(
+    partition_ids: impl IntoIterator<Item = PartitionId>,
+    local_state: &BTreeMap<PartitionId, PartitionProcessorStatus>,
+    remote_leaders: &BTreeMap<GenerationalNodeId, BTreeSet<PartitionId>>,
+) -> PartitionLocations {
+    let mut locations = PartitionLocations::new();
+
+    for partition_id in partition_ids {
+        let local_status = local_state.get(&partition_id);
+        let remote_leader = remote_leaders
+            .iter()
+            .find(|(_, partition_ids)| partition_ids.contains(&partition_id))
+            .map(|(node_id, _)| *node_id);
+
+        let location = match (local_status, remote_leader) {
+            (Some(status), _) if status.is_effective_leader() => PartitionLocation::Local,
+            (_, Some(leader)) => PartitionLocation::Remote(leader),
+            (Some(_), None) => PartitionLocation::Local,
+            (None, None) => {
+                warn!(
+                    "Skipping partition {partition_id} in the query, because its leader is unknown"
+                );
+                continue;
+            }
+        };
+        locations.insert(partition_id, location);
+    }
+
+    locations
+}
+
+/// Selects all the partitions of the cluster whose location is known. The locations are resolved
+/// once per query, and shared with the [`DistributedPartitionsScanner`] of the same table.
+#[derive(Debug, Clone)]
+pub(crate) struct ClusterPartitionSelector {
+    manager: RemoteScannerManager,
+    locations: Arc<Mutex<Arc<PartitionLocations>>>,
+}
+
+#[async_trait]
+impl SelectPartitions for ClusterPartitionSelector {
+    async fn get_live_partitions(&self) -> Result<Vec<PartitionId>, GenericError> {
+        let locations = Arc::new(self.manager.get_partition_locations().await?);
+        let partition_ids = locations.keys().copied().collect();
+        // concurrent queries can replace the locations, which are then just more recent
+        *self.locations.lock().expect("lock not poisoned") = locations;
+        Ok(partition_ids)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DistributedPartitionsScanner<T> {
+    table: String,
+    local_scanner: Arc<T>,
+    manager: RemoteScannerManager,
+    locations: Arc<Mutex<Arc<PartitionLocations>>>,
+}
+
+impl<T> ScanPartition for DistributedPartitionsScanner<T>
+where
+    T: ScanPartition,
+{
+    fn scan_partition(
+        &self,
+        partition_id: PartitionId,
+        range: RangeInclusive<PartitionKey>,
+        projection: SchemaRef,
+    ) -> SendableRecordBatchStream {
+        let mut stream_builder = RecordBatchReceiverStream::builder(projection.clone(), 16);
+        let tx = stream_builder.tx();
+        let table = self.table.clone();
+        let local_scanner = self.local_scanner.clone();
+        let manager = self.manager.clone();
+        let location = self
+            .locations
+            .lock()
+            .expect("lock not poisoned")
+            .get(&partition_id)
+            .copied()
+            // the selector resolved the locations when planning the query
+            .unwrap_or(PartitionLocation::Local);
+        let background_task = async move {
+            let mut batches = match location {
+                PartitionLocation::Local => local_scanner
+                    .scan_partition(partition_id, range, projection)
+                    .boxed(),
+                PartitionLocation::Remote(node_id) => {
+                    let request = ScanPartitionRequest {
+                        table,
+                        partition_id: *partition_id,
+                        range_start: *range.start(),
+                        range_end: *range.end(),
+                        columns: projection
+                            .fields()
+                            .iter()
+                            .map(|field| field.name().clone())
+                            .collect(),
+                    };
+                    manager
+                        .scan_remote_partition(node_id, request)
+                        .await
+                        .map_err(DataFusionError::External)?
+                }
+            };
+
+            while let Some(batch) = batches.next().await {
+                if tx.send(batch).await.is_err() {
+                    // the consumer has hung up on us
+                    return Ok(());
+                }
+            }
+
+            Ok(())
+        };
+        stream_builder.spawn(background_task);
+        stream_builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_types::cluster::cluster_state::RunMode;
+
+    fn status(effective_mode: RunMode) -> PartitionProcessorStatus {
+        let mut status = PartitionProcessorStatus::new(effective_mode);
+        status.effective_mode = Some(effective_mode);
+        status
+    }
+
+    #[test]
+    fn partitions_are_located_at_their_leaders() {
+        let remote_node = GenerationalNodeId::new(2, 1);
+        let local_state = BTreeMap::from([
+            (PartitionId::from(0), status(RunMode::Leader)),
+            (PartitionId::from(1), status(RunMode::Follower)),
+            (PartitionId::from(2), status(RunMode::Follower)),
+        ]);
+        let remote_leaders = BTreeMap::from([(
+            remote_node,
+            BTreeSet::from([PartitionId::from(1), PartitionId::from(3)]),
+        )]);
+
+        let locations = resolve_partition_locations(
+            (0..5).map(PartitionId::from),
+            &local_state,
+            &remote_leaders,
+        );
+
+        assert_eq!(
+            locations,
+            BTreeMap::from([
+                (PartitionId::from(0), PartitionLocation::Local),
+                (PartitionId::from(1), PartitionLocation::Remote(remote_node)),
+                // no leader is known, but the local follower has the data
+                (PartitionId::from(2), PartitionLocation::Local),
+                (PartitionId::from(3), PartitionLocation::Remote(remote_node)),
+            ])
+        );
+    }
+
+    #[test]
+    fn local_leader_takes_precedence() {
+        let local_state = BTreeMap::from([(PartitionId::from(0), status(RunMode::Leader))]);
+        // the other node hasn't noticed yet that it lost the leadership
+        let remote_leaders = BTreeMap::from([(
+            GenerationalNodeId::new(2, 1),
+            BTreeSet::from([PartitionId::from(0)]),
+        )]);
+
+        let locations =
+            resolve_partition_locations([PartitionId::from(0)], &local_state, &remote_leaders);
+
+        assert_eq!(
+            locations,
+            BTreeMap::from([(PartitionId::from(0), PartitionLocation::Local)])
+        );
+    }
+}
@@ -11,7 +11,6 @@
 use bytes::Bytes;
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
-use std::sync::Arc;
 
 use futures::Stream;
 
@@ -23,22 +22,18 @@ use crate::context::{QueryContext, SelectPartitions};
 use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
 use crate::state::row::append_state_row;
 use crate::state::schema::StateBuilder;
-use crate::table_providers::PartitionedTableProvider;
 
 pub(crate) fn register_self(
     ctx: &QueryContext,
     partition_selector: impl SelectPartitions,
     partition_store_manager: PartitionStoreManager,
 ) -> datafusion::common::Result<()> {
-    let table = PartitionedTableProvider::new(
+    ctx.register_partitioned_table(
+        "state",
         partition_selector,
         StateBuilder::schema(),
         LocalPartitionsScanner::new(partition_store_manager, StateScanner),
-    );
-
-    ctx.as_ref()
-        .register_table("state", Arc::new(table))
-        .map(|_| ())
+    )
 }
 
 #[derive(Debug, Clone)]
@@ -55,7 +50,7 @@ impl ScanLocalPartition for StateScanner {
         partition_store.get_all_user_states(range)
     }
 
-    fn append_row(row_builder: &mut Self::Builder, _: &mut String, value: Self::Item) {
+    fn append_row(&self, row_builder: &mut Self::Builder, _: &mut String, value: Self::Item) {
         append_state_row(row_builder, value.0, value.1, value.2);
     }
 }
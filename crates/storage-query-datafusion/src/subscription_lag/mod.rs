@@ -0,0 +1,23 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod row;
+pub(crate) mod schema;
+mod table;
+
+use restate_types::subscription::SubscriptionPartitionLag;
+
+pub(crate) use table::register_self;
+
+/// Provides the lag of the subscription consumers running on this node. The lag of the
+/// consumers running on other nodes is not visible, hence `sys_subscription_lag` is node-local.
+pub trait ConsumerLagReader {
+    fn consumer_lag(&self) -> Vec<SubscriptionPartitionLag>;
+}
@@ -0,0 +1,29 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::SysSubscriptionLagBuilder;
+use crate::table_util::format_using;
+use restate_types::subscription::SubscriptionPartitionLag;
+
+#[inline]
+pub(crate) fn append_subscription_lag_row(
+    builder: &mut SysSubscriptionLagBuilder,
+    output: &mut String,
+    partition_lag: SubscriptionPartitionLag,
+) {
+    let mut row = builder.row();
+    row.subscription_id(format_using(output, &partition_lag.subscription_id));
+    row.topic(&partition_lag.topic);
+    row.partition(partition_lag.partition);
+    row.offset(partition_lag.offset.max(0) as u64);
+    row.high_watermark(partition_lag.high_watermark.max(0) as u64);
+    row.lag(partition_lag.lag());
+    row.updated_at(partition_lag.updated_at.as_u64() as i64);
+}
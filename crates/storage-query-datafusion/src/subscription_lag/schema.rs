@@ -0,0 +1,40 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![allow(dead_code)]
+
+use crate::table_macro::*;
+
+use datafusion::arrow::datatypes::DataType;
+
+// The lag is reported by the Kafka consumers running in this process, hence the table is
+// node-local: it lists only the subscriptions consumed by the node that executes the query.
+define_table!(sys_subscription_lag(
+    /// The ID of the subscription. The table only contains the partitions consumed by the node that runs the query.
+    subscription_id: DataType::LargeUtf8,
+
+    /// The Kafka topic consumed by the subscription.
+    topic: DataType::LargeUtf8,
+
+    /// The partition of the topic.
+    partition: DataType::Int32,
+
+    /// Offset of the last message ingested from the partition.
+    offset: DataType::UInt64,
+
+    /// Offset of the next message that will be appended to the partition, as last reported by the broker.
+    high_watermark: DataType::UInt64,
+
+    /// Number of messages appended to the partition that are not yet ingested.
+    lag: DataType::UInt64,
+
+    /// Timestamp of the last update of the lag of this partition.
+    updated_at: DataType::Date64,
+));
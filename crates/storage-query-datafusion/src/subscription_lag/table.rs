@@ -0,0 +1,94 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fmt;
+use std::sync::Arc;
+
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use tokio::sync::mpsc::Sender;
+
+use restate_types::subscription::SubscriptionPartitionLag;
+
+use super::row::append_subscription_lag_row;
+use super::schema::SysSubscriptionLagBuilder;
+use super::ConsumerLagReader;
+use crate::context::QueryContext;
+use crate::table_providers::{GenericTableProvider, Scan};
+use crate::table_util::Builder;
+
+pub(crate) fn register_self(
+    ctx: &QueryContext,
+    reader: impl ConsumerLagReader + Send + Sync + 'static,
+) -> datafusion::common::Result<()> {
+    let subscription_lag_table = GenericTableProvider::new(
+        SysSubscriptionLagBuilder::schema(),
+        Arc::new(ConsumerLagScanner(reader)),
+    );
+
+    ctx.as_ref()
+        .register_table("sys_subscription_lag", Arc::new(subscription_lag_table))
+        .map(|_| ())
+}
+
+struct ConsumerLagScanner<R>(R);
+
+impl<R> fmt::Debug for ConsumerLagScanner<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConsumerLagScanner")
+    }
+}
+
+impl<R: ConsumerLagReader + Send + Sync + 'static> Scan for ConsumerLagScanner<R> {
+    fn scan(
+        &self,
+        projection: SchemaRef,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> SendableRecordBatchStream {
+        let schema = projection.clone();
+        let mut stream_builder = RecordBatchReceiverStream::builder(projection, 16);
+        let tx = stream_builder.tx();
+
+        let rows = self.0.consumer_lag();
+        stream_builder.spawn(async move {
+            for_each_partition_lag(schema, tx, rows).await;
+            Ok(())
+        });
+        stream_builder.build()
+    }
+}
+
+async fn for_each_partition_lag(
+    schema: SchemaRef,
+    tx: Sender<datafusion::common::Result<RecordBatch>>,
+    rows: Vec<SubscriptionPartitionLag>,
+) {
+    let mut builder = SysSubscriptionLagBuilder::new(schema.clone());
+    let mut temp = String::new();
+    for partition_lag in rows {
+        append_subscription_lag_row(&mut builder, &mut temp, partition_lag);
+        if builder.full() {
+            let batch = builder.finish();
+            if tx.send(batch).await.is_err() {
+                // the other side has hung up on us
+                return;
+            }
+            builder = SysSubscriptionLagBuilder::new(schema.clone());
+        }
+    }
+    if !builder.empty() {
+        let result = builder.finish();
+        let _ = tx.send(result).await;
+    }
+}
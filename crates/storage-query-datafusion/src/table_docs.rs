@@ -10,7 +10,7 @@
 
 use crate::{
     deployment, idempotency, inbox, invocation_state, invocation_status, journal,
-    keyed_service_status, promise, service, state,
+    keyed_service_status, outbox, promise, service, state, subscription_lag, timer,
 };
 use std::borrow::Cow;
 
@@ -24,8 +24,11 @@ pub const ALL_TABLE_DOCS: &[StaticTableDocs] = &[
     inbox::schema::TABLE_DOCS,
     idempotency::schema::TABLE_DOCS,
     promise::schema::TABLE_DOCS,
+    timer::schema::TABLE_DOCS,
+    outbox::schema::TABLE_DOCS,
     service::schema::TABLE_DOCS,
     deployment::schema::TABLE_DOCS,
+    subscription_lag::schema::TABLE_DOCS,
 ];
 
 pub trait TableDocs {
@@ -129,3 +132,52 @@ pub fn sys_invocation_table_docs() -> OwnedTableDocs {
         columns,
     }
 }
+
+pub fn sys_awakeable_table_docs() -> OwnedTableDocs {
+    // We need to compile this manually, due to the fact that it's a view.
+    use std::collections::HashMap;
+    let mut sys_journal: HashMap<&'static str, TableColumn> = journal::schema::TABLE_DOCS
+        .columns
+        .iter()
+        .map(|column| (column.name, *column))
+        .collect();
+
+    let columns = vec![
+        TableColumn {
+            name: "id",
+            column_type: "Utf8",
+            description: "ID of the awakeable, to use to resolve or reject it.",
+        },
+        TableColumn {
+            name: "invocation_id",
+            column_type: "Utf8",
+            description: "[Invocation ID](/operate/invocation#invocation-identifier) of the invocation which created the awakeable.",
+        },
+        TableColumn {
+            name: "invocation_target",
+            column_type: "Utf8",
+            description: "Invocation Target of the invocation which created the awakeable. Format for plain services: `ServiceName/HandlerName`, e.g. `Greeter/greet`. Format for virtual objects/workflows: `VirtualObjectName/Key/HandlerName`, e.g. `Greeter/Francesco/greet`.",
+        },
+        TableColumn {
+            name: "entry_index",
+            column_type: "UInt32",
+            description: "The index of the awakeable entry in the journal of the invocation.",
+        },
+        sys_journal.remove("name").expect("name should exist"),
+        TableColumn {
+            name: "expires_at",
+            column_type: "Date64",
+            description: "When the awakeable fails unless completed before, if it was created with an expiry.",
+        },
+        TableColumn {
+            name: "status",
+            column_type: "Utf8",
+            description: "Either `pending` or `resolved` or `rejected` or `expired`.",
+        },
+    ];
+
+    OwnedTableDocs {
+        name: Cow::Borrowed("sys_awakeable"),
+        columns,
+    }
+}
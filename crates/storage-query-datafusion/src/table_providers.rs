@@ -21,13 +21,14 @@ use datafusion::execution::context::{SessionState, TaskContext};
 use datafusion::logical_expr::{Expr, TableProviderFilterPushDown};
 use datafusion::physical_expr::PhysicalSortExpr;
 use datafusion::physical_plan::{
-    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream,
+    DisplayAs, DisplayFormatType, EmptyRecordBatchStream, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream,
 };
 
 use restate_types::identifiers::{PartitionId, PartitionKey};
 
 use crate::context::SelectPartitions;
-use crate::table_util::compute_ordering;
+use crate::table_util::{compute_ordering, partition_key_range};
 
 pub(crate) trait ScanPartition: Send + Sync + Debug + 'static {
     fn scan_partition(
@@ -76,7 +77,7 @@ where
         &self,
         _state: &SessionState,
         projection: Option<&Vec<usize>>,
-        _filters: &[Expr],
+        filters: &[Expr],
         _limit: Option<usize>,
     ) -> datafusion::common::Result<Arc<dyn ExecutionPlan>> {
         let projected_schema = match projection {
@@ -91,6 +92,7 @@ where
 
         Ok(Arc::new(PartitionedExecutionPlan {
             live_partitions,
+            range: partition_key_range(filters),
             output_ordering: compute_ordering(projected_schema.clone()),
             projected_schema,
             scanner: self.partition_scanner.clone(),
@@ -113,6 +115,8 @@ where
 #[derive(Debug, Clone)]
 struct PartitionedExecutionPlan<T> {
     live_partitions: Vec<PartitionId>,
+    /// Partition key range derived from the pushed down filters, `None` if it is empty.
+    range: Option<RangeInclusive<PartitionKey>>,
     output_ordering: Option<Vec<PhysicalSortExpr>>,
     projected_schema: SchemaRef,
     scanner: T,
@@ -160,8 +164,11 @@ where
         partition: usize,
         _context: Arc<TaskContext>,
     ) -> datafusion::common::Result<SendableRecordBatchStream> {
-        // fake range until we can handle filters
-        let range = 0..=PartitionKey::MAX;
+        let Some(range) = self.range.clone() else {
+            return Ok(Box::pin(EmptyRecordBatchStream::new(
+                self.projected_schema.clone(),
+            )));
+        };
         // map df partitions to our partition ids by index.
         let partition_id = self
             .live_partitions
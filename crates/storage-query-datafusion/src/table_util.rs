@@ -10,9 +10,14 @@
 
 use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::ScalarValue;
+use datafusion::logical_expr::expr::{Between, BinaryExpr, InList};
+use datafusion::logical_expr::{Expr, Operator};
 use datafusion::physical_expr::expressions::col;
 use datafusion::physical_expr::PhysicalSortExpr;
+use restate_types::identifiers::PartitionKey;
 use std::fmt::Write;
+use std::ops::RangeInclusive;
 
 pub(crate) fn compute_ordering(schema: SchemaRef) -> Option<Vec<PhysicalSortExpr>> {
     let ordering = vec![PhysicalSortExpr {
@@ -23,6 +28,110 @@ pub(crate) fn compute_ordering(schema: SchemaRef) -> Option<Vec<PhysicalSortExpr
     Some(ordering)
 }
 
+/// Narrows the scanned partition key range using the `partition_key` predicates found in the
+/// given filters. Predicates that can't be interpreted are ignored, since the filters are
+/// re-applied on the scanned rows anyway. Returns `None` if no key can satisfy the filters.
+pub(crate) fn partition_key_range(filters: &[Expr]) -> Option<RangeInclusive<PartitionKey>> {
+    filters
+        .iter()
+        .try_fold(PartitionKey::MIN..=PartitionKey::MAX, |range, filter| {
+            intersect(range, predicate_range(filter)?)
+        })
+}
+
+/// Returns the range of the partition keys which can satisfy the predicate, or `None` if no key
+/// can.
+fn predicate_range(expr: &Expr) -> Option<RangeInclusive<PartitionKey>> {
+    let all_keys = PartitionKey::MIN..=PartitionKey::MAX;
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => intersect(predicate_range(left)?, predicate_range(right)?),
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::Or,
+            right,
+        }) => match (predicate_range(left), predicate_range(right)) {
+            (Some(left), Some(right)) => {
+                Some(*left.start().min(right.start())..=*left.end().max(right.end()))
+            }
+            (left, right) => left.or(right),
+        },
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            let (op, key) = match (
+                is_partition_key_column(left),
+                partition_key_literal(right),
+                is_partition_key_column(right),
+                partition_key_literal(left),
+            ) {
+                (true, Some(key), _, _) => (*op, key),
+                (_, _, true, Some(key)) => match op.swap() {
+                    Some(op) => (op, key),
+                    None => return Some(all_keys),
+                },
+                _ => return Some(all_keys),
+            };
+            match op {
+                Operator::Eq => Some(key..=key),
+                Operator::Gt => Some(key.checked_add(1)?..=PartitionKey::MAX),
+                Operator::GtEq => Some(key..=PartitionKey::MAX),
+                Operator::Lt => Some(PartitionKey::MIN..=key.checked_sub(1)?),
+                Operator::LtEq => Some(PartitionKey::MIN..=key),
+                _ => Some(all_keys),
+            }
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) if is_partition_key_column(expr) => {
+            match (partition_key_literal(low), partition_key_literal(high)) {
+                (Some(low), Some(high)) => (low <= high).then_some(low..=high),
+                _ => Some(all_keys),
+            }
+        }
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) if is_partition_key_column(expr) => {
+            match list
+                .iter()
+                .map(partition_key_literal)
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(keys) => Some(*keys.iter().min()?..=*keys.iter().max()?),
+                None => Some(all_keys),
+            }
+        }
+        _ => Some(all_keys),
+    }
+}
+
+fn intersect(
+    a: RangeInclusive<PartitionKey>,
+    b: RangeInclusive<PartitionKey>,
+) -> Option<RangeInclusive<PartitionKey>> {
+    let start = *a.start().max(b.start());
+    let end = *a.end().min(b.end());
+    (start <= end).then_some(start..=end)
+}
+
+fn is_partition_key_column(expr: &Expr) -> bool {
+    matches!(expr, Expr::Column(column) if column.name == "partition_key")
+}
+
+fn partition_key_literal(expr: &Expr) -> Option<PartitionKey> {
+    match expr {
+        Expr::Literal(ScalarValue::UInt64(Some(value))) => Some(*value),
+        Expr::Literal(ScalarValue::Int64(Some(value))) => PartitionKey::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
 #[inline]
 pub(crate) fn format_using<'a>(output: &'a mut String, what: &impl std::fmt::Display) -> &'a str {
     output.clear();
@@ -39,3 +148,121 @@ pub(crate) trait Builder {
 
     fn finish(self) -> datafusion::common::Result<RecordBatch>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use datafusion::logical_expr::{col, lit};
+
+    fn range(filters: &[Expr]) -> Option<RangeInclusive<PartitionKey>> {
+        partition_key_range(filters)
+    }
+
+    #[test]
+    fn no_partition_key_predicate_scans_all_keys() {
+        assert_eq!(
+            range(&[col("service_name").eq(lit("greeter"))]),
+            Some(PartitionKey::MIN..=PartitionKey::MAX)
+        );
+        assert_eq!(range(&[]), Some(PartitionKey::MIN..=PartitionKey::MAX));
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(range(&[col("partition_key").eq(lit(42u64))]), Some(42..=42));
+        assert_eq!(range(&[lit(42u64).eq(col("partition_key"))]), Some(42..=42));
+        assert_eq!(
+            range(&[
+                col("partition_key").eq(lit(42u64)),
+                col("partition_key").eq(lit(43u64))
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn in_list() {
+        assert_eq!(
+            range(&[col("partition_key").in_list(vec![lit(7u64), lit(3u64), lit(5u64)], false)]),
+            Some(3..=7)
+        );
+        assert_eq!(range(&[col("partition_key").in_list(vec![], false)]), None);
+        // Negated lists can't narrow the range
+        assert_eq!(
+            range(&[col("partition_key").in_list(vec![lit(3u64)], true)]),
+            Some(PartitionKey::MIN..=PartitionKey::MAX)
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(
+            range(&[
+                col("partition_key").gt(lit(10u64)),
+                col("partition_key").lt_eq(lit(20u64))
+            ]),
+            Some(11..=20)
+        );
+        assert_eq!(
+            range(&[col("partition_key")
+                .gt_eq(lit(10u64))
+                .and(col("partition_key").lt(lit(20u64)))]),
+            Some(10..=19)
+        );
+        // Swapped operands
+        assert_eq!(
+            range(&[lit(10u64).lt(col("partition_key"))]),
+            Some(11..=PartitionKey::MAX)
+        );
+        assert_eq!(
+            range(&[col("partition_key").between(lit(5i64), lit(8i64))]),
+            Some(5..=8)
+        );
+        assert_eq!(range(&[col("partition_key").lt(lit(0u64))]), None);
+        assert_eq!(
+            range(&[col("partition_key").gt(lit(PartitionKey::MAX))]),
+            None
+        );
+        assert_eq!(
+            range(&[
+                col("partition_key").gt(lit(20u64)),
+                col("partition_key").lt(lit(10u64))
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn disjunction() {
+        assert_eq!(
+            range(&[col("partition_key")
+                .eq(lit(3u64))
+                .or(col("partition_key").eq(lit(9u64)))]),
+            Some(3..=9)
+        );
+        // A side without partition key predicate can match any key
+        assert_eq!(
+            range(&[col("partition_key")
+                .eq(lit(3u64))
+                .or(col("service_name").eq(lit("greeter")))]),
+            Some(PartitionKey::MIN..=PartitionKey::MAX)
+        );
+        // A side which can't match any key is ignored
+        assert_eq!(
+            range(&[col("partition_key")
+                .lt(lit(0u64))
+                .or(col("partition_key").eq(lit(9u64)))]),
+            Some(9..=9)
+        );
+        assert_eq!(
+            range(&[
+                col("partition_key").gt(lit(5u64)),
+                col("partition_key")
+                    .eq(lit(3u64))
+                    .or(col("partition_key").eq(lit(9u64)))
+            ]),
+            Some(6..=9)
+        );
+    }
+}
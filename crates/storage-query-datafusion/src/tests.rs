@@ -18,17 +18,21 @@ use googletest::prelude::{assert_that, eq};
 use restate_core::TaskCenterBuilder;
 use restate_invoker_api::status_handle::test_util::MockStatusHandle;
 use restate_invoker_api::status_handle::InvocationStatusReportInner;
-use restate_invoker_api::{InvocationErrorReport, InvocationStatusReport};
+use restate_invoker_api::{
+    CircuitBreakerState, DeploymentHealthReport, InvocationErrorReport, InvocationStatusReport,
+};
 use restate_storage_api::invocation_status_table::{
     InFlightInvocationMetadata, InvocationStatus, InvocationStatusTable,
 };
+use restate_storage_api::state_table::StateTable;
 use restate_storage_api::Transaction;
 use restate_types::errors::InvocationError;
 use restate_types::identifiers::LeaderEpoch;
 use restate_types::identifiers::PartitionId;
-use restate_types::identifiers::{DeploymentId, InvocationId};
+use restate_types::identifiers::{DeploymentId, InvocationId, PartitionKey, ServiceId};
 use restate_types::invocation::InvocationTarget;
 use restate_types::journal::EntryType;
+use restate_types::schema::deployment::Deployment;
 use std::time::{Duration, SystemTime};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -120,3 +124,126 @@ async fn query_sys_invocation() {
         ))
     );
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn scan_local_partition() {
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+    let mut engine = tc
+        .run_in_scope("mock-query-engine", None, MockQueryEngine::create())
+        .await;
+
+    let mut tx = engine.partition_store().transaction();
+    tx.put_user_state(&ServiceId::new("MySvc", "my-key"), b"my-state", b"my-value")
+        .await;
+    tx.commit().await.unwrap();
+
+    let records = engine
+        .query_context()
+        .scan_local_partition(
+            "state",
+            PartitionId::MIN,
+            PartitionKey::MIN..=PartitionKey::MAX,
+            &["service_key".to_owned(), "key".to_owned()],
+        )
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_eq!(records.num_columns(), 2);
+    assert_that!(
+        records,
+        all!(row!(
+            0,
+            {
+                "service_key" => LargeStringArray: eq("my-key"),
+                "key" => LargeStringArray: eq("my-state"),
+            }
+        ))
+    );
+
+    assert!(engine
+        .query_context()
+        .scan_local_partition(
+            "unknown",
+            PartitionId::MIN,
+            PartitionKey::MIN..=PartitionKey::MAX,
+            &[]
+        )
+        .is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn query_sys_deployment_circuit_breaker() {
+    let healthy_deployment = Deployment::mock_with_uri("http://localhost:9080");
+    let unreachable_deployment = Deployment::mock_with_uri("http://localhost:9081");
+
+    let mut schemas = MockSchemas::default();
+    schemas
+        .1
+        .mock_service_with_metadata("Healthy", healthy_deployment.clone());
+    schemas
+        .1
+        .mock_service_with_metadata("Unreachable", unreachable_deployment.clone());
+
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+    let engine = tc
+        .run_in_scope(
+            "mock-query-engine",
+            None,
+            MockQueryEngine::create_with(
+                MockStatusHandle::default().with_deployment_health(DeploymentHealthReport {
+                    deployment_id: unreachable_deployment.id,
+                    circuit_breaker_state: CircuitBreakerState::Open,
+                    consecutive_failures: 5,
+                    held_invocations: 3,
+                    opened_at: Some(SystemTime::now()),
+                }),
+                schemas,
+            ),
+        )
+        .await;
+
+    let records = engine
+        .execute(&format!(
+            "SELECT id, circuit_breaker_state, held_invocations
+            FROM sys_deployment
+            ORDER BY id = '{}'",
+            unreachable_deployment.id
+        ))
+        .await
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_that!(
+        records,
+        all!(
+            row!(
+                0,
+                {
+                    "id" => LargeStringArray: eq(healthy_deployment.id.to_string()),
+                    "circuit_breaker_state" => LargeStringArray: eq("closed"),
+                    "held_invocations" => UInt64Array: eq(0),
+                }
+            ),
+            row!(
+                1,
+                {
+                    "id" => LargeStringArray: eq(unreachable_deployment.id.to_string()),
+                    "circuit_breaker_state" => LargeStringArray: eq("open"),
+                    "held_invocations" => UInt64Array: eq(3),
+                }
+            )
+        )
+    );
+}
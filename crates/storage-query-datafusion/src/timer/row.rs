@@ -0,0 +1,55 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::schema::SysTimerBuilder;
+use crate::table_util::format_using;
+use restate_storage_api::timer_table::{Timer, TimerKey};
+use restate_types::identifiers::PartitionId;
+
+#[inline]
+pub(crate) fn append_timer_row(
+    builder: &mut SysTimerBuilder,
+    output: &mut String,
+    partition_id: PartitionId,
+    timer_key: TimerKey,
+    timer: Timer,
+) {
+    let mut row = builder.row();
+
+    row.partition_id(*partition_id);
+    row.fire_at(timer_key.timestamp as i64);
+
+    if row.is_id_defined() {
+        row.id(format_using(output, &timer.invocation_id()));
+    }
+
+    match timer {
+        Timer::Invoke(service_invocation) => {
+            row.kind("invoke");
+
+            let invocation_target = service_invocation.invocation_target;
+            row.target_service_name(invocation_target.service_name());
+            if let Some(key) = invocation_target.key() {
+                row.target_service_key(key);
+            }
+            row.target_handler_name(invocation_target.handler_name());
+            if row.is_target_defined() {
+                row.target(format_using(output, &invocation_target));
+            }
+        }
+        Timer::CompleteJournalEntry(_, journal_index) => {
+            row.kind("complete_journal_entry");
+            row.journal_index(journal_index);
+        }
+        Timer::CleanInvocationStatus(_) => {
+            row.kind("clean_invocation_status");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+#![allow(dead_code)]
+
+use crate::table_macro::*;
+
+use datafusion::arrow::datatypes::DataType;
+
+define_table!(sys_timer(
+    /// The partition storing the timer.
+    partition_id: DataType::UInt64,
+
+    /// Timestamp indicating when the timer fires.
+    fire_at: DataType::Date64,
+
+    /// Either `invoke` for delayed invocations, `complete_journal_entry` for sleeps and other
+    /// timed journal entries, or `clean_invocation_status` for the cleanup of completed
+    /// invocations once their retention expires.
+    kind: DataType::LargeUtf8,
+
+    /// [Invocation ID](/operate/invocation#invocation-identifier) of the invocation the timer
+    /// belongs to.
+    id: DataType::LargeUtf8,
+
+    /// The index of the journal entry completed by the timer. Null unless `kind` is
+    /// `complete_journal_entry`.
+    journal_index: DataType::UInt32,
+
+    /// Target of the delayed invocation. Format for plain services: `ServiceName/HandlerName`,
+    /// e.g. `Greeter/greet`. Format for virtual objects/workflows:
+    /// `VirtualObjectName/Key/HandlerName`, e.g. `Greeter/Francesco/greet`. Null unless `kind`
+    /// is `invoke`.
+    target: DataType::LargeUtf8,
+
+    /// The name of the service targeted by the delayed invocation. Null unless `kind` is
+    /// `invoke`.
+    target_service_name: DataType::LargeUtf8,
+
+    /// The key of the virtual object or the workflow ID targeted by the delayed invocation.
+    /// Null for regular services, or unless `kind` is `invoke`.
+    target_service_key: DataType::LargeUtf8,
+
+    /// The handler targeted by the delayed invocation. Null unless `kind` is `invoke`.
+    target_handler_name: DataType::LargeUtf8,
+));
@@ -0,0 +1,65 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+use futures::{Stream, StreamExt};
+
+use restate_partition_store::{PartitionStore, PartitionStoreManager};
+use restate_storage_api::timer_table::{ReadOnlyTimerTable, Timer, TimerKey};
+use restate_types::identifiers::{PartitionId, PartitionKey};
+
+use crate::context::{QueryContext, SelectPartitions};
+use crate::partition_store_scanner::{LocalPartitionsScanner, ScanLocalPartition};
+use crate::timer::row::append_timer_row;
+use crate::timer::schema::SysTimerBuilder;
+
+pub(crate) fn register_self(
+    ctx: &QueryContext,
+    partition_selector: impl SelectPartitions,
+    partition_store_manager: PartitionStoreManager,
+) -> datafusion::common::Result<()> {
+    ctx.register_partitioned_table(
+        "sys_timer",
+        partition_selector,
+        SysTimerBuilder::schema(),
+        LocalPartitionsScanner::new(partition_store_manager, TimerScanner),
+    )
+}
+
+#[derive(Debug, Clone)]
+struct TimerScanner;
+
+impl ScanLocalPartition for TimerScanner {
+    type Builder = SysTimerBuilder;
+    type Item = (PartitionId, TimerKey, Timer);
+
+    fn scan_partition_store(
+        partition_store: &PartitionStore,
+        _range: RangeInclusive<PartitionKey>,
+    ) -> impl Stream<Item = restate_storage_api::Result<Self::Item>> + Send {
+        // Timers are stored by partition id, so the whole partition is scanned
+        let partition_id = partition_store.partition_id();
+        partition_store
+            .all_timers(partition_id)
+            .map(move |timer| timer.map(|(timer_key, timer)| (partition_id, timer_key, timer)))
+    }
+
+    fn append_row(
+        &self,
+        row_builder: &mut Self::Builder,
+        string_buffer: &mut String,
+        value: Self::Item,
+    ) {
+        let (partition_id, timer_key, timer) = value;
+        append_timer_row(row_builder, string_buffer, partition_id, timer_key, timer);
+    }
+}
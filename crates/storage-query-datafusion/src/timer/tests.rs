@@ -0,0 +1,71 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::mocks::*;
+use crate::row;
+use datafusion::arrow::array::{LargeStringArray, UInt32Array};
+use datafusion::arrow::record_batch::RecordBatch;
+use futures::StreamExt;
+use googletest::all;
+use googletest::prelude::{assert_that, eq};
+use restate_core::TaskCenterBuilder;
+use restate_storage_api::timer_table::{Timer, TimerTable};
+use restate_storage_api::Transaction;
+use restate_types::identifiers::{InvocationId, PartitionId};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_timers() {
+    let tc = TaskCenterBuilder::default()
+        .default_runtime_handle(tokio::runtime::Handle::current())
+        .build()
+        .expect("task_center builds");
+    let mut engine = tc
+        .run_in_scope("mock-query-engine", None, MockQueryEngine::create())
+        .await;
+
+    let mut tx = engine.partition_store().transaction();
+    let invocation_id_1 = InvocationId::mock_random();
+    let (timer_key, timer) = Timer::complete_journal_entry(1_000, invocation_id_1, 3);
+    tx.add_timer(PartitionId::MIN, &timer_key, timer).await;
+    let invocation_id_2 = InvocationId::mock_random();
+    let (timer_key, timer) = Timer::clean_invocation_status(2_000, invocation_id_2);
+    tx.add_timer(PartitionId::MIN, &timer_key, timer).await;
+    tx.commit().await.unwrap();
+
+    let records = engine
+        .execute("SELECT * FROM sys_timer ORDER BY fire_at")
+        .await
+        .unwrap()
+        .collect::<Vec<Result<RecordBatch, _>>>()
+        .await
+        .remove(0)
+        .unwrap();
+
+    assert_that!(
+        records,
+        all!(
+            row!(
+                0,
+                {
+                    "id" => LargeStringArray: eq(invocation_id_1.to_string()),
+                    "kind" => LargeStringArray: eq("complete_journal_entry"),
+                    "journal_index" => UInt32Array: eq(3),
+                }
+            ),
+            row!(
+                1,
+                {
+                    "id" => LargeStringArray: eq(invocation_id_2.to_string()),
+                    "kind" => LargeStringArray: eq("clean_invocation_status"),
+                }
+            )
+        )
+    );
+}
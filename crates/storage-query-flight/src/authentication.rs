@@ -0,0 +1,140 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use restate_types::jwt::{constant_time_eq, AdminAuthenticator};
+
+/// Session of an authenticated client, identified by its bearer token. The prepared statements
+/// are accessible only within the session which created them.
+#[derive(Clone)]
+pub(crate) struct Session(Arc<str>);
+
+impl Session {
+    pub(crate) fn from_token(token: &str) -> Self {
+        Self(token.into())
+    }
+
+    pub(crate) fn from_request<T>(request: &Request<T>) -> Option<&Session> {
+        request.extensions().get::<Session>()
+    }
+}
+
+impl PartialEq for Session {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(self.0.as_bytes(), other.0.as_bytes())
+    }
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Session(..)")
+    }
+}
+
+/// Authenticates every request with the bearer tokens of the Admin API, if the admin
+/// authentication is enabled. Since the queries are read only, every role is allowed, like for
+/// the query endpoint of the Admin API.
+#[derive(Clone)]
+pub(crate) struct AuthenticationInterceptor {
+    authenticator: Option<Arc<AdminAuthenticator>>,
+}
+
+impl AuthenticationInterceptor {
+    pub(crate) fn new(authenticator: Option<AdminAuthenticator>) -> Self {
+        Self {
+            authenticator: authenticator.map(Arc::new),
+        }
+    }
+}
+
+impl Interceptor for AuthenticationInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(authenticator) = &self.authenticator else {
+            return Ok(request);
+        };
+
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+
+        if authenticator.authenticate(token).is_none() {
+            return Err(Status::unauthenticated("invalid bearer token"));
+        }
+
+        let session = Session::from_token(token);
+        request.extensions_mut().insert(session);
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_types::config::{AdminAuthenticationOptions, AdminRole, AdminToken};
+
+    fn request_with(authorization: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(authorization) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn requires_admin_bearer_token() {
+        let mut interceptor = AuthenticationInterceptor::new(Some(
+            AdminAuthenticator::from_options(&AdminAuthenticationOptions {
+                tokens: vec![AdminToken {
+                    name: "dashboards".to_owned(),
+                    token: "secret".to_owned(),
+                    role: AdminRole::Viewer,
+                }],
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+
+        let request = interceptor
+            .call(request_with(Some("Bearer secret")))
+            .unwrap();
+        assert_eq!(
+            Session::from_request(&request),
+            Some(&Session::from_token("secret"))
+        );
+        assert_eq!(
+            interceptor.call(request_with(None)).unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            interceptor
+                .call(request_with(Some("Bearer wrong")))
+                .unwrap_err()
+                .code(),
+            tonic::Code::Unauthenticated
+        );
+
+        // without admin authentication, requests have no session
+        let request = AuthenticationInterceptor::new(None)
+            .call(request_with(None))
+            .unwrap();
+        assert_eq!(Session::from_request(&request), None);
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::sql::metadata::{SqlInfoData, SqlInfoDataBuilder};
+use arrow_flight::sql::server::{FlightSqlService, PeekableFlightDataStream};
+use arrow_flight::sql::{
+    ActionClosePreparedStatementRequest, ActionCreatePreparedStatementRequest,
+    ActionCreatePreparedStatementResult, CommandGetCatalogs, CommandGetDbSchemas,
+    CommandGetSqlInfo, CommandGetTableTypes, CommandGetTables, CommandPreparedStatementQuery,
+    CommandStatementQuery, ProstMessageExt, SqlInfo, TicketStatementQuery,
+};
+use arrow_flight::{
+    Action, FlightDescriptor, FlightEndpoint, FlightInfo, HandshakeRequest, HandshakeResponse,
+    IpcMessage, PutResult, SchemaAsIpc, Ticket,
+};
+use bytes::Bytes;
+use datafusion::arrow::array::{ArrayRef, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::ipc::writer::IpcWriteOptions;
+use datafusion::arrow::ipc::MetadataVersion;
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::DataFusionError;
+use datafusion::datasource::TableType;
+use datafusion::logical_expr::LogicalPlan;
+use futures::{stream, Stream, TryStreamExt};
+use prost::Message;
+use tonic::{Request, Response, Status, Streaming};
+
+use restate_storage_query_datafusion::context::QueryContext;
+
+use crate::authentication::Session;
+use crate::prepared_statements::{PreparedStatement, PreparedStatements};
+
+type DoGetStream = <FlightSqlServer as FlightService>::DoGetStream;
+type DoPutStream = <FlightSqlServer as FlightService>::DoPutStream;
+
+const TABLE_TYPES: [&str; 2] = ["TABLE", "VIEW"];
+
+/// Version of the Arrow IPC format used to encode schemas and results.
+const IPC_METADATA_VERSION: MetadataVersion = MetadataVersion::V5;
+
+/// Serves the storage query engine to Flight SQL clients. The handle of an ad-hoc statement is
+/// its SQL text, while prepared statements are kept by the server until the client closes them,
+/// together with the parameters bound to them. The requests are authenticated by the
+/// [`AuthenticationInterceptor`].
+///
+/// [`AuthenticationInterceptor`]: crate::authentication::AuthenticationInterceptor
+pub(crate) struct FlightSqlServer {
+    query_context: QueryContext,
+    prepared_statements: PreparedStatements,
+    sql_info: Arc<SqlInfoData>,
+}
+
+impl FlightSqlServer {
+    pub(crate) fn new(query_context: QueryContext) -> Self {
+        Self {
+            query_context,
+            prepared_statements: PreparedStatements::default(),
+            sql_info: Arc::new(sql_info()),
+        }
+    }
+
+    async fn plan(&self, sql: &str) -> Result<LogicalPlan, Status> {
+        self.query_context
+            .create_logical_plan(sql)
+            .await
+            .map_err(|err| Status::invalid_argument(format!("failed planning the query: {err}")))
+    }
+
+    async fn execute(&self, plan: LogicalPlan) -> Result<Response<DoGetStream>, Status> {
+        let record_stream = self
+            .query_context
+            .execute_logical_plan(plan)
+            .await
+            .map_err(datafusion_error_to_status)?;
+
+        Ok(do_get_response(
+            record_stream.schema(),
+            record_stream.map_err(|err| FlightError::from(ArrowError::from(err))),
+        ))
+    }
+}
+
+#[tonic::async_trait]
+impl FlightSqlService for FlightSqlServer {
+    type FlightService = FlightSqlServer;
+
+    async fn do_handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<
+        Response<Pin<Box<dyn Stream<Item = Result<HandshakeResponse, Status>> + Send>>>,
+        Status,
+    > {
+        // every request is authenticated with its bearer token, so the handshake issues no token
+        let response = HandshakeResponse {
+            protocol_version: 0,
+            payload: Bytes::new(),
+        };
+        Ok(Response::new(Box::pin(stream::once(async {
+            Ok(response)
+        }))))
+    }
+
+    async fn get_flight_info_statement(
+        &self,
+        query: CommandStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let plan = self.plan(&query.query).await?;
+        let ticket = TicketStatementQuery {
+            statement_handle: query.query.into_bytes().into(),
+        };
+        flight_info(&ticket, &plan_schema(&plan), request.into_inner())
+    }
+
+    async fn get_flight_info_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let statement = self.prepared_statements.get(
+            Session::from_request(&request),
+            &query.prepared_statement_handle,
+        )?;
+        flight_info(&query, &statement.plan_schema(), request.into_inner())
+    }
+
+    async fn get_flight_info_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&query, &schema, request.into_inner())
+    }
+
+    async fn get_flight_info_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&query, &schema, request.into_inner())
+    }
+
+    async fn get_flight_info_tables(
+        &self,
+        query: CommandGetTables,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder().schema();
+        flight_info(&query, &schema, request.into_inner())
+    }
+
+    async fn get_flight_info_table_types(
+        &self,
+        query: CommandGetTableTypes,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        flight_info(&query, &table_types_schema(), request.into_inner())
+    }
+
+    async fn get_flight_info_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let schema = query.clone().into_builder(&self.sql_info).schema();
+        flight_info(&query, &schema, request.into_inner())
+    }
+
+    async fn do_get_statement(
+        &self,
+        ticket: TicketStatementQuery,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let plan = self.plan(&handle_to_sql(&ticket.statement_handle)?).await?;
+        self.execute(plan).await
+    }
+
+    async fn do_get_prepared_statement(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let plan = self
+            .prepared_statements
+            .get(
+                Session::from_request(&request),
+                &query.prepared_statement_handle,
+            )?
+            .bound_plan()?;
+        self.execute(plan).await
+    }
+
+    async fn do_get_catalogs(
+        &self,
+        query: CommandGetCatalogs,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        for catalog_name in self.query_context.as_ref().catalog_names() {
+            builder.append(catalog_name);
+        }
+        single_batch_response(builder.schema(), builder.build())
+    }
+
+    async fn do_get_schemas(
+        &self,
+        query: CommandGetDbSchemas,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        let ctx = self.query_context.as_ref();
+        for catalog_name in ctx.catalog_names() {
+            let Some(catalog) = ctx.catalog(&catalog_name) else {
+                continue;
+            };
+            for schema_name in catalog.schema_names() {
+                builder.append(&catalog_name, schema_name);
+            }
+        }
+        single_batch_response(builder.schema(), builder.build())
+    }
+
+    async fn do_get_tables(
+        &self,
+        query: CommandGetTables,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let mut builder = query.into_builder();
+        let ctx = self.query_context.as_ref();
+        for catalog_name in ctx.catalog_names() {
+            let Some(catalog) = ctx.catalog(&catalog_name) else {
+                continue;
+            };
+            for schema_name in catalog.schema_names() {
+                let Some(schema) = catalog.schema(&schema_name) else {
+                    continue;
+                };
+                for table_name in schema.table_names() {
+                    let Some(table) = schema.table(&table_name).await else {
+                        continue;
+                    };
+                    let table_type = match table.table_type() {
+                        TableType::View => "VIEW",
+                        TableType::Base | TableType::Temporary => "TABLE",
+                    };
+                    builder
+                        .append(
+                            &catalog_name,
+                            &schema_name,
+                            &table_name,
+                            table_type,
+                            &table.schema(),
+                        )
+                        .map_err(arrow_error_to_status)?;
+                }
+            }
+        }
+        single_batch_response(builder.schema(), builder.build())
+    }
+
+    async fn do_get_table_types(
+        &self,
+        _query: CommandGetTableTypes,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let schema = table_types_schema();
+        let table_types: ArrayRef = Arc::new(StringArray::from(TABLE_TYPES.to_vec()));
+        single_batch_response(
+            schema.clone(),
+            RecordBatch::try_new(schema, vec![table_types]),
+        )
+    }
+
+    async fn do_get_sql_info(
+        &self,
+        query: CommandGetSqlInfo,
+        _request: Request<Ticket>,
+    ) -> Result<Response<DoGetStream>, Status> {
+        let builder = query.into_builder(&self.sql_info);
+        single_batch_response(builder.schema(), builder.build())
+    }
+
+    async fn do_action_create_prepared_statement(
+        &self,
+        query: ActionCreatePreparedStatementRequest,
+        request: Request<Action>,
+    ) -> Result<ActionCreatePreparedStatementResult, Status> {
+        let statement = PreparedStatement::new(self.plan(&query.query).await?)?;
+        let dataset_schema = schema_to_ipc(&statement.plan_schema())?;
+        let parameter_schema = if statement.parameter_schema().fields().is_empty() {
+            Bytes::new()
+        } else {
+            schema_to_ipc(statement.parameter_schema())?
+        };
+
+        Ok(ActionCreatePreparedStatementResult {
+            prepared_statement_handle: self
+                .prepared_statements
+                .insert(Session::from_request(&request), statement),
+            dataset_schema,
+            parameter_schema,
+        })
+    }
+
+    async fn do_put_prepared_statement_query(
+        &self,
+        query: CommandPreparedStatementQuery,
+        request: Request<PeekableFlightDataStream>,
+    ) -> Result<Response<DoPutStream>, Status> {
+        let session = Session::from_request(&request).cloned();
+        let mut parameters = FlightRecordBatchStream::new_from_flight_data(
+            request.into_inner().map_err(FlightError::from),
+        );
+        let mut bound = false;
+        while let Some(batch) = parameters.try_next().await.map_err(Status::from)? {
+            self.prepared_statements.bind(
+                session.as_ref(),
+                &query.prepared_statement_handle,
+                &batch,
+            )?;
+            bound = true;
+        }
+        if !bound {
+            return Err(Status::invalid_argument("no parameters to bind"));
+        }
+
+        Ok(Response::new(Box::pin(stream::once(async {
+            Ok(PutResult {
+                app_metadata: Bytes::new(),
+            })
+        }))))
+    }
+
+    async fn do_action_close_prepared_statement(
+        &self,
+        query: ActionClosePreparedStatementRequest,
+        request: Request<Action>,
+    ) -> Result<(), Status> {
+        self.prepared_statements.remove(
+            Session::from_request(&request),
+            &query.prepared_statement_handle,
+        )
+    }
+
+    async fn register_sql_info(&self, _id: i32, _result: &SqlInfo) {}
+}
+
+fn sql_info() -> SqlInfoData {
+    let mut sql_info = SqlInfoDataBuilder::new();
+    sql_info.append(SqlInfo::FlightSqlServerName, "Restate");
+    sql_info.append(SqlInfo::FlightSqlServerVersion, env!("CARGO_PKG_VERSION"));
+    sql_info.append(
+        SqlInfo::FlightSqlServerArrowVersion,
+        IPC_METADATA_VERSION.variant_name().unwrap_or_default(),
+    );
+    sql_info.append(SqlInfo::FlightSqlServerReadOnly, true);
+    sql_info.build().expect("valid sql info")
+}
+
+fn ipc_write_options() -> IpcWriteOptions {
+    IpcWriteOptions::try_new(8, false, IPC_METADATA_VERSION).expect("valid ipc write options")
+}
+
+fn schema_to_ipc(schema: &Schema) -> Result<Bytes, Status> {
+    let IpcMessage(schema) = SchemaAsIpc::new(schema, &ipc_write_options())
+        .try_into()
+        .map_err(arrow_error_to_status)?;
+    Ok(schema)
+}
+
+fn flight_info(
+    command: &impl ProstMessageExt,
+    schema: &Schema,
+    descriptor: FlightDescriptor,
+) -> Result<Response<FlightInfo>, Status> {
+    let ticket = Ticket {
+        ticket: command.as_any().encode_to_vec().into(),
+    };
+    let flight_info = FlightInfo::new()
+        .try_with_schema(schema)
+        .map_err(arrow_error_to_status)?
+        .with_endpoint(FlightEndpoint::new().with_ticket(ticket))
+        .with_descriptor(descriptor);
+
+    Ok(Response::new(flight_info))
+}
+
+fn do_get_response(
+    schema: SchemaRef,
+    batches: impl Stream<Item = Result<RecordBatch, FlightError>> + Send + 'static,
+) -> Response<DoGetStream> {
+    let flight_data = FlightDataEncoderBuilder::new()
+        .with_options(ipc_write_options())
+        .with_schema(schema)
+        .build(batches)
+        .map_err(Status::from);
+    Response::new(Box::pin(flight_data))
+}
+
+fn single_batch_response(
+    schema: SchemaRef,
+    batch: Result<RecordBatch, ArrowError>,
+) -> Result<Response<DoGetStream>, Status> {
+    let batch = batch.map_err(arrow_error_to_status)?;
+    Ok(do_get_response(schema, stream::once(async { Ok(batch) })))
+}
+
+fn plan_schema(plan: &LogicalPlan) -> Schema {
+    plan.schema().as_ref().into()
+}
+
+fn table_types_schema() -> SchemaRef {
+    SchemaRef::new(Schema::new(vec![Field::new(
+        "table_type",
+        DataType::Utf8,
+        false,
+    )]))
+}
+
+fn handle_to_sql(handle: &Bytes) -> Result<String, Status> {
+    String::from_utf8(handle.to_vec())
+        .map_err(|_| Status::invalid_argument("statement handle is not valid UTF-8"))
+}
+
+fn datafusion_error_to_status(err: DataFusionError) -> Status {
+    Status::internal(format!("failed executing the query: {err}"))
+}
+
+fn arrow_error_to_status(err: ArrowError) -> Status {
+    Status::internal(err.to_string())
+}
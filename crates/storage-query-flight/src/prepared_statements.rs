@@ -0,0 +1,417 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::common::{ParamValues, ScalarValue};
+use datafusion::logical_expr::LogicalPlan;
+use tonic::Status;
+
+use crate::authentication::Session;
+
+/// Prepared statements are closed by the clients. For the clients which don't close them, the
+/// statements are closed once they haven't been used for this long.
+const PREPARED_STATEMENT_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Bounds the memory used by the prepared statements. Once reached, the least recently used
+/// statement is closed to prepare a new one.
+const MAX_PREPARED_STATEMENTS: usize = 1024;
+
+/// Handles are random, so that they can't be guessed by the other clients.
+const HANDLE_LENGTH: usize = 16;
+
+type Handle = [u8; HANDLE_LENGTH];
+
+/// A statement prepared by a client, with the parameters bound to it.
+#[derive(Debug, Clone)]
+pub(crate) struct PreparedStatement {
+    plan: LogicalPlan,
+    parameter_schema: SchemaRef,
+    parameters: Option<ParamValues>,
+}
+
+impl PreparedStatement {
+    pub(crate) fn new(plan: LogicalPlan) -> Result<Self, Status> {
+        let parameter_schema = Arc::new(parameter_schema(&plan)?);
+        Ok(Self {
+            plan,
+            parameter_schema,
+            parameters: None,
+        })
+    }
+
+    pub(crate) fn plan_schema(&self) -> Schema {
+        self.plan.schema().as_ref().into()
+    }
+
+    pub(crate) fn parameter_schema(&self) -> &SchemaRef {
+        &self.parameter_schema
+    }
+
+    /// Returns the plan with the bound parameters in place of its placeholders.
+    pub(crate) fn bound_plan(&self) -> Result<LogicalPlan, Status> {
+        match &self.parameters {
+            Some(parameters) => self
+                .plan
+                .clone()
+                .replace_params_with_values(parameters)
+                .map_err(|err| Status::invalid_argument(format!("cannot bind parameters: {err}"))),
+            None if self.parameter_schema.fields().is_empty() => Ok(self.plan.clone()),
+            None => Err(Status::invalid_argument(
+                "the parameters of the prepared statement are not bound",
+            )),
+        }
+    }
+}
+
+/// Prepared statements of the Flight SQL clients, identified by an opaque handle. A statement
+/// is accessible only within the [`Session`] which prepared it.
+#[derive(Debug)]
+pub(crate) struct PreparedStatements {
+    idle_timeout: Duration,
+    max_statements: usize,
+    statements: Mutex<HashMap<Handle, OwnedStatement>>,
+}
+
+#[derive(Debug)]
+struct OwnedStatement {
+    session: Option<Session>,
+    statement: PreparedStatement,
+    last_used: Instant,
+}
+
+impl Default for PreparedStatements {
+    fn default() -> Self {
+        Self::new(PREPARED_STATEMENT_IDLE_TIMEOUT, MAX_PREPARED_STATEMENTS)
+    }
+}
+
+impl PreparedStatements {
+    fn new(idle_timeout: Duration, max_statements: usize) -> Self {
+        Self {
+            idle_timeout,
+            max_statements,
+            statements: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn insert(&self, session: Option<&Session>, statement: PreparedStatement) -> Bytes {
+        let now = Instant::now();
+        let mut statements = self.statements.lock().unwrap();
+        statements.retain(|_, owned| now.duration_since(owned.last_used) < self.idle_timeout);
+        if statements.len() >= self.max_statements {
+            let least_recently_used = statements
+                .iter()
+                .min_by_key(|(_, owned)| owned.last_used)
+                .map(|(handle, _)| *handle);
+            if let Some(handle) = least_recently_used {
+                statements.remove(&handle);
+            }
+        }
+
+        let handle: Handle = rand::random();
+        statements.insert(
+            handle,
+            OwnedStatement {
+                session: session.cloned(),
+                statement,
+                last_used: now,
+            },
+        );
+        Bytes::copy_from_slice(&handle)
+    }
+
+    pub(crate) fn get(
+        &self,
+        session: Option<&Session>,
+        handle: &[u8],
+    ) -> Result<PreparedStatement, Status> {
+        self.with_statement(session, handle, |statement| Ok(statement.clone()))
+    }
+
+    /// Binds the single row of `parameters` to the placeholders of the statement.
+    pub(crate) fn bind(
+        &self,
+        session: Option<&Session>,
+        handle: &[u8],
+        parameters: &RecordBatch,
+    ) -> Result<(), Status> {
+        self.with_statement(session, handle, |statement| {
+            statement.parameters = Some(bind_parameters(parameters, &statement.parameter_schema)?);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn remove(&self, session: Option<&Session>, handle: &[u8]) -> Result<(), Status> {
+        let handle = parse_handle(handle)?;
+        let mut statements = self.statements.lock().unwrap();
+        match statements.get(&handle) {
+            Some(owned) if owned.session.as_ref() == session => {
+                statements.remove(&handle);
+                Ok(())
+            }
+            _ => Err(unknown_handle()),
+        }
+    }
+
+    fn with_statement<R>(
+        &self,
+        session: Option<&Session>,
+        handle: &[u8],
+        f: impl FnOnce(&mut PreparedStatement) -> Result<R, Status>,
+    ) -> Result<R, Status> {
+        let handle = parse_handle(handle)?;
+        let now = Instant::now();
+        let mut statements = self.statements.lock().unwrap();
+        let owned = statements
+            .get_mut(&handle)
+            .filter(|owned| {
+                owned.session.as_ref() == session
+                    && now.duration_since(owned.last_used) < self.idle_timeout
+            })
+            .ok_or_else(unknown_handle)?;
+
+        owned.last_used = now;
+        f(&mut owned.statement)
+    }
+}
+
+fn parse_handle(handle: &[u8]) -> Result<Handle, Status> {
+    Handle::try_from(handle).map_err(|_| unknown_handle())
+}
+
+fn unknown_handle() -> Status {
+    Status::not_found("unknown prepared statement handle")
+}
+
+/// Schema of the `$n` placeholders of the plan, in the order of their index. Parameters whose
+/// type can't be inferred are bound as text.
+fn parameter_schema(plan: &LogicalPlan) -> Result<Schema, Status> {
+    let mut parameters = plan
+        .get_parameter_types()
+        .map_err(|err| Status::invalid_argument(format!("failed planning the query: {err}")))?
+        .into_iter()
+        .map(|(placeholder, data_type)| {
+            Ok((parameter_index(&placeholder)?, placeholder, data_type))
+        })
+        .collect::<Result<Vec<_>, Status>>()?;
+    parameters.sort_by_key(|(index, _, _)| *index);
+
+    // placeholders are bound by position, which must match their index
+    if let Some((position, (_, placeholder, _))) = parameters
+        .iter()
+        .enumerate()
+        .find(|(position, (index, _, _))| position != index)
+    {
+        return Err(Status::invalid_argument(format!(
+            "placeholder '{placeholder}' is used, but ${} is not",
+            position + 1
+        )));
+    }
+
+    Ok(Schema::new(
+        parameters
+            .into_iter()
+            .map(|(_, placeholder, data_type)| {
+                Field::new(placeholder, data_type.unwrap_or(DataType::Utf8), true)
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Parses the index of a `$n` placeholder, starting from 0.
+fn parameter_index(placeholder: &str) -> Result<usize, Status> {
+    placeholder
+        .strip_prefix('$')
+        .and_then(|index| index.parse::<usize>().ok())
+        .and_then(|index| index.checked_sub(1))
+        .ok_or_else(|| Status::invalid_argument(format!("unsupported placeholder '{placeholder}'")))
+}
+
+/// Casts the values of the single row of `parameters` to the types of the placeholders.
+fn bind_parameters(
+    parameters: &RecordBatch,
+    parameter_schema: &Schema,
+) -> Result<ParamValues, Status> {
+    if parameters.num_rows() != 1 {
+        return Err(Status::invalid_argument(format!(
+            "expected a single row of parameters, got {}",
+            parameters.num_rows()
+        )));
+    }
+    if parameters.num_columns() != parameter_schema.fields().len() {
+        return Err(Status::invalid_argument(format!(
+            "expected {} parameters, got {}",
+            parameter_schema.fields().len(),
+            parameters.num_columns()
+        )));
+    }
+
+    parameters
+        .columns()
+        .iter()
+        .zip(parameter_schema.fields())
+        .map(|(column, field)| {
+            ScalarValue::try_from_array(column, 0)
+                .and_then(|value| value.cast_to(field.data_type()))
+                .map_err(|err| {
+                    Status::invalid_argument(format!(
+                        "cannot bind parameter '{}': {err}",
+                        field.name()
+                    ))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(ParamValues::List)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use datafusion::arrow::array::{ArrayRef, Int64Array, StringArray};
+    use datafusion::prelude::SessionContext;
+
+    async fn prepare(sql: &str) -> Result<PreparedStatement, Status> {
+        let plan = SessionContext::new()
+            .state()
+            .create_logical_plan(sql)
+            .await
+            .unwrap();
+        PreparedStatement::new(plan)
+    }
+
+    fn parameters(columns: Vec<(&str, ArrayRef)>) -> RecordBatch {
+        RecordBatch::try_from_iter(columns).unwrap()
+    }
+
+    #[tokio::test]
+    async fn parameters_are_ordered_by_index() {
+        let statement = prepare("SELECT $2 + 1 AS a, $1 || 'b' AS b").await.unwrap();
+        let fields: Vec<_> = statement
+            .parameter_schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(fields, vec!["$1", "$2"]);
+
+        assert!(prepare("SELECT $2 + 1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn statements_without_parameters_need_no_binding() {
+        let statement = prepare("SELECT 1").await.unwrap();
+        assert!(statement.parameter_schema().fields().is_empty());
+        assert!(statement.bound_plan().is_ok());
+    }
+
+    #[tokio::test]
+    async fn bind_parameters_to_the_statement() {
+        let statements = PreparedStatements::default();
+        let handle = statements.insert(
+            None,
+            prepare("SELECT $1 + 1 AS a, $2 || 'y' AS b").await.unwrap(),
+        );
+
+        // executing before binding fails
+        assert!(statements.get(None, &handle).unwrap().bound_plan().is_err());
+
+        statements
+            .bind(
+                None,
+                &handle,
+                &parameters(vec![
+                    ("$1", Arc::new(Int64Array::from(vec![41])) as ArrayRef),
+                    ("$2", Arc::new(StringArray::from(vec!["x"])) as ArrayRef),
+                ]),
+            )
+            .unwrap();
+        let plan = statements.get(None, &handle).unwrap().bound_plan().unwrap();
+        assert!(plan.get_parameter_types().unwrap().is_empty());
+
+        // the number of parameters must match
+        let err = statements
+            .bind(
+                None,
+                &handle,
+                &parameters(vec![(
+                    "$1",
+                    Arc::new(Int64Array::from(vec![41])) as ArrayRef,
+                )]),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        statements.remove(None, &handle).unwrap();
+        assert_eq!(
+            statements.get(None, &handle).unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn statements_are_scoped_to_their_session() {
+        let statements = PreparedStatements::default();
+        let session = Session::from_token("secret");
+        let handle = statements.insert(Some(&session), prepare("SELECT 1").await.unwrap());
+        assert_eq!(handle.len(), HANDLE_LENGTH);
+
+        assert!(statements.get(Some(&session), &handle).is_ok());
+        let other_session = Session::from_token("other");
+        assert_eq!(
+            statements
+                .get(Some(&other_session), &handle)
+                .unwrap_err()
+                .code(),
+            tonic::Code::NotFound
+        );
+        assert_eq!(
+            statements.remove(None, &handle).unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+        assert!(statements.remove(Some(&session), &handle).is_ok());
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_statements_are_evicted() {
+        let statements = PreparedStatements::new(PREPARED_STATEMENT_IDLE_TIMEOUT, 2);
+        let statement = prepare("SELECT 1").await.unwrap();
+        let first = statements.insert(None, statement.clone());
+        let second = statements.insert(None, statement.clone());
+
+        // using the first statement makes the second one the least recently used
+        statements.get(None, &first).unwrap();
+        let third = statements.insert(None, statement);
+
+        assert!(statements.get(None, &first).is_ok());
+        assert!(statements.get(None, &second).is_err());
+        assert!(statements.get(None, &third).is_ok());
+        assert_eq!(
+            statements.get(None, b"not a handle").unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn idle_statements_are_closed() {
+        let statements = PreparedStatements::new(Duration::ZERO, MAX_PREPARED_STATEMENTS);
+        let handle = statements.insert(None, prepare("SELECT 1").await.unwrap());
+        assert_eq!(
+            statements.get(None, &handle).unwrap_err().code(),
+            tonic::Code::NotFound
+        );
+    }
+}
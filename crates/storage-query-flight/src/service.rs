@@ -0,0 +1,105 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::net::SocketAddr;
+
+use arrow_flight::flight_service_server::FlightServiceServer;
+use codederror::CodedError;
+
+use restate_core::cancellation_watcher;
+use restate_core::network::grpc_util::{self, run_hyper_server};
+use restate_core::network::tls::{ReloadableTlsConfig, TlsError};
+use restate_storage_query_datafusion::context::QueryContext;
+use restate_types::config::{AdminAuthenticationOptions, QueryEngineOptions, TlsOptions};
+use restate_types::jwt::{AdminAuthenticator, JwksError};
+use restate_types::net::BindAddress;
+
+use crate::authentication::AuthenticationInterceptor;
+use crate::flight_sql_server::FlightSqlServer;
+
+#[derive(Debug, thiserror::Error, CodedError)]
+pub enum Error {
+    #[error(
+        "failed running the server bound to 'admin.query-engine.flight-sql-bind-address': {0}"
+    )]
+    #[code(unknown)]
+    Server(#[from] grpc_util::Error),
+    #[error("failed loading the certificates specified in 'admin.query-engine.pgsql-tls': {0}")]
+    #[code(unknown)]
+    Tls(#[from] TlsError),
+    #[error("invalid 'admin.authentication.jwks-file': {0}")]
+    #[code(unknown)]
+    Authentication(#[from] JwksError),
+}
+
+pub struct FlightSqlQueryService {
+    pub bind_address: SocketAddr,
+    pub tls: Option<TlsOptions>,
+    /// If set, the requests must be authenticated with the bearer tokens of the Admin API.
+    pub authentication: Option<AdminAuthenticationOptions>,
+    pub query_context: QueryContext,
+}
+
+impl FlightSqlQueryService {
+    /// Returns `None` if the Flight SQL service is not enabled.
+    pub fn from_options(
+        options: &QueryEngineOptions,
+        authentication: Option<&AdminAuthenticationOptions>,
+        query_context: QueryContext,
+    ) -> Option<Self> {
+        options.flight_sql_bind_address.map(|bind_address| Self {
+            bind_address,
+            tls: options.pgsql_tls.clone(),
+            authentication: authentication.cloned(),
+            query_context,
+        })
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        let FlightSqlQueryService {
+            bind_address,
+            tls,
+            authentication,
+            query_context,
+        } = self;
+
+        let authenticator = authentication
+            .as_ref()
+            .map(AdminAuthenticator::from_options)
+            .transpose()
+            .map_err(Error::Authentication)?;
+
+        // same certificates as the psql service
+        let tls =
+            ReloadableTlsConfig::start_server(tls.as_ref(), "flight-sql-tls-reload", |config| {
+                config.admin.query_engine.pgsql_tls.as_ref()
+            })
+            .map_err(Error::Tls)?;
+
+        let service = tonic::transport::Server::builder()
+            .add_service(FlightServiceServer::with_interceptor(
+                FlightSqlServer::new(query_context),
+                AuthenticationInterceptor::new(authenticator),
+            ))
+            .into_service();
+
+        run_hyper_server(
+            &BindAddress::Socket(bind_address),
+            tls,
+            service,
+            cancellation_watcher(),
+            "flight-sql",
+        )
+        .await
+        .map_err(Error::Server)?;
+
+        Ok(())
+    }
+}
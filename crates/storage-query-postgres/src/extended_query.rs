@@ -8,32 +8,72 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use pgwire::api::portal::Portal;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use datafusion::arrow::datatypes::{DataType, Schema, TimeUnit};
+use datafusion::common::{ParamValues, ScalarValue};
+use datafusion::logical_expr::LogicalPlan;
+use pgwire::api::portal::{Format, Portal};
 use pgwire::api::query::ExtendedQueryHandler;
 use pgwire::api::results::{DescribePortalResponse, DescribeStatementResponse, Response};
-use pgwire::api::stmt::{NoopQueryParser, StoredStatement};
-use pgwire::api::ClientInfo;
+use pgwire::api::stmt::{QueryParser, StoredStatement};
+use pgwire::api::{ClientInfo, Type};
 use pgwire::error::{PgWireError, PgWireResult};
-use std::sync::Arc;
 
+use restate_storage_query_datafusion::context::QueryContext;
+
+use crate::pgwire_server::{arrow_to_pg_encoder, into_pg_fields, into_pg_type, DfSessionService};
+
+/// A parsed statement of the extended query protocol, with the types of its `$n` parameters.
 #[derive(Debug, Clone)]
-pub struct NoopExtendedQueryHandler {
-    query_parser: Arc<NoopQueryParser>,
+pub struct Statement {
+    plan: LogicalPlan,
+    /// Types of the parameters, indexed by their placeholder number (`$1` at 0).
+    parameter_types: Vec<DataType>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DfQueryParser {
+    query_context: QueryContext,
+}
+
+impl DfQueryParser {
+    pub(crate) fn new(query_context: QueryContext) -> Self {
+        Self { query_context }
+    }
 }
 
-impl NoopExtendedQueryHandler {
-    pub(crate) fn new() -> Self {
-        NoopExtendedQueryHandler {
-            query_parser: Arc::new(Default::default()),
-        }
+#[async_trait]
+impl QueryParser for DfQueryParser {
+    type Statement = Statement;
+
+    async fn parse_sql(&self, sql: &str, types: &[Type]) -> PgWireResult<Self::Statement> {
+        let plan = self
+            .query_context
+            .create_logical_plan(sql)
+            .await
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+
+        let parameter_types = parameter_types(
+            plan.get_parameter_types()
+                .map_err(|e| PgWireError::ApiError(Box::new(e)))?,
+            types,
+        )?;
+
+        Ok(Statement {
+            plan,
+            parameter_types,
+        })
     }
 }
 
 #[async_trait]
-impl ExtendedQueryHandler for NoopExtendedQueryHandler {
-    type Statement = String;
-    type QueryParser = NoopQueryParser;
+impl ExtendedQueryHandler for DfSessionService {
+    type Statement = Statement;
+    type QueryParser = DfQueryParser;
 
     fn query_parser(&self) -> Arc<Self::QueryParser> {
         self.query_parser.clone()
@@ -42,40 +82,204 @@ impl ExtendedQueryHandler for NoopExtendedQueryHandler {
     async fn do_describe_statement<C>(
         &self,
         _client: &mut C,
-        _statement: &StoredStatement<Self::Statement>,
+        statement: &StoredStatement<Self::Statement>,
     ) -> PgWireResult<DescribeStatementResponse>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
-        Err(PgWireError::ApiError(
-            "Extended Query is not implemented on this server.".into(),
-        ))
+        let statement = &statement.statement;
+        let parameter_types = statement
+            .parameter_types
+            .iter()
+            .map(into_pg_type)
+            .collect::<PgWireResult<Vec<_>>>()?;
+        // the result format is only known once the statement is bound to a portal
+        let fields = into_pg_fields(&plan_schema(&statement.plan), &Format::UnifiedText)?;
+
+        Ok(DescribeStatementResponse::new(parameter_types, fields))
     }
 
     async fn do_describe_portal<C>(
         &self,
         _client: &mut C,
-        _portal: &Portal<Self::Statement>,
+        portal: &Portal<Self::Statement>,
     ) -> PgWireResult<DescribePortalResponse>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
-        Err(PgWireError::ApiError(
-            "Extended Query is not implemented on this server.".into(),
-        ))
+        let fields = into_pg_fields(
+            &plan_schema(&portal.statement.statement.plan),
+            &portal.result_column_format,
+        )?;
+
+        Ok(DescribePortalResponse::new(fields))
     }
 
     async fn do_query<'a, 'b: 'a, C>(
         &'b self,
         _client: &mut C,
-        _portal: &'a Portal<Self::Statement>,
+        portal: &'a Portal<Self::Statement>,
         _max_rows: usize,
     ) -> PgWireResult<Response<'a>>
     where
         C: ClientInfo + Unpin + Send + Sync,
     {
-        Err(PgWireError::ApiError(
-            "Extended Query is not implemented on this server.".into(),
-        ))
+        let statement = &portal.statement.statement;
+        let parameters = bind_parameters(portal, &statement.parameter_types)?;
+        let plan = statement
+            .plan
+            .clone()
+            .replace_params_with_values(&parameters)
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+
+        let ctx = self.session_context.lock().await;
+        let df = ctx
+            .execute_logical_plan(plan)
+            .await
+            .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+
+        let resp = arrow_to_pg_encoder(df, &portal.result_column_format).await?;
+        Ok(Response::Query(resp))
+    }
+}
+
+fn plan_schema(plan: &LogicalPlan) -> Schema {
+    plan.schema().as_ref().into()
+}
+
+/// Types of the parameters, indexed by their placeholder number. The types inferred by DataFusion
+/// take precedence over the ones declared by the client, parameters whose type is unknown to both
+/// (e.g. the ones not used by the query) are bound as text.
+fn parameter_types(
+    inferred_types: HashMap<String, Option<DataType>>,
+    types: &[Type],
+) -> PgWireResult<Vec<DataType>> {
+    let mut inferred_types = inferred_types
+        .into_iter()
+        .map(|(placeholder, data_type)| Ok((parameter_index(&placeholder)?, data_type)))
+        .collect::<PgWireResult<BTreeMap<_, _>>>()?;
+    let parameter_count = inferred_types
+        .last_key_value()
+        .map_or(0, |(index, _)| index + 1)
+        .max(types.len());
+
+    Ok((0..parameter_count)
+        .map(|index| {
+            inferred_types
+                .remove(&index)
+                .flatten()
+                .or_else(|| types.get(index).and_then(from_pg_type))
+                .unwrap_or(DataType::Utf8)
+        })
+        .collect())
+}
+
+/// Parses the index of a `$n` placeholder, starting from 0.
+fn parameter_index(placeholder: &str) -> PgWireResult<usize> {
+    placeholder
+        .strip_prefix('$')
+        .and_then(|index| index.parse::<usize>().ok())
+        .and_then(|index| index.checked_sub(1))
+        .ok_or_else(|| {
+            PgWireError::ApiError(format!("unsupported placeholder '{placeholder}'").into())
+        })
+}
+
+fn from_pg_type(pg_type: &Type) -> Option<DataType> {
+    Some(match *pg_type {
+        Type::BOOL => DataType::Boolean,
+        Type::CHAR => DataType::Int8,
+        Type::INT2 => DataType::Int16,
+        Type::INT4 => DataType::Int32,
+        Type::INT8 => DataType::Int64,
+        Type::FLOAT4 => DataType::Float32,
+        Type::FLOAT8 => DataType::Float64,
+        Type::BYTEA => DataType::Binary,
+        Type::TIMESTAMP => DataType::Timestamp(TimeUnit::Microsecond, None),
+        Type::TIMESTAMPTZ => DataType::Timestamp(TimeUnit::Microsecond, Some("+00:00".into())),
+        Type::TEXT | Type::VARCHAR => DataType::Utf8,
+        _ => return None,
+    })
+}
+
+/// Decodes the parameters bound to the portal as the pg types announced to the client, then
+/// casts them to the types expected by the plan.
+fn bind_parameters(
+    portal: &Portal<Statement>,
+    parameter_types: &[DataType],
+) -> PgWireResult<ParamValues> {
+    let mut values = Vec::with_capacity(parameter_types.len());
+    for (index, data_type) in parameter_types.iter().enumerate() {
+        let pg_type = into_pg_type(data_type)?;
+        let value = match pg_type {
+            Type::BOOL => ScalarValue::Boolean(portal.parameter::<bool>(index, &pg_type)?),
+            Type::CHAR => ScalarValue::Int8(portal.parameter::<i8>(index, &pg_type)?),
+            Type::INT2 => ScalarValue::Int16(portal.parameter::<i16>(index, &pg_type)?),
+            Type::INT4 => ScalarValue::Int32(portal.parameter::<i32>(index, &pg_type)?),
+            Type::INT8 => ScalarValue::Int64(portal.parameter::<i64>(index, &pg_type)?),
+            Type::FLOAT4 => ScalarValue::Float32(portal.parameter::<f32>(index, &pg_type)?),
+            Type::FLOAT8 => ScalarValue::Float64(portal.parameter::<f64>(index, &pg_type)?),
+            Type::BYTEA => ScalarValue::Binary(portal.parameter::<Vec<u8>>(index, &pg_type)?),
+            Type::TIMESTAMP => ScalarValue::TimestampMicrosecond(
+                portal
+                    .parameter::<NaiveDateTime>(index, &pg_type)?
+                    .map(|timestamp| timestamp.and_utc().timestamp_micros()),
+                None,
+            ),
+            Type::TIMESTAMPTZ => ScalarValue::TimestampMicrosecond(
+                portal
+                    .parameter::<DateTime<Utc>>(index, &pg_type)?
+                    .map(|timestamp| timestamp.timestamp_micros()),
+                Some("+00:00".into()),
+            ),
+            // everything else is bound through its text representation
+            _ => ScalarValue::Utf8(portal.parameter::<String>(index, &Type::VARCHAR)?),
+        };
+
+        let value = if value.data_type() == *data_type {
+            value
+        } else {
+            value
+                .cast_to(data_type)
+                .map_err(|e| PgWireError::ApiError(Box::new(e)))?
+        };
+        values.push(value);
+    }
+
+    Ok(ParamValues::List(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_indexed_from_zero() {
+        assert_eq!(parameter_index("$1").unwrap(), 0);
+        assert_eq!(parameter_index("$12").unwrap(), 11);
+        assert!(parameter_index("$0").is_err());
+        assert!(parameter_index("?").is_err());
+    }
+
+    #[test]
+    fn parameters_are_indexed_by_placeholder_number() {
+        let inferred = HashMap::from([
+            ("$1".to_owned(), Some(DataType::Int64)),
+            ("$3".to_owned(), None),
+        ]);
+
+        assert_eq!(
+            parameter_types(inferred.clone(), &[]).unwrap(),
+            vec![DataType::Int64, DataType::Utf8, DataType::Utf8]
+        );
+        assert_eq!(
+            parameter_types(inferred, &[Type::TEXT, Type::INT4, Type::BOOL, Type::INT8]).unwrap(),
+            vec![
+                DataType::Int64,
+                DataType::Int32,
+                DataType::Boolean,
+                DataType::Int64
+            ]
+        );
     }
 }
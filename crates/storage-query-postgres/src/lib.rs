@@ -11,5 +11,6 @@
 mod extended_query;
 mod pgwire_server;
 pub mod service;
+mod tls;
 
 pub use service::Error;
@@ -8,33 +8,50 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::io;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use bytes::{BufMut, BytesMut};
+use chrono::{NaiveDateTime, TimeZone, Utc};
 use datafusion::arrow::array::{
-    Array, BinaryArray, BooleanArray, Date32Array, Date64Array, LargeBinaryArray, LargeStringArray,
-    PrimitiveArray, StringArray,
+    Array, ArrayRef, AsArray, BinaryArray, BooleanArray, Date32Array, Date64Array,
+    LargeBinaryArray, LargeStringArray, PrimitiveArray, StringArray,
 };
 use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::datatypes::Decimal128Type;
+use datafusion::arrow::datatypes::Decimal256Type;
 use datafusion::arrow::datatypes::Float32Type;
 use datafusion::arrow::datatypes::Float64Type;
 use datafusion::arrow::datatypes::Int16Type;
 use datafusion::arrow::datatypes::Int32Type;
 use datafusion::arrow::datatypes::Int64Type;
 use datafusion::arrow::datatypes::Int8Type;
+use datafusion::arrow::datatypes::UInt16Type;
 use datafusion::arrow::datatypes::UInt32Type;
 use datafusion::arrow::datatypes::UInt64Type;
+use datafusion::arrow::datatypes::UInt8Type;
+use datafusion::arrow::datatypes::{
+    Schema, TimeUnit, TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType,
+};
+use datafusion::arrow::json::writer::array_to_json_array;
 use datafusion::arrow::record_batch::RecordBatch;
 use datafusion::arrow::temporal_conversions::{date32_to_datetime, date64_to_datetime};
+use datafusion::arrow::util::display::{ArrayFormatter, FormatOptions};
 use datafusion::physical_plan::SendableRecordBatchStream;
 use futures::{stream, StreamExt};
+use pgwire_tokio_rustls::TlsAcceptor;
+use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 
-use crate::extended_query::NoopExtendedQueryHandler;
+use crate::extended_query::DfQueryParser;
 use pgwire::api::auth::noop::NoopStartupHandler;
 use pgwire::api::copy::NoopCopyHandler;
+use pgwire::api::portal::Format;
 use pgwire::api::query::SimpleQueryHandler;
 use pgwire::api::results::{DataRowEncoder, FieldFormat, FieldInfo, QueryResponse, Response};
 use pgwire::api::{ClientInfo, PgWireHandlerFactory, Type};
@@ -42,11 +59,15 @@ use pgwire::error::{ErrorInfo, PgWireError, PgWireResult};
 use pgwire::messages::data::DataRow;
 use pgwire::tokio::process_socket;
 use restate_storage_query_datafusion::context::QueryContext;
-use tracing::warn;
+use tracing::{debug, warn};
+
+/// Length and code of the SSLRequest message, which starts the connections upgraded to TLS
+const SSL_REQUEST: [u8; 8] = [0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f];
+/// Time a client has to send the first message after connecting
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub(crate) struct HandlerFactory {
     processor: Arc<DfSessionService>,
-    placeholder: Arc<NoopExtendedQueryHandler>,
     authenticator: Arc<NoopStartupHandler>,
     copy_handler: Arc<NoopCopyHandler>,
 }
@@ -54,7 +75,7 @@ pub(crate) struct HandlerFactory {
 impl PgWireHandlerFactory for HandlerFactory {
     type StartupHandler = NoopStartupHandler;
     type SimpleQueryHandler = DfSessionService;
-    type ExtendedQueryHandler = NoopExtendedQueryHandler;
+    type ExtendedQueryHandler = DfSessionService;
     type CopyHandler = NoopCopyHandler;
 
     fn simple_query_handler(&self) -> Arc<Self::SimpleQueryHandler> {
@@ -62,7 +83,7 @@ impl PgWireHandlerFactory for HandlerFactory {
     }
 
     fn extended_query_handler(&self) -> Arc<Self::ExtendedQueryHandler> {
-        self.placeholder.clone()
+        self.processor.clone()
     }
 
     fn startup_handler(&self) -> Arc<Self::StartupHandler> {
@@ -77,14 +98,11 @@ impl PgWireHandlerFactory for HandlerFactory {
 impl HandlerFactory {
     pub fn new(ctx: QueryContext) -> Self {
         let processor = Arc::new(DfSessionService::new(ctx));
-        // We have not implemented extended query in this server, use placeholder instead
-        let placeholder = Arc::new(NoopExtendedQueryHandler::new());
         let authenticator = Arc::new(NoopStartupHandler);
         let copy_handler = Arc::new(NoopCopyHandler);
 
         Self {
             processor,
-            placeholder,
             authenticator,
             copy_handler,
         }
@@ -95,9 +113,30 @@ pub fn spawn_connection(
     factory: Arc<HandlerFactory>,
     incoming_socket: TcpStream,
     addr: SocketAddr,
+    tls_acceptor: Option<TlsAcceptor>,
 ) {
     tokio::spawn(async move {
-        let result = process_socket(incoming_socket, None, factory).await;
+        if tls_acceptor.is_some() {
+            // pgwire falls back to plaintext if the client doesn't request TLS
+            match tokio::time::timeout(STARTUP_TIMEOUT, is_ssl_request(&incoming_socket)).await {
+                Ok(Ok(true)) => {}
+                Ok(Ok(false)) => {
+                    debug!("Rejecting plaintext connection '{addr}', TLS is required");
+                    let _ = reject_plaintext_connection(incoming_socket).await;
+                    return;
+                }
+                Ok(Err(err)) => {
+                    debug!("Failed reading the startup message of connection '{addr}': {err}");
+                    return;
+                }
+                Err(_) => {
+                    debug!("Connection '{addr}' timed out before sending the startup message");
+                    return;
+                }
+            }
+        }
+
+        let result = process_socket(incoming_socket, tls_acceptor, factory).await;
 
         if let Err(err) = result {
             warn!("Failed processing socket for connection '{addr}': {err}");
@@ -105,13 +144,57 @@ pub fn spawn_connection(
     });
 }
 
+/// Checks, without consuming it, whether the first message of the connection is an SSLRequest.
+async fn is_ssl_request(socket: &TcpStream) -> io::Result<bool> {
+    let mut buf = [0; SSL_REQUEST.len()];
+    loop {
+        let read = socket.peek(&mut buf).await?;
+        if read == 0 || buf[..read] != SSL_REQUEST[..read] {
+            return Ok(false);
+        }
+        if read == SSL_REQUEST.len() {
+            return Ok(true);
+        }
+        // the message is incomplete, peek returns immediately until more bytes arrive
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+async fn reject_plaintext_connection(mut socket: TcpStream) -> io::Result<()> {
+    let fields: [(u8, &str); 4] = [
+        (b'S', "FATAL"),
+        (b'V', "FATAL"),
+        (b'C', "28000"),
+        (b'M', "TLS is required, connect with sslmode=require"),
+    ];
+
+    // ErrorResponse message: 'E', length, and the null terminated fields
+    let mut body = BytesMut::new();
+    for (code, value) in fields {
+        body.put_u8(code);
+        body.put_slice(value.as_bytes());
+        body.put_u8(0);
+    }
+    body.put_u8(0);
+
+    let mut message = BytesMut::with_capacity(body.len() + 5);
+    message.put_u8(b'E');
+    message.put_u32(u32::try_from(body.len() + 4).expect("error message fits in u32"));
+    message.put_slice(&body);
+
+    socket.write_all(&message).await?;
+    socket.shutdown().await
+}
+
 pub struct DfSessionService {
-    session_context: Mutex<QueryContext>,
+    pub(crate) session_context: Mutex<QueryContext>,
+    pub(crate) query_parser: Arc<DfQueryParser>,
 }
 
 impl DfSessionService {
     pub fn new(ctx: QueryContext) -> DfSessionService {
         DfSessionService {
+            query_parser: Arc::new(DfQueryParser::new(ctx.clone())),
             session_context: Mutex::new(ctx),
         }
     }
@@ -133,12 +216,12 @@ impl SimpleQueryHandler for DfSessionService {
             .await
             .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
 
-        let resp = arrow_to_pg_encoder(df).await?;
+        let resp = arrow_to_pg_encoder(df, &Format::UnifiedText).await?;
         Ok(vec![Response::Query(resp)])
     }
 }
 
-fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
+pub(crate) fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
     Ok(match df_type {
         DataType::Null => Type::UNKNOWN,
         DataType::Boolean => Type::BOOL,
@@ -146,11 +229,13 @@ fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
         DataType::Int16 => Type::INT2,
         DataType::Int32 => Type::INT4,
         DataType::Int64 => Type::INT8,
-        DataType::UInt8 => Type::CHAR,
-        DataType::UInt16 => Type::INT2,
-        DataType::UInt32 => Type::INT4,
+        // unsigned integers are widened, postgres has no unsigned types
+        DataType::UInt8 => Type::INT2,
+        DataType::UInt16 => Type::INT4,
+        DataType::UInt32 => Type::INT8,
         DataType::UInt64 => Type::INT8,
-        DataType::Timestamp(_, _) => Type::TIMESTAMP,
+        DataType::Timestamp(_, None) => Type::TIMESTAMP,
+        DataType::Timestamp(_, Some(_)) => Type::TIMESTAMPTZ,
         DataType::Time32(_) | DataType::Time64(_) => Type::TIME,
         DataType::Date32 | DataType::Date64 => Type::VARCHAR,
         DataType::Binary => Type::BYTEA,
@@ -159,6 +244,11 @@ fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
         DataType::Float64 => Type::FLOAT8,
         DataType::Utf8 => Type::VARCHAR,
         DataType::LargeUtf8 => Type::VARCHAR,
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => Type::NUMERIC,
+        DataType::List(field) | DataType::LargeList(field) | DataType::FixedSizeList(field, _) => {
+            into_pg_array_type(&into_pg_type(field.data_type())?)
+        }
+        DataType::Struct(_) => Type::JSON,
         _ => {
             return Err(PgWireError::UserError(Box::new(ErrorInfo::new(
                 "ERROR".to_owned(),
@@ -169,26 +259,72 @@ fn into_pg_type(df_type: &DataType) -> PgWireResult<Type> {
     })
 }
 
-async fn arrow_to_pg_encoder<'a>(
+fn into_pg_array_type(element_type: &Type) -> Type {
+    match *element_type {
+        Type::BOOL => Type::BOOL_ARRAY,
+        Type::CHAR => Type::CHAR_ARRAY,
+        Type::INT2 => Type::INT2_ARRAY,
+        Type::INT4 => Type::INT4_ARRAY,
+        Type::INT8 => Type::INT8_ARRAY,
+        Type::FLOAT4 => Type::FLOAT4_ARRAY,
+        Type::FLOAT8 => Type::FLOAT8_ARRAY,
+        Type::NUMERIC => Type::NUMERIC_ARRAY,
+        Type::TIMESTAMP => Type::TIMESTAMP_ARRAY,
+        Type::TIMESTAMPTZ => Type::TIMESTAMPTZ_ARRAY,
+        Type::TIME => Type::TIME_ARRAY,
+        Type::BYTEA => Type::BYTEA_ARRAY,
+        Type::VARCHAR => Type::VARCHAR_ARRAY,
+        Type::JSON => Type::JSON_ARRAY,
+        // nested lists are represented as arrays of their text representation
+        _ => Type::TEXT_ARRAY,
+    }
+}
+
+/// Only the types whose values are encoded as their pg type support the binary format, all the
+/// others (e.g. decimals, dates, lists and structs) are encoded as text regardless of the format
+/// requested by the client.
+fn into_pg_field_format(df_type: &DataType, requested: FieldFormat) -> FieldFormat {
+    match df_type {
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::Utf8
+        | DataType::LargeUtf8
+        | DataType::Binary
+        | DataType::LargeBinary
+        | DataType::Timestamp(_, _) => requested,
+        _ => FieldFormat::Text,
+    }
+}
+
+pub(crate) fn into_pg_fields(schema: &Schema, format: &Format) -> PgWireResult<Vec<FieldInfo>> {
+    schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+            let pg_type = into_pg_type(f.data_type())?;
+            let format = into_pg_field_format(f.data_type(), format.format_for(idx));
+
+            Ok(FieldInfo::new(f.name().into(), None, None, pg_type, format))
+        })
+        .collect()
+}
+
+pub(crate) async fn arrow_to_pg_encoder<'a>(
     recordbatch_stream: SendableRecordBatchStream,
+    format: &Format,
 ) -> PgWireResult<QueryResponse<'a>> {
     let schema = recordbatch_stream.schema();
-    let fields = Arc::new(
-        schema
-            .fields()
-            .iter()
-            .map(|f| {
-                let pg_type = into_pg_type(f.data_type())?;
-                let format = if matches!(f.data_type(), DataType::Binary) {
-                    FieldFormat::Binary
-                } else {
-                    FieldFormat::Text
-                };
-
-                Ok(FieldInfo::new(f.name().into(), None, None, pg_type, format))
-            })
-            .collect::<PgWireResult<Vec<FieldInfo>>>()?,
-    );
+    let fields = Arc::new(into_pg_fields(&schema, format)?);
 
     let fields_ref = fields.clone();
     let pg_row_stream = recordbatch_stream
@@ -222,12 +358,7 @@ fn encode_row(
     let cols = rb.num_columns();
     let mut encoder = DataRowEncoder::new(Arc::clone(fields_ref));
     for col in 0..cols {
-        let array = rb.column(col);
-        if array.is_null(row) {
-            encoder.encode_field(&None::<i8>)?;
-        } else {
-            encode_value(&mut encoder, array, row)?;
-        }
+        encode_value(&mut encoder, rb.column(col), row)?;
     }
     encoder.finish()
 }
@@ -276,11 +407,88 @@ get_primitive_value!(get_i8_value, Int8Type, i8);
 get_primitive_value!(get_i16_value, Int16Type, i16);
 get_primitive_value!(get_i32_value, Int32Type, i32);
 get_primitive_value!(get_i64_value, Int64Type, i64);
+get_primitive_value!(get_u8_value, UInt8Type, u8);
+get_primitive_value!(get_u16_value, UInt16Type, u16);
 get_primitive_value!(get_u32_value, UInt32Type, u32);
 get_primitive_value!(get_u64_value, UInt64Type, u64);
 get_primitive_value!(get_f32_value, Float32Type, f32);
 get_primitive_value!(get_f64_value, Float64Type, f64);
 
+fn get_timestamp_value(arr: &Arc<dyn Array>, idx: usize) -> PgWireResult<NaiveDateTime> {
+    let timestamp = match arr.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => arr
+            .as_primitive::<TimestampSecondType>()
+            .value_as_datetime(idx),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => arr
+            .as_primitive::<TimestampMillisecondType>()
+            .value_as_datetime(idx),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => arr
+            .as_primitive::<TimestampMicrosecondType>()
+            .value_as_datetime(idx),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => arr
+            .as_primitive::<TimestampNanosecondType>()
+            .value_as_datetime(idx),
+        _ => None,
+    };
+    timestamp.ok_or_else(|| {
+        PgWireError::ApiError(format!("timestamp of {} is out of range", arr.data_type()).into())
+    })
+}
+
+fn get_list_value(arr: &Arc<dyn Array>, idx: usize) -> PgWireResult<ArrayRef> {
+    as_list_value(arr, idx).ok_or_else(|| {
+        PgWireError::ApiError(format!("expected a list array, got {}", arr.data_type()).into())
+    })
+}
+
+fn as_list_value(arr: &Arc<dyn Array>, idx: usize) -> Option<ArrayRef> {
+    match arr.data_type() {
+        DataType::List(_) => Some(arr.as_list::<i32>().value(idx)),
+        DataType::LargeList(_) => Some(arr.as_list::<i64>().value(idx)),
+        DataType::FixedSizeList(_, _) => Some(arr.as_fixed_size_list().value(idx)),
+        _ => None,
+    }
+}
+
+/// Formats the list as a postgres array literal, quoting every element.
+fn format_pg_array(values: &ArrayRef) -> PgWireResult<String> {
+    let formatter = ArrayFormatter::try_new(values.as_ref(), &FormatOptions::default())
+        .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+
+    let mut literal = String::from("{");
+    for idx in 0..values.len() {
+        if idx > 0 {
+            literal.push(',');
+        }
+        if values.is_null(idx) {
+            literal.push_str("NULL");
+            continue;
+        }
+
+        let element = match as_list_value(values, idx) {
+            Some(nested) => format_pg_array(&nested)?,
+            None => formatter.value(idx).to_string(),
+        };
+        literal.push('"');
+        for c in element.chars() {
+            if c == '"' || c == '\\' {
+                literal.push('\\');
+            }
+            literal.push(c);
+        }
+        literal.push('"');
+    }
+    literal.push('}');
+
+    Ok(literal)
+}
+
+fn format_json(arr: &Arc<dyn Array>, idx: usize) -> PgWireResult<String> {
+    let value = array_to_json_array(arr.slice(idx, 1).as_ref())
+        .map_err(|e| PgWireError::ApiError(Box::new(e)))?;
+    Ok(value[0].to_string())
+}
+
 fn get_utf8_value(arr: &Arc<dyn Array>, idx: usize) -> &str {
     arr.as_any()
         .downcast_ref::<StringArray>()
@@ -309,27 +517,83 @@ fn get_large_binary_value(arr: &Arc<dyn Array>, idx: usize) -> &[u8] {
         .value(idx)
 }
 
+/// Returns the value at `idx` of the array, or `None` if it is null.
+fn non_null<T>(arr: &Arc<dyn Array>, idx: usize, value: impl FnOnce() -> T) -> Option<T> {
+    if arr.is_null(idx) {
+        None
+    } else {
+        Some(value())
+    }
+}
+
 fn encode_value(
     encoder: &mut DataRowEncoder,
     arr: &Arc<dyn Array>,
     idx: usize,
 ) -> PgWireResult<()> {
     match arr.data_type() {
-        DataType::Boolean => encoder.encode_field(&get_bool_value(arr, idx))?,
-        DataType::Int8 => encoder.encode_field(&get_i8_value(arr, idx))?,
-        DataType::Int16 => encoder.encode_field(&get_i16_value(arr, idx))?,
-        DataType::Int32 => encoder.encode_field(&get_i32_value(arr, idx))?,
-        DataType::Int64 => encoder.encode_field(&get_i64_value(arr, idx))?,
-        DataType::UInt32 => encoder.encode_field(&get_u32_value(arr, idx))?,
-        DataType::UInt64 => encoder.encode_field(&(get_u64_value(arr, idx) as i64))?,
-        DataType::Float32 => encoder.encode_field(&get_f32_value(arr, idx))?,
-        DataType::Float64 => encoder.encode_field(&get_f64_value(arr, idx))?,
-        DataType::Utf8 => encoder.encode_field(&get_utf8_value(arr, idx))?,
-        DataType::LargeUtf8 => encoder.encode_field(&get_large_utf8_value(arr, idx))?,
-        DataType::Binary => encoder.encode_field(&get_binary_value(arr, idx))?,
-        DataType::LargeBinary => encoder.encode_field(&get_large_binary_value(arr, idx))?,
-        DataType::Date64 => encoder.encode_field(&get_date64_value(arr, idx))?,
-        DataType::Date32 => encoder.encode_field(&get_date32_value(arr, idx))?,
+        DataType::Null => encoder.encode_field(&None::<i8>)?,
+        DataType::Boolean => {
+            encoder.encode_field(&non_null(arr, idx, || get_bool_value(arr, idx)))?
+        }
+        DataType::Int8 => encoder.encode_field(&non_null(arr, idx, || get_i8_value(arr, idx)))?,
+        DataType::Int16 => encoder.encode_field(&non_null(arr, idx, || get_i16_value(arr, idx)))?,
+        DataType::Int32 => encoder.encode_field(&non_null(arr, idx, || get_i32_value(arr, idx)))?,
+        DataType::Int64 => encoder.encode_field(&non_null(arr, idx, || get_i64_value(arr, idx)))?,
+        DataType::UInt8 => {
+            encoder.encode_field(&non_null(arr, idx, || i16::from(get_u8_value(arr, idx))))?
+        }
+        DataType::UInt16 => {
+            encoder.encode_field(&non_null(arr, idx, || i32::from(get_u16_value(arr, idx))))?
+        }
+        DataType::UInt32 => {
+            encoder.encode_field(&non_null(arr, idx, || i64::from(get_u32_value(arr, idx))))?
+        }
+        DataType::UInt64 => {
+            encoder.encode_field(&non_null(arr, idx, || get_u64_value(arr, idx) as i64))?
+        }
+        DataType::Float32 => {
+            encoder.encode_field(&non_null(arr, idx, || get_f32_value(arr, idx)))?
+        }
+        DataType::Float64 => {
+            encoder.encode_field(&non_null(arr, idx, || get_f64_value(arr, idx)))?
+        }
+        DataType::Utf8 => encoder.encode_field(&non_null(arr, idx, || get_utf8_value(arr, idx)))?,
+        DataType::LargeUtf8 => {
+            encoder.encode_field(&non_null(arr, idx, || get_large_utf8_value(arr, idx)))?
+        }
+        DataType::Binary => {
+            encoder.encode_field(&non_null(arr, idx, || get_binary_value(arr, idx)))?
+        }
+        DataType::LargeBinary => {
+            encoder.encode_field(&non_null(arr, idx, || get_large_binary_value(arr, idx)))?
+        }
+        DataType::Date64 => {
+            encoder.encode_field(&non_null(arr, idx, || get_date64_value(arr, idx)))?
+        }
+        DataType::Date32 => {
+            encoder.encode_field(&non_null(arr, idx, || get_date32_value(arr, idx)))?
+        }
+        DataType::Timestamp(_, None) => encoder
+            .encode_field(&non_null(arr, idx, || get_timestamp_value(arr, idx)).transpose()?)?,
+        DataType::Timestamp(_, Some(_)) => encoder.encode_field(
+            &non_null(arr, idx, || get_timestamp_value(arr, idx))
+                .transpose()?
+                .map(|timestamp| Utc.from_utc_datetime(&timestamp)),
+        )?,
+        DataType::Decimal128(_, _) => encoder.encode_field(&non_null(arr, idx, || {
+            arr.as_primitive::<Decimal128Type>().value_as_string(idx)
+        }))?,
+        DataType::Decimal256(_, _) => encoder.encode_field(&non_null(arr, idx, || {
+            arr.as_primitive::<Decimal256Type>().value_as_string(idx)
+        }))?,
+        DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _) => encoder
+            .encode_field(
+                &non_null(arr, idx, || format_pg_array(&get_list_value(arr, idx)?)).transpose()?,
+            )?,
+        DataType::Struct(_) => {
+            encoder.encode_field(&non_null(arr, idx, || format_json(arr, idx)).transpose()?)?
+        }
         _ => {
             return Err(PgWireError::UserError(Box::new(ErrorInfo::new(
                 "ERROR".to_owned(),
@@ -344,3 +608,170 @@ fn encode_value(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use datafusion::arrow::array::{
+        Decimal128Array, Int64Array, ListArray, StructArray, TimestampMicrosecondArray, UInt32Array,
+    };
+    use datafusion::arrow::datatypes::Field;
+    use pgwire::messages::Message;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Encodes every row of the batch, returning the raw value of each field.
+    fn encode_rows(rb: &RecordBatch, format: &Format) -> Vec<Vec<Option<Vec<u8>>>> {
+        let fields = Arc::new(into_pg_fields(&rb.schema(), format).unwrap());
+
+        (0..rb.num_rows())
+            .map(|row| {
+                let mut buf = BytesMut::new();
+                encode_row(row, rb, &fields)
+                    .unwrap()
+                    .encode(&mut buf)
+                    .unwrap();
+                decode_data_row(&buf)
+            })
+            .collect()
+    }
+
+    fn decode_data_row(buf: &[u8]) -> Vec<Option<Vec<u8>>> {
+        assert_eq!(buf[0], b'D');
+        let field_count = i16::from_be_bytes(buf[5..7].try_into().unwrap());
+        let mut buf = &buf[7..];
+        (0..field_count)
+            .map(|_| {
+                let len = i32::from_be_bytes(buf[..4].try_into().unwrap());
+                buf = &buf[4..];
+                if len < 0 {
+                    return None;
+                }
+                let (value, rest) = buf.split_at(len as usize);
+                buf = rest;
+                Some(value.to_vec())
+            })
+            .collect()
+    }
+
+    fn text(value: &str) -> Option<Vec<u8>> {
+        Some(value.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn encode_nulls_of_every_type() {
+        let list = ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+            Some(vec![Some(1), None]),
+            None,
+        ]);
+        let structs = StructArray::try_new(
+            vec![Field::new("a", DataType::Int64, true)].into(),
+            vec![Arc::new(Int64Array::from(vec![Some(1), Some(2)])) as ArrayRef],
+            Some(vec![true, false].into()),
+        )
+        .unwrap();
+        let rb = RecordBatch::try_from_iter(vec![
+            (
+                "timestamp",
+                Arc::new(TimestampMicrosecondArray::from(vec![Some(0), None])) as ArrayRef,
+            ),
+            (
+                "decimal",
+                Arc::new(
+                    Decimal128Array::from(vec![Some(12345), None])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ) as ArrayRef,
+            ),
+            ("list", Arc::new(list) as ArrayRef),
+            ("struct", Arc::new(structs) as ArrayRef),
+        ])
+        .unwrap();
+
+        let rows = encode_rows(&rb, &Format::UnifiedText);
+        assert!(rows[0][0].is_some());
+        assert_eq!(
+            rows[0][1..],
+            vec![text("123.45"), text(r#"{"1",NULL}"#), text(r#"{"a":1}"#),]
+        );
+        assert_eq!(rows[1], vec![None, None, None, None]);
+    }
+
+    #[test]
+    fn encode_binary_only_for_supported_types() {
+        let rb = RecordBatch::try_from_iter(vec![
+            (
+                "unsigned",
+                Arc::new(UInt32Array::from(vec![u32::MAX])) as ArrayRef,
+            ),
+            (
+                "decimal",
+                Arc::new(
+                    Decimal128Array::from(vec![12345])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(into_pg_type(&DataType::UInt32).unwrap(), Type::INT8);
+        assert_eq!(
+            into_pg_field_format(&DataType::UInt32, FieldFormat::Binary),
+            FieldFormat::Binary
+        );
+        assert_eq!(
+            into_pg_field_format(&DataType::Decimal128(10, 2), FieldFormat::Binary),
+            FieldFormat::Text
+        );
+
+        let rows = encode_rows(&rb, &Format::UnifiedBinary);
+        assert_eq!(
+            rows[0],
+            vec![
+                Some(i64::from(u32::MAX).to_be_bytes().to_vec()),
+                text("123.45")
+            ]
+        );
+    }
+
+    async fn connected_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn detects_ssl_request_sent_in_parts() {
+        let (mut client, server) = connected_pair().await;
+        client.write_all(&SSL_REQUEST[..3]).await.unwrap();
+
+        let check = tokio::spawn(async move { is_ssl_request(&server).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.write_all(&SSL_REQUEST[3..]).await.unwrap();
+
+        assert!(check.await.unwrap().unwrap());
+    }
+
+    #[tokio::test]
+    async fn rejects_plaintext_startup() {
+        let (mut client, server) = connected_pair().await;
+        // StartupMessage with protocol version 3.0
+        client.write_all(&[0, 0, 0, 8, 0, 3, 0, 0]).await.unwrap();
+        assert!(!is_ssl_request(&server).await.unwrap());
+
+        reject_plaintext_connection(server).await.unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response[0], b'E');
+        assert_eq!(
+            u32::from_be_bytes(response[1..5].try_into().unwrap()) as usize,
+            response.len() - 1
+        );
+        assert!(response.windows(5).any(|w| w == b"28000"));
+    }
+}
@@ -9,11 +9,14 @@
 // by the Apache License, Version 2.0.
 
 use crate::pgwire_server::{spawn_connection, HandlerFactory};
+use crate::tls::pgwire_server_config;
 use codederror::CodedError;
 use restate_core::cancellation_watcher;
+use restate_core::network::tls::{ReloadableTlsConfig, TlsError};
 use restate_storage_query_datafusion::context::QueryContext;
 
-use restate_types::config::QueryEngineOptions;
+use pgwire_tokio_rustls::TlsAcceptor;
+use restate_types::config::{AdminAuthenticationOptions, QueryEngineOptions, TlsOptions};
 use restate_types::errors::GenericError;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
@@ -29,30 +32,69 @@ pub enum Error {
     )]
     #[code(unknown)]
     AddrInUse(SocketAddr),
+    #[error(
+        "the psql service doesn't authenticate clients and must be bound to a loopback address while 'admin.authentication' is enabled, but 'admin.query-engine.pgsql-bind-address' is '{0}'"
+    )]
+    #[code(unknown)]
+    UnauthenticatedBindAddress(SocketAddr),
+    #[error("failed loading the certificates specified in 'admin.query-engine.pgsql-tls': {0}")]
+    #[code(unknown)]
+    Tls(#[from] TlsError),
     #[error("error: {0:?}")]
     #[code(unknown)]
     Other(#[from] GenericError),
 }
 
+/// Serves the storage query engine over the Postgres wire protocol.
+///
+/// The pgwire handler doesn't authenticate clients. When admin authentication is enabled, the
+/// service therefore refuses to start unless it is bound to a loopback address.
 pub struct PostgresQueryService {
     pub bind_address: SocketAddr,
+    pub tls: Option<TlsOptions>,
     pub query_context: QueryContext,
+    pub require_loopback: bool,
 }
 
 impl PostgresQueryService {
-    pub fn from_options(options: &QueryEngineOptions, query_context: QueryContext) -> Self {
+    pub fn from_options(
+        options: &QueryEngineOptions,
+        authentication: Option<&AdminAuthenticationOptions>,
+        query_context: QueryContext,
+    ) -> Self {
         Self {
             bind_address: options.pgsql_bind_address,
+            tls: options.pgsql_tls.clone(),
             query_context,
+            require_loopback: authentication.is_some(),
         }
     }
 
     pub async fn run(self) -> anyhow::Result<()> {
         let PostgresQueryService {
             bind_address,
+            tls,
             query_context,
+            require_loopback,
         } = self;
 
+        if require_loopback && !bind_address.ip().is_loopback() {
+            return Err(Error::UnauthenticatedBindAddress(bind_address).into());
+        }
+
+        let tls = tls
+            .map(|tls_options| {
+                let tls = ReloadableTlsConfig::load(&tls_options, pgwire_server_config)?;
+                tls.reload_on_change(
+                    "pgsql-tls-reload",
+                    |config| config.admin.query_engine.pgsql_tls.as_ref(),
+                    pgwire_server_config,
+                )?;
+                Ok::<_, TlsError>(tls)
+            })
+            .transpose()
+            .map_err(Error::Tls)?;
+
         let listener = TcpListener::bind(&bind_address).await.map_err(|e| {
             if e.kind() == ErrorKind::AddrInUse {
                 Error::AddrInUse(bind_address)
@@ -69,7 +111,11 @@ impl PostgresQueryService {
             select! {
                 incoming_socket = listener.accept() => {
                     match incoming_socket {
-                        Ok((stream, addr)) => spawn_connection(factory.clone(), stream, addr),
+                        Ok((stream, addr)) => {
+                            let tls_acceptor =
+                                tls.as_ref().map(|tls| TlsAcceptor::from(tls.current()));
+                            spawn_connection(factory.clone(), stream, addr, tls_acceptor)
+                        }
                         Err(err) => {
                             warn!("Failed to accept storage query connection: {err}");
                         }
@@ -0,0 +1,54 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use pgwire_tokio_rustls::rustls::pki_types::{
+    CertificateDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer, PrivateSec1KeyDer,
+};
+use pgwire_tokio_rustls::rustls::server::WebPkiClientVerifier;
+use pgwire_tokio_rustls::rustls::{RootCertStore, ServerConfig};
+
+use restate_core::network::tls::{PrivateKeyDer, TlsError, TlsMaterial};
+
+/// Builds the server configuration for pgwire, which uses a different rustls version than the
+/// other servers.
+pub(crate) fn pgwire_server_config(material: TlsMaterial) -> Result<ServerConfig, TlsError> {
+    let cert_chain = material
+        .cert_chain
+        .into_iter()
+        .map(CertificateDer::from)
+        .collect();
+    let private_key = match material.private_key {
+        PrivateKeyDer::Pkcs8(der) => PrivatePkcs8KeyDer::from(der).into(),
+        PrivateKeyDer::Pkcs1(der) => PrivatePkcs1KeyDer::from(der).into(),
+        PrivateKeyDer::Sec1(der) => PrivateSec1KeyDer::from(der).into(),
+    };
+
+    let builder = ServerConfig::builder();
+    let builder = if let Some(client_ca) = material.client_ca {
+        let mut roots = RootCertStore::empty();
+        for ca in client_ca {
+            roots
+                .add(CertificateDer::from(ca))
+                .map_err(|e| TlsError::Config(e.to_string()))?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots))
+            .build()
+            .map_err(|e| TlsError::Config(e.to_string()))?;
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    builder
+        .with_single_cert(cert_chain, private_key)
+        .map_err(|e| TlsError::Config(e.to_string()))
+}
@@ -14,7 +14,7 @@ mod processor;
 mod tracer;
 
 use crate::pretty::PrettyFields;
-use crate::processor::ResourceModifyingSpanProcessor;
+use crate::processor::{ResourceModifyingSpanProcessor, SpanEventsProcessor};
 use crate::tracer::SpanModifyingTracer;
 use opentelemetry::trace::{TraceError, TracerProvider};
 use opentelemetry::KeyValue;
@@ -86,7 +86,8 @@ where
     ]);
 
     // the following logic is based on `opentelemetry_otlp::span::build_batch_with_exporter`
-    // but also injecting ResourceModifyingSpanProcessor around the BatchSpanProcessor
+    // but also injecting ResourceModifyingSpanProcessor and SpanEventsProcessor around the
+    // BatchSpanProcessor
 
     let mut tracer_provider_builder = opentelemetry_sdk::trace::TracerProvider::builder()
         .with_config(opentelemetry_sdk::trace::config().with_resource(resource));
@@ -98,15 +99,16 @@ where
                 .with_endpoint(endpoint),
         )
         .build_span_exporter()?;
-        tracer_provider_builder =
-            tracer_provider_builder.with_span_processor(ResourceModifyingSpanProcessor::new(
+        tracer_provider_builder = tracer_provider_builder.with_span_processor(
+            ResourceModifyingSpanProcessor::new(SpanEventsProcessor::new(
                 BatchSpanProcessor::builder(exporter, opentelemetry_sdk::runtime::Tokio).build(),
-            ));
+            )),
+        );
     }
 
     if let Some(path) = &common_opts.tracing_json_path {
-        tracer_provider_builder =
-            tracer_provider_builder.with_span_processor(ResourceModifyingSpanProcessor::new(
+        tracer_provider_builder = tracer_provider_builder.with_span_processor(
+            ResourceModifyingSpanProcessor::new(SpanEventsProcessor::new(
                 BatchSpanProcessor::builder(
                     JaegerJsonExporter::new(
                         path.into(),
@@ -117,7 +119,8 @@ where
                     opentelemetry_sdk::runtime::Tokio,
                 )
                 .build(),
-            ));
+            )),
+        );
     }
 
     let provider = tracer_provider_builder.build();
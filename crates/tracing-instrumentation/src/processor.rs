@@ -8,13 +8,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use opentelemetry::trace::TraceResult;
+use opentelemetry::trace::{Event, SpanId, TraceId, TraceResult};
 use opentelemetry::{Context, Key, KeyValue};
 use opentelemetry_sdk::export::trace::SpanData;
 use opentelemetry_sdk::trace::Span;
 use opentelemetry_sdk::Resource;
 use opentelemetry_semantic_conventions::resource::SERVICE_NAME;
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 /// `RPC_SERVICE` is used to override `service.name` on the `SpanBuilder`
 const RPC_SERVICE: Key = Key::from_static_str("rpc.service");
@@ -62,3 +65,403 @@ impl<T: opentelemetry_sdk::trace::SpanProcessor> opentelemetry_sdk::trace::SpanP
         self.inner.shutdown()
     }
 }
+
+/// `SPAN_EVENT` marks the spans which must be exported as an event of their parent span
+const SPAN_EVENT: Key = Key::from_static_str("restate.internal.span_event");
+
+/// Maximum number of parent spans with buffered events. When exceeded, the events of the parent
+/// span buffered first are exported as its child spans.
+const MAX_BUFFERED_PARENT_SPANS: usize = 10_000;
+/// Maximum number of buffered events per parent span. Further events are counted as dropped.
+const MAX_EVENTS_PER_SPAN: usize = 1024;
+/// Maximum time the events wait for their parent span to end. Afterward, they are exported as its
+/// child spans, as the parent span might end much later (e.g. long-running workflows) or on
+/// another node.
+const MAX_BUFFERING_DURATION: Duration = Duration::from_secs(60);
+
+type SpanKey = (TraceId, SpanId);
+
+#[derive(Debug)]
+struct ParentSpanEvents {
+    /// Key of the parent span in [`BufferedEvents::buffering_order`]
+    buffered_at: (Instant, u64),
+    spans: Vec<SpanData>,
+    dropped_count: u32,
+}
+
+#[derive(Debug, Default)]
+struct BufferedEvents {
+    events: HashMap<SpanKey, ParentSpanEvents>,
+    /// Parent spans ordered by the time their first event was buffered
+    buffering_order: BTreeMap<(Instant, u64), SpanKey>,
+    next_sequence_number: u64,
+}
+
+impl BufferedEvents {
+    /// Buffers the event span until its parent span ends, returning the event spans which must be
+    /// exported as regular spans because their parent span didn't end in time.
+    fn push(&mut self, parent: SpanKey, span: SpanData, now: Instant) -> Vec<SpanData> {
+        let mut expired = self.take_expired(now);
+
+        if !self.events.contains_key(&parent) {
+            if self.events.len() >= MAX_BUFFERED_PARENT_SPANS {
+                if let Some((_, oldest)) = self.buffering_order.pop_first() {
+                    expired.extend(
+                        self.events
+                            .remove(&oldest)
+                            .map(|e| e.spans)
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            let buffered_at = (now, self.next_sequence_number);
+            self.next_sequence_number += 1;
+            self.buffering_order.insert(buffered_at, parent);
+            self.events.insert(
+                parent,
+                ParentSpanEvents {
+                    buffered_at,
+                    spans: vec![],
+                    dropped_count: 0,
+                },
+            );
+        }
+
+        let parent_events = self
+            .events
+            .get_mut(&parent)
+            .expect("parent span was inserted above");
+        if parent_events.spans.len() < MAX_EVENTS_PER_SPAN {
+            parent_events.spans.push(span);
+        } else {
+            parent_events.dropped_count += 1;
+        }
+
+        expired
+    }
+
+    /// Returns the buffered event spans of the given parent span, and the number of dropped ones.
+    fn take(&mut self, parent: &SpanKey) -> Option<(Vec<SpanData>, u32)> {
+        let parent_events = self.events.remove(parent)?;
+        self.buffering_order.remove(&parent_events.buffered_at);
+        Some((parent_events.spans, parent_events.dropped_count))
+    }
+
+    /// Returns the event spans which waited longer than [`MAX_BUFFERING_DURATION`] for their
+    /// parent span.
+    fn take_expired(&mut self, now: Instant) -> Vec<SpanData> {
+        let mut expired = vec![];
+        while let Some(entry) = self.buffering_order.first_entry() {
+            if now.saturating_duration_since(entry.key().0) < MAX_BUFFERING_DURATION {
+                break;
+            }
+            let parent = entry.remove();
+            expired.extend(
+                self.events
+                    .remove(&parent)
+                    .map(|e| e.spans)
+                    .unwrap_or_default(),
+            );
+        }
+        expired
+    }
+
+    fn take_all(&mut self) -> Vec<SpanData> {
+        self.buffering_order.clear();
+        self.events
+            .drain()
+            .flat_map(|(_, parent_events)| parent_events.spans)
+            .collect()
+    }
+}
+
+/// `SpanEventsProcessor` wraps a `opentelemetry::sdk::trace::SpanProcessor` in order to export the
+/// spans marked with `restate.internal.span_event` as events of their parent span. Because the
+/// parent span might end after its children (e.g. the invocation span is created only once the
+/// invocation completes), the events are buffered until the parent span ends, for at most
+/// [`MAX_BUFFERING_DURATION`]. Events whose parent span doesn't end in time are exported as its
+/// child spans instead.
+#[derive(Debug)]
+pub(crate) struct SpanEventsProcessor<T> {
+    inner: T,
+    buffered_events: Mutex<BufferedEvents>,
+}
+
+impl<T> SpanEventsProcessor<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        SpanEventsProcessor {
+            inner,
+            buffered_events: Default::default(),
+        }
+    }
+
+    fn buffered_events(&self) -> MutexGuard<'_, BufferedEvents> {
+        self.buffered_events
+            .lock()
+            .expect("buffered events lock must not be poisoned")
+    }
+}
+
+impl<T: opentelemetry_sdk::trace::SpanProcessor> opentelemetry_sdk::trace::SpanProcessor
+    for SpanEventsProcessor<T>
+{
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, data: SpanData) {
+        let mut data = data;
+
+        if data.attributes.iter().any(|kv| kv.key == SPAN_EVENT) {
+            data.attributes.retain(|kv| kv.key != SPAN_EVENT);
+            let parent = (data.span_context.trace_id(), data.parent_span_id);
+            let expired = self.buffered_events().push(parent, data, Instant::now());
+            for span in expired {
+                self.inner.on_end(span);
+            }
+            return;
+        }
+
+        let (buffered, expired) = {
+            let mut buffered_events = self.buffered_events();
+            (
+                buffered_events.take(&(data.span_context.trace_id(), data.span_context.span_id())),
+                buffered_events.take_expired(Instant::now()),
+            )
+        };
+        if let Some((spans, dropped_count)) = buffered {
+            data.events.events.extend(
+                spans
+                    .into_iter()
+                    .map(|span| Event::new(span.name, span.start_time, span.attributes, 0)),
+            );
+            data.events.dropped_count += dropped_count;
+        }
+
+        self.inner.on_end(data);
+        for span in expired {
+            self.inner.on_end(span);
+        }
+    }
+
+    fn force_flush(&self) -> TraceResult<()> {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&mut self) -> TraceResult<()> {
+        let buffered = self
+            .buffered_events
+            .get_mut()
+            .expect("buffered events lock must not be poisoned")
+            .take_all();
+        for span in buffered {
+            self.inner.on_end(span);
+        }
+        self.inner.shutdown()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::time::SystemTime;
+
+    use opentelemetry::trace::{SpanContext, SpanKind, Status, TraceFlags, TraceState};
+    use opentelemetry::InstrumentationLibrary;
+    use opentelemetry_sdk::trace::SpanProcessor;
+
+    const TRACE_ID: TraceId = TraceId::from_u128(1);
+    const PARENT_SPAN_ID: SpanId = SpanId::from_u64(1);
+
+    #[derive(Debug, Clone, Default)]
+    struct CollectingProcessor(Arc<Mutex<Vec<SpanData>>>);
+
+    impl CollectingProcessor {
+        fn take(&self) -> Vec<SpanData> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    impl SpanProcessor for CollectingProcessor {
+        fn on_start(&self, _: &mut Span, _: &Context) {}
+
+        fn on_end(&self, span: SpanData) {
+            self.0.lock().unwrap().push(span)
+        }
+
+        fn force_flush(&self) -> TraceResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&mut self) -> TraceResult<()> {
+            Ok(())
+        }
+    }
+
+    fn span(span_id: u64, parent_span_id: SpanId, name: &'static str, event: bool) -> SpanData {
+        let mut attributes = vec![KeyValue::new("restate.journal.index", 1)];
+        if event {
+            attributes.push(KeyValue::new(SPAN_EVENT, true));
+        }
+        SpanData {
+            span_context: SpanContext::new(
+                TRACE_ID,
+                SpanId::from_u64(span_id),
+                TraceFlags::SAMPLED,
+                false,
+                TraceState::default(),
+            ),
+            parent_span_id,
+            span_kind: SpanKind::Internal,
+            name: name.into(),
+            start_time: SystemTime::UNIX_EPOCH,
+            end_time: SystemTime::UNIX_EPOCH,
+            attributes,
+            dropped_attributes_count: 0,
+            events: Default::default(),
+            links: Default::default(),
+            status: Status::Unset,
+            resource: Cow::Owned(Resource::empty()),
+            instrumentation_lib: InstrumentationLibrary::default(),
+        }
+    }
+
+    fn event_span(span_id: u64, parent: u64) -> SpanData {
+        span(span_id, SpanId::from_u64(parent), "sleep", true)
+    }
+
+    #[test]
+    fn events_are_exported_with_their_parent_span() {
+        let inner = CollectingProcessor::default();
+        let processor = SpanEventsProcessor::new(inner.clone());
+
+        processor.on_end(span(2, PARENT_SPAN_ID, "sleep", true));
+        processor.on_end(span(3, PARENT_SPAN_ID, "get_state", true));
+        assert!(inner.take().is_empty());
+
+        processor.on_end(span(1, SpanId::INVALID, "invoke", false));
+        let exported = inner.take();
+        assert_eq!(exported.len(), 1);
+        let invocation_span = &exported[0];
+        assert_eq!(invocation_span.name, "invoke");
+        let names: Vec<_> = invocation_span
+            .events
+            .events
+            .iter()
+            .map(|event| event.name.clone())
+            .collect();
+        assert_eq!(names, vec!["sleep", "get_state"]);
+        assert!(invocation_span.events.events[0]
+            .attributes
+            .iter()
+            .all(|kv| kv.key != SPAN_EVENT));
+        assert_eq!(invocation_span.events.dropped_count, 0);
+    }
+
+    #[test]
+    fn events_over_the_limit_are_counted_as_dropped() {
+        let inner = CollectingProcessor::default();
+        let processor = SpanEventsProcessor::new(inner.clone());
+
+        for i in 0..MAX_EVENTS_PER_SPAN as u64 + 3 {
+            processor.on_end(event_span(i + 2, 1));
+        }
+        processor.on_end(span(1, SpanId::INVALID, "invoke", false));
+
+        let exported = inner.take();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].events.events.len(), MAX_EVENTS_PER_SPAN);
+        assert_eq!(exported[0].events.dropped_count, 3);
+    }
+
+    #[test]
+    fn oldest_parent_span_events_are_exported_as_spans_when_full() {
+        let now = Instant::now();
+        let mut buffered_events = BufferedEvents::default();
+
+        for parent in 0..MAX_BUFFERED_PARENT_SPANS as u64 {
+            assert!(buffered_events
+                .push(
+                    (TRACE_ID, SpanId::from_u64(parent + 1)),
+                    event_span(1, parent + 1),
+                    now
+                )
+                .is_empty());
+        }
+        // The parent span buffered first is evicted
+        let evicted = buffered_events.push(
+            (TRACE_ID, SpanId::from_u64(u64::MAX)),
+            event_span(1, u64::MAX),
+            now,
+        );
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].parent_span_id, SpanId::from_u64(1));
+        assert!(buffered_events
+            .take(&(TRACE_ID, SpanId::from_u64(1)))
+            .is_none());
+
+        // Taking the events of a parent span removes them from the buffering order too
+        assert!(buffered_events
+            .take(&(TRACE_ID, SpanId::from_u64(2)))
+            .is_some());
+        assert_eq!(buffered_events.events.len(), MAX_BUFFERED_PARENT_SPANS - 1);
+        assert_eq!(
+            buffered_events.buffering_order.len(),
+            MAX_BUFFERED_PARENT_SPANS - 1
+        );
+    }
+
+    #[test]
+    fn expired_events_are_exported_as_spans() {
+        let now = Instant::now();
+        let mut buffered_events = BufferedEvents::default();
+
+        assert!(buffered_events
+            .push((TRACE_ID, SpanId::from_u64(1)), event_span(2, 1), now)
+            .is_empty());
+        assert!(buffered_events
+            .push(
+                (TRACE_ID, SpanId::from_u64(10)),
+                event_span(11, 10),
+                now + MAX_BUFFERING_DURATION / 2
+            )
+            .is_empty());
+        // Events of an already buffered parent span don't extend its deadline
+        assert!(buffered_events
+            .push(
+                (TRACE_ID, SpanId::from_u64(1)),
+                event_span(3, 1),
+                now + MAX_BUFFERING_DURATION / 2
+            )
+            .is_empty());
+
+        let expired = buffered_events.take_expired(now + MAX_BUFFERING_DURATION);
+        let span_ids: Vec<_> = expired
+            .iter()
+            .map(|span| span.span_context.span_id())
+            .collect();
+        assert_eq!(span_ids, vec![SpanId::from_u64(2), SpanId::from_u64(3)]);
+        assert!(buffered_events
+            .take(&(TRACE_ID, SpanId::from_u64(1)))
+            .is_none());
+        assert!(buffered_events
+            .take(&(TRACE_ID, SpanId::from_u64(10)))
+            .is_some());
+    }
+
+    #[test]
+    fn buffered_events_are_exported_as_spans_on_shutdown() {
+        let inner = CollectingProcessor::default();
+        let mut processor = SpanEventsProcessor::new(inner.clone());
+
+        processor.on_end(event_span(2, 1));
+        processor.shutdown().unwrap();
+
+        let exported = inner.take();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].span_context.span_id(), SpanId::from_u64(2));
+        assert!(exported[0].attributes.iter().all(|kv| kv.key != SPAN_EVENT));
+    }
+}
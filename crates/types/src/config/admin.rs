@@ -16,7 +16,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 use tokio::sync::Semaphore;
 
-use super::QueryEngineOptions;
+use super::{QueryEngineOptions, TlsOptions};
 
 /// # Admin server options
 #[serde_as]
@@ -31,6 +31,11 @@ pub struct AdminOptions {
     /// Address to bind for the Admin APIs.
     pub bind_address: SocketAddr,
 
+    /// # TLS
+    ///
+    /// If set, the Admin API serves HTTPS only.
+    pub tls: Option<TlsOptions>,
+
     /// # Concurrency limit
     ///
     /// Concurrency limit for the Admin APIs. Default is unlimited.
@@ -58,6 +63,13 @@ pub struct AdminOptions {
     /// can remove equal or more entries than this threshold. This prevents too many small trim
     /// operations.
     pub log_trim_threshold: u64,
+
+    /// # Authentication
+    ///
+    /// Authentication and role based access control for the Admin and Flight SQL storage query
+    /// APIs. If unset, every request is accepted. The psql service doesn't authenticate clients,
+    /// so when this is set `admin.query-engine.pgsql-bind-address` must be a loopback address.
+    pub authentication: Option<AdminAuthenticationOptions>,
 }
 
 impl AdminOptions {
@@ -79,6 +91,7 @@ impl Default for AdminOptions {
     fn default() -> Self {
         Self {
             bind_address: "0.0.0.0:9070".parse().unwrap(),
+            tls: None,
             // max is limited by Tower's LoadShedLayer.
             concurrent_api_requests_limit: None,
             query_engine: Default::default(),
@@ -86,6 +99,77 @@ impl Default for AdminOptions {
             // try to trim the log every hour
             log_trim_interval: Some(Duration::from_secs(60 * 60).into()),
             log_trim_threshold: 1000,
+            authentication: None,
         }
     }
 }
+
+/// # Admin authentication options
+///
+/// Requests must carry either a static token or a JWT as `Authorization: Bearer <token>` header.
+/// Each token grants a role: `viewer` can only read, `operator` can additionally cancel/kill
+/// invocations and manage services and subscriptions, `admin` can perform any operation, including
+/// registering and removing deployments and modifying the service state.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(default))]
+#[serde(rename_all = "kebab-case", default)]
+pub struct AdminAuthenticationOptions {
+    /// # Static tokens
+    ///
+    /// Static bearer tokens accepted by the Admin API.
+    pub tokens: Vec<AdminToken>,
+
+    /// # JWKS file
+    ///
+    /// Path to a local JSON Web Key Set file, containing the keys used to verify the JWTs.
+    /// If unset, JWTs are not accepted.
+    pub jwks_file: Option<PathBuf>,
+
+    /// # JWT issuer
+    ///
+    /// If set, the `iss` claim of the JWTs must match this value.
+    pub jwt_issuer: Option<String>,
+
+    /// # JWT audience
+    ///
+    /// If set, the `aud` claim of the JWTs must contain this value.
+    pub jwt_audience: Option<String>,
+
+    /// # Role claim
+    ///
+    /// The JWT claim containing the role, either as string or as array of strings. If more roles
+    /// are listed, the most privileged one is used. Default is `restate_role`.
+    pub role_claim: Option<String>,
+}
+
+impl AdminAuthenticationOptions {
+    pub fn role_claim(&self) -> &str {
+        self.role_claim.as_deref().unwrap_or("restate_role")
+    }
+}
+
+/// # Admin token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct AdminToken {
+    /// Name identifying the token owner in the logs.
+    pub name: String,
+    /// The token, to be sent as bearer token.
+    pub token: String,
+    /// The role granted by this token.
+    pub role: AdminRole,
+}
+
+/// # Admin role
+///
+/// Roles are ordered by privilege, each role includes the permissions of the previous ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum AdminRole {
+    Viewer,
+    Operator,
+    Admin,
+}
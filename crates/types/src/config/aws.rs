@@ -31,4 +31,10 @@ pub struct AwsOptions {
     /// https://docs.aws.amazon.com/IAM/latest/UserGuide/id_roles_create_for-user_externalid.html
     /// Can be overridden by the `AWS_EXTERNAL_ID` environment variable.
     pub aws_assume_role_external_id: Option<String>,
+
+    /// # Lambda endpoint URL
+    ///
+    /// Overrides the endpoint of the AWS Lambda API, for example to test against a local Lambda
+    /// runtime interface emulator such as "http://localhost:9000".
+    pub aws_lambda_endpoint_url: Option<String>,
 }
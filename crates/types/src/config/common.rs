@@ -24,7 +24,7 @@ use crate::net::{AdvertisedAddress, BindAddress};
 use crate::nodes_config::Role;
 use crate::PlainNodeId;
 
-use super::{AwsOptions, HttpOptions, PerfStatsLevel, RocksDbOptions};
+use super::{AwsOptions, HttpOptions, PerfStatsLevel, RocksDbOptions, TlsOptions};
 
 const DEFAULT_STORAGE_DIRECTORY: &str = "restate-data";
 
@@ -75,6 +75,13 @@ pub struct CommonOptions {
     #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub advertised_address: AdvertisedAddress,
 
+    /// # TLS
+    ///
+    /// If set, the Node server serves TLS only and the connections to other nodes and to the
+    /// metadata store use TLS, presenting the same certificate as client certificate. In this
+    /// case, the advertised addresses must use the `https` scheme.
+    pub tls: Option<TlsOptions>,
+
     /// # Partitions
     ///
     /// Number of partitions that will be provisioned during cluster bootstrap,
@@ -128,6 +135,14 @@ pub struct CommonOptions {
     /// Distributed tracing exporter filter.
     /// Check the [`RUST_LOG` documentation](https://docs.rs/tracing-subscriber/latest/tracing_subscriber/filter/struct.EnvFilter.html) for more details how to configure it.
     pub tracing_filter: String,
+
+    /// # Journal entries tracing
+    ///
+    /// Records the journal entries of the invocations (e.g. sleep, state get/set, run, awakeables)
+    /// in their traces, together with the entry index and the time at which the entry was
+    /// recorded. Higher verbosity increases the volume of the exported traces.
+    pub tracing_journal_entries: JournalEntryTracing,
+
     /// # Logging Filter
     ///
     /// Log filter configuration. Can be overridden by the `RUST_LOG` environment variable.
@@ -331,6 +346,7 @@ impl Default for CommonOptions {
                 .expect("valid metadata store address"),
             bind_address: "0.0.0.0:5122".parse().unwrap(),
             advertised_address: AdvertisedAddress::from_str("http://127.0.0.1:5122/").unwrap(),
+            tls: None,
             bootstrap_num_partitions: NonZeroU64::new(24).unwrap(),
             histogram_inactivity_timeout: None,
             disable_prometheus: false,
@@ -339,6 +355,7 @@ impl Default for CommonOptions {
             tracing_endpoint: None,
             tracing_json_path: None,
             tracing_filter: "info".to_owned(),
+            tracing_journal_entries: JournalEntryTracing::None,
             log_filter: "warn,restate=info".to_string(),
             log_format: Default::default(),
             log_disable_ansi_codes: false,
@@ -376,16 +393,42 @@ pub struct ServiceClientOptions {
 
     /// # Request identity private key PEM file
     ///
-    /// A path to a file, such as "/var/secrets/key.pem", which contains exactly one ed25519 private
-    /// key in PEM format. Such a file can be generated with `openssl genpkey -algorithm ed25519`.
-    /// If provided, this key will be used to attach JWTs to requests from this client which
+    /// A path to a file, such as "/var/secrets/key.pem", which contains one or more ed25519 private
+    /// keys in PEM format. Such a key can be generated with `openssl genpkey -algorithm ed25519`.
+    /// If provided, the first key will be used to attach JWTs to requests from this client which
     /// SDKs may optionally verify, proving that the caller is a particular Restate instance.
     ///
-    /// This file is currently only read on client creation, but this may change in future.
+    /// The public keys of all the keys in the file are published by the admin API at
+    /// `/.well-known/jwks.json`. To rotate the key, first append the new key to the file, then
+    /// move it to the front once the deployments know about it, and finally remove the old key.
+    /// The file is read again on every configuration update, and whenever it changes.
     /// Parsed public keys will be logged at INFO level in the same format that SDKs expect.
     pub request_identity_private_key_pem_file: Option<PathBuf>,
 }
 
+/// # Journal entries tracing
+#[derive(Debug, Clone, Copy, Hash, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum JournalEntryTracing {
+    /// # None
+    ///
+    /// Journal entries are not recorded in the traces.
+    #[default]
+    None,
+    /// # Events
+    ///
+    /// Every journal entry is recorded as an event of the invocation span. The entries of
+    /// invocations which don't complete within a minute on the same node are recorded as child
+    /// spans instead.
+    Events,
+    /// # Spans
+    ///
+    /// Every journal entry is recorded as a child span of the invocation span. The span of an
+    /// entry waiting for a completion, e.g. a sleep or a call, lasts until the entry is completed.
+    Spans,
+}
+
 /// # Log format
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[derive(Debug, Clone, Copy, Hash, Default, Serialize, Deserialize)]
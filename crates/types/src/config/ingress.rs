@@ -8,13 +8,15 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
 
-use super::KafkaClusterOptions;
+use super::{KafkaClusterOptions, TlsOptions};
 
 /// # Ingress options
 #[derive(Debug, Clone, Serialize, Deserialize, derive_builder::Builder)]
@@ -29,6 +31,11 @@ pub struct IngressOptions {
     /// The address to bind for the ingress.
     pub bind_address: SocketAddr,
 
+    /// # TLS
+    ///
+    /// If set, the ingress serves HTTPS only.
+    pub tls: Option<TlsOptions>,
+
     /// # Concurrency limit
     ///
     /// Local concurrency limit to use to limit the amount of concurrent requests. If exceeded,
@@ -36,6 +43,23 @@ pub struct IngressOptions {
     concurrent_api_requests_limit: Option<NonZeroUsize>,
 
     kafka_clusters: Vec<KafkaClusterOptions>,
+
+    /// # Rate limits
+    ///
+    /// Token bucket rate limits applied to the ingress requests, keyed by client IP, request header
+    /// or target service. Requests exceeding a limit are rejected with `429 Too Many Requests`.
+    pub rate_limits: Vec<IngressRateLimitRule>,
+
+    /// # Authentication
+    ///
+    /// Authentication and authorization of the ingress requests. If unset, every request is accepted.
+    pub authentication: Option<IngressAuthenticationOptions>,
+
+    /// # Callback allowed hosts
+    ///
+    /// Hosts to which the results of sends with the `x-restate-callback-url` header can be
+    /// delivered, optionally followed by `:<port>`. If empty, callback urls are rejected.
+    pub callback_allowed_hosts: Vec<String>,
 }
 
 impl IngressOptions {
@@ -58,9 +82,182 @@ impl Default for IngressOptions {
     fn default() -> Self {
         Self {
             bind_address: "0.0.0.0:8080".parse().unwrap(),
+            tls: None,
             // max is limited by Tower's LoadShedLayer.
             concurrent_api_requests_limit: None,
             kafka_clusters: Default::default(),
+            rate_limits: Default::default(),
+            authentication: None,
+            callback_allowed_hosts: Default::default(),
         }
     }
 }
+
+/// Returns whether the host and port of a callback url match one of the allowed hosts.
+pub fn is_callback_host_allowed(allowed_hosts: &[String], host: &str, port: u16) -> bool {
+    allowed_hosts.iter().any(|allowed_host| {
+        match allowed_host
+            .rsplit_once(':')
+            .and_then(|(allowed_host, allowed_port)| {
+                Some((allowed_host, allowed_port.parse::<u16>().ok()?))
+            }) {
+            Some((allowed_host, allowed_port)) => {
+                allowed_host.eq_ignore_ascii_case(host) && allowed_port == port
+            }
+            None => allowed_host.eq_ignore_ascii_case(host),
+        }
+    })
+}
+
+/// # Ingress rate limit rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct IngressRateLimitRule {
+    /// # Name
+    ///
+    /// Name of the rule, used as label of the rate limiting metrics.
+    pub name: String,
+
+    /// # Key
+    ///
+    /// What the requests are grouped by. Each distinct key gets its own token bucket.
+    pub key: RateLimitKey,
+
+    /// # Requests per second
+    ///
+    /// Rate at which the token bucket of each key is refilled.
+    pub requests_per_second: NonZeroU32,
+
+    /// # Burst
+    ///
+    /// Maximum number of requests accepted at once for a key. Default is `requests-per-second`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<NonZeroU32>,
+}
+
+impl IngressRateLimitRule {
+    pub fn burst(&self) -> NonZeroU32 {
+        self.burst.unwrap_or(self.requests_per_second)
+    }
+}
+
+/// # Rate limit key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RateLimitKey {
+    /// Group the requests by client IP address.
+    ClientIp,
+    /// Group the requests by the value of the given header, e.g. an API key. Requests without the
+    /// header are not limited by this rule.
+    Header { name: String },
+    /// Group the requests by target service.
+    Service,
+}
+
+/// # Ingress authentication options
+///
+/// Requests must carry either a static API key or a JWT as `Authorization: Bearer <token>` header.
+/// The authenticated principal is forwarded to the invoked service in the `x-restate-principal` header.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(default))]
+#[serde(rename_all = "kebab-case", default)]
+pub struct IngressAuthenticationOptions {
+    /// # API keys
+    ///
+    /// Static API keys accepted by the ingress, each one identifying a principal.
+    pub api_keys: Vec<IngressApiKey>,
+
+    /// # JWKS file
+    ///
+    /// Path to a local JSON Web Key Set file, containing the keys used to verify the JWTs.
+    /// If unset, JWTs are not accepted.
+    pub jwks_file: Option<PathBuf>,
+
+    /// # JWT issuer
+    ///
+    /// If set, the `iss` claim of the JWTs must match this value.
+    pub jwt_issuer: Option<String>,
+
+    /// # JWT audience
+    ///
+    /// If set, the `aud` claim of the JWTs must contain this value.
+    pub jwt_audience: Option<String>,
+
+    /// # Principal claim
+    ///
+    /// The JWT claim identifying the principal. Default is `sub`.
+    pub principal_claim: Option<String>,
+
+    /// # Allow rules
+    ///
+    /// Rules granting access to services and handlers. A request is allowed if at least one rule
+    /// matches both the invoked target and the principal. If no rule is configured, every
+    /// authenticated principal is allowed.
+    pub rules: Vec<IngressAllowRule>,
+}
+
+impl IngressAuthenticationOptions {
+    pub fn principal_claim(&self) -> &str {
+        self.principal_claim.as_deref().unwrap_or("sub")
+    }
+}
+
+/// # Ingress API key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct IngressApiKey {
+    /// Name of the principal authenticated by this key.
+    pub principal: String,
+    /// The key, to be sent as bearer token.
+    pub key: String,
+}
+
+/// # Ingress allow rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct IngressAllowRule {
+    /// # Service
+    ///
+    /// Name of the service this rule applies to, or `*` for every service. The built-in
+    /// awakeables API is exposed as handler `awakeables` of the service `restate`. Invocations
+    /// addressed by their id are checked against the service and handler they target.
+    pub service: String,
+
+    /// # Handler
+    ///
+    /// Name of the handler this rule applies to. If unset, the rule applies to every handler.
+    /// Workflow promises are exposed as handlers `promise:resolve`, `promise:reject` and
+    /// `promise:peek` of the workflow service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+
+    /// # Principals
+    ///
+    /// Principals granted by this rule. If empty, the rule doesn't restrict the principal name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub principals: Vec<String>,
+
+    /// # Claims
+    ///
+    /// JWT claims the principal must have. A claim matches if its value is equal to the
+    /// configured value, or contains it either as array element or as space separated item
+    /// (like the `scope` claim).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub claims: HashMap<String, String>,
+}
+
+impl IngressAllowRule {
+    pub fn matches_target(&self, service: &str, handler: Option<&str>) -> bool {
+        (self.service == "*" || self.service == service)
+            && match (&self.handler, handler) {
+                (None, _) => true,
+                (Some(rule_handler), Some(handler)) => rule_handler == handler,
+                (Some(_), None) => false,
+            }
+    }
+}
@@ -23,6 +23,7 @@ mod kafka;
 mod metadata_store;
 mod query_engine;
 mod rocksdb;
+mod tls;
 mod worker;
 
 pub use admin::*;
@@ -37,6 +38,7 @@ pub use kafka::*;
 pub use metadata_store::*;
 pub use query_engine::*;
 pub use rocksdb::*;
+pub use tls::*;
 pub use worker::*;
 
 use std::path::PathBuf;
@@ -16,6 +16,8 @@ use serde_with::serde_as;
 
 use restate_serde_util::NonZeroByteCount;
 
+use super::TlsOptions;
+
 /// # Storage query engine options
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, derive_builder::Builder)]
@@ -43,8 +45,23 @@ pub struct QueryEngineOptions {
 
     /// # Pgsql Bind address
     ///
-    /// The address to bind for the psql service.
+    /// The address to bind for the psql service. The psql service doesn't authenticate clients,
+    /// so this must be a loopback address when `admin.authentication` is enabled.
     pub pgsql_bind_address: SocketAddr,
+
+    /// # Pgsql TLS
+    ///
+    /// If set, the psql service accepts TLS connections from clients requesting it,
+    /// e.g. with `sslmode=require`.
+    pub pgsql_tls: Option<TlsOptions>,
+
+    /// # Flight SQL Bind address
+    ///
+    /// The address to bind for the Arrow Flight SQL service, used by JDBC and ADBC Flight SQL
+    /// clients. If unset, the Flight SQL service is disabled. The service uses the TLS
+    /// configuration of `pgsql-tls`. If `admin.authentication` is set, clients must send a
+    /// bearer token of the Admin API in the `authorization` header.
+    pub flight_sql_bind_address: Option<SocketAddr>,
 }
 
 impl QueryEngineOptions {
@@ -59,6 +76,8 @@ impl Default for QueryEngineOptions {
             tmp_dir: None,
             query_parallelism: None,
             pgsql_bind_address: "0.0.0.0:9071".parse().unwrap(),
+            pgsql_tls: None,
+            flight_sql_bind_address: None,
         }
     }
 }
@@ -0,0 +1,40 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// # TLS options
+///
+/// Certificate and private key used to terminate TLS. The files are read again on every
+/// configuration update, so certificates can be rotated without restarting the node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub struct TlsOptions {
+    /// # Certificate file
+    ///
+    /// Path to the PEM encoded certificate chain, starting with the server certificate.
+    pub cert_file: PathBuf,
+
+    /// # Private key file
+    ///
+    /// Path to the PEM encoded private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_file: PathBuf,
+
+    /// # Client CA file
+    ///
+    /// Path to the PEM encoded CA certificates. If set, clients must present a certificate
+    /// signed by one of these CAs (mutual TLS). For node-to-node traffic, the same CAs are used
+    /// to verify the certificates of the other nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_ca_file: Option<PathBuf>,
+}
@@ -20,6 +20,7 @@ use restate_serde_util::NonZeroByteCount;
 
 use super::{CommonOptions, RocksDbOptions, RocksDbOptionsBuilder};
 use crate::retries::RetryPolicy;
+use crate::service_protocol::MessageCompression;
 
 /// # Worker options
 #[serde_as]
@@ -37,6 +38,12 @@ pub struct WorkerOptions {
     /// The number of timers in memory limit is used to bound the amount of timers loaded in memory. If this limit is set, when exceeding it, the timers farther in the future will be spilled to disk.
     num_timers_in_memory_limit: Option<NonZeroUsize>,
 
+    /// # Callback retry policy
+    ///
+    /// Retry policy to use when delivering invocation results to the callback urls
+    /// registered through the ingress. Once the retries are exhausted, the result is dropped.
+    pub callback_retry_policy: RetryPolicy,
+
     pub storage: StorageOptions,
 
     pub invoker: InvokerOptions,
@@ -57,6 +64,12 @@ impl Default for WorkerOptions {
         Self {
             internal_queue_length: NonZeroUsize::new(10000).unwrap(),
             num_timers_in_memory_limit: None,
+            callback_retry_policy: RetryPolicy::exponential(
+                Duration::from_millis(100),
+                2.0,
+                Some(10),
+                Some(Duration::from_secs(10)),
+            ),
             storage: StorageOptions::default(),
             invoker: Default::default(),
         }
@@ -119,6 +132,20 @@ pub struct InvokerOptions {
     #[cfg_attr(feature = "schemars", schemars(with = "Option<NonZeroByteCount>"))]
     message_size_limit: Option<NonZeroUsize>,
 
+    /// # Message compression
+    ///
+    /// Algorithm used to compress the protocol messages sent to the deployments supporting
+    /// service protocol version 2 or newer. Compressed messages received from deployments are
+    /// decompressed regardless of this option. If unset, messages are sent uncompressed.
+    pub message_compression: Option<MessageCompression>,
+
+    /// # Message compression threshold
+    ///
+    /// Protocol messages smaller than the specified amount are sent uncompressed.
+    #[serde_as(as = "NonZeroByteCount")]
+    #[cfg_attr(feature = "schemars", schemars(with = "NonZeroByteCount"))]
+    pub message_compression_threshold: NonZeroUsize,
+
     /// # Temporary directory
     ///
     /// Temporary directory to use for the invoker temporary files.
@@ -137,7 +164,27 @@ pub struct InvokerOptions {
     /// Number of concurrent invocations that can be processed by the invoker.
     concurrent_invocations_limit: Option<NonZeroUsize>,
 
+    /// # Circuit breaker failure threshold
+    ///
+    /// Number of consecutive connection failures to a deployment after which the invoker
+    /// opens its circuit breaker. While the breaker is open, the invocations targeting the
+    /// deployment are held instead of being retried, until a health probe succeeds. The held
+    /// invocations are then released gradually, one more batch at each probe, until one of them
+    /// reaches the deployment. If unset, the circuit breaker is disabled, which is the default.
+    circuit_breaker_failure_threshold: Option<NonZeroUsize>,
+
+    /// # Circuit breaker probe interval
+    ///
+    /// Interval between the health probes sent to a deployment whose circuit breaker is open or
+    /// half-open.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub circuit_breaker_probe_interval: humantime::Duration,
+
     // -- Private config options (not exposed in the schema)
+    /// Disables eager state for all services, overriding the eager state policy they declare.
     #[cfg_attr(feature = "schemars", schemars(skip))]
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
     pub disable_eager_state: bool,
@@ -161,6 +208,10 @@ impl InvokerOptions {
     pub fn message_size_limit(&self) -> Option<usize> {
         self.message_size_limit.map(Into::into)
     }
+
+    pub fn circuit_breaker_failure_threshold(&self) -> Option<usize> {
+        self.circuit_breaker_failure_threshold.map(Into::into)
+    }
 }
 
 impl Default for InvokerOptions {
@@ -178,8 +229,12 @@ impl Default for InvokerOptions {
             abort_timeout: Duration::from_secs(60).into(),
             message_size_warning: NonZeroUsize::new(10_000_000).unwrap(), // 10MB
             message_size_limit: None,
+            message_compression: None,
+            message_compression_threshold: NonZeroUsize::new(4 * 1024).unwrap(), // 4KiB
             tmp_dir: None,
             concurrent_invocations_limit: Some(NonZeroUsize::new(10_000).unwrap()),
+            circuit_breaker_failure_threshold: None,
+            circuit_breaker_probe_interval: Duration::from_secs(5).into(),
             disable_eager_state: false,
         }
     }
@@ -235,6 +290,20 @@ pub struct StorageOptions {
     /// the last persisting. This prevents the worker from flushing the RocksDB memtables too often.
     pub persist_lsn_threshold: u64,
 
+    /// # Blob offload threshold
+    ///
+    /// Journal entries and state values larger than this size are written to a content-addressed
+    /// blob store, and only referenced by their hash from RocksDB. This keeps large payloads out
+    /// of the RocksDB compactions. If unset, all payloads are stored inline.
+    ///
+    /// Blobs which are no longer referenced are deleted once the partition store is flushed, see
+    /// `persist-lsn-interval`. If the threshold is unset again, the blobs offloaded so far remain
+    /// readable, and are still deleted once they are no longer referenced.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[serde_as(as = "Option<NonZeroByteCount>")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<NonZeroByteCount>"))]
+    pub blob_offload_threshold: Option<NonZeroUsize>,
+
     /// Whether to perform commits in background IO thread pools eagerly or not
     #[cfg_attr(feature = "schemars", schemars(skip))]
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
@@ -285,6 +354,10 @@ impl StorageOptions {
     pub fn data_dir(&self) -> PathBuf {
         super::data_dir("db")
     }
+
+    pub fn blob_store_dir(&self) -> PathBuf {
+        super::data_dir("blobs")
+    }
 }
 
 impl Default for StorageOptions {
@@ -303,6 +376,7 @@ impl Default for StorageOptions {
             // persist the lsn every hour
             persist_lsn_interval: Some(Duration::from_secs(60 * 60).into()),
             persist_lsn_threshold: 1000,
+            blob_offload_threshold: None,
             always_commit_in_background: false,
         }
     }
@@ -71,6 +71,7 @@ pub mod codes {
 
     pub const BAD_REQUEST: InvocationErrorCode = InvocationErrorCode(400);
     pub const NOT_FOUND: InvocationErrorCode = InvocationErrorCode(404);
+    pub const TIMEOUT: InvocationErrorCode = InvocationErrorCode(408);
     pub const INTERNAL: InvocationErrorCode = InvocationErrorCode(500);
     pub const UNKNOWN: InvocationErrorCode = INTERNAL;
     pub const ABORTED: InvocationErrorCode = InvocationErrorCode(409);
@@ -209,6 +210,9 @@ pub const GONE_INVOCATION_ERROR: InvocationError = InvocationError::new_static(c
 pub const NOT_FOUND_INVOCATION_ERROR: InvocationError =
     InvocationError::new_static(codes::NOT_FOUND, "not found");
 
+pub const EXPIRED_AWAKEABLE_INVOCATION_ERROR: InvocationError =
+    InvocationError::new_static(codes::TIMEOUT, "awakeable expired");
+
 pub const ATTACH_NOT_SUPPORTED_INVOCATION_ERROR: InvocationError =
     InvocationError::new_static(codes::BAD_REQUEST, "attach not supported for this invocation. You can attach only to invocations created with an idempotency key, or for workflow methods.");
 
@@ -345,6 +345,20 @@ impl WithPartitionKey for InvocationResponse {
     }
 }
 
+/// Representing a response to deliver to a callback url
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CallbackResponse {
+    pub url: ByteString,
+    pub invocation_id: InvocationId,
+    pub result: ResponseResult,
+}
+
+impl WithPartitionKey for CallbackResponse {
+    fn partition_key(&self) -> PartitionKey {
+        self.invocation_id.partition_key()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ResponseResult {
     Success(Bytes),
@@ -394,6 +408,8 @@ pub enum ServiceInvocationResponseSink {
         node_id: GenerationalNodeId,
         request_id: IngressRequestId,
     },
+    /// The invocation has been submitted through the ingress with a callback url, where the result should be POSTed.
+    Callback { url: ByteString },
 }
 
 impl ServiceInvocationResponseSink {
@@ -410,6 +426,10 @@ impl ServiceInvocationResponseSink {
             request_id,
         }
     }
+
+    pub fn callback(url: impl Into<ByteString>) -> Self {
+        Self::Callback { url: url.into() }
+    }
 }
 
 /// Source of an invocation
@@ -706,6 +726,30 @@ pub struct PurgeInvocationRequest {
     pub invocation_id: InvocationId,
 }
 
+/// Message to retry an invocation immediately, skipping the remaining retry backoff.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RetryInvocationRequest {
+    pub invocation_id: InvocationId,
+}
+
+/// Message to complete a durable promise of a workflow from outside the workflow, e.g. through
+/// the ingress. Invocations awaiting the promise are woken up.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CompletePromiseRequest {
+    pub service_id: ServiceId,
+    pub key: ByteString,
+    pub result: ResponseResult,
+    /// Sink notified with an empty success once the promise is completed, or with a conflict
+    /// if it was already completed.
+    pub response_sink: Option<ServiceInvocationResponseSink>,
+}
+
+impl WithPartitionKey for CompletePromiseRequest {
+    fn partition_key(&self) -> PartitionKey {
+        self.service_id.partition_key()
+    }
+}
+
 // A hack to allow spancontext to be serialized.
 // Details in https://github.com/open-telemetry/opentelemetry-rust/issues/576#issuecomment-1253396100
 #[derive(serde::Serialize, serde::Deserialize)]
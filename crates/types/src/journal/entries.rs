@@ -73,7 +73,11 @@ impl Entry {
     }
 
     pub fn get_state_keys(value: Option<GetStateKeysResult>) -> Self {
-        Entry::GetStateKeys(GetStateKeysEntry { value })
+        Entry::GetStateKeys(GetStateKeysEntry {
+            limit: None,
+            start_after: None,
+            value,
+        })
     }
 
     pub fn clear_all_state() -> Self {
@@ -102,7 +106,10 @@ impl Entry {
     }
 
     pub fn awakeable(result: Option<EntryResult>) -> Self {
-        Entry::Awakeable(AwakeableEntry { result })
+        Entry::Awakeable(AwakeableEntry {
+            expiry_time: None,
+            result,
+        })
     }
 }
 
@@ -196,6 +203,17 @@ impl From<EntryResult> for ResponseResult {
     }
 }
 
+impl From<ResponseResult> for EntryResult {
+    fn from(value: ResponseResult) -> Self {
+        match value {
+            ResponseResult::Success(bytes) => EntryResult::Success(bytes),
+            ResponseResult::Failure(err) => {
+                EntryResult::Failure(err.code(), err.message().to_owned().into())
+            }
+        }
+    }
+}
+
 pub trait CompletableEntry: private::Sealed {
     /// Returns true if the entry is completed.
     fn is_completed(&self) -> bool;
@@ -250,12 +268,16 @@ pub struct ClearStateEntry {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GetStateKeysResult {
-    Result(Vec<Bytes>),
+    Result { keys: Vec<Bytes>, has_more: bool },
     Failure(InvocationErrorCode, ByteString),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetStateKeysEntry {
+    /// Maximum number of keys to return. If unset or zero, all the keys are returned.
+    pub limit: Option<u32>,
+    /// If set, only the keys following this key are returned.
+    pub start_after: Option<Bytes>,
     pub value: Option<GetStateKeysResult>,
 }
 
@@ -373,6 +395,8 @@ pub struct OneWayCallEntry {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AwakeableEntry {
+    /// Time after which the awakeable is failed, if not completed before.
+    pub expiry_time: Option<u64>,
     pub result: Option<EntryResult>,
 }
 
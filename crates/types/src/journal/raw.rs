@@ -283,7 +283,7 @@ pub trait RawEntryCodec {
         input_message: Bytes,
     ) -> enriched::EnrichedRawEntry;
 
-    fn serialize_get_state_keys_completion(keys: Vec<Bytes>) -> CompletionResult;
+    fn serialize_get_state_keys_completion(keys: Vec<Bytes>, has_more: bool) -> CompletionResult;
 
     fn deserialize(entry_type: EntryType, entry_value: Bytes) -> Result<Entry, RawEntryCodecError>;
 
@@ -0,0 +1,275 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Verification of bearer tokens, shared by the authentication of the Admin, storage query and
+//! Ingress APIs.
+
+use std::path::{Path, PathBuf};
+
+use jsonwebtoken::jwk::{JwkSet, KeyAlgorithm};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::config::{AdminAuthenticationOptions, AdminRole};
+
+#[derive(Debug, thiserror::Error)]
+pub enum JwksError {
+    #[error("cannot read the JWKS file '{0}': {1}")]
+    Read(PathBuf, #[source] std::io::Error),
+    #[error("cannot parse the JWKS file '{0}': {1}")]
+    Parse(PathBuf, #[source] serde_json::Error),
+}
+
+/// Verifies JWTs signed by one of the keys of a JWKS, returning their claims.
+#[derive(Debug, Clone)]
+pub struct JwtVerifier {
+    jwks: JwkSet,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtVerifier {
+    pub fn new(jwks: JwkSet, issuer: Option<String>, audience: Option<String>) -> Self {
+        Self {
+            jwks,
+            issuer,
+            audience,
+        }
+    }
+
+    /// Reads the JWKS from the given file.
+    pub fn from_jwks_file(
+        path: &Path,
+        issuer: Option<String>,
+        audience: Option<String>,
+    ) -> Result<Self, JwksError> {
+        let content = std::fs::read(path).map_err(|e| JwksError::Read(path.to_path_buf(), e))?;
+        let jwks = serde_json::from_slice(&content)
+            .map_err(|e| JwksError::Parse(path.to_path_buf(), e))?;
+        Ok(Self::new(jwks, issuer, audience))
+    }
+
+    /// Returns the claims of the given token, if it's valid.
+    pub fn verify(&self, token: &str) -> Option<Map<String, Value>> {
+        let header = jsonwebtoken::decode_header(token)
+            .map_err(|e| debug!("Cannot decode the JWT header: {}", e))
+            .ok()?;
+        let jwk = match &header.kid {
+            Some(kid) => self.jwks.find(kid)?,
+            None if self.jwks.keys.len() == 1 => &self.jwks.keys[0],
+            None => return None,
+        };
+        // If the key declares its algorithm, the token must use the same one
+        if let Some(key_algorithm) = jwk.common.key_algorithm {
+            if signing_algorithm(key_algorithm) != Some(header.alg) {
+                debug!("The JWT algorithm doesn't match the key algorithm");
+                return None;
+            }
+        }
+        let key = DecodingKey::from_jwk(jwk)
+            .map_err(|e| debug!("Cannot use the JWK: {}", e))
+            .ok()?;
+
+        // The key family is checked against the algorithm when decoding
+        let mut validation = Validation::new(header.alg);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        } else {
+            validation.validate_aud = false;
+        }
+
+        jsonwebtoken::decode::<Map<String, Value>>(token, &key, &validation)
+            .map_err(|e| debug!("Rejecting JWT: {}", e))
+            .ok()
+            .map(|data| data.claims)
+    }
+}
+
+/// Returns the JWS algorithm of the given key algorithm, or `None` for encryption algorithms.
+fn signing_algorithm(key_algorithm: KeyAlgorithm) -> Option<Algorithm> {
+    match key_algorithm {
+        KeyAlgorithm::HS256 => Some(Algorithm::HS256),
+        KeyAlgorithm::HS384 => Some(Algorithm::HS384),
+        KeyAlgorithm::HS512 => Some(Algorithm::HS512),
+        KeyAlgorithm::ES256 => Some(Algorithm::ES256),
+        KeyAlgorithm::ES384 => Some(Algorithm::ES384),
+        KeyAlgorithm::RS256 => Some(Algorithm::RS256),
+        KeyAlgorithm::RS384 => Some(Algorithm::RS384),
+        KeyAlgorithm::RS512 => Some(Algorithm::RS512),
+        KeyAlgorithm::PS256 => Some(Algorithm::PS256),
+        KeyAlgorithm::PS384 => Some(Algorithm::PS384),
+        KeyAlgorithm::PS512 => Some(Algorithm::PS512),
+        KeyAlgorithm::EdDSA => Some(Algorithm::EdDSA),
+        KeyAlgorithm::RSA1_5 | KeyAlgorithm::RSA_OAEP | KeyAlgorithm::RSA_OAEP_256 => None,
+    }
+}
+
+/// Authenticates the bearer tokens of the Admin and storage query APIs, either static tokens or
+/// JWTs, returning the role they grant.
+pub struct AdminAuthenticator {
+    tokens: Vec<(String, String, AdminRole)>,
+    jwt_verifier: Option<JwtVerifier>,
+    role_claim: String,
+}
+
+impl AdminAuthenticator {
+    pub fn from_options(options: &AdminAuthenticationOptions) -> Result<Self, JwksError> {
+        let jwt_verifier = options
+            .jwks_file
+            .as_ref()
+            .map(|path| {
+                JwtVerifier::from_jwks_file(
+                    path,
+                    options.jwt_issuer.clone(),
+                    options.jwt_audience.clone(),
+                )
+            })
+            .transpose()?;
+
+        Ok(Self {
+            tokens: options
+                .tokens
+                .iter()
+                .map(|token| (token.token.clone(), token.name.clone(), token.role))
+                .collect(),
+            jwt_verifier,
+            role_claim: options.role_claim().to_owned(),
+        })
+    }
+
+    /// Returns the role granted by the given bearer token, if valid.
+    pub fn authenticate(&self, token: &str) -> Option<AdminRole> {
+        if let Some((_, name, role)) = self
+            .tokens
+            .iter()
+            .find(|(t, _, _)| constant_time_eq(t.as_bytes(), token.as_bytes()))
+        {
+            debug!("Authenticated request with the static token of '{}'", name);
+            return Some(*role);
+        }
+
+        let claims = self.jwt_verifier.as_ref()?.verify(token)?;
+        match claims.get(&self.role_claim)? {
+            Value::String(role) => parse_role(role),
+            Value::Array(roles) => roles
+                .iter()
+                .filter_map(|role| role.as_str().and_then(parse_role))
+                .max(),
+            _ => None,
+        }
+    }
+}
+
+fn parse_role(role: &str) -> Option<AdminRole> {
+    match role {
+        "viewer" => Some(AdminRole::Viewer),
+        "operator" => Some(AdminRole::Operator),
+        "admin" => Some(AdminRole::Admin),
+        _ => None,
+    }
+}
+
+/// Compares the two secrets in a time independent of their content.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    use crate::config::AdminToken;
+
+    fn verifier(key_algorithm: Option<&str>) -> JwtVerifier {
+        let mut jwk = json!({"kty": "oct", "kid": "k1", "k": "c2VjcmV0"});
+        if let Some(alg) = key_algorithm {
+            jwk["alg"] = json!(alg);
+        }
+        JwtVerifier::new(
+            serde_json::from_value(json!({ "keys": [jwk] })).unwrap(),
+            Some("restate".to_owned()),
+            None,
+        )
+    }
+
+    fn token(alg: Algorithm, issuer: &str) -> String {
+        let mut header = Header::new(alg);
+        header.kid = Some("k1".to_owned());
+        encode(
+            &header,
+            &json!({"iss": issuer, "sub": "billing", "exp": u32::MAX}),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn verify_token() {
+        let claims = verifier(Some("HS256"))
+            .verify(&token(Algorithm::HS256, "restate"))
+            .unwrap();
+        assert_eq!(claims["sub"], "billing");
+
+        assert!(verifier(None)
+            .verify(&token(Algorithm::HS384, "restate"))
+            .is_some());
+        assert!(verifier(Some("HS256"))
+            .verify(&token(Algorithm::HS256, "other"))
+            .is_none());
+    }
+
+    #[test]
+    fn key_algorithm_must_match() {
+        assert!(verifier(Some("HS256"))
+            .verify(&token(Algorithm::HS512, "restate"))
+            .is_none());
+        assert_eq!(
+            signing_algorithm(KeyAlgorithm::PS384),
+            Some(Algorithm::PS384)
+        );
+        assert_eq!(signing_algorithm(KeyAlgorithm::RSA_OAEP), None);
+    }
+
+    #[test]
+    fn static_tokens_and_roles() {
+        let authenticator = AdminAuthenticator::from_options(&AdminAuthenticationOptions {
+            tokens: vec![AdminToken {
+                name: "ops".to_owned(),
+                token: "secret".to_owned(),
+                role: AdminRole::Operator,
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            authenticator.authenticate("secret"),
+            Some(AdminRole::Operator)
+        );
+        assert_eq!(authenticator.authenticate("wrong"), None);
+        assert_eq!(parse_role("admin"), Some(AdminRole::Admin));
+        assert_eq!(parse_role("root"), None);
+        assert!(AdminRole::Viewer < AdminRole::Operator && AdminRole::Operator < AdminRole::Admin);
+    }
+
+    #[test]
+    fn compare_secrets() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
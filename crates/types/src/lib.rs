@@ -27,6 +27,7 @@ pub mod identifiers;
 pub mod ingress;
 pub mod invocation;
 pub mod journal;
+pub mod jwt;
 pub mod live;
 pub mod logs;
 pub mod message;
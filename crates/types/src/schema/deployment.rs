@@ -11,6 +11,7 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
 
 use bytestring::ByteString;
@@ -31,6 +32,18 @@ pub enum ProtocolType {
     BidiStream,
 }
 
+/// How Lambda functions are invoked, named after the invoke modes of the Lambda function URLs.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum LambdaInvokeMode {
+    /// The response is returned once the function completes. Payloads are limited to 6 MB.
+    #[default]
+    Buffered,
+    /// The response is streamed while the function runs, allowing the deployment to send the
+    /// journal entries as soon as they are produced.
+    ResponseStream,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct DeliveryOptions {
@@ -39,11 +52,61 @@ pub struct DeliveryOptions {
     )]
     #[cfg_attr(feature = "schemars", schemars(with = "HashMap<String, String>"))]
     pub additional_headers: HashMap<HeaderName, HeaderValue>,
+    // this field did not used to be stored, so we must consider it optional when deserialising
+    #[serde(default)]
+    pub http_connection: HttpConnectionOptions,
 }
 
 impl DeliveryOptions {
     pub fn new(additional_headers: HashMap<HeaderName, HeaderValue>) -> Self {
-        Self { additional_headers }
+        Self {
+            additional_headers,
+            http_connection: HttpConnectionOptions::default(),
+        }
+    }
+
+    pub fn with_http_connection(mut self, http_connection: HttpConnectionOptions) -> Self {
+        self.http_connection = http_connection;
+        self
+    }
+}
+
+/// Options of the pool of connections to an http deployment. Whether the connections use HTTP/1.1
+/// or HTTP/2 (h2c for plain text deployments) is determined by the `http_version` of the deployment.
+///
+/// When no option is set, the connections are shared with all the deployments using the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HttpConnectionOptions {
+    /// # Max connections
+    ///
+    /// Maximum number of connections to open to the deployment. With HTTP/2, the streams are
+    /// spread across the connections. With HTTP/1.1, this caps the number of concurrent requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<NonZeroUsize>,
+
+    /// # Max concurrent streams
+    ///
+    /// Maximum number of concurrent HTTP/2 streams per connection. Once all the connections
+    /// reached this limit, the requests wait for a stream to complete.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_streams: Option<NonZeroUsize>,
+
+    /// # Pool idle timeout
+    ///
+    /// How long an idle connection is kept open.
+    #[serde(
+        with = "serde_with::As::<Option<serde_with::DisplayFromStr>>",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    pub pool_idle_timeout: Option<humantime::Duration>,
+}
+
+impl HttpConnectionOptions {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
     }
 }
 
@@ -82,6 +145,7 @@ pub enum DeploymentType {
         arn: LambdaARN,
         #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
         assume_role_arn: Option<ByteString>,
+        invoke_mode: LambdaInvokeMode,
     },
 }
 
@@ -101,6 +165,9 @@ enum DeploymentTypeShadow {
     Lambda {
         arn: LambdaARN,
         assume_role_arn: Option<ByteString>,
+        // this field did not used to be stored, so we must consider it optional when deserialising
+        #[serde(default)]
+        invoke_mode: LambdaInvokeMode,
     },
 }
 
@@ -122,9 +189,11 @@ impl From<DeploymentTypeShadow> for DeploymentType {
             DeploymentTypeShadow::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
             } => Self::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
             },
         }
     }
@@ -136,7 +205,7 @@ mod serde_tests {
     use bytestring::ByteString;
     use http::Uri;
 
-    use super::{DeploymentType, ProtocolType};
+    use super::{DeploymentType, LambdaInvokeMode, ProtocolType};
 
     #[derive(serde::Serialize, serde::Deserialize)]
     enum OldDeploymentType {
@@ -194,6 +263,33 @@ mod serde_tests {
             dt
         );
     }
+
+    #[test]
+    fn can_deserialise_without_lambda_invoke_mode() {
+        let arn: LambdaARN =
+            "arn:aws:lambda:eu-central-1:1234567890:function:e2e-node-services:version"
+                .parse()
+                .unwrap();
+
+        let mut buf = bytes::BytesMut::default();
+        StorageCodec::encode(
+            &OldDeploymentType::Lambda {
+                arn: arn.clone(),
+                assume_role_arn: None,
+            },
+            &mut buf,
+        )
+        .unwrap();
+        let dt: DeploymentType = StorageCodec::decode(&mut buf).unwrap();
+        assert_eq!(
+            DeploymentType::Lambda {
+                arn,
+                assume_role_arn: None,
+                invoke_mode: LambdaInvokeMode::Buffered,
+            },
+            dt
+        );
+    }
 }
 
 impl DeploymentType {
@@ -249,6 +345,7 @@ impl DeploymentMetadata {
     pub fn new_lambda(
         arn: LambdaARN,
         assume_role_arn: Option<ByteString>,
+        invoke_mode: LambdaInvokeMode,
         delivery_options: DeliveryOptions,
         supported_protocol_versions: RangeInclusive<i32>,
     ) -> Self {
@@ -256,6 +353,7 @@ impl DeploymentMetadata {
             ty: DeploymentType::Lambda {
                 arn,
                 assume_role_arn,
+                invoke_mode,
             },
             delivery_options,
             created_at: MillisSinceEpoch::now(),
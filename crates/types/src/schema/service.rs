@@ -69,6 +69,33 @@ pub struct ServiceMetadata {
     )]
     #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
     pub workflow_completion_retention: Option<humantime::Duration>,
+
+    /// # Eager state
+    ///
+    /// Which state is sent to the service together with the invocation, as declared in the
+    /// endpoint manifest.
+    #[serde(default, skip_serializing_if = "EagerStatePolicy::is_full")]
+    pub eager_state: EagerStatePolicy,
+}
+
+/// Which state of a Virtual Object or Workflow is sent eagerly to the service, in the start message
+/// of its invocations. The state which is not sent eagerly is loaded lazily by the service.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum EagerStatePolicy {
+    /// The whole state is sent.
+    #[default]
+    Full,
+    /// No state is sent.
+    None,
+    /// Only the given state keys are sent.
+    Keys(Vec<String>),
+}
+
+impl EagerStatePolicy {
+    pub fn is_full(&self) -> bool {
+        *self == EagerStatePolicy::Full
+    }
 }
 
 // This type is used only for exposing the handler metadata, and not internally. See [ServiceAndHandlerType].
@@ -120,6 +147,11 @@ pub trait ServiceMetadataResolver {
 
     fn resolve_latest_service_type(&self, service_name: impl AsRef<str>) -> Option<ServiceType>;
 
+    fn resolve_latest_service_eager_state(
+        &self,
+        service_name: impl AsRef<str>,
+    ) -> Option<EagerStatePolicy>;
+
     fn list_services(&self) -> Vec<ServiceMetadata>;
 }
 
@@ -136,6 +168,8 @@ pub struct ServiceSchemas {
     pub location: ServiceLocation,
     pub idempotency_retention: Duration,
     pub workflow_completion_retention: Option<Duration>,
+    #[serde(default)]
+    pub eager_state: EagerStatePolicy,
 }
 
 impl ServiceSchemas {
@@ -158,6 +192,7 @@ impl ServiceSchemas {
             public: self.location.public,
             idempotency_retention: self.idempotency_retention.into(),
             workflow_completion_retention: self.workflow_completion_retention.map(Into::into),
+            eager_state: self.eager_state.clone(),
         }
     }
 }
@@ -180,6 +215,15 @@ impl ServiceMetadataResolver for Schema {
         self.use_service_schema(service_name.as_ref(), |service_schemas| service_schemas.ty)
     }
 
+    fn resolve_latest_service_eager_state(
+        &self,
+        service_name: impl AsRef<str>,
+    ) -> Option<EagerStatePolicy> {
+        self.use_service_schema(service_name.as_ref(), |service_schemas| {
+            service_schemas.eager_state.clone()
+        })
+    }
+
     fn list_services(&self) -> Vec<ServiceMetadata> {
         self.services
             .iter()
@@ -219,6 +263,15 @@ pub mod test_util {
             self.0.get(service_name.as_ref()).map(|c| c.ty)
         }
 
+        fn resolve_latest_service_eager_state(
+            &self,
+            service_name: impl AsRef<str>,
+        ) -> Option<EagerStatePolicy> {
+            self.0
+                .get(service_name.as_ref())
+                .map(|c| c.eager_state.clone())
+        }
+
         fn list_services(&self) -> Vec<ServiceMetadata> {
             self.0.values().cloned().collect()
         }
@@ -246,6 +299,7 @@ pub mod test_util {
                 public: true,
                 idempotency_retention: std::time::Duration::from_secs(60).into(),
                 workflow_completion_retention: None,
+                eager_state: EagerStatePolicy::Full,
             }
         }
 
@@ -270,6 +324,7 @@ pub mod test_util {
                 public: true,
                 idempotency_retention: std::time::Duration::from_secs(60).into(),
                 workflow_completion_retention: None,
+                eager_state: EagerStatePolicy::Full,
             }
         }
     }
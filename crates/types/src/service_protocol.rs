@@ -13,7 +13,7 @@ use std::ops::RangeInclusive;
 
 // Range of supported service protocol versions by this server
 pub const MIN_SERVICE_PROTOCOL_VERSION: ServiceProtocolVersion = ServiceProtocolVersion::V1;
-pub const MAX_SERVICE_PROTOCOL_VERSION: ServiceProtocolVersion = ServiceProtocolVersion::V1;
+pub const MAX_SERVICE_PROTOCOL_VERSION: ServiceProtocolVersion = ServiceProtocolVersion::V2;
 
 pub const MAX_SERVICE_PROTOCOL_VERSION_VALUE: i32 = i32::MAX;
 
@@ -33,6 +33,21 @@ impl ServiceProtocolVersion {
         MIN_SERVICE_PROTOCOL_VERSION <= *self && *self <= MAX_SERVICE_PROTOCOL_VERSION
     }
 
+    /// Message bodies can be compressed starting from [`ServiceProtocolVersion::V2`].
+    pub fn supports_message_compression(&self) -> bool {
+        *self >= ServiceProtocolVersion::V2
+    }
+
+    /// Awakeables can have an expiry time starting from [`ServiceProtocolVersion::V2`].
+    pub fn supports_awakeable_expiry(&self) -> bool {
+        *self >= ServiceProtocolVersion::V2
+    }
+
+    /// State keys can be read in pages starting from [`ServiceProtocolVersion::V2`].
+    pub fn supports_state_keys_paging(&self) -> bool {
+        *self >= ServiceProtocolVersion::V2
+    }
+
     pub fn choose_max_supported_version(
         versions: &RangeInclusive<i32>,
     ) -> Option<ServiceProtocolVersion> {
@@ -47,6 +62,15 @@ impl ServiceProtocolVersion {
     }
 }
 
+/// Algorithm used to compress the body of a service protocol message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum MessageCompression {
+    Gzip,
+    Zstd,
+}
+
 impl From<ErrorMessage> for InvocationError {
     fn from(value: ErrorMessage) -> Self {
         if value.description.is_empty() {
@@ -134,10 +158,13 @@ mod pb_into {
 
         fn try_from(msg: GetStateKeysEntryMessage) -> Result<Self, Self::Error> {
             Ok(Self::GetStateKeys(GetStateKeysEntry {
+                limit: msg.limit,
+                start_after: msg.start_after,
                 value: msg.result.map(|v| match v {
-                    get_state_keys_entry_message::Result::Value(b) => {
-                        GetStateKeysResult::Result(b.keys)
-                    }
+                    get_state_keys_entry_message::Result::Value(b) => GetStateKeysResult::Result {
+                        keys: b.keys,
+                        has_more: b.has_more,
+                    },
                     get_state_keys_entry_message::Result::Failure(failure) => {
                         GetStateKeysResult::Failure(failure.code.into(), failure.message.into())
                     }
@@ -269,6 +296,7 @@ mod pb_into {
 
         fn try_from(msg: AwakeableEntryMessage) -> Result<Self, Self::Error> {
             Ok(Self::Awakeable(AwakeableEntry {
+                expiry_time: msg.expiry_time,
                 result: msg.result.map(|v| match v {
                     awakeable_entry_message::Result::Value(r) => EntryResult::Success(r),
                     awakeable_entry_message::Result::Failure(Failure { code, message }) => {
@@ -38,6 +38,8 @@ pub enum StorageCodecKind {
     Protobuf = 1,
     // flexbuffers + serde
     FlexbuffersSerde = 2,
+    // reference to a value which has been offloaded to a blob store
+    BlobReference = 3,
 }
 
 impl From<StorageCodecKind> for u8 {
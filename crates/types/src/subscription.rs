@@ -77,6 +77,27 @@ impl schemars::JsonSchema for SubscriptionId {
     }
 }
 
+/// Progress of a subscription's consumer on a single partition of the source topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionPartitionLag {
+    pub subscription_id: SubscriptionId,
+    pub topic: String,
+    pub partition: i32,
+    /// Offset of the last message ingested by the consumer
+    pub offset: i64,
+    /// Offset of the next message that will be appended to the partition, as last reported by
+    /// the broker
+    pub high_watermark: i64,
+    pub updated_at: MillisSinceEpoch,
+}
+
+impl SubscriptionPartitionLag {
+    /// Number of messages appended to the partition that are not yet ingested.
+    pub fn lag(&self) -> u64 {
+        (self.high_watermark - self.offset - 1).max(0) as u64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -14,8 +14,8 @@ use restate_core::{metadata, ShutdownError};
 use restate_storage_api::deduplication_table::DedupInformation;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
 use restate_types::invocation::{
-    AttachInvocationRequest, InvocationResponse, InvocationTermination, PurgeInvocationRequest,
-    ServiceInvocation,
+    AttachInvocationRequest, CompletePromiseRequest, InvocationResponse, InvocationTermination,
+    PurgeInvocationRequest, RetryInvocationRequest, ServiceInvocation,
 };
 use restate_types::message::MessageIndex;
 use restate_types::state_mut::ExternalStateMutation;
@@ -126,6 +126,8 @@ pub enum Command {
     TerminateInvocation(InvocationTermination),
     /// Purge a completed invocation
     PurgeInvocation(PurgeInvocationRequest),
+    /// Retry an invoked invocation now, without waiting for the retry backoff
+    RetryInvocation(RetryInvocationRequest),
     /// Start an invocation on this partition
     Invoke(ServiceInvocation),
     /// Outbox can be truncated up to this index
@@ -134,6 +136,8 @@ pub enum Command {
     ProxyThrough(ServiceInvocation),
     /// Attach to an existing invocation
     AttachInvocation(AttachInvocationRequest),
+    /// Complete a workflow promise from outside the workflow
+    CompletePromise(CompletePromiseRequest),
 
     // -- Partition processor events for PP
     /// Invoker is reporting effect(s) from an ongoing invocation.
@@ -9,22 +9,34 @@
 // by the Apache License, Version 2.0.
 
 use anyhow::{anyhow, Error};
+use assert2::let_assert;
+use bytestring::ByteString;
 use restate_core::metadata;
-use restate_ingress_http::{GetOutputResult, InvocationStorageReader};
-use restate_partition_store::PartitionStoreManager;
+use restate_ingress_http::{
+    GetAwakeableResult, GetOutputResult, InvocationStorageReader, PeekPromiseResult,
+};
+use restate_partition_store::{PartitionStore, PartitionStoreManager};
+use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_api::idempotency_table::ReadOnlyIdempotencyTable;
 use restate_storage_api::invocation_status_table::{
     InvocationStatus, ReadOnlyInvocationStatusTable,
 };
+use restate_storage_api::journal_table::{JournalEntry, ReadOnlyJournalTable};
+use restate_storage_api::promise_table::{Promise, PromiseState, ReadOnlyPromiseTable};
 use restate_storage_api::service_status_table::{
     ReadOnlyVirtualObjectStatusTable, VirtualObjectStatus,
 };
-use restate_types::identifiers::WithPartitionKey;
+use restate_types::errors::InvocationError;
+use restate_types::identifiers::{
+    EntryIndex, InvocationId, PartitionKey, ServiceId, WithPartitionKey,
+};
 use restate_types::ingress::{IngressResponseResult, InvocationResponse};
 use restate_types::invocation::{
     InvocationQuery, InvocationTarget, InvocationTargetType, ResponseResult, WorkflowHandlerType,
 };
+use restate_types::journal::{AwakeableEntry, CompletionResult, Entry, EntryType};
 use restate_types::partition_table::FindPartition;
+use restate_types::time::MillisSinceEpoch;
 
 #[derive(Debug, Clone)]
 pub struct InvocationStorageReaderImpl {
@@ -37,16 +49,16 @@ impl InvocationStorageReaderImpl {
             partition_store_manager,
         }
     }
-}
 
-impl InvocationStorageReader for InvocationStorageReaderImpl {
-    async fn get_output(&self, query: InvocationQuery) -> Result<GetOutputResult, Error> {
+    async fn get_partition_store(
+        &self,
+        partition_key: PartitionKey,
+    ) -> Result<PartitionStore, Error> {
         let partition_id = metadata()
             .partition_table()
             .ok_or_else(|| anyhow!("Can't find partition table"))?
-            .find_partition_id(query.partition_key())?;
-        let mut partition_storage = self
-            .partition_store_manager
+            .find_partition_id(partition_key)?;
+        self.partition_store_manager
             .get_partition_store(partition_id)
             .await
             .ok_or_else(|| {
@@ -54,7 +66,13 @@ impl InvocationStorageReader for InvocationStorageReaderImpl {
                     "Can't find partition store for partition id {}",
                     partition_id
                 )
-            })?;
+            })
+    }
+}
+
+impl InvocationStorageReader for InvocationStorageReaderImpl {
+    async fn get_output(&self, query: InvocationQuery) -> Result<GetOutputResult, Error> {
+        let mut partition_storage = self.get_partition_store(query.partition_key()).await?;
 
         let invocation_id = match query {
             InvocationQuery::Invocation(iid) => iid,
@@ -103,4 +121,80 @@ impl InvocationStorageReader for InvocationStorageReaderImpl {
             _ => Ok(GetOutputResult::NotReady),
         }
     }
+
+    async fn peek_promise(
+        &self,
+        workflow_id: ServiceId,
+        key: ByteString,
+    ) -> Result<PeekPromiseResult, Error> {
+        let mut partition_storage = self
+            .get_partition_store(workflow_id.partition_key())
+            .await?;
+
+        Ok(
+            match partition_storage.get_promise(&workflow_id, &key).await? {
+                Some(Promise {
+                    state: PromiseState::Completed(result),
+                }) => PeekPromiseResult::Completed(result.into()),
+                _ => PeekPromiseResult::NotCompleted,
+            },
+        )
+    }
+
+    async fn get_awakeable(
+        &self,
+        invocation_id: InvocationId,
+        entry_index: EntryIndex,
+    ) -> Result<GetAwakeableResult, Error> {
+        let mut partition_storage = self
+            .get_partition_store(invocation_id.partition_key())
+            .await?;
+
+        Ok(
+            match partition_storage
+                .get_journal_entry(&invocation_id, entry_index)
+                .await?
+            {
+                Some(JournalEntry::Entry(entry)) if entry.ty() == EntryType::Awakeable => {
+                    let_assert!(
+                        Entry::Awakeable(AwakeableEntry {
+                            expiry_time,
+                            result
+                        }) = entry.deserialize_entry_ref::<ProtobufRawEntryCodec>()?
+                    );
+                    match result {
+                        Some(result) => GetAwakeableResult::Completed(result.into()),
+                        None => GetAwakeableResult::Pending {
+                            expiry_time: expiry_time.map(MillisSinceEpoch::new),
+                        },
+                    }
+                }
+                // The awakeable has been completed before its entry was stored
+                Some(JournalEntry::Completion(CompletionResult::Success(value))) => {
+                    GetAwakeableResult::Completed(ResponseResult::Success(value))
+                }
+                Some(JournalEntry::Completion(CompletionResult::Failure(code, message))) => {
+                    GetAwakeableResult::Completed(ResponseResult::Failure(InvocationError::new(
+                        code, message,
+                    )))
+                }
+                _ => GetAwakeableResult::NotFound,
+            },
+        )
+    }
+
+    async fn get_invocation_target(
+        &self,
+        invocation_id: InvocationId,
+    ) -> Result<Option<InvocationTarget>, Error> {
+        let mut partition_storage = self
+            .get_partition_store(invocation_id.partition_key())
+            .await?;
+
+        Ok(partition_storage
+            .get_invocation_status(&invocation_id)
+            .await?
+            .invocation_target()
+            .cloned())
+    }
 }
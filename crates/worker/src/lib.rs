@@ -17,18 +17,21 @@ mod invoker_integration;
 mod metric_definitions;
 mod partition;
 mod partition_processor_manager;
+mod state_changes;
 mod subscription_controller;
 mod subscription_integration;
 
 use codederror::CodedError;
 use tokio::sync::oneshot;
 
+pub use crate::state_changes::{StateChange, StateChangeFeed, StateChangeFeedError, StateMutation};
 pub use crate::subscription_controller::SubscriptionController;
 pub use crate::subscription_integration::SubscriptionControllerHandle;
 
 use restate_bifrost::Bifrost;
 use restate_core::network::MessageRouterBuilder;
 use restate_core::network::Networking;
+use restate_core::worker_api::ProcessorsManagerHandle;
 use restate_core::{task_center, Metadata, TaskKind};
 use restate_ingress_dispatcher::IngressDispatcher;
 use restate_ingress_http::HyperServerIngress;
@@ -38,8 +41,11 @@ use restate_invoker_impl::{
 };
 use restate_metadata_store::MetadataStoreClient;
 use restate_partition_store::{PartitionStore, PartitionStoreManager};
+use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys, ServiceClient};
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_query_datafusion::context::QueryContext;
+use restate_storage_query_datafusion::remote_query_scanner_manager::RemoteScannerManager;
+use restate_storage_query_flight::service::FlightSqlQueryService;
 use restate_storage_query_postgres::service::PostgresQueryService;
 use restate_types::config::Configuration;
 use restate_types::live::Live;
@@ -80,8 +86,6 @@ pub enum BuildError {
         #[code]
         restate_rocksdb::RocksError,
     ),
-    #[code(unknown)]
-    Invoker(#[from] restate_invoker_impl::BuildError),
 }
 
 #[derive(Debug, thiserror::Error, CodedError)]
@@ -98,6 +102,7 @@ pub struct Worker {
     updateable_config: Live<Configuration>,
     storage_query_context: QueryContext,
     storage_query_postgres: PostgresQueryService,
+    storage_query_flight_sql: Option<FlightSqlQueryService>,
     #[allow(clippy::type_complexity)]
     invoker: InvokerService<
         InvokerStorageReader<PartitionStore>,
@@ -108,6 +113,7 @@ pub struct Worker {
     ingress_kafka: IngressKafkaService,
     subscription_controller_handle: SubscriptionControllerHandle,
     partition_processor_manager: PartitionProcessorManager,
+    state_change_feed: StateChangeFeed,
 }
 
 impl Worker {
@@ -119,6 +125,7 @@ impl Worker {
         router_builder: &mut MessageRouterBuilder,
         schema: Live<Schema>,
         metadata_store_client: MetadataStoreClient,
+        request_identity_keys: RequestIdentityKeys,
     ) -> Result<Self, BuildError> {
         metric_definitions::describe_metrics();
 
@@ -158,7 +165,17 @@ impl Worker {
             &config.worker.invoker,
             EntryEnricher::new(schema.clone()),
             schema.clone(),
-        )?;
+            request_identity_keys.clone(),
+        );
+
+        // used by the partition processors to deliver the invocation results to callback urls
+        let service_client = ServiceClient::from_options(
+            &config.common.service_client,
+            AssumeRoleCacheMode::None,
+            request_identity_keys,
+        );
+
+        let state_change_feed = StateChangeFeed::new(metadata.clone());
 
         let partition_processor_manager = PartitionProcessorManager::new(
             task_center(),
@@ -170,6 +187,8 @@ impl Worker {
             networking,
             bifrost,
             invoker.handle(),
+            service_client,
+            state_change_feed.clone(),
         );
 
         let storage_query_context = QueryContext::create(
@@ -178,11 +197,23 @@ impl Worker {
             partition_store_manager.clone(),
             invoker.status_reader(),
             schema.clone(),
+            subscription_integration::ConsumerLagReaderImpl(ingress_kafka.consumer_lag()),
+            Some(RemoteScannerManager::new(
+                metadata.clone(),
+                partition_processor_manager.handle(),
+            )),
         )
         .await?;
 
         let storage_query_postgres = PostgresQueryService::from_options(
             &config.admin.query_engine,
+            config.admin.authentication.as_ref(),
+            storage_query_context.clone(),
+        );
+
+        let storage_query_flight_sql = FlightSqlQueryService::from_options(
+            &config.admin.query_engine,
+            config.admin.authentication.as_ref(),
             storage_query_context.clone(),
         );
 
@@ -190,11 +221,13 @@ impl Worker {
             updateable_config,
             storage_query_context,
             storage_query_postgres,
+            storage_query_flight_sql,
             invoker,
             external_client_ingress: ingress_http,
             ingress_kafka,
             subscription_controller_handle,
             partition_processor_manager,
+            state_change_feed,
         })
     }
 
@@ -206,6 +239,14 @@ impl Worker {
         &self.storage_query_context
     }
 
+    pub fn state_change_feed(&self) -> StateChangeFeed {
+        self.state_change_feed.clone()
+    }
+
+    pub fn partition_processor_manager_handle(&self) -> ProcessorsManagerHandle {
+        self.partition_processor_manager.handle()
+    }
+
     pub async fn run(self, all_partitions_started_rx: oneshot::Receiver<()>) -> anyhow::Result<()> {
         let tc = task_center();
 
@@ -228,6 +269,16 @@ impl Worker {
             self.storage_query_postgres.run(),
         )?;
 
+        // Flight SQL external server
+        if let Some(storage_query_flight_sql) = self.storage_query_flight_sql {
+            tc.spawn_child(
+                TaskKind::RpcServer,
+                "flight-sql-query-server",
+                None,
+                storage_query_flight_sql.run(),
+            )?;
+        }
+
         // Kafka Ingress
         tc.spawn_child(
             TaskKind::SystemService,
@@ -18,6 +18,9 @@ pub const PARTITION_TIMER_DUE_HANDLED: &str = "restate.partition.timer_due_handl
 pub const PARTITION_STORAGE_TX_CREATED: &str = "restate.partition.storage_tx_created.total";
 pub const PARTITION_STORAGE_TX_COMMITTED: &str = "restate.partition.storage_tx_committed.total";
 pub const PARTITION_HANDLE_LEADER_ACTIONS: &str = "restate.partition.handle_leader_action.total";
+pub const PARTITION_CALLBACK_DELIVERED: &str = "restate.partition.callback_delivered.total";
+pub const PARTITION_CALLBACK_DELIVERY_FAILED: &str =
+    "restate.partition.callback_delivery_failed.total";
 
 pub const NUM_ACTIVE_PARTITIONS: &str = "restate.num_active_partitions";
 pub const PARTITION_TIME_SINCE_LAST_STATUS_UPDATE: &str =
@@ -62,6 +65,16 @@ pub(crate) fn describe_metrics() {
         Unit::Count,
         "Storage transactions committed by applying partition state machine commands"
     );
+    describe_counter!(
+        PARTITION_CALLBACK_DELIVERED,
+        Unit::Count,
+        "Invocation results delivered to callback urls"
+    );
+    describe_counter!(
+        PARTITION_CALLBACK_DELIVERY_FAILED,
+        Unit::Count,
+        "Invocation results which could not be delivered to callback urls after exhausting the retries"
+    );
     describe_histogram!(
         PP_APPLY_RECORD_DURATION,
         Unit::Seconds,
@@ -0,0 +1,383 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::sync::Arc;
+
+use bytestring::ByteString;
+use hyper::header::CONTENT_TYPE;
+use hyper::http::uri::{InvalidUriParts, PathAndQuery};
+use hyper::http::{HeaderName, HeaderValue};
+use hyper::{Body, HeaderMap, Uri, Version};
+use tracing::debug;
+
+use restate_service_client::{Endpoint, Method, Parts, Request, ServiceClient, ServiceClientError};
+use restate_types::config::is_callback_host_allowed;
+use restate_types::invocation::{CallbackResponse, ResponseResult};
+use restate_types::retries::RetryPolicy;
+
+/// Contains the string representation of the invocation id
+const X_RESTATE_ID: HeaderName = HeaderName::from_static("x-restate-id");
+/// Either `success` or `failure`, depending on the invocation result
+const X_RESTATE_RESULT: HeaderName = HeaderName::from_static("x-restate-result");
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum CallbackError {
+    #[error("bad callback url '{0}': {1}")]
+    BadUrl(ByteString, String),
+    #[error("callback endpoint replied with status code {0}")]
+    BadStatusCode(u16),
+    #[error(transparent)]
+    Client(#[from] ServiceClientError),
+}
+
+impl CallbackError {
+    pub(crate) fn is_retryable(&self) -> bool {
+        match self {
+            CallbackError::BadUrl(_, _) => false,
+            CallbackError::BadStatusCode(status_code) => {
+                // Retry on server errors, request timeouts and rate limiting
+                *status_code >= 500 || *status_code == 408 || *status_code == 429
+            }
+            CallbackError::Client(err) => err.is_retryable(),
+        }
+    }
+}
+
+/// Delivers invocation results to the callback urls registered through the ingress.
+///
+/// The result is POSTed to the callback url. Successful results are sent as the raw response
+/// body, failures as the JSON representation of the [`restate_types::errors::InvocationError`].
+///
+/// The shuffle delivers the callback responses concurrently with the other outbox messages, see
+/// [`crate::partition::shuffle`]. A callback response stays in the outbox until it is delivered,
+/// or until the callback endpoint rejects it, hence the next leader delivers it again if the
+/// partition loses leadership. The delivery is therefore at least once.
+///
+/// The callback urls are checked again against the allowed callback hosts of the ingress before
+/// sending, since the configuration could have changed since the invocation was accepted.
+#[derive(Debug, Clone)]
+pub(crate) struct CallbackClient {
+    client: ServiceClient,
+    retry_policy: RetryPolicy,
+    allowed_hosts: Arc<[String]>,
+}
+
+impl CallbackClient {
+    pub(crate) fn new(
+        client: ServiceClient,
+        retry_policy: RetryPolicy,
+        allowed_hosts: Arc<[String]>,
+    ) -> Self {
+        Self {
+            client,
+            retry_policy,
+            allowed_hosts,
+        }
+    }
+
+    /// Delivers the callback response, retrying according to the configured retry policy.
+    /// Fails with the last error once the retries are exhausted, or if the error is not retryable.
+    pub(crate) async fn deliver(
+        &self,
+        callback_response: &CallbackResponse,
+    ) -> Result<(), CallbackError> {
+        let invocation_id = callback_response.invocation_id;
+        let url = &callback_response.url;
+
+        self.retry_policy
+            .clone()
+            .retry_if(
+                || self.send(callback_response),
+                |err: &CallbackError| {
+                    if err.is_retryable() {
+                        debug!(
+                            restate.invocation.id = %invocation_id,
+                            "Failed delivering the response to callback url '{}', retrying: {}",
+                            url,
+                            err
+                        );
+                        true
+                    } else {
+                        false
+                    }
+                },
+            )
+            .await
+    }
+
+    async fn send(&self, callback_response: &CallbackResponse) -> Result<(), CallbackError> {
+        let (address, path) = split_callback_url(&callback_response.url, &self.allowed_hosts)?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_RESTATE_ID,
+            HeaderValue::try_from(callback_response.invocation_id.to_string())
+                .expect("invocation id to be a valid header value"),
+        );
+
+        let body = match &callback_response.result {
+            ResponseResult::Success(response) => {
+                headers.insert(X_RESTATE_RESULT, HeaderValue::from_static("success"));
+                headers.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/octet-stream"),
+                );
+                Body::from(response.clone())
+            }
+            ResponseResult::Failure(error) => {
+                headers.insert(X_RESTATE_RESULT, HeaderValue::from_static("failure"));
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                Body::from(
+                    serde_json::to_vec(error).expect("serializing InvocationError should not fail"),
+                )
+            }
+        };
+
+        let response = self
+            .client
+            .call(Request::new(
+                Parts::new(Method::POST, address, path, headers),
+                body,
+            ))
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(CallbackError::BadStatusCode(response.status().as_u16()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the callback url in the endpoint address and the request path. The query of the url
+/// is kept on the address, because the [`ServiceClient`] preserves only the query of the address
+/// when joining the address with the request path.
+fn split_callback_url(
+    url: &ByteString,
+    allowed_hosts: &[String],
+) -> Result<(Endpoint, PathAndQuery), CallbackError> {
+    let uri: Uri = url.parse().map_err(|e: hyper::http::uri::InvalidUri| {
+        CallbackError::BadUrl(url.clone(), e.to_string())
+    })?;
+    let default_port = if uri.scheme_str() == Some("https") {
+        443
+    } else {
+        80
+    };
+    if !is_callback_host_allowed(
+        allowed_hosts,
+        uri.host().unwrap_or_default(),
+        uri.port_u16().unwrap_or(default_port),
+    ) {
+        return Err(CallbackError::BadUrl(
+            url.clone(),
+            "the host is not in the allowed callback hosts of the ingress".to_owned(),
+        ));
+    }
+    let mut parts = uri.into_parts();
+    if parts.scheme.is_none() || parts.authority.is_none() {
+        return Err(CallbackError::BadUrl(
+            url.clone(),
+            "expected an absolute url".to_owned(),
+        ));
+    }
+
+    let (path, address_path_and_query) = match parts.path_and_query.take() {
+        Some(path_and_query) => (
+            PathAndQuery::try_from(path_and_query.path())
+                .map_err(|e| CallbackError::BadUrl(url.clone(), e.to_string()))?,
+            match path_and_query.query() {
+                Some(query) => PathAndQuery::try_from(format!("/?{query}"))
+                    .map_err(|e| CallbackError::BadUrl(url.clone(), e.to_string()))?,
+                None => PathAndQuery::from_static("/"),
+            },
+        ),
+        None => (
+            PathAndQuery::from_static("/"),
+            PathAndQuery::from_static("/"),
+        ),
+    };
+    parts.path_and_query = Some(address_path_and_query);
+
+    let address = Uri::from_parts(parts)
+        .map_err(|e: InvalidUriParts| CallbackError::BadUrl(url.clone(), e.to_string()))?;
+
+    Ok((
+        Endpoint::Http(address, Version::default(), Default::default()),
+        path,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+
+    use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys};
+    use restate_types::config::ServiceClientOptions;
+    use restate_types::identifiers::InvocationId;
+
+    fn allowed_hosts() -> Vec<String> {
+        vec![
+            "example.com".to_owned(),
+            "localhost:8080".to_owned(),
+            "127.0.0.1".to_owned(),
+        ]
+    }
+
+    fn split(url: &'static str) -> (String, String) {
+        let (endpoint, path) =
+            split_callback_url(&ByteString::from_static(url), &allowed_hosts()).unwrap();
+        (endpoint.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn split_url_with_path_and_query() {
+        assert_eq!(
+            split("https://example.com/hooks/restate?token=abc"),
+            (
+                "https://example.com/?token=abc".to_owned(),
+                "/hooks/restate".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn split_url_without_path() {
+        assert_eq!(
+            split("http://localhost:8080"),
+            ("http://localhost:8080/".to_owned(), "/".to_owned())
+        );
+    }
+
+    #[test]
+    fn relative_url_is_rejected() {
+        assert!(split_callback_url(&ByteString::from_static("/hooks"), &allowed_hosts()).is_err());
+    }
+
+    #[test]
+    fn not_allowed_host_is_rejected() {
+        let err = split_callback_url(
+            &ByteString::from_static("http://169.254.169.254/latest/meta-data"),
+            &allowed_hosts(),
+        )
+        .unwrap_err();
+        assert!(!err.is_retryable());
+        assert!(split_callback_url(
+            &ByteString::from_static("http://localhost:9090/hooks"),
+            &allowed_hosts()
+        )
+        .is_err());
+    }
+
+    /// Starts a callback endpoint replying with the given status codes, and then with 200.
+    pub(crate) fn start_callback_endpoint(
+        status_codes: Vec<u16>,
+    ) -> (SocketAddr, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let status_codes = Arc::new(status_codes);
+
+        let make_service = {
+            let requests = Arc::clone(&requests);
+            make_service_fn(move |_| {
+                let requests = Arc::clone(&requests);
+                let status_codes = Arc::clone(&status_codes);
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |_: Request<Body>| {
+                        let attempt = requests.fetch_add(1, Ordering::SeqCst);
+                        let status_code = status_codes.get(attempt).copied().unwrap_or(200);
+                        async move {
+                            Ok::<_, Infallible>(
+                                Response::builder()
+                                    .status(status_code)
+                                    .body(Body::empty())
+                                    .unwrap(),
+                            )
+                        }
+                    }))
+                }
+            })
+        };
+
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let address = server.local_addr();
+        tokio::spawn(server);
+
+        (address, requests)
+    }
+
+    fn callback_client(max_retries: usize) -> CallbackClient {
+        CallbackClient::new(
+            ServiceClient::from_options(
+                &ServiceClientOptions::default(),
+                AssumeRoleCacheMode::None,
+                RequestIdentityKeys::default(),
+            ),
+            RetryPolicy::fixed_delay(Duration::from_millis(10), Some(max_retries)),
+            Arc::from(allowed_hosts()),
+        )
+    }
+
+    fn callback_response(address: SocketAddr) -> CallbackResponse {
+        CallbackResponse {
+            url: ByteString::from(format!("http://{address}/hooks/restate")),
+            invocation_id: InvocationId::mock_random(),
+            result: ResponseResult::Success(Bytes::from_static(b"result")),
+        }
+    }
+
+    #[tokio::test]
+    async fn deliver_retries_server_errors() {
+        let (address, requests) = start_callback_endpoint(vec![503, 429]);
+
+        callback_client(5)
+            .deliver(&callback_response(address))
+            .await
+            .unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn deliver_fails_after_exhausting_retries() {
+        let (address, requests) = start_callback_endpoint(vec![500; 10]);
+
+        let err = callback_client(3)
+            .deliver(&callback_response(address))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, CallbackError::BadStatusCode(500)));
+        // the initial request and 3 retries
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn deliver_does_not_retry_client_errors() {
+        let (address, requests) = start_callback_endpoint(vec![400]);
+
+        let err = callback_client(5)
+            .deliver(&callback_response(address))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, CallbackError::BadStatusCode(400)));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
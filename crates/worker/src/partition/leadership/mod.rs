@@ -40,6 +40,7 @@ use restate_wal_protocol::timer::TimerKeyValue;
 use super::storage::invoker::InvokerStorageReader;
 use crate::metric_definitions::PARTITION_HANDLE_LEADER_ACTIONS;
 use crate::partition::action_effect_handler::ActionEffectHandler;
+use crate::partition::callback::CallbackClient;
 use crate::partition::shuffle::{HintSender, Shuffle, ShuffleMetadata};
 use crate::partition::state_machine::Action;
 use crate::partition::{shuffle, storage};
@@ -65,6 +66,7 @@ pub(crate) struct FollowerState<I> {
     networking: Networking,
     partition_key_range: RangeInclusive<PartitionKey>,
     bifrost: Bifrost,
+    callback_client: CallbackClient,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -99,6 +101,7 @@ where
         invoker_tx: InvokerInputSender,
         bifrost: Bifrost,
         networking: Networking,
+        callback_client: CallbackClient,
     ) -> (Self, ActionEffectStream) {
         (
             Self::Follower(FollowerState {
@@ -109,6 +112,7 @@ where
                 invoker_tx,
                 bifrost,
                 networking,
+                callback_client,
             }),
             ActionEffectStream::Follower,
         )
@@ -171,6 +175,7 @@ where
                 shuffle_tx,
                 follower_state.channel_size,
                 follower_state.bifrost.clone(),
+                follower_state.callback_client.clone(),
             );
 
             let shuffle_hint_tx = shuffle.create_hint_sender();
@@ -267,6 +272,7 @@ where
                     mut invoker_tx,
                     bifrost,
                     networking,
+                    callback_client,
                 },
             leader_state:
                 LeaderState {
@@ -297,6 +303,7 @@ where
                 invoker_tx,
                 bifrost,
                 networking,
+                callback_client,
             ))
         } else {
             Ok((self, ActionEffectStream::Follower))
@@ -397,6 +404,10 @@ where
                 .abort_invocation(partition_leader_epoch, invocation_id)
                 .await
                 .map_err(Error::Invoker)?,
+            Action::RetryInvocationNow(invocation_id) => invoker_tx
+                .retry_invocation_now(partition_leader_epoch, invocation_id)
+                .await
+                .map_err(Error::Invoker)?,
             Action::IngressResponse(ingress_response) => {
                 Self::send_ingress_message(
                     networking,
@@ -426,6 +437,9 @@ where
                     .send(ActionEffect::ScheduleCleanupTimer(invocation_id, retention))
                     .await;
             }
+            Action::PublishStateChange { .. } => {
+                // published by the partition processor, independently of the leadership
+            }
         }
 
         Ok(())
@@ -42,11 +42,14 @@ use crate::metric_definitions::{
     PARTITION_ACTUATOR_HANDLED, PARTITION_LABEL, PARTITION_LEADER_HANDLE_ACTION_BATCH_DURATION,
     PARTITION_TIMER_DUE_HANDLED, PP_APPLY_RECORD_DURATION,
 };
+use crate::partition::callback::CallbackClient;
 use crate::partition::leadership::{ActionEffect, LeadershipState};
-use crate::partition::state_machine::{ActionCollector, Effects, StateMachine};
+use crate::partition::state_machine::{Action, ActionCollector, Effects, StateMachine};
 use crate::partition::storage::{DedupSequenceNumberResolver, PartitionStorage, Transaction};
+use crate::state_changes::StateChangeFeed;
 
 mod action_effect_handler;
+pub mod callback;
 mod leadership;
 pub mod shuffle;
 mod state_machine;
@@ -66,6 +69,8 @@ pub(super) struct PartitionProcessor<RawEntryCodec, InvokerInputSender> {
 
     status: PartitionProcessorStatus,
     invoker_tx: InvokerInputSender,
+    callback_client: CallbackClient,
+    state_change_feed: StateChangeFeed,
     control_rx: mpsc::Receiver<PartitionProcessorControlCommand>,
     status_watch_tx: watch::Sender<PartitionProcessorStatus>,
 
@@ -88,6 +93,8 @@ where
         control_rx: mpsc::Receiver<PartitionProcessorControlCommand>,
         status_watch_tx: watch::Sender<PartitionProcessorStatus>,
         invoker_tx: InvokerInputSender,
+        callback_client: CallbackClient,
+        state_change_feed: StateChangeFeed,
     ) -> Self {
         Self {
             partition_id,
@@ -96,6 +103,8 @@ where
             num_timers_in_memory_limit,
             channel_size,
             invoker_tx,
+            callback_client,
+            state_change_feed,
             control_rx,
             status_watch_tx,
             _entry_codec: Default::default(),
@@ -115,6 +124,8 @@ where
             num_timers_in_memory_limit,
             channel_size,
             invoker_tx,
+            callback_client,
+            state_change_feed,
             ..
         } = self;
 
@@ -130,6 +141,7 @@ where
         let last_applied_lsn = partition_storage.load_applied_lsn().await?;
         let last_applied_lsn = last_applied_lsn.unwrap_or(Lsn::INVALID);
         self.status.last_applied_log_lsn = Some(last_applied_lsn);
+        let state_change_publisher = state_change_feed.publisher(partition_id, last_applied_lsn);
         let current_tail = bifrost
             .find_tail(LogId::from(partition_id), FindTailAttributes::default())
             .await?;
@@ -176,6 +188,7 @@ where
             invoker_tx,
             bifrost,
             networking,
+            callback_client,
         );
         // avoid synchronized timers. We pick a randomised timer between 500 and 1023 millis.
         let mut status_update_timer =
@@ -209,6 +222,7 @@ where
                         anyhow::bail!("Read stream terminated for partition processor");
                     };
                     let record = record??;
+                    let lsn = record.0;
                     trace!(lsn = %record.0, "Processing bifrost record for '{}': {:?}", record.1.command.name(), record.1.header);
 
                     let mut transaction = partition_storage.create_transaction();
@@ -264,6 +278,10 @@ where
                         // Commit our changes and notify actuators about actions if we are the leader
                         transaction.commit().await?;
                         apply_record_latency.record(command_start.elapsed());
+                        state_change_publisher.publish(lsn, action_collector.iter().filter_map(|action| match action {
+                            Action::PublishStateChange { service_id, mutation } => Some((service_id.clone(), mutation.clone())),
+                            _ => None,
+                        }));
                         let actions_start = Instant::now();
                         state.handle_actions(action_collector.drain(..)).await?;
                         record_actions_latency.record(actions_start.elapsed());
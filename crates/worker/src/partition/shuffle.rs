@@ -9,23 +9,48 @@
 // by the Apache License, Version 2.0.
 
 use std::future::Future;
+use std::time::Duration;
 
+use anyhow::anyhow;
 use async_channel::{TryRecvError, TrySendError};
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use metrics::counter;
 use tokio::sync::mpsc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use restate_bifrost::Bifrost;
 use restate_core::cancellation_watcher;
 use restate_storage_api::deduplication_table::DedupInformation;
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
+use restate_types::invocation::CallbackResponse;
 use restate_types::message::MessageIndex;
 use restate_types::NodeId;
 use restate_wal_protocol::{append_envelope_to_bifrost, Destination, Envelope, Header, Source};
 
+use crate::metric_definitions::{PARTITION_CALLBACK_DELIVERED, PARTITION_CALLBACK_DELIVERY_FAILED};
+use crate::partition::callback::{CallbackClient, CallbackError};
 use crate::partition::shuffle::state_machine::StateMachine;
 use crate::partition::types::OutboxMessageExt;
 
+/// Number of callback responses which are delivered concurrently by a shuffle
+const MAX_CONCURRENT_CALLBACK_DELIVERIES: usize = 64;
+/// Number of shuffled callback responses waiting for their delivery. Once full, the shuffle
+/// stops shuffling until a delivery completes.
+const CALLBACK_QUEUE_SIZE: usize = 1024;
+/// Delay before retrying the delivery of a callback response whose retries were exhausted
+const CALLBACK_REDELIVERY_DELAY: Duration = Duration::from_secs(60);
+
+/// Outcome of shuffling an outbox message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SendOutcome {
+    /// The message was sent, hence it can be truncated from the outbox
+    Sent,
+    /// The message is being delivered, and it must stay in the outbox until it is
+    DeliveryPending,
+}
+
 #[derive(Debug)]
 pub(crate) struct NewOutboxMessage {
     seq_number: MessageIndex,
@@ -182,6 +207,9 @@ pub(super) struct Shuffle<OR> {
 
     // used to create the senders into the shuffle
     hint_tx: async_channel::Sender<NewOutboxMessage>,
+
+    // used to deliver the responses to callback urls
+    callback_client: CallbackClient,
 }
 
 impl<OR> Shuffle<OR>
@@ -194,6 +222,7 @@ where
         truncation_tx: mpsc::Sender<OutboxTruncation>,
         channel_size: usize,
         bifrost: Bifrost,
+        callback_client: CallbackClient,
     ) -> Self {
         let (hint_tx, hint_rx) = async_channel::bounded(channel_size);
 
@@ -204,6 +233,7 @@ where
             hint_rx,
             hint_tx,
             bifrost,
+            callback_client,
         }
     }
 
@@ -218,20 +248,45 @@ where
             outbox_reader,
             truncation_tx,
             bifrost,
+            callback_client,
             ..
         } = self;
 
         debug!(restate.node = %metadata.node_id, restate.partition.id = %metadata.partition_id, "Running shuffle");
 
         let node_id = metadata.node_id;
+
+        // callback deliveries can take long because of retries, so they are not awaited by the
+        // state machine to avoid blocking the other outbox messages. Their outbox messages are
+        // truncated only once delivered, so that the next leader delivers them again in case of
+        // a failover.
+        let (callback_tx, mut callback_rx) = mpsc::channel(CALLBACK_QUEUE_SIZE);
+        let mut callback_deliveries = FuturesUnordered::new();
+
         let state_machine = StateMachine::new(
-            metadata,
             outbox_reader,
-            |msg| {
+            |seq_number, message| {
                 let mut bifrost = bifrost.clone();
+                let callback_tx = callback_tx.clone();
                 async move {
-                    append_envelope_to_bifrost(&mut bifrost, msg).await?;
-                    Ok(())
+                    match message {
+                        // callback responses leave the cluster, hence they are not sent through bifrost
+                        OutboxMessage::CallbackResponse(callback_response) => {
+                            callback_tx
+                                .send((seq_number, callback_response))
+                                .await
+                                .map_err(|_| anyhow!("callback delivery queue is closed"))?;
+                            Ok(SendOutcome::DeliveryPending)
+                        }
+                        message => {
+                            append_envelope_to_bifrost(
+                                &mut bifrost,
+                                wrap_outbox_message_in_envelope(message, seq_number, &metadata),
+                            )
+                            .await?;
+                            Ok(SendOutcome::Sent)
+                        }
+                    }
                 }
             },
             &mut hint_rx,
@@ -241,11 +296,45 @@ where
 
         loop {
             tokio::select! {
-                shuffled_message_index = state_machine.as_mut().shuffle_next_message() => {
-                    let shuffled_message_index = shuffled_message_index?;
+                shuffled_message = state_machine.as_mut().shuffle_next_message() => {
+                    let (shuffled_message_index, send_outcome) = shuffled_message?;
 
-                    // this is just a hint which we can drop
-                    let _ = truncation_tx.try_send(OutboxTruncation::new(shuffled_message_index));
+                    if send_outcome == SendOutcome::Sent {
+                        // this is just a hint which we can drop
+                        let _ = truncation_tx.try_send(OutboxTruncation::new(shuffled_message_index));
+                    }
+                },
+                Some((seq_number, callback_response)) = callback_rx.recv(), if callback_deliveries.len() < MAX_CONCURRENT_CALLBACK_DELIVERIES => {
+                    callback_deliveries.push(deliver_callback(callback_client.clone(), seq_number, callback_response, None));
+                },
+                Some((seq_number, callback_response, result)) = callback_deliveries.next() => {
+                    match result {
+                        Ok(()) => counter!(PARTITION_CALLBACK_DELIVERED).increment(1),
+                        Err(err) if err.is_retryable() => {
+                            counter!(PARTITION_CALLBACK_DELIVERY_FAILED).increment(1);
+                            warn!(
+                                restate.invocation.id = %callback_response.invocation_id,
+                                "Failed delivering the response to callback url '{}', retrying in {:?}: {}",
+                                callback_response.url,
+                                CALLBACK_REDELIVERY_DELAY,
+                                err
+                            );
+                            callback_deliveries.push(deliver_callback(callback_client.clone(), seq_number, callback_response, Some(CALLBACK_REDELIVERY_DELAY)));
+                            continue;
+                        }
+                        Err(err) => {
+                            counter!(PARTITION_CALLBACK_DELIVERY_FAILED).increment(1);
+                            warn!(
+                                restate.invocation.id = %callback_response.invocation_id,
+                                "Dropping the response for callback url '{}' which cannot be delivered: {}",
+                                callback_response.url,
+                                err
+                            );
+                        }
+                    }
+
+                    // if the truncation gets lost, the next leader delivers the response once more
+                    let _ = truncation_tx.send(OutboxTruncation::new(seq_number)).await;
                 },
                 _ = cancellation_watcher() => {
                     break;
@@ -259,6 +348,19 @@ where
     }
 }
 
+async fn deliver_callback(
+    callback_client: CallbackClient,
+    seq_number: MessageIndex,
+    callback_response: CallbackResponse,
+    delay: Option<Duration>,
+) -> (MessageIndex, CallbackResponse, Result<(), CallbackError>) {
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    let result = callback_client.deliver(&callback_response).await;
+    (seq_number, callback_response, result)
+}
+
 mod state_machine {
     use std::cmp::Ordering;
     use std::future::Future;
@@ -270,12 +372,9 @@ mod state_machine {
 
     use restate_storage_api::outbox_table::OutboxMessage;
     use restate_types::message::MessageIndex;
-    use restate_wal_protocol::Envelope;
 
     use crate::partition::shuffle;
-    use crate::partition::shuffle::{
-        wrap_outbox_message_in_envelope, NewOutboxMessage, OutboxReaderError, ShuffleMetadata,
-    };
+    use crate::partition::shuffle::{NewOutboxMessage, OutboxReaderError, SendOutcome};
 
     type ReadFuture<OutboxReader> = ReusableBoxFuture<
         'static,
@@ -294,7 +393,6 @@ mod state_machine {
 
     #[pin_project]
     pub(super) struct StateMachine<'a, OutboxReader, SendOp, SendFuture> {
-        metadata: ShuffleMetadata,
         current_sequence_number: MessageIndex,
         outbox_reader: Option<OutboxReader>,
         read_future: ReadFuture<OutboxReader>,
@@ -317,12 +415,11 @@ mod state_machine {
 
     impl<'a, OutboxReader, SendOp, SendFuture> StateMachine<'a, OutboxReader, SendOp, SendFuture>
     where
-        SendFuture: Future<Output = Result<(), anyhow::Error>>,
-        SendOp: Fn(Envelope) -> SendFuture,
+        SendFuture: Future<Output = Result<SendOutcome, anyhow::Error>>,
+        SendOp: Fn(MessageIndex, OutboxMessage) -> SendFuture,
         OutboxReader: shuffle::OutboxReader + Send + Sync + 'static,
     {
         pub(super) fn new(
-            metadata: ShuffleMetadata,
             outbox_reader: OutboxReader,
             send_operation: SendOp,
             hint_rx: &'a mut async_channel::Receiver<NewOutboxMessage>,
@@ -334,7 +431,6 @@ mod state_machine {
             let reading_future = get_next_message(outbox_reader, current_sequence_number);
 
             Self {
-                metadata,
                 current_sequence_number,
                 outbox_reader: None,
                 read_future: ReusableBoxFuture::new(reading_future),
@@ -346,7 +442,7 @@ mod state_machine {
 
         pub(super) async fn shuffle_next_message(
             self: Pin<&mut Self>,
-        ) -> Result<MessageIndex, anyhow::Error> {
+        ) -> Result<(MessageIndex, SendOutcome), anyhow::Error> {
             let mut this = self.project();
             loop {
                 match this.state.as_mut().project() {
@@ -363,12 +459,7 @@ mod state_machine {
 
                             match seq_number.cmp(this.current_sequence_number) {
                                 Ordering::Equal => {
-                                    let send_future =
-                                        (this.send_operation)(wrap_outbox_message_in_envelope(
-                                            message,
-                                            seq_number,
-                                            this.metadata,
-                                        ));
+                                    let send_future = (this.send_operation)(seq_number, message);
                                     this.state.set(State::Sending(send_future));
                                     break;
                                 }
@@ -401,9 +492,7 @@ mod state_machine {
 
                             *this.current_sequence_number = seq_number;
 
-                            let send_future = (this.send_operation)(
-                                wrap_outbox_message_in_envelope(message, seq_number, this.metadata),
-                            );
+                            let send_future = (this.send_operation)(seq_number, message);
 
                             this.state.set(State::Sending(send_future));
                         } else {
@@ -411,7 +500,7 @@ mod state_machine {
                         }
                     }
                     StateProj::Sending(send_future) => {
-                        send_future.await?;
+                        let send_outcome = send_future.await?;
 
                         let successfully_shuffled_sequence_number = *this.current_sequence_number;
                         *this.current_sequence_number += 1;
@@ -424,7 +513,7 @@ mod state_machine {
                         ));
                         this.state.set(State::ReadingOutbox);
 
-                        return Ok(successfully_shuffled_sequence_number);
+                        return Ok((successfully_shuffled_sequence_number, send_outcome));
                     }
                 }
             }
@@ -436,27 +525,50 @@ mod state_machine {
 mod tests {
     use anyhow::anyhow;
     use assert2::let_assert;
+    use bytes::Bytes;
+    use bytestring::ByteString;
     use futures::{Stream, StreamExt};
     use std::iter;
+    use std::net::SocketAddr;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
+    use std::time::Duration;
     use test_log::test;
     use tokio::sync::mpsc;
 
     use restate_bifrost::{Bifrost, LogRecord, Record};
     use restate_core::{MockNetworkSender, TaskKind, TestCoreEnv, TestCoreEnvBuilder};
+    use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys, ServiceClient};
     use restate_storage_api::outbox_table::OutboxMessage;
     use restate_storage_api::StorageError;
+    use restate_types::config::ServiceClientOptions;
     use restate_types::identifiers::{InvocationId, LeaderEpoch, PartitionId};
-    use restate_types::invocation::ServiceInvocation;
+    use restate_types::invocation::{CallbackResponse, ResponseResult, ServiceInvocation};
     use restate_types::logs::{LogId, Lsn, SequenceNumber};
     use restate_types::message::MessageIndex;
     use restate_types::partition_table::FixedPartitionTable;
+    use restate_types::retries::RetryPolicy;
     use restate_types::storage::StorageCodec;
     use restate_types::{NodeId, Version};
     use restate_wal_protocol::{Command, Envelope};
 
-    use crate::partition::shuffle::{OutboxReader, OutboxReaderError, Shuffle, ShuffleMetadata};
+    use crate::partition::callback::tests::start_callback_endpoint;
+    use crate::partition::callback::CallbackClient;
+    use crate::partition::shuffle::{
+        OutboxReader, OutboxReaderError, OutboxTruncation, Shuffle, ShuffleMetadata,
+    };
+
+    fn callback_client() -> CallbackClient {
+        CallbackClient::new(
+            ServiceClient::from_options(
+                &ServiceClientOptions::default(),
+                AssumeRoleCacheMode::None,
+                RequestIdentityKeys::default(),
+            ),
+            RetryPolicy::None,
+            Arc::from(["127.0.0.1".to_owned()]),
+        )
+    }
 
     struct MockOutboxReader {
         base_offset: MessageIndex,
@@ -618,6 +730,7 @@ mod tests {
         env: TestCoreEnv<MockNetworkSender>,
         bifrost: Bifrost,
         shuffle: Shuffle<OR>,
+        truncation_rx: mpsc::Receiver<OutboxTruncation>,
     }
 
     async fn create_shuffle_env<OR: OutboxReader + Send + Sync + 'static>(
@@ -635,7 +748,7 @@ mod tests {
             NodeId::new(0, Some(0)),
         );
 
-        let (truncation_tx, _truncation_rx) = mpsc::channel(1);
+        let (truncation_tx, truncation_rx) = mpsc::channel(1);
 
         let bifrost = tc
             .run_in_scope(
@@ -644,15 +757,98 @@ mod tests {
                 Bifrost::init_in_memory(env.metadata.clone()),
             )
             .await;
-        let shuffle = Shuffle::new(metadata, outbox_reader, truncation_tx, 1, bifrost.clone());
+        let shuffle = Shuffle::new(
+            metadata,
+            outbox_reader,
+            truncation_tx,
+            1,
+            bifrost.clone(),
+            callback_client(),
+        );
 
         ShuffleEnv {
             env,
             bifrost,
             shuffle,
+            truncation_rx,
+        }
+    }
+
+    /// Outbox reader containing a single callback response.
+    struct CallbackOutboxReader(CallbackResponse);
+
+    impl OutboxReader for CallbackOutboxReader {
+        async fn get_next_message(
+            &mut self,
+            next_sequence_number: MessageIndex,
+        ) -> Result<Option<(MessageIndex, OutboxMessage)>, OutboxReaderError> {
+            Ok((next_sequence_number == 0)
+                .then(|| (0, OutboxMessage::CallbackResponse(self.0.clone()))))
+        }
+    }
+
+    fn callback_response(address: SocketAddr) -> CallbackResponse {
+        CallbackResponse {
+            url: ByteString::from(format!("http://{address}/hooks/restate")),
+            invocation_id: InvocationId::mock_random(),
+            result: ResponseResult::Success(Bytes::from_static(b"result")),
         }
     }
 
+    #[test(tokio::test)]
+    async fn truncate_callback_response_once_delivered() -> anyhow::Result<()> {
+        let (address, requests) = start_callback_endpoint(vec![]);
+        let shuffle_env =
+            create_shuffle_env(CallbackOutboxReader(callback_response(address))).await;
+        let tc = shuffle_env.env.tc.clone();
+        let mut truncation_rx = shuffle_env.truncation_rx;
+
+        tc.run_in_scope("test", None, async {
+            tc.spawn_child(
+                TaskKind::Shuffle,
+                "shuffle",
+                None,
+                shuffle_env.shuffle.run(),
+            )?;
+
+            let truncation = truncation_rx.recv().await.expect("truncation to be sent");
+            assert_eq!(truncation.index(), 0);
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await
+    }
+
+    #[test(tokio::test)]
+    async fn keep_undelivered_callback_response() -> anyhow::Result<()> {
+        let (address, requests) = start_callback_endpoint(vec![503; 10]);
+        let shuffle_env =
+            create_shuffle_env(CallbackOutboxReader(callback_response(address))).await;
+        let tc = shuffle_env.env.tc.clone();
+        let mut truncation_rx = shuffle_env.truncation_rx;
+
+        tc.run_in_scope("test", None, async {
+            tc.spawn_child(
+                TaskKind::Shuffle,
+                "shuffle",
+                None,
+                shuffle_env.shuffle.run(),
+            )?;
+
+            // the delivery failed, but the response stays in the outbox for the redelivery
+            assert!(
+                tokio::time::timeout(Duration::from_millis(500), truncation_rx.recv())
+                    .await
+                    .is_err()
+            );
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await
+    }
+
     #[test(tokio::test)]
     async fn shuffle_consecutive_outbox() -> anyhow::Result<()> {
         let expected_messages = iter::repeat_with(|| Some(ServiceInvocation::mock()))
@@ -797,6 +993,7 @@ mod tests {
                                 truncation_tx.clone(),
                                 1,
                                 shuffle_env.bifrost.clone(),
+                                callback_client(),
                             );
                         }
 
@@ -11,7 +11,7 @@
 use restate_invoker_api::InvokeInputJournal;
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::timer_table::TimerKey;
-use restate_types::identifiers::{EntryIndex, InvocationId};
+use restate_types::identifiers::{EntryIndex, InvocationId, ServiceId};
 use restate_types::ingress;
 use restate_types::ingress::IngressResponseEnvelope;
 use restate_types::invocation::InvocationTarget;
@@ -20,6 +20,8 @@ use restate_types::message::MessageIndex;
 use restate_wal_protocol::timer::TimerKeyValue;
 use std::time::Duration;
 
+use crate::state_changes::StateMutation;
+
 #[derive(Debug, strum_macros::IntoStaticStr)]
 pub enum Action {
     Invoke {
@@ -46,12 +48,19 @@ pub enum Action {
         completion: Completion,
     },
     AbortInvocation(InvocationId),
+    RetryInvocationNow(InvocationId),
     IngressResponse(IngressResponseEnvelope<ingress::InvocationResponse>),
     IngressSubmitNotification(IngressResponseEnvelope<ingress::SubmittedInvocationNotification>),
     ScheduleInvocationStatusCleanup {
         invocation_id: InvocationId,
         retention: Duration,
     },
+    /// Published by the partition processor on leaders and followers, once the record has been
+    /// applied.
+    PublishStateChange {
+        service_id: ServiceId,
+        mutation: StateMutation,
+    },
 }
 
 impl Action {
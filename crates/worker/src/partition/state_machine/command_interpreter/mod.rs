@@ -15,6 +15,7 @@ use crate::partition::state_machine::effects::Effects;
 use crate::partition::types::{InvokerEffect, InvokerEffectKind, OutboxMessageExt};
 use assert2::let_assert;
 use bytes::Bytes;
+use bytestring::ByteString;
 use futures::{Stream, StreamExt};
 use metrics::{histogram, Histogram};
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
@@ -31,8 +32,9 @@ use restate_storage_api::timer_table::Timer;
 use restate_storage_api::Result as StorageResult;
 use restate_types::errors::{
     InvocationError, InvocationErrorCode, ALREADY_COMPLETED_INVOCATION_ERROR,
-    ATTACH_NOT_SUPPORTED_INVOCATION_ERROR, CANCELED_INVOCATION_ERROR, GONE_INVOCATION_ERROR,
-    KILLED_INVOCATION_ERROR, NOT_FOUND_INVOCATION_ERROR, WORKFLOW_ALREADY_INVOKED_INVOCATION_ERROR,
+    ATTACH_NOT_SUPPORTED_INVOCATION_ERROR, CANCELED_INVOCATION_ERROR,
+    EXPIRED_AWAKEABLE_INVOCATION_ERROR, GONE_INVOCATION_ERROR, KILLED_INVOCATION_ERROR,
+    NOT_FOUND_INVOCATION_ERROR, WORKFLOW_ALREADY_INVOKED_INVOCATION_ERROR,
 };
 use restate_types::identifiers::{
     EntryIndex, IdempotencyId, InvocationId, JournalEntryId, PartitionKey, ServiceId,
@@ -41,10 +43,11 @@ use restate_types::identifiers::{
 use restate_types::ingress;
 use restate_types::ingress::{IngressResponseEnvelope, IngressResponseResult};
 use restate_types::invocation::{
-    AttachInvocationRequest, InvocationQuery, InvocationResponse, InvocationTarget,
-    InvocationTargetType, InvocationTermination, ResponseResult, ServiceInvocation,
-    ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source, SpanRelationCause,
-    SubmitNotificationSink, TerminationFlavor, VirtualObjectHandlerType, WorkflowHandlerType,
+    AttachInvocationRequest, CallbackResponse, CompletePromiseRequest, InvocationQuery,
+    InvocationResponse, InvocationTarget, InvocationTargetType, InvocationTermination,
+    ResponseResult, ServiceInvocation, ServiceInvocationResponseSink, ServiceInvocationSpanContext,
+    Source, SpanRelationCause, SubmitNotificationSink, TerminationFlavor, VirtualObjectHandlerType,
+    WorkflowHandlerType,
 };
 use restate_types::journal::enriched::{
     AwakeableEnrichmentResult, CallEnrichmentResult, EnrichedEntryHeader, EnrichedRawEntry,
@@ -90,10 +93,14 @@ pub trait StateReader {
         key: &Bytes,
     ) -> impl Future<Output = StorageResult<Option<Bytes>>> + Send;
 
+    /// Loads up to `limit` state keys following `start_after`, or all of them if `limit` is unset
+    /// or zero. Returns the keys, and whether there are more keys after them.
     fn load_state_keys(
         &mut self,
         service_id: &ServiceId,
-    ) -> impl Future<Output = StorageResult<Vec<Bytes>>> + Send;
+        start_after: Option<&Bytes>,
+        limit: Option<usize>,
+    ) -> impl Future<Output = StorageResult<(Vec<Bytes>, bool)>> + Send;
 
     fn load_completion_result(
         &mut self,
@@ -172,6 +179,19 @@ where
                 entry_index,
                 result,
             }) => {
+                if let Some(JournalEntry::Entry(journal_entry)) =
+                    state.get_journal_entry(&id, entry_index).await?
+                {
+                    if journal_entry.ty() == EntryType::Awakeable {
+                        Self::delete_awakeable_expiry_timer(
+                            id,
+                            entry_index,
+                            &journal_entry,
+                            effects,
+                        )?;
+                    }
+                }
+
                 let completion = Completion {
                     entry_index,
                     result: result.into(),
@@ -190,6 +210,10 @@ where
                 self.handle_attach_invocation_request(effects, state, attach_invocation_request)
                     .await
             }
+            Command::CompletePromise(complete_promise_request) => {
+                self.handle_complete_promise_request(effects, state, complete_promise_request)
+                    .await
+            }
             Command::InvokerEffect(effect) => self.try_invoker_effect(effects, state, effect).await,
             Command::TruncateOutbox(index) => {
                 effects.truncate_outbox(index);
@@ -204,6 +228,10 @@ where
                 self.try_purge_invocation(purge_invocation_request.invocation_id, state, effects)
                     .await
             }
+            Command::RetryInvocation(retry_invocation_request) => {
+                self.try_retry_invocation(retry_invocation_request.invocation_id, state, effects)
+                    .await
+            }
             Command::PatchState(mutation) => {
                 self.handle_external_state_mutation(mutation, state, effects)
                     .await
@@ -729,10 +757,7 @@ where
                             effects,
                         );
                     }
-                    EnrichedEntryHeader::Awakeable { is_completed }
-                    | EnrichedEntryHeader::GetState { is_completed }
-                        if !is_completed =>
-                    {
+                    EnrichedEntryHeader::GetState { is_completed } if !is_completed => {
                         resume_invocation |= Self::cancel_journal_entry_with(
                             invocation_id,
                             &invocation_status,
@@ -741,6 +766,30 @@ where
                             canceled_result.clone(),
                         );
                     }
+                    EnrichedEntryHeader::Awakeable { is_completed } if !is_completed => {
+                        resume_invocation |= Self::cancel_journal_entry_with(
+                            invocation_id,
+                            &invocation_status,
+                            effects,
+                            journal_index,
+                            canceled_result.clone(),
+                        );
+
+                        let_assert!(
+                            Entry::Awakeable(AwakeableEntry { expiry_time, .. }) =
+                                ProtobufRawEntryCodec::deserialize(EntryType::Awakeable, entry)?
+                        );
+
+                        if let Some(expiry_time) = expiry_time {
+                            let (timer_key, _) = Timer::complete_journal_entry(
+                                expiry_time,
+                                invocation_id,
+                                journal_index,
+                            );
+
+                            effects.delete_timer(timer_key);
+                        }
+                    }
                     EnrichedEntryHeader::Sleep { is_completed } if !is_completed => {
                         resume_invocation |= Self::cancel_journal_entry_with(
                             invocation_id,
@@ -776,6 +825,42 @@ where
         Ok(resume_invocation)
     }
 
+    /// Deletes the awakeable expiry timers recorded in the invocation metadata when the
+    /// invocation ends. Deleting the timers which fired already is a no-op.
+    fn delete_awakeable_expiry_timers(
+        invocation_id: InvocationId,
+        invocation_metadata: &InFlightInvocationMetadata,
+        effects: &mut Effects,
+    ) {
+        for (journal_index, expiry_time) in &invocation_metadata.awakeable_expiry_timers {
+            let (timer_key, _) =
+                Timer::complete_journal_entry(expiry_time.as_u64(), invocation_id, *journal_index);
+
+            effects.delete_timer(timer_key);
+        }
+    }
+
+    fn delete_awakeable_expiry_timer(
+        invocation_id: InvocationId,
+        journal_index: EntryIndex,
+        journal_entry: &EnrichedRawEntry,
+        effects: &mut Effects,
+    ) -> Result<(), Error> {
+        let_assert!(
+            Entry::Awakeable(AwakeableEntry { expiry_time, .. }) =
+                journal_entry.deserialize_entry_ref::<ProtobufRawEntryCodec>()?
+        );
+
+        if let Some(expiry_time) = expiry_time {
+            let (timer_key, _) =
+                Timer::complete_journal_entry(expiry_time, invocation_id, journal_index);
+
+            effects.delete_timer(timer_key);
+        }
+
+        Ok(())
+    }
+
     fn cancel_journal_entry_with(
         invocation_id: InvocationId,
         invocation_status: &InvocationStatusProjection,
@@ -857,7 +942,29 @@ where
         Ok(())
     }
 
-    async fn on_timer<State: StateReader + ReadOnlyIdempotencyTable>(
+    async fn try_retry_invocation<State: StateReader>(
+        &mut self,
+        invocation_id: InvocationId,
+        state: &mut State,
+        effects: &mut Effects,
+    ) -> Result<(), Error> {
+        match Self::get_invocation_status_and_trace(state, &invocation_id, effects).await? {
+            InvocationStatus::Invoked(_) => {
+                // Only the invoker knows whether the invocation is waiting for a retry, running
+                // invocations are left untouched by it.
+                effects.retry_invocation_now(invocation_id);
+            }
+            _ => {
+                trace!(
+                    "Ignoring retry command as the invocation '{invocation_id}' is not invoked."
+                );
+            }
+        };
+
+        Ok(())
+    }
+
+    async fn on_timer<State: StateReader + ReadOnlyJournalTable + ReadOnlyIdempotencyTable>(
         &mut self,
         timer_value: TimerKeyValue,
         state: &mut State,
@@ -868,11 +975,29 @@ where
 
         match value {
             Timer::CompleteJournalEntry(invocation_id, entry_index) => {
+                // Sleeps complete with an empty result, whereas awakeables fail when they expire
+                let result = match state.get_journal_entry(&invocation_id, entry_index).await? {
+                    Some(JournalEntry::Entry(journal_entry))
+                        if journal_entry.ty() == EntryType::Awakeable =>
+                    {
+                        if journal_entry.header().is_completed() == Some(true) {
+                            trace!(
+                                restate.invocation.id = %invocation_id,
+                                restate.journal.index = entry_index,
+                                "Ignoring expiry of an awakeable which has been completed already."
+                            );
+                            return Ok(());
+                        }
+                        CompletionResult::from(&EXPIRED_AWAKEABLE_INVOCATION_ERROR)
+                    }
+                    _ => CompletionResult::Empty,
+                };
+
                 Self::handle_completion(
                     invocation_id,
                     Completion {
                         entry_index,
-                        result: CompletionResult::Empty,
+                        result,
                     },
                     state,
                     effects,
@@ -1005,6 +1130,8 @@ where
         let journal_length = invocation_metadata.journal_metadata.length;
         let completion_retention_time = invocation_metadata.completion_retention_time;
 
+        Self::delete_awakeable_expiry_timers(invocation_id, &invocation_metadata, effects);
+
         self.notify_invocation_result(
             invocation_id,
             invocation_metadata.invocation_target.clone(),
@@ -1073,6 +1200,8 @@ where
     ) -> Result<(), Error> {
         let journal_length = invocation_metadata.journal_metadata.length;
 
+        Self::delete_awakeable_expiry_timers(invocation_id, &invocation_metadata, effects);
+
         self.notify_invocation_result(
             invocation_id,
             invocation_metadata.invocation_target.clone(),
@@ -1152,6 +1281,18 @@ where
                     } }
                     , effects)
                 }
+                ServiceInvocationResponseSink::Callback { url } => {
+                    // the callback receiver identifies the response by the invocation id
+                    let Some(invocation_id) = invocation_id else {
+                        warn!("Dropping the response for callback url '{}' because the invocation id is unknown", url);
+                        continue;
+                    };
+                    self.handle_outgoing_message(OutboxMessage::CallbackResponse(CallbackResponse {
+                        url,
+                        invocation_id,
+                        result: result.clone(),
+                    }), effects)
+                }
             }
         }
     }
@@ -1174,7 +1315,7 @@ where
         invocation_id: InvocationId,
         entry_index: EntryIndex,
         mut journal_entry: EnrichedRawEntry,
-        invocation_metadata: InFlightInvocationMetadata,
+        mut invocation_metadata: InFlightInvocationMetadata,
     ) -> Result<(), Error> {
         debug_assert_eq!(
             entry_index, invocation_metadata.journal_metadata.length,
@@ -1283,20 +1424,35 @@ where
             }
             EnrichedEntryHeader::GetStateKeys { is_completed, .. } => {
                 if !is_completed {
+                    let_assert!(
+                        Entry::GetStateKeys(GetStateKeysEntry {
+                            limit,
+                            start_after,
+                            ..
+                        }) = journal_entry.deserialize_entry_ref::<Codec>()?
+                    );
+
                     // Load state and write completion
-                    let value = if let Some(service_id) =
+                    let (keys, has_more) = if let Some(service_id) =
                         invocation_metadata.invocation_target.as_keyed_service_id()
                     {
-                        state.load_state_keys(&service_id).await?
+                        state
+                            .load_state_keys(
+                                &service_id,
+                                start_after.as_ref(),
+                                limit.map(|limit| limit as usize),
+                            )
+                            .await?
                     } else {
                         warn!(
                             "Trying to process entry {} for a target that has no state",
                             journal_entry.header().as_entry_type()
                         );
-                        vec![]
+                        (vec![], false)
                     };
 
-                    let completion_result = Codec::serialize_get_state_keys_completion(value);
+                    let completion_result =
+                        Codec::serialize_get_state_keys_completion(keys, has_more);
                     Codec::write_completion(&mut journal_entry, completion_result.clone())?;
 
                     // We can already forward the completion
@@ -1422,53 +1578,9 @@ where
                     if let Some(service_id) =
                         invocation_metadata.invocation_target.as_keyed_service_id()
                     {
-                        // Load state and write completion
-                        let promise_metadata = state.get_promise(&service_id, &key).await?;
-
-                        let completion_result = match promise_metadata {
-                            None => {
-                                // Just register the promise completion
-                                effects.put_promise(
-                                    service_id,
-                                    key,
-                                    Promise {
-                                        state: PromiseState::Completed(completion),
-                                    },
-                                );
-                                CompletionResult::Empty
-                            }
-                            Some(Promise {
-                                state: PromiseState::NotCompleted(listeners),
-                            }) => {
-                                // Send response to listeners
-                                for listener in listeners {
-                                    self.handle_outgoing_message(
-                                        OutboxMessage::ServiceResponse(InvocationResponse {
-                                            id: listener.invocation_id(),
-                                            entry_index: listener.journal_index(),
-                                            result: completion.clone().into(),
-                                        }),
-                                        effects,
-                                    );
-                                }
-
-                                // Now register the promise completion
-                                effects.put_promise(
-                                    service_id,
-                                    key,
-                                    Promise {
-                                        state: PromiseState::Completed(completion),
-                                    },
-                                );
-                                CompletionResult::Empty
-                            }
-                            Some(Promise {
-                                state: PromiseState::Completed(_),
-                            }) => {
-                                // Conflict!
-                                (&ALREADY_COMPLETED_INVOCATION_ERROR).into()
-                            }
-                        };
+                        let completion_result = self
+                            .complete_promise(state, effects, service_id, key, completion)
+                            .await?;
 
                         Codec::write_completion(&mut journal_entry, completion_result.clone())?;
 
@@ -1620,6 +1732,26 @@ where
                         invocation_id,
                         Completion::new(entry_index, completion_result),
                     );
+                } else {
+                    let_assert!(
+                        Entry::Awakeable(AwakeableEntry { expiry_time, .. }) =
+                            journal_entry.deserialize_entry_ref::<Codec>()?
+                    );
+
+                    if let Some(expiry_time) = expiry_time {
+                        let expiry_time = MillisSinceEpoch::new(expiry_time);
+                        effects.register_timer(
+                            TimerKeyValue::complete_journal_entry(
+                                expiry_time,
+                                invocation_id,
+                                entry_index,
+                            ),
+                            invocation_metadata.journal_metadata.span_context.clone(),
+                        );
+                        invocation_metadata
+                            .awakeable_expiry_timers
+                            .push((entry_index, expiry_time));
+                    }
                 }
             }
             EnrichedEntryHeader::CompleteAwakeable {
@@ -1872,6 +2004,124 @@ where
         Ok(())
     }
 
+    async fn handle_complete_promise_request<State: ReadOnlyPromiseTable>(
+        &mut self,
+        effects: &mut Effects,
+        state: &mut State,
+        complete_promise_request: CompletePromiseRequest,
+    ) -> Result<(), Error> {
+        debug_assert!(
+            self.partition_key_range.contains(&complete_promise_request.partition_key()),
+            "Complete promise request with partition key '{}' has been delivered to a partition processor with key range '{:?}'. This indicates a bug.",
+            complete_promise_request.partition_key(),
+            self.partition_key_range);
+
+        let CompletePromiseRequest {
+            service_id,
+            key,
+            result,
+            response_sink,
+        } = complete_promise_request;
+
+        let response = match self
+            .complete_promise(
+                state,
+                effects,
+                service_id.clone(),
+                key.clone(),
+                result.into(),
+            )
+            .await?
+        {
+            CompletionResult::Failure(code, message) => {
+                debug!(
+                    restate.workflow.id = %service_id,
+                    "Ignoring the completion of promise '{}', failed with code {}",
+                    key,
+                    code
+                );
+                ResponseResult::Failure(InvocationError::new(code, message))
+            }
+            _ => ResponseResult::Success(Bytes::new()),
+        };
+
+        // Successful responses to the ingress need a target, which the ingress ignores for
+        // promise completions
+        let invocation_target = InvocationTarget::workflow(
+            service_id.service_name,
+            service_id.key,
+            key,
+            WorkflowHandlerType::Shared,
+        );
+        self.send_response_to_sinks(
+            effects,
+            response_sink,
+            response,
+            None,
+            Some(&invocation_target),
+        );
+
+        Ok(())
+    }
+
+    /// Completes the promise and wakes up the invocations awaiting it. Returns the result of the
+    /// completion, which is a failure if the promise was already completed.
+    async fn complete_promise<State: ReadOnlyPromiseTable>(
+        &mut self,
+        state: &mut State,
+        effects: &mut Effects,
+        service_id: ServiceId,
+        key: ByteString,
+        completion: EntryResult,
+    ) -> Result<CompletionResult, Error> {
+        let promise_metadata = state.get_promise(&service_id, &key).await?;
+
+        Ok(match promise_metadata {
+            None => {
+                // Just register the promise completion
+                effects.put_promise(
+                    service_id,
+                    key,
+                    Promise {
+                        state: PromiseState::Completed(completion),
+                    },
+                );
+                CompletionResult::Empty
+            }
+            Some(Promise {
+                state: PromiseState::NotCompleted(listeners),
+            }) => {
+                // Send response to listeners
+                for listener in listeners {
+                    self.handle_outgoing_message(
+                        OutboxMessage::ServiceResponse(InvocationResponse {
+                            id: listener.invocation_id(),
+                            entry_index: listener.journal_index(),
+                            result: completion.clone().into(),
+                        }),
+                        effects,
+                    );
+                }
+
+                // Now register the promise completion
+                effects.put_promise(
+                    service_id,
+                    key,
+                    Promise {
+                        state: PromiseState::Completed(completion),
+                    },
+                );
+                CompletionResult::Empty
+            }
+            Some(Promise {
+                state: PromiseState::Completed(_),
+            }) => {
+                // Conflict!
+                (&ALREADY_COMPLETED_INVOCATION_ERROR).into()
+            }
+        })
+    }
+
     fn ingress_response(
         &mut self,
         ingress_response: IngressResponseEnvelope<ingress::InvocationResponse>,
@@ -13,7 +13,7 @@ use super::*;
 use bytestring::ByteString;
 use futures::stream;
 use googletest::matcher::Matcher;
-use googletest::{all, any, assert_that, pat, unordered_elements_are};
+use googletest::{all, any, assert_that, elements_are, pat, unordered_elements_are};
 use prost::Message;
 use restate_invoker_api::EffectKind;
 use restate_service_protocol::awakeable_id::AwakeableIdentifier;
@@ -28,7 +28,7 @@ use restate_test_util::matchers::*;
 use restate_test_util::{assert_eq, let_assert};
 use restate_types::errors::codes;
 use restate_types::identifiers::{InvocationUuid, WithPartitionKey};
-use restate_types::invocation::InvocationTarget;
+use restate_types::invocation::{InvocationTarget, RetryInvocationRequest};
 use restate_types::journal::EntryResult;
 use restate_types::journal::{CompleteAwakeableEntry, Entry};
 use restate_types::service_protocol;
@@ -184,7 +184,12 @@ impl StateReader for StateReaderMock {
         todo!()
     }
 
-    async fn load_state_keys(&mut self, _: &ServiceId) -> StorageResult<Vec<Bytes>> {
+    async fn load_state_keys(
+        &mut self,
+        _: &ServiceId,
+        _: Option<&Bytes>,
+        _: Option<usize>,
+    ) -> StorageResult<(Vec<Bytes>, bool)> {
         todo!()
     }
 
@@ -410,6 +415,246 @@ async fn awakeable_with_failure() {
     assert_eq!(failure.message(), "Some failure");
 }
 
+#[test(tokio::test)]
+async fn expired_awakeable_fails() -> Result<(), Error> {
+    let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
+        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+    let mut effects = Effects::default();
+    let mut state_reader = StateReaderMock::default();
+
+    let invocation_id = state_reader.register_invoked_status_and_locked(
+        InvocationTarget::mock_virtual_object(),
+        vec![
+            JournalEntry::Entry(EnrichedRawEntry::new(
+                EnrichedEntryHeader::Input {},
+                Bytes::default(),
+            )),
+            JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Awakeable(
+                AwakeableEntry {
+                    expiry_time: Some(1337),
+                    result: None,
+                },
+            ))),
+        ],
+    );
+
+    state_machine
+        .on_apply(
+            Command::Timer(TimerKeyValue::complete_journal_entry(
+                MillisSinceEpoch::new(1337),
+                invocation_id,
+                1,
+            )),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    let expired_completion_matcher = || {
+        pat!(Completion {
+            entry_index: eq(1),
+            result: pat!(CompletionResult::Failure(
+                eq(codes::TIMEOUT),
+                eq(ByteString::from_static("awakeable expired"))
+            ))
+        })
+    };
+    assert_that!(
+        effects.into_inner(),
+        unordered_elements_are![
+            delete_timer(1),
+            pat!(Effect::StoreCompletion {
+                completion: expired_completion_matcher(),
+            }),
+            pat!(Effect::ForwardCompletion {
+                completion: expired_completion_matcher(),
+            }),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn expiry_of_completed_awakeable_is_ignored() -> Result<(), Error> {
+    let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
+        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+    let mut effects = Effects::default();
+    let mut state_reader = StateReaderMock::default();
+
+    let invocation_id = state_reader.register_invoked_status_and_locked(
+        InvocationTarget::mock_virtual_object(),
+        vec![
+            JournalEntry::Entry(EnrichedRawEntry::new(
+                EnrichedEntryHeader::Input {},
+                Bytes::default(),
+            )),
+            JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Awakeable(
+                AwakeableEntry {
+                    expiry_time: Some(1337),
+                    result: Some(EntryResult::Success(Bytes::default())),
+                },
+            ))),
+        ],
+    );
+
+    state_machine
+        .on_apply(
+            Command::Timer(TimerKeyValue::complete_journal_entry(
+                MillisSinceEpoch::new(1337),
+                invocation_id,
+                1,
+            )),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    assert_that!(effects.into_inner(), elements_are![delete_timer(1)]);
+
+    Ok(())
+}
+
+fn pending_awakeable_journal() -> Vec<JournalEntry> {
+    vec![
+        JournalEntry::Entry(EnrichedRawEntry::new(
+            EnrichedEntryHeader::Input {},
+            Bytes::default(),
+        )),
+        JournalEntry::Entry(ProtobufRawEntryCodec::serialize_enriched(Entry::Awakeable(
+            AwakeableEntry {
+                expiry_time: Some(1337),
+                result: None,
+            },
+        ))),
+    ]
+}
+
+#[test(tokio::test)]
+async fn completed_awakeable_deletes_expiry_timer() -> Result<(), Error> {
+    let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
+        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+    let mut effects = Effects::default();
+    let mut state_reader = StateReaderMock::default();
+
+    let invocation_id = state_reader.register_invoked_status_and_locked(
+        InvocationTarget::mock_virtual_object(),
+        pending_awakeable_journal(),
+    );
+
+    state_machine
+        .on_apply(
+            Command::InvocationResponse(InvocationResponse {
+                id: invocation_id,
+                entry_index: 1,
+                result: ResponseResult::Success(Bytes::from_static(b"hello")),
+            }),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        all!(
+            contains(delete_timer(1)),
+            contains(pat!(Effect::ForwardCompletion {
+                invocation_id: eq(invocation_id),
+                completion: pat!(Completion { entry_index: eq(1) })
+            }))
+        )
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn awakeable_entry_records_expiry_timer() -> Result<(), Error> {
+    let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
+        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+    let mut effects = Effects::default();
+    let mut state_reader = StateReaderMock::default();
+
+    let mut journal = pending_awakeable_journal();
+    let awakeable_entry = journal.pop().unwrap();
+    let_assert!(JournalEntry::Entry(awakeable_entry) = awakeable_entry);
+    let invocation_id = state_reader
+        .register_invoked_status_and_locked(InvocationTarget::mock_virtual_object(), journal);
+
+    state_machine
+        .on_apply(
+            Command::InvokerEffect(InvokerEffect {
+                invocation_id,
+                kind: EffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: awakeable_entry,
+                },
+            }),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    let effects = effects.into_inner();
+    assert!(effects
+        .iter()
+        .any(|effect| matches!(effect, Effect::RegisterTimer { .. })));
+    let_assert!(
+        Some(Effect::AppendJournalEntry {
+            previous_invocation_status: InvocationStatus::Invoked(metadata),
+            ..
+        }) = effects
+            .iter()
+            .find(|effect| matches!(effect, Effect::AppendJournalEntry { .. }))
+    );
+    assert_eq!(
+        metadata.awakeable_expiry_timers,
+        vec![(1, MillisSinceEpoch::new(1337))]
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn killed_invocation_deletes_awakeable_expiry_timer() -> Result<(), Error> {
+    let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
+        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+    let mut effects = Effects::default();
+    let mut state_reader = StateReaderMock::default();
+
+    let invocation_id = state_reader.register_invoked_status_and_locked(
+        InvocationTarget::mock_virtual_object(),
+        pending_awakeable_journal(),
+    );
+    state_reader
+        .invocations
+        .get_mut(&invocation_id)
+        .and_then(InvocationStatus::get_invocation_metadata_mut)
+        .unwrap()
+        .awakeable_expiry_timers
+        .push((1, MillisSinceEpoch::new(1337)));
+
+    state_machine
+        .on_apply(
+            Command::TerminateInvocation(InvocationTermination::kill(invocation_id)),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        all!(
+            contains(delete_timer(1)),
+            contains(pat!(Effect::DropJournal {
+                invocation_id: eq(invocation_id),
+            }))
+        )
+    );
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn send_response_using_invocation_id() {
     let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
@@ -638,6 +883,37 @@ fn uncompleted_invoke_entry(invocation_id: InvocationId) -> JournalEntry {
     ))
 }
 
+#[test(tokio::test)]
+async fn retry_invoked_invocation() -> Result<(), Error> {
+    let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
+        0,
+        0,
+        PartitionKey::MIN..=PartitionKey::MAX,
+    );
+    let mut state_reader = StateReaderMock::default();
+    let mut effects = Effects::default();
+
+    let invocation_id = state_reader
+        .register_invoked_status_and_locked(InvocationTarget::mock_virtual_object(), vec![]);
+
+    command_interpreter
+        .on_apply(
+            Command::RetryInvocation(RetryInvocationRequest { invocation_id }),
+            &mut effects,
+            &mut state_reader,
+        )
+        .await?;
+
+    assert_that!(
+        effects.into_inner(),
+        elements_are![pat!(Effect::SendRetryInvocationNowToInvoker(eq(
+            invocation_id
+        )))]
+    );
+
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn cancel_invoked_invocation() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
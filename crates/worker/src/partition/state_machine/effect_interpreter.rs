@@ -12,6 +12,7 @@ use super::{Effects, Error};
 
 use crate::partition::state_machine::actions::Action;
 use crate::partition::state_machine::effects::Effect;
+use crate::state_changes::StateMutation;
 use assert2::let_assert;
 use bytes::Bytes;
 use futures::{Stream, TryStreamExt};
@@ -318,15 +319,32 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                 value,
                 ..
             } => {
-                state_storage.store_state(&service_id, key, value).await?;
+                state_storage
+                    .store_state(&service_id, key.clone(), value.clone())
+                    .await?;
+
+                collector.push(Action::PublishStateChange {
+                    service_id,
+                    mutation: StateMutation::Set { key, value },
+                });
             }
             Effect::ClearState {
                 service_id, key, ..
             } => {
                 state_storage.clear_state(&service_id, &key).await?;
+
+                collector.push(Action::PublishStateChange {
+                    service_id,
+                    mutation: StateMutation::Clear { key },
+                });
             }
             Effect::ClearAllState { service_id, .. } => {
                 state_storage.clear_all_state(&service_id).await?;
+
+                collector.push(Action::PublishStateChange {
+                    service_id,
+                    mutation: StateMutation::ClearAll,
+                });
             }
             Effect::RegisterTimer { timer_value, .. } => {
                 state_storage
@@ -434,6 +452,9 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
             Effect::SendAbortInvocationToInvoker(invocation_id) => {
                 collector.push(Action::AbortInvocation(invocation_id))
             }
+            Effect::SendRetryInvocationNowToInvoker(invocation_id) => {
+                collector.push(Action::RetryInvocationNow(invocation_id))
+            }
             Effect::SendStoredEntryAckToInvoker(invocation_id, entry_index) => {
                 collector.push(Action::AckStoredEntry {
                     invocation_id,
@@ -441,7 +462,7 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                 });
             }
             Effect::MutateState(state_mutation) => {
-                Self::mutate_state(state_storage, state_mutation).await?;
+                Self::mutate_state(state_storage, collector, state_mutation).await?;
             }
             Effect::IngressResponse(ingress_response) => {
                 collector.push(Action::IngressResponse(ingress_response));
@@ -500,7 +521,7 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                     return Ok(());
                 }
                 InboxEntry::StateMutation(state_mutation) => {
-                    Self::mutate_state(state_storage, state_mutation).await?;
+                    Self::mutate_state(state_storage, collector, state_mutation).await?;
                 }
             }
         }
@@ -514,6 +535,7 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
 
     async fn mutate_state<S: StateStorage>(
         state_storage: &mut S,
+        collector: &mut ActionCollector,
         state_mutation: ExternalStateMutation,
     ) -> StorageResult<()> {
         let ExternalStateMutation {
@@ -542,12 +564,22 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
         for (key, _) in &all_user_states {
             if !state.contains_key(key) {
                 state_storage.clear_state(&service_id, key).await?;
+                collector.push(Action::PublishStateChange {
+                    service_id: service_id.clone(),
+                    mutation: StateMutation::Clear { key: key.clone() },
+                });
             }
         }
 
         // overwrite existing key value pairs
         for (key, value) in state {
-            state_storage.store_state(&service_id, key, value).await?
+            state_storage
+                .store_state(&service_id, key.clone(), value.clone())
+                .await?;
+            collector.push(Action::PublishStateChange {
+                service_id: service_id.clone(),
+                mutation: StateMutation::Set { key, value },
+            });
         }
 
         Ok(())
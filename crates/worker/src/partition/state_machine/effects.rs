@@ -21,13 +21,14 @@ use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::promise_table::Promise;
 use restate_storage_api::promise_table::PromiseState;
 use restate_storage_api::timer_table::{Timer, TimerKey};
+use restate_types::config::{Configuration, JournalEntryTracing};
 use restate_types::deployment::PinnedDeployment;
 use restate_types::errors::InvocationErrorCode;
 use restate_types::identifiers::{EntryIndex, IdempotencyId, InvocationId, ServiceId};
 use restate_types::ingress;
 use restate_types::ingress::{IngressResponseEnvelope, IngressResponseResult};
 use restate_types::invocation::{
-    InvocationResponse, InvocationTarget, ResponseResult, ServiceInvocation,
+    CallbackResponse, InvocationResponse, InvocationTarget, ResponseResult, ServiceInvocation,
     ServiceInvocationResponseSink, ServiceInvocationSpanContext, SpanRelation,
 };
 use restate_types::journal::enriched::EnrichedRawEntry;
@@ -37,11 +38,11 @@ use restate_types::state_mut::ExternalStateMutation;
 use restate_types::time::MillisSinceEpoch;
 use restate_wal_protocol::timer::TimerKeyDisplay;
 use restate_wal_protocol::timer::TimerKeyValue;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::time::Duration;
 use std::vec::Drain;
-use tracing::{debug_span, event_enabled, span_enabled, trace, trace_span, Level};
+use tracing::{debug_span, event_enabled, info_span, span_enabled, trace, trace_span, Level, Span};
 
 #[derive(Debug)]
 pub(crate) enum Effect {
@@ -171,6 +172,7 @@ pub(crate) enum Effect {
 
     // Invoker commands
     SendAbortInvocationToInvoker(InvocationId),
+    SendRetryInvocationNowToInvoker(InvocationId),
     SendStoredEntryAckToInvoker(InvocationId, EntryIndex),
 
     // State mutations
@@ -214,8 +216,41 @@ macro_rules! info_span_if_leader {
     }};
 }
 
+/// Spans of the journal entries which are not completed yet, by invocation and entry index.
+type JournalEntrySpans = HashMap<InvocationId, HashMap<EntryIndex, Span>>;
+
 impl Effect {
-    fn log(&self, is_leader: bool) {
+    /// Ends the spans of the journal entries completed or dropped by this effect, by dropping them.
+    fn end_journal_entry_spans(&self, journal_entry_spans: &mut JournalEntrySpans) {
+        match self {
+            Effect::StoreCompletion {
+                invocation_id,
+                completion,
+            }
+            | Effect::ForwardCompletion {
+                invocation_id,
+                completion,
+            } => {
+                if let Some(entry_spans) = journal_entry_spans.get_mut(invocation_id) {
+                    entry_spans.remove(&completion.entry_index);
+                    if entry_spans.is_empty() {
+                        journal_entry_spans.remove(invocation_id);
+                    }
+                }
+            }
+            Effect::DropJournal { invocation_id, .. } => {
+                journal_entry_spans.remove(invocation_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn log(
+        &self,
+        is_leader: bool,
+        journal_entry_tracing: JournalEntryTracing,
+        journal_entry_spans: &mut JournalEntrySpans,
+    ) {
         match self {
             Effect::InvokeService(ServiceInvocation { .. }) => {
                 debug_if_leader!(is_leader, "Effect: Invoke service")
@@ -326,6 +361,15 @@ impl Effect {
                 e,
                 entry_index
             ),
+            Effect::EnqueueIntoOutbox {
+                seq_number,
+                message: OutboxMessage::CallbackResponse(CallbackResponse { invocation_id, .. }),
+            } => debug_if_leader!(
+                is_leader,
+                restate.invocation.id = %invocation_id,
+                restate.outbox.seq = seq_number,
+                "Effect: Send response to callback url"
+            ),
             Effect::IngressResponse(IngressResponseEnvelope {
                 inner:
                     ingress::InvocationResponse {
@@ -544,14 +588,64 @@ impl Effect {
                 journal_entry,
                 entry_index,
                 invocation_id,
-                ..
-            } => debug_if_leader!(
-                is_leader,
-                restate.journal.index = entry_index,
-                restate.invocation.id = %invocation_id,
-                "Effect: Write journal entry {:?} to storage",
-                journal_entry.header().as_entry_type()
-            ),
+                previous_invocation_status,
+            } => {
+                let entry_type = journal_entry.header().as_entry_type();
+                if let (Some(journal_metadata), Some(invocation_target)) = (
+                    previous_invocation_status.get_journal_metadata(),
+                    previous_invocation_status.invocation_target(),
+                ) {
+                    let span_context = &journal_metadata.span_context;
+                    match journal_entry_tracing {
+                        JournalEntryTracing::None => {}
+                        JournalEntryTracing::Events => {
+                            // the span is turned into an event of the invocation span by the
+                            // tracing instrumentation, when the invocation span ends
+                            info_span_if_leader!(
+                                is_leader,
+                                span_context.is_sampled(),
+                                span_context.as_parent(),
+                                "journal_entry",
+                                otel.name = format!("journal_entry {entry_type}"),
+                                restate.journal.index = entry_index,
+                                restate.journal.entry_type = %entry_type,
+                                restate.internal.span_event = true,
+                            );
+                        }
+                        JournalEntryTracing::Spans if is_leader && span_context.is_sampled() => {
+                            let span = info_span!(
+                                "journal_entry",
+                                otel.name = format!("journal_entry {entry_type}"),
+                                rpc.service = %invocation_target.service_name(),
+                                rpc.method = %invocation_target.handler_name(),
+                                restate.invocation.id = %invocation_id,
+                                restate.journal.index = entry_index,
+                                restate.journal.entry_type = %entry_type,
+                            );
+                            span_context.as_parent().attach_to_span(&span);
+
+                            // the span of an entry waiting for its completion lasts until the
+                            // completion is stored, e.g. when the sleep timer fires or the call
+                            // returns, otherwise it ends right away
+                            if journal_entry.header().is_completed() == Some(false) {
+                                journal_entry_spans
+                                    .entry(*invocation_id)
+                                    .or_default()
+                                    .insert(*entry_index, span);
+                            }
+                        }
+                        JournalEntryTracing::Spans => {}
+                    }
+                }
+
+                debug_if_leader!(
+                    is_leader,
+                    restate.journal.index = entry_index,
+                    restate.invocation.id = %invocation_id,
+                    "Effect: Write journal entry {:?} to storage",
+                    entry_type
+                )
+            }
             Effect::StoreCompletion {
                 completion:
                     Completion {
@@ -649,6 +743,9 @@ impl Effect {
             Effect::SendAbortInvocationToInvoker(invocation_id) => {
                 debug_if_leader!(is_leader, restate.invocation.id = %invocation_id, "Effect: Send abort command to invoker");
             }
+            Effect::SendRetryInvocationNowToInvoker(invocation_id) => {
+                debug_if_leader!(is_leader, restate.invocation.id = %invocation_id, "Effect: Send retry now command to invoker");
+            }
             Effect::SendStoredEntryAckToInvoker(_, _) => {
                 // We can ignore these
             }
@@ -723,6 +820,8 @@ pub struct Effects {
     related_invocation_target: Option<InvocationTarget>,
     related_span: SpanRelation,
     effects: Vec<Effect>,
+    /// Not cleared together with the effects, as the spans end when the entries are completed.
+    journal_entry_spans: JournalEntrySpans,
 }
 
 impl Effects {
@@ -1029,6 +1128,11 @@ impl Effects {
             .push(Effect::SendAbortInvocationToInvoker(invocation_id));
     }
 
+    pub(crate) fn retry_invocation_now(&mut self, invocation_id: InvocationId) {
+        self.effects
+            .push(Effect::SendRetryInvocationNowToInvoker(invocation_id));
+    }
+
     pub(crate) fn store_idempotency_id(
         &mut self,
         idempotency_id: IdempotencyId,
@@ -1077,7 +1181,20 @@ impl Effects {
 
     /// We log only if the log level is TRACE, or if the log level is DEBUG and we're the leader,
     /// or if the span level is INFO and we're the leader.
-    pub(crate) fn log(&self, is_leader: bool) {
+    pub(crate) fn log(&mut self, is_leader: bool) {
+        self.log_effects(is_leader);
+
+        // The spans of the journal entries must end even if logging is disabled or this
+        // partition processor is no longer the leader. This happens after logging the effects,
+        // as an entry can be completed by the same command which appended it.
+        if !self.journal_entry_spans.is_empty() {
+            for effect in self.effects.iter() {
+                effect.end_journal_entry_spans(&mut self.journal_entry_spans);
+            }
+        }
+    }
+
+    fn log_effects(&mut self, is_leader: bool) {
         // Skip this method altogether if logging is disabled
         if !(((event_enabled!(Level::DEBUG) || span_enabled!(Level::INFO)) && is_leader)
             || event_enabled!(Level::TRACE))
@@ -1130,8 +1247,13 @@ impl Effects {
         let _enter = span.enter();
 
         // Log all the effects
+        let journal_entry_tracing = Configuration::pinned().common.tracing_journal_entries;
         for effect in self.effects.iter() {
-            effect.log(is_leader);
+            effect.log(
+                is_leader,
+                journal_entry_tracing,
+                &mut self.journal_entry_spans,
+            );
         }
     }
 }
@@ -89,6 +89,7 @@ mod tests {
     use super::*;
 
     use crate::partition::types::{InvokerEffect, InvokerEffectKind};
+    use crate::state_changes::StateMutation;
     use assert2::assert;
     use bytes::Bytes;
     use bytestring::ByteString;
@@ -124,7 +125,7 @@ mod tests {
     };
     use restate_types::journal::enriched::EnrichedRawEntry;
     use restate_types::journal::{Completion, CompletionResult, EntryResult};
-    use restate_types::journal::{Entry, EntryType};
+    use restate_types::journal::{Entry, EntryType, GetStateKeysEntry};
     use restate_types::live::{Constant, Live};
     use restate_types::state_mut::ExternalStateMutation;
     use restate_types::{ingress, GenerationalNodeId};
@@ -578,7 +579,7 @@ mod tests {
         let invocation_id =
             mock_start_invocation_with_service_id(&mut state_machine, service_id.clone()).await;
 
-        state_machine
+        let actions = state_machine
             .apply(Command::InvokerEffect(InvokerEffect {
                 invocation_id,
                 kind: InvokerEffectKind::JournalEntry {
@@ -587,6 +588,13 @@ mod tests {
                 },
             }))
             .await;
+        assert_that!(
+            actions,
+            contains(pat!(Action::PublishStateChange {
+                service_id: eq(service_id.clone()),
+                mutation: eq(StateMutation::ClearAll),
+            }))
+        );
 
         let states: Vec<restate_storage_api::Result<(Bytes, Bytes)>> = state_machine
             .rocksdb_storage
@@ -598,6 +606,67 @@ mod tests {
         Ok(())
     }
 
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+    async fn publish_only_applied_state_changes() -> TestResult {
+        let tc = TaskCenterBuilder::default()
+            .default_runtime_handle(tokio::runtime::Handle::current())
+            .build()
+            .expect("task_center builds");
+        let mut state_machine = tc
+            .run_in_scope("mock-state-machine", None, MockStateMachine::create())
+            .await;
+        let service_id = ServiceId::mock_random();
+        let state = HashMap::from([(Bytes::from_static(b"key"), Bytes::from_static(b"value"))]);
+
+        // the patch is rejected because the expected version doesn't match
+        let actions = state_machine
+            .apply(Command::PatchState(ExternalStateMutation {
+                service_id: service_id.clone(),
+                version: Some("not-the-current-version".to_owned()),
+                state: state.clone(),
+            }))
+            .await;
+        assert_that!(
+            actions,
+            not(contains(pat!(Action::PublishStateChange { .. })))
+        );
+
+        let actions = state_machine
+            .apply(Command::PatchState(ExternalStateMutation {
+                service_id: service_id.clone(),
+                version: None,
+                state,
+            }))
+            .await;
+        assert_that!(
+            actions,
+            contains(pat!(Action::PublishStateChange {
+                service_id: eq(service_id.clone()),
+                mutation: eq(StateMutation::Set {
+                    key: Bytes::from_static(b"key"),
+                    value: Bytes::from_static(b"value"),
+                }),
+            }))
+        );
+
+        // the effects of invocations which are no longer running are ignored
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                invocation_id: InvocationId::mock_random(),
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::clear_all_state()),
+                },
+            }))
+            .await;
+        assert_that!(
+            actions,
+            not(contains(pat!(Action::PublishStateChange { .. })))
+        );
+
+        Ok(())
+    }
+
     #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
     async fn get_state_keys() -> TestResult {
         let tc = TaskCenterBuilder::default()
@@ -634,10 +703,131 @@ mod tests {
                 invocation_id: eq(invocation_id),
                 completion: eq(Completion::new(
                     1,
-                    ProtobufRawEntryCodec::serialize_get_state_keys_completion(vec![
-                        Bytes::copy_from_slice(b"key1"),
-                        Bytes::copy_from_slice(b"key2"),
-                    ])
+                    ProtobufRawEntryCodec::serialize_get_state_keys_completion(
+                        vec![
+                            Bytes::copy_from_slice(b"key1"),
+                            Bytes::copy_from_slice(b"key2"),
+                        ],
+                        false,
+                    )
+                ))
+            }))
+        );
+        Ok(())
+    }
+
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+    async fn get_state_keys_paged() -> TestResult {
+        let tc = TaskCenterBuilder::default()
+            .default_runtime_handle(tokio::runtime::Handle::current())
+            .build()
+            .expect("task_center builds");
+        let mut state_machine = tc
+            .run_in_scope("mock-state-machine", None, MockStateMachine::create())
+            .await;
+        let service_id = ServiceId::mock_random();
+        let invocation_id =
+            mock_start_invocation_with_service_id(&mut state_machine, service_id.clone()).await;
+
+        // Mock some state
+        let mut txn = state_machine.rocksdb_storage.transaction();
+        txn.put_user_state(&service_id, b"key1", b"value1").await;
+        txn.put_user_state(&service_id, b"key2", b"value2").await;
+        txn.put_user_state(&service_id, b"key3", b"value3").await;
+        txn.commit().await.unwrap();
+
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                invocation_id,
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::GetStateKeys(
+                        GetStateKeysEntry {
+                            limit: Some(2),
+                            start_after: None,
+                            value: None,
+                        },
+                    )),
+                },
+            }))
+            .await;
+        assert_that!(
+            actions,
+            contains(pat!(Action::ForwardCompletion {
+                invocation_id: eq(invocation_id),
+                completion: eq(Completion::new(
+                    1,
+                    ProtobufRawEntryCodec::serialize_get_state_keys_completion(
+                        vec![
+                            Bytes::copy_from_slice(b"key1"),
+                            Bytes::copy_from_slice(b"key2"),
+                        ],
+                        true,
+                    )
+                ))
+            }))
+        );
+
+        // Get the next page
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                invocation_id,
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 2,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::GetStateKeys(
+                        GetStateKeysEntry {
+                            limit: Some(2),
+                            start_after: Some(Bytes::copy_from_slice(b"key2")),
+                            value: None,
+                        },
+                    )),
+                },
+            }))
+            .await;
+        assert_that!(
+            actions,
+            contains(pat!(Action::ForwardCompletion {
+                invocation_id: eq(invocation_id),
+                completion: eq(Completion::new(
+                    2,
+                    ProtobufRawEntryCodec::serialize_get_state_keys_completion(
+                        vec![Bytes::copy_from_slice(b"key3")],
+                        false,
+                    )
+                ))
+            }))
+        );
+
+        // A zero limit returns all the keys
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                invocation_id,
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 3,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::GetStateKeys(
+                        GetStateKeysEntry {
+                            limit: Some(0),
+                            start_after: None,
+                            value: None,
+                        },
+                    )),
+                },
+            }))
+            .await;
+        assert_that!(
+            actions,
+            contains(pat!(Action::ForwardCompletion {
+                invocation_id: eq(invocation_id),
+                completion: eq(Completion::new(
+                    3,
+                    ProtobufRawEntryCodec::serialize_get_state_keys_completion(
+                        vec![
+                            Bytes::copy_from_slice(b"key1"),
+                            Bytes::copy_from_slice(b"key2"),
+                            Bytes::copy_from_slice(b"key3"),
+                        ],
+                        false,
+                    )
                 ))
             }))
         );
@@ -1617,12 +1807,17 @@ mod tests {
         use std::time::Duration;
 
         use restate_storage_api::invocation_status_table::{CompletedInvocation, StatusTimestamps};
+        use restate_storage_api::outbox_table::OutboxMessage;
+        use restate_storage_api::promise_table::{Promise, PromiseState, ReadOnlyPromiseTable};
         use restate_storage_api::service_status_table::ReadOnlyVirtualObjectStatusTable;
         use restate_storage_api::timer_table::{Timer, TimerKey, TimerKeyKind};
-        use restate_types::errors::WORKFLOW_ALREADY_INVOKED_INVOCATION_ERROR;
+        use restate_types::errors::{
+            ALREADY_COMPLETED_INVOCATION_ERROR, WORKFLOW_ALREADY_INVOKED_INVOCATION_ERROR,
+        };
         use restate_types::invocation::{
-            AttachInvocationRequest, InvocationQuery, InvocationTarget,
+            AttachInvocationRequest, CompletePromiseRequest, InvocationQuery, InvocationTarget,
         };
+        use restate_types::journal::GetPromiseEntry;
         use restate_wal_protocol::timer::TimerKeyValue;
         use test_log::test;
 
@@ -1967,6 +2162,124 @@ mod tests {
             );
         }
 
+        #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+        async fn complete_promise_from_outside() {
+            let tc = TaskCenterBuilder::default()
+                .default_runtime_handle(tokio::runtime::Handle::current())
+                .build()
+                .expect("task_center builds");
+            let mut state_machine = tc
+                .run_in_scope("mock-state-machine", None, MockStateMachine::create())
+                .await;
+
+            let invocation_target = InvocationTarget::mock_workflow();
+            let service_id = invocation_target.as_keyed_service_id().unwrap();
+            let invocation_id = InvocationId::mock_random();
+            let promise_key = ByteString::from_static("approval");
+
+            // Start the workflow and await the promise
+            let _ = state_machine
+                .apply_multiple([
+                    Command::Invoke(ServiceInvocation {
+                        invocation_id,
+                        invocation_target: invocation_target.clone(),
+                        ..ServiceInvocation::mock()
+                    }),
+                    Command::InvokerEffect(InvokerEffect {
+                        invocation_id,
+                        kind: InvokerEffectKind::JournalEntry {
+                            entry_index: 1,
+                            entry: ProtobufRawEntryCodec::serialize_enriched(Entry::GetPromise(
+                                GetPromiseEntry {
+                                    key: promise_key.clone(),
+                                    value: None,
+                                },
+                            )),
+                        },
+                    }),
+                ])
+                .await;
+
+            // Completing the promise wakes up the waiting entry
+            let node_id = GenerationalNodeId::new(1, 1);
+            let request_id_1 = IngressRequestId::default();
+            let request_id_2 = IngressRequestId::default();
+            let approved = Bytes::from_static(b"approved");
+            let actions = state_machine
+                .apply(Command::CompletePromise(CompletePromiseRequest {
+                    service_id: service_id.clone(),
+                    key: promise_key.clone(),
+                    result: ResponseResult::Success(approved.clone()),
+                    response_sink: Some(ServiceInvocationResponseSink::Ingress {
+                        node_id,
+                        request_id: request_id_1,
+                    }),
+                }))
+                .await;
+            assert_that!(
+                actions,
+                all!(
+                    contains(pat!(Action::NewOutboxMessage {
+                        message: pat!(OutboxMessage::ServiceResponse(pat!(InvocationResponse {
+                            id: eq(invocation_id),
+                            entry_index: eq(1),
+                            result: eq(ResponseResult::Success(approved.clone()))
+                        })))
+                    })),
+                    contains(pat!(Action::IngressResponse(pat!(
+                        IngressResponseEnvelope {
+                            target_node: eq(node_id),
+                            inner: pat!(ingress::InvocationResponse {
+                                request_id: eq(request_id_1),
+                                response: pat!(IngressResponseResult::Success(
+                                    anything(),
+                                    eq(Bytes::new())
+                                ))
+                            })
+                        }
+                    ))))
+                )
+            );
+
+            // A second completion doesn't overwrite the first one, and is rejected with a conflict
+            let actions = state_machine
+                .apply(Command::CompletePromise(CompletePromiseRequest {
+                    service_id: service_id.clone(),
+                    key: promise_key.clone(),
+                    result: ResponseResult::Success(Bytes::from_static(b"rejected")),
+                    response_sink: Some(ServiceInvocationResponseSink::Ingress {
+                        node_id,
+                        request_id: request_id_2,
+                    }),
+                }))
+                .await;
+            assert_that!(
+                actions,
+                contains(pat!(Action::IngressResponse(pat!(
+                    IngressResponseEnvelope {
+                        target_node: eq(node_id),
+                        inner: pat!(ingress::InvocationResponse {
+                            request_id: eq(request_id_2),
+                            response: eq(IngressResponseResult::Failure(
+                                ALREADY_COMPLETED_INVOCATION_ERROR
+                            ))
+                        })
+                    }
+                ))))
+            );
+            assert_that!(
+                state_machine
+                    .storage()
+                    .transaction()
+                    .get_promise(&service_id, &promise_key)
+                    .await
+                    .unwrap(),
+                some(eq(Promise {
+                    state: PromiseState::Completed(EntryResult::Success(approved))
+                }))
+            );
+        }
+
         #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
         async fn timer_cleanup() {
             let tc = TaskCenterBuilder::default()
@@ -101,4 +101,19 @@ where
 
         Ok(EagerState::new_complete(user_states.into_iter()))
     }
+
+    async fn read_state_keys<'a>(
+        &'a mut self,
+        service_id: &'a ServiceId,
+        keys: &'a [Bytes],
+    ) -> Result<EagerState<Self::StateIter>, Self::Error> {
+        let mut user_states = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(value) = self.0.get_user_state(service_id, key).await? {
+                user_states.push((key.clone(), value));
+            }
+        }
+
+        Ok(EagerState::new_partial(user_states.into_iter()))
+    }
 }
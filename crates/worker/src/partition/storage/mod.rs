@@ -301,11 +301,31 @@ where
         super::state_machine::StateStorage::load_state(self, service_id, key).await
     }
 
-    async fn load_state_keys(&mut self, service_id: &ServiceId) -> StorageResult<Vec<Bytes>> {
-        super::state_machine::StateStorage::get_all_user_states(self, service_id)
-            .map(|res| res.map(|v| v.0))
+    async fn load_state_keys(
+        &mut self,
+        service_id: &ServiceId,
+        start_after: Option<&Bytes>,
+        limit: Option<usize>,
+    ) -> StorageResult<(Vec<Bytes>, bool)> {
+        self.assert_partition_key(service_id);
+        // A zero limit is treated as unset, otherwise the SDK would keep asking for empty pages
+        let limit = limit.filter(|limit| *limit > 0);
+        // Read one more key, to know whether there are more keys after this page
+        let mut keys: Vec<_> = self
+            .inner
+            .get_user_state_keys(
+                service_id,
+                start_after.map(|key| key.as_ref()),
+                limit.map(|limit| limit.saturating_add(1)),
+            )
             .try_collect()
-            .await
+            .await?;
+
+        let has_more = limit.is_some_and(|limit| keys.len() > limit);
+        if let Some(limit) = limit {
+            keys.truncate(limit);
+        }
+        Ok((keys, has_more))
     }
 
     async fn load_completion_result(
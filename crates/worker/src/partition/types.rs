@@ -50,6 +50,11 @@ impl OutboxMessageExt for OutboxMessage {
             OutboxMessage::ServiceInvocation(si) => Command::Invoke(si),
             OutboxMessage::ServiceResponse(sr) => Command::InvocationResponse(sr),
             OutboxMessage::InvocationTermination(it) => Command::TerminateInvocation(it),
+            OutboxMessage::CallbackResponse(_) => {
+                unreachable!(
+                    "callback responses are delivered by the shuffle and are never sent to bifrost"
+                )
+            }
         }
     }
 }
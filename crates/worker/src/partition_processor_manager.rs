@@ -34,6 +34,7 @@ use restate_core::{cancellation_watcher, Metadata, ShutdownError, TaskId, TaskKi
 use restate_invoker_impl::InvokerHandle;
 use restate_metadata_store::{MetadataStoreClient, ReadModifyWriteError};
 use restate_partition_store::{OpenMode, PartitionStore, PartitionStoreManager};
+use restate_service_client::ServiceClient;
 use restate_storage_api::StorageError;
 use restate_types::cluster::cluster_state::ReplayStatus;
 use restate_types::cluster::cluster_state::{PartitionProcessorStatus, RunMode};
@@ -62,9 +63,11 @@ use crate::metric_definitions::PARTITION_LAST_APPLIED_LOG_LSN;
 use crate::metric_definitions::PARTITION_LAST_PERSISTED_LOG_LSN;
 use crate::metric_definitions::PARTITION_TIME_SINCE_LAST_RECORD;
 use crate::metric_definitions::PARTITION_TIME_SINCE_LAST_STATUS_UPDATE;
+use crate::partition::callback::CallbackClient;
 use crate::partition::storage::invoker::InvokerStorageReader;
 use crate::partition::storage::PartitionStorage;
 use crate::partition::PartitionProcessorControlCommand;
+use crate::state_changes::StateChangeFeed;
 use crate::PartitionProcessor;
 
 pub struct PartitionProcessorManager {
@@ -81,6 +84,8 @@ pub struct PartitionProcessorManager {
     networking: Networking,
     bifrost: Bifrost,
     invoker_handle: InvokerHandle<InvokerStorageReader<PartitionStore>>,
+    service_client: ServiceClient,
+    state_change_feed: StateChangeFeed,
     rx: mpsc::Receiver<ProcessorsManagerCommand>,
     tx: mpsc::Sender<ProcessorsManagerCommand>,
     latest_attach_response: Option<(GenerationalNodeId, AttachResponse)>,
@@ -116,6 +121,8 @@ impl PartitionProcessorManager {
         networking: Networking,
         bifrost: Bifrost,
         invoker_handle: InvokerHandle<InvokerStorageReader<PartitionStore>>,
+        service_client: ServiceClient,
+        state_change_feed: StateChangeFeed,
     ) -> Self {
         let attach_router = RpcRouter::new(networking.clone(), router_builder);
         let incoming_get_state = router_builder.subscribe_to_stream(2);
@@ -133,6 +140,8 @@ impl PartitionProcessorManager {
             networking,
             bifrost,
             invoker_handle,
+            service_client,
+            state_change_feed,
             attach_router,
             rx,
             tx,
@@ -309,6 +318,14 @@ impl PartitionProcessorManager {
                 let live_partitions = self.running_partition_processors.keys().cloned().collect();
                 let _ = sender.send(live_partitions);
             }
+            GetState(sender) => {
+                let state = self
+                    .running_partition_processors
+                    .iter()
+                    .map(|(partition_id, state)| (*partition_id, state.watch_rx.borrow().clone()))
+                    .collect();
+                let _ = sender.send(state);
+            }
         }
     }
 
@@ -376,6 +393,12 @@ impl PartitionProcessorManager {
             control_rx,
             watch_tx,
             self.invoker_handle.clone(),
+            CallbackClient::new(
+                self.service_client.clone(),
+                options.callback_retry_policy.clone(),
+                config.ingress.callback_allowed_hosts.clone().into(),
+            ),
+            self.state_change_feed.clone(),
         );
         let networking = self.networking.clone();
         let mut bifrost = self.bifrost.clone();
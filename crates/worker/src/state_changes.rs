@@ -0,0 +1,328 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+
+use restate_core::Metadata;
+use restate_types::identifiers::{PartitionId, ServiceId};
+use restate_types::logs::Lsn;
+
+/// Number of state changes retained per partition, to let subscribers resume after the last
+/// change they processed.
+const RETAINED_STATE_CHANGES: usize = 1024;
+
+/// Mutation of the state of a virtual object or workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateMutation {
+    Set { key: Bytes, value: Bytes },
+    Clear { key: Bytes },
+    ClearAll,
+}
+
+/// A state mutation applied by a partition processor, together with the position of the log
+/// record which caused it. Changes of the same partition are streamed in log order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    pub partition_id: PartitionId,
+    pub lsn: Lsn,
+    pub service_id: ServiceId,
+    pub mutation: StateMutation,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StateChangeFeedError {
+    #[error("the partition table is not available yet")]
+    NoPartitionTable,
+    #[error("partition {0} is not running on this node")]
+    PartitionNotRunning(PartitionId),
+    #[error("the state changes of partition {0} up to lsn {1} are no longer retained, the state needs to be re-synchronized from the state table")]
+    Trimmed(PartitionId, Lsn),
+    #[error("the subscriber fell behind the state changes of partition {0}, the state needs to be re-synchronized from the state table")]
+    Lagged(PartitionId),
+}
+
+/// Feed of the state changes of virtual objects and workflows, published by the partition
+/// processors of this node once they applied them.
+///
+/// Leaders and followers apply the same records, hence the changes don't depend on which node
+/// leads a partition. Changes which the partition processor discards, like a state patch with an
+/// outdated version or the journal entries of a killed invocation, are not published.
+///
+/// The last [`RETAINED_STATE_CHANGES`] changes of each partition are kept in memory, so that
+/// subscribers can resume after the last [`StateChange::lsn`] they processed. The retained changes
+/// are lost on restart.
+#[derive(Clone)]
+pub struct StateChangeFeed {
+    metadata: Metadata,
+    publishers: Arc<Mutex<HashMap<PartitionId, StateChangePublisher>>>,
+}
+
+impl StateChangeFeed {
+    pub fn new(metadata: Metadata) -> Self {
+        Self {
+            metadata,
+            publishers: Default::default(),
+        }
+    }
+
+    /// Returns the publisher for the partition processor of `partition_id`, which applies the
+    /// records after `last_applied_lsn`.
+    pub(crate) fn publisher(
+        &self,
+        partition_id: PartitionId,
+        last_applied_lsn: Lsn,
+    ) -> StateChangePublisher {
+        let publisher = self
+            .publishers
+            .lock()
+            .entry(partition_id)
+            .or_insert_with(|| StateChangePublisher::new(partition_id))
+            .clone();
+        publisher.start(last_applied_lsn);
+        publisher
+    }
+
+    /// Streams the state changes of all partitions. The changes of a partition start after the
+    /// lsn given in `resume_after`, or with the next applied record if absent.
+    ///
+    /// The stream ends with an error if the subscriber falls behind the published changes.
+    pub fn subscribe(
+        &self,
+        resume_after: &BTreeMap<PartitionId, Lsn>,
+    ) -> Result<BoxStream<'static, Result<StateChange, StateChangeFeedError>>, StateChangeFeedError>
+    {
+        let partition_table = self
+            .metadata
+            .partition_table()
+            .ok_or(StateChangeFeedError::NoPartitionTable)?;
+
+        let publishers = self.publishers.lock();
+        let mut partition_streams = Vec::with_capacity(partition_table.num_partitions() as usize);
+        for (partition_id, _) in partition_table.partitioner() {
+            let publisher = publishers
+                .get(&partition_id)
+                .ok_or(StateChangeFeedError::PartitionNotRunning(partition_id))?;
+            partition_streams.push(publisher.subscribe(resume_after.get(&partition_id).copied())?);
+        }
+
+        Ok(stream::select_all(partition_streams).boxed())
+    }
+}
+
+/// Publishes the state changes applied by the partition processor of a partition.
+#[derive(Clone)]
+pub(crate) struct StateChangePublisher {
+    partition_id: PartitionId,
+    inner: Arc<Mutex<PublisherState>>,
+}
+
+struct PublisherState {
+    /// The changes of all the records after this lsn are retained. Unset until a partition
+    /// processor started.
+    retained_after: Option<Lsn>,
+    retained: VecDeque<StateChange>,
+    tx: broadcast::Sender<StateChange>,
+}
+
+impl StateChangePublisher {
+    fn new(partition_id: PartitionId) -> Self {
+        Self {
+            partition_id,
+            inner: Arc::new(Mutex::new(PublisherState {
+                retained_after: None,
+                retained: VecDeque::with_capacity(RETAINED_STATE_CHANGES),
+                tx: broadcast::channel(RETAINED_STATE_CHANGES).0,
+            })),
+        }
+    }
+
+    fn start(&self, last_applied_lsn: Lsn) {
+        // a restarted partition processor continues after the records applied by the previous
+        // one, which published their changes already
+        self.inner
+            .lock()
+            .retained_after
+            .get_or_insert(last_applied_lsn);
+    }
+
+    /// Publishes the state changes caused by the record at `lsn`, once it has been applied.
+    pub(crate) fn publish(
+        &self,
+        lsn: Lsn,
+        state_changes: impl IntoIterator<Item = (ServiceId, StateMutation)>,
+    ) {
+        let mut state = self.inner.lock();
+        for (service_id, mutation) in state_changes {
+            let state_change = StateChange {
+                partition_id: self.partition_id,
+                lsn,
+                service_id,
+                mutation,
+            };
+
+            if state.retained.len() == RETAINED_STATE_CHANGES {
+                if let Some(evicted) = state.retained.pop_front() {
+                    state.retained_after = Some(evicted.lsn);
+                }
+            }
+            // fails only if there are no subscribers
+            let _ = state.tx.send(state_change.clone());
+            state.retained.push_back(state_change);
+        }
+    }
+
+    fn subscribe(
+        &self,
+        resume_after: Option<Lsn>,
+    ) -> Result<BoxStream<'static, Result<StateChange, StateChangeFeedError>>, StateChangeFeedError>
+    {
+        let partition_id = self.partition_id;
+        let state = self.inner.lock();
+        let retained_after = state
+            .retained_after
+            .ok_or(StateChangeFeedError::PartitionNotRunning(partition_id))?;
+
+        let retained: Vec<_> = match resume_after {
+            Some(lsn) if lsn < retained_after => {
+                return Err(StateChangeFeedError::Trimmed(partition_id, retained_after))
+            }
+            Some(lsn) => state
+                .retained
+                .iter()
+                .filter(|state_change| state_change.lsn > lsn)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        // subscribing while holding the lock makes sure that no change is missed or repeated
+        let rx = state.tx.subscribe();
+        drop(state);
+
+        let published = stream::unfold(Some(rx), move |rx| async move {
+            let mut rx = rx?;
+            match rx.recv().await {
+                Ok(state_change) => Some((Ok(state_change), Some(rx))),
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    Some((Err(StateChangeFeedError::Lagged(partition_id)), None))
+                }
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        });
+
+        Ok(stream::iter(retained.into_iter().map(Ok))
+            .chain(published)
+            .boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::FutureExt;
+    use test_log::test;
+
+    fn set_state(value: &'static str) -> (ServiceId, StateMutation) {
+        (
+            ServiceId::new("Counter", "my-key"),
+            StateMutation::Set {
+                key: Bytes::from_static(b"count"),
+                value: Bytes::from_static(value.as_bytes()),
+            },
+        )
+    }
+
+    fn lsns(state_changes: Vec<Result<StateChange, StateChangeFeedError>>) -> Vec<u64> {
+        state_changes
+            .into_iter()
+            .map(|state_change| state_change.unwrap().lsn.as_u64())
+            .collect()
+    }
+
+    #[test(tokio::test)]
+    async fn resumes_after_the_retained_changes() {
+        let publisher = StateChangePublisher::new(PartitionId::from(0));
+        publisher.start(Lsn::from(1));
+
+        publisher.publish(Lsn::from(2), [set_state("1")]);
+        publisher.publish(Lsn::from(3), []);
+        publisher.publish(Lsn::from(4), [set_state("2"), set_state("3")]);
+
+        let mut state_changes = publisher.subscribe(Some(Lsn::from(2))).unwrap();
+        publisher.publish(Lsn::from(5), [set_state("4")]);
+
+        let received: Vec<_> = state_changes.by_ref().take(3).collect().await;
+        assert_eq!(lsns(received), vec![4, 4, 5]);
+        assert!(state_changes.next().now_or_never().is_none());
+
+        // without a position, only the changes published afterwards are streamed
+        let mut state_changes = publisher.subscribe(None).unwrap();
+        assert!(state_changes.next().now_or_never().is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn fails_to_resume_from_evicted_changes() {
+        let publisher = StateChangePublisher::new(PartitionId::from(0));
+        assert!(matches!(
+            publisher.subscribe(None),
+            Err(StateChangeFeedError::PartitionNotRunning(_))
+        ));
+
+        publisher.start(Lsn::from(10));
+        assert!(matches!(
+            publisher.subscribe(Some(Lsn::from(9))),
+            Err(StateChangeFeedError::Trimmed(_, lsn)) if lsn == Lsn::from(10)
+        ));
+
+        for lsn in 11..=(11 + RETAINED_STATE_CHANGES as u64) {
+            publisher.publish(Lsn::from(lsn), [set_state("1")]);
+        }
+        assert!(matches!(
+            publisher.subscribe(Some(Lsn::from(10))),
+            Err(StateChangeFeedError::Trimmed(_, lsn)) if lsn == Lsn::from(11)
+        ));
+
+        let state_changes: Vec<_> = publisher
+            .subscribe(Some(Lsn::from(11)))
+            .unwrap()
+            .take(RETAINED_STATE_CHANGES)
+            .collect()
+            .await;
+        assert_eq!(
+            lsns(state_changes),
+            (12..=(11 + RETAINED_STATE_CHANGES as u64)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn lagging_subscribers_fail() {
+        let publisher = StateChangePublisher::new(PartitionId::from(0));
+        publisher.start(Lsn::INVALID);
+
+        let state_changes = publisher.subscribe(None).unwrap();
+        for lsn in 1..=(RETAINED_STATE_CHANGES as u64 + 1) {
+            publisher.publish(Lsn::from(lsn), [set_state("1")]);
+        }
+
+        let state_changes: Vec<_> = state_changes.collect().await;
+        assert_eq!(state_changes.len(), 1);
+        assert!(matches!(
+            state_changes[0],
+            Err(StateChangeFeedError::Lagged(_))
+        ));
+    }
+}
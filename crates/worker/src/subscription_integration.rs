@@ -11,10 +11,12 @@
 use std::ops::Deref;
 use std::sync::Arc;
 
-use restate_ingress_kafka::SubscriptionCommandSender;
+use restate_ingress_kafka::{ConsumerLag, SubscriptionCommandSender};
+use restate_storage_query_datafusion::ConsumerLagReader;
 use restate_types::config::IngressOptions;
 use restate_types::identifiers::SubscriptionId;
 use restate_types::schema::subscriptions::{Subscription, SubscriptionValidator};
+use restate_types::subscription::SubscriptionPartitionLag;
 
 use crate::{SubscriptionController, WorkerHandleError};
 
@@ -70,3 +72,13 @@ impl SubscriptionController for SubscriptionControllerHandle {
             .map_err(|_| WorkerHandleError::Unreachable)
     }
 }
+
+/// Exposes the lag of the Kafka consumers to the storage query engine.
+#[derive(Debug, Clone)]
+pub(crate) struct ConsumerLagReaderImpl(pub(crate) ConsumerLag);
+
+impl ConsumerLagReader for ConsumerLagReaderImpl {
+    fn consumer_lag(&self) -> Vec<SubscriptionPartitionLag> {
+        self.0.snapshot()
+    }
+}
@@ -22,7 +22,7 @@ use restate_bifrost::Bifrost;
 use restate_core::network::protobuf::node_svc::node_svc_client::NodeSvcClient;
 use restate_core::TaskKind;
 use restate_core::TestCoreEnv;
-use restate_service_client::{AssumeRoleCacheMode, ServiceClient};
+use restate_service_client::{AssumeRoleCacheMode, RequestIdentityKeys, ServiceClient};
 use restate_service_protocol::discovery::ServiceDiscovery;
 use restate_storage_query_datafusion::table_docs;
 use restate_types::config::Configuration;
@@ -120,9 +120,13 @@ async fn generate_rest_api_doc() -> anyhow::Result<()> {
         Mock,
         ServiceDiscovery::new(
             RetryPolicy::default(),
-            ServiceClient::from_options(&config.common.service_client, AssumeRoleCacheMode::None)
-                .unwrap(),
+            ServiceClient::from_options(
+                &config.common.service_client,
+                AssumeRoleCacheMode::None,
+                RequestIdentityKeys::default(),
+            ),
         ),
+        RequestIdentityKeys::default(),
     );
 
     node_env.tc.spawn(
@@ -162,8 +166,9 @@ fn render_table_docs(mut write: impl Write) -> io::Result<()> {
         render_table_doc(table_doc, &mut write)?;
     }
 
-    // sys_invocation is a view which was not registered at table_docs::TABLE_DOCS
+    // sys_invocation and sys_awakeable are views which were not registered at table_docs::TABLE_DOCS
     render_table_doc(&table_docs::sys_invocation_table_docs(), &mut write)?;
+    render_table_doc(&table_docs::sys_awakeable_table_docs(), &mut write)?;
 
     Ok(())
 }